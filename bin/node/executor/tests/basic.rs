@@ -602,7 +602,7 @@ fn deploying_wasm_contract_should_work() {
 			CheckedExtrinsic {
 				signed: Some((charlie(), signed_extra(0, 0))),
 				function: Call::Contracts(
-					pallet_contracts::Call::put_code::<Runtime>(transfer_code)
+					pallet_contracts::Call::upload_code::<Runtime>(transfer_code, None)
 				),
 			},
 			CheckedExtrinsic {
@@ -611,6 +611,7 @@ fn deploying_wasm_contract_should_work() {
 					pallet_contracts::Call::instantiate::<Runtime>(
 						1 * DOLLARS + subsistence,
 						500_000_000,
+						None,
 						transfer_ch,
 						Vec::new(),
 						Vec::new(),
@@ -624,6 +625,7 @@ fn deploying_wasm_contract_should_work() {
 						sp_runtime::MultiAddress::Id(addr.clone()),
 						10,
 						500_000_000,
+						None,
 						vec![0x00, 0x01, 0x02, 0x03]
 					)
 				),
@@ -645,7 +647,6 @@ fn deploying_wasm_contract_should_work() {
 		// Verify that the contract constructor worked well and code of TRANSFER contract is actually deployed.
 		assert_eq!(
 			&pallet_contracts::ContractInfoOf::<Runtime>::get(addr)
-				.and_then(|c| c.get_alive())
 				.unwrap()
 				.code_hash,
 			&transfer_ch
@@ -412,6 +412,7 @@ impl BenchDb {
 			state_cache_child_ratio: Some((0, 100)),
 			pruning: PruningMode::ArchiveAll,
 			source: database_type.into_settings(dir.into()),
+			event_topics_index: false,
 		};
 		let task_executor = TaskExecutor::new();
 
@@ -22,7 +22,7 @@ use std::sync::Arc;
 use std::collections::{HashMap, HashSet};
 use sp_core::ChangesTrieConfigurationRange;
 use sp_core::offchain::{OffchainStorage,storage::OffchainOverlayedChanges};
-use sp_runtime::{generic::BlockId, Justification, Storage};
+use sp_runtime::{generic::BlockId, Justification, Justifications, Storage};
 use sp_runtime::traits::{Block as BlockT, NumberFor, HashFor};
 use sp_state_machine::{
 	ChangesTrieState, ChangesTrieStorage as StateChangesTrieStorage, ChangesTrieTransaction,
@@ -148,7 +148,7 @@ pub trait BlockImportOperation<Block: BlockT> {
 		&mut self,
 		header: Block::Header,
 		body: Option<Vec<Block::Extrinsic>>,
-		justification: Option<Justification>,
+		justifications: Option<Justifications>,
 		state: NewBlockState,
 	) -> sp_blockchain::Result<()>;
 
@@ -191,7 +191,8 @@ pub trait BlockImportOperation<Block: BlockT> {
 	fn insert_aux<I>(&mut self, ops: I) -> sp_blockchain::Result<()>
 		where I: IntoIterator<Item=(Vec<u8>, Option<Vec<u8>>)>;
 
-	/// Mark a block as finalized.
+	/// Mark a block as finalized. The justification, if any, is stored along with the ones
+	/// of the other consensus engines for the block.
 	fn mark_finalized(
 		&mut self,
 		id: BlockId<Block>,
@@ -216,7 +217,8 @@ pub trait Finalizer<Block: BlockT, B: Backend<Block>> {
 	/// Mark all blocks up to given as finalized in operation.
 	///
 	/// If `justification` is provided it is stored with the given finalized
-	/// block (any other finalized blocks are left unjustified).
+	/// block, along with the justifications of the other consensus engines
+	/// (any other finalized blocks are left unjustified).
 	///
 	/// If the block being finalized is on a different fork from the current
 	/// best block the finalized block is set as best, this might be slightly
@@ -432,6 +434,16 @@ pub trait Backend<Block: BlockT>: AuxStore + Send + Sync {
 		justification: Option<Justification>,
 	) -> sp_blockchain::Result<()>;
 
+	/// Append justification to the block with the given Id.
+	///
+	/// This should only be called for blocks that are already finalized. Fails if the block
+	/// already has a justification from the same consensus engine.
+	fn append_justification(
+		&self,
+		block: BlockId<Block>,
+		justification: Justification,
+	) -> sp_blockchain::Result<()>;
+
 	/// Returns reference to blockchain backend.
 	fn blockchain(&self) -> &Self::Blockchain;
 
@@ -444,6 +456,11 @@ pub trait Backend<Block: BlockT>: AuxStore + Send + Sync {
 	/// Returns a handle to offchain storage.
 	fn offchain_storage(&self) -> Option<Self::OffchainStorage>;
 
+	/// Returns the index of event topics if the backend maintains one.
+	fn event_topics_index(&self) -> Option<Arc<dyn EventTopicsIndex<Block>>> {
+		None
+	}
+
 	/// Returns true if state for given block is available.
 	fn have_state_at(&self, hash: &Block::Hash, _number: NumberFor<Block>) -> bool {
 		self.state_at(BlockId::Hash(hash.clone())).is_ok()
@@ -505,6 +522,39 @@ pub trait PrunableStateChangesTrieStorage<Block: BlockT>:
 	fn oldest_pruned_digest_range_end(&self) -> NumberFor<Block>;
 }
 
+/// An event of a finalized block that was deposited with an indexed topic.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct IndexedEvent<Hash, Number> {
+	/// Number of the block that deposited the event.
+	pub block_number: Number,
+	/// Hash of the block that deposited the event.
+	pub block_hash: Hash,
+	/// Index of the event within the events of its block.
+	pub event_index: u32,
+}
+
+/// Index from event topics to the events that were deposited with them.
+///
+/// Only events of finalized blocks are indexed. Events of blocks whose state was pruned are
+/// removed from the index as well.
+pub trait EventTopicsIndex<Block: BlockT>: Send + Sync {
+	/// Returns the events deposited with `topic` in chain order.
+	///
+	/// Only events at or after the position `from` (block number and event index) and not
+	/// later than block `to` are returned. At most `limit` events are returned. When more
+	/// events match, the position of the next one is returned alongside.
+	fn events_by_topic(
+		&self,
+		topic: &Block::Hash,
+		from: (NumberFor<Block>, u32),
+		to: NumberFor<Block>,
+		limit: usize,
+	) -> sp_blockchain::Result<(
+		Vec<IndexedEvent<Block::Hash, NumberFor<Block>>>,
+		Option<(NumberFor<Block>, u32)>,
+	)>;
+}
+
 /// Mark for all Backend implementations, that are making use of state data, stored locally.
 pub trait LocalBackend<Block: BlockT>: Backend<Block> {}
 
@@ -21,7 +21,7 @@ use sp_core::storage::StorageKey;
 use sp_runtime::{
 	traits::{Block as BlockT, NumberFor},
 	generic::{BlockId, SignedBlock},
-	Justification,
+	Justifications,
 };
 use sp_consensus::BlockOrigin;
 
@@ -88,13 +88,34 @@ pub trait BlockBackend<Block: BlockT> {
 	/// Get block status.
 	fn block_status(&self, id: &BlockId<Block>) -> sp_blockchain::Result<sp_consensus::BlockStatus>;
 
-	/// Get block justification set by id.
-	fn justification(&self, id: &BlockId<Block>) -> sp_blockchain::Result<Option<Justification>>;
+	/// Get block justifications for the block with the given id.
+	fn justifications(&self, id: &BlockId<Block>) -> sp_blockchain::Result<Option<Justifications>>;
 
 	/// Get block hash by number.
 	fn block_hash(&self, number: NumberFor<Block>) -> sp_blockchain::Result<Option<Block::Hash>>;
 }
 
+/// A block of the history missing below a block that was imported with its state.
+#[derive(Debug, Clone)]
+pub struct HistoryBlock<Block: BlockT> {
+	/// Block header.
+	pub header: Block::Header,
+	/// Block body, if downloaded.
+	pub body: Option<Vec<<Block as BlockT>::Extrinsic>>,
+	/// Block justifications, if any.
+	pub justifications: Option<Justifications>,
+}
+
+/// Interface for storing the block history missing below a block imported with its state.
+pub trait BlockHistoryImport<Block: BlockT> {
+	/// Store the given blocks without executing them.
+	///
+	/// The blocks must be in descending order, the first one being the parent of the lowest
+	/// block above the gap reported by [`Info::block_gap`], and each following block the parent
+	/// of the previous one.
+	fn import_block_history(&self, blocks: Vec<HistoryBlock<Block>>) -> sp_blockchain::Result<()>;
+}
+
 /// Provide a list of potential uncle headers for a given block.
 pub trait ProvideUncles<Block: BlockT> {
 	/// Gets the uncles of the block with `target_hash` going back `max_generation` ancestors.
@@ -22,6 +22,7 @@ use sp_runtime::{
 	traits::{Block as BlockT},
 };
 use crate::{StorageProof, ChangesProof};
+use sp_state_machine::ProvenStateRange;
 use sp_storage::{ChildInfo, StorageKey, PrefixedStorageKey};
 
 /// Interface for providing block proving utilities.
@@ -70,4 +71,24 @@ pub trait ProofProvider<Block: BlockT> {
 		storage_key: Option<&PrefixedStorageKey>,
 		key: &StorageKey,
 	) -> sp_blockchain::Result<ChangesProof<Block::Header>>;
+
+	/// Given a `BlockId` iterate over all storage values starting at `start_at`, including
+	/// the values of the default child tries, and return a proof of them.
+	/// The proof is limited to around `size_limit` bytes and contains at least one entry.
+	/// Returns the proof and the number of entries it contains.
+	fn read_proof_collection(
+		&self,
+		id: &BlockId<Block>,
+		start_at: &[Vec<u8>],
+		size_limit: usize,
+	) -> sp_blockchain::Result<(StorageProof, u32)>;
+
+	/// Verify a proof returned by `read_proof_collection` against the given state root and
+	/// return the storage entries it proves, along with the position to continue from.
+	fn verify_range_proof(
+		&self,
+		root: Block::Hash,
+		proof: StorageProof,
+		start_at: &[Vec<u8>],
+	) -> sp_blockchain::Result<ProvenStateRange>;
 }
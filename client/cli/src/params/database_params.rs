@@ -34,6 +34,14 @@ pub struct DatabaseParams {
 	/// Limit the memory the database cache can use.
 	#[structopt(long = "db-cache", value_name = "MiB")]
 	pub database_cache_size: Option<usize>,
+
+	/// Maintain an index from event topics to the events of finalized blocks.
+	///
+	/// The index can be queried with the `events_byTopic` RPC. Events are indexed starting
+	/// with the blocks finalized after the index was enabled and are pruned together with
+	/// the state of their block. Use an archive node to keep the whole history indexed.
+	#[structopt(long = "event-topics-index")]
+	pub event_topics_index: bool,
 }
 
 impl DatabaseParams {
@@ -46,4 +54,9 @@ impl DatabaseParams {
 	pub fn database_cache_size(&self) -> Option<usize> {
 		self.database_cache_size
 	}
+
+	/// Is the event topics index enabled?
+	pub fn event_topics_index(&self) -> bool {
+		self.event_topics_index
+	}
 }
@@ -16,13 +16,17 @@
 // You should have received a copy of the GNU General Public License
 // along with this program. If not, see <https://www.gnu.org/licenses/>.
 
-use crate::params::node_key_params::NodeKeyParams;
+use crate::{arg_enums::SyncMode, params::node_key_params::NodeKeyParams};
 use sc_network::{
-	config::{NetworkConfiguration, NodeKeyConfig, NonReservedPeerMode, TransportConfig},
+	config::{
+		BandwidthLimits, IpNetwork, NetworkConfiguration, NodeKeyConfig, NonReservedPeerMode,
+		TransportConfig,
+	},
 	multiaddr::Protocol,
+	PeerId,
 };
 use sc_service::{ChainSpec, ChainType, config::{Multiaddr, MultiaddrWithPeerId}};
-use std::path::PathBuf;
+use std::{num::NonZeroU64, path::PathBuf};
 use structopt::StructOpt;
 
 /// Parameters used to create the network configuration.
@@ -43,6 +47,22 @@ pub struct NetworkParams {
 	#[structopt(long = "reserved-only")]
 	pub reserved_only: bool,
 
+	/// Specify a list of peer IDs that are permanently banned.
+	///
+	/// The node never connects to these peers and refuses their connections. Bans can also be
+	/// managed at runtime with the `system_banPeer` and `system_unbanPeer` RPC methods. The
+	/// permanent bans made with `system_banPeer` are saved and restored on restart.
+	#[structopt(long = "banned-peers", value_name = "PEER_ID")]
+	pub banned_peers: Vec<PeerId>,
+
+	/// Specify a list of IP ranges, in CIDR notation, that are permanently banned.
+	///
+	/// Connections from and to addresses in these ranges are refused, for example
+	/// `--banned-ip-ranges 192.0.2.0/24 2001:db8::/32`. Bans can also be managed at runtime with
+	/// the `system_banIpRange` and `system_unbanIpRange` RPC methods.
+	#[structopt(long = "banned-ip-ranges", value_name = "IP_RANGE")]
+	pub banned_ip_ranges: Vec<IpNetwork>,
+
 	/// The public address that other nodes will use to connect to it.
 	/// This can be used if there's a proxy in front of this node.
 	#[structopt(long, value_name = "PUBLIC_ADDR")]
@@ -106,6 +126,52 @@ pub struct NetworkParams {
 	/// security improvements.
 	#[structopt(long)]
 	pub kademlia_disjoint_query_paths: bool,
+
+	/// Blockchain syncing mode.
+	///
+	/// - `Full`: Download and validate full blockchain history.
+	///
+	/// - `Fast`: Download block headers, then download and import the state of the latest
+	///   finalized block instead of executing all blocks.
+	#[structopt(
+		long,
+		value_name = "SYNC_MODE",
+		possible_values = &SyncMode::variants(),
+		case_insensitive = true,
+		default_value = "Full"
+	)]
+	pub sync: SyncMode,
+
+	/// Maximum number of blocks per second downloaded in the background to fill the block
+	/// history missing after a warp sync.
+	///
+	/// Set to 0 to never download the missing history.
+	#[structopt(long = "block-history-rate", value_name = "COUNT", default_value = "256")]
+	pub block_history_rate: u32,
+
+	/// Maximum download rate from all the peers combined, in KiB/s.
+	///
+	/// Unlimited by default.
+	#[structopt(long = "max-download-rate", value_name = "KIB_PER_SEC")]
+	pub max_download_rate: Option<NonZeroU64>,
+
+	/// Maximum upload rate to all the peers combined, in KiB/s.
+	///
+	/// Unlimited by default.
+	#[structopt(long = "max-upload-rate", value_name = "KIB_PER_SEC")]
+	pub max_upload_rate: Option<NonZeroU64>,
+
+	/// Maximum download rate from each individual peer, in KiB/s.
+	///
+	/// Unlimited by default.
+	#[structopt(long = "max-download-rate-per-peer", value_name = "KIB_PER_SEC")]
+	pub max_download_rate_per_peer: Option<NonZeroU64>,
+
+	/// Maximum upload rate to each individual peer, in KiB/s.
+	///
+	/// Unlimited by default.
+	#[structopt(long = "max-upload-rate-per-peer", value_name = "KIB_PER_SEC")]
+	pub max_upload_rate_per_peer: Option<NonZeroU64>,
 }
 
 impl NetworkParams {
@@ -171,8 +237,23 @@ impl NetworkParams {
 				wasm_external_transport: None,
 			},
 			max_parallel_downloads: self.max_parallel_downloads,
+			sync_mode: self.sync.into(),
+			block_history_rate: self.block_history_rate,
 			allow_non_globals_in_dht,
 			kademlia_disjoint_query_paths: self.kademlia_disjoint_query_paths,
+			bandwidth_limits: BandwidthLimits {
+				max_download_rate: self.max_download_rate.and_then(kib_to_bytes),
+				max_upload_rate: self.max_upload_rate.and_then(kib_to_bytes),
+				max_download_rate_per_peer: self.max_download_rate_per_peer.and_then(kib_to_bytes),
+				max_upload_rate_per_peer: self.max_upload_rate_per_peer.and_then(kib_to_bytes),
+			},
+			banned_peers: self.banned_peers.clone(),
+			banned_ip_ranges: self.banned_ip_ranges.clone(),
 		}
 	}
 }
+
+/// Converts a rate in KiB/s into bytes/s.
+fn kib_to_bytes(rate: NonZeroU64) -> Option<NonZeroU64> {
+	NonZeroU64::new(rate.get().saturating_mul(1024))
+}
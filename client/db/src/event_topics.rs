@@ -0,0 +1,581 @@
+// This file is part of Substrate.
+
+// Copyright (C) 2020 Parity Technologies (UK) Ltd.
+// SPDX-License-Identifier: GPL-3.0-or-later WITH Classpath-exception-2.0
+
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with this program. If not, see <https://www.gnu.org/licenses/>.
+
+//! Secondary index from event topics to the events of finalized blocks.
+//!
+//! `frame_system` records the topics of all events deposited in a block under
+//! `System::EventTopics` and clears them when the next block is initialized. The topics
+//! of a block can therefore be extracted from the storage changes of its import. They are
+//! kept per height until one of the blocks at that height is finalized. The topics of the
+//! finalized block are then appended to a paged list per topic while the other blocks at
+//! that height are dropped. Entries of blocks that fall out of the pruning window are
+//! removed again.
+
+use std::collections::{HashMap, HashSet};
+use std::marker::PhantomData;
+use std::sync::Arc;
+
+use codec::{Decode, Encode};
+use sc_client_api::backend::{EventTopicsIndex, IndexedEvent};
+use sp_blockchain::{Error as ClientError, Result as ClientResult};
+use sp_core::hashing::twox_128;
+use sp_database::{Database, Transaction};
+use sp_runtime::traits::{Block as BlockT, NumberFor, SaturatedConversion};
+use sp_state_machine::StorageCollection;
+use crate::{DbHash, utils};
+
+/// Maximum number of events stored in a single page of a topic.
+const PAGE_SIZE: usize = 256;
+
+/// Events of not yet finalized blocks: `number => Vec<(hash, Vec<(topic, event index)>)>`.
+const PENDING_PREFIX: u8 = 0;
+/// Topics of an indexed block: `number => Vec<topic>`. Used for pruning and reverting.
+const BLOCK_PREFIX: u8 = 1;
+/// The range of pages that hold the events of a topic: `topic => TopicPages`.
+const TOPIC_PREFIX: u8 = 2;
+/// A page of events of a topic: `topic ++ page => Vec<(number, event index)>`.
+const PAGE_PREFIX: u8 = 3;
+
+/// Length of the hashed prefix of `System::EventTopics` followed by the `blake2_128`
+/// part of the `blake2_128_concat` hasher.
+const TOPIC_OFFSET: usize = 48;
+
+type Topic = Vec<u8>;
+type PendingEvents<Hash> = Vec<(Hash, Vec<(Topic, u32)>)>;
+type Page<Number> = Vec<(Number, u32)>;
+
+/// How the events of a block are removed from the index.
+#[derive(Clone, Copy)]
+enum Removal {
+	/// The block fell out of the pruning window. Its events are at the start of the pages.
+	Prune,
+	/// The block was reverted. Its events are at the end of the pages.
+	Revert,
+}
+
+/// The pages `first .. next` of a topic are in use.
+#[derive(Encode, Decode, Default)]
+struct TopicPages {
+	first: u32,
+	next: u32,
+}
+
+/// Changes to the index that are written as part of a database transaction.
+///
+/// The index is read and modified several times while a single operation is committed.
+/// The overlay makes these modifications visible to reads before the transaction is
+/// committed.
+#[derive(Default)]
+pub struct EventTopicsTransaction {
+	changes: HashMap<Vec<u8>, Option<Vec<u8>>>,
+}
+
+/// Database backed index of event topics.
+pub struct EventTopicsDb<Block: BlockT> {
+	db: Arc<dyn Database<DbHash>>,
+	column: u32,
+	key_lookup_column: u32,
+	/// Number of finalized blocks that stay indexed. `None` keeps all of them.
+	keep_blocks: Option<u32>,
+	_phantom: PhantomData<Block>,
+}
+
+impl<Block: BlockT> EventTopicsDb<Block> {
+	/// Create a new index that is stored in `column`.
+	pub fn new(
+		db: Arc<dyn Database<DbHash>>,
+		column: u32,
+		key_lookup_column: u32,
+		keep_blocks: Option<u32>,
+	) -> Self {
+		EventTopicsDb {
+			db,
+			column,
+			key_lookup_column,
+			keep_blocks,
+			_phantom: Default::default(),
+		}
+	}
+
+	/// Remember the topics of the events that were deposited by an imported block.
+	pub fn note_imported(
+		&self,
+		tx: &mut EventTopicsTransaction,
+		number: NumberFor<Block>,
+		hash: Block::Hash,
+		storage_changes: &StorageCollection,
+	) -> ClientResult<()> {
+		let events = block_events::<Block>(number, storage_changes);
+		if events.is_empty() {
+			return Ok(());
+		}
+
+		let key = number_key(PENDING_PREFIX, number)?;
+		let mut pending: PendingEvents<Block::Hash> = self.read(tx, &key)?.unwrap_or_default();
+		pending.retain(|(h, _)| *h != hash);
+		pending.push((hash, events));
+		write(tx, key, &pending);
+		Ok(())
+	}
+
+	/// Add the events of a finalized block to the index and drop the events of the other
+	/// blocks at the same height.
+	///
+	/// Blocks must be finalized in order.
+	pub fn note_finalized(
+		&self,
+		tx: &mut EventTopicsTransaction,
+		number: NumberFor<Block>,
+		hash: Block::Hash,
+	) -> ClientResult<()> {
+		let key = number_key(PENDING_PREFIX, number)?;
+		let pending: PendingEvents<Block::Hash> = self.read(tx, &key)?.unwrap_or_default();
+		remove(tx, key);
+
+		if let Some((_, events)) = pending.into_iter().find(|(h, _)| *h == hash) {
+			let mut topics = Vec::new();
+			for (topic, event_index) in events {
+				self.append(tx, &topic, number, event_index)?;
+				if !topics.contains(&topic) {
+					topics.push(topic);
+				}
+			}
+			write(tx, number_key(BLOCK_PREFIX, number)?, &topics);
+		}
+
+		let number = number.saturated_into::<u64>();
+		match self.keep_blocks {
+			Some(keep_blocks) if number > keep_blocks as u64 =>
+				self.prune(tx, (number - keep_blocks as u64).saturated_into()),
+			_ => Ok(()),
+		}
+	}
+
+	/// Remove all traces of a reverted block.
+	pub fn revert(
+		&self,
+		tx: &mut EventTopicsTransaction,
+		number: NumberFor<Block>,
+		hash: Block::Hash,
+		finalized: bool,
+	) -> ClientResult<()> {
+		let key = number_key(PENDING_PREFIX, number)?;
+		let mut pending: PendingEvents<Block::Hash> = self.read(tx, &key)?.unwrap_or_default();
+		pending.retain(|(h, _)| *h != hash);
+		if pending.is_empty() {
+			remove(tx, key);
+		} else {
+			write(tx, key, &pending);
+		}
+
+		if finalized {
+			self.remove_block(tx, number, Removal::Revert)?;
+		}
+		Ok(())
+	}
+
+	/// Write the changes collected in `tx` into the database `transaction`.
+	pub fn apply(&self, tx: EventTopicsTransaction, transaction: &mut Transaction<DbHash>) {
+		for (key, value) in tx.changes {
+			match value {
+				Some(value) => transaction.set_from_vec(self.column, &key, value),
+				None => transaction.remove(self.column, &key),
+			}
+		}
+	}
+
+	/// Remove the events of the block at `number` which fell out of the pruning window.
+	fn prune(&self, tx: &mut EventTopicsTransaction, number: NumberFor<Block>) -> ClientResult<()> {
+		self.remove_block(tx, number, Removal::Prune)
+	}
+
+	/// Append an event to the last page of `topic`.
+	fn append(
+		&self,
+		tx: &mut EventTopicsTransaction,
+		topic: &[u8],
+		number: NumberFor<Block>,
+		event_index: u32,
+	) -> ClientResult<()> {
+		let topic_key = topic_key(topic);
+		let mut pages: TopicPages = self.read(tx, &topic_key)?.unwrap_or_default();
+
+		let mut page: Page<NumberFor<Block>> = if pages.first < pages.next {
+			self.read(tx, &page_key(topic, pages.next - 1))?.unwrap_or_default()
+		} else {
+			Vec::new()
+		};
+		if page.is_empty() || page.len() >= PAGE_SIZE {
+			page = Vec::new();
+			pages.next += 1;
+		}
+		page.push((number, event_index));
+
+		write(tx, page_key(topic, pages.next - 1), &page);
+		write(tx, topic_key, &pages);
+		Ok(())
+	}
+
+	/// Remove the events of the block at `number` from the pages of all of its topics.
+	///
+	/// Emptied pages are dropped.
+	fn remove_block(
+		&self,
+		tx: &mut EventTopicsTransaction,
+		number: NumberFor<Block>,
+		removal: Removal,
+	) -> ClientResult<()> {
+		let block_key = number_key(BLOCK_PREFIX, number)?;
+		let topics: Vec<Topic> = self.read(tx, &block_key)?.unwrap_or_default();
+		remove(tx, block_key);
+
+		for topic in topics {
+			let topic_key = topic_key(&topic);
+			let mut pages: TopicPages = match self.read(tx, &topic_key)? {
+				Some(pages) => pages,
+				None => continue,
+			};
+			while pages.first < pages.next {
+				let page_index = match removal {
+					Removal::Prune => pages.first,
+					Removal::Revert => pages.next - 1,
+				};
+				let key = page_key(&topic, page_index);
+				let mut page: Page<NumberFor<Block>> = self.read(tx, &key)?.unwrap_or_default();
+				let len = page.len();
+				match removal {
+					Removal::Prune => page.retain(|(n, _)| *n > number),
+					Removal::Revert => page.retain(|(n, _)| *n < number),
+				}
+				if !page.is_empty() {
+					if page.len() < len {
+						write(tx, key, &page);
+					}
+					break;
+				}
+				remove(tx, key);
+				match removal {
+					Removal::Prune => pages.first += 1,
+					Removal::Revert => pages.next -= 1,
+				}
+			}
+			if pages.first < pages.next {
+				write(tx, topic_key, &pages);
+			} else {
+				remove(tx, topic_key);
+			}
+		}
+		Ok(())
+	}
+
+	/// Read and decode a value, taking the changes of `tx` into account.
+	fn read<T: Decode>(&self, tx: &EventTopicsTransaction, key: &[u8]) -> ClientResult<Option<T>> {
+		let value = match tx.changes.get(key) {
+			Some(value) => value.clone(),
+			None => self.db.get(self.column, key),
+		};
+		value.map(|value| Decode::decode(&mut &value[..])).transpose().map_err(|err|
+			ClientError::Backend(format!("Error decoding event topics index: {}", err))
+		)
+	}
+
+	/// Returns the hash of the canonical block at `number`.
+	fn canonical_hash(&self, number: NumberFor<Block>) -> ClientResult<Option<Block::Hash>> {
+		let number_key = utils::number_index_key(number)?;
+		let lookup_key = match self.db.get(self.key_lookup_column, &number_key) {
+			Some(lookup_key) => lookup_key,
+			None => return Ok(None),
+		};
+		// The lookup key is the block number followed by the block hash.
+		Decode::decode(&mut &lookup_key[4..]).map(Some).map_err(|err|
+			ClientError::Backend(format!("Error decoding block hash: {}", err))
+		)
+	}
+}
+
+impl<Block: BlockT> EventTopicsIndex<Block> for EventTopicsDb<Block> {
+	fn events_by_topic(
+		&self,
+		topic: &Block::Hash,
+		from: (NumberFor<Block>, u32),
+		to: NumberFor<Block>,
+		limit: usize,
+	) -> ClientResult<(
+		Vec<IndexedEvent<Block::Hash, NumberFor<Block>>>,
+		Option<(NumberFor<Block>, u32)>,
+	)> {
+		let tx = EventTopicsTransaction::default();
+		let topic = topic.as_ref();
+		let pages: TopicPages = match self.read(&tx, &topic_key(topic))? {
+			Some(pages) => pages,
+			None => return Ok((Vec::new(), None)),
+		};
+		let read_page = |index| -> ClientResult<Page<NumberFor<Block>>> {
+			Ok(self.read(&tx, &page_key(topic, index))?.unwrap_or_default())
+		};
+
+		// Find the first page whose last event is not before `from`.
+		let (mut low, mut high) = (pages.first, pages.next);
+		while low < high {
+			let mid = low + (high - low) / 2;
+			match read_page(mid)?.last() {
+				Some(last) if *last < from => low = mid + 1,
+				_ => high = mid,
+			}
+		}
+
+		let mut events = Vec::new();
+		let mut block_hash = None;
+		for index in low .. pages.next {
+			for (number, event_index) in read_page(index)? {
+				if (number, event_index) < from {
+					continue;
+				}
+				if number > to {
+					return Ok((events, None));
+				}
+				if events.len() >= limit {
+					return Ok((events, Some((number, event_index))));
+				}
+				let hash = match block_hash {
+					Some((n, hash)) if n == number => hash,
+					_ => {
+						let hash = self.canonical_hash(number)?.ok_or_else(||
+							ClientError::UnknownBlock(format!("Indexed block {} not found", number))
+						)?;
+						block_hash = Some((number, hash));
+						hash
+					},
+				};
+				events.push(IndexedEvent { block_number: number, block_hash: hash, event_index });
+			}
+		}
+		Ok((events, None))
+	}
+}
+
+/// Extract the topics of the events deposited in the block at `number` from its storage
+/// changes.
+fn block_events<Block: BlockT>(
+	number: NumberFor<Block>,
+	storage_changes: &StorageCollection,
+) -> Vec<(Topic, u32)> {
+	let prefix = [twox_128(b"System"), twox_128(b"EventTopics")].concat();
+	let mut events = Vec::new();
+	let mut seen = HashSet::new();
+	for (key, value) in storage_changes {
+		let value = match value {
+			Some(value) if key.starts_with(&prefix) && key.len() > TOPIC_OFFSET => value,
+			_ => continue,
+		};
+		let topic = key[TOPIC_OFFSET..].to_vec();
+		let entries = match Page::<NumberFor<Block>>::decode(&mut &value[..]) {
+			Ok(entries) => entries,
+			Err(_) => continue,
+		};
+		for (n, event_index) in entries {
+			if n == number && seen.insert((topic.clone(), event_index)) {
+				events.push((topic.clone(), event_index));
+			}
+		}
+	}
+	events.sort_by_key(|(_, event_index)| *event_index);
+	events
+}
+
+fn number_key<N: std::convert::TryInto<u32>>(prefix: u8, number: N) -> ClientResult<Vec<u8>> {
+	let mut key = vec![prefix];
+	key.extend_from_slice(&utils::number_index_key(number)?);
+	Ok(key)
+}
+
+fn topic_key(topic: &[u8]) -> Vec<u8> {
+	let mut key = vec![TOPIC_PREFIX];
+	key.extend_from_slice(topic);
+	key
+}
+
+fn page_key(topic: &[u8], page: u32) -> Vec<u8> {
+	let mut key = vec![PAGE_PREFIX];
+	key.extend_from_slice(topic);
+	key.extend_from_slice(&page.to_be_bytes());
+	key
+}
+
+fn write<T: Encode>(tx: &mut EventTopicsTransaction, key: Vec<u8>, value: &T) {
+	tx.changes.insert(key, Some(value.encode()));
+}
+
+fn remove(tx: &mut EventTopicsTransaction, key: Vec<u8>) {
+	tx.changes.insert(key, None);
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use sc_client_api::backend::{Backend as _, BlockImportOperation as _, NewBlockState};
+	use sp_core::{H256, hashing::blake2_128};
+	use sp_runtime::generic::BlockId;
+	use sp_runtime::testing::Header;
+	use sp_runtime::traits::{BlakeTwo256, Hash, Header as _};
+	use crate::{Backend, DatabaseSettings, DatabaseSettingsSrc, PruningMode};
+	use crate::tests::Block;
+	use sp_blockchain::HeaderBackend as _;
+
+	fn topic(n: u8) -> H256 {
+		H256::repeat_byte(n)
+	}
+
+	fn new_backend(pruning: PruningMode) -> Backend<Block> {
+		let db = kvdb_memorydb::create(crate::utils::NUM_COLUMNS);
+		Backend::new(DatabaseSettings {
+			state_cache_size: 16777216,
+			state_cache_child_ratio: Some((50, 100)),
+			pruning,
+			source: DatabaseSettingsSrc::Custom(sp_database::as_database(db)),
+			event_topics_index: true,
+		}, 0).unwrap()
+	}
+
+	/// Import a block that deposited events with the given topics and event indices.
+	fn import(
+		backend: &Backend<Block>,
+		number: u64,
+		parent_hash: H256,
+		fork: u8,
+		events: &[(H256, u32)],
+		state: NewBlockState,
+	) -> H256 {
+		let mut changes: HashMap<H256, Vec<(u64, u32)>> = HashMap::new();
+		for (topic, event_index) in events {
+			changes.entry(*topic).or_default().push((number, *event_index));
+		}
+		let storage = changes.into_iter().map(|(topic, entries)| {
+			let key = [
+				&twox_128(b"System")[..],
+				&twox_128(b"EventTopics")[..],
+				&blake2_128(topic.as_ref())[..],
+				topic.as_ref(),
+			].concat();
+			(key, Some(entries.encode()))
+		}).collect();
+
+		let header = Header {
+			number,
+			parent_hash,
+			state_root: BlakeTwo256::trie_root(Vec::new()),
+			digest: Default::default(),
+			extrinsics_root: H256::repeat_byte(fork),
+		};
+		let hash = header.hash();
+		let parent = if number == 0 {
+			BlockId::Hash(Default::default())
+		} else {
+			BlockId::Hash(parent_hash)
+		};
+		let mut op = backend.begin_operation().unwrap();
+		backend.begin_state_operation(&mut op, parent).unwrap();
+		op.set_block_data(header, Some(Vec::new()), None, state).unwrap();
+		op.update_storage(storage, Vec::new()).unwrap();
+		backend.commit_operation(op).unwrap();
+		hash
+	}
+
+	fn query(
+		backend: &Backend<Block>,
+		topic: H256,
+		from: (u64, u32),
+		to: u64,
+		limit: usize,
+	) -> (Vec<(u64, u32)>, Option<(u64, u32)>) {
+		let index = backend.event_topics_index().unwrap();
+		let (events, next) = index.events_by_topic(&topic, from, to, limit).unwrap();
+		for event in &events {
+			assert_eq!(
+				Some(event.block_hash),
+				backend.blockchain().hash(event.block_number).unwrap(),
+			);
+		}
+		(events.into_iter().map(|e| (e.block_number, e.event_index)).collect(), next)
+	}
+
+	#[test]
+	fn events_are_indexed_when_finalized() {
+		let backend = new_backend(PruningMode::ArchiveAll);
+		let genesis = import(&backend, 0, Default::default(), 0, &[], NewBlockState::Final);
+		let events = [(topic(1), 0), (topic(2), 1), (topic(1), 2)];
+		let block1 = import(&backend, 1, genesis, 0, &events, NewBlockState::Best);
+		import(&backend, 1, genesis, 1, &[(topic(1), 5)], NewBlockState::Normal);
+
+		assert_eq!(query(&backend, topic(1), (0, 0), 10, 10), (vec![], None));
+
+		backend.finalize_block(BlockId::Hash(block1), None).unwrap();
+		assert_eq!(query(&backend, topic(1), (0, 0), 10, 10), (vec![(1, 0), (1, 2)], None));
+		assert_eq!(query(&backend, topic(2), (0, 0), 10, 10), (vec![(1, 1)], None));
+		assert_eq!(query(&backend, topic(3), (0, 0), 10, 10), (vec![], None));
+	}
+
+	#[test]
+	fn queries_are_paged() {
+		let backend = new_backend(PruningMode::ArchiveAll);
+		let mut hash = import(&backend, 0, Default::default(), 0, &[], NewBlockState::Final);
+		for number in 1 ..= 300 {
+			hash = import(
+				&backend, number, hash, 0, &[(topic(1), 0), (topic(1), 1)], NewBlockState::Final,
+			);
+		}
+
+		let (events, next) = query(&backend, topic(1), (0, 0), 300, 100);
+		assert_eq!(events.len(), 100);
+		assert_eq!(events[99], (50, 1));
+		assert_eq!(next, Some((51, 0)));
+
+		let (events, next) = query(&backend, topic(1), (150, 1), 152, 100);
+		assert_eq!(events, vec![(150, 1), (151, 0), (151, 1), (152, 0), (152, 1)]);
+		assert_eq!(next, None);
+
+		let (events, next) = query(&backend, topic(1), (299, 0), 1000, 100);
+		assert_eq!(events, vec![(299, 0), (299, 1), (300, 0), (300, 1)]);
+		assert_eq!(next, None);
+	}
+
+	#[test]
+	fn events_are_pruned_with_state() {
+		let backend = new_backend(PruningMode::keep_blocks(5));
+		let mut hash = import(&backend, 0, Default::default(), 0, &[], NewBlockState::Final);
+		for number in 1 ..= 10 {
+			hash = import(&backend, number, hash, 0, &[(topic(1), 0)], NewBlockState::Final);
+		}
+
+		let (events, next) = query(&backend, topic(1), (0, 0), 10, 100);
+		assert_eq!(events, vec![(6, 0), (7, 0), (8, 0), (9, 0), (10, 0)]);
+		assert_eq!(next, None);
+	}
+
+	#[test]
+	fn reverted_events_are_removed() {
+		let backend = new_backend(PruningMode::ArchiveAll);
+		let mut hash = import(&backend, 0, Default::default(), 0, &[], NewBlockState::Final);
+		for number in 1 ..= 3 {
+			hash = import(&backend, number, hash, 0, &[(topic(1), 0)], NewBlockState::Final);
+		}
+
+		assert_eq!(backend.revert(1, true).unwrap().0, 1);
+		assert_eq!(query(&backend, topic(1), (0, 0), 10, 10), (vec![(1, 0), (2, 0)], None));
+	}
+}
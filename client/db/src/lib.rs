@@ -37,6 +37,7 @@ pub mod bench;
 mod children;
 mod cache;
 mod changes_tries_storage;
+mod event_topics;
 mod storage_cache;
 #[cfg(any(feature = "with-kvdb-rocksdb", test))]
 mod upgrade;
@@ -55,7 +56,7 @@ use log::{trace, debug, warn};
 
 use sc_client_api::{
 	UsageInfo, MemoryInfo, IoInfo, MemorySize,
-	backend::{NewBlockState, PrunableStateChangesTrieStorage, ProvideChtRoots},
+	backend::{NewBlockState, PrunableStateChangesTrieStorage, ProvideChtRoots, EventTopicsIndex},
 	leaves::{LeafSet, FinalizationDisplaced}, cht,
 };
 use sp_blockchain::{
@@ -70,7 +71,7 @@ use sp_core::ChangesTrieConfiguration;
 use sp_core::offchain::storage::{OffchainOverlayedChange, OffchainOverlayedChanges};
 use sp_core::storage::{well_known_keys, ChildInfo};
 use sp_arithmetic::traits::Saturating;
-use sp_runtime::{generic::{DigestItem, BlockId}, Justification, Storage};
+use sp_runtime::{generic::{DigestItem, BlockId}, Justification, Justifications, Storage};
 use sp_runtime::traits::{
 	Block as BlockT, Header as HeaderT, NumberFor, Zero, One, SaturatedConversion, HashFor,
 };
@@ -81,6 +82,7 @@ use sp_state_machine::{
 };
 use crate::utils::{DatabaseType, Meta, meta_keys, read_db, read_meta};
 use crate::changes_tries_storage::{DbChangesTrieStorage, DbChangesTrieStorageTransaction};
+use crate::event_topics::{EventTopicsDb, EventTopicsTransaction};
 use sc_state_db::StateDb;
 use sp_blockchain::{CachedHeaderMetadata, HeaderMetadata, HeaderMetadataCache};
 use crate::storage_cache::{CachingState, SyncingCachingState, SharedCache, new_shared_cache};
@@ -268,6 +270,10 @@ pub struct DatabaseSettings {
 	pub pruning: PruningMode,
 	/// Where to find the database.
 	pub source: DatabaseSettingsSrc,
+	/// Maintain an index from event topics to the events of finalized blocks.
+	///
+	/// Indexed events are pruned together with the state of their block.
+	pub event_topics_index: bool,
 }
 
 /// Where to find the database..
@@ -328,17 +334,19 @@ pub(crate) mod columns {
 	pub const KEY_LOOKUP: u32 = 3;
 	pub const HEADER: u32 = 4;
 	pub const BODY: u32 = 5;
-	pub const JUSTIFICATION: u32 = 6;
+	pub const JUSTIFICATIONS: u32 = 6;
 	pub const CHANGES_TRIE: u32 = 7;
 	pub const AUX: u32 = 8;
 	/// Offchain workers local storage
 	pub const OFFCHAIN: u32 = 9;
 	pub const CACHE: u32 = 10;
+	/// Index from event topics to events of finalized blocks.
+	pub const EVENT_TOPICS: u32 = 11;
 }
 
 struct PendingBlock<Block: BlockT> {
 	header: Block::Header,
-	justification: Option<Justification>,
+	justifications: Option<Justifications>,
 	body: Option<Vec<Block::Extrinsic>>,
 	leaf_state: NewBlockState,
 }
@@ -447,13 +455,18 @@ impl<Block: BlockT> sc_client_api::blockchain::HeaderBackend<Block> for Blockcha
 			finalized_hash: meta.finalized_hash,
 			finalized_number: meta.finalized_number,
 			number_leaves: self.leaves.read().count(),
+			block_gap: meta.block_gap,
 		}
 	}
 
 	fn status(&self, id: BlockId<Block>) -> ClientResult<sc_client_api::blockchain::BlockStatus> {
 		let exists = match id {
 			BlockId::Hash(_) => self.header(id)?.is_some(),
-			BlockId::Number(n) => n <= self.meta.read().best_number,
+			BlockId::Number(n) => {
+				let meta = self.meta.read();
+				n <= meta.best_number &&
+					!meta.block_gap.map_or(false, |(start, end)| start <= n && n <= end)
+			},
 		};
 		match exists {
 			true => Ok(sc_client_api::blockchain::BlockStatus::InChain),
@@ -486,12 +499,12 @@ impl<Block: BlockT> sc_client_api::blockchain::Backend<Block> for BlockchainDb<B
 		}
 	}
 
-	fn justification(&self, id: BlockId<Block>) -> ClientResult<Option<Justification>> {
-		match read_db(&*self.db, columns::KEY_LOOKUP, columns::JUSTIFICATION, id)? {
-			Some(justification) => match Decode::decode(&mut &justification[..]) {
-				Ok(justification) => Ok(Some(justification)),
+	fn justifications(&self, id: BlockId<Block>) -> ClientResult<Option<Justifications>> {
+		match read_db(&*self.db, columns::KEY_LOOKUP, columns::JUSTIFICATIONS, id)? {
+			Some(justifications) => match Decode::decode(&mut &justifications[..]) {
+				Ok(justifications) => Ok(Some(justifications)),
 				Err(err) => return Err(sp_blockchain::Error::Backend(
-					format!("Error decoding justification: {}", err)
+					format!("Error decoding justifications: {}", err)
 				)),
 			}
 			None => Ok(None),
@@ -613,6 +626,7 @@ pub struct BlockImportOperation<Block: BlockT> {
 	finalized_blocks: Vec<(BlockId<Block>, Option<Justification>)>,
 	set_head: Option<BlockId<Block>>,
 	commit_state: bool,
+	reset_storage: bool,
 }
 
 impl<Block: BlockT> BlockImportOperation<Block> {
@@ -651,7 +665,7 @@ impl<Block: BlockT> sc_client_api::backend::BlockImportOperation<Block> for Bloc
 		&mut self,
 		header: Block::Header,
 		body: Option<Vec<Block::Extrinsic>>,
-		justification: Option<Justification>,
+		justifications: Option<Justifications>,
 		leaf_state: NewBlockState,
 	) -> ClientResult<()> {
 		assert!(self.pending_block.is_none(), "Only one block per operation is allowed");
@@ -661,7 +675,7 @@ impl<Block: BlockT> sc_client_api::backend::BlockImportOperation<Block> for Bloc
 		self.pending_block = Some(PendingBlock {
 			header,
 			body,
-			justification,
+			justifications,
 			leaf_state,
 		});
 		Ok(())
@@ -706,6 +720,7 @@ impl<Block: BlockT> sc_client_api::backend::BlockImportOperation<Block> for Bloc
 		self.db_updates = transaction;
 		self.changes_trie_config_update = Some(changes_trie_config);
 		self.commit_state = true;
+		self.reset_storage = true;
 		Ok(root)
 	}
 
@@ -850,6 +865,7 @@ pub struct Backend<Block: BlockT> {
 	storage: Arc<StorageDb<Block>>,
 	offchain_storage: offchain::LocalStorage,
 	changes_tries_storage: DbChangesTrieStorage<Block>,
+	event_topics: Option<Arc<EventTopicsDb<Block>>>,
 	blockchain: BlockchainDb<Block>,
 	canonicalization_delay: u64,
 	shared_cache: SharedCache<Block>,
@@ -878,6 +894,7 @@ impl<Block: BlockT> Backend<Block> {
 			state_cache_child_ratio: Some((50, 100)),
 			pruning: PruningMode::keep_blocks(keep_blocks),
 			source: DatabaseSettingsSrc::Custom(db),
+			event_topics_index: false,
 		};
 
 		Self::new(db_setting, canonicalization_delay).expect("failed to create test-db")
@@ -903,6 +920,20 @@ impl<Block: BlockT> Backend<Block> {
 			prefix_keys: !config.source.supports_ref_counting(),
 		};
 		let offchain_storage = offchain::LocalStorage::new(db.clone());
+		let event_topics = if config.event_topics_index {
+			let keep_blocks = match &config.pruning {
+				PruningMode::Constrained(constraints) => Some(constraints.max_blocks.unwrap_or(0)),
+				PruningMode::ArchiveAll | PruningMode::ArchiveCanonical => None,
+			};
+			Some(Arc::new(EventTopicsDb::new(
+				db.clone(),
+				columns::EVENT_TOPICS,
+				columns::KEY_LOOKUP,
+				keep_blocks,
+			)))
+		} else {
+			None
+		};
 		let changes_tries_storage = DbChangesTrieStorage::new(
 			db,
 			blockchain.header_metadata_cache.clone(),
@@ -923,6 +954,7 @@ impl<Block: BlockT> Backend<Block> {
 			storage: Arc::new(storage_db),
 			offchain_storage,
 			changes_tries_storage,
+			event_topics,
 			blockchain,
 			canonicalization_delay,
 			shared_cache: new_shared_cache(
@@ -954,8 +986,11 @@ impl<Block: BlockT> Backend<Block> {
 
 		let meta = self.blockchain.meta.read();
 
-		// cannot find tree route with empty DB.
-		if meta.best_hash != Default::default() {
+		// cannot find tree route with empty DB, or for a block imported with its state whose
+		// ancestors are unknown.
+		let route_to_known = self.blockchain.status(BlockId::hash(route_to))?
+			== sc_client_api::blockchain::BlockStatus::InChain;
+		if meta.best_hash != Default::default() && route_to_known {
 			let tree_route = sp_blockchain::tree_route(
 				&self.blockchain,
 				meta.best_hash,
@@ -1044,10 +1079,21 @@ impl<Block: BlockT> Backend<Block> {
 		)?;
 
 		if let Some(justification) = justification {
+			let stored_justifications = sc_client_api::blockchain::Backend::justifications(
+				&self.blockchain,
+				BlockId::Hash(*hash),
+			)?;
+			let justifications = match stored_justifications {
+				Some(mut justifications) => {
+					justifications.append(justification);
+					justifications
+				},
+				None => Justifications::from(justification),
+			};
 			transaction.set_from_vec(
-				columns::JUSTIFICATION,
+				columns::JUSTIFICATIONS,
 				&utils::number_and_hash_to_lookup_key(number, hash)?,
-				justification.encode(),
+				justifications.encode(),
 			);
 		}
 		Ok((*hash, number, false, true))
@@ -1078,6 +1124,11 @@ impl<Block: BlockT> Backend<Block> {
 						implies existence of blocks with all numbers before it; qed")
 			};
 
+			if self.storage.state_db.is_pruned(&hash, new_canonical) {
+				// the block was imported without state.
+				return Ok(())
+			}
+
 			trace!(target: "db", "Canonicalize block #{} ({:?})", new_canonical, hash);
 			let commit = self.storage.state_db.canonicalize_block(&hash)
 				.map_err(|e: sc_state_db::Error<io::Error>| sp_blockchain::Error::from_state_db(e))?;
@@ -1087,23 +1138,116 @@ impl<Block: BlockT> Backend<Block> {
 		Ok(())
 	}
 
+	// Stores a block of the history missing below a block that was imported with its state.
+	// The block is not executed, and must be the parent of the lowest block above the gap.
+	fn commit_history_block(
+		&self,
+		mut operation: BlockImportOperation<Block>,
+		pending_block: PendingBlock<Block>,
+		(gap_start, gap_end): (NumberFor<Block>, NumberFor<Block>),
+	) -> ClientResult<()> {
+		let header = pending_block.header;
+		let hash = header.hash();
+		let number = *header.number();
+		if number != gap_end {
+			return Err(sp_blockchain::Error::Backend(format!(
+				"History block #{} imported out of order, expected #{}",
+				number,
+				gap_end,
+			)));
+		}
+		let child = self.blockchain.expect_header(BlockId::Number(number + One::one()))?;
+		if *child.parent_hash() != hash {
+			return Err(sp_blockchain::Error::Backend(format!(
+				"History block {:?} is not the parent of #{}",
+				hash,
+				child.number(),
+			)));
+		}
+		if number == gap_start {
+			let parent_hash = self.blockchain.expect_block_hash_from_id(
+				&BlockId::Number(number - One::one())
+			)?;
+			if *header.parent_hash() != parent_hash {
+				return Err(sp_blockchain::Error::Backend(format!(
+					"History block {:?} is not a child of #{}",
+					hash,
+					number - One::one(),
+				)));
+			}
+		}
+
+		let mut transaction = Transaction::new();
+		operation.apply_aux(&mut transaction);
+
+		let lookup_key = utils::number_and_hash_to_lookup_key(number, hash)?;
+		utils::insert_hash_to_key_mapping(&mut transaction, columns::KEY_LOOKUP, number, hash)?;
+		utils::insert_number_to_key_mapping(&mut transaction, columns::KEY_LOOKUP, number, hash)?;
+		transaction.set_from_vec(columns::HEADER, &lookup_key, header.encode());
+		if let Some(body) = pending_block.body {
+			transaction.set_from_vec(columns::BODY, &lookup_key, body.encode());
+		}
+		if let Some(justifications) = pending_block.justifications {
+			transaction.set_from_vec(columns::JUSTIFICATIONS, &lookup_key, justifications.encode());
+		}
+
+		let block_gap = if number == gap_start {
+			transaction.remove(columns::META, meta_keys::BLOCK_GAP);
+			None
+		} else {
+			let block_gap = (gap_start, number - One::one());
+			transaction.set_from_vec(columns::META, meta_keys::BLOCK_GAP, block_gap.encode());
+			Some(block_gap)
+		};
+
+		self.storage.db.commit(transaction)?;
+
+		debug!(target: "db", "DB Commit history block {:?} ({})", hash, number);
+		let header_metadata = CachedHeaderMetadata::from(&header);
+		self.blockchain.insert_header_metadata(header_metadata.hash, header_metadata);
+		cache_header(&mut self.blockchain.header_cache.lock(), hash, Some(header));
+		self.blockchain.meta.write().block_gap = block_gap;
+
+		Ok(())
+	}
+
 	fn try_commit_operation(
 		&self,
 		mut operation: BlockImportOperation<Block>,
 	) -> ClientResult<()> {
+		let block_gap = self.blockchain.meta.read().block_gap;
+		if let Some((gap_start, gap_end)) = block_gap {
+			let history_block = operation.pending_block.as_ref().map_or(false, |pending_block| {
+				let number = *pending_block.header.number();
+				gap_start <= number && number <= gap_end
+			});
+			if history_block && !operation.commit_state {
+				let pending_block = operation.pending_block.take()
+					.expect("history blocks are only found in pending blocks; qed");
+				return self.commit_history_block(operation, pending_block, (gap_start, gap_end));
+			}
+		}
+
 		let mut transaction = Transaction::new();
 		let mut finalization_displaced_leaves = None;
+		let mut new_block_gap = None;
 
 		operation.apply_aux(&mut transaction);
 		operation.apply_offchain(&mut transaction);
 
 		let mut meta_updates = Vec::with_capacity(operation.finalized_blocks.len());
-		let mut last_finalized_hash = self.blockchain.meta.read().finalized_hash;
+		let (best_num, mut last_finalized_hash, last_finalized_num) = {
+			let meta = self.blockchain.meta.read();
+			(meta.best_number, meta.finalized_hash, meta.finalized_number)
+		};
 
 		let mut changes_trie_cache_ops = None;
+		let mut event_topics_ops = EventTopicsTransaction::default();
+		let mut finalized_numbers = Vec::with_capacity(operation.finalized_blocks.len());
 		for (block, justification) in operation.finalized_blocks {
 			let block_hash = self.blockchain.expect_block_hash_from_id(&block)?;
 			let block_header = self.blockchain.expect_header(BlockId::Hash(block_hash))?;
+			finalized_numbers.push((*block_header.number(), block_hash));
 
 			meta_updates.push(self.finalize_block_with_transaction(
 				&mut transaction,
@@ -1125,6 +1269,19 @@ impl<Block: BlockT> Backend<Block> {
 			// blocks are keyed by number + hash.
 			let lookup_key = utils::number_and_hash_to_lookup_key(number, hash)?;
 
+			// an existing header is re-imported to add its body or state.
+			let existing_header = number <= best_num &&
+				self.blockchain.header(BlockId::hash(hash))?.is_some();
+			// a block imported with its state may come without its ancestors.
+			let detached = operation.reset_storage && !number.is_zero() &&
+				self.blockchain.header(BlockId::hash(parent_hash))?.is_none();
+			if detached && number > last_finalized_num + One::one() {
+				// the history between the last finalized block and this one is missing.
+				let block_gap = (last_finalized_num + One::one(), number - One::one());
+				transaction.set_from_vec(columns::META, meta_keys::BLOCK_GAP, block_gap.encode());
+				new_block_gap = Some(block_gap);
+			}
+
 			let (enacted, retracted) = if pending_block.leaf_state.is_best() {
 				self.set_head_with_transaction(&mut transaction, parent_hash, (number, hash))?
 			} else {
@@ -1142,8 +1299,8 @@ impl<Block: BlockT> Backend<Block> {
 			if let Some(body) = &pending_block.body {
 				transaction.set_from_vec(columns::BODY, &lookup_key, body.encode());
 			}
-			if let Some(justification) = pending_block.justification {
-				transaction.set_from_vec(columns::JUSTIFICATION, &lookup_key, justification.encode());
+			if let Some(justifications) = pending_block.justifications {
+				transaction.set_from_vec(columns::JUSTIFICATIONS, &lookup_key, justifications.encode());
 			}
 
 			if number.is_zero() {
@@ -1156,6 +1313,15 @@ impl<Block: BlockT> Backend<Block> {
 				}
 			}
 
+			if let Some(event_topics) = &self.event_topics {
+				event_topics.note_imported(
+					&mut event_topics_ops,
+					number,
+					hash,
+					&operation.storage_updates,
+				)?;
+			}
+
 			let finalized = if operation.commit_state {
 				let mut changeset: sc_state_db::ChangeSet<Vec<u8>> = sc_state_db::ChangeSet::default();
 				let mut ops: u64 = 0;
@@ -1205,14 +1371,31 @@ impl<Block: BlockT> Backend<Block> {
 				}
 				self.state_usage.tally_writes(ops, bytes);
 				let number_u64 = number.saturated_into::<u64>();
-				let commit = self.storage.state_db.insert_block(
-					&hash,
-					number_u64,
-					&pending_block.header.parent_hash(),
-					changeset,
-				).map_err(|e: sc_state_db::Error<io::Error>| sp_blockchain::Error::from_state_db(e))?;
+				let commit = if operation.reset_storage && !number.is_zero() {
+					// the state was imported as a whole, the ancestors may have no state at all.
+					self.storage.state_db.insert_block_with_state(
+						&hash,
+						number_u64,
+						&pending_block.header.parent_hash(),
+						changeset,
+					)
+				} else {
+					self.storage.state_db.insert_block(
+						&hash,
+						number_u64,
+						&pending_block.header.parent_hash(),
+						changeset,
+					)
+				}.map_err(|e: sc_state_db::Error<io::Error>| sp_blockchain::Error::from_state_db(e))?;
 				apply_state_commit(&mut transaction, commit);
 
+				if existing_header && number <= last_finalized_num {
+					// the block is already finalized, so its state is canonical.
+					let commit = self.storage.state_db.canonicalize_block(&hash)
+						.map_err(|e: sc_state_db::Error<io::Error>| sp_blockchain::Error::from_state_db(e))?;
+					apply_state_commit(&mut transaction, commit);
+				}
+
 				// Check if need to finalize. Genesis is always finalized instantly.
 				let finalized = number_u64 == 0 || pending_block.leaf_state.is_final();
 				finalized
@@ -1242,8 +1425,11 @@ impl<Block: BlockT> Backend<Block> {
 			let cache = operation.old_state.into_cache_changes();
 
 			if finalized {
+				finalized_numbers.push((number, hash));
 				// TODO: ensure best chain contains this block.
-				self.ensure_sequential_finalization(header, Some(last_finalized_hash))?;
+				if !detached {
+					self.ensure_sequential_finalization(header, Some(last_finalized_hash))?;
+				}
 				self.note_finalized(
 					&mut transaction,
 					true,
@@ -1259,29 +1445,31 @@ impl<Block: BlockT> Backend<Block> {
 
 			debug!(target: "db", "DB Commit {:?} ({}), best = {}", hash, number, is_best);
 
-			let displaced_leaf = {
+			let displaced_leaf = if existing_header {
+				None
+			} else {
 				let mut leaves = self.blockchain.leaves.write();
 				let displaced_leaf = leaves.import(hash, number, parent_hash);
 				leaves.prepare_transaction(&mut transaction, columns::META, meta_keys::LEAF_PREFIX);
 
+				let mut children = children::read_children(
+					&*self.storage.db,
+					columns::META,
+					meta_keys::CHILDREN_PREFIX,
+					parent_hash,
+				)?;
+				children.push(hash);
+				children::write_children(
+					&mut transaction,
+					columns::META,
+					meta_keys::CHILDREN_PREFIX,
+					parent_hash,
+					children,
+				);
+
 				displaced_leaf
 			};
 
-			let mut children = children::read_children(
-				&*self.storage.db,
-				columns::META,
-				meta_keys::CHILDREN_PREFIX,
-				parent_hash,
-			)?;
-			children.push(hash);
-			children::write_children(
-				&mut transaction,
-				columns::META,
-				meta_keys::CHILDREN_PREFIX,
-				parent_hash,
-				children,
-			);
-
 			meta_updates.push((hash, number, pending_block.leaf_state.is_best(), finalized));
 
 			Some((pending_block.header, number, hash, enacted, retracted, displaced_leaf, is_best, cache))
@@ -1308,6 +1496,13 @@ impl<Block: BlockT> Backend<Block> {
 			None
 		};
 
+		if let Some(event_topics) = &self.event_topics {
+			for (number, hash) in finalized_numbers {
+				event_topics.note_finalized(&mut event_topics_ops, number, hash)?;
+			}
+			event_topics.apply(event_topics_ops, &mut transaction);
+		}
+
 		self.storage.db.commit(transaction)?;
 
 		// Apply all in-memory state shanges.
@@ -1352,6 +1547,9 @@ impl<Block: BlockT> Backend<Block> {
 		for (hash, number, is_best, is_finalized) in meta_updates {
 			self.blockchain.update_meta(hash, number, is_best, is_finalized);
 		}
+		if new_block_gap.is_some() {
+			self.blockchain.meta.write().block_gap = new_block_gap;
+		}
 
 		Ok(())
 	}
@@ -1374,9 +1572,12 @@ impl<Block: BlockT> Backend<Block> {
 			let lookup_key = utils::number_and_hash_to_lookup_key(f_num, f_hash.clone())?;
 			transaction.set_from_vec(columns::META, meta_keys::FINALIZED_BLOCK, lookup_key);
 
-			let commit = self.storage.state_db.canonicalize_block(&f_hash)
-				.map_err(|e: sc_state_db::Error<io::Error>| sp_blockchain::Error::from_state_db(e))?;
-			apply_state_commit(transaction, commit);
+			// blocks imported without state are not known to the state database.
+			if !self.storage.state_db.is_pruned(&f_hash, f_num.saturated_into::<u64>()) {
+				let commit = self.storage.state_db.canonicalize_block(&f_hash)
+					.map_err(|e: sc_state_db::Error<io::Error>| sp_blockchain::Error::from_state_db(e))?;
+				apply_state_commit(transaction, commit);
+			}
 
 			if !f_num.is_zero() {
 				let new_changes_trie_cache_ops = self.changes_tries_storage.finalize(
@@ -1464,6 +1665,7 @@ impl<Block: BlockT> sc_client_api::backend::Backend<Block> for Backend<Block> {
 			finalized_blocks: Vec::new(),
 			set_head: None,
 			commit_state: false,
+			reset_storage: false,
 		})
 	}
 
@@ -1518,12 +1720,60 @@ impl<Block: BlockT> sc_client_api::backend::Backend<Block> for Backend<Block> {
 			&mut changes_trie_cache_ops,
 			&mut displaced,
 		)?;
+		if let Some(event_topics) = &self.event_topics {
+			let mut event_topics_ops = EventTopicsTransaction::default();
+			event_topics.note_finalized(&mut event_topics_ops, number, hash)?;
+			event_topics.apply(event_topics_ops, &mut transaction);
+		}
 		self.storage.db.commit(transaction)?;
 		self.blockchain.update_meta(hash, number, is_best, is_finalized);
 		self.changes_tries_storage.post_commit(changes_trie_cache_ops);
 		Ok(())
 	}
 
+	fn append_justification(
+		&self,
+		block: BlockId<Block>,
+		justification: Justification,
+	) -> ClientResult<()> {
+		let mut transaction: Transaction<DbHash> = Transaction::new();
+		let hash = self.blockchain.expect_block_hash_from_id(&block)?;
+		let header = self.blockchain.expect_header(block)?;
+		let number = *header.number();
+
+		// only finalized blocks have justifications.
+		let info = self.blockchain.info();
+		if number > info.finalized_number ||
+			sc_client_api::blockchain::HeaderBackend::hash(&self.blockchain, number)? != Some(hash)
+		{
+			return Err(ClientError::NotInFinalizedChain);
+		}
+
+		let stored_justifications =
+			sc_client_api::blockchain::Backend::justifications(&self.blockchain, block)?;
+		let justifications = match stored_justifications {
+			Some(mut stored_justifications) => {
+				if !stored_justifications.append(justification) {
+					return Err(ClientError::BadJustification(
+						"Duplicate consensus engine ID".into()
+					));
+				}
+				stored_justifications
+			},
+			None => Justifications::from(justification),
+		};
+
+		transaction.set_from_vec(
+			columns::JUSTIFICATIONS,
+			&utils::number_and_hash_to_lookup_key(number, hash)?,
+			justifications.encode(),
+		);
+
+		self.storage.db.commit(transaction)?;
+
+		Ok(())
+	}
+
 	fn changes_trie_storage(&self) -> Option<&dyn PrunableStateChangesTrieStorage<Block>> {
 		Some(&self.changes_tries_storage)
 	}
@@ -1532,6 +1782,10 @@ impl<Block: BlockT> sc_client_api::backend::Backend<Block> for Backend<Block> {
 		Some(self.offchain_storage.clone())
 	}
 
+	fn event_topics_index(&self) -> Option<Arc<dyn EventTopicsIndex<Block>>> {
+		self.event_topics.clone().map(|index| index as Arc<_>)
+	}
+
 	fn usage_info(&self) -> Option<UsageInfo> {
 		let (io_stats, state_stats) = self.io_stats.take_or_else(||
 			(
@@ -1634,6 +1888,16 @@ impl<Block: BlockT> sc_client_api::backend::Backend<Block> for Backend<Block> {
 							);
 							reverted_finalized.insert(removed_hash);
 						}
+						if let Some(event_topics) = &self.event_topics {
+							let mut event_topics_ops = EventTopicsTransaction::default();
+							event_topics.revert(
+								&mut event_topics_ops,
+								removed_number,
+								removed_hash,
+								update_finalized,
+							)?;
+							event_topics.apply(event_topics_ops, &mut transaction);
+						}
 						transaction.set_from_vec(columns::META, meta_keys::BEST_BLOCK, key);
 						transaction.remove(columns::KEY_LOOKUP, removed.hash().as_ref());
 						children::remove_children(&mut transaction, columns::META, meta_keys::CHILDREN_PREFIX, best_hash);
@@ -1776,10 +2040,13 @@ pub(crate) mod tests {
 	use sc_client_api::blockchain::Backend as BLBTrait;
 	use sp_runtime::testing::{Header, Block as RawBlock, ExtrinsicWrapper};
 	use sp_runtime::traits::{Hash, BlakeTwo256};
-	use sp_runtime::generic::DigestItem;
+	use sp_runtime::{ConsensusEngineId, generic::DigestItem};
 	use sp_state_machine::{TrieMut, TrieDBMut};
 	use sp_blockchain::{lowest_common_ancestor, tree_route};
 
+	const CONS0_ENGINE_ID: ConsensusEngineId = *b"CON0";
+	const CONS1_ENGINE_ID: ConsensusEngineId = *b"CON1";
+
 	pub(crate) type Block = RawBlock<ExtrinsicWrapper<u64>>;
 
 	pub fn prepare_changes(changes: Vec<(Vec<u8>, Vec<u8>)>) -> (H256, MemoryDB<BlakeTwo256>) {
@@ -1884,6 +2151,7 @@ pub(crate) mod tests {
 			state_cache_child_ratio: Some((50, 100)),
 			pruning: PruningMode::keep_blocks(1),
 			source: DatabaseSettingsSrc::Custom(backing),
+			event_topics_index: false,
 		}, 0).unwrap();
 		assert_eq!(backend.blockchain().info().best_number, 9);
 		for i in 0..10 {
@@ -2354,12 +2622,44 @@ pub(crate) mod tests {
 		let block0 = insert_header(&backend, 0, Default::default(), None, Default::default());
 		let _ = insert_header(&backend, 1, block0, None, Default::default());
 
-		let justification = Some(vec![1, 2, 3]);
+		let justification = Some((CONS0_ENGINE_ID, vec![1, 2, 3]));
 		backend.finalize_block(BlockId::Number(1), justification.clone()).unwrap();
 
 		assert_eq!(
-			backend.blockchain().justification(BlockId::Number(1)).unwrap(),
-			justification,
+			backend.blockchain().justifications(BlockId::Number(1)).unwrap(),
+			justification.map(Justifications::from),
+		);
+	}
+
+	#[test]
+	fn test_append_justification_to_finalized_block() {
+		use sc_client_api::blockchain::{Backend as BlockChainBackend};
+
+		let backend = Backend::<Block>::new_test(10, 10);
+
+		let block0 = insert_header(&backend, 0, Default::default(), None, Default::default());
+		let _ = insert_header(&backend, 1, block0, None, Default::default());
+
+		let just0 = (CONS0_ENGINE_ID, vec![1, 2, 3]);
+		backend.finalize_block(BlockId::Number(1), Some(just0.clone())).unwrap();
+
+		let just1 = (CONS1_ENGINE_ID, vec![4, 5]);
+		backend.append_justification(BlockId::Number(1), just1.clone()).unwrap();
+
+		let just2 = (CONS1_ENGINE_ID, vec![6, 7]);
+		assert!(matches!(
+			backend.append_justification(BlockId::Number(1), just2),
+			Err(ClientError::BadJustification(_))
+		));
+
+		let justifications = {
+			let mut just = Justifications::from(just0);
+			just.append(just1);
+			just
+		};
+		assert_eq!(
+			backend.blockchain().justifications(BlockId::Number(1)).unwrap(),
+			Some(justifications),
 		);
 	}
 
@@ -2403,6 +2703,131 @@ pub(crate) mod tests {
 		}
 	}
 
+	#[test]
+	fn import_state_of_finalized_block_without_ancestor_state() {
+		let backend = Backend::<Block>::new_test(10, 10);
+		let genesis = insert_header(&backend, 0, Default::default(), None, Default::default());
+
+		let storage = Storage {
+			top: vec![(b"key".to_vec(), b"value".to_vec())].into_iter().collect(),
+			children_default: Default::default(),
+		};
+		let state_root = BlakeTwo256::trie_root(vec![(b"key".to_vec(), b"value".to_vec())]);
+
+		// import headers without state and finalize them.
+		let mut headers = Vec::new();
+		let mut parent_hash = genesis;
+		for number in 1..4 {
+			let header = Header {
+				number,
+				parent_hash,
+				state_root: if number == 2 { state_root } else { Default::default() },
+				digest: Default::default(),
+				extrinsics_root: Default::default(),
+			};
+			parent_hash = header.hash();
+			let mut op = backend.begin_operation().unwrap();
+			op.set_block_data(header.clone(), None, None, NewBlockState::Best).unwrap();
+			backend.commit_operation(op).unwrap();
+			headers.push(header);
+		}
+		let mut op = backend.begin_operation().unwrap();
+		for header in &headers {
+			op.mark_finalized(BlockId::Hash(header.hash()), None).unwrap();
+		}
+		backend.commit_operation(op).unwrap();
+		assert_eq!(backend.blockchain().info().finalized_number, 3);
+		assert!(!backend.have_state_at(&headers[1].hash(), 2));
+
+		// import the state of block #2.
+		let mut op = backend.begin_operation().unwrap();
+		assert_eq!(op.reset_storage(storage).unwrap(), state_root);
+		op.set_block_data(headers[1].clone(), None, None, NewBlockState::Normal).unwrap();
+		backend.commit_operation(op).unwrap();
+
+		assert!(backend.have_state_at(&headers[1].hash(), 2));
+		assert_eq!(
+			backend.state_at(BlockId::Number(2)).unwrap().storage(b"key").unwrap(),
+			Some(b"value".to_vec()),
+		);
+		assert_eq!(backend.blockchain().leaves().unwrap(), vec![headers[2].hash()]);
+		assert_eq!(backend.blockchain().info().best_number, 3);
+
+		// blocks on top of it can now be imported with state.
+		let mut op = backend.begin_operation().unwrap();
+		backend.begin_state_operation(&mut op, BlockId::Number(2)).unwrap();
+		op.set_block_data(headers[2].clone(), Some(Vec::new()), None, NewBlockState::Normal).unwrap();
+		backend.commit_operation(op).unwrap();
+		assert!(backend.have_state_at(&headers[2].hash(), 3));
+		assert_eq!(backend.blockchain().leaves().unwrap(), vec![headers[2].hash()]);
+
+		let hash = insert_header(&backend, 4, headers[2].hash(), None, Default::default());
+		let mut op = backend.begin_operation().unwrap();
+		op.mark_finalized(BlockId::Hash(hash), None).unwrap();
+		backend.commit_operation(op).unwrap();
+		assert!(backend.have_state_at(&hash, 4));
+	}
+
+	#[test]
+	fn history_blocks_fill_block_gap_below_imported_state() {
+		let backend = Backend::<Block>::new_test(10, 10);
+		let genesis = insert_header(&backend, 0, Default::default(), None, Default::default());
+
+		let mut headers = Vec::new();
+		let mut parent_hash = genesis;
+		for number in 1..5 {
+			let header = Header {
+				number,
+				parent_hash,
+				state_root: Default::default(),
+				digest: Default::default(),
+				extrinsics_root: Default::default(),
+			};
+			parent_hash = header.hash();
+			headers.push(header);
+		}
+
+		// import the state of block #4 without its ancestors.
+		let mut op = backend.begin_operation().unwrap();
+		op.reset_storage(Default::default()).unwrap();
+		op.set_block_data(headers[3].clone(), None, None, NewBlockState::Final).unwrap();
+		backend.commit_operation(op).unwrap();
+		assert_eq!(backend.blockchain().info().block_gap, Some((1, 3)));
+		assert_eq!(
+			backend.blockchain().status(BlockId::Number(2)).unwrap(),
+			sc_client_api::blockchain::BlockStatus::Unknown,
+		);
+
+		let import_history = |header: &Header| {
+			let mut op = backend.begin_operation().unwrap();
+			op.set_block_data(header.clone(), Some(Vec::new()), None, NewBlockState::Normal).unwrap();
+			backend.commit_operation(op)
+		};
+
+		// history blocks are imported in reverse order.
+		import_history(&headers[1]).unwrap_err();
+		import_history(&headers[2]).unwrap();
+		assert_eq!(backend.blockchain().info().block_gap, Some((1, 2)));
+		assert_eq!(backend.blockchain().hash(3).unwrap(), Some(headers[2].hash()));
+
+		// the block must be the parent of the lowest block above the gap.
+		let mut fake = headers[1].clone();
+		fake.state_root = H256::repeat_byte(1);
+		import_history(&fake).unwrap_err();
+
+		import_history(&headers[1]).unwrap();
+		import_history(&headers[0]).unwrap();
+		assert_eq!(backend.blockchain().info().block_gap, None);
+		assert_eq!(
+			backend.blockchain().status(BlockId::Number(2)).unwrap(),
+			sc_client_api::blockchain::BlockStatus::InChain,
+		);
+		assert_eq!(backend.blockchain().hash(1).unwrap(), Some(headers[0].hash()));
+		assert_eq!(backend.blockchain().body(BlockId::Number(1)).unwrap(), Some(Vec::new()));
+		assert_eq!(backend.blockchain().leaves().unwrap(), vec![headers[3].hash()]);
+		assert_eq!(backend.blockchain().info().best_number, 4);
+	}
+
 	#[test]
 	fn header_cht_root_works() {
 		use sc_client_api::ProvideChtRoots;
@@ -155,6 +155,7 @@ impl<Block> BlockchainHeaderBackend<Block> for LightStorage<Block>
 			finalized_hash: meta.finalized_hash,
 			finalized_number: meta.finalized_number,
 			number_leaves: 1,
+			block_gap: None,
 		}
 	}
 
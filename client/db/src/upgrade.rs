@@ -20,23 +20,36 @@ use std::fs;
 use std::io::{Read, Write, ErrorKind};
 use std::path::{Path, PathBuf};
 
+use codec::{Decode, Encode};
 use sp_runtime::traits::Block as BlockT;
-use crate::utils::DatabaseType;
+use crate::{columns, utils::DatabaseType};
+use kvdb_rocksdb::{Database, DatabaseConfig};
 
 /// Version file name.
 const VERSION_FILE_NAME: &'static str = "db_version";
 
 /// Current db version.
-const CURRENT_VERSION: u32 = 1;
+const CURRENT_VERSION: u32 = 3;
+
+/// Number of columns in v1.
+const V1_NUM_COLUMNS: u32 = 11;
+
+/// Number of columns in v2.
+const V2_NUM_COLUMNS: u32 = 12;
 
 /// Upgrade database to current version.
-pub fn upgrade_db<Block: BlockT>(db_path: &Path, _db_type: DatabaseType) -> sp_blockchain::Result<()> {
+pub fn upgrade_db<Block: BlockT>(db_path: &Path, db_type: DatabaseType) -> sp_blockchain::Result<()> {
 	let is_empty = db_path.read_dir().map_or(true, |mut d| d.next().is_none());
 	if !is_empty {
 		let db_version = current_version(db_path)?;
 		match db_version {
 			0 => Err(sp_blockchain::Error::Backend(format!("Unsupported database version: {}", db_version)))?,
-			1 => (),
+			1 => {
+				migrate_1_to_2::<Block>(db_path, db_type)?;
+				migrate_2_to_3::<Block>(db_path, db_type)?
+			},
+			2 => migrate_2_to_3::<Block>(db_path, db_type)?,
+			CURRENT_VERSION => (),
 			_ => Err(sp_blockchain::Error::Backend(format!("Future database version: {}", db_version)))?,
 		}
 	}
@@ -44,6 +57,37 @@ pub fn upgrade_db<Block: BlockT>(db_path: &Path, _db_type: DatabaseType) -> sp_b
 	update_version(db_path)
 }
 
+/// Migration from version1 to version2:
+/// 1) the number of columns has changed from 11 to 12;
+/// 2) the event topics index column is added.
+fn migrate_1_to_2<Block: BlockT>(db_path: &Path, _db_type: DatabaseType) -> sp_blockchain::Result<()> {
+	let db_path = db_path.to_str()
+		.ok_or_else(|| sp_blockchain::Error::Backend("Invalid database path".into()))?;
+	let db_cfg = DatabaseConfig::with_columns(V1_NUM_COLUMNS);
+	let db = Database::open(&db_cfg, db_path).map_err(db_err)?;
+	db.add_column().map_err(db_err)
+}
+
+/// Migration from version2 to version3:
+/// - the justifications of a block are now tagged with the id of the consensus engine that
+///   produced them. Every existing justification is tagged as a GRANDPA one.
+fn migrate_2_to_3<Block: BlockT>(db_path: &Path, _db_type: DatabaseType) -> sp_blockchain::Result<()> {
+	let db_path = db_path.to_str()
+		.ok_or_else(|| sp_blockchain::Error::Backend("Invalid database path".into()))?;
+	let db_cfg = DatabaseConfig::with_columns(V2_NUM_COLUMNS);
+	let db = Database::open(&db_cfg, db_path).map_err(db_err)?;
+
+	let mut transaction = db.transaction();
+	for (key, justification) in db.iter(columns::JUSTIFICATIONS) {
+		// the hardcoded GRANDPA engine id avoids depending on the GRANDPA crate.
+		let justification = Vec::<u8>::decode(&mut &justification[..])
+			.map_err(|_| sp_blockchain::Error::Backend("Invalid justification blob".into()))?;
+		let justifications = sp_runtime::Justifications::from((*b"FRNK", justification));
+		transaction.put_vec(columns::JUSTIFICATIONS, &key, justifications.encode());
+	}
+	db.write(transaction).map_err(db_err)
+}
+
 
 /// Reads current database version from the file at given path.
 /// If the file does not exist returns 0.
@@ -103,6 +147,7 @@ mod tests {
 			state_cache_child_ratio: None,
 			pruning: PruningMode::ArchiveAll,
 			source: DatabaseSettingsSrc::RocksDb { path: db_path.to_owned(), cache_size: 128 },
+			event_topics_index: false,
 		}, DatabaseType::Full).map(|_| ())
 	}
 
@@ -113,6 +158,44 @@ mod tests {
 		assert!(open_database(db_dir.path()).is_err());
 	}
 
+	#[test]
+	fn upgrade_from_1_to_2_works() {
+		let db_dir = tempfile::TempDir::new().unwrap();
+		{
+			let db_path = db_dir.path().to_str().unwrap();
+			let db_cfg = DatabaseConfig::with_columns(V1_NUM_COLUMNS);
+			Database::open(&db_cfg, db_path).unwrap();
+		}
+		create_db(db_dir.path(), Some(1));
+		open_database(db_dir.path()).unwrap();
+		assert_eq!(current_version(db_dir.path()).unwrap(), CURRENT_VERSION);
+	}
+
+	#[test]
+	fn upgrade_from_2_to_3_tags_justifications() {
+		let db_dir = tempfile::TempDir::new().unwrap();
+		let key = b"block".to_vec();
+		{
+			let db_path = db_dir.path().to_str().unwrap();
+			let db_cfg = DatabaseConfig::with_columns(V2_NUM_COLUMNS);
+			let db = Database::open(&db_cfg, db_path).unwrap();
+			let mut transaction = db.transaction();
+			transaction.put_vec(columns::JUSTIFICATIONS, &key, vec![1u8, 2, 3].encode());
+			db.write(transaction).unwrap();
+		}
+		create_db(db_dir.path(), Some(2));
+		open_database(db_dir.path()).unwrap();
+		assert_eq!(current_version(db_dir.path()).unwrap(), CURRENT_VERSION);
+
+		let db_path = db_dir.path().to_str().unwrap();
+		let db = Database::open(&DatabaseConfig::with_columns(V2_NUM_COLUMNS), db_path).unwrap();
+		let stored = db.get(columns::JUSTIFICATIONS, &key).unwrap().unwrap();
+		assert_eq!(
+			sp_runtime::Justifications::decode(&mut &stored[..]).unwrap(),
+			sp_runtime::Justifications::from((*b"FRNK", vec![1, 2, 3])),
+		);
+	}
+
 	#[test]
 	fn open_empty_database_works() {
 		let db_dir = tempfile::TempDir::new().unwrap();
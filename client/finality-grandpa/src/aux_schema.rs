@@ -30,12 +30,15 @@ use crate::authorities::{AuthoritySet, SharedAuthoritySet, PendingChange, DelayK
 use crate::environment::{
 	CompletedRound, CompletedRounds, CurrentRounds, HasVoted, SharedVoterSetState, VoterSetState,
 };
+use crate::justification::GrandpaJustification;
 use crate::NewAuthoritySet;
 
 const VERSION_KEY: &[u8] = b"grandpa_schema_version";
 const SET_STATE_KEY: &[u8] = b"grandpa_completed_round";
 const CONCLUDED_ROUNDS: &[u8] = b"grandpa_concluded_rounds";
 const AUTHORITY_SET_KEY: &[u8] = b"grandpa_voters";
+const AUTHORITY_SET_CHANGE_PREFIX: &[u8] = b"grandpa_set_change";
+const BEST_JUSTIFICATION: &[u8] = b"grandpa_best_justification";
 
 const CURRENT_VERSION: u32 = 2;
 
@@ -353,6 +356,40 @@ pub(crate) fn load_persistent<Block: BlockT, B, G>(
 	})
 }
 
+fn authority_set_change_key(set_id: SetId) -> Vec<u8> {
+	let mut key = AUTHORITY_SET_CHANGE_PREFIX.to_vec();
+	set_id.using_encoded(|s| key.extend(s));
+	key
+}
+
+/// Load the number of the last block finalized by the given authority set, i.e. the block
+/// which enacted the following set. Returns `None` if the set is still live or if the
+/// handoff happened before this node started keeping track of them.
+pub(crate) fn load_authority_set_change<B: AuxStore, N: Decode>(
+	backend: &B,
+	set_id: SetId,
+) -> ClientResult<Option<N>> {
+	load_decode(backend, &authority_set_change_key(set_id))
+}
+
+/// Load the justification of the latest block finalized by this node, if any.
+pub(crate) fn best_justification<B: AuxStore, Block: BlockT>(
+	backend: &B,
+) -> ClientResult<Option<GrandpaJustification<Block>>> {
+	load_decode(backend, BEST_JUSTIFICATION)
+}
+
+/// Update the justification of the latest finalized block on disk.
+pub(crate) fn update_best_justification<Block: BlockT, F, R>(
+	justification: &GrandpaJustification<Block>,
+	write_aux: F,
+) -> R where
+	F: FnOnce(&[(&[u8], &[u8])]) -> R,
+{
+	let encoded_justification = justification.encode();
+	write_aux(&[(BEST_JUSTIFICATION, &encoded_justification[..])])
+}
+
 /// Update the authority set on disk after a change.
 ///
 /// If there has just been a handoff, pass a `new_set` parameter that describes the
@@ -363,7 +400,7 @@ pub(crate) fn update_authority_set<Block: BlockT, F, R>(
 	new_set: Option<&NewAuthoritySet<Block::Hash, NumberFor<Block>>>,
 	write_aux: F
 ) -> R where
-	F: FnOnce(&[(&'static [u8], &[u8])]) -> R,
+	F: FnOnce(&[(&[u8], &[u8])]) -> R,
 {
 	// write new authority set state to disk.
 	let encoded_set = set.encode();
@@ -379,9 +416,15 @@ pub(crate) fn update_authority_set<Block: BlockT, F, R>(
 		);
 		let encoded = set_state.encode();
 
+		// keep track of the block where the previous set handed off, this is
+		// used to build warp sync proofs.
+		let change_key = authority_set_change_key(new_set.set_id.saturating_sub(1));
+		let encoded_change = new_set.canon_number.encode();
+
 		write_aux(&[
 			(AUTHORITY_SET_KEY, &encoded_set[..]),
 			(SET_STATE_KEY, &encoded[..]),
+			(&change_key[..], &encoded_change[..]),
 		])
 	} else {
 		write_aux(&[(AUTHORITY_SET_KEY, &encoded_set[..])])
@@ -627,4 +670,39 @@ mod test {
 			Some(completed_round),
 		);
 	}
+
+	#[test]
+	fn update_authority_set_tracks_set_changes() {
+		let client = substrate_test_runtime_client::new();
+		let authorities = vec![(AuthorityId::default(), 100)];
+		let set = AuthoritySet::<H256, u64>::new(
+			authorities.clone(),
+			1,
+			ForkTree::new(),
+			Vec::new(),
+		).unwrap();
+
+		// no handoff, nothing to track
+		update_authority_set::<substrate_test_runtime_client::runtime::Block, _, _>(
+			&set,
+			None,
+			|insert| client.insert_aux(insert, &[]),
+		).unwrap();
+		assert_eq!(load_authority_set_change::<_, u64>(&client, 0).unwrap(), None);
+
+		let new_set = NewAuthoritySet {
+			canon_hash: H256::random(),
+			canon_number: 42,
+			set_id: 1,
+			authorities,
+		};
+		update_authority_set::<substrate_test_runtime_client::runtime::Block, _, _>(
+			&set,
+			Some(&new_set),
+			|insert| client.insert_aux(insert, &[]),
+		).unwrap();
+
+		assert_eq!(load_authority_set_change::<_, u64>(&client, 0).unwrap(), Some(42));
+		assert_eq!(load_authority_set_change::<_, u64>(&client, 1).unwrap(), None);
+	}
 }
@@ -56,7 +56,7 @@ use crate::until_imported::UntilVoteTargetImported;
 use crate::voting_rule::VotingRule;
 use sp_finality_grandpa::{
 	AuthorityId, AuthoritySignature, Equivocation, EquivocationProof,
-	GrandpaApi, RoundNumber, SetId,
+	GrandpaApi, RoundNumber, SetId, GRANDPA_ENGINE_ID,
 };
 use prometheus_endpoint::{register, Counter, Gauge, PrometheusError, U64};
 
@@ -1238,10 +1238,11 @@ where
 		// `N+1`. this assumption is required to make sure we store
 		// justifications for transition blocks which will be requested by
 		// syncing clients.
-		let justification = match justification_or_commit {
+		let (justification, best_justification) = match justification_or_commit {
 			JustificationOrCommit::Justification(justification) => {
 				notify_justification(justification_sender, || Ok(justification.clone()));
-				Some(justification.encode())
+				let encoded = justification.encode();
+				(Some(encoded), justification)
 			},
 			JustificationOrCommit::Commit((round_number, commit)) => {
 				let mut justification_required =
@@ -1260,27 +1261,19 @@ where
 					}
 				}
 
-				// NOTE: the code below is a bit more verbose because we
-				// really want to avoid creating a justification if it isn't
-				// needed (e.g. if there's no subscribers), and also to avoid
-				// creating it twice. depending on the vote tree for the round,
-				// creating a justification might require multiple fetches of
-				// headers from the database.
-				let justification = || GrandpaJustification::from_commit(
+				// the justification is always created, the latest one is kept to serve
+				// warp sync proofs up to the latest finalized block.
+				let justification = GrandpaJustification::from_commit(
 					&client,
 					round_number,
 					commit,
-				);
+				)?;
+				notify_justification(justification_sender, || Ok(justification.clone()));
 
 				if justification_required {
-					let justification = justification()?;
-					notify_justification(justification_sender, || Ok(justification.clone()));
-
-					Some(justification.encode())
+					(Some(justification.encode()), justification)
 				} else {
-					notify_justification(justification_sender, justification);
-
-					None
+					(None, justification)
 				}
 			},
 		};
@@ -1289,6 +1282,7 @@ where
 
 		// ideally some handle to a synchronization oracle would be used
 		// to avoid unconditionally notifying.
+		let justification = justification.map(|j| (GRANDPA_ENGINE_ID, j));
 		client.apply_finality(import_op, BlockId::Hash(hash), justification, true).map_err(|e| {
 			warn!(target: "afg", "Error applying finality to block {:?}: {:?}", (hash, number), e);
 			e
@@ -1328,6 +1322,12 @@ where
 			None
 		};
 
+		// the justification is kept around even if it isn't stored in the database.
+		crate::aux_schema::update_best_justification(
+			&best_justification,
+			|insert| apply_aux(import_op, insert, &[]),
+		)?;
+
 		if status.changed {
 			let write_result = crate::aux_schema::update_authority_set::<Block, _, _>(
 				&authority_set,
@@ -38,6 +38,14 @@
 //! Finality proof provider can choose how to provide finality proof on its own. The incomplete
 //! finality proof (that finalizes some block C that is ancestor of the B and descendant
 //! of the U) could be returned.
+//!
+//! Warp sync proofs are a lighter variant that only prove the authority set changes: for
+//! every set change after the block known to the caller, the header of the block enacting
+//! the change is provided along with its justification, signed by the previous set. The new
+//! set is read from the change digest of the header, so only changes scheduled without any
+//! delay can be proven this way. Forced changes can't be proven either, since the block that
+//! enacts them isn't justified by the previous set. A complete proof ends with the justification
+//! of the latest finalized block.
 
 use std::sync::Arc;
 use log::trace;
@@ -51,19 +59,28 @@ use sc_client_api::{
 use parity_scale_codec::{Encode, Decode};
 use finality_grandpa::BlockNumberOps;
 use sp_runtime::{
-	Justification, generic::BlockId,
-	traits::{NumberFor, Block as BlockT, Header as HeaderT, One},
+	EncodedJustification, generic::BlockId,
+	traits::{NumberFor, Block as BlockT, Header as HeaderT, One, Zero},
 };
 use sp_core::storage::StorageKey;
 use sc_telemetry::{telemetry, CONSENSUS_INFO};
-use sp_finality_grandpa::{AuthorityId, AuthorityList, VersionedAuthorityList, GRANDPA_AUTHORITIES_KEY};
+use sp_finality_grandpa::{
+	AuthorityId, AuthorityList, SetId, VersionedAuthorityList, GRANDPA_AUTHORITIES_KEY,
+	GRANDPA_ENGINE_ID,
+};
 
+use crate::aux_schema::{best_justification, load_authority_set_change};
+use crate::import::find_scheduled_change;
 use crate::justification::GrandpaJustification;
 use crate::VoterSet;
 
 /// Maximum number of fragments that we want to return in a single prove_finality call.
 const MAX_FRAGMENTS_IN_PROOF: usize = 8;
 
+/// Maximum number of authority set change proofs that we want to return in a single warp sync
+/// proof.
+pub(crate) const MAX_WARP_SYNC_PROOFS: usize = 64;
+
 /// GRANDPA authority set related methods for the finality proof provider.
 pub trait AuthoritySetForFinalityProver<Block: BlockT>: Send + Sync {
 	/// Read GRANDPA_AUTHORITIES_KEY from storage at given block.
@@ -209,6 +226,28 @@ impl<B, Block> FinalityProofProvider<B, Block>
 	}
 }
 
+/// Proof of a single authority set change, as used by warp sync.
+///
+/// The last proof of a finished [`WarpSyncProof`] may instead prove the finality of the latest
+/// finalized block, in which case its header doesn't signal any change.
+#[derive(Debug, PartialEq, Encode, Decode, Clone)]
+pub struct AuthoritySetChangeProof<Header: HeaderT> {
+	/// The header of the block that enacts the authority set change. The change must be
+	/// signalled in this header.
+	pub header: Header,
+	/// Justification of the block, signed by the previous authority set.
+	pub justification: EncodedJustification,
+}
+
+/// Proof of the authority set changes that happened after some block.
+#[derive(Debug, PartialEq, Encode, Decode, Clone)]
+pub struct WarpSyncProof<Header: HeaderT> {
+	/// Authority set change proofs, ordered by block number.
+	pub proofs: Vec<AuthoritySetChangeProof<Header>>,
+	/// Whether the proofs go up to the latest authority set change known to the prover.
+	pub is_finished: bool,
+}
+
 /// The effects of block finality.
 #[derive(Debug, PartialEq)]
 pub struct FinalityEffects<Header: HeaderT> {
@@ -331,7 +370,9 @@ pub(crate) fn prove_finality<Block: BlockT, B: BlockchainBackend<Block>, J>(
 			unknown_headers.push(unknown_header);
 		}
 
-		if let Some(justification) = blockchain.justification(current_id)? {
+		let justification = blockchain.justifications(current_id)?
+			.and_then(|justifications| justifications.into_justification(GRANDPA_ENGINE_ID));
+		if let Some(justification) = justification {
 			// check if the current block enacts new GRANDPA authorities set
 			let new_authorities = authorities_provider.authorities(&current_id)?;
 			let new_authorities_proof = if current_authorities != new_authorities {
@@ -425,6 +466,137 @@ pub(crate) fn prove_finality<Block: BlockT, B: BlockchainBackend<Block>, J>(
 	}
 }
 
+/// Prepare a warp sync proof of the authority set changes that happened after the block `begin`.
+///
+/// At most `max_proofs` changes are proven, the caller is expected to request the remaining
+/// ones starting at the last block of the proof.
+pub(crate) fn prove_warp_sync<Block: BlockT, B: Backend<Block>>(
+	backend: &B,
+	current_set_id: SetId,
+	begin: Block::Hash,
+	max_proofs: usize,
+) -> ClientResult<WarpSyncProof<Block::Header>> {
+	let blockchain = backend.blockchain();
+	let begin_number = blockchain.expect_block_number_from_id(&BlockId::Hash(begin))?;
+	if begin_number > blockchain.info().finalized_number {
+		return Err(ClientError::Backend(
+			format!("Cannot generate warp sync proof for non-finalized block: {}", begin),
+		));
+	}
+
+	let canonical_begin = blockchain.expect_block_hash_from_id(&BlockId::Number(begin_number))?;
+	if begin != canonical_begin {
+		return Err(ClientError::Backend(
+			format!("Cannot generate warp sync proof for non-canonical block: {}", begin),
+		));
+	}
+
+	// walk the set changes backwards until we reach the set of the `begin` block, every
+	// change in between must be known to us.
+	let mut change_numbers = Vec::new();
+	for set_id in (0..current_set_id).rev() {
+		match load_authority_set_change::<_, NumberFor<Block>>(backend, set_id)? {
+			Some(number) if number <= begin_number => break,
+			Some(number) => change_numbers.push(number),
+			None => return Err(ClientError::Backend(
+				format!("Missing authority set change of set #{} to prove warp sync from {}", set_id, begin),
+			)),
+		}
+	}
+
+	let is_finished = change_numbers.len() <= max_proofs;
+	let mut proofs = Vec::new();
+	for number in change_numbers.into_iter().rev().take(max_proofs) {
+		let id = BlockId::Number(number);
+		let header = blockchain.expect_header(id)?;
+
+		let signalled = find_scheduled_change::<Block>(&header)
+			.map_or(false, |change| change.delay.is_zero());
+		if !signalled {
+			return Err(ClientError::Backend(
+				format!("Authority set change enacted at block #{} is not signalled in its header", number),
+			));
+		}
+
+		let justification = blockchain.justifications(id)?
+			.and_then(|justifications| justifications.into_justification(GRANDPA_ENGINE_ID))
+			.ok_or_else(|| ClientError::Backend(
+				format!("Missing justification for authority set change enacted at block #{}", number),
+			))?;
+
+		proofs.push(AuthoritySetChangeProof { header, justification });
+	}
+
+	// a finished proof ends with the latest finalized block, so that it can be used as a
+	// warp sync target even if the last set change is old.
+	if is_finished {
+		let last_number = proofs.last().map_or(begin_number, |proof| *proof.header.number());
+		if let Some(justification) = best_justification::<_, Block>(backend)? {
+			let number = justification.commit.target_number;
+			if number > last_number {
+				let header = blockchain.expect_header(BlockId::Hash(justification.commit.target_hash))?;
+				proofs.push(AuthoritySetChangeProof { header, justification: justification.encode() });
+			}
+		}
+	}
+
+	trace!(
+		target: "afg",
+		"Built warp sync proof from {} with {} authority set changes, finished: {}.",
+		begin,
+		proofs.len(),
+		is_finished,
+	);
+
+	Ok(WarpSyncProof { proofs, is_finished })
+}
+
+/// Check a warp sync proof, starting with the given authority set.
+///
+/// Returns the authority set after the last proven change, along with the header of the last
+/// proven block, or `None` if the proof is empty.
+pub(crate) fn check_warp_sync_proof<Block: BlockT>(
+	mut set_id: SetId,
+	mut authorities: AuthorityList,
+	proof: WarpSyncProof<Block::Header>,
+) -> ClientResult<(SetId, AuthorityList, Option<Block::Header>)>
+	where
+		NumberFor<Block>: BlockNumberOps,
+{
+	let mut last_header = None;
+	let num_proofs = proof.proofs.len();
+	let is_finished = proof.is_finished;
+	for (index, fragment) in proof.proofs.into_iter().enumerate() {
+		let AuthoritySetChangeProof { header, justification } = fragment;
+		let voters = VoterSet::new(authorities.iter().cloned()).ok_or(
+			ClientError::Consensus(sp_consensus::Error::InvalidAuthoritiesSet),
+		)?;
+
+		GrandpaJustification::<Block>::decode_and_verify_finalizes(
+			&justification,
+			(header.hash(), *header.number()),
+			set_id,
+			&voters,
+		)?;
+
+		match find_scheduled_change::<Block>(&header).filter(|change| change.delay.is_zero()) {
+			Some(change) => {
+				authorities = change.next_authorities;
+				set_id += 1;
+			},
+			// the latest finalized block closes a finished proof.
+			None if is_finished && index + 1 == num_proofs => {},
+			None => return Err(ClientError::BadJustification(
+				"warp sync proof for a block that doesn't enact an authority set change".into(),
+			)),
+		}
+
+		last_header = Some(header);
+	}
+
+	Ok((set_id, authorities, last_header))
+}
+
 /// Check GRANDPA proof-of-finality for the given block.
 ///
 /// Returns the vector of headers that MUST be validated + imported
@@ -558,7 +730,7 @@ pub(crate) trait ProvableJustification<Header: HeaderT>: Encode + Decode {
 
 	/// Decode and verify justification.
 	fn decode_and_verify(
-		justification: &Justification,
+		justification: &EncodedJustification,
 		set_id: u64,
 		authorities: &[(AuthorityId, u64)],
 	) -> ClientResult<Self> {
@@ -585,10 +757,15 @@ impl<Block: BlockT> ProvableJustification<Block::Header> for GrandpaJustificatio
 #[cfg(test)]
 pub(crate) mod tests {
 	use substrate_test_runtime_client::runtime::{Block, Header, H256};
-	use sc_client_api::NewBlockState;
-	use sc_client_api::in_mem::Blockchain as InMemoryBlockchain;
+	use sc_client_api::{AuxStore, NewBlockState};
+	use sp_runtime::Justifications;
+	use sc_client_api::in_mem::{Backend as InMemoryBackend, Blockchain as InMemoryBlockchain};
 	use super::*;
+	use crate::authorities::AuthoritySet;
 	use sp_core::crypto::Public;
+	use sp_finality_grandpa::{ConsensusLog, ScheduledChange, GRANDPA_ENGINE_ID};
+	use sp_keyring::Ed25519Keyring;
+	use sp_runtime::DigestItem;
 
 	pub(crate) type FinalityProof = super::FinalityProof<Header>;
 
@@ -665,10 +842,28 @@ pub(crate) mod tests {
 
 	fn test_blockchain() -> InMemoryBlockchain<Block> {
 		let blockchain = InMemoryBlockchain::<Block>::new();
-		blockchain.insert(header(0).hash(), header(0), Some(vec![0]), None, NewBlockState::Final).unwrap();
-		blockchain.insert(header(1).hash(), header(1), Some(vec![1]), None, NewBlockState::Final).unwrap();
+		blockchain.insert(
+			header(0).hash(),
+			header(0),
+			Some(Justifications::from((GRANDPA_ENGINE_ID, vec![0]))),
+			None,
+			NewBlockState::Final,
+		).unwrap();
+		blockchain.insert(
+			header(1).hash(),
+			header(1),
+			Some(Justifications::from((GRANDPA_ENGINE_ID, vec![1]))),
+			None,
+			NewBlockState::Final,
+		).unwrap();
 		blockchain.insert(header(2).hash(), header(2), None, None, NewBlockState::Best).unwrap();
-		blockchain.insert(header(3).hash(), header(3), Some(vec![3]), None, NewBlockState::Final).unwrap();
+		blockchain.insert(
+			header(3).hash(),
+			header(3),
+			Some(Justifications::from((GRANDPA_ENGINE_ID, vec![3]))),
+			None,
+			NewBlockState::Final,
+		).unwrap();
 		blockchain
 	}
 
@@ -719,7 +914,13 @@ pub(crate) mod tests {
 		blockchain.insert(side_header(4).hash(), side_header(4), None, None, NewBlockState::Best).unwrap();
 		blockchain.insert(second_side_header(5).hash(), second_side_header(5), None, None, NewBlockState::Best)
 			.unwrap();
-		blockchain.insert(header(5).hash(), header(5), Some(vec![5]), None, NewBlockState::Final).unwrap();
+		blockchain.insert(
+			header(5).hash(),
+			header(5),
+			Some(Justifications::from((GRANDPA_ENGINE_ID, vec![5]))),
+			None,
+			NewBlockState::Final,
+		).unwrap();
 
 		// chain is 1 -> 2 -> 3 -> 4 -> 5
 		//                      \> 4' -> 5'
@@ -763,8 +964,20 @@ pub(crate) mod tests {
 		let authorities = vec![(AuthorityId::from_slice(&[1u8; 32]), 1u64)];
 		let just4 = TestJustification((0, authorities.clone()), vec![4]).encode();
 		let just5 = TestJustification((0, authorities.clone()), vec![5]).encode();
-		blockchain.insert(header(4).hash(), header(4), Some(just4), None, NewBlockState::Final).unwrap();
-		blockchain.insert(header(5).hash(), header(5), Some(just5.clone()), None, NewBlockState::Final).unwrap();
+		blockchain.insert(
+			header(4).hash(),
+			header(4),
+			Some(Justifications::from((GRANDPA_ENGINE_ID, just4))),
+			None,
+			NewBlockState::Final,
+		).unwrap();
+		blockchain.insert(
+			header(5).hash(),
+			header(5),
+			Some(Justifications::from((GRANDPA_ENGINE_ID, just5.clone()))),
+			None,
+			NewBlockState::Final,
+		).unwrap();
 
 		// blocks 4 && 5 are finalized with justification
 		// => since authorities are the same, we only need justification for 5
@@ -789,7 +1002,13 @@ pub(crate) mod tests {
 	#[test]
 	fn finality_proof_finalized_earlier_block_if_no_justification_for_target_is_known() {
 		let blockchain = test_blockchain();
-		blockchain.insert(header(4).hash(), header(4), Some(vec![4]), None, NewBlockState::Final).unwrap();
+		blockchain.insert(
+			header(4).hash(),
+			header(4),
+			Some(Justifications::from((GRANDPA_ENGINE_ID, vec![4]))),
+			None,
+			NewBlockState::Final,
+		).unwrap();
 		blockchain.insert(header(5).hash(), header(5), None, None, NewBlockState::Final).unwrap();
 
 		// block 4 is finalized with justification + we request for finality of 5
@@ -821,10 +1040,28 @@ pub(crate) mod tests {
 		let just4 = TestJustification((0, auth3.clone()), vec![4]).encode();
 		let just5 = TestJustification((0, auth3.clone()), vec![5]).encode();
 		let just7 = TestJustification((1, auth5.clone()), vec![7]).encode();
-		blockchain.insert(header(4).hash(), header(4), Some(just4), None, NewBlockState::Final).unwrap();
-		blockchain.insert(header(5).hash(), header(5), Some(just5.clone()), None, NewBlockState::Final).unwrap();
+		blockchain.insert(
+			header(4).hash(),
+			header(4),
+			Some(Justifications::from((GRANDPA_ENGINE_ID, just4))),
+			None,
+			NewBlockState::Final,
+		).unwrap();
+		blockchain.insert(
+			header(5).hash(),
+			header(5),
+			Some(Justifications::from((GRANDPA_ENGINE_ID, just5.clone()))),
+			None,
+			NewBlockState::Final,
+		).unwrap();
 		blockchain.insert(header(6).hash(), header(6), None, None, NewBlockState::Final).unwrap();
-		blockchain.insert(header(7).hash(), header(7), Some(just7.clone()), None, NewBlockState::Final).unwrap();
+		blockchain.insert(
+			header(7).hash(),
+			header(7),
+			Some(Justifications::from((GRANDPA_ENGINE_ID, just7.clone()))),
+			None,
+			NewBlockState::Final,
+		).unwrap();
 
 		// when querying for finality of 6, we assume that the #3 is the last block known to the requester
 		// => since we only have justification for #7, we provide #7
@@ -1013,7 +1250,13 @@ pub(crate) mod tests {
 		// finality proof at all
 		let blockchain = test_blockchain();
 		let just4 = TestJustification((0, vec![(AuthorityId::from_slice(&[42u8; 32]), 1u64)]), vec![4]).encode();
-		blockchain.insert(header(4).hash(), header(4), Some(just4), None, NewBlockState::Final).unwrap();
+		blockchain.insert(
+			header(4).hash(),
+			header(4),
+			Some(Justifications::from((GRANDPA_ENGINE_ID, just4))),
+			None,
+			NewBlockState::Final,
+		).unwrap();
 
 		let proof_of_4 = prove_finality::<_, _, TestJustification>(
 			&blockchain,
@@ -1027,4 +1270,197 @@ pub(crate) mod tests {
 		).unwrap();
 		assert!(proof_of_4.is_none());
 	}
+
+	fn warp_sync_header(number: u64, parent_hash: H256, change: Option<AuthorityList>) -> Header {
+		let mut header = Header::new(
+			number,
+			H256::from_low_u64_be(0),
+			H256::from_low_u64_be(0),
+			parent_hash,
+			Default::default(),
+		);
+		if let Some(next_authorities) = change {
+			let log = ConsensusLog::ScheduledChange(ScheduledChange { next_authorities, delay: 0 });
+			header.digest_mut().push(DigestItem::Consensus(GRANDPA_ENGINE_ID, log.encode()));
+		}
+		header
+	}
+
+	fn warp_sync_justification(
+		blockchain: &InMemoryBlockchain<Block>,
+		header: &Header,
+		set_id: u64,
+		voter: Ed25519Keyring,
+	) -> EncodedJustification {
+		let round = 1;
+		let precommit = finality_grandpa::Precommit {
+			target_hash: header.hash(),
+			target_number: *header.number(),
+		};
+
+		let msg = finality_grandpa::Message::Precommit(precommit.clone());
+		let encoded = sp_finality_grandpa::localized_payload(round, set_id, &msg);
+		let precommit = finality_grandpa::SignedPrecommit {
+			precommit,
+			signature: voter.sign(&encoded[..]).into(),
+			id: voter.public().into(),
+		};
+
+		let commit = finality_grandpa::Commit {
+			target_hash: header.hash(),
+			target_number: *header.number(),
+			precommits: vec![precommit],
+		};
+
+		GrandpaJustification::from_commit(&Arc::new(blockchain.clone()), round, commit)
+			.unwrap()
+			.encode()
+	}
+
+	// builds a chain of 10 finalized blocks, the authority set changes from Alice to Bob
+	// at block 4 and from Bob to Charlie at block 8.
+	fn warp_sync_backend() -> (InMemoryBackend<Block>, Vec<Header>) {
+		let backend = InMemoryBackend::<Block>::new();
+		let blockchain = backend.blockchain();
+		let changes = vec![
+			(4, 0, Ed25519Keyring::Alice, Ed25519Keyring::Bob),
+			(8, 1, Ed25519Keyring::Bob, Ed25519Keyring::Charlie),
+		];
+
+		let mut headers = vec![warp_sync_header(0, Default::default(), None)];
+		blockchain.insert(headers[0].hash(), headers[0].clone(), None, None, NewBlockState::Final).unwrap();
+
+		for number in 1..=10 {
+			let change = changes.iter().find(|change| change.0 == number);
+			let header = warp_sync_header(
+				number,
+				headers.last().unwrap().hash(),
+				change.map(|(_, _, _, next)| vec![(next.public().into(), 1)]),
+			);
+			let justification = change.map(|(_, set_id, voter, _)| {
+				warp_sync_justification(blockchain, &header, *set_id, *voter)
+			});
+			let justifications = justification
+				.map(|justification| Justifications::from((GRANDPA_ENGINE_ID, justification)));
+			blockchain.insert(header.hash(), header.clone(), justifications, None, NewBlockState::Final).unwrap();
+
+			if let Some((_, set_id, _, next)) = change {
+				let authorities = vec![(next.public().into(), 1)];
+				let set = AuthoritySet::genesis(authorities.clone()).unwrap();
+				let new_set = crate::NewAuthoritySet {
+					canon_hash: header.hash(),
+					canon_number: number,
+					set_id: set_id + 1,
+					authorities,
+				};
+				crate::aux_schema::update_authority_set::<Block, _, _>(
+					&set,
+					Some(&new_set),
+					|insert| AuxStore::insert_aux(&backend, insert, &[]),
+				).unwrap();
+			}
+
+			headers.push(header);
+		}
+
+		(backend, headers)
+	}
+
+	#[test]
+	fn warp_sync_proof_proves_authority_set_changes() {
+		let (backend, headers) = warp_sync_backend();
+		let genesis_authorities = vec![(Ed25519Keyring::Alice.public().into(), 1)];
+		let last_authorities: AuthorityList = vec![(Ed25519Keyring::Charlie.public().into(), 1)];
+
+		let proof = prove_warp_sync::<Block, _>(&backend, 2, headers[0].hash(), MAX_WARP_SYNC_PROOFS).unwrap();
+		assert!(proof.is_finished);
+		assert_eq!(
+			proof.proofs.iter().map(|p| p.header.clone()).collect::<Vec<_>>(),
+			vec![headers[4].clone(), headers[8].clone()],
+		);
+
+		assert_eq!(
+			check_warp_sync_proof::<Block>(0, genesis_authorities.clone(), proof.clone()).unwrap(),
+			(2, last_authorities.clone(), Some(headers[8].clone())),
+		);
+
+		// a proof is only valid starting at the right authority set
+		assert!(check_warp_sync_proof::<Block>(1, genesis_authorities.clone(), proof.clone()).is_err());
+		assert!(check_warp_sync_proof::<Block>(0, last_authorities.clone(), proof).is_err());
+
+		// the proof only includes changes after the requested block
+		let proof = prove_warp_sync::<Block, _>(&backend, 2, headers[5].hash(), MAX_WARP_SYNC_PROOFS).unwrap();
+		assert!(proof.is_finished);
+		assert_eq!(proof.proofs.len(), 1);
+		assert_eq!(proof.proofs[0].header, headers[8]);
+
+		let proof = prove_warp_sync::<Block, _>(&backend, 2, headers[8].hash(), MAX_WARP_SYNC_PROOFS).unwrap();
+		assert_eq!(proof, WarpSyncProof { proofs: Vec::new(), is_finished: true });
+	}
+
+	#[test]
+	fn warp_sync_proof_ends_at_latest_finalized_block() {
+		let (backend, headers) = warp_sync_backend();
+		let genesis_authorities = vec![(Ed25519Keyring::Alice.public().into(), 1)];
+		let last_authorities: AuthorityList = vec![(Ed25519Keyring::Charlie.public().into(), 1)];
+
+		let justification = warp_sync_justification(
+			backend.blockchain(),
+			&headers[10],
+			2,
+			Ed25519Keyring::Charlie,
+		);
+		let justification = GrandpaJustification::<Block>::decode(&mut &justification[..]).unwrap();
+		crate::aux_schema::update_best_justification(
+			&justification,
+			|insert| AuxStore::insert_aux(&backend, insert, &[]),
+		).unwrap();
+
+		let proof = prove_warp_sync::<Block, _>(&backend, 2, headers[0].hash(), MAX_WARP_SYNC_PROOFS).unwrap();
+		assert!(proof.is_finished);
+		assert_eq!(
+			proof.proofs.iter().map(|p| p.header.clone()).collect::<Vec<_>>(),
+			vec![headers[4].clone(), headers[8].clone(), headers[10].clone()],
+		);
+		assert_eq!(
+			check_warp_sync_proof::<Block>(0, genesis_authorities.clone(), proof).unwrap(),
+			(2, last_authorities, Some(headers[10].clone())),
+		);
+
+		// the latest finalized block is only accepted at the end of a finished proof
+		let mut proof = prove_warp_sync::<Block, _>(&backend, 2, headers[0].hash(), MAX_WARP_SYNC_PROOFS).unwrap();
+		proof.is_finished = false;
+		assert!(check_warp_sync_proof::<Block>(0, genesis_authorities, proof).is_err());
+	}
+
+	#[test]
+	fn warp_sync_proof_is_split_when_too_large() {
+		let (backend, headers) = warp_sync_backend();
+		let genesis_authorities = vec![(Ed25519Keyring::Alice.public().into(), 1)];
+
+		let proof = prove_warp_sync::<Block, _>(&backend, 2, headers[0].hash(), 1).unwrap();
+		assert!(!proof.is_finished);
+		assert_eq!(proof.proofs.len(), 1);
+
+		let (set_id, authorities, last_header) =
+			check_warp_sync_proof::<Block>(0, genesis_authorities, proof).unwrap();
+		assert_eq!(set_id, 1);
+		assert_eq!(last_header, Some(headers[4].clone()));
+
+		// continue from the last proven block
+		let proof = prove_warp_sync::<Block, _>(&backend, 2, headers[4].hash(), 1).unwrap();
+		assert!(proof.is_finished);
+		assert_eq!(
+			check_warp_sync_proof::<Block>(set_id, authorities, proof).unwrap().2,
+			Some(headers[8].clone()),
+		);
+	}
+
+	#[test]
+	fn warp_sync_proof_fails_with_missing_set_changes() {
+		let (backend, headers) = warp_sync_backend();
+
+		// the node claims to be on set 3 but only knows about two set changes
+		assert!(prove_warp_sync::<Block, _>(&backend, 3, headers[0].hash(), MAX_WARP_SYNC_PROOFS).is_err());
+	}
 }
@@ -19,11 +19,14 @@
 use std::{sync::Arc, collections::HashMap};
 
 use log::debug;
-use parity_scale_codec::Encode;
+use parity_scale_codec::{Decode, Encode};
 use parking_lot::RwLockWriteGuard;
 
 use sp_blockchain::{BlockStatus, well_known_cache_keys};
-use sc_client_api::{backend::Backend, utils::is_descendent_of};
+use sc_client_api::{
+	backend::Backend, utils::is_descendent_of, CallExecutor, ExecutionStrategy, ExecutorProvider,
+};
+use fork_tree::ForkTree;
 use sp_utils::mpsc::TracingUnboundedSender;
 use sp_api::{TransactionFor};
 
@@ -32,7 +35,7 @@ use sp_consensus::{
 	BlockCheckParams, BlockImportParams, BlockOrigin, ImportResult, JustificationImport,
 	SelectChain,
 };
-use sp_finality_grandpa::{ConsensusLog, ScheduledChange, SetId, GRANDPA_ENGINE_ID};
+use sp_finality_grandpa::{AuthorityList, ConsensusLog, ScheduledChange, SetId, GRANDPA_ENGINE_ID};
 use sp_runtime::Justification;
 use sp_runtime::generic::{BlockId, OpaqueDigestItemId};
 use sp_runtime::traits::{
@@ -182,7 +185,7 @@ impl<'a, Block: 'a + BlockT> Drop for PendingSetChanges<'a, Block> {
 	}
 }
 
-fn find_scheduled_change<B: BlockT>(header: &B::Header)
+pub(crate) fn find_scheduled_change<B: BlockT>(header: &B::Header)
 	-> Option<ScheduledChange<NumberFor<B>>>
 {
 	let id = OpaqueDigestItemId::Consensus(&GRANDPA_ENGINE_ID);
@@ -401,6 +404,72 @@ where
 	}
 }
 
+impl<BE, Block: BlockT, Client, SC> GrandpaBlockImport<BE, Block, Client, SC> where
+	NumberFor<Block>: finality_grandpa::BlockNumberOps,
+	DigestFor<Block>: Encode,
+	BE: Backend<Block>,
+	Client: crate::ClientForGrandpa<Block, BE>,
+	for<'a> &'a Client:
+		BlockImport<Block, Error = ConsensusError, Transaction = TransactionFor<Client, Block>>,
+{
+	/// Import a block along with its state downloaded by warp sync.
+	///
+	/// Finality of the block was proven by the warp sync proof, so the block is imported as
+	/// finalized and the authority set is reset to the one found in its state.
+	fn import_state(
+		&mut self,
+		mut block: BlockImportParams<Block, TransactionFor<Client, Block>>,
+		new_cache: HashMap<well_known_cache_keys::Id, Vec<u8>>,
+	) -> Result<ImportResult, ConsensusError> {
+		let hash = block.post_hash();
+		let number = *block.header.number();
+		block.finalized = true;
+
+		let aux = match (&*self.inner).import_block(block, new_cache) {
+			Ok(ImportResult::Imported(aux)) => aux,
+			Ok(r) => return Ok(r),
+			Err(e) => return Err(ConsensusError::ClientImport(e.to_string())),
+		};
+
+		let id = BlockId::Hash(hash);
+		let authorities: AuthorityList = call_grandpa_api(&*self.inner, &id, "grandpa_authorities")?;
+		let set_id: SetId = call_grandpa_api(&*self.inner, &id, "current_set_id")?;
+		let authority_set = AuthoritySet::new(authorities.clone(), set_id, ForkTree::new(), Vec::new())
+			.ok_or_else(|| ConsensusError::ClientImport(
+				"Invalid GRANDPA authority set in imported state".into(),
+			))?;
+		let new_set = NewAuthoritySet { canon_number: number, canon_hash: hash, set_id, authorities };
+
+		// the block where the previous set handed off is unknown, so no set change is
+		// recorded. the voter resets its state when handling the `ChangeAuthorities` command.
+		crate::aux_schema::update_authority_set::<Block, _, _>(
+			&authority_set,
+			None,
+			|insert| self.inner.insert_aux(insert, &[]),
+		).map_err(|e| ConsensusError::ClientImport(e.to_string()))?;
+
+		debug!(target: "afg", "Reset authority set to #{} after importing state of {:?}", set_id, hash);
+		*self.authority_set.inner().write() = authority_set;
+		let _ = self.send_voter_commands.unbounded_send(VoterCommand::ChangeAuthorities(new_set));
+
+		Ok(ImportResult::Imported(aux))
+	}
+}
+
+/// Call a `GrandpaApi` function at the given block, bypassing the runtime api version checks.
+fn call_grandpa_api<Block: BlockT, Client: ExecutorProvider<Block>, T: Decode>(
+	client: &Client,
+	id: &BlockId<Block>,
+	function: &str,
+) -> Result<T, ConsensusError> {
+	let result = client.executor()
+		.call(id, &format!("GrandpaApi_{}", function), &[], ExecutionStrategy::NativeElseWasm, None)
+		.map_err(|e| ConsensusError::ClientImport(e.to_string()))?;
+	T::decode(&mut &result[..]).map_err(|e| ConsensusError::ClientImport(
+		format!("Failed to decode result of GrandpaApi_{}: {:?}", function, e),
+	))
+}
+
 impl<BE, Block: BlockT, Client, SC> BlockImport<Block>
 	for GrandpaBlockImport<BE, Block, Client, SC> where
 		NumberFor<Block>: finality_grandpa::BlockNumberOps,
@@ -424,7 +493,17 @@ impl<BE, Block: BlockT, Client, SC> BlockImport<Block>
 		// early exit if block already in chain, otherwise the check for
 		// authority changes will error when trying to re-import a change block
 		match self.inner.status(BlockId::Hash(hash)) {
+			Ok(BlockStatus::InChain) if block.import_existing => {
+				// the block is re-imported to add its state or body, authority set
+				// changes were already tracked when the header was imported.
+				block.justifications = None;
+				return (&*self.inner).import_block(block, new_cache);
+			},
 			Ok(BlockStatus::InChain) => return Ok(ImportResult::AlreadyInChain),
+			Ok(BlockStatus::Unknown) if block.importing_state.is_some() => {
+				// the block comes from warp sync, its ancestors are unknown.
+				return self.import_state(block, new_cache);
+			},
 			Ok(BlockStatus::Unknown) => {},
 			Err(e) => return Err(ConsensusError::ClientImport(e.to_string())),
 		}
@@ -435,7 +514,9 @@ impl<BE, Block: BlockT, Client, SC> BlockImport<Block>
 		let pending_changes = self.make_authorities_changes(&mut block, hash, initial_sync)?;
 
 		// we don't want to finalize on `inner.import_block`
-		let mut justification = block.justification.take();
+		let mut justification = block.justifications
+			.take()
+			.and_then(|justifications| justifications.into_justification(GRANDPA_ENGINE_ID));
 		let import_result = (&*self.inner).import_block(block, new_cache);
 
 		let mut imported_aux = {
@@ -507,7 +588,7 @@ impl<BE, Block: BlockT, Client, SC> BlockImport<Block>
 				let import_res = self.import_justification(
 					hash,
 					number,
-					justification,
+					(GRANDPA_ENGINE_ID, justification),
 					needs_justification,
 					initial_sync,
 				);
@@ -619,8 +700,14 @@ where
 		enacts_change: bool,
 		initial_sync: bool,
 	) -> Result<(), ConsensusError> {
+		if justification.0 != GRANDPA_ENGINE_ID {
+			return Err(ConsensusError::ClientImport(
+				"GRANDPA can only import GRANDPA justifications.".into(),
+			));
+		}
+
 		let justification = GrandpaJustification::decode_and_verify_finalizes(
-			&justification,
+			&justification.1,
 			(hash, number),
 			self.authority_set.set_id(),
 			&self.authority_set.current_authorities(),
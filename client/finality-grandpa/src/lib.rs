@@ -120,10 +120,14 @@ mod notification;
 mod observer;
 mod until_imported;
 mod voting_rule;
+pub mod warp_proof;
 
 pub use authorities::{SharedAuthoritySet, AuthoritySet};
 pub use communication::GRANDPA_PROTOCOL_NAME;
-pub use finality_proof::{FinalityProofFragment, FinalityProofProvider, StorageAndProofProvider};
+pub use finality_proof::{
+	AuthoritySetChangeProof, FinalityProofFragment, FinalityProofProvider, StorageAndProofProvider,
+	WarpSyncProof,
+};
 pub use notification::{GrandpaJustificationSender, GrandpaJustificationStream};
 pub use import::GrandpaBlockImport;
 pub use justification::GrandpaJustification;
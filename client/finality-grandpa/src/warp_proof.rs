@@ -0,0 +1,97 @@
+// This file is part of Substrate.
+
+// Copyright (C) 2020 Parity Technologies (UK) Ltd.
+// SPDX-License-Identifier: GPL-3.0-or-later WITH Classpath-exception-2.0
+
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with this program. If not, see <https://www.gnu.org/licenses/>.
+
+//! Warp sync support for the network, backed by GRANDPA authority set change proofs.
+//!
+//! The proofs start at the genesis authorities and end with the latest block finalized by the
+//! serving node, which becomes the warp sync target.
+
+use std::sync::Arc;
+
+use parity_scale_codec::{Decode, Encode};
+use sc_client_api::Backend;
+use sc_network::warp_request_handler::{EncodedProof, VerificationResult, WarpSyncProvider};
+use sp_blockchain::Error as ClientError;
+use sp_finality_grandpa::{AuthorityList, SetId};
+use sp_runtime::traits::{Block as BlockT, Header as HeaderT, NumberFor};
+
+use crate::{
+	authorities::SharedAuthoritySet,
+	finality_proof::{check_warp_sync_proof, prove_warp_sync, WarpSyncProof, MAX_WARP_SYNC_PROOFS},
+	BlockNumberOps,
+};
+
+/// Serves and verifies GRANDPA warp sync proofs for the network.
+pub struct NetworkProvider<Block: BlockT, B: Backend<Block>> {
+	backend: Arc<B>,
+	authority_set: SharedAuthoritySet<Block::Hash, NumberFor<Block>>,
+}
+
+impl<Block: BlockT, B: Backend<Block>> NetworkProvider<Block, B> {
+	/// Create a new instance for the given backend and authority set.
+	pub fn new(
+		backend: Arc<B>,
+		authority_set: SharedAuthoritySet<Block::Hash, NumberFor<Block>>,
+	) -> Self {
+		NetworkProvider { backend, authority_set }
+	}
+}
+
+impl<Block: BlockT, B: Backend<Block>> WarpSyncProvider<Block> for NetworkProvider<Block, B>
+	where
+		NumberFor<Block>: BlockNumberOps,
+{
+	fn generate(
+		&self,
+		start: Block::Hash,
+	) -> Result<EncodedProof, Box<dyn std::error::Error + Send + Sync>> {
+		let set_id = self.authority_set.set_id();
+		let proof =
+			prove_warp_sync::<Block, _>(&*self.backend, set_id, start, MAX_WARP_SYNC_PROOFS)?;
+		Ok(EncodedProof(proof.encode()))
+	}
+
+	fn verify(
+		&self,
+		proof: &EncodedProof,
+		set_id: SetId,
+		authorities: AuthorityList,
+	) -> Result<VerificationResult<Block>, Box<dyn std::error::Error + Send + Sync>> {
+		let EncodedProof(proof) = proof;
+		let proof = WarpSyncProof::<Block::Header>::decode(&mut proof.as_slice())
+			.map_err(|e| format!("Proof decoding error: {:?}", e))?;
+		let is_finished = proof.is_finished;
+
+		let (set_id, authorities, last_header) =
+			check_warp_sync_proof::<Block>(set_id, authorities, proof)?;
+
+		match last_header {
+			Some(header) if is_finished =>
+				Ok(VerificationResult::Complete(set_id, authorities, header)),
+			Some(header) =>
+				Ok(VerificationResult::Partial(set_id, authorities, header.hash())),
+			None => Err(Box::new(ClientError::BadJustification(
+				"warp sync proof without any authority set change".into(),
+			))),
+		}
+	}
+
+	fn current_authorities(&self) -> AuthorityList {
+		self.authority_set.inner().read().current().1.to_vec()
+	}
+}
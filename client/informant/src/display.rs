@@ -101,10 +101,16 @@ impl<B: BlockT> InformantDisplay<B> {
 			),
 		};
 
+		// Blocks missing below a block imported with its state are downloaded in the background.
+		let history = match info.chain.block_gap {
+			Some((start, end)) => format!(", history missing #{}..#{}", start, end),
+			None => String::new(),
+		};
+
 		if self.format.enable_color {
 			info!(
 				target: "substrate",
-				"{} {}{} ({} peers), best: #{} ({}), finalized #{} ({}), {} {}",
+				"{} {}{} ({} peers), best: #{} ({}), finalized #{} ({}){}, {} {}",
 				level,
 				Colour::White.bold().paint(&status),
 				target,
@@ -113,13 +119,14 @@ impl<B: BlockT> InformantDisplay<B> {
 				best_hash,
 				Colour::White.bold().paint(format!("{}", finalized_number)),
 				info.chain.finalized_hash,
+				history,
 				Colour::Green.paint(format!("⬇ {}", TransferRateFormat(avg_bytes_per_sec_inbound))),
 				Colour::Red.paint(format!("⬆ {}", TransferRateFormat(avg_bytes_per_sec_outbound))),
 			)
 		} else {
 			info!(
 				target: "substrate",
-				"{} {}{} ({} peers), best: #{} ({}), finalized #{} ({}), ⬇ {} ⬆ {}",
+				"{} {}{} ({} peers), best: #{} ({}), finalized #{} ({}){}, ⬇ {} ⬆ {}",
 				level,
 				status,
 				target,
@@ -128,6 +135,7 @@ impl<B: BlockT> InformantDisplay<B> {
 				best_hash,
 				finalized_number,
 				info.chain.finalized_hash,
+				history,
 				TransferRateFormat(avg_bytes_per_sec_inbound),
 				TransferRateFormat(avg_bytes_per_sec_outbound),
 			)
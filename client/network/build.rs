@@ -1,5 +1,6 @@
 const PROTOS: &[&str] = &[
 	"src/schema/api.v1.proto",
+	"src/schema/api.v2.proto",
 	"src/schema/light.v1.proto"
 ];
 
@@ -15,10 +15,10 @@
 // along with Substrate.  If not, see <http://www.gnu.org/licenses/>.
 
 use crate::{
-	config::{ProtocolId, Role}, block_requests, light_client_handler,
+	config::{ProtocolId, Role}, block_request_handler, block_requests, light_client_handler,
 	peer_info, request_responses, discovery::{DiscoveryBehaviour, DiscoveryConfig, DiscoveryOut},
-	protocol::{message::Roles, CustomMessageOutcome, NotificationsSink, Protocol},
-	ObservedRole, DhtEvent, ExHashT,
+	protocol::{message::{self, Roles}, CustomMessageOutcome, NotificationsSink, Protocol},
+	schema, traffic::ProtocolsTraffic, warp_request_handler, ObservedRole, DhtEvent, ExHashT,
 };
 
 use bytes::Bytes;
@@ -28,14 +28,16 @@ use libp2p::core::{Multiaddr, PeerId, PublicKey};
 use libp2p::identify::IdentifyInfo;
 use libp2p::kad::record;
 use libp2p::swarm::{NetworkBehaviourAction, NetworkBehaviourEventProcess, PollParameters};
-use log::debug;
+use log::{debug, trace};
+use prost::Message;
 use sp_consensus::{BlockOrigin, import_queue::{IncomingBlock, Origin}};
-use sp_runtime::{traits::{Block as BlockT, NumberFor}, Justification};
+use sp_runtime::{traits::{Block as BlockT, NumberFor}, Justifications};
 use std::{
 	borrow::Cow,
-	collections::{HashSet, VecDeque},
+	collections::{HashMap, HashSet, VecDeque},
 	iter,
 	task::{Context, Poll},
+	sync::Arc,
 	time::Duration,
 };
 
@@ -68,12 +70,42 @@ pub struct Behaviour<B: BlockT, H: ExHashT> {
 	/// Role of our local node, as originally passed from the configuration.
 	#[behaviour(ignore)]
 	role: Role,
+
+	/// Name of the protocol used to download blocks. Peers that don't support it are sent their
+	/// requests through `block_requests` instead.
+	#[behaviour(ignore)]
+	block_request_protocol: String,
+
+	/// Block requests in progress, along with the peer they were sent to and the original
+	/// request.
+	#[behaviour(ignore)]
+	pending_block_requests: HashMap<RequestId, (PeerId, message::BlockRequest<B>)>,
+
+	/// Last block request sent to each peer. Responses to the previous ones are obsolete.
+	#[behaviour(ignore)]
+	latest_block_requests: HashMap<PeerId, RequestId>,
+
+	/// Name of the protocol used to download the state when fast syncing.
+	#[behaviour(ignore)]
+	state_request_protocol: String,
+
+	/// State requests in progress, and the peer they were sent to.
+	#[behaviour(ignore)]
+	pending_state_requests: HashMap<RequestId, PeerId>,
+
+	/// Name of the protocol used to download warp proofs when warp syncing.
+	#[behaviour(ignore)]
+	warp_sync_protocol: String,
+
+	/// Warp proof requests in progress, and the peer they were sent to.
+	#[behaviour(ignore)]
+	pending_warp_sync_requests: HashMap<RequestId, PeerId>,
 }
 
 /// Event generated by `Behaviour`.
 pub enum BehaviourOut<B: BlockT> {
 	BlockImport(BlockOrigin, Vec<IncomingBlock<B>>),
-	JustificationImport(Origin, B::Hash, NumberFor<B>, Justification),
+	JustificationImport(Origin, B::Hash, NumberFor<B>, Justifications),
 
 	/// Started a random iterative Kademlia discovery query.
 	RandomKademliaStarted(ProtocolId),
@@ -182,17 +214,30 @@ impl<B: BlockT, H: ExHashT> Behaviour<B, H> {
 		light_client_handler: light_client_handler::LightClientHandler<B>,
 		disco_config: DiscoveryConfig,
 		request_response_protocols: Vec<request_responses::ProtocolConfig>,
+		protocols_traffic: Arc<ProtocolsTraffic>,
+		block_request_protocol: String,
+		state_request_protocol: String,
+		warp_sync_protocol: String,
 	) -> Result<Self, request_responses::RegisterError> {
 		Ok(Behaviour {
 			substrate,
 			peer_info: peer_info::PeerInfoBehaviour::new(user_agent, local_public_key),
 			discovery: disco_config.finish(),
-			request_responses:
-				request_responses::RequestResponsesBehaviour::new(request_response_protocols.into_iter())?,
+			request_responses: request_responses::RequestResponsesBehaviour::new(
+				request_response_protocols.into_iter(),
+				protocols_traffic,
+			)?,
 			block_requests,
 			light_client_handler,
 			events: VecDeque::new(),
 			role,
+			block_request_protocol,
+			pending_block_requests: HashMap::new(),
+			latest_block_requests: HashMap::new(),
+			state_request_protocol,
+			pending_state_requests: HashMap::new(),
+			warp_sync_protocol,
+			pending_warp_sync_requests: HashMap::new(),
 		})
 	}
 
@@ -296,6 +341,109 @@ impl<B: BlockT, H: ExHashT> Behaviour<B, H> {
 	pub fn light_client_request(&mut self, r: light_client_handler::Request<B>) -> Result<(), light_client_handler::Error> {
 		self.light_client_handler.request(r)
 	}
+
+	/// Sends a block request through the first version of the protocol.
+	fn send_legacy_block_request(&mut self, target: PeerId, request: message::BlockRequest<B>) {
+		match self.block_requests.send_request(&target, request) {
+			block_requests::SendRequestOutcome::Ok => {
+				self.events.push_back(BehaviourOut::OpaqueRequestStarted {
+					peer: target,
+					protocol: self.block_requests.protocol_name().to_owned(),
+				});
+			},
+			block_requests::SendRequestOutcome::Replaced { request_duration, .. } => {
+				self.events.push_back(BehaviourOut::OpaqueRequestFinished {
+					peer: target.clone(),
+					protocol: self.block_requests.protocol_name().to_owned(),
+					request_duration,
+				});
+				self.events.push_back(BehaviourOut::OpaqueRequestStarted {
+					peer: target,
+					protocol: self.block_requests.protocol_name().to_owned(),
+				});
+			}
+			block_requests::SendRequestOutcome::NotConnected |
+			block_requests::SendRequestOutcome::EncodeError(_) => {},
+		}
+	}
+
+	/// Hands the outcome of a block request over to the sync, or retries it through the first
+	/// version of the protocol if the remote doesn't support the second one.
+	fn on_block_request_finished(
+		&mut self,
+		request_id: RequestId,
+		peer: PeerId,
+		request: message::BlockRequest<B>,
+		result: Result<Vec<u8>, RequestFailure>,
+	) {
+		if self.latest_block_requests.get(&peer) != Some(&request_id) {
+			trace!(target: "sync", "Discarding outcome of obsolete block request to {}", peer);
+			return
+		}
+		self.latest_block_requests.remove(&peer);
+
+		let response = match result {
+			Ok(response) => block_request_handler::decode_block_response(&request, &response),
+			Err(RequestFailure::Network(OutboundFailure::UnsupportedProtocols)) => {
+				trace!(target: "sync", "Falling back to the legacy block request protocol for {}", peer);
+				self.send_legacy_block_request(peer, request);
+				return
+			}
+			Err(err) => {
+				debug!(target: "sync", "Block request to {} failed: {:?}", peer, err);
+				self.substrate.on_block_request_failed(&peer);
+				return
+			}
+		};
+
+		match response {
+			Ok(response) => {
+				let ev = self.substrate.on_block_response(peer, response);
+				self.inject_event(ev);
+			}
+			Err(err) => {
+				debug!(target: "sync", "Failed to decode block response from {}: {}", peer, err);
+				self.substrate.on_block_request_failed(&peer);
+			}
+		}
+	}
+
+	/// Hands the outcome of a state request over to the sync.
+	fn on_state_request_finished(&mut self, peer: PeerId, result: Result<Vec<u8>, RequestFailure>) {
+		let response = match result {
+			Ok(response) => schema::v1::StateResponse::decode(&response[..]),
+			Err(err) => {
+				debug!(target: "sync", "State request to {} failed: {:?}", peer, err);
+				self.substrate.on_state_request_failed(&peer);
+				return
+			}
+		};
+
+		match response {
+			Ok(response) => {
+				let ev = self.substrate.on_state_response(peer, response);
+				self.inject_event(ev);
+			}
+			Err(err) => {
+				debug!(target: "sync", "Failed to decode state response from {}: {:?}", peer, err);
+				self.substrate.on_state_request_failed(&peer);
+			}
+		}
+	}
+
+	/// Hands the outcome of a warp proof request over to the sync.
+	fn on_warp_sync_request_finished(&mut self, peer: PeerId, result: Result<Vec<u8>, RequestFailure>) {
+		match result {
+			Ok(response) => self.substrate.on_warp_sync_response(
+				peer,
+				warp_request_handler::EncodedProof(response),
+			),
+			Err(err) => {
+				debug!(target: "sync", "Warp proof request to {} failed: {:?}", peer, err);
+				self.substrate.on_warp_sync_request_failed(&peer);
+			}
+		}
+	}
 }
 
 fn reported_roles_to_observed_role(local_role: &Role, remote: &PeerId, roles: Roles) -> ObservedRole {
@@ -330,26 +478,25 @@ Behaviour<B, H> {
 			CustomMessageOutcome::JustificationImport(origin, hash, nb, justification) =>
 				self.events.push_back(BehaviourOut::JustificationImport(origin, hash, nb, justification)),
 			CustomMessageOutcome::BlockRequest { target, request } => {
-				match self.block_requests.send_request(&target, request) {
-					block_requests::SendRequestOutcome::Ok => {
-						self.events.push_back(BehaviourOut::OpaqueRequestStarted {
-							peer: target,
-							protocol: self.block_requests.protocol_name().to_owned(),
-						});
+				let protobuf_rq = block_request_handler::build_protobuf_block_request(
+					&request,
+					block_request_handler::MAX_RESPONSE_BYTES,
+				);
+				let mut buf = Vec::with_capacity(protobuf_rq.encoded_len());
+				if let Err(err) = protobuf_rq.encode(&mut buf) {
+					log::warn!(target: "sync", "Failed to encode block request {:?}: {:?}", protobuf_rq, err);
+					return
+				}
+
+				match self.request_responses.send_request(&target, &self.block_request_protocol, buf) {
+					Ok(request_id) => {
+						self.latest_block_requests.insert(target.clone(), request_id);
+						self.pending_block_requests.insert(request_id, (target, request));
+					},
+					Err(err) => {
+						debug!(target: "sync", "Failed to send block request to {}: {:?}", target, err);
+						self.substrate.on_block_request_failed(&target);
 					},
-					block_requests::SendRequestOutcome::Replaced { request_duration, .. } => {
-						self.events.push_back(BehaviourOut::OpaqueRequestFinished {
-							peer: target.clone(),
-							protocol: self.block_requests.protocol_name().to_owned(),
-							request_duration,
-						});
-						self.events.push_back(BehaviourOut::OpaqueRequestStarted {
-							peer: target,
-							protocol: self.block_requests.protocol_name().to_owned(),
-						});
-					}
-					block_requests::SendRequestOutcome::NotConnected |
-					block_requests::SendRequestOutcome::EncodeError(_) => {},
 				}
 			},
 			CustomMessageOutcome::NotificationStreamOpened { remote, protocols, roles, notifications_sink } => {
@@ -381,6 +528,35 @@ Behaviour<B, H> {
 			CustomMessageOutcome::NotificationsReceived { remote, messages } => {
 				self.events.push_back(BehaviourOut::NotificationsReceived { remote, messages });
 			},
+			CustomMessageOutcome::StateRequest { target, request } => {
+				let mut buf = Vec::with_capacity(request.encoded_len());
+				if let Err(err) = request.encode(&mut buf) {
+					log::warn!(target: "sync", "Failed to encode state request {:?}: {:?}", request, err);
+					return
+				}
+
+				match self.request_responses.send_request(&target, &self.state_request_protocol, buf) {
+					Ok(request_id) => {
+						self.pending_state_requests.insert(request_id, target);
+					},
+					Err(err) => {
+						debug!(target: "sync", "Failed to send state request to {}: {:?}", target, err);
+						self.substrate.on_state_request_failed(&target);
+					},
+				}
+			},
+			CustomMessageOutcome::WarpSyncRequest { target, request } => {
+				let buf = request.encode();
+				match self.request_responses.send_request(&target, &self.warp_sync_protocol, buf) {
+					Ok(request_id) => {
+						self.pending_warp_sync_requests.insert(request_id, target);
+					},
+					Err(err) => {
+						debug!(target: "sync", "Failed to send warp proof request to {}: {:?}", target, err);
+						self.substrate.on_warp_sync_request_failed(&target);
+					},
+				}
+			},
 			CustomMessageOutcome::PeerNewBest(peer_id, number) => {
 				self.light_client_handler.update_best_block(&peer_id, number);
 			}
@@ -401,10 +577,18 @@ impl<B: BlockT, H: ExHashT> NetworkBehaviourEventProcess<request_responses::Even
 			}
 
 			request_responses::Event::RequestFinished { request_id, result } => {
-				self.events.push_back(BehaviourOut::RequestFinished {
-					request_id,
-					result,
-				});
+				if let Some((peer, request)) = self.pending_block_requests.remove(&request_id) {
+					self.on_block_request_finished(request_id, peer, request, result);
+				} else if let Some(peer) = self.pending_state_requests.remove(&request_id) {
+					self.on_state_request_finished(peer, result);
+				} else if let Some(peer) = self.pending_warp_sync_requests.remove(&request_id) {
+					self.on_warp_sync_request_finished(peer, result);
+				} else {
+					self.events.push_back(BehaviourOut::RequestFinished {
+						request_id,
+						result,
+					});
+				}
 			},
 		}
 	}
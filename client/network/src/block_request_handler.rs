@@ -0,0 +1,629 @@
+// Copyright 2020 Parity Technologies (UK) Ltd.
+// This file is part of Substrate.
+//
+// Substrate is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// Substrate is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with Substrate.  If not, see <http://www.gnu.org/licenses/>.
+
+//! Helper for handling (i.e. answering) block requests from a remote peer via the
+//! [`crate::request_responses::RequestResponsesBehaviour`].
+//!
+//! This is the second version of the block request protocol, whose messages are defined in
+//! `api.v2.proto`. Compared to the first one, served by [`crate::block_requests`], every block
+//! carries all its justifications tagged with the consensus engine that produced them, the
+//! requester can ask for a storage proof of a list of keys at each block, and it tells the
+//! maximum size of the response it accepts.
+//!
+//! Identical requests repeated by the same peer, peers sending too many requests and requests
+//! asking a proof for too many keys are refused.
+
+use codec::{Encode, Decode};
+use crate::{
+	chain::Client,
+	config::ProtocolId,
+	protocol::message::{self, BlockAttributes},
+	request_responses::{IncomingRequest, ProtocolConfig},
+	schema,
+};
+use futures::{channel::mpsc, prelude::*};
+use libp2p::PeerId;
+use lru::LruCache;
+use prost::Message;
+use sp_runtime::{generic::BlockId, traits::{Block as BlockT, Header, One, Zero}, Justifications};
+use std::{
+	cmp::min,
+	convert::TryFrom,
+	sync::Arc,
+	task::{Context, Poll},
+	time::Duration,
+};
+use wasm_timer::Instant;
+
+const LOG_TARGET: &str = "sync";
+/// Maximum number of blocks in a single response.
+const MAX_BLOCKS_IN_RESPONSE: u32 = 128;
+/// Maximum total size of the block bodies in a single response. At least one block is always
+/// sent regardless of this limit.
+const MAX_BODY_BYTES: usize = 8 * 1024 * 1024;
+/// Maximum size of a response, used when the requester doesn't ask for a lower limit.
+pub(crate) const MAX_RESPONSE_BYTES: u64 = 16 * 1024 * 1024;
+/// Maximum number of storage keys a request can ask a proof for. Requests with more keys are
+/// refused.
+const MAX_PROOF_KEYS: usize = 64;
+/// Maximum number of requests buffered before new ones are rejected.
+const MAX_NUMBER_OF_CONCURRENT_REQUESTS: usize = 20;
+/// Number of times the same request from the same peer is answered before being refused.
+const MAX_NUMBER_OF_SAME_REQUESTS_PER_PEER: usize = 2;
+/// Number of distinct requests remembered in order to detect repeated ones.
+const SEEN_REQUESTS_CACHE_SIZE: usize = 1024;
+/// Number of requests a peer can make within [`RATE_LIMIT_PERIOD`].
+const MAX_REQUESTS_PER_PEER_PER_PERIOD: usize = 64;
+/// Period over which the requests of each peer are counted.
+const RATE_LIMIT_PERIOD: Duration = Duration::from_secs(10);
+/// Number of peers whose requests are counted at the same time.
+const RATE_LIMITED_PEERS_CACHE_SIZE: usize = 512;
+
+/// Generates a [`ProtocolConfig`] for the block request protocol, refusing incoming requests.
+pub fn generate_protocol_config(protocol_id: &ProtocolId) -> ProtocolConfig {
+	ProtocolConfig {
+		name: generate_protocol_name(protocol_id).into(),
+		max_request_size: 1024 * 1024,
+		max_response_size: MAX_RESPONSE_BYTES,
+		request_timeout: Duration::from_secs(40),
+		inbound_queue: None,
+	}
+}
+
+/// Generate the block protocol name from chain specific protocol identifier.
+pub fn generate_protocol_name(protocol_id: &ProtocolId) -> String {
+	format!("/{}/sync/3", protocol_id.as_ref())
+}
+
+/// Handler for incoming block requests from a remote peer.
+pub struct BlockRequestHandler<B: BlockT> {
+	client: Arc<dyn Client<B>>,
+	request_receiver: mpsc::Receiver<IncomingRequest>,
+	/// Number of times each request has been received from each peer, indexed by the hash of
+	/// the request payload.
+	seen_requests: LruCache<(PeerId, [u8; 32]), usize>,
+	/// Start of the current rate limiting period of each peer, and number of requests received
+	/// from it since then.
+	requests_per_peer: LruCache<PeerId, (Instant, usize)>,
+}
+
+impl<B: BlockT> BlockRequestHandler<B> {
+	/// Create a new [`BlockRequestHandler`], along with the protocol configuration it answers
+	/// requests for.
+	pub fn new(protocol_id: &ProtocolId, client: Arc<dyn Client<B>>) -> (Self, ProtocolConfig) {
+		let (tx, request_receiver) = mpsc::channel(MAX_NUMBER_OF_CONCURRENT_REQUESTS);
+
+		let mut protocol_config = generate_protocol_config(protocol_id);
+		protocol_config.inbound_queue = Some(tx);
+
+		let handler = Self {
+			client,
+			request_receiver,
+			seen_requests: LruCache::new(SEEN_REQUESTS_CACHE_SIZE),
+			requests_per_peer: LruCache::new(RATE_LIMITED_PEERS_CACHE_SIZE),
+		};
+
+		(handler, protocol_config)
+	}
+
+	/// Answer all the requests that are currently queued.
+	pub fn poll(&mut self, cx: &mut Context) {
+		while let Poll::Ready(Some(request)) = self.request_receiver.poll_next_unpin(cx) {
+			let IncomingRequest { peer, payload, pending_response } = request;
+
+			// Dropping `pending_response` refuses the request.
+			if let Err(e) = self.check_request_allowed(&peer, &payload) {
+				log::debug!(
+					target: LOG_TARGET,
+					"Refusing block request from {}: {}",
+					peer, e,
+				);
+				continue
+			}
+
+			match self.handle_request(&payload) {
+				Ok(response) => {
+					// The remote might have stopped waiting for the response in the meantime.
+					let _ = pending_response.send(response);
+				}
+				Err(e) => log::debug!(
+					target: LOG_TARGET,
+					"Failed to handle block request from {}: {}",
+					peer, e,
+				),
+			}
+		}
+	}
+
+	/// Accounts for a new request from `peer` and checks that it is neither repeated too often
+	/// nor sent by a peer that went over its rate limit.
+	fn check_request_allowed(&mut self, peer: &PeerId, payload: &[u8]) -> Result<(), HandleRequestError> {
+		let now = Instant::now();
+		match self.requests_per_peer.get_mut(peer) {
+			Some((period_start, count)) if now.duration_since(*period_start) < RATE_LIMIT_PERIOD => {
+				*count += 1;
+				if *count > MAX_REQUESTS_PER_PEER_PER_PERIOD {
+					return Err(HandleRequestError::RateLimited);
+				}
+			}
+			_ => {
+				self.requests_per_peer.put(peer.clone(), (now, 1));
+			}
+		}
+
+		let key = (peer.clone(), sp_core::hashing::blake2_256(payload));
+		match self.seen_requests.get_mut(&key) {
+			Some(count) => {
+				*count += 1;
+				if *count > MAX_NUMBER_OF_SAME_REQUESTS_PER_PEER {
+					return Err(HandleRequestError::Repeated(*count));
+				}
+			}
+			None => {
+				self.seen_requests.put(key, 1);
+			}
+		}
+
+		Ok(())
+	}
+
+	fn handle_request(&self, payload: &[u8]) -> Result<Vec<u8>, HandleRequestError> {
+		let request = schema::v2::BlockRequest::decode(payload)?;
+		if request.proof_keys.len() > MAX_PROOF_KEYS {
+			return Err(HandleRequestError::TooManyProofKeys(request.proof_keys.len()));
+		}
+
+		let from_block_id = match request.from_block {
+			Some(schema::v2::block_request::FromBlock::Hash(ref h)) =>
+				BlockId::<B>::Hash(Decode::decode(&mut h.as_ref())?),
+			Some(schema::v2::block_request::FromBlock::Number(ref n)) =>
+				BlockId::<B>::Number(Decode::decode(&mut n.as_ref())?),
+			None => return Err(HandleRequestError::MissingFromField),
+		};
+
+		let max_blocks = if request.max_blocks == 0 {
+			MAX_BLOCKS_IN_RESPONSE
+		} else {
+			min(request.max_blocks, MAX_BLOCKS_IN_RESPONSE)
+		};
+
+		let max_response_bytes = if request.max_response_bytes == 0 {
+			MAX_RESPONSE_BYTES
+		} else {
+			min(request.max_response_bytes, MAX_RESPONSE_BYTES)
+		} as usize;
+
+		let direction = if request.direction == schema::v2::Direction::Ascending as i32 {
+			schema::v2::Direction::Ascending
+		} else if request.direction == schema::v2::Direction::Descending as i32 {
+			schema::v2::Direction::Descending
+		} else {
+			return Err(HandleRequestError::InvalidDirection(request.direction));
+		};
+
+		let attributes = BlockAttributes::from_be_u32(request.fields)?;
+		let get_header = attributes.contains(BlockAttributes::HEADER);
+		let get_body = attributes.contains(BlockAttributes::BODY);
+		let get_justification = attributes.contains(BlockAttributes::JUSTIFICATION);
+
+		log::trace!(
+			target: LOG_TARGET,
+			"Handling block request from {:?}, max {} blocks, max {} bytes, {} proof keys",
+			from_block_id,
+			max_blocks,
+			max_response_bytes,
+			request.proof_keys.len(),
+		);
+
+		let mut blocks = Vec::new();
+		let mut block_id = from_block_id;
+		let mut total_body_size = 0;
+		let mut total_size = 0;
+		while let Some(header) = self.client.header(block_id).unwrap_or(None) {
+			if blocks.len() >= max_blocks as usize
+				|| (!blocks.is_empty() && total_body_size > MAX_BODY_BYTES)
+			{
+				break
+			}
+
+			let number = *header.number();
+			let hash = header.hash();
+			let parent_hash = *header.parent_hash();
+
+			// The proof is usually the largest part of a block. Nothing else is read for the
+			// block when the proof alone doesn't fit in the response anymore.
+			let proof = if !request.proof_keys.is_empty() {
+				let mut keys = request.proof_keys.iter().map(|key| &key[..]);
+				match self.client.read_proof(&BlockId::Hash(hash), &mut keys) {
+					Ok(proof) => proof.encode(),
+					Err(e) => {
+						log::trace!(target: LOG_TARGET, "Missing state for block request: {}", e);
+						break;
+					}
+				}
+			} else {
+				Vec::new()
+			};
+			if total_size + proof.len() > max_response_bytes {
+				break
+			}
+
+			let justifications = if get_justification {
+				self.client.justifications(&BlockId::Hash(hash))?
+					.into_iter()
+					.flatten()
+					.map(|(engine_id, justification)| schema::v2::Justification {
+						engine_id: engine_id.to_vec(),
+						justification,
+					})
+					.collect()
+			} else {
+				Vec::new()
+			};
+
+			let body = if get_body {
+				match self.client.block_body(&BlockId::Hash(hash))? {
+					Some(extrinsics) => extrinsics.iter()
+						.map(|extrinsic| extrinsic.encode())
+						.collect(),
+					None => {
+						log::trace!(target: LOG_TARGET, "Missing body for block request.");
+						break;
+					}
+				}
+			} else {
+				Vec::new()
+			};
+
+			let block_data = schema::v2::BlockData {
+				hash: hash.encode(),
+				header: if get_header {
+					header.encode()
+				} else {
+					Vec::new()
+				},
+				body,
+				receipt: Vec::new(),
+				message_queue: Vec::new(),
+				justifications,
+				proof,
+			};
+
+			// Leave some room for the encoding of the field holding the block.
+			let block_size = block_data.encoded_len() + 16;
+			if total_size + block_size > max_response_bytes {
+				break
+			}
+
+			total_size += block_size;
+			total_body_size += block_data.body.iter().map(|b| b.len()).sum::<usize>();
+			blocks.push(block_data);
+
+			match direction {
+				schema::v2::Direction::Ascending => {
+					block_id = BlockId::Number(number + One::one())
+				}
+				schema::v2::Direction::Descending => {
+					if number.is_zero() {
+						break
+					}
+					block_id = BlockId::Hash(parent_hash)
+				}
+			}
+		}
+
+		log::trace!(
+			target: LOG_TARGET,
+			"Sending block response with {} blocks ({} bytes)",
+			blocks.len(),
+			total_size,
+		);
+
+		let response = schema::v2::BlockResponse { blocks };
+		let mut data = Vec::with_capacity(response.encoded_len());
+		response.encode(&mut data)?;
+		Ok(data)
+	}
+}
+
+/// Build the protobuf message of a request for the given blocks.
+pub(crate) fn build_protobuf_block_request<B: BlockT>(
+	request: &message::BlockRequest<B>,
+	max_response_bytes: u64,
+) -> schema::v2::BlockRequest {
+	schema::v2::BlockRequest {
+		fields: request.fields.to_be_u32(),
+		from_block: match request.from {
+			message::FromBlock::Hash(ref h) =>
+				Some(schema::v2::block_request::FromBlock::Hash(h.encode())),
+			message::FromBlock::Number(ref n) =>
+				Some(schema::v2::block_request::FromBlock::Number(n.encode())),
+		},
+		to_block: request.to.as_ref().map(|h| h.encode()).unwrap_or_default(),
+		direction: match request.direction {
+			message::Direction::Ascending => schema::v2::Direction::Ascending as i32,
+			message::Direction::Descending => schema::v2::Direction::Descending as i32,
+		},
+		max_blocks: request.max.unwrap_or(0),
+		max_response_bytes,
+		proof_keys: Vec::new(),
+	}
+}
+
+/// Decode the response to `request` into the blocks it contains.
+pub(crate) fn decode_block_response<B: BlockT>(
+	request: &message::BlockRequest<B>,
+	response: &[u8],
+) -> Result<message::BlockResponse<B>, HandleResponseError> {
+	let response = schema::v2::BlockResponse::decode(response)?;
+
+	let blocks = response.blocks.into_iter().map(|block_data| {
+		Ok(message::BlockData::<B> {
+			hash: Decode::decode(&mut block_data.hash.as_ref())?,
+			header: if !block_data.header.is_empty() {
+				Some(Decode::decode(&mut block_data.header.as_ref())?)
+			} else {
+				None
+			},
+			body: if request.fields.contains(BlockAttributes::BODY) {
+				Some(block_data.body.iter().map(|body| {
+					Decode::decode(&mut body.as_ref())
+				}).collect::<Result<Vec<_>, _>>()?)
+			} else {
+				None
+			},
+			receipt: if !block_data.receipt.is_empty() {
+				Some(block_data.receipt)
+			} else {
+				None
+			},
+			message_queue: if !block_data.message_queue.is_empty() {
+				Some(block_data.message_queue)
+			} else {
+				None
+			},
+			justifications: decode_justifications(block_data.justifications)?,
+		})
+	}).collect::<Result<Vec<_>, codec::Error>>()?;
+
+	Ok(message::BlockResponse { id: request.id, blocks })
+}
+
+/// Builds the justifications of a block from the ones of a response. Only the first
+/// justification of each consensus engine is kept.
+fn decode_justifications(
+	justifications: Vec<schema::v2::Justification>,
+) -> Result<Option<Justifications>, codec::Error> {
+	let mut decoded: Option<Justifications> = None;
+	for justification in justifications {
+		let engine_id = <[u8; 4]>::try_from(&justification.engine_id[..])
+			.map_err(|_| codec::Error::from("Invalid consensus engine id"))?;
+		match decoded.as_mut() {
+			Some(decoded) => {
+				decoded.append((engine_id, justification.justification));
+			},
+			None => decoded = Some(Justifications::from((engine_id, justification.justification))),
+		}
+	}
+	Ok(decoded)
+}
+
+#[derive(Debug, derive_more::Display, derive_more::From)]
+enum HandleRequestError {
+	#[display(fmt = "Failed to decode request: {}.", _0)]
+	DecodeProto(prost::DecodeError),
+	#[display(fmt = "Failed to encode response: {}.", _0)]
+	EncodeProto(prost::EncodeError),
+	#[display(fmt = "Failed to decode block hash or number: {}.", _0)]
+	DecodeScale(codec::Error),
+	#[display(fmt = "Missing `BlockRequest::from_block` field.")]
+	#[from(ignore)]
+	MissingFromField,
+	#[display(fmt = "Invalid `BlockRequest::direction` value: {}.", _0)]
+	#[from(ignore)]
+	InvalidDirection(i32),
+	#[display(fmt = "Proof requested for {} keys.", _0)]
+	#[from(ignore)]
+	TooManyProofKeys(usize),
+	#[display(fmt = "Failed to read the chain: {}.", _0)]
+	Client(sp_blockchain::Error),
+	#[display(fmt = "Same request received {} times.", _0)]
+	#[from(ignore)]
+	Repeated(usize),
+	#[display(fmt = "Too many requests.")]
+	#[from(ignore)]
+	RateLimited,
+}
+
+/// Error while decoding a block response.
+#[derive(Debug, derive_more::Display, derive_more::From)]
+pub(crate) enum HandleResponseError {
+	#[display(fmt = "Failed to decode response: {}.", _0)]
+	DecodeProto(prost::DecodeError),
+	#[display(fmt = "Failed to decode block data: {}.", _0)]
+	DecodeScale(codec::Error),
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use assert_matches::assert_matches;
+	use sc_block_builder::BlockBuilderProvider;
+	use sc_client_api::StorageProof;
+	use sp_consensus::BlockOrigin;
+	use sp_core::storage::well_known_keys;
+	use sp_runtime::traits::BlakeTwo256;
+	use substrate_test_runtime_client::{
+		runtime::Block,
+		ClientBlockImportExt, ClientExt, DefaultTestClientBuilderExt, TestClient,
+		TestClientBuilder, TestClientBuilderExt,
+	};
+
+	/// A handler serving a chain of `blocks` blocks on top of the genesis block.
+	fn handler_with_blocks(blocks: usize) -> (BlockRequestHandler<Block>, Arc<TestClient>) {
+		let mut client = Arc::new(TestClientBuilder::new().build());
+		for _ in 0..blocks {
+			let block = client.new_block(Default::default()).unwrap().build().unwrap().block;
+			client.import(BlockOrigin::Own, block).unwrap();
+		}
+		let (handler, _) = BlockRequestHandler::new(&ProtocolId::from("test"), client.clone());
+		(handler, client)
+	}
+
+	/// A request for `max_blocks` blocks in ascending order starting at block #1.
+	fn request(fields: BlockAttributes, max_blocks: u32) -> schema::v2::BlockRequest {
+		schema::v2::BlockRequest {
+			fields: fields.to_be_u32(),
+			from_block: Some(schema::v2::block_request::FromBlock::Number(1u64.encode())),
+			to_block: Vec::new(),
+			direction: schema::v2::Direction::Ascending as i32,
+			max_blocks,
+			max_response_bytes: 0,
+			proof_keys: Vec::new(),
+		}
+	}
+
+	fn encode(request: &schema::v2::BlockRequest) -> Vec<u8> {
+		let mut payload = Vec::with_capacity(request.encoded_len());
+		request.encode(&mut payload).unwrap();
+		payload
+	}
+
+	fn respond(
+		handler: &BlockRequestHandler<Block>,
+		request: &schema::v2::BlockRequest,
+	) -> schema::v2::BlockResponse {
+		let response = handler.handle_request(&encode(request)).unwrap();
+		schema::v2::BlockResponse::decode(&response[..]).unwrap()
+	}
+
+	#[test]
+	fn repeated_requests_are_refused() {
+		let (mut handler, _) = handler_with_blocks(0);
+		let peer = PeerId::random();
+		let payload = encode(&request(BlockAttributes::HEADER, 1));
+
+		for _ in 0..MAX_NUMBER_OF_SAME_REQUESTS_PER_PEER {
+			assert_matches!(handler.check_request_allowed(&peer, &payload), Ok(()));
+		}
+		assert_matches!(
+			handler.check_request_allowed(&peer, &payload),
+			Err(HandleRequestError::Repeated(count))
+				if count == MAX_NUMBER_OF_SAME_REQUESTS_PER_PEER + 1
+		);
+
+		// The same request from another peer is still answered.
+		assert_matches!(handler.check_request_allowed(&PeerId::random(), &payload), Ok(()));
+	}
+
+	#[test]
+	fn requests_over_the_rate_limit_are_refused() {
+		let (mut handler, _) = handler_with_blocks(0);
+		let peer = PeerId::random();
+
+		for max_blocks in 0..MAX_REQUESTS_PER_PEER_PER_PERIOD {
+			let payload = encode(&request(BlockAttributes::HEADER, max_blocks as u32));
+			assert_matches!(handler.check_request_allowed(&peer, &payload), Ok(()));
+		}
+		let payload = encode(&request(BlockAttributes::BODY, 1));
+		assert_matches!(
+			handler.check_request_allowed(&peer, &payload),
+			Err(HandleRequestError::RateLimited)
+		);
+
+		// Other peers are not affected.
+		assert_matches!(handler.check_request_allowed(&PeerId::random(), &payload), Ok(()));
+	}
+
+	#[test]
+	fn response_stops_at_the_size_limit() {
+		let (handler, _) = handler_with_blocks(10);
+		let fields = BlockAttributes::HEADER | BlockAttributes::BODY;
+		assert_eq!(respond(&handler, &request(fields, 10)).blocks.len(), 10);
+
+		let mut one_block = request(fields, 1);
+		one_block.proof_keys = vec![well_known_keys::CODE.to_vec()];
+		let block_size = respond(&handler, &one_block).encoded_len() as u64;
+
+		// Proofs count against the limit, too.
+		let mut request = request(fields, 10);
+		request.proof_keys = vec![well_known_keys::CODE.to_vec()];
+		request.max_response_bytes = 2 * block_size + block_size / 2;
+		let response = respond(&handler, &request);
+		assert_eq!(response.blocks.len(), 2);
+		assert!(response.encoded_len() as u64 <= request.max_response_bytes);
+	}
+
+	#[test]
+	fn justifications_are_included() {
+		let (handler, client) = handler_with_blocks(3);
+		let justification = (*b"FRNK", vec![1, 2, 3]);
+		client.finalize_block(BlockId::Number(2), Some(justification.clone())).unwrap();
+
+		let fields = BlockAttributes::HEADER | BlockAttributes::JUSTIFICATION;
+		let blocks = respond(&handler, &request(fields, 3)).blocks;
+		assert_eq!(blocks.len(), 3);
+		assert!(blocks[0].justifications.is_empty());
+		assert_eq!(
+			blocks[1].justifications,
+			vec![schema::v2::Justification {
+				engine_id: justification.0.to_vec(),
+				justification: justification.1,
+			}],
+		);
+		assert!(blocks[2].justifications.is_empty());
+
+		// Justifications are only sent when asked for.
+		let blocks = respond(&handler, &request(BlockAttributes::HEADER, 3)).blocks;
+		assert!(blocks.iter().all(|block| block.justifications.is_empty()));
+	}
+
+	#[test]
+	fn proofs_verify_against_the_state_root() {
+		let (handler, _) = handler_with_blocks(2);
+		let keys = vec![well_known_keys::CODE.to_vec(), b"missing".to_vec()];
+		let mut request = request(BlockAttributes::HEADER, 2);
+		request.proof_keys = keys.clone();
+
+		let blocks = respond(&handler, &request).blocks;
+		assert_eq!(blocks.len(), 2);
+		for block in blocks {
+			let header = <Block as BlockT>::Header::decode(&mut &block.header[..]).unwrap();
+			let proof = StorageProof::decode(&mut &block.proof[..]).unwrap();
+			let values = sp_state_machine::read_proof_check::<BlakeTwo256, _>(
+				*header.state_root(),
+				proof,
+				&keys,
+			).unwrap();
+			assert!(values[well_known_keys::CODE].is_some());
+			assert_eq!(values[&b"missing"[..]], None);
+		}
+	}
+
+	#[test]
+	fn too_many_proof_keys_are_refused() {
+		let (handler, _) = handler_with_blocks(1);
+		let mut request = request(BlockAttributes::HEADER, 1);
+		request.proof_keys = (0..=MAX_PROOF_KEYS as u32).map(|key| key.encode()).collect();
+		assert_matches!(
+			handler.handle_request(&encode(&request)),
+			Err(HandleRequestError::TooManyProofKeys(count)) if count == MAX_PROOF_KEYS + 1
+		);
+
+		request.proof_keys.truncate(MAX_PROOF_KEYS);
+		assert!(handler.handle_request(&encode(&request)).is_ok());
+	}
+}
@@ -53,7 +53,8 @@ use libp2p::{
 	}
 };
 use prost::Message;
-use sp_runtime::{generic::BlockId, traits::{Block, Header, One, Zero}};
+use sp_finality_grandpa::GRANDPA_ENGINE_ID;
+use sp_runtime::{generic::BlockId, traits::{Block, Header, One, Zero}, Justifications};
 use std::{
 	cmp::min,
 	collections::{HashMap, VecDeque},
@@ -401,8 +402,11 @@ where
 			let number = *header.number();
 			let hash = header.hash();
 			let parent_hash = *header.parent_hash();
+			// this protocol predates justifications of multiple consensus engines and only
+			// carries the GRANDPA one.
 			let justification = if get_justification {
-				self.chain.justification(&BlockId::Hash(hash))?
+				self.chain.justifications(&BlockId::Hash(hash))?
+					.and_then(|justifications| justifications.into_justification(GRANDPA_ENGINE_ID))
 			} else {
 				None
 			};
@@ -648,10 +652,10 @@ where
 						} else {
 							None
 						},
-						justification: if !block_data.justification.is_empty() {
-							Some(block_data.justification)
+						justifications: if !block_data.justification.is_empty() {
+							Some(Justifications::from((GRANDPA_ENGINE_ID, block_data.justification)))
 						} else if block_data.is_empty_justification {
-							Some(Vec::new())
+							Some(Justifications::from((GRANDPA_ENGINE_ID, Vec::new())))
 						} else {
 							None
 						},
@@ -25,13 +25,14 @@ pub use crate::chain::Client;
 pub use crate::on_demand_layer::{AlwaysBadChecker, OnDemand};
 pub use crate::request_responses::{IncomingRequest, ProtocolConfig as RequestResponseConfig};
 pub use libp2p::{identity, core::PublicKey, wasm_ext::ExtTransport, build_multiaddr};
+pub use ip_network::IpNetwork;
 
 // Note: this re-export shouldn't be part of the public API of the crate and will be removed in
 // the future.
 #[doc(hidden)]
 pub use crate::protocol::ProtocolConfig;
 
-use crate::ExHashT;
+use crate::{ExHashT, warp_request_handler::WarpSyncProvider};
 
 use core::{fmt, iter};
 use futures::future;
@@ -49,6 +50,7 @@ use std::{
 	fs,
 	io::{self, Write},
 	net::Ipv4Addr,
+	num::NonZeroU64,
 	path::{Path, PathBuf},
 	str,
 	sync::Arc,
@@ -95,6 +97,10 @@ pub struct Params<B: BlockT, H: ExHashT> {
 
 	/// Registry for recording prometheus metrics to.
 	pub metrics_registry: Option<Registry>,
+
+	/// Optional warp sync provider. If `Some`, warp sync proofs are served to other nodes and
+	/// `SyncMode::Warp` can be used.
+	pub warp_sync: Option<Arc<dyn WarpSyncProvider<B>>>,
 }
 
 /// Role of the local node.
@@ -370,8 +376,8 @@ pub struct NetworkConfiguration {
 	pub boot_nodes: Vec<MultiaddrWithPeerId>,
 	/// The node key configuration, which determines the node's network identity keypair.
 	pub node_key: NodeKeyConfig,
-	/// List of names of notifications protocols that the node supports.
-	pub notifications_protocols: Vec<Cow<'static, str>>,
+	/// List of notifications protocols that the node supports.
+	pub notifications_protocols: Vec<NotificationsProtocolConfig>,
 	/// List of request-response protocols that the node supports.
 	pub request_response_protocols: Vec<RequestResponseConfig>,
 	/// Maximum allowed number of incoming connections.
@@ -390,11 +396,26 @@ pub struct NetworkConfiguration {
 	pub transport: TransportConfig,
 	/// Maximum number of peers to ask the same blocks in parallel.
 	pub max_parallel_downloads: u32,
+	/// Initial syncing mode.
+	pub sync_mode: SyncMode,
+	/// Maximum number of blocks per second downloaded in the background to fill the history
+	/// missing below a block imported with its state, e.g. after a warp sync. `0` disables the
+	/// download.
+	pub block_history_rate: u32,
 	/// Should we insert non-global addresses into the DHT?
 	pub allow_non_globals_in_dht: bool,
 	/// Require iterative Kademlia DHT queries to use disjoint paths for increased resiliency in the
 	/// presence of potentially adversarial nodes.
 	pub kademlia_disjoint_query_paths: bool,
+	/// Limits on the bandwidth used by the node.
+	pub bandwidth_limits: BandwidthLimits,
+	/// Peers that are permanently banned. We never connect to them and always refuse their
+	/// connections.
+	pub banned_peers: Vec<PeerId>,
+	/// IP ranges that are permanently banned. Connections from and to addresses in these ranges
+	/// are refused by the transport. More ranges can be banned at runtime with
+	/// [`NetworkService::ban_ip_range`](crate::NetworkService::ban_ip_range).
+	pub banned_ip_ranges: Vec<IpNetwork>,
 }
 
 impl NetworkConfiguration {
@@ -425,8 +446,13 @@ impl NetworkConfiguration {
 				wasm_external_transport: None,
 			},
 			max_parallel_downloads: 5,
+			sync_mode: SyncMode::Full,
+			block_history_rate: 256,
 			allow_non_globals_in_dht: false,
 			kademlia_disjoint_query_paths: false,
+			bandwidth_limits: BandwidthLimits::default(),
+			banned_peers: Vec::new(),
+			banned_ip_ranges: Vec::new(),
 		}
 	}
 
@@ -498,6 +524,58 @@ pub enum TransportConfig {
 	MemoryOnly,
 }
 
+/// Configuration of a notifications protocol.
+#[derive(Clone, Debug)]
+pub struct NotificationsProtocolConfig {
+	/// Name of the protocol.
+	pub name: Cow<'static, str>,
+	/// If `Some`, the protocol doesn't share the peers of the main set and instead opens
+	/// substreams with peers of its own set, configured with this value.
+	///
+	/// If `None`, the protocol is opened with the peers we are syncing with.
+	pub set_config: Option<SetConfig>,
+}
+
+impl From<Cow<'static, str>> for NotificationsProtocolConfig {
+	fn from(name: Cow<'static, str>) -> Self {
+		NotificationsProtocolConfig {
+			name,
+			set_config: None,
+		}
+	}
+}
+
+impl From<&'static str> for NotificationsProtocolConfig {
+	fn from(name: &'static str) -> Self {
+		NotificationsProtocolConfig::from(Cow::Borrowed(name))
+	}
+}
+
+/// Configuration of a set of peers managed independently from the main one.
+#[derive(Clone, Debug)]
+pub struct SetConfig {
+	/// Maximum allowed number of incoming substreams related to this set.
+	pub in_peers: u32,
+	/// Number of outgoing substreams related to this set that we're trying to maintain.
+	pub out_peers: u32,
+	/// List of reserved node addresses.
+	pub reserved_nodes: Vec<MultiaddrWithPeerId>,
+	/// Whether nodes that aren't in [`SetConfig::reserved_nodes`] are accepted or automatically
+	/// refused.
+	pub non_reserved_mode: NonReservedPeerMode,
+}
+
+impl Default for SetConfig {
+	fn default() -> Self {
+		SetConfig {
+			in_peers: 25,
+			out_peers: 75,
+			reserved_nodes: Vec::new(),
+			non_reserved_mode: NonReservedPeerMode::Accept,
+		}
+	}
+}
+
 /// The policy for connections to non-reserved peers.
 #[derive(Clone, Debug, PartialEq, Eq)]
 pub enum NonReservedPeerMode {
@@ -518,6 +596,43 @@ impl NonReservedPeerMode {
 	}
 }
 
+/// Sync operation mode.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum SyncMode {
+	/// Full block download and verification.
+	Full,
+	/// Download block headers up to the latest finalized block, then download the state of that
+	/// block instead of executing all blocks. Bodies and state of earlier blocks are not available.
+	Fast,
+	/// Download a proof of the finality authority set changes from genesis up to a recently
+	/// finalized block, then download the state of that block. Requires a warp sync provider.
+	/// Earlier blocks are not downloaded.
+	Warp,
+}
+
+impl Default for SyncMode {
+	fn default() -> Self {
+		SyncMode::Full
+	}
+}
+
+/// Limits on the bandwidth used by the node, in bytes per second. `None` means unlimited.
+///
+/// Only the data of the substreams is accounted for, not the overhead of the encryption and
+/// multiplexing layers. Block announces and block requests can use a share of the limits that
+/// the other protocols can't, so that a node under load keeps up with the chain.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct BandwidthLimits {
+	/// Maximum rate at which data is received from all the peers combined.
+	pub max_download_rate: Option<NonZeroU64>,
+	/// Maximum rate at which data is sent to all the peers combined.
+	pub max_upload_rate: Option<NonZeroU64>,
+	/// Maximum rate at which data is received from each individual peer.
+	pub max_download_rate_per_peer: Option<NonZeroU64>,
+	/// Maximum rate at which data is sent to each individual peer.
+	pub max_upload_rate_per_peer: Option<NonZeroU64>,
+}
+
 /// The configuration of a node's secret key, describing the type of key
 /// and how it is obtained. A node's identity keypair is the result of
 /// the evaluation of the node key configuration.
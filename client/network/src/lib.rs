@@ -129,10 +129,23 @@
 //! requests for information about blocks. Each request is the encoding of a `BlockRequest` and
 //! each response is the encoding of a `BlockResponse`, as defined in the `api.v1.proto` file in
 //! this source tree.
+//! - **`/<protocol-id>/sync/3`** is the second version of the above. Each request is the encoding
+//! of a `BlockRequest` and each response is the encoding of a `BlockResponse`, as defined in the
+//! `api.v2.proto` file in this source tree. Blocks carry all their justifications, optionally a
+//! storage proof of the requested keys, and the response doesn't exceed the size the requester
+//! asked for. Nodes fall back to `/<protocol-id>/sync/2` when the remote doesn't support it.
 //! - **`/<protocol-id>/light/2`** is a request-response protocol (see below) that lets one perform
 //! light-client-related requests for information about the state. Each request is the encoding of
 //! a `light::Request` and each response is the encoding of a `light::Response`, as defined in the
 //! `light.v1.proto` file in this source tree.
+//! - **`/<protocol-id>/state/1`** is a request-response protocol (see below) that lets one
+//! download the state of a block in ranges of key-value pairs, each proven against the state
+//! root of the block. Each request is the encoding of a `StateRequest` and each response is the
+//! encoding of a `StateResponse`, as defined in the `api.v1.proto` file in this source tree.
+//! - **`/<protocol-id>/sync/warp`** is a request-response protocol (see below) that lets one
+//! download a proof of the finality of a recent block, starting from the genesis authorities.
+//! Each request is the SCALE encoding of a `WarpProofRequest` and each response is an opaque
+//! proof generated by the finality gadget, see the `warp_request_handler` module.
 //! - **`/<protocol-id>/transactions/1`** is a notifications protocol (see below) where
 //! transactions are pushed to other nodes. The handshake is empty on both sides. The message
 //! format is a SCALE-encoded list of transactions, where each transaction is an opaque list of
@@ -155,6 +168,12 @@
 //!
 //! For more information about the PSM, see the *sc-peerset* crate.
 //!
+//! The PSM manages several independent sets of peers. The first one contains the nodes we sync
+//! with, and is the one the unique substream is opened with. Notifications protocols can be given
+//! a set of their own through `NotificationsProtocolConfig::set_config`, in which case their
+//! substreams are opened with the nodes of that set, which have their own slots and reserved
+//! nodes, instead of with the nodes we sync with.
+//!
 //! Note that at the moment there is no mechanism in place to solve the issues that arise where the
 //! two sides of a connection open the unique substream simultaneously. In order to not run into
 //! issues, only the dialer of a connection is allowed to open the unique substream. When the
@@ -246,16 +265,20 @@
 //!
 
 mod behaviour;
+mod block_request_handler;
 mod block_requests;
 mod chain;
 mod peer_info;
 mod discovery;
 mod light_client_handler;
 mod on_demand_layer;
+mod peer_store;
 mod protocol;
 mod request_responses;
 mod schema;
 mod service;
+mod state_request_handler;
+mod traffic;
 mod transport;
 mod utils;
 
@@ -263,9 +286,11 @@ pub mod config;
 pub mod error;
 pub mod gossip;
 pub mod network_state;
+pub mod warp_request_handler;
 
 #[doc(inline)]
 pub use libp2p::{multiaddr, Multiaddr, PeerId};
+pub use peer_store::PeerRecord;
 pub use protocol::{event::{DhtEvent, Event, ObservedRole}, sync::SyncState, PeerInfo};
 pub use service::{
 	NetworkService, NetworkWorker, RequestFailure, OutboundFailure, NotificationSender,
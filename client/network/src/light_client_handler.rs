@@ -1485,11 +1485,15 @@ mod tests {
 
 	fn peerset() -> (sc_peerset::Peerset, sc_peerset::PeersetHandle) {
 		let cfg = sc_peerset::PeersetConfig {
-			in_peers: 128,
-			out_peers: 128,
-			bootnodes: Vec::new(),
-			reserved_only: false,
+			sets: vec![sc_peerset::SetConfig {
+				in_peers: 128,
+				out_peers: 128,
+				bootnodes: Vec::new(),
+				reserved_nodes: Default::default(),
+				reserved_only: false,
+			}],
 			priority_groups: Vec::new(),
+			banned_peers: Default::default(),
 		};
 		sc_peerset::Peerset::from_config(cfg)
 	}
@@ -0,0 +1,422 @@
+// This file is part of Substrate.
+
+// Copyright (C) 2021 Parity Technologies (UK) Ltd.
+// SPDX-License-Identifier: GPL-3.0-or-later WITH Classpath-exception-2.0
+
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with this program. If not, see <https://www.gnu.org/licenses/>.
+
+//! Persistent store of the peers we know about.
+//!
+//! The store is saved in the network configuration directory and loaded when the network starts.
+//! The addresses it contains are used as additional discovery sources, so that the node doesn't
+//! have to rediscover the network from the bootnodes, and the reputations it contains are
+//! restored, so that the peers we have banned stay banned across restarts. The peers banned
+//! permanently with [`NetworkService::ban_peer`](crate::NetworkService::ban_peer) are saved, too.
+//!
+//! The store is written to the disk by a background thread, so that the network worker is never
+//! blocked on the file system.
+
+use libp2p::{Multiaddr, PeerId};
+use serde::{Deserialize, Serialize};
+use std::{
+	collections::{HashMap, HashSet},
+	fs, io,
+	path::{Path, PathBuf},
+	sync::mpsc,
+	thread,
+	time::{Duration, SystemTime, UNIX_EPOCH},
+};
+
+/// Name of the file, within the network configuration directory, the store is saved to.
+const FILE_NAME: &str = "peers.json";
+
+/// Interval between two flushes of the store to the disk.
+pub const FLUSH_INTERVAL: Duration = Duration::from_secs(60);
+
+/// Maximum number of peers that aren't banned in the store. The peers we haven't seen for the
+/// longest time are removed first.
+const MAX_PEERS: usize = 1024;
+
+/// Maximum number of banned peers in the store. The peers whose ban ends first are removed first.
+///
+/// Kept separate from [`MAX_PEERS`], so that a remote creating identities that get banned can
+/// neither grow the store without limit nor push out the peers we want to reconnect to.
+const MAX_BANNED_PEERS: usize = 256;
+
+/// Maximum number of addresses saved for each peer.
+const MAX_ADDRESSES_PER_PEER: usize = 8;
+
+/// Peers that haven't been seen for that long and aren't banned are removed from the store.
+const FORGET_AFTER: Duration = Duration::from_secs(7 * 24 * 3600);
+
+/// Information about a peer in the store.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PeerRecord {
+	/// Addresses the peer is known to be reachable at.
+	pub addresses: Vec<Multiaddr>,
+	/// Last time we were connected to the peer, in seconds since the UNIX epoch.
+	pub last_seen: u64,
+	/// Reputation of the peer when the store was last updated.
+	pub reputation: i32,
+	/// Time until which the peer is banned, in seconds since the UNIX epoch.
+	pub banned_until: Option<u64>,
+}
+
+impl PeerRecord {
+	/// Returns true if the peer is still banned at the given time.
+	pub fn is_banned(&self, now: u64) -> bool {
+		self.banned_until.map_or(false, |until| until > now)
+	}
+}
+
+/// Content of the file.
+#[derive(Debug, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct StoreFile {
+	/// When the store was last updated, in seconds since the UNIX epoch.
+	updated_at: u64,
+	/// Peers, by base58-encoded `PeerId`.
+	peers: HashMap<String, PeerRecord>,
+	/// Base58-encoded `PeerId`s of the peers that are permanently banned.
+	#[serde(default)]
+	banned_peers: Vec<String>,
+}
+
+/// Store of peers, persisted in the network configuration directory.
+#[derive(Debug)]
+pub struct PeerStore {
+	/// Path of the file the store is saved to.
+	path: PathBuf,
+	/// When the reputations of the store were last updated, in seconds since the UNIX epoch.
+	updated_at: u64,
+	peers: HashMap<PeerId, PeerRecord>,
+	/// Peers that are permanently banned. Not subject to [`MAX_BANNED_PEERS`] because only the
+	/// local node operator can ban peers permanently.
+	banned_peers: HashSet<PeerId>,
+	/// Channel to the thread writing the store to the disk, and handle of that thread. Started on
+	/// the first flush. The thread stops once the store is dropped and the last content it was
+	/// sent is written.
+	writer: Option<(mpsc::Sender<Vec<u8>>, thread::JoinHandle<()>)>,
+}
+
+impl PeerStore {
+	/// Loads the store saved in the given network configuration directory. Starts with an empty
+	/// store if there isn't any or if it can't be read.
+	pub fn load(net_config_path: &Path) -> Self {
+		let path = net_config_path.join(FILE_NAME);
+		let file = match fs::read(&path) {
+			Ok(content) => serde_json::from_slice::<StoreFile>(&content).unwrap_or_else(|err| {
+				log::warn!(target: "sub-libp2p", "Ignoring corrupted peer store {:?}: {}", path, err);
+				StoreFile::default()
+			}),
+			Err(err) if err.kind() == io::ErrorKind::NotFound => StoreFile::default(),
+			Err(err) => {
+				log::warn!(target: "sub-libp2p", "Failed to read peer store {:?}: {}", path, err);
+				StoreFile::default()
+			}
+		};
+
+		let peers = file.peers.into_iter()
+			.filter_map(|(peer_id, record)| Some((peer_id.parse().ok()?, record)))
+			.collect::<HashMap<_, _>>();
+		let banned_peers = file.banned_peers.into_iter()
+			.filter_map(|peer_id| peer_id.parse().ok())
+			.collect::<HashSet<_>>();
+
+		log::debug!(
+			target: "sub-libp2p",
+			"Loaded {} peers and {} permanently banned peers from {:?}",
+			peers.len(), banned_peers.len(), path,
+		);
+
+		PeerStore {
+			path,
+			updated_at: file.updated_at,
+			peers,
+			banned_peers,
+			writer: None,
+		}
+	}
+
+	/// Returns all the peers in the store.
+	pub fn peers(&self) -> impl Iterator<Item = (&PeerId, &PeerRecord)> {
+		self.peers.iter()
+	}
+
+	/// Returns the peers that are permanently banned.
+	pub fn banned_peers(&self) -> impl Iterator<Item = &PeerId> {
+		self.banned_peers.iter()
+	}
+
+	/// Replaces the peers that are permanently banned.
+	pub fn set_banned_peers(&mut self, banned_peers: impl IntoIterator<Item = PeerId>) {
+		self.banned_peers = banned_peers.into_iter().collect();
+	}
+
+	/// Returns the addresses of the peers that aren't banned, to be used as discovery sources.
+	pub fn known_addresses(&self) -> impl Iterator<Item = (&PeerId, &Multiaddr)> {
+		let now = unix_time();
+		self.peers.iter()
+			.filter(move |(_, record)| !record.is_banned(now))
+			.flat_map(|(peer_id, record)| record.addresses.iter().map(move |addr| (peer_id, addr)))
+	}
+
+	/// Returns the reputations to restore in the peerset, taking into account the time elapsed
+	/// since they were saved.
+	pub fn reputations(&self) -> impl Iterator<Item = (&PeerId, i32)> {
+		let elapsed = Duration::from_secs(unix_time().saturating_sub(self.updated_at));
+		self.peers.iter()
+			.map(move |(peer_id, record)| {
+				(peer_id, sc_peerset::decayed_reputation(record.reputation, elapsed))
+			})
+			.filter(|(_, reputation)| *reputation != 0)
+	}
+
+	/// Reports that we are or have just been connected to the given peer. If we have dialed it,
+	/// `address` is the address we have reached it at.
+	pub fn on_connected(&mut self, peer_id: &PeerId, address: Option<&Multiaddr>) {
+		let record = self.peers.entry(peer_id.clone()).or_insert_with(|| PeerRecord {
+			addresses: Vec::new(),
+			last_seen: 0,
+			reputation: 0,
+			banned_until: None,
+		});
+
+		record.last_seen = unix_time();
+		if let Some(address) = address {
+			if !record.addresses.contains(address) {
+				record.addresses.insert(0, address.clone());
+				record.addresses.truncate(MAX_ADDRESSES_PER_PEER);
+			}
+		}
+	}
+
+	/// Adds addresses to the peers of the store. Addresses of peers that aren't in the store are
+	/// ignored.
+	pub fn add_addresses(&mut self, peer_id: &PeerId, addresses: impl IntoIterator<Item = Multiaddr>) {
+		if let Some(record) = self.peers.get_mut(peer_id) {
+			for address in addresses {
+				if record.addresses.len() >= MAX_ADDRESSES_PER_PEER {
+					break;
+				}
+				if !record.addresses.contains(&address) {
+					record.addresses.push(address);
+				}
+			}
+		}
+	}
+
+	/// Updates the reputations of the peers with the ones of the peerset. Peers that aren't in
+	/// the list have a reputation of zero. Banned peers that aren't in the store are added.
+	pub fn set_reputations(&mut self, reputations: impl IntoIterator<Item = (PeerId, i32)>) {
+		let now = unix_time();
+		let mut reputations = reputations.into_iter().collect::<HashMap<_, _>>();
+
+		for (peer_id, record) in self.peers.iter_mut() {
+			record.reputation = reputations.remove(peer_id).unwrap_or(0);
+			record.banned_until = ban_end(record.reputation, now);
+		}
+
+		for (peer_id, reputation) in reputations {
+			if let Some(banned_until) = ban_end(reputation, now) {
+				self.peers.insert(peer_id, PeerRecord {
+					addresses: Vec::new(),
+					last_seen: 0,
+					reputation,
+					banned_until: Some(banned_until),
+				});
+			}
+		}
+
+		self.updated_at = now;
+		self.prune();
+	}
+
+	/// Removes the peers that aren't worth keeping and sends the store to the background thread
+	/// writing it to the disk. Errors while writing are logged by that thread.
+	pub fn flush(&mut self) -> io::Result<()> {
+		self.prune();
+
+		let file = StoreFile {
+			updated_at: self.updated_at,
+			peers: self.peers.iter()
+				.map(|(peer_id, record)| (peer_id.to_base58(), record.clone()))
+				.collect(),
+			banned_peers: self.banned_peers.iter().map(|peer_id| peer_id.to_base58()).collect(),
+		};
+		let content = serde_json::to_vec(&file)
+			.map_err(|err| io::Error::new(io::ErrorKind::Other, err))?;
+
+		if self.writer.is_none() {
+			let (tx, rx) = mpsc::channel();
+			let path = self.path.clone();
+			let handle = thread::Builder::new()
+				.name("peer-store-writer".into())
+				.spawn(move || write_loop(&path, rx))?;
+			self.writer = Some((tx, handle));
+		}
+
+		let sent = self.writer.as_ref().map_or(false, |(tx, _)| tx.send(content).is_ok());
+		if !sent {
+			self.writer = None;
+			return Err(io::Error::new(io::ErrorKind::Other, "Peer store writer has stopped"));
+		}
+		Ok(())
+	}
+
+	/// Removes the peers we can't connect to and the ones we haven't seen for a long time, and
+	/// enforces [`MAX_PEERS`] and [`MAX_BANNED_PEERS`].
+	fn prune(&mut self) {
+		let now = unix_time();
+		let forget_before = now.saturating_sub(FORGET_AFTER.as_secs());
+		self.peers.retain(|_, record| {
+			record.is_banned(now) || (!record.addresses.is_empty() && record.last_seen >= forget_before)
+		});
+
+		// Sort keys of the peers to remove first.
+		let mut not_banned = Vec::new();
+		let mut banned = Vec::new();
+		for (peer_id, record) in self.peers.iter() {
+			if record.is_banned(now) {
+				banned.push((record.banned_until.unwrap_or(0), peer_id.clone()));
+			} else {
+				not_banned.push((record.last_seen, peer_id.clone()));
+			}
+		}
+
+		for (mut peers, max) in vec![(not_banned, MAX_PEERS), (banned, MAX_BANNED_PEERS)] {
+			if peers.len() > max {
+				peers.sort_unstable_by(|a, b| a.0.cmp(&b.0));
+				let excess = peers.len() - max;
+				for (_, peer_id) in peers.into_iter().take(excess) {
+					self.peers.remove(&peer_id);
+				}
+			}
+		}
+	}
+}
+
+/// Writes the contents received from `rx` to `path` until the sender is dropped. Only the latest
+/// of the contents waiting in the channel is written.
+fn write_loop(path: &Path, rx: mpsc::Receiver<Vec<u8>>) {
+	while let Ok(content) = rx.recv() {
+		let content = rx.try_iter().last().unwrap_or(content);
+
+		// Write to a temporary file first, so that the store isn't lost if we are interrupted.
+		let tmp_path = path.with_extension("json.tmp");
+		let result = fs::write(&tmp_path, content).and_then(|()| fs::rename(&tmp_path, path));
+		if let Err(err) = result {
+			log::warn!(target: "sub-libp2p", "Failed to save the peer store {:?}: {}", path, err);
+		}
+	}
+}
+
+/// Returns the time, in seconds since the UNIX epoch, until which a peer with the given
+/// reputation is banned.
+fn ban_end(reputation: i32, now: u64) -> Option<u64> {
+	sc_peerset::ban_duration(reputation).map(|duration| now + duration.as_secs())
+}
+
+/// Returns the number of seconds since the UNIX epoch.
+fn unix_time() -> u64 {
+	SystemTime::now().duration_since(UNIX_EPOCH).map(|d| d.as_secs()).unwrap_or(0)
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	fn tempdir() -> tempfile::TempDir {
+		tempfile::Builder::new().prefix("peer-store").tempdir().unwrap()
+	}
+
+	/// Flushes the store and waits until the writer thread has written it.
+	fn flush_and_wait(store: &mut PeerStore) {
+		store.flush().unwrap();
+		let (tx, handle) = store.writer.take().unwrap();
+		drop(tx);
+		handle.join().unwrap();
+	}
+
+	#[test]
+	fn store_survives_restart() {
+		let dir = tempdir();
+		let peer = PeerId::random();
+		let banned = PeerId::random();
+		let address: Multiaddr = "/ip4/127.0.0.1/tcp/30333".parse().unwrap();
+
+		let mut store = PeerStore::load(dir.path());
+		assert_eq!(store.peers().count(), 0);
+		store.on_connected(&peer, Some(&address));
+		store.set_reputations(vec![(peer.clone(), 100), (banned.clone(), i32::min_value())]);
+		store.set_banned_peers(vec![banned.clone()]);
+		flush_and_wait(&mut store);
+
+		let store = PeerStore::load(dir.path());
+		let records = store.peers().collect::<HashMap<_, _>>();
+		assert_eq!(records.len(), 2);
+		assert_eq!(records[&peer].addresses, vec![address.clone()]);
+		assert_eq!(records[&peer].reputation, 100);
+		assert_eq!(records[&peer].banned_until, None);
+		assert!(records[&banned].is_banned(unix_time()));
+
+		assert_eq!(store.known_addresses().collect::<Vec<_>>(), vec![(&peer, &address)]);
+		let reputations = store.reputations().collect::<HashMap<_, _>>();
+		assert!(reputations[&banned] < 0);
+		assert_eq!(store.banned_peers().collect::<Vec<_>>(), vec![&banned]);
+	}
+
+	#[test]
+	fn prune_removes_unreachable_and_old_peers() {
+		let dir = tempdir();
+		let address: Multiaddr = "/ip4/127.0.0.1/tcp/30333".parse().unwrap();
+		let recent = PeerId::random();
+		let old = PeerId::random();
+		let no_address = PeerId::random();
+
+		let mut store = PeerStore::load(dir.path());
+		store.on_connected(&recent, Some(&address));
+		store.on_connected(&old, Some(&address));
+		store.peers.get_mut(&old).unwrap().last_seen -= FORGET_AFTER.as_secs() + 1;
+		store.on_connected(&no_address, None);
+		store.prune();
+
+		assert_eq!(store.peers().map(|(p, _)| p.clone()).collect::<Vec<_>>(), vec![recent]);
+	}
+
+	#[test]
+	fn banned_peers_are_bounded() {
+		let dir = tempdir();
+		let address: Multiaddr = "/ip4/127.0.0.1/tcp/30333".parse().unwrap();
+		let peer = PeerId::random();
+
+		let mut store = PeerStore::load(dir.path());
+		store.on_connected(&peer, Some(&address));
+		let banned = (0..MAX_BANNED_PEERS + 10).map(|_| (PeerId::random(), i32::min_value()));
+		store.set_reputations(banned.chain(std::iter::once((peer.clone(), 0))));
+
+		let now = unix_time();
+		assert_eq!(store.peers().count(), MAX_BANNED_PEERS + 1);
+		assert_eq!(store.peers().filter(|(_, record)| record.is_banned(now)).count(), MAX_BANNED_PEERS);
+		assert!(store.peers().any(|(peer_id, _)| *peer_id == peer));
+	}
+
+	#[test]
+	fn corrupted_store_is_ignored() {
+		let dir = tempdir();
+		fs::write(dir.path().join(FILE_NAME), b"not json").unwrap();
+		assert_eq!(PeerStore::load(dir.path()).peers().count(), 0);
+	}
+}
@@ -19,9 +19,11 @@
 use crate::{
 	ExHashT,
 	chain::Client,
-	config::{ProtocolId, TransactionPool, TransactionImportFuture, TransactionImport},
+	config::{self, ProtocolId, TransactionPool, TransactionImportFuture, TransactionImport},
 	error,
+	traffic::ProtocolsTraffic,
 	utils::{interval, LruHashSet},
+	warp_request_handler::WarpSyncProvider,
 };
 
 use bytes::{Bytes, BytesMut};
@@ -37,7 +39,7 @@ use sp_consensus::{
 	import_queue::{BlockImportResult, BlockImportError, IncomingBlock, Origin}
 };
 use codec::{Decode, DecodeAll, Encode};
-use sp_runtime::{generic::BlockId, Justification};
+use sp_runtime::{generic::BlockId, Justifications};
 use sp_runtime::traits::{
 	Block as BlockT, Header as HeaderT, NumberFor, Zero, CheckedSub
 };
@@ -91,6 +93,11 @@ pub(crate) const MIN_VERSION: u32 = 3;
 /// and disconnect to free connection slot.
 const LIGHT_MAXIMAL_BLOCKS_DIFFERENCE: u64 = 8192;
 
+/// Identifier of the peerset of the nodes we sync with and that the legacy substream, the block
+/// announces, the transactions and the notifications protocols without a set of their own are
+/// opened with.
+pub(crate) const HARDCODED_PEERSETS_SYNC: sc_peerset::SetId = sc_peerset::SetId::from(0);
+
 mod rep {
 	use sc_peerset::ReputationChange as Rep;
 	/// Reputation change when a peer doesn't respond in time to our messages.
@@ -220,6 +227,12 @@ pub struct Protocol<B: BlockT, H: ExHashT> {
 	behaviour: GenericProto,
 	/// List of notifications protocols that have been registered.
 	notification_protocols: Vec<Cow<'static, str>>,
+	/// Notifications protocols that have a peers set of their own. The set of the protocol at
+	/// index `n` is the one with identifier `n + 1`, the set `0` being the one we sync with.
+	extra_sets: Vec<Cow<'static, str>>,
+	/// Number of bytes received on each notifications protocol, and sent on the ones handled
+	/// by `Protocol`.
+	protocols_traffic: Arc<ProtocolsTraffic>,
 	/// For each protocol name, the legacy equivalent.
 	legacy_equiv_by_name: HashMap<Cow<'static, str>, Fallback>,
 	/// Name of the protocol used for transactions.
@@ -282,6 +295,10 @@ pub struct ProtocolConfig {
 	pub roles: Roles,
 	/// Maximum number of peers to ask the same blocks in parallel.
 	pub max_parallel_downloads: u32,
+	/// Initial syncing mode.
+	pub sync_mode: config::SyncMode,
+	/// Maximum number of history blocks downloaded per second.
+	pub block_history_rate: u32,
 }
 
 impl Default for ProtocolConfig {
@@ -289,6 +306,8 @@ impl Default for ProtocolConfig {
 		ProtocolConfig {
 			roles: Roles::FULL,
 			max_parallel_downloads: 5,
+			sync_mode: config::SyncMode::Full,
+			block_history_rate: 0,
 		}
 	}
 }
@@ -354,17 +373,23 @@ impl<B: BlockT, H: ExHashT> Protocol<B, H> {
 		transaction_pool: Arc<dyn TransactionPool<H, B>>,
 		protocol_id: ProtocolId,
 		peerset_config: sc_peerset::PeersetConfig,
+		extra_sets: Vec<Cow<'static, str>>,
 		block_announce_validator: Box<dyn BlockAnnounceValidator<B> + Send>,
 		metrics_registry: Option<&Registry>,
+		protocols_traffic: Arc<ProtocolsTraffic>,
 		boot_node_ids: Arc<HashSet<PeerId>>,
+		warp_sync_provider: Option<Arc<dyn WarpSyncProvider<B>>>,
 	) -> error::Result<(Protocol<B, H>, sc_peerset::PeersetHandle)> {
 		let info = chain.info();
 		let sync = ChainSync::new(
+			config.sync_mode,
 			config.roles,
 			chain.clone(),
 			&info,
 			block_announce_validator,
 			config.max_parallel_downloads,
+			warp_sync_provider,
+			config.block_history_rate,
 		);
 
 		let important_peers = {
@@ -372,6 +397,9 @@ impl<B: BlockT, H: ExHashT> Protocol<B, H> {
 			for reserved in peerset_config.priority_groups.iter().flat_map(|(_, l)| l.iter()) {
 				imp_p.insert(reserved.clone());
 			}
+			for reserved in peerset_config.sets.iter().flat_map(|set| set.reserved_nodes.iter()) {
+				imp_p.insert(reserved.clone());
+			}
 			imp_p.shrink_to_fit();
 			imp_p
 		};
@@ -398,6 +426,10 @@ impl<B: BlockT, H: ExHashT> Protocol<B, H> {
 		});
 		legacy_equiv_by_name.insert(block_announces_protocol.clone(), Fallback::BlockAnnounce);
 
+		for protocol in &extra_sets {
+			legacy_equiv_by_name.insert(protocol.clone(), Fallback::Consensus);
+		}
+
 		let behaviour = {
 			let versions = &((MIN_VERSION as u8)..=(CURRENT_VERSION as u8)).collect::<Vec<u8>>();
 			let block_announces_handshake = BlockAnnouncesHandshake::build(&config, &chain).encode();
@@ -411,6 +443,7 @@ impl<B: BlockT, H: ExHashT> Protocol<B, H> {
 				// one carrying the handshake reported in the `CustomProtocolOpen` event.
 				iter::once((block_announces_protocol.clone(), block_announces_handshake))
 					.chain(iter::once((transactions_protocol.clone(), vec![]))),
+				extra_sets.iter().map(|protocol| (protocol.clone(), config.roles.encode())),
 			)
 		};
 
@@ -433,6 +466,8 @@ impl<B: BlockT, H: ExHashT> Protocol<B, H> {
 			peerset_handle: peerset_handle.clone(),
 			behaviour,
 			notification_protocols: Vec::new(),
+			extra_sets,
+			protocols_traffic,
 			legacy_equiv_by_name,
 			transactions_protocol,
 			block_announces_protocol,
@@ -447,19 +482,24 @@ impl<B: BlockT, H: ExHashT> Protocol<B, H> {
 		Ok((protocol, peerset_handle))
 	}
 
+	/// Returns the name of the block announces protocol on the wire.
+	pub fn block_announces_protocol(&self) -> &str {
+		&self.block_announces_protocol
+	}
+
 	/// Returns the list of all the peers we have an open channel to.
 	pub fn open_peers(&self) -> impl Iterator<Item = &PeerId> {
-		self.behaviour.open_peers()
+		self.behaviour.open_peers(HARDCODED_PEERSETS_SYNC)
 	}
 
 	/// Returns true if we have a channel open with this node.
 	pub fn is_open(&self, peer_id: &PeerId) -> bool {
-		self.behaviour.is_open(peer_id)
+		self.behaviour.is_open(peer_id, HARDCODED_PEERSETS_SYNC)
 	}
 
 	/// Returns the list of all the peers that the peerset currently requests us to be connected to.
 	pub fn requested_peers(&self) -> impl Iterator<Item = &PeerId> {
-		self.behaviour.requested_peers()
+		self.behaviour.requested_peers(HARDCODED_PEERSETS_SYNC)
 	}
 
 	/// Returns the number of discovered nodes that we keep in memory.
@@ -467,14 +507,16 @@ impl<B: BlockT, H: ExHashT> Protocol<B, H> {
 		self.behaviour.num_discovered_peers()
 	}
 
-	/// Disconnects the given peer if we are connected to it.
+	/// Disconnects the given peer from all the sets if we are connected to it.
 	pub fn disconnect_peer(&mut self, peer_id: &PeerId) {
-		self.behaviour.disconnect_peer(peer_id)
+		for set_index in 0..self.behaviour.num_sets() {
+			self.behaviour.disconnect_peer(peer_id, sc_peerset::SetId::from(set_index));
+		}
 	}
 
 	/// Returns true if we try to open protocols with the given peer.
 	pub fn is_enabled(&self, peer_id: &PeerId) -> bool {
-		self.behaviour.is_enabled(peer_id)
+		self.behaviour.is_enabled(peer_id, HARDCODED_PEERSETS_SYNC)
 	}
 
 	/// Returns the state of the peerset manager, for debugging purposes.
@@ -482,6 +524,16 @@ impl<B: BlockT, H: ExHashT> Protocol<B, H> {
 		self.behaviour.peerset_debug_info()
 	}
 
+	/// Returns the reputation of all the nodes known to the peerset.
+	pub fn peers_reputations(&mut self) -> Vec<(PeerId, i32)> {
+		self.behaviour.peers_reputations()
+	}
+
+	/// Returns the peers banned in the peerset, and for how long they remain banned.
+	pub fn banned_peers(&mut self) -> Vec<(PeerId, Option<time::Duration>)> {
+		self.behaviour.banned_peers()
+	}
+
 	/// Returns the number of peers we're connected to.
 	pub fn num_connected_peers(&self) -> usize {
 		self.context_data.peers.values().count()
@@ -606,7 +658,7 @@ impl<B: BlockT, H: ExHashT> Protocol<B, H> {
 					"Received no longer supported legacy request from {:?}",
 					who
 				);
-				self.disconnect_peer(&who);
+				self.behaviour.disconnect_peer(&who, HARDCODED_PEERSETS_SYNC);
 				self.peerset_handle.report_peer(who, rep::BAD_PROTOCOL);
 			},
 		}
@@ -665,14 +717,14 @@ impl<B: BlockT, H: ExHashT> Protocol<B, H> {
 				}
 				None => {
 					trace!(target: "sync", "Unexpected response packet from unknown peer {}", peer);
-					self.behaviour.disconnect_peer(&peer);
+					self.behaviour.disconnect_peer(&peer, HARDCODED_PEERSETS_SYNC);
 					self.peerset_handle.report_peer(peer, rep::UNEXPECTED_RESPONSE);
 					return CustomMessageOutcome::None;
 				}
 			}
 		} else {
 			trace!(target: "sync", "Unexpected response packet from unknown peer {}", peer);
-			self.behaviour.disconnect_peer(&peer);
+			self.behaviour.disconnect_peer(&peer, HARDCODED_PEERSETS_SYNC);
 			self.peerset_handle.report_peer(peer, rep::UNEXPECTED_RESPONSE);
 			return CustomMessageOutcome::None;
 		};
@@ -695,10 +747,10 @@ impl<B: BlockT, H: ExHashT> Protocol<B, H> {
 		if request.fields == message::BlockAttributes::JUSTIFICATION {
 			match self.sync.on_block_justification(peer, response) {
 				Ok(sync::OnBlockJustification::Nothing) => CustomMessageOutcome::None,
-				Ok(sync::OnBlockJustification::Import { peer, hash, number, justification }) =>
-					CustomMessageOutcome::JustificationImport(peer, hash, number, justification),
+				Ok(sync::OnBlockJustification::Import { peer, hash, number, justifications }) =>
+					CustomMessageOutcome::JustificationImport(peer, hash, number, justifications),
 				Err(sync::BadPeer(id, repu)) => {
-					self.behaviour.disconnect_peer(&id);
+					self.behaviour.disconnect_peer(&id, HARDCODED_PEERSETS_SYNC);
 					self.peerset_handle.report_peer(id, repu);
 					CustomMessageOutcome::None
 				}
@@ -715,7 +767,7 @@ impl<B: BlockT, H: ExHashT> Protocol<B, H> {
 					}
 				}
 				Err(sync::BadPeer(id, repu)) => {
-					self.behaviour.disconnect_peer(&id);
+					self.behaviour.disconnect_peer(&id, HARDCODED_PEERSETS_SYNC);
 					self.peerset_handle.report_peer(id, repu);
 					CustomMessageOutcome::None
 				}
@@ -729,7 +781,59 @@ impl<B: BlockT, H: ExHashT> Protocol<B, H> {
 		peer: &PeerId,
 	) {
 		self.peerset_handle.report_peer(peer.clone(), rep::TIMEOUT);
-		self.behaviour.disconnect_peer(peer);
+		self.behaviour.disconnect_peer(peer, HARDCODED_PEERSETS_SYNC);
+	}
+
+	/// Must be called in response to a [`CustomMessageOutcome::StateRequest`] being emitted.
+	/// Must contain the same `PeerId` that has been emitted.
+	pub fn on_state_response(
+		&mut self,
+		peer: PeerId,
+		response: crate::schema::v1::StateResponse,
+	) -> CustomMessageOutcome<B> {
+		match self.sync.on_state_data(&peer, response) {
+			Ok(sync::OnStateData::Import(origin, block)) =>
+				CustomMessageOutcome::BlockImport(origin, vec![block]),
+			Ok(sync::OnStateData::Continue) => CustomMessageOutcome::None,
+			Err(sync::BadPeer(id, repu)) => {
+				self.behaviour.disconnect_peer(&id, HARDCODED_PEERSETS_SYNC);
+				self.peerset_handle.report_peer(id, repu);
+				CustomMessageOutcome::None
+			}
+		}
+	}
+
+	/// Must be called in response to a [`CustomMessageOutcome::StateRequest`] if it has failed.
+	pub fn on_state_request_failed(
+		&mut self,
+		peer: &PeerId,
+	) {
+		self.sync.on_state_request_failed(peer);
+		self.peerset_handle.report_peer(peer.clone(), rep::TIMEOUT);
+		self.behaviour.disconnect_peer(peer, HARDCODED_PEERSETS_SYNC);
+	}
+
+	/// Must be called in response to a [`CustomMessageOutcome::WarpSyncRequest`] being emitted.
+	/// Must contain the same `PeerId` that has been emitted.
+	pub fn on_warp_sync_response(
+		&mut self,
+		peer: PeerId,
+		response: crate::warp_request_handler::EncodedProof,
+	) {
+		if let Err(sync::BadPeer(id, repu)) = self.sync.on_warp_sync_data(&peer, response) {
+			self.behaviour.disconnect_peer(&id, HARDCODED_PEERSETS_SYNC);
+			self.peerset_handle.report_peer(id, repu);
+		}
+	}
+
+	/// Must be called in response to a [`CustomMessageOutcome::WarpSyncRequest`] if it has failed.
+	pub fn on_warp_sync_request_failed(
+		&mut self,
+		peer: &PeerId,
+	) {
+		self.sync.on_warp_sync_request_failed(peer);
+		self.peerset_handle.report_peer(peer.clone(), rep::TIMEOUT);
+		self.behaviour.disconnect_peer(peer, HARDCODED_PEERSETS_SYNC);
 	}
 
 	/// Perform time based maintenance.
@@ -764,7 +868,7 @@ impl<B: BlockT, H: ExHashT> Protocol<B, H> {
 		}
 
 		for p in aborting {
-			self.behaviour.disconnect_peer(&p);
+			self.behaviour.disconnect_peer(&p, HARDCODED_PEERSETS_SYNC);
 			self.peerset_handle.report_peer(p, rep::TIMEOUT);
 		}
 	}
@@ -790,7 +894,7 @@ impl<B: BlockT, H: ExHashT> Protocol<B, H> {
 				self.genesis_hash, status.genesis_hash
 			);
 			self.peerset_handle.report_peer(who.clone(), rep::GENESIS_MISMATCH);
-			self.behaviour.disconnect_peer(&who);
+			self.behaviour.disconnect_peer(&who, HARDCODED_PEERSETS_SYNC);
 
 			if self.boot_node_ids.contains(&who) {
 				error!(
@@ -810,7 +914,7 @@ impl<B: BlockT, H: ExHashT> Protocol<B, H> {
 			if status.roles.is_light() {
 				debug!(target: "sync", "Peer {} is unable to serve light requests", who);
 				self.peerset_handle.report_peer(who.clone(), rep::BAD_ROLE);
-				self.behaviour.disconnect_peer(&who);
+				self.behaviour.disconnect_peer(&who, HARDCODED_PEERSETS_SYNC);
 				return CustomMessageOutcome::None;
 			}
 
@@ -827,7 +931,7 @@ impl<B: BlockT, H: ExHashT> Protocol<B, H> {
 			if blocks_difference > LIGHT_MAXIMAL_BLOCKS_DIFFERENCE {
 				debug!(target: "sync", "Peer {} is far behind us and will unable to serve light requests", who);
 				self.peerset_handle.report_peer(who.clone(), rep::PEER_BEHIND_US_LIGHT);
-				self.behaviour.disconnect_peer(&who);
+				self.behaviour.disconnect_peer(&who, HARDCODED_PEERSETS_SYNC);
 				return CustomMessageOutcome::None;
 			}
 		}
@@ -863,7 +967,7 @@ impl<B: BlockT, H: ExHashT> Protocol<B, H> {
 					});
 				},
 				Err(sync::BadPeer(id, repu)) => {
-					self.behaviour.disconnect_peer(&id);
+					self.behaviour.disconnect_peer(&id, HARDCODED_PEERSETS_SYNC);
 					self.peerset_handle.report_peer(id, repu)
 				}
 			}
@@ -900,7 +1004,7 @@ impl<B: BlockT, H: ExHashT> Protocol<B, H> {
 
 		let behaviour = &self.behaviour;
 		self.context_data.peers.iter().filter_map(move |(peer_id, peer)| {
-			if let Some(notifications_sink) = behaviour.notifications_sink(peer_id) {
+			if let Some(notifications_sink) = behaviour.notifications_sink(peer_id, HARDCODED_PEERSETS_SYNC) {
 				Some((peer_id, peer.info.roles, notifications_sink))
 			} else {
 				log::error!("State mismatch: no notifications sink for opened peer {:?}", peer_id);
@@ -918,7 +1022,7 @@ impl<B: BlockT, H: ExHashT> Protocol<B, H> {
 		// sending transaction to light node is considered a bad behavior
 		if !self.config.roles.is_full() {
 			trace!(target: "sync", "Peer {} is trying to send transactions to the light node", who);
-			self.behaviour.disconnect_peer(&who);
+			self.behaviour.disconnect_peer(&who, HARDCODED_PEERSETS_SYNC);
 			self.peerset_handle.report_peer(who, rep::UNEXPECTED_TRANSACTIONS);
 			return;
 		}
@@ -1016,10 +1120,12 @@ impl<B: BlockT, H: ExHashT> Protocol<B, H> {
 						.push(who.to_base58());
 				}
 				trace!(target: "sync", "Sending {} transactions to {}", to_send.len(), who);
+				let encoded = to_send.encode();
+				self.protocols_traffic.report_outbound(&self.transactions_protocol, encoded.len());
 				self.behaviour.write_notification(
 					who,
 					self.transactions_protocol.clone(),
-					to_send.encode()
+					encoded
 				);
 			}
 		}
@@ -1082,10 +1188,12 @@ impl<B: BlockT, H: ExHashT> Protocol<B, H> {
 					data: Some(data.clone()),
 				};
 
+				let encoded = message.encode();
+				self.protocols_traffic.report_outbound(&self.block_announces_protocol, encoded.len());
 				self.behaviour.write_notification(
 					who,
 					self.block_announces_protocol.clone(),
-					message.encode()
+					encoded
 				);
 			}
 		}
@@ -1170,7 +1278,7 @@ impl<B: BlockT, H: ExHashT> Protocol<B, H> {
 						body: None,
 						receipt: None,
 						message_queue: None,
-						justification: None,
+						justifications: None,
 					},
 				],
 			},
@@ -1194,7 +1302,7 @@ impl<B: BlockT, H: ExHashT> Protocol<B, H> {
 				}
 			}
 			Err(sync::BadPeer(id, repu)) => {
-				self.behaviour.disconnect_peer(&id);
+				self.behaviour.disconnect_peer(&id, HARDCODED_PEERSETS_SYNC);
 				self.peerset_handle.report_peer(id, repu);
 				CustomMessageOutcome::None
 			}
@@ -1246,7 +1354,7 @@ impl<B: BlockT, H: ExHashT> Protocol<B, H> {
 					});
 				}
 				Err(sync::BadPeer(id, repu)) => {
-					self.behaviour.disconnect_peer(&id);
+					self.behaviour.disconnect_peer(&id, HARDCODED_PEERSETS_SYNC);
 					self.peerset_handle.report_peer(id, repu)
 				}
 			}
@@ -1318,7 +1426,7 @@ impl<B: BlockT, H: ExHashT> Protocol<B, H> {
 #[must_use]
 pub enum CustomMessageOutcome<B: BlockT> {
 	BlockImport(BlockOrigin, Vec<IncomingBlock<B>>),
-	JustificationImport(Origin, B::Hash, NumberFor<B>, Justification),
+	JustificationImport(Origin, B::Hash, NumberFor<B>, Justifications),
 	/// Notification protocols have been opened with a remote.
 	NotificationStreamOpened {
 		remote: PeerId,
@@ -1343,6 +1451,14 @@ pub enum CustomMessageOutcome<B: BlockT> {
 	/// must be silently discarded.
 	/// It is the responsibility of the handler to ensure that a timeout exists.
 	BlockRequest { target: PeerId, request: message::BlockRequest<B> },
+	/// A new state request must be emitted.
+	/// You must later call either [`Protocol::on_state_response`] or
+	/// [`Protocol::on_state_request_failed`].
+	StateRequest { target: PeerId, request: crate::schema::v1::StateRequest },
+	/// A new warp proof request must be emitted.
+	/// You must later call either [`Protocol::on_warp_sync_response`] or
+	/// [`Protocol::on_warp_sync_request_failed`].
+	WarpSyncRequest { target: PeerId, request: crate::warp_request_handler::WarpProofRequest<B> },
 	/// Peer has a reported a new head of chain.
 	PeerNewBest(PeerId, NumberFor<B>),
 	None,
@@ -1439,6 +1555,28 @@ impl<B: BlockT, H: ExHashT> NetworkBehaviour for Protocol<B, H> {
 			};
 			self.pending_messages.push_back(event);
 		}
+		if let Some((id, mut r)) = self.sync.history_request() {
+			update_peer_request(&mut self.context_data.peers, &id, &mut r);
+			let event = CustomMessageOutcome::BlockRequest {
+				target: id,
+				request: r,
+			};
+			self.pending_messages.push_back(event);
+		}
+		if let Some((id, request)) = self.sync.state_request() {
+			let event = CustomMessageOutcome::StateRequest {
+				target: id,
+				request,
+			};
+			self.pending_messages.push_back(event);
+		}
+		if let Some((id, request)) = self.sync.warp_sync_request() {
+			let event = CustomMessageOutcome::WarpSyncRequest {
+				target: id,
+				request,
+			};
+			self.pending_messages.push_back(event);
+		}
 		if let Poll::Ready(Some((tx_hash, result))) = self.pending_transactions.poll_next_unpin(cx) {
 			if let Some(peers) = self.pending_transactions_peers.remove(&tx_hash) {
 				peers.into_iter().for_each(|p| self.on_handle_transaction_import(p, result));
@@ -1473,7 +1611,13 @@ impl<B: BlockT, H: ExHashT> NetworkBehaviour for Protocol<B, H> {
 		};
 
 		let outcome = match event {
-			GenericProtoOut::CustomProtocolOpen { peer_id, received_handshake, notifications_sink, .. } => {
+			GenericProtoOut::CustomProtocolOpen {
+				peer_id,
+				set_id,
+				received_handshake,
+				notifications_sink,
+				..
+			} if set_id == HARDCODED_PEERSETS_SYNC => {
 				// `received_handshake` can be either a `Status` message if received from the
 				// legacy substream ,or a `BlockAnnouncesHandshake` if received from the block
 				// announces substream.
@@ -1519,19 +1663,64 @@ impl<B: BlockT, H: ExHashT> NetworkBehaviour for Protocol<B, H> {
 					}
 				}
 			}
-			GenericProtoOut::CustomProtocolReplaced { peer_id, notifications_sink, .. } => {
+			GenericProtoOut::CustomProtocolOpen {
+				peer_id,
+				set_id,
+				received_handshake,
+				notifications_sink,
+				..
+			} => {
+				// Extra sets are made of a single notifications protocol whose handshake is
+				// the roles of the remote.
+				let protocol = self.extra_sets[usize::from(set_id) - 1].clone();
+				match <Roles as DecodeAll>::decode_all(&mut &received_handshake[..]) {
+					Ok(roles) => CustomMessageOutcome::NotificationStreamOpened {
+						remote: peer_id,
+						protocols: vec![protocol],
+						roles,
+						notifications_sink,
+					},
+					Err(err) => {
+						debug!(
+							target: "sync",
+							"Couldn't decode handshake sent by {} on {:?}: {:?}: {}",
+							peer_id,
+							protocol,
+							received_handshake,
+							err.what(),
+						);
+						self.behaviour.disconnect_peer(&peer_id, set_id);
+						self.peerset_handle.report_peer(peer_id, rep::BAD_MESSAGE);
+						CustomMessageOutcome::None
+					}
+				}
+			}
+			GenericProtoOut::CustomProtocolReplaced { peer_id, set_id, notifications_sink, .. } => {
+				let protocols = if set_id == HARDCODED_PEERSETS_SYNC {
+					self.notification_protocols.clone()
+				} else {
+					vec![self.extra_sets[usize::from(set_id) - 1].clone()]
+				};
 				CustomMessageOutcome::NotificationStreamReplaced {
 					remote: peer_id,
-					protocols: self.notification_protocols.clone(),
+					protocols,
 					notifications_sink,
 				}
 			},
-			GenericProtoOut::CustomProtocolClosed { peer_id } => {
-				self.on_peer_disconnected(peer_id)
+			GenericProtoOut::CustomProtocolClosed { peer_id, set_id } => {
+				if set_id == HARDCODED_PEERSETS_SYNC {
+					self.on_peer_disconnected(peer_id)
+				} else {
+					CustomMessageOutcome::NotificationStreamClosed {
+						remote: peer_id,
+						protocols: vec![self.extra_sets[usize::from(set_id) - 1].clone()],
+					}
+				}
 			},
 			GenericProtoOut::LegacyMessage { peer_id, message } =>
 				self.on_custom_message(peer_id, message),
-			GenericProtoOut::Notification { peer_id, protocol_name, message } =>
+			GenericProtoOut::Notification { peer_id, protocol_name, message, .. } => {
+				self.protocols_traffic.report_inbound(&protocol_name, message.len());
 				match self.legacy_equiv_by_name.get(&protocol_name) {
 					Some(Fallback::Consensus) => {
 						CustomMessageOutcome::NotificationsReceived {
@@ -1568,6 +1757,7 @@ impl<B: BlockT, H: ExHashT> NetworkBehaviour for Protocol<B, H> {
 						CustomMessageOutcome::None
 					}
 				}
+			}
 		};
 
 		if let CustomMessageOutcome::None = outcome {
@@ -37,7 +37,7 @@ use rand::distributions::{Distribution as _, Uniform};
 use smallvec::SmallVec;
 use std::task::{Context, Poll};
 use std::{borrow::Cow, cmp, collections::{hash_map::Entry, VecDeque}};
-use std::{error, mem, pin::Pin, str, sync::Arc, time::Duration};
+use std::{error, iter, mem, pin::Pin, str, sync::Arc, time::Duration};
 use wasm_timer::Instant;
 
 /// Network behaviour that handles opening substreams for custom protocols with other peers.
@@ -51,7 +51,11 @@ use wasm_timer::Instant;
 /// - The peerset manager (PSM) that requests links to peers to be established or broken.
 /// - The external API, that requires knowledge of the links that have been established.
 ///
-/// In the state machine below, each `PeerId` is attributed one of these states:
+/// The notifications protocols are grouped into *sets*, each corresponding to a set of the
+/// peerset manager. The first set additionally contains the legacy substream. Each set is
+/// handled independently from the others.
+///
+/// In the state machine below, each `(PeerId, SetId)` is attributed one of these states:
 ///
 /// - [`PeerState::Requested`]: No open connection, but requested by the peerset. Currently dialing.
 /// - [`PeerState::Disabled`]: Has open TCP connection(s) unbeknownst to the peerset. No substream
@@ -101,25 +105,28 @@ pub struct GenericProto {
 	/// Legacy protocol to open with peers. Never modified.
 	legacy_protocol: RegisteredProtocol,
 
-	/// Notification protocols. Entries are only ever added and not removed.
+	/// Notification protocols, grouped by set. The index of a set within this list is its
+	/// [`sc_peerset::SetId`]. Entries are only ever added and not removed.
 	/// Contains, for each protocol, the protocol name and the message to send as part of the
 	/// initial handshake.
-	notif_protocols: Vec<(Cow<'static, str>, Arc<RwLock<Vec<u8>>>)>,
+	notif_protocols: Vec<Vec<(Cow<'static, str>, Arc<RwLock<Vec<u8>>>)>>,
 
 	/// Receiver for instructions about who to connect to or disconnect from.
 	peerset: sc_peerset::Peerset,
 
-	/// List of peers in our state.
-	peers: FnvHashMap<PeerId, PeerState>,
+	/// List of peers in our state, for each set.
+	peers: FnvHashMap<(PeerId, sc_peerset::SetId), PeerState>,
 
 	/// The elements in `peers` occasionally contain `Delay` objects that we would normally have
 	/// to be polled one by one. In order to avoid doing so, as an optimization, every `Delay` is
 	/// instead put inside of `delays` and reference by a [`DelayId`]. This stream
-	/// yields `PeerId`s whose `DelayId` is potentially ready.
+	/// yields `PeerId`s and sets whose `DelayId` is potentially ready.
 	///
 	/// By design, we never remove elements from this list. Elements are removed only when the
 	/// `Delay` triggers. As such, this stream may produce obsolete elements.
-	delays: stream::FuturesUnordered<Pin<Box<dyn Future<Output = (DelayId, PeerId)> + Send>>>,
+	delays: stream::FuturesUnordered<
+		Pin<Box<dyn Future<Output = (DelayId, PeerId, sc_peerset::SetId)> + Send>>
+	>,
 
 	/// [`DelayId`] to assign to the next delay.
 	next_delay_id: DelayId,
@@ -299,6 +306,8 @@ enum ConnectionState {
 struct IncomingPeer {
 	/// Id of the remote peer of the incoming connection.
 	peer_id: PeerId,
+	/// Set the incoming connection is for.
+	set_id: sc_peerset::SetId,
 	/// If true, this "incoming" still corresponds to an actual connection. If false, then the
 	/// connection corresponding to it has been closed or replaced already.
 	alive: bool,
@@ -313,6 +322,8 @@ pub enum GenericProtoOut {
 	CustomProtocolOpen {
 		/// Id of the peer we are connected to.
 		peer_id: PeerId,
+		/// Peerset set the protocols belong to.
+		set_id: sc_peerset::SetId,
 		/// Handshake that was sent to us.
 		/// This is normally a "Status" message, but this is out of the concern of this code.
 		received_handshake: Vec<u8>,
@@ -328,6 +339,8 @@ pub enum GenericProtoOut {
 	CustomProtocolReplaced {
 		/// Id of the peer we are connected to.
 		peer_id: PeerId,
+		/// Peerset set the protocols belong to.
+		set_id: sc_peerset::SetId,
 		/// Replacement for the previous [`NotificationsSink`].
 		notifications_sink: NotificationsSink,
 	},
@@ -337,6 +350,8 @@ pub enum GenericProtoOut {
 	CustomProtocolClosed {
 		/// Id of the peer we were connected to.
 		peer_id: PeerId,
+		/// Peerset set the protocols belong to.
+		set_id: sc_peerset::SetId,
 	},
 
 	/// Receives a message on the legacy substream.
@@ -353,6 +368,8 @@ pub enum GenericProtoOut {
 	Notification {
 		/// Id of the peer the message came from.
 		peer_id: PeerId,
+		/// Peerset set the protocol belongs to.
+		set_id: sc_peerset::SetId,
 		/// Engine corresponding to the message.
 		protocol_name: Cow<'static, str>,
 		/// Message that has been received.
@@ -362,6 +379,10 @@ pub enum GenericProtoOut {
 
 impl GenericProto {
 	/// Creates a `CustomProtos`.
+	///
+	/// `notif_protocols` are the protocols of the first set, which also contains the legacy
+	/// substream. Each of the `extra_sets` is a set made of a single protocol. The peerset must
+	/// have been configured with one set per set of protocols, in the same order.
 	pub fn new(
 		local_peer_id: PeerId,
 		protocol: impl Into<ProtocolId>,
@@ -369,12 +390,17 @@ impl GenericProto {
 		handshake_message: Vec<u8>,
 		peerset: sc_peerset::Peerset,
 		notif_protocols: impl Iterator<Item = (Cow<'static, str>, Vec<u8>)>,
+		extra_sets: impl Iterator<Item = (Cow<'static, str>, Vec<u8>)>,
 	) -> Self {
-		let notif_protocols = notif_protocols
+		let default_set = notif_protocols
 			.map(|(n, hs)| (n, Arc::new(RwLock::new(hs))))
 			.collect::<Vec<_>>();
 
-		assert!(!notif_protocols.is_empty());
+		assert!(!default_set.is_empty());
+
+		let notif_protocols = iter::once(default_set)
+			.chain(extra_sets.map(|(n, hs)| vec![(n, Arc::new(RwLock::new(hs)))]))
+			.collect::<Vec<_>>();
 
 		let legacy_handshake_message = Arc::new(RwLock::new(handshake_message));
 		let legacy_protocol = RegisteredProtocol::new(protocol, versions, legacy_handshake_message);
@@ -393,7 +419,7 @@ impl GenericProto {
 		}
 	}
 
-	/// Registers a new notifications protocol.
+	/// Registers a new notifications protocol in the first set.
 	///
 	/// You are very strongly encouraged to call this method very early on. Any open connection
 	/// will retain the protocols that were registered then, and not any new one.
@@ -402,7 +428,7 @@ impl GenericProto {
 		protocol_name: impl Into<Cow<'static, str>>,
 		handshake_msg: impl Into<Vec<u8>>
 	) {
-		self.notif_protocols.push((protocol_name.into(), Arc::new(RwLock::new(handshake_msg.into()))));
+		self.notif_protocols[0].push((protocol_name.into(), Arc::new(RwLock::new(handshake_msg.into()))));
 	}
 
 	/// Modifies the handshake of the given notifications protocol.
@@ -413,7 +439,10 @@ impl GenericProto {
 		protocol_name: &str,
 		handshake_message: impl Into<Vec<u8>>
 	) {
-		if let Some(protocol) = self.notif_protocols.iter_mut().find(|(name, _)| name == protocol_name) {
+		if let Some(protocol) = self.notif_protocols.iter_mut()
+			.flat_map(|set| set.iter_mut())
+			.find(|(name, _)| name == protocol_name)
+		{
 			*protocol.1.write() = handshake_message.into();
 		}
 	}
@@ -431,35 +460,46 @@ impl GenericProto {
 		self.peerset.num_discovered_peers()
 	}
 
-	/// Returns the list of all the peers we have an open channel to.
-	pub fn open_peers<'a>(&'a self) -> impl Iterator<Item = &'a PeerId> + 'a {
-		self.peers.iter().filter(|(_, state)| state.is_open()).map(|(id, _)| id)
+	/// Returns the number of sets of protocols.
+	pub fn num_sets(&self) -> usize {
+		self.notif_protocols.len()
 	}
 
-	/// Returns true if we have an open connection to the given peer.
-	pub fn is_open(&self, peer_id: &PeerId) -> bool {
-		self.peers.get(peer_id).map(|p| p.is_open()).unwrap_or(false)
+	/// Returns the list of all the peers we have an open channel to in the given set.
+	pub fn open_peers<'a>(&'a self, set_id: sc_peerset::SetId) -> impl Iterator<Item = &'a PeerId> + 'a {
+		self.peers.iter()
+			.filter(move |((_, set), state)| *set == set_id && state.is_open())
+			.map(|((id, _), _)| id)
+	}
+
+	/// Returns true if we have an open connection to the given peer in the given set.
+	pub fn is_open(&self, peer_id: &PeerId, set_id: sc_peerset::SetId) -> bool {
+		self.peers.get(&(peer_id.clone(), set_id)).map(|p| p.is_open()).unwrap_or(false)
 	}
 
 	/// Returns the [`NotificationsSink`] that sends notifications to the given peer, or `None`
-	/// if the custom protocols aren't opened with this peer.
+	/// if the custom protocols of the given set aren't opened with this peer.
 	///
-	/// If [`GenericProto::is_open`] returns `true` for this `PeerId`, then this method is
-	/// guaranteed to return `Some`.
-	pub fn notifications_sink(&self, peer_id: &PeerId) -> Option<&NotificationsSink> {
-		self.peers.get(peer_id).and_then(|p| p.get_open())
+	/// If [`GenericProto::is_open`] returns `true` for this `PeerId` and set, then this method
+	/// is guaranteed to return `Some`.
+	pub fn notifications_sink(
+		&self,
+		peer_id: &PeerId,
+		set_id: sc_peerset::SetId,
+	) -> Option<&NotificationsSink> {
+		self.peers.get(&(peer_id.clone(), set_id)).and_then(|p| p.get_open())
 	}
 
-	/// Disconnects the given peer if we are connected to it.
-	pub fn disconnect_peer(&mut self, peer_id: &PeerId) {
-		debug!(target: "sub-libp2p", "External API => Disconnect {:?}", peer_id);
-		self.disconnect_peer_inner(peer_id, None);
+	/// Disconnects the given peer from the given set if we are connected to it.
+	pub fn disconnect_peer(&mut self, peer_id: &PeerId, set_id: sc_peerset::SetId) {
+		debug!(target: "sub-libp2p", "External API => Disconnect({}, {:?})", peer_id, set_id);
+		self.disconnect_peer_inner(peer_id, set_id, None);
 	}
 
 	/// Inner implementation of `disconnect_peer`. If `ban` is `Some`, we ban the peer
 	/// for the specific duration.
-	fn disconnect_peer_inner(&mut self, peer_id: &PeerId, ban: Option<Duration>) {
-		let mut entry = if let Entry::Occupied(entry) = self.peers.entry(peer_id.clone()) {
+	fn disconnect_peer_inner(&mut self, peer_id: &PeerId, set_id: sc_peerset::SetId, ban: Option<Duration>) {
+		let mut entry = if let Entry::Occupied(entry) = self.peers.entry((peer_id.clone(), set_id)) {
 			entry
 		} else {
 			return
@@ -478,8 +518,8 @@ impl GenericProto {
 				timer_deadline,
 				timer: _
 			} => {
-				debug!(target: "sub-libp2p", "PSM <= Dropped({:?})", peer_id);
-				self.peerset.dropped(peer_id.clone());
+				debug!(target: "sub-libp2p", "PSM <= Dropped({}, {:?})", peer_id, set_id);
+				self.peerset.dropped(set_id, peer_id.clone());
 				let backoff_until = Some(if let Some(ban) = ban {
 					cmp::max(timer_deadline, Instant::now() + ban)
 				} else {
@@ -495,13 +535,14 @@ impl GenericProto {
 			// All open or opening connections are sent a `Close` message.
 			// If relevant, the external API is instantly notified.
 			PeerState::Enabled { mut connections } => {
-				debug!(target: "sub-libp2p", "PSM <= Dropped({:?})", peer_id);
-				self.peerset.dropped(peer_id.clone());
+				debug!(target: "sub-libp2p", "PSM <= Dropped({}, {:?})", peer_id, set_id);
+				self.peerset.dropped(set_id, peer_id.clone());
 
 				if connections.iter().any(|(_, s)| matches!(s, ConnectionState::Open(_))) {
 					debug!(target: "sub-libp2p", "External API <= Closed({})", peer_id);
 					let event = GenericProtoOut::CustomProtocolClosed {
 						peer_id: peer_id.clone(),
+						set_id,
 					};
 					self.events.push_back(NetworkBehaviourAction::GenerateEvent(event));
 				}
@@ -513,7 +554,7 @@ impl GenericProto {
 					self.events.push_back(NetworkBehaviourAction::NotifyHandler {
 						peer_id: peer_id.clone(),
 						handler: NotifyHandler::One(*connec_id),
-						event: NotifsHandlerIn::Close,
+						event: NotifsHandlerIn::Close { set_index: set_id.into() },
 					});
 					*connec_state = ConnectionState::Closing;
 				}
@@ -525,7 +566,7 @@ impl GenericProto {
 					self.events.push_back(NetworkBehaviourAction::NotifyHandler {
 						peer_id: peer_id.clone(),
 						handler: NotifyHandler::One(*connec_id),
-						event: NotifsHandlerIn::Close,
+						event: NotifsHandlerIn::Close { set_index: set_id.into() },
 					});
 					*connec_state = ConnectionState::OpeningThenClosing;
 				}
@@ -544,7 +585,7 @@ impl GenericProto {
 			// Ongoing opening requests from the remote are rejected.
 			PeerState::Incoming { mut connections, backoff_until } => {
 				let inc = if let Some(inc) = self.incoming.iter_mut()
-					.find(|i| i.peer_id == *entry.key() && i.alive) {
+					.find(|i| i.peer_id == *peer_id && i.set_id == set_id && i.alive) {
 					inc
 				} else {
 					error!(target: "sub-libp2p", "State mismatch in libp2p: no entry in \
@@ -561,7 +602,7 @@ impl GenericProto {
 					self.events.push_back(NetworkBehaviourAction::NotifyHandler {
 						peer_id: peer_id.clone(),
 						handler: NotifyHandler::One(*connec_id),
-						event: NotifsHandlerIn::Close,
+						event: NotifsHandlerIn::Close { set_index: set_id.into() },
 					});
 					*connec_state = ConnectionState::Closing;
 				}
@@ -585,14 +626,20 @@ impl GenericProto {
 		}
 	}
 
-	/// Returns the list of all the peers that the peerset currently requests us to be connected to.
-	pub fn requested_peers<'a>(&'a self) -> impl Iterator<Item = &'a PeerId> + 'a {
-		self.peers.iter().filter(|(_, state)| state.is_requested()).map(|(id, _)| id)
+	/// Returns the list of all the peers that the peerset currently requests us to be connected
+	/// to in the given set.
+	pub fn requested_peers<'a>(
+		&'a self,
+		set_id: sc_peerset::SetId,
+	) -> impl Iterator<Item = &'a PeerId> + 'a {
+		self.peers.iter()
+			.filter(move |((_, set), state)| *set == set_id && state.is_requested())
+			.map(|((id, _), _)| id)
 	}
 
-	/// Returns true if we try to open protocols with the given peer.
-	pub fn is_enabled(&self, peer_id: &PeerId) -> bool {
-		match self.peers.get(peer_id) {
+	/// Returns true if we try to open protocols of the given set with the given peer.
+	pub fn is_enabled(&self, peer_id: &PeerId, set_id: sc_peerset::SetId) -> bool {
+		match self.peers.get(&(peer_id.clone(), set_id)) {
 			None => false,
 			Some(PeerState::Disabled { .. }) => false,
 			Some(PeerState::DisabledPendingEnable { .. }) => false,
@@ -607,10 +654,10 @@ impl GenericProto {
 
 	/// Notify the behaviour that we have learned about the existence of nodes.
 	///
-	/// Can be called multiple times with the same `PeerId`s.
+	/// The nodes are added to every set. Can be called multiple times with the same `PeerId`s.
 	pub fn add_discovered_nodes(&mut self, peer_ids: impl Iterator<Item = PeerId>) {
 		let local_peer_id = &self.local_peer_id;
-		self.peerset.discovered(peer_ids.filter_map(|peer_id| {
+		let peer_ids = peer_ids.filter_map(|peer_id| {
 			if peer_id == *local_peer_id {
 				error!(
 					target: "sub-libp2p",
@@ -621,7 +668,11 @@ impl GenericProto {
 
 			debug!(target: "sub-libp2p", "PSM <= Discovered({:?})", peer_id);
 			Some(peer_id)
-		}));
+		}).collect::<Vec<_>>();
+
+		for set_id in (0..self.notif_protocols.len()).map(sc_peerset::SetId::from) {
+			self.peerset.discovered(set_id, peer_ids.iter().cloned());
+		}
 	}
 
 	/// Sends a notification to a peer.
@@ -640,7 +691,19 @@ impl GenericProto {
 		protocol_name: Cow<'static, str>,
 		message: impl Into<Vec<u8>>,
 	) {
-		let notifs_sink = match self.peers.get(target).and_then(|p| p.get_open()) {
+		let set_id = match self.notif_protocols.iter()
+			.position(|set| set.iter().any(|(name, _)| *name == protocol_name))
+		{
+			Some(set_index) => sc_peerset::SetId::from(set_index),
+			None => {
+				debug!(target: "sub-libp2p",
+					"Tried to sent notification on unknown protocol {:?}.",
+					protocol_name);
+				return
+			},
+		};
+
+		let notifs_sink = match self.peers.get(&(target.clone(), set_id)).and_then(|p| p.get_open()) {
 			None => {
 				debug!(target: "sub-libp2p",
 					"Tried to sent notification to {:?} without an open channel.",
@@ -672,17 +735,28 @@ impl GenericProto {
 		self.peerset.debug_info()
 	}
 
+	/// Returns the reputation of all the nodes known to the peerset.
+	pub fn peers_reputations(&mut self) -> Vec<(PeerId, i32)> {
+		self.peerset.reputations()
+	}
+
+	/// Returns the peers banned in the peerset, and for how long they remain banned.
+	pub fn banned_peers(&mut self) -> Vec<(PeerId, Option<Duration>)> {
+		self.peerset.banned_peers()
+	}
+
 	/// Function that is called when the peerset wants us to connect to a peer.
-	fn peerset_report_connect(&mut self, peer_id: PeerId) {
+	fn peerset_report_connect(&mut self, peer_id: PeerId, set_id: sc_peerset::SetId) {
 		// If `PeerId` is unknown to us, insert an entry, start dialing, and return early.
-		let mut occ_entry = match self.peers.entry(peer_id.clone()) {
+		let mut occ_entry = match self.peers.entry((peer_id.clone(), set_id)) {
 			Entry::Occupied(entry) => entry,
 			Entry::Vacant(entry) => {
 				// If there's no entry in `self.peers`, start dialing.
-				debug!(target: "sub-libp2p", "PSM => Connect({:?}): Starting to connect", entry.key());
-				debug!(target: "sub-libp2p", "Libp2p <= Dial {:?}", entry.key());
+				debug!(target: "sub-libp2p", "PSM => Connect({}, {:?}): Starting to connect",
+					entry.key().0, set_id);
+				debug!(target: "sub-libp2p", "Libp2p <= Dial {}", entry.key().0);
 				self.events.push_back(NetworkBehaviourAction::DialPeer {
-					peer_id: entry.key().clone(),
+					peer_id: entry.key().0.clone(),
 					condition: DialPeerCondition::Disconnected
 				});
 				entry.insert(PeerState::Requested);
@@ -695,9 +769,9 @@ impl GenericProto {
 		match mem::replace(occ_entry.get_mut(), PeerState::Poisoned) {
 			// Backoff (not expired) => PendingRequest
 			PeerState::Backoff { ref timer, ref timer_deadline } if *timer_deadline > now => {
-				let peer_id = occ_entry.key().clone();
-				debug!(target: "sub-libp2p", "PSM => Connect({:?}): Will start to connect at \
-					until {:?}", peer_id, timer_deadline);
+				let peer_id = occ_entry.key().0.clone();
+				debug!(target: "sub-libp2p", "PSM => Connect({}, {:?}): Will start to connect at \
+					until {:?}", peer_id, set_id, timer_deadline);
 				*occ_entry.into_mut() = PeerState::PendingRequest {
 					timer: *timer,
 					timer_deadline: *timer_deadline,
@@ -706,10 +780,11 @@ impl GenericProto {
 
 			// Backoff (expired) => Requested
 			PeerState::Backoff { .. } => {
-				debug!(target: "sub-libp2p", "PSM => Connect({:?}): Starting to connect", occ_entry.key());
+				debug!(target: "sub-libp2p", "PSM => Connect({}, {:?}): Starting to connect",
+					occ_entry.key().0, set_id);
 				debug!(target: "sub-libp2p", "Libp2p <= Dial {:?}", occ_entry.key());
 				self.events.push_back(NetworkBehaviourAction::DialPeer {
-					peer_id: occ_entry.key().clone(),
+					peer_id: occ_entry.key().0.clone(),
 					condition: DialPeerCondition::Disconnected
 				});
 				*occ_entry.into_mut() = PeerState::Requested;
@@ -720,16 +795,16 @@ impl GenericProto {
 				connections,
 				backoff_until: Some(ref backoff)
 			} if *backoff > now => {
-				let peer_id = occ_entry.key().clone();
-				debug!(target: "sub-libp2p", "PSM => Connect({:?}): But peer is backed-off until {:?}",
-					peer_id, backoff);
+				let peer_id = occ_entry.key().0.clone();
+				debug!(target: "sub-libp2p", "PSM => Connect({}, {:?}): But peer is backed-off until {:?}",
+					peer_id, set_id, backoff);
 
 				let delay_id = self.next_delay_id;
 				self.next_delay_id.0 += 1;
 				let delay = futures_timer::Delay::new(*backoff - now);
 				self.delays.push(async move {
 					delay.await;
-					(delay_id, peer_id)
+					(delay_id, peer_id, set_id)
 				}.boxed());
 
 				*occ_entry.into_mut() = PeerState::DisabledPendingEnable {
@@ -749,13 +824,13 @@ impl GenericProto {
 				if let Some((connec_id, connec_state)) = connections.iter_mut()
 					.find(|(_, s)| matches!(s, ConnectionState::Closed))
 				{
-					debug!(target: "sub-libp2p", "PSM => Connect({:?}): Enabling connections.",
-						occ_entry.key());
+					debug!(target: "sub-libp2p", "PSM => Connect({}, {:?}): Enabling connections.",
+						occ_entry.key().0, set_id);
 					debug!(target: "sub-libp2p", "Handler({:?}, {:?}) <= Open", peer_id, *connec_id);
 					self.events.push_back(NetworkBehaviourAction::NotifyHandler {
 						peer_id: peer_id.clone(),
 						handler: NotifyHandler::One(*connec_id),
-						event: NotifsHandlerIn::Open,
+						event: NotifsHandlerIn::Open { set_index: set_id.into() },
 					});
 					*connec_state = ConnectionState::Opening;
 					*occ_entry.into_mut() = PeerState::Enabled { connections };
@@ -767,8 +842,8 @@ impl GenericProto {
 					}));
 					debug!(
 						target: "sub-libp2p",
-						"PSM => Connect({:?}): No connection in proper state. Delaying.",
-						occ_entry.key()
+						"PSM => Connect({}, {:?}): No connection in proper state. Delaying.",
+						occ_entry.key().0, set_id
 					);
 
 					let timer_deadline = {
@@ -786,7 +861,7 @@ impl GenericProto {
 					let delay = futures_timer::Delay::new(timer_deadline - now);
 					self.delays.push(async move {
 						delay.await;
-						(delay_id, peer_id)
+						(delay_id, peer_id, set_id)
 					}.boxed());
 
 					*occ_entry.into_mut() = PeerState::DisabledPendingEnable {
@@ -799,10 +874,10 @@ impl GenericProto {
 
 			// Incoming => Enabled
 			PeerState::Incoming { mut connections, .. } => {
-				debug!(target: "sub-libp2p", "PSM => Connect({:?}): Enabling connections.",
-					occ_entry.key());
+				debug!(target: "sub-libp2p", "PSM => Connect({}, {:?}): Enabling connections.",
+					occ_entry.key().0, set_id);
 				if let Some(inc) = self.incoming.iter_mut()
-					.find(|i| i.peer_id == *occ_entry.key() && i.alive) {
+					.find(|i| i.peer_id == occ_entry.key().0 && i.set_id == set_id && i.alive) {
 					inc.alive = false;
 				} else {
 					error!(target: "sub-libp2p", "State mismatch in libp2p: no entry in \
@@ -813,11 +888,12 @@ impl GenericProto {
 				for (connec_id, connec_state) in connections.iter_mut()
 					.filter(|(_, s)| matches!(s, ConnectionState::OpenDesiredByRemote))
 				{
-					debug!(target: "sub-libp2p", "Handler({:?}, {:?}) <= Open", occ_entry.key(), *connec_id);
+					debug!(target: "sub-libp2p", "Handler({:?}, {:?}) <= Open({:?})",
+						occ_entry.key().0, *connec_id, set_id);
 					self.events.push_back(NetworkBehaviourAction::NotifyHandler {
-						peer_id: occ_entry.key().clone(),
+						peer_id: occ_entry.key().0.clone(),
 						handler: NotifyHandler::One(*connec_id),
-						event: NotifsHandlerIn::Open,
+						event: NotifsHandlerIn::Open { set_index: set_id.into() },
 					});
 					*connec_state = ConnectionState::Opening;
 				}
@@ -828,22 +904,22 @@ impl GenericProto {
 			// Other states are kept as-is.
 			st @ PeerState::Enabled { .. } => {
 				warn!(target: "sub-libp2p",
-					"PSM => Connect({:?}): Already connected.",
-					occ_entry.key());
+					"PSM => Connect({}, {:?}): Already connected.",
+					occ_entry.key().0, set_id);
 				*occ_entry.into_mut() = st;
 				debug_assert!(false);
 			},
 			st @ PeerState::DisabledPendingEnable { .. } => {
 				warn!(target: "sub-libp2p",
-					"PSM => Connect({:?}): Already pending enabling.",
-					occ_entry.key());
+					"PSM => Connect({}, {:?}): Already pending enabling.",
+					occ_entry.key().0, set_id);
 				*occ_entry.into_mut() = st;
 				debug_assert!(false);
 			},
 			st @ PeerState::Requested { .. } | st @ PeerState::PendingRequest { .. } => {
 				warn!(target: "sub-libp2p",
-					"PSM => Connect({:?}): Duplicate request.",
-					occ_entry.key());
+					"PSM => Connect({}, {:?}): Duplicate request.",
+					occ_entry.key().0, set_id);
 				*occ_entry.into_mut() = st;
 				debug_assert!(false);
 			},
@@ -856,18 +932,20 @@ impl GenericProto {
 	}
 
 	/// Function that is called when the peerset wants us to disconnect from a peer.
-	fn peerset_report_disconnect(&mut self, peer_id: PeerId) {
-		let mut entry = match self.peers.entry(peer_id) {
+	fn peerset_report_disconnect(&mut self, peer_id: PeerId, set_id: sc_peerset::SetId) {
+		let mut entry = match self.peers.entry((peer_id, set_id)) {
 			Entry::Occupied(entry) => entry,
 			Entry::Vacant(entry) => {
-				debug!(target: "sub-libp2p", "PSM => Drop({:?}): Already disabled.", entry.key());
+				debug!(target: "sub-libp2p", "PSM => Drop({}, {:?}): Already disabled.",
+					entry.key().0, set_id);
 				return
 			}
 		};
 
 		match mem::replace(entry.get_mut(), PeerState::Poisoned) {
 			st @ PeerState::Disabled { .. } | st @ PeerState::Backoff { .. } => {
-				debug!(target: "sub-libp2p", "PSM => Drop({:?}): Already disabled.", entry.key());
+				debug!(target: "sub-libp2p", "PSM => Drop({}, {:?}): Already disabled.",
+					entry.key().0, set_id);
 				*entry.into_mut() = st;
 			},
 
@@ -875,8 +953,8 @@ impl GenericProto {
 			PeerState::DisabledPendingEnable { connections, timer_deadline, timer: _ } => {
 				debug_assert!(!connections.is_empty());
 				debug!(target: "sub-libp2p",
-					"PSM => Drop({:?}): Interrupting pending enabling.",
-					entry.key());
+					"PSM => Drop({}, {:?}): Interrupting pending enabling.",
+					entry.key().0, set_id);
 				*entry.into_mut() = PeerState::Disabled {
 					connections,
 					backoff_until: Some(timer_deadline),
@@ -885,15 +963,17 @@ impl GenericProto {
 
 			// Enabled => Disabled
 			PeerState::Enabled { mut connections } => {
-				debug!(target: "sub-libp2p", "PSM => Drop({:?}): Disabling connections.", entry.key());
+				debug!(target: "sub-libp2p", "PSM => Drop({}, {:?}): Disabling connections.",
+					entry.key().0, set_id);
 
 				debug_assert!(connections.iter().any(|(_, s)|
 					matches!(s, ConnectionState::Opening | ConnectionState::Open(_))));
 
 				if connections.iter().any(|(_, s)| matches!(s, ConnectionState::Open(_))) {
-					debug!(target: "sub-libp2p", "External API <= Closed({})", entry.key());
+					debug!(target: "sub-libp2p", "External API <= Closed({}, {:?})", entry.key().0, set_id);
 					let event = GenericProtoOut::CustomProtocolClosed {
-						peer_id: entry.key().clone(),
+						peer_id: entry.key().0.clone(),
+						set_id,
 					};
 					self.events.push_back(NetworkBehaviourAction::GenerateEvent(event));
 				}
@@ -901,11 +981,12 @@ impl GenericProto {
 				for (connec_id, connec_state) in connections.iter_mut()
 					.filter(|(_, s)| matches!(s, ConnectionState::Opening))
 				{
-					debug!(target: "sub-libp2p", "Handler({:?}, {:?}) <= Close", entry.key(), *connec_id);
+					debug!(target: "sub-libp2p", "Handler({:?}, {:?}) <= Close({:?})",
+						entry.key().0, *connec_id, set_id);
 					self.events.push_back(NetworkBehaviourAction::NotifyHandler {
-						peer_id: entry.key().clone(),
+						peer_id: entry.key().0.clone(),
 						handler: NotifyHandler::One(*connec_id),
-						event: NotifsHandlerIn::Close,
+						event: NotifsHandlerIn::Close { set_index: set_id.into() },
 					});
 					*connec_state = ConnectionState::OpeningThenClosing;
 				}
@@ -913,11 +994,12 @@ impl GenericProto {
 				for (connec_id, connec_state) in connections.iter_mut()
 					.filter(|(_, s)| matches!(s, ConnectionState::Open(_)))
 				{
-					debug!(target: "sub-libp2p", "Handler({:?}, {:?}) <= Close", entry.key(), *connec_id);
+					debug!(target: "sub-libp2p", "Handler({:?}, {:?}) <= Close({:?})",
+						entry.key().0, *connec_id, set_id);
 					self.events.push_back(NetworkBehaviourAction::NotifyHandler {
-						peer_id: entry.key().clone(),
+						peer_id: entry.key().0.clone(),
 						handler: NotifyHandler::One(*connec_id),
-						event: NotifsHandlerIn::Close,
+						event: NotifsHandlerIn::Close { set_index: set_id.into() },
 					});
 					*connec_state = ConnectionState::Closing;
 				}
@@ -930,20 +1012,22 @@ impl GenericProto {
 				// We don't cancel dialing. Libp2p doesn't expose that on purpose, as other
 				// sub-systems (such as the discovery mechanism) may require dialing this peer as
 				// well at the same time.
-				debug!(target: "sub-libp2p", "PSM => Drop({:?}): Not yet connected.", entry.key());
+				debug!(target: "sub-libp2p", "PSM => Drop({}, {:?}): Not yet connected.",
+					entry.key().0, set_id);
 				entry.remove();
 			},
 
 			// PendingRequest => Backoff
 			PeerState::PendingRequest { timer, timer_deadline } => {
-				debug!(target: "sub-libp2p", "PSM => Drop({:?}): Not yet connected", entry.key());
+				debug!(target: "sub-libp2p", "PSM => Drop({}, {:?}): Not yet connected",
+					entry.key().0, set_id);
 				*entry.into_mut() = PeerState::Backoff { timer, timer_deadline }
 			},
 
 			// Invalid state transitions.
 			st @ PeerState::Incoming { .. } => {
-				error!(target: "sub-libp2p", "PSM => Drop({:?}): Not enabled (Incoming).",
-					entry.key());
+				error!(target: "sub-libp2p", "PSM => Drop({}, {:?}): Not enabled (Incoming).",
+					entry.key().0, set_id);
 				*entry.into_mut() = st;
 				debug_assert!(!false);
 			},
@@ -967,18 +1051,19 @@ impl GenericProto {
 		if !incoming.alive {
 			debug!(target: "sub-libp2p", "PSM => Accept({:?}, {:?}): Obsolete incoming",
 				index, incoming.peer_id);
-			match self.peers.get_mut(&incoming.peer_id) {
+			match self.peers.get_mut(&(incoming.peer_id.clone(), incoming.set_id)) {
 				Some(PeerState::DisabledPendingEnable { .. }) |
 				Some(PeerState::Enabled { .. }) => {}
 				_ => {
-					debug!(target: "sub-libp2p", "PSM <= Dropped({:?})", incoming.peer_id);
-					self.peerset.dropped(incoming.peer_id);
+					debug!(target: "sub-libp2p", "PSM <= Dropped({}, {:?})",
+						incoming.peer_id, incoming.set_id);
+					self.peerset.dropped(incoming.set_id, incoming.peer_id);
 				},
 			}
 			return
 		}
 
-		let state = match self.peers.get_mut(&incoming.peer_id) {
+		let state = match self.peers.get_mut(&(incoming.peer_id.clone(), incoming.set_id)) {
 			Some(s) => s,
 			None => {
 				debug_assert!(false);
@@ -1000,7 +1085,7 @@ impl GenericProto {
 					self.events.push_back(NetworkBehaviourAction::NotifyHandler {
 						peer_id: incoming.peer_id.clone(),
 						handler: NotifyHandler::One(*connec_id),
-						event: NotifsHandlerIn::Open,
+						event: NotifsHandlerIn::Open { set_index: incoming.set_id.into() },
 					});
 					*connec_state = ConnectionState::Opening;
 				}
@@ -1033,7 +1118,7 @@ impl GenericProto {
 			return
 		}
 
-		let state = match self.peers.get_mut(&incoming.peer_id) {
+		let state = match self.peers.get_mut(&(incoming.peer_id.clone(), incoming.set_id)) {
 			Some(s) => s,
 			None => {
 				debug_assert!(false);
@@ -1055,7 +1140,7 @@ impl GenericProto {
 					self.events.push_back(NetworkBehaviourAction::NotifyHandler {
 						peer_id: incoming.peer_id.clone(),
 						handler: NotifyHandler::One(*connec_id),
-						event: NotifsHandlerIn::Close,
+						event: NotifsHandlerIn::Close { set_index: incoming.set_id.into() },
 					});
 					*connec_state = ConnectionState::Closing;
 				}
@@ -1088,303 +1173,311 @@ impl NetworkBehaviour for GenericProto {
 	}
 
 	fn inject_connection_established(&mut self, peer_id: &PeerId, conn: &ConnectionId, endpoint: &ConnectedPoint) {
-		match self.peers.entry(peer_id.clone()).or_insert(PeerState::Poisoned) {
-			// Requested | PendingRequest => Enabled
-			st @ &mut PeerState::Requested |
-			st @ &mut PeerState::PendingRequest { .. } => {
-				debug!(target: "sub-libp2p",
-					"Libp2p => Connected({}, {:?}): Connection was requested by PSM.",
-					peer_id, endpoint
-				);
-				debug!(target: "sub-libp2p", "Handler({:?}, {:?}) <= Open", peer_id, *conn);
-				self.events.push_back(NetworkBehaviourAction::NotifyHandler {
-					peer_id: peer_id.clone(),
-					handler: NotifyHandler::One(*conn),
-					event: NotifsHandlerIn::Open
-				});
+		for set_id in (0..self.notif_protocols.len()).map(sc_peerset::SetId::from) {
+			match self.peers.entry((peer_id.clone(), set_id)).or_insert(PeerState::Poisoned) {
+				// Requested | PendingRequest => Enabled
+				st @ &mut PeerState::Requested |
+				st @ &mut PeerState::PendingRequest { .. } => {
+					debug!(target: "sub-libp2p",
+						"Libp2p => Connected({}, {:?}, {:?}): Connection was requested by PSM.",
+						peer_id, set_id, endpoint
+					);
+					debug!(target: "sub-libp2p", "Handler({:?}, {:?}) <= Open({:?})", peer_id, *conn, set_id);
+					self.events.push_back(NetworkBehaviourAction::NotifyHandler {
+						peer_id: peer_id.clone(),
+						handler: NotifyHandler::One(*conn),
+						event: NotifsHandlerIn::Open { set_index: set_id.into() }
+					});
 
-				let mut connections = SmallVec::new();
-				connections.push((*conn, ConnectionState::Opening));
-				*st = PeerState::Enabled { connections };
-			}
+					let mut connections = SmallVec::new();
+					connections.push((*conn, ConnectionState::Opening));
+					*st = PeerState::Enabled { connections };
+				}
 
-			// Poisoned gets inserted above if the entry was missing.
-			// Ø | Backoff => Disabled
-			st @ &mut PeerState::Poisoned |
-			st @ &mut PeerState::Backoff { .. } => {
-				let backoff_until = if let PeerState::Backoff { timer_deadline, .. } = st {
-					Some(*timer_deadline)
-				} else {
-					None
-				};
-				debug!(target: "sub-libp2p",
-					"Libp2p => Connected({}, {:?}, {:?}): Not requested by PSM, disabling.",
-					peer_id, endpoint, *conn);
+				// Poisoned gets inserted above if the entry was missing.
+				// Ø | Backoff => Disabled
+				st @ &mut PeerState::Poisoned |
+				st @ &mut PeerState::Backoff { .. } => {
+					let backoff_until = if let PeerState::Backoff { timer_deadline, .. } = st {
+						Some(*timer_deadline)
+					} else {
+						None
+					};
+					debug!(target: "sub-libp2p",
+						"Libp2p => Connected({}, {:?}, {:?}, {:?}): Not requested by PSM, disabling.",
+						peer_id, set_id, endpoint, *conn);
 
-				let mut connections = SmallVec::new();
-				connections.push((*conn, ConnectionState::Closed));
-				*st = PeerState::Disabled { connections, backoff_until };
-			}
+					let mut connections = SmallVec::new();
+					connections.push((*conn, ConnectionState::Closed));
+					*st = PeerState::Disabled { connections, backoff_until };
+				}
 
-			// In all other states, add this new connection to the list of closed inactive
-			// connections.
-			PeerState::Incoming { connections, .. } |
-			PeerState::Disabled { connections, .. } |
-			PeerState::DisabledPendingEnable { connections, .. } |
-			PeerState::Enabled { connections, .. } => {
-				debug!(target: "sub-libp2p",
-					"Libp2p => Connected({}, {:?}, {:?}): Secondary connection. Leaving closed.",
-					peer_id, endpoint, *conn);
-				connections.push((*conn, ConnectionState::Closed));
+				// In all other states, add this new connection to the list of closed inactive
+				// connections.
+				PeerState::Incoming { connections, .. } |
+				PeerState::Disabled { connections, .. } |
+				PeerState::DisabledPendingEnable { connections, .. } |
+				PeerState::Enabled { connections, .. } => {
+					debug!(target: "sub-libp2p",
+						"Libp2p => Connected({}, {:?}, {:?}, {:?}): Secondary connection. Leaving closed.",
+						peer_id, set_id, endpoint, *conn);
+					connections.push((*conn, ConnectionState::Closed));
+				}
 			}
 		}
 	}
 
 	fn inject_connection_closed(&mut self, peer_id: &PeerId, conn: &ConnectionId, _endpoint: &ConnectedPoint) {
-		let mut entry = if let Entry::Occupied(entry) = self.peers.entry(peer_id.clone()) {
-			entry
-		} else {
-			error!(target: "sub-libp2p", "inject_connection_closed: State mismatch in the custom protos handler");
-			debug_assert!(false);
-			return
-		};
+		for set_id in (0..self.notif_protocols.len()).map(sc_peerset::SetId::from) {
+			let mut entry = if let Entry::Occupied(entry) = self.peers.entry((peer_id.clone(), set_id)) {
+				entry
+			} else {
+				error!(target: "sub-libp2p", "inject_connection_closed: State mismatch in the custom protos handler");
+				debug_assert!(false);
+				continue
+			};
 
-		match mem::replace(entry.get_mut(), PeerState::Poisoned) {
-			// Disabled => Disabled | Backoff | Ø
-			PeerState::Disabled { mut connections, backoff_until } => {
-				debug!(target: "sub-libp2p", "Libp2p => Disconnected({}, {:?}): Disabled.", peer_id, *conn);
+			match mem::replace(entry.get_mut(), PeerState::Poisoned) {
+				// Disabled => Disabled | Backoff | Ø
+				PeerState::Disabled { mut connections, backoff_until } => {
+					debug!(target: "sub-libp2p", "Libp2p => Disconnected({}, {:?}, {:?}): Disabled.",
+						peer_id, set_id, *conn);
 
-				if let Some(pos) = connections.iter().position(|(c, _)| *c == *conn) {
-					connections.remove(pos);
-				} else {
-					debug_assert!(false);
-					error!(target: "sub-libp2p",
-						"inject_connection_closed: State mismatch in the custom protos handler");
-				}
+					if let Some(pos) = connections.iter().position(|(c, _)| *c == *conn) {
+						connections.remove(pos);
+					} else {
+						debug_assert!(false);
+						error!(target: "sub-libp2p",
+							"inject_connection_closed: State mismatch in the custom protos handler");
+					}
 
-				if connections.is_empty() {
-					if let Some(until) = backoff_until {
-						let now = Instant::now();
-						if until > now {
-							let delay_id = self.next_delay_id;
-							self.next_delay_id.0 += 1;
-							let delay = futures_timer::Delay::new(until - now);
-							let peer_id = peer_id.clone();
-							self.delays.push(async move {
-								delay.await;
-								(delay_id, peer_id)
-							}.boxed());
-
-							*entry.get_mut() = PeerState::Backoff {
-								timer: delay_id,
-								timer_deadline: until,
-							};
+					if connections.is_empty() {
+						if let Some(until) = backoff_until {
+							let now = Instant::now();
+							if until > now {
+								let delay_id = self.next_delay_id;
+								self.next_delay_id.0 += 1;
+								let delay = futures_timer::Delay::new(until - now);
+								let peer_id = peer_id.clone();
+								self.delays.push(async move {
+									delay.await;
+									(delay_id, peer_id, set_id)
+								}.boxed());
+
+								*entry.get_mut() = PeerState::Backoff {
+									timer: delay_id,
+									timer_deadline: until,
+								};
+							} else {
+								entry.remove();
+							}
 						} else {
 							entry.remove();
 						}
 					} else {
-						entry.remove();
+						*entry.get_mut() = PeerState::Disabled { connections, backoff_until };
 					}
-				} else {
-					*entry.get_mut() = PeerState::Disabled { connections, backoff_until };
-				}
-			},
-
-			// DisabledPendingEnable => DisabledPendingEnable | Backoff
-			PeerState::DisabledPendingEnable { mut connections, timer_deadline, timer } => {
-				debug!(
-					target: "sub-libp2p",
-					"Libp2p => Disconnected({}, {:?}): Disabled but pending enable.",
-					peer_id, *conn
-				);
-
-				if let Some(pos) = connections.iter().position(|(c, _)| *c == *conn) {
-					connections.remove(pos);
-				} else {
-					debug_assert!(false);
-					error!(target: "sub-libp2p",
-						"inject_connection_closed: State mismatch in the custom protos handler");
-				}
+				},
 
-				if connections.is_empty() {
-					debug!(target: "sub-libp2p", "PSM <= Dropped({})", peer_id);
-					self.peerset.dropped(peer_id.clone());
-					*entry.get_mut() = PeerState::Backoff { timer, timer_deadline };
+				// DisabledPendingEnable => DisabledPendingEnable | Backoff
+				PeerState::DisabledPendingEnable { mut connections, timer_deadline, timer } => {
+					debug!(
+						target: "sub-libp2p",
+						"Libp2p => Disconnected({}, {:?}, {:?}): Disabled but pending enable.",
+						peer_id, set_id, *conn
+					);
 
-				} else {
-					*entry.get_mut() = PeerState::DisabledPendingEnable {
-						connections, timer_deadline, timer
-					};
-				}
-			},
+					if let Some(pos) = connections.iter().position(|(c, _)| *c == *conn) {
+						connections.remove(pos);
+					} else {
+						debug_assert!(false);
+						error!(target: "sub-libp2p",
+							"inject_connection_closed: State mismatch in the custom protos handler");
+					}
 
-			// Incoming => Incoming | Disabled | Backoff | Ø
-			PeerState::Incoming { mut connections, backoff_until } => {
-				debug!(
-					target: "sub-libp2p",
-					"Libp2p => Disconnected({}, {:?}): OpenDesiredByRemote.",
-					peer_id, *conn
-				);
+					if connections.is_empty() {
+						debug!(target: "sub-libp2p", "PSM <= Dropped({}, {:?})", peer_id, set_id);
+						self.peerset.dropped(set_id, peer_id.clone());
+						*entry.get_mut() = PeerState::Backoff { timer, timer_deadline };
 
-				debug_assert!(connections.iter().any(|(_, s)| matches!(s, ConnectionState::OpenDesiredByRemote)));
+					} else {
+						*entry.get_mut() = PeerState::DisabledPendingEnable {
+							connections, timer_deadline, timer
+						};
+					}
+				},
 
-				if let Some(pos) = connections.iter().position(|(c, _)| *c == *conn) {
-					connections.remove(pos);
-				} else {
-					debug_assert!(false);
-					error!(target: "sub-libp2p",
-						"inject_connection_closed: State mismatch in the custom protos handler");
-				}
+				// Incoming => Incoming | Disabled | Backoff | Ø
+				PeerState::Incoming { mut connections, backoff_until } => {
+					debug!(
+						target: "sub-libp2p",
+						"Libp2p => Disconnected({}, {:?}, {:?}): OpenDesiredByRemote.",
+						peer_id, set_id, *conn
+					);
 
-				let no_desired_left = !connections.iter().any(|(_, s)| {
-					matches!(s, ConnectionState::OpenDesiredByRemote)
-				});
+					debug_assert!(connections.iter().any(|(_, s)| matches!(s, ConnectionState::OpenDesiredByRemote)));
 
-				// If no connection is `OpenDesiredByRemote` anymore, clean up the peerset incoming
-				// request.
-				if no_desired_left {
-					// In the incoming state, we don't report "Dropped". Instead we will just
-					// ignore the corresponding Accept/Reject.
-					if let Some(state) = self.incoming.iter_mut()
-						.find(|i| i.alive && i.peer_id == *peer_id)
-					{
-						state.alive = false;
+					if let Some(pos) = connections.iter().position(|(c, _)| *c == *conn) {
+						connections.remove(pos);
 					} else {
-						error!(target: "sub-libp2p", "State mismatch in libp2p: no entry in \
-							incoming corresponding to an incoming state in peers");
 						debug_assert!(false);
+						error!(target: "sub-libp2p",
+							"inject_connection_closed: State mismatch in the custom protos handler");
 					}
-				}
 
-				if connections.is_empty() {
-					if let Some(until) = backoff_until {
-						let now = Instant::now();
-						if until > now {
-							let delay_id = self.next_delay_id;
-							self.next_delay_id.0 += 1;
-							let delay = futures_timer::Delay::new(until - now);
-							let peer_id = peer_id.clone();
-							self.delays.push(async move {
-								delay.await;
-								(delay_id, peer_id)
-							}.boxed());
-
-							*entry.get_mut() = PeerState::Backoff {
-								timer: delay_id,
-								timer_deadline: until,
-							};
+					let no_desired_left = !connections.iter().any(|(_, s)| {
+						matches!(s, ConnectionState::OpenDesiredByRemote)
+					});
+
+					// If no connection is `OpenDesiredByRemote` anymore, clean up the peerset incoming
+					// request.
+					if no_desired_left {
+						// In the incoming state, we don't report "Dropped". Instead we will just
+						// ignore the corresponding Accept/Reject.
+						if let Some(state) = self.incoming.iter_mut()
+							.find(|i| i.alive && i.set_id == set_id && i.peer_id == *peer_id)
+						{
+							state.alive = false;
+						} else {
+							error!(target: "sub-libp2p", "State mismatch in libp2p: no entry in \
+								incoming corresponding to an incoming state in peers");
+							debug_assert!(false);
+						}
+					}
+
+					if connections.is_empty() {
+						if let Some(until) = backoff_until {
+							let now = Instant::now();
+							if until > now {
+								let delay_id = self.next_delay_id;
+								self.next_delay_id.0 += 1;
+								let delay = futures_timer::Delay::new(until - now);
+								let peer_id = peer_id.clone();
+								self.delays.push(async move {
+									delay.await;
+									(delay_id, peer_id, set_id)
+								}.boxed());
+
+								*entry.get_mut() = PeerState::Backoff {
+									timer: delay_id,
+									timer_deadline: until,
+								};
+							} else {
+								entry.remove();
+							}
 						} else {
 							entry.remove();
 						}
+
+					} else if no_desired_left {
+						// If no connection is `OpenDesiredByRemote` anymore, switch to `Disabled`.
+						*entry.get_mut() = PeerState::Disabled { connections, backoff_until };
 					} else {
-						entry.remove();
+						*entry.get_mut() = PeerState::Incoming { connections, backoff_until };
 					}
-
-				} else if no_desired_left {
-					// If no connection is `OpenDesiredByRemote` anymore, switch to `Disabled`.
-					*entry.get_mut() = PeerState::Disabled { connections, backoff_until };
-				} else {
-					*entry.get_mut() = PeerState::Incoming { connections, backoff_until };
 				}
-			}
-
-			// Enabled => Enabled | Backoff
-			// Peers are always backed-off when disconnecting while Enabled.
-			PeerState::Enabled { mut connections } => {
-				debug!(
-					target: "sub-libp2p",
-					"Libp2p => Disconnected({}, {:?}): Enabled.",
-					peer_id, *conn
-				);
 
-				debug_assert!(connections.iter().any(|(_, s)|
-					matches!(s, ConnectionState::Opening | ConnectionState::Open(_))));
+				// Enabled => Enabled | Backoff
+				// Peers are always backed-off when disconnecting while Enabled.
+				PeerState::Enabled { mut connections } => {
+					debug!(
+						target: "sub-libp2p",
+						"Libp2p => Disconnected({}, {:?}, {:?}): Enabled.",
+						peer_id, set_id, *conn
+					);
 
-				if let Some(pos) = connections.iter().position(|(c, _)| *c == *conn) {
-					let (_, state) = connections.remove(pos);
-					if let ConnectionState::Open(_) = state {
-						if let Some((replacement_pos, replacement_sink)) = connections
-							.iter()
-							.enumerate()
-							.filter_map(|(num, (_, s))| {
-								match s {
-									ConnectionState::Open(s) => Some((num, s.clone())),
-									_ => None
+					debug_assert!(connections.iter().any(|(_, s)|
+						matches!(s, ConnectionState::Opening | ConnectionState::Open(_))));
+
+					if let Some(pos) = connections.iter().position(|(c, _)| *c == *conn) {
+						let (_, state) = connections.remove(pos);
+						if let ConnectionState::Open(_) = state {
+							if let Some((replacement_pos, replacement_sink)) = connections
+								.iter()
+								.enumerate()
+								.filter_map(|(num, (_, s))| {
+									match s {
+										ConnectionState::Open(s) => Some((num, s.clone())),
+										_ => None
+									}
+								})
+								.next()
+							{
+								if pos <= replacement_pos {
+									debug!(target: "sub-libp2p", "External API <= Sink replaced({}, {:?})",
+										peer_id, set_id);
+									let event = GenericProtoOut::CustomProtocolReplaced {
+										peer_id: peer_id.clone(),
+										set_id,
+										notifications_sink: replacement_sink,
+									};
+									self.events.push_back(NetworkBehaviourAction::GenerateEvent(event));
 								}
-							})
-							.next()
-						{
-							if pos <= replacement_pos {
-								debug!(target: "sub-libp2p", "External API <= Sink replaced({})", peer_id);
-								let event = GenericProtoOut::CustomProtocolReplaced {
+							} else {
+								debug!(target: "sub-libp2p", "External API <= Closed({}, {:?})", peer_id, set_id);
+								let event = GenericProtoOut::CustomProtocolClosed {
 									peer_id: peer_id.clone(),
-									notifications_sink: replacement_sink,
+									set_id,
 								};
 								self.events.push_back(NetworkBehaviourAction::GenerateEvent(event));
 							}
-						} else {
-							debug!(target: "sub-libp2p", "External API <= Closed({})", peer_id);
-							let event = GenericProtoOut::CustomProtocolClosed {
-								peer_id: peer_id.clone(),
-							};
-							self.events.push_back(NetworkBehaviourAction::GenerateEvent(event));
 						}
-					}
 
-				} else {
-					error!(target: "sub-libp2p",
-						"inject_connection_closed: State mismatch in the custom protos handler");
-					debug_assert!(false);
-				}
+					} else {
+						error!(target: "sub-libp2p",
+							"inject_connection_closed: State mismatch in the custom protos handler");
+						debug_assert!(false);
+					}
 
-				if connections.is_empty() {
-					debug!(target: "sub-libp2p", "PSM <= Dropped({})", peer_id);
-					self.peerset.dropped(peer_id.clone());
-					let ban_dur = Uniform::new(5, 10).sample(&mut rand::thread_rng());
+					if connections.is_empty() {
+						debug!(target: "sub-libp2p", "PSM <= Dropped({}, {:?})", peer_id, set_id);
+						self.peerset.dropped(set_id, peer_id.clone());
+						let ban_dur = Uniform::new(5, 10).sample(&mut rand::thread_rng());
 
-					let delay_id = self.next_delay_id;
-					self.next_delay_id.0 += 1;
-					let delay = futures_timer::Delay::new(Duration::from_secs(ban_dur));
-					let peer_id = peer_id.clone();
-					self.delays.push(async move {
-						delay.await;
-						(delay_id, peer_id)
-					}.boxed());
+						let delay_id = self.next_delay_id;
+						self.next_delay_id.0 += 1;
+						let delay = futures_timer::Delay::new(Duration::from_secs(ban_dur));
+						let peer_id = peer_id.clone();
+						self.delays.push(async move {
+							delay.await;
+							(delay_id, peer_id, set_id)
+						}.boxed());
 
-					*entry.get_mut() = PeerState::Backoff {
-						timer: delay_id,
-						timer_deadline: Instant::now() + Duration::from_secs(ban_dur),
-					};
+						*entry.get_mut() = PeerState::Backoff {
+							timer: delay_id,
+							timer_deadline: Instant::now() + Duration::from_secs(ban_dur),
+						};
 
-				} else if !connections.iter().any(|(_, s)|
-					matches!(s, ConnectionState::Opening | ConnectionState::Open(_)))
-				{
-					debug!(target: "sub-libp2p", "PSM <= Dropped({:?})", peer_id);
-					self.peerset.dropped(peer_id.clone());
+					} else if !connections.iter().any(|(_, s)|
+						matches!(s, ConnectionState::Opening | ConnectionState::Open(_)))
+					{
+						debug!(target: "sub-libp2p", "PSM <= Dropped({}, {:?})", peer_id, set_id);
+						self.peerset.dropped(set_id, peer_id.clone());
 
-					*entry.get_mut() = PeerState::Disabled {
-						connections,
-						backoff_until: None
-					};
+						*entry.get_mut() = PeerState::Disabled {
+							connections,
+							backoff_until: None
+						};
 
-				} else {
-					*entry.get_mut() = PeerState::Enabled { connections };
+					} else {
+						*entry.get_mut() = PeerState::Enabled { connections };
+					}
 				}
-			}
 
-			PeerState::Requested |
-			PeerState::PendingRequest { .. } |
-			PeerState::Backoff { .. } => {
-				// This is a serious bug either in this state machine or in libp2p.
-				error!(target: "sub-libp2p",
-					"`inject_connection_closed` called for unknown peer {}",
-					peer_id);
-				debug_assert!(false);
-			},
-			PeerState::Poisoned => {
-				error!(target: "sub-libp2p", "State of peer {} is poisoned", peer_id);
-				debug_assert!(false);
-			},
+				PeerState::Requested |
+				PeerState::PendingRequest { .. } |
+				PeerState::Backoff { .. } => {
+					// This is a serious bug either in this state machine or in libp2p.
+					error!(target: "sub-libp2p",
+						"`inject_connection_closed` called for unknown peer {}",
+						peer_id);
+					debug_assert!(false);
+				},
+				PeerState::Poisoned => {
+					error!(target: "sub-libp2p", "State of peer {} is poisoned", peer_id);
+					debug_assert!(false);
+				},
+			}
 		}
 	}
 
@@ -1396,61 +1489,63 @@ impl NetworkBehaviour for GenericProto {
 	}
 
 	fn inject_dial_failure(&mut self, peer_id: &PeerId) {
-		if let Entry::Occupied(mut entry) = self.peers.entry(peer_id.clone()) {
-			match mem::replace(entry.get_mut(), PeerState::Poisoned) {
-				// The peer is not in our list.
-				st @ PeerState::Backoff { .. } => {
-					trace!(target: "sub-libp2p", "Libp2p => Dial failure for {:?}", peer_id);
-					*entry.into_mut() = st;
-				},
+		for set_id in (0..self.notif_protocols.len()).map(sc_peerset::SetId::from) {
+			if let Entry::Occupied(mut entry) = self.peers.entry((peer_id.clone(), set_id)) {
+				match mem::replace(entry.get_mut(), PeerState::Poisoned) {
+					// The peer is not in our list.
+					st @ PeerState::Backoff { .. } => {
+						trace!(target: "sub-libp2p", "Libp2p => Dial failure for {:?}", peer_id);
+						*entry.into_mut() = st;
+					},
 
-				// "Basic" situation: we failed to reach a peer that the peerset requested.
-				st @ PeerState::Requested |
-				st @ PeerState::PendingRequest { .. } => {
-					debug!(target: "sub-libp2p", "Libp2p => Dial failure for {:?}", peer_id);
+					// "Basic" situation: we failed to reach a peer that the peerset requested.
+					st @ PeerState::Requested |
+					st @ PeerState::PendingRequest { .. } => {
+						debug!(target: "sub-libp2p", "Libp2p => Dial failure for {:?}", peer_id);
 
-					debug!(target: "sub-libp2p", "PSM <= Dropped({:?})", peer_id);
-					self.peerset.dropped(peer_id.clone());
+						debug!(target: "sub-libp2p", "PSM <= Dropped({}, {:?})", peer_id, set_id);
+						self.peerset.dropped(set_id, peer_id.clone());
 
-					let now = Instant::now();
-					let ban_duration = match st {
-						PeerState::PendingRequest { timer_deadline, .. } if timer_deadline > now =>
-							cmp::max(timer_deadline - now, Duration::from_secs(5)),
-						_ => Duration::from_secs(5)
-					};
+						let now = Instant::now();
+						let ban_duration = match st {
+							PeerState::PendingRequest { timer_deadline, .. } if timer_deadline > now =>
+								cmp::max(timer_deadline - now, Duration::from_secs(5)),
+							_ => Duration::from_secs(5)
+						};
 
-					let delay_id = self.next_delay_id;
-					self.next_delay_id.0 += 1;
-					let delay = futures_timer::Delay::new(ban_duration);
-					let peer_id = peer_id.clone();
-					self.delays.push(async move {
-						delay.await;
-						(delay_id, peer_id)
-					}.boxed());
+						let delay_id = self.next_delay_id;
+						self.next_delay_id.0 += 1;
+						let delay = futures_timer::Delay::new(ban_duration);
+						let peer_id = peer_id.clone();
+						self.delays.push(async move {
+							delay.await;
+							(delay_id, peer_id, set_id)
+						}.boxed());
 
-					*entry.into_mut() = PeerState::Backoff {
-						timer: delay_id,
-						timer_deadline: now + ban_duration,
-					};
-				},
+						*entry.into_mut() = PeerState::Backoff {
+							timer: delay_id,
+							timer_deadline: now + ban_duration,
+						};
+					},
 
-				// We can still get dial failures even if we are already connected to the peer,
-				// as an extra diagnostic for an earlier attempt.
-				st @ PeerState::Disabled { .. } | st @ PeerState::Enabled { .. } |
-					st @ PeerState::DisabledPendingEnable { .. } | st @ PeerState::Incoming { .. } => {
-					debug!(target: "sub-libp2p", "Libp2p => Dial failure for {:?}", peer_id);
-					*entry.into_mut() = st;
-				},
+					// We can still get dial failures even if we are already connected to the peer,
+					// as an extra diagnostic for an earlier attempt.
+					st @ PeerState::Disabled { .. } | st @ PeerState::Enabled { .. } |
+						st @ PeerState::DisabledPendingEnable { .. } | st @ PeerState::Incoming { .. } => {
+						debug!(target: "sub-libp2p", "Libp2p => Dial failure for {:?}", peer_id);
+						*entry.into_mut() = st;
+					},
 
-				PeerState::Poisoned => {
-					error!(target: "sub-libp2p", "State of {:?} is poisoned", peer_id);
-					debug_assert!(false);
-				},
-			}
+					PeerState::Poisoned => {
+						error!(target: "sub-libp2p", "State of {:?} is poisoned", peer_id);
+						debug_assert!(false);
+					},
+				}
 
-		} else {
-			// The peer is not in our list.
-			trace!(target: "sub-libp2p", "Libp2p => Dial failure for {:?}", peer_id);
+			} else {
+				// The peer is not in our list.
+				trace!(target: "sub-libp2p", "Libp2p => Dial failure for {:?}", peer_id);
+			}
 		}
 	}
 
@@ -1461,12 +1556,14 @@ impl NetworkBehaviour for GenericProto {
 		event: NotifsHandlerOut,
 	) {
 		match event {
-			NotifsHandlerOut::OpenDesiredByRemote => {
+			NotifsHandlerOut::OpenDesiredByRemote { set_index } => {
+				let set_id = sc_peerset::SetId::from(set_index);
+
 				debug!(target: "sub-libp2p",
-					"Handler({:?}, {:?}]) => OpenDesiredByRemote",
-					source, connection);
+					"Handler({:?}, {:?}]) => OpenDesiredByRemote({:?})",
+					source, connection, set_id);
 
-				let mut entry = if let Entry::Occupied(entry) = self.peers.entry(source.clone()) {
+				let mut entry = if let Entry::Occupied(entry) = self.peers.entry((source.clone(), set_id)) {
 					entry
 				} else {
 					error!(target: "sub-libp2p", "OpenDesiredByRemote: State mismatch in the custom protos handler");
@@ -1509,11 +1606,12 @@ impl NetworkBehaviour for GenericProto {
 
 						if let Some((_, connec_state)) = connections.iter_mut().find(|(c, _)| *c == connection) {
 							if let ConnectionState::Closed = *connec_state {
-								debug!(target: "sub-libp2p", "Handler({:?}, {:?}) <= Open", source, connection);
+								debug!(target: "sub-libp2p", "Handler({:?}, {:?}) <= Open({:?})",
+									source, connection, set_id);
 								self.events.push_back(NetworkBehaviourAction::NotifyHandler {
 									peer_id: source,
 									handler: NotifyHandler::One(connection),
-									event: NotifsHandlerIn::Open,
+									event: NotifsHandlerIn::Open { set_index },
 								});
 								*connec_state = ConnectionState::Opening;
 							} else {
@@ -1546,11 +1644,12 @@ impl NetworkBehaviour for GenericProto {
 								let incoming_id = self.next_incoming_index;
 								self.next_incoming_index.0 += 1;
 
-								debug!(target: "sub-libp2p", "PSM <= Incoming({}, {:?}).",
-									source, incoming_id);
-								self.peerset.incoming(source.clone(), incoming_id);
+								debug!(target: "sub-libp2p", "PSM <= Incoming({}, {:?}, {:?}).",
+									source, set_id, incoming_id);
+								self.peerset.incoming(set_id, source.clone(), incoming_id);
 								self.incoming.push(IncomingPeer {
 									peer_id: source.clone(),
+									set_id,
 									alive: true,
 									incoming_id,
 								});
@@ -1580,12 +1679,12 @@ impl NetworkBehaviour for GenericProto {
 					PeerState::DisabledPendingEnable { mut connections, timer, timer_deadline } => {
 						if let Some((_, connec_state)) = connections.iter_mut().find(|(c, _)| *c == connection) {
 							if let ConnectionState::Closed = *connec_state {
-								debug!(target: "sub-libp2p", "Handler({:?}, {:?}) <= Open",
-									source, connection);
+								debug!(target: "sub-libp2p", "Handler({:?}, {:?}) <= Open({:?})",
+									source, connection, set_id);
 								self.events.push_back(NetworkBehaviourAction::NotifyHandler {
 									peer_id: source.clone(),
 									handler: NotifyHandler::One(connection),
-									event: NotifsHandlerIn::Open,
+									event: NotifsHandlerIn::Open { set_index },
 								});
 								*connec_state = ConnectionState::Opening;
 
@@ -1624,12 +1723,14 @@ impl NetworkBehaviour for GenericProto {
 				};
 			}
 
-			NotifsHandlerOut::CloseDesired => {
+			NotifsHandlerOut::CloseDesired { set_index } => {
+				let set_id = sc_peerset::SetId::from(set_index);
+
 				debug!(target: "sub-libp2p",
-					"Handler({}, {:?}) => CloseDesired",
-					source, connection);
+					"Handler({}, {:?}) => CloseDesired({:?})",
+					source, connection, set_id);
 
-				let mut entry = if let Entry::Occupied(entry) = self.peers.entry(source.clone()) {
+				let mut entry = if let Entry::Occupied(entry) = self.peers.entry((source.clone(), set_id)) {
 					entry
 				} else {
 					error!(target: "sub-libp2p", "CloseDesired: State mismatch in the custom protos handler");
@@ -1660,11 +1761,11 @@ impl NetworkBehaviour for GenericProto {
 						debug_assert!(matches!(connections[pos].1, ConnectionState::Open(_)));
 						connections[pos].1 = ConnectionState::Closing;
 
-						debug!(target: "sub-libp2p", "Handler({}, {:?}) <= Close", source, connection);
+						debug!(target: "sub-libp2p", "Handler({}, {:?}) <= Close({:?})", source, connection, set_id);
 						self.events.push_back(NetworkBehaviourAction::NotifyHandler {
 							peer_id: source.clone(),
 							handler: NotifyHandler::One(connection),
-							event: NotifsHandlerIn::Close,
+							event: NotifsHandlerIn::Close { set_index },
 						});
 
 						if let Some((replacement_pos, replacement_sink)) = connections
@@ -1679,9 +1780,11 @@ impl NetworkBehaviour for GenericProto {
 							.next()
 						{
 							if pos <= replacement_pos {
-								debug!(target: "sub-libp2p", "External API <= Sink replaced({:?})", source);
+								debug!(target: "sub-libp2p", "External API <= Sink replaced({:?}, {:?})",
+									source, set_id);
 								let event = GenericProtoOut::CustomProtocolReplaced {
 									peer_id: source,
+									set_id,
 									notifications_sink: replacement_sink,
 								};
 								self.events.push_back(NetworkBehaviourAction::GenerateEvent(event));
@@ -1691,8 +1794,8 @@ impl NetworkBehaviour for GenericProto {
 						} else {
 							// List of open connections wasn't empty before but now it is.
 							if !connections.iter().any(|(_, s)| matches!(s, ConnectionState::Opening)) {
-								debug!(target: "sub-libp2p", "PSM <= Dropped({:?})", source);
-								self.peerset.dropped(source.clone());
+								debug!(target: "sub-libp2p", "PSM <= Dropped({:?}, {:?})", source, set_id);
+								self.peerset.dropped(set_id, source.clone());
 								*entry.into_mut() = PeerState::Disabled {
 									connections, backoff_until: None
 								};
@@ -1700,9 +1803,10 @@ impl NetworkBehaviour for GenericProto {
 								*entry.into_mut() = PeerState::Enabled { connections };
 							}
 
-							debug!(target: "sub-libp2p", "External API <= Closed({:?})", source);
+							debug!(target: "sub-libp2p", "External API <= Closed({:?}, {:?})", source, set_id);
 							let event = GenericProtoOut::CustomProtocolClosed {
 								peer_id: source,
+								set_id,
 							};
 							self.events.push_back(NetworkBehaviourAction::GenerateEvent(event));
 						}
@@ -1724,12 +1828,14 @@ impl NetworkBehaviour for GenericProto {
 				}
 			}
 
-			NotifsHandlerOut::CloseResult => {
+			NotifsHandlerOut::CloseResult { set_index } => {
+				let set_id = sc_peerset::SetId::from(set_index);
+
 				debug!(target: "sub-libp2p",
-					"Handler({}, {:?}) => CloseResult",
-					source, connection);
+					"Handler({}, {:?}) => CloseResult({:?})",
+					source, connection, set_id);
 
-				match self.peers.get_mut(&source) {
+				match self.peers.get_mut(&(source.clone(), set_id)) {
 					// Move the connection from `Closing` to `Closed`.
 					Some(PeerState::DisabledPendingEnable { connections, .. }) |
 					Some(PeerState::Disabled { connections, .. }) |
@@ -1755,12 +1861,14 @@ impl NetworkBehaviour for GenericProto {
 				}
 			}
 
-			NotifsHandlerOut::OpenResultOk { received_handshake, notifications_sink, .. } => {
+			NotifsHandlerOut::OpenResultOk { set_index, received_handshake, notifications_sink, .. } => {
+				let set_id = sc_peerset::SetId::from(set_index);
+
 				debug!(target: "sub-libp2p",
-					"Handler({}, {:?}) => OpenResultOk",
-					source, connection);
+					"Handler({}, {:?}) => OpenResultOk({:?})",
+					source, connection, set_id);
 
-				match self.peers.get_mut(&source) {
+				match self.peers.get_mut(&(source.clone(), set_id)) {
 					Some(PeerState::Enabled { connections, .. }) => {
 						debug_assert!(connections.iter().any(|(_, s)|
 							matches!(s, ConnectionState::Opening | ConnectionState::Open(_))));
@@ -1770,9 +1878,10 @@ impl NetworkBehaviour for GenericProto {
 							*c == connection && matches!(s, ConnectionState::Opening))
 						{
 							if !any_open {
-								debug!(target: "sub-libp2p", "External API <= Open({:?})", source);
+								debug!(target: "sub-libp2p", "External API <= Open({:?}, {:?})", source, set_id);
 								let event = GenericProtoOut::CustomProtocolOpen {
 									peer_id: source,
+									set_id,
 									received_handshake,
 									notifications_sink: notifications_sink.clone(),
 								};
@@ -1813,12 +1922,14 @@ impl NetworkBehaviour for GenericProto {
 				}
 			}
 
-			NotifsHandlerOut::OpenResultErr => {
+			NotifsHandlerOut::OpenResultErr { set_index } => {
+				let set_id = sc_peerset::SetId::from(set_index);
+
 				debug!(target: "sub-libp2p",
-					"Handler({:?}, {:?}) => OpenResultErr",
-					source, connection);
+					"Handler({:?}, {:?}) => OpenResultErr({:?})",
+					source, connection, set_id);
 
-				let mut entry = if let Entry::Occupied(entry) = self.peers.entry(source.clone()) {
+				let mut entry = if let Entry::Occupied(entry) = self.peers.entry((source.clone(), set_id)) {
 					entry
 				} else {
 					error!(target: "sub-libp2p", "OpenResultErr: State mismatch in the custom protos handler");
@@ -1849,8 +1960,8 @@ impl NetworkBehaviour for GenericProto {
 						if !connections.iter().any(|(_, s)|
 							matches!(s, ConnectionState::Opening | ConnectionState::Open(_)))
 						{
-							debug!(target: "sub-libp2p", "PSM <= Dropped({:?})", source);
-							self.peerset.dropped(source.clone());
+							debug!(target: "sub-libp2p", "PSM <= Dropped({:?}, {:?})", source, set_id);
+							self.peerset.dropped(set_id, source.clone());
 
 							*entry.into_mut() = PeerState::Disabled {
 								connections,
@@ -1900,7 +2011,8 @@ impl NetworkBehaviour for GenericProto {
 			}
 
 			NotifsHandlerOut::CustomMessage { message } => {
-				if self.is_open(&source) {
+				// The legacy substream belongs to the first set.
+				if self.is_open(&source, sc_peerset::SetId::from(0)) {
 					trace!(target: "sub-libp2p", "Handler({:?}) => Message", source);
 					trace!(target: "sub-libp2p", "External API <= Message({:?})", source);
 					let event = GenericProtoOut::LegacyMessage {
@@ -1918,8 +2030,9 @@ impl NetworkBehaviour for GenericProto {
 				}
 			}
 
-			NotifsHandlerOut::Notification { protocol_name, message } => {
-				if self.is_open(&source) {
+			NotifsHandlerOut::Notification { set_index, protocol_name, message } => {
+				let set_id = sc_peerset::SetId::from(set_index);
+				if self.is_open(&source, set_id) {
 					trace!(
 						target: "sub-libp2p",
 						"Handler({:?}) => Notification({:?}, {} bytes)",
@@ -1930,6 +2043,7 @@ impl NetworkBehaviour for GenericProto {
 					trace!(target: "sub-libp2p", "External API <= Message({:?}, {:?})", protocol_name, source);
 					let event = GenericProtoOut::Notification {
 						peer_id: source,
+						set_id,
 						protocol_name,
 						message,
 					};
@@ -1972,11 +2086,11 @@ impl NetworkBehaviour for GenericProto {
 				Poll::Ready(Some(sc_peerset::Message::Reject(index))) => {
 					self.peerset_report_reject(index);
 				}
-				Poll::Ready(Some(sc_peerset::Message::Connect(id))) => {
-					self.peerset_report_connect(id);
+				Poll::Ready(Some(sc_peerset::Message::Connect { peer_id, set_id })) => {
+					self.peerset_report_connect(peer_id, set_id);
 				}
-				Poll::Ready(Some(sc_peerset::Message::Drop(id))) => {
-					self.peerset_report_disconnect(id);
+				Poll::Ready(Some(sc_peerset::Message::Drop { peer_id, set_id })) => {
+					self.peerset_report_disconnect(peer_id, set_id);
 				}
 				Poll::Ready(None) => {
 					error!(target: "sub-libp2p", "Peerset receiver stream has returned None");
@@ -1986,9 +2100,9 @@ impl NetworkBehaviour for GenericProto {
 			}
 		}
 
-		while let Poll::Ready(Some((delay_id, peer_id))) =
+		while let Poll::Ready(Some((delay_id, peer_id, set_id))) =
 			Pin::new(&mut self.delays).poll_next(cx) {
-			let peer_state = match self.peers.get_mut(&peer_id) {
+			let peer_state = match self.peers.get_mut(&(peer_id.clone(), set_id)) {
 				Some(s) => s,
 				// We intentionally never remove elements from `delays`, and it may
 				// thus contain peers which are now gone. This is a normal situation.
@@ -1998,7 +2112,7 @@ impl NetworkBehaviour for GenericProto {
 			match peer_state {
 				PeerState::Backoff { timer, .. } if *timer == delay_id => {
 					debug!(target: "sub-libp2p", "Libp2p <= Clean up ban of {:?} from the state", peer_id);
-					self.peers.remove(&peer_id);
+					self.peers.remove(&(peer_id, set_id));
 				}
 
 				PeerState::PendingRequest { timer, .. } if *timer == delay_id => {
@@ -2017,12 +2131,12 @@ impl NetworkBehaviour for GenericProto {
 					if let Some((connec_id, connec_state)) = connections.iter_mut()
 						.find(|(_, s)| matches!(s, ConnectionState::Closed))
 					{
-						debug!(target: "sub-libp2p", "Handler({:?}, {:?}) <= Open (ban expired)",
-							peer_id, *connec_id);
+						debug!(target: "sub-libp2p", "Handler({:?}, {:?}) <= Open({:?}) (ban expired)",
+							peer_id, *connec_id, set_id);
 						self.events.push_back(NetworkBehaviourAction::NotifyHandler {
 							peer_id: peer_id.clone(),
 							handler: NotifyHandler::One(*connec_id),
-							event: NotifsHandlerIn::Open,
+							event: NotifsHandlerIn::Open { set_index: set_id.into() },
 						});
 						*connec_state = ConnectionState::Opening;
 						*peer_state = PeerState::Enabled {
@@ -2034,7 +2148,7 @@ impl NetworkBehaviour for GenericProto {
 						let timer = *timer;
 						self.delays.push(async move {
 							delay.await;
-							(timer, peer_id)
+							(timer, peer_id, set_id)
 						}.boxed());
 					}
 				}
@@ -22,7 +22,12 @@
 //!
 //! # Usage
 //!
-//! From an API perspective, the [`NotifsHandler`] is always in one of the following state (see [`State`]):
+//! The notifications protocols are grouped into *sets*. The first set additionally contains the
+//! legacy substream. Each set is opened and closed independently from the others, and all the
+//! messages described below are relative to a specific set, designated by its index.
+//!
+//! From an API perspective, for each set, the [`NotifsHandler`] is always in one of the following
+//! state (see [`State`]):
 //!
 //! - Closed substreams. This is the initial state.
 //! - Closed substreams, but remote desires them to be open.
@@ -108,13 +113,9 @@ const INITIAL_KEEPALIVE_TIME: Duration = Duration::from_secs(5);
 ///
 /// See the documentation at the module level for more information.
 pub struct NotifsHandlerProto {
-	/// Prototypes for upgrades for inbound substreams, and the message we respond with in the
-	/// handshake.
-	in_protocols: Vec<(NotificationsIn, Arc<RwLock<Vec<u8>>>)>,
-
-	/// Name of protocols available for outbound substreams, and the initial handshake message we
-	/// send.
-	out_protocols: Vec<(Cow<'static, str>, Arc<RwLock<Vec<u8>>>)>,
+	/// For each set, name of the notifications protocols of the set, and the handshake message
+	/// we send.
+	sets: Vec<Vec<(Cow<'static, str>, Arc<RwLock<Vec<u8>>>)>>,
 
 	/// Configuration for the legacy protocol upgrade.
 	legacy_protocol: RegisteredProtocol,
@@ -124,13 +125,12 @@ pub struct NotifsHandlerProto {
 ///
 /// See the documentation at the module level for more information.
 pub struct NotifsHandler {
-	/// Prototypes for upgrades for inbound substreams, and the message we respond with in the
-	/// handshake.
-	in_protocols: Vec<(NotificationsIn, Arc<RwLock<Vec<u8>>>)>,
+	/// Protocols and state of each set.
+	sets: Vec<ProtocolsSet>,
 
-	/// Name of protocols available for outbound substreams, and the initial handshake message we
-	/// send.
-	out_protocols: Vec<(Cow<'static, str>, Arc<RwLock<Vec<u8>>>)>,
+	/// For each inbound protocol, in the order of the inbound upgrade, the index of its set in
+	/// [`NotifsHandler::sets`] and its index within [`ProtocolsSet::in_protocols`].
+	in_protocols_index: Vec<(usize, usize)>,
 
 	/// When the connection with the remote has been successfully established.
 	when_connection_open: Instant,
@@ -141,9 +141,6 @@ pub struct NotifsHandler {
 	/// Remote we are connected to.
 	peer_id: PeerId,
 
-	/// State of this handler.
-	state: State,
-
 	/// Configuration for the legacy protocol upgrade.
 	legacy_protocol: RegisteredProtocol,
 
@@ -155,15 +152,31 @@ pub struct NotifsHandler {
 
 	/// Events to return in priority from `poll`.
 	events_queue: VecDeque<
-		ProtocolsHandlerEvent<NotificationsOut, usize, NotifsHandlerOut, NotifsHandlerError>
+		ProtocolsHandlerEvent<NotificationsOut, (usize, usize), NotifsHandlerOut, NotifsHandlerError>
 	>,
 }
 
+/// Notifications protocols of a set, and the state of their substreams.
+struct ProtocolsSet {
+	/// Prototypes for upgrades for inbound substreams, and the message we respond with in the
+	/// handshake.
+	in_protocols: Vec<(NotificationsIn, Arc<RwLock<Vec<u8>>>)>,
+
+	/// Name of protocols available for outbound substreams, and the initial handshake message we
+	/// send.
+	out_protocols: Vec<(Cow<'static, str>, Arc<RwLock<Vec<u8>>>)>,
+
+	/// State of the substreams of this set.
+	state: State,
+}
+
+/// State of a set of protocols.
+///
 /// See the module-level documentation to learn about the meaning of these variants.
 enum State {
 	/// Handler is in the "Closed" state.
 	Closed {
-		/// Vec of the same length as [`NotifsHandler::out_protocols`]. For each protocol, contains
+		/// Vec of the same length as [`ProtocolsSet::out_protocols`]. For each protocol, contains
 		/// a boolean indicating whether an outgoing substream is still in the process of being
 		/// opened.
 		pending_opening: Vec<bool>,
@@ -171,7 +184,7 @@ enum State {
 
 	/// Handler is in the "Closed" state. A [`NotifsHandlerOut::OpenDesiredByRemote`] has been emitted.
 	OpenDesiredByRemote {
-		/// Vec of the same length as [`NotifsHandler::in_protocols`]. For each protocol, contains
+		/// Vec of the same length as [`ProtocolsSet::in_protocols`]. For each protocol, contains
 		/// a substream opened by the remote and that hasn't been accepted/rejected yet.
 		///
 		/// Must always contain at least one `Some`.
@@ -193,14 +206,14 @@ enum State {
 		/// be reported through the external API.
 		pending_handshake: Option<Vec<u8>>,
 
-		/// Vec of the same length as [`NotifsHandler::in_protocols`]. For each protocol, contains
+		/// Vec of the same length as [`ProtocolsSet::in_protocols`]. For each protocol, contains
 		/// a substream opened by the remote and that has been accepted.
 		///
 		/// Contrary to [`State::OpenDesiredByRemote::in_substreams`], it is possible for this to
 		/// contain only `None`s.
 		in_substreams: Vec<Option<NotificationsInSubstream<NegotiatedSubstream>>>,
 
-		/// Vec of the same length as [`NotifsHandler::out_protocols`]. For each protocol, contains
+		/// Vec of the same length as [`ProtocolsSet::out_protocols`]. For each protocol, contains
 		/// an outbound substream that has been accepted by the remote.
 		///
 		/// Items that contain `None` mean that a substream is still being opened or has been
@@ -230,7 +243,7 @@ enum State {
 			stream::Fuse<mpsc::Receiver<NotificationsSinkMessage>>
 		>,
 
-		/// Vec of the same length as [`NotifsHandler::out_protocols`]. For each protocol, contains
+		/// Vec of the same length as [`ProtocolsSet::out_protocols`]. For each protocol, contains
 		/// an outbound substream that has been accepted by the remote.
 		///
 		/// On transition to [`State::Open`], all the elements must be `Some`. Elements are
@@ -238,7 +251,7 @@ enum State {
 		/// must be true.
 		out_substreams: Vec<Option<NotificationsOutSubstream<NegotiatedSubstream>>>,
 
-		/// Vec of the same length as [`NotifsHandler::in_protocols`]. For each protocol, contains
+		/// Vec of the same length as [`ProtocolsSet::in_protocols`]. For each protocol, contains
 		/// a substream opened by the remote and that has been accepted.
 		///
 		/// Contrary to [`State::OpenDesiredByRemote::in_substreams`], it is possible for this to
@@ -256,25 +269,38 @@ impl IntoProtocolsHandler for NotifsHandlerProto {
 	type Handler = NotifsHandler;
 
 	fn inbound_protocol(&self) -> SelectUpgrade<UpgradeCollec<NotificationsIn>, RegisteredProtocol> {
-		let in_protocols = self.in_protocols.iter()
-			.map(|(h, _)| h.clone())
+		let in_protocols = self.sets.iter()
+			.flat_map(|set| set.iter())
+			.map(|(name, _)| NotificationsIn::new(name.clone()))
 			.collect::<UpgradeCollec<_>>();
 
 		SelectUpgrade::new(in_protocols, self.legacy_protocol.clone())
 	}
 
 	fn into_handler(self, peer_id: &PeerId, connected_point: &ConnectedPoint) -> Self::Handler {
-		let num_out_proto = self.out_protocols.len();
+		let in_protocols_index = self.sets.iter()
+			.enumerate()
+			.flat_map(|(set_index, set)| (0..set.len()).map(move |num| (set_index, num)))
+			.collect();
+
+		let sets = self.sets.into_iter()
+			.map(|protocols| ProtocolsSet {
+				in_protocols: protocols.iter()
+					.map(|(name, handshake)| (NotificationsIn::new(name.clone()), handshake.clone()))
+					.collect(),
+				state: State::Closed {
+					pending_opening: (0..protocols.len()).map(|_| false).collect(),
+				},
+				out_protocols: protocols,
+			})
+			.collect();
 
 		NotifsHandler {
-			in_protocols: self.in_protocols,
-			out_protocols: self.out_protocols,
+			sets,
+			in_protocols_index,
 			peer_id: peer_id.clone(),
 			endpoint: connected_point.clone(),
 			when_connection_open: Instant::now(),
-			state: State::Closed {
-				pending_opening: (0..num_out_proto).map(|_| false).collect(),
-			},
 			legacy_protocol: self.legacy_protocol,
 			legacy_substreams: SmallVec::new(),
 			legacy_shutdown: SmallVec::new(),
@@ -286,20 +312,26 @@ impl IntoProtocolsHandler for NotifsHandlerProto {
 /// Event that can be received by a `NotifsHandler`.
 #[derive(Debug, Clone)]
 pub enum NotifsHandlerIn {
-	/// Instruct the handler to open the notification substreams.
+	/// Instruct the handler to open the notification substreams of a set.
 	///
 	/// Must always be answered by a [`NotifsHandlerOut::OpenResultOk`] or a
 	/// [`NotifsHandlerOut::OpenResultErr`] event.
 	///
 	/// Importantly, it is forbidden to send a [`NotifsHandlerIn::Open`] while a previous one is
 	/// already in the fly. It is however possible if a `Close` is still in the fly.
-	Open,
+	Open {
+		/// Index of the set to open.
+		set_index: usize,
+	},
 
-	/// Instruct the handler to close the notification substreams, or reject any pending incoming
-	/// substream request.
+	/// Instruct the handler to close the notification substreams of a set, or reject any pending
+	/// incoming substream request.
 	///
 	/// Must always be answered by a [`NotifsHandlerOut::CloseResult`] event.
-	Close,
+	Close {
+		/// Index of the set to close.
+		set_index: usize,
+	},
 }
 
 /// Event that can be emitted by a `NotifsHandler`.
@@ -307,6 +339,8 @@ pub enum NotifsHandlerIn {
 pub enum NotifsHandlerOut {
 	/// Acknowledges a [`NotifsHandlerIn::Open`].
 	OpenResultOk {
+		/// Index of the set that has been opened.
+		set_index: usize,
 		/// The endpoint of the connection that is open for custom protocols.
 		endpoint: ConnectedPoint,
 		/// Handshake that was sent to us.
@@ -318,27 +352,39 @@ pub enum NotifsHandlerOut {
 
 	/// Acknowledges a [`NotifsHandlerIn::Open`]. The remote has refused the attempt to open
 	/// notification substreams.
-	OpenResultErr,
+	OpenResultErr {
+		/// Index of the set that has failed to open.
+		set_index: usize,
+	},
 
 	/// Acknowledges a [`NotifsHandlerIn::Close`].
-	CloseResult,
+	CloseResult {
+		/// Index of the set that has been closed.
+		set_index: usize,
+	},
 
 	/// The remote would like the substreams to be open. Send a [`NotifsHandlerIn::Open`] or a
 	/// [`NotifsHandlerIn::Close`] in order to either accept or deny this request. If a
 	/// [`NotifsHandlerIn::Open`] or [`NotifsHandlerIn::Close`] has been sent before and has not
 	/// yet been acknowledged by a matching [`NotifsHandlerOut`], then you don't need to a send
 	/// another [`NotifsHandlerIn`].
-	OpenDesiredByRemote,
+	OpenDesiredByRemote {
+		/// Index of the set the remote would like to open.
+		set_index: usize,
+	},
 
 	/// The remote would like the substreams to be closed. Send a [`NotifsHandlerIn::Close`] in
 	/// order to close them. If a [`NotifsHandlerIn::Close`] has been sent before and has not yet
 	/// been acknowledged by a [`NotifsHandlerOut::CloseResult`], then you don't need to a send
 	/// another one.
-	CloseDesired,
+	CloseDesired {
+		/// Index of the set the remote would like to close.
+		set_index: usize,
+	},
 
 	/// Received a non-gossiping message on the legacy substream.
 	///
-	/// Can only happen when the handler is in the open state.
+	/// Can only happen when the first set is in the open state.
 	CustomMessage {
 		/// Message that has been received.
 		///
@@ -349,8 +395,11 @@ pub enum NotifsHandlerOut {
 
 	/// Received a message on a custom protocol substream.
 	///
-	/// Can only happen when the handler is in the open state.
+	/// Can only happen when the set of the protocol is in the open state.
 	Notification {
+		/// Index of the set the protocol belongs to.
+		set_index: usize,
+
 		/// Name of the protocol of the message.
 		protocol_name: Cow<'static, str>,
 
@@ -488,37 +537,27 @@ pub enum NotifsHandlerError {
 impl NotifsHandlerProto {
 	/// Builds a new handler.
 	///
-	/// `list` is a list of notification protocols names, and the message to send as part of the
-	/// handshake. At the moment, the message is always the same whether we open a substream
-	/// ourselves or respond to handshake from the remote.
+	/// `sets` is a list of sets, each containing a list of notification protocols names, and the
+	/// message to send as part of the handshake. At the moment, the message is always the same
+	/// whether we open a substream ourselves or respond to handshake from the remote.
 	///
-	/// The first protocol in `list` is special-cased as the protocol that contains the handshake
-	/// to report through the [`NotifsHandlerOut::Open`] event.
+	/// The first protocol of each set is special-cased as the protocol that contains the
+	/// handshake to report through the [`NotifsHandlerOut::OpenResultOk`] event.
 	///
 	/// # Panic
 	///
-	/// - Panics if `list` is empty.
+	/// - Panics if `sets` is empty, or if one of the sets is empty.
 	///
 	pub fn new(
 		legacy_protocol: RegisteredProtocol,
-		list: impl Into<Vec<(Cow<'static, str>, Arc<RwLock<Vec<u8>>>)>>,
+		sets: impl Into<Vec<Vec<(Cow<'static, str>, Arc<RwLock<Vec<u8>>>)>>>,
 	) -> Self {
-		let list = list.into();
-		assert!(!list.is_empty());
-
-		let out_protocols = list
-			.clone()
-			.into_iter()
-			.collect();
-
-		let in_protocols = list.clone()
-			.into_iter()
-			.map(|(proto_name, msg)| (NotificationsIn::new(proto_name), msg))
-			.collect();
+		let sets = sets.into();
+		assert!(!sets.is_empty());
+		assert!(sets.iter().all(|set| !set.is_empty()));
 
 		NotifsHandlerProto {
-			in_protocols,
-			out_protocols,
+			sets,
 			legacy_protocol,
 		}
 	}
@@ -530,12 +569,13 @@ impl ProtocolsHandler for NotifsHandler {
 	type Error = NotifsHandlerError;
 	type InboundProtocol = SelectUpgrade<UpgradeCollec<NotificationsIn>, RegisteredProtocol>;
 	type OutboundProtocol = NotificationsOut;
-	// Index within the `out_protocols`.
-	type OutboundOpenInfo = usize;
+	// Index of the set, and index within the `out_protocols` of this set.
+	type OutboundOpenInfo = (usize, usize);
 	type InboundOpenInfo = ();
 
 	fn listen_protocol(&self) -> SubstreamProtocol<Self::InboundProtocol, ()> {
-		let in_protocols = self.in_protocols.iter()
+		let in_protocols = self.sets.iter()
+			.flat_map(|set| set.in_protocols.iter())
 			.map(|(h, _)| h.clone())
 			.collect::<UpgradeCollec<_>>();
 
@@ -551,17 +591,20 @@ impl ProtocolsHandler for NotifsHandler {
 		match out {
 			// Received notifications substream.
 			EitherOutput::First(((_remote_handshake, mut proto), num)) => {
-				match &mut self.state {
+				let (set_index, num) = self.in_protocols_index[num];
+				let set = &mut self.sets[set_index];
+
+				match &mut set.state {
 					State::Closed { pending_opening } => {
 						self.events_queue.push_back(ProtocolsHandlerEvent::Custom(
-							NotifsHandlerOut::OpenDesiredByRemote
+							NotifsHandlerOut::OpenDesiredByRemote { set_index }
 						));
 
-						let mut in_substreams = (0..self.in_protocols.len())
+						let mut in_substreams = (0..set.in_protocols.len())
 							.map(|_| None)
 							.collect::<Vec<_>>();
 						in_substreams[num] = Some(proto);
-						self.state = State::OpenDesiredByRemote {
+						set.state = State::OpenDesiredByRemote {
 							in_substreams,
 							pending_opening: mem::replace(pending_opening, Vec::new()),
 						};
@@ -588,7 +631,7 @@ impl ProtocolsHandler for NotifsHandler {
 
 						// We create `handshake_message` on a separate line to be sure
 						// that the lock is released as soon as possible.
-						let handshake_message = self.in_protocols[num].1.read().clone();
+						let handshake_message = set.in_protocols[num].1.read().clone();
 						proto.send_handshake(handshake_message);
 						in_substreams[num] = Some(proto);
 					},
@@ -613,9 +656,10 @@ impl ProtocolsHandler for NotifsHandler {
 	fn inject_fully_negotiated_outbound(
 		&mut self,
 		(handshake, substream): <Self::OutboundProtocol as OutboundUpgrade<NegotiatedSubstream>>::Output,
-		num: Self::OutboundOpenInfo
+		(set_index, num): Self::OutboundOpenInfo
 	) {
-		match &mut self.state {
+		let set = &mut self.sets[set_index];
+		match &mut set.state {
 			State::Closed { pending_opening } |
 			State::OpenDesiredByRemote { pending_opening, .. } => {
 				debug_assert!(pending_opening[num]);
@@ -653,7 +697,7 @@ impl ProtocolsHandler for NotifsHandler {
 						.map(|s| s.expect("checked by the if above; qed"))
 						.collect();
 
-					self.state = State::Open {
+					set.state = State::Open {
 						notifications_sink_rx: stream::select(async_rx.fuse(), sync_rx.fuse()),
 						out_substreams,
 						in_substreams: mem::replace(in_substreams, Vec::new()),
@@ -662,6 +706,7 @@ impl ProtocolsHandler for NotifsHandler {
 
 					self.events_queue.push_back(ProtocolsHandlerEvent::Custom(
 						NotifsHandlerOut::OpenResultOk {
+							set_index,
 							endpoint: self.endpoint.clone(),
 							received_handshake: pending_handshake,
 							notifications_sink
@@ -674,29 +719,30 @@ impl ProtocolsHandler for NotifsHandler {
 
 	fn inject_event(&mut self, message: NotifsHandlerIn) {
 		match message {
-			NotifsHandlerIn::Open => {
-				match &mut self.state {
+			NotifsHandlerIn::Open { set_index } => {
+				let set = &mut self.sets[set_index];
+				match &mut set.state {
 					State::Closed { .. } | State::OpenDesiredByRemote { .. } => {
-						let (pending_opening, mut in_substreams) = match &mut self.state {
+						let (pending_opening, mut in_substreams) = match &mut set.state {
 							State::Closed { pending_opening } => (pending_opening, None),
 							State::OpenDesiredByRemote { pending_opening, in_substreams } =>
 								(pending_opening, Some(mem::replace(in_substreams, Vec::new()))),
 							_ => unreachable!()
 						};
 
-						debug_assert_eq!(pending_opening.len(), self.out_protocols.len());
+						debug_assert_eq!(pending_opening.len(), set.out_protocols.len());
 						for (n, is_pending) in pending_opening.iter().enumerate() {
 							if *is_pending {
 								continue;
 							}
 
 							let proto = NotificationsOut::new(
-								self.out_protocols[n].0.clone(),
-								self.out_protocols[n].1.read().clone()
+								set.out_protocols[n].0.clone(),
+								set.out_protocols[n].1.read().clone()
 							);
 
 							self.events_queue.push_back(ProtocolsHandlerEvent::OutboundSubstreamRequest {
-								protocol: SubstreamProtocol::new(proto, n)
+								protocol: SubstreamProtocol::new(proto, (set_index, n))
 									.with_timeout(OPEN_TIMEOUT),
 							});
 						}
@@ -708,19 +754,19 @@ impl ProtocolsHandler for NotifsHandler {
 									None => continue,
 								};
 
-								let handshake_message = self.in_protocols[num].1.read().clone();
+								let handshake_message = set.in_protocols[num].1.read().clone();
 								substream.send_handshake(handshake_message);
 							}
 						}
 
-						self.state = State::Opening {
+						set.state = State::Opening {
 							pending_handshake: None,
 							in_substreams: if let Some(in_substreams) = in_substreams {
 								in_substreams
 							} else {
-								(0..self.in_protocols.len()).map(|_| None).collect()
+								(0..set.in_protocols.len()).map(|_| None).collect()
 							},
-							out_substreams: (0..self.out_protocols.len()).map(|_| None).collect(),
+							out_substreams: (0..set.out_protocols.len()).map(|_| None).collect(),
 						};
 					},
 					State::Opening { .. } |
@@ -733,31 +779,35 @@ impl ProtocolsHandler for NotifsHandler {
 				}
 			},
 
-			NotifsHandlerIn::Close => {
-				for mut substream in self.legacy_substreams.drain(..) {
-					substream.shutdown();
-					self.legacy_shutdown.push(substream);
+			NotifsHandlerIn::Close { set_index } => {
+				// The legacy substream belongs to the first set.
+				if set_index == 0 {
+					for mut substream in self.legacy_substreams.drain(..) {
+						substream.shutdown();
+						self.legacy_shutdown.push(substream);
+					}
 				}
 
-				match &mut self.state {
+				let set = &mut self.sets[set_index];
+				match &mut set.state {
 					State::Open { .. } => {
-						let pending_opening = self.out_protocols.iter().map(|_| false).collect();
-						self.state = State::Closed {
+						let pending_opening = set.out_protocols.iter().map(|_| false).collect();
+						set.state = State::Closed {
 							pending_opening,
 						};
 					},
 					State::Opening { out_substreams, .. } => {
 						let pending_opening = out_substreams.iter().map(|s| s.is_none()).collect();
-						self.state = State::Closed {
+						set.state = State::Closed {
 							pending_opening,
 						};
 
 						self.events_queue.push_back(ProtocolsHandlerEvent::Custom(
-							NotifsHandlerOut::OpenResultErr
+							NotifsHandlerOut::OpenResultErr { set_index }
 						));
 					},
 					State::OpenDesiredByRemote { pending_opening, .. } => {
-						self.state = State::Closed {
+						set.state = State::Closed {
 							pending_opening: mem::replace(pending_opening, Vec::new()),
 						};
 					}
@@ -765,7 +815,7 @@ impl ProtocolsHandler for NotifsHandler {
 				}
 
 				self.events_queue.push_back(
-					ProtocolsHandlerEvent::Custom(NotifsHandlerOut::CloseResult)
+					ProtocolsHandlerEvent::Custom(NotifsHandlerOut::CloseResult { set_index })
 				);
 			},
 		}
@@ -773,10 +823,11 @@ impl ProtocolsHandler for NotifsHandler {
 
 	fn inject_dial_upgrade_error(
 		&mut self,
-		num: usize,
+		(set_index, num): (usize, usize),
 		_: ProtocolsHandlerUpgrErr<NotificationsHandshakeError>
 	) {
-		match &mut self.state {
+		let set = &mut self.sets[set_index];
+		match &mut set.state {
 			State::Closed { pending_opening } | State::OpenDesiredByRemote { pending_opening, .. } => {
 				debug_assert!(pending_opening[num]);
 				pending_opening[num] = false;
@@ -813,7 +864,7 @@ impl ProtocolsHandler for NotifsHandler {
 						.map(|s| s.expect("checked by the if above; qed"))
 						.collect();
 
-					self.state = State::Open {
+					set.state = State::Open {
 						notifications_sink_rx: stream::select(async_rx.fuse(), sync_rx.fuse()),
 						out_substreams,
 						in_substreams: mem::replace(in_substreams, Vec::new()),
@@ -822,6 +873,7 @@ impl ProtocolsHandler for NotifsHandler {
 
 					self.events_queue.push_back(ProtocolsHandlerEvent::Custom(
 						NotifsHandlerOut::OpenResultOk {
+							set_index,
 							endpoint: self.endpoint.clone(),
 							received_handshake: pending_handshake,
 							notifications_sink
@@ -830,12 +882,12 @@ impl ProtocolsHandler for NotifsHandler {
 
 				} else {
 					// Open failure!
-					self.state = State::Closed {
-						pending_opening: (0..self.out_protocols.len()).map(|_| false).collect(),
+					set.state = State::Closed {
+						pending_opening: (0..set.out_protocols.len()).map(|_| false).collect(),
 					};
 
 					self.events_queue.push_back(ProtocolsHandlerEvent::Custom(
-						NotifsHandlerOut::OpenResultErr
+						NotifsHandlerOut::OpenResultErr { set_index }
 					));
 				}
 			}
@@ -850,10 +902,11 @@ impl ProtocolsHandler for NotifsHandler {
 			return KeepAlive::Yes;
 		}
 
-		match self.state {
-			State::Closed { .. } => KeepAlive::Until(self.when_connection_open + INITIAL_KEEPALIVE_TIME),
-			State::OpenDesiredByRemote { .. } | State::Opening { .. } | State::Open { .. } =>
-				KeepAlive::Yes,
+		// `Yes` if any set is in a non-closed state.
+		if self.sets.iter().any(|set| !matches!(set.state, State::Closed { .. })) {
+			KeepAlive::Yes
+		} else {
+			KeepAlive::Until(self.when_connection_open + INITIAL_KEEPALIVE_TIME)
 		}
 	}
 
@@ -867,164 +920,172 @@ impl ProtocolsHandler for NotifsHandler {
 			return Poll::Ready(ev);
 		}
 
-		// Poll inbound substreams.
-		// Inbound substreams being closed is always tolerated, except for the
-		// `OpenDesiredByRemote` state which might need to be switched back to `Closed`.
-		match &mut self.state {
-			State::Closed { .. } => {}
-			State::Open { in_substreams, .. } => {
-				for (num, substream) in in_substreams.iter_mut().enumerate() {
-					match substream.as_mut().map(|s| Stream::poll_next(Pin::new(s), cx)) {
-						None | Some(Poll::Pending) => continue,
-						Some(Poll::Ready(Some(Ok(message)))) => {
-							let event = NotifsHandlerOut::Notification {
-								message,
-								protocol_name: self.in_protocols[num].0.protocol_name().clone(),
-							};
-							return Poll::Ready(ProtocolsHandlerEvent::Custom(event))
-						},
-						Some(Poll::Ready(None)) | Some(Poll::Ready(Some(Err(_)))) =>
-							*substream = None,
+		for set_index in 0..self.sets.len() {
+			let set = &mut self.sets[set_index];
+
+			// Poll inbound substreams.
+			// Inbound substreams being closed is always tolerated, except for the
+			// `OpenDesiredByRemote` state which might need to be switched back to `Closed`.
+			match &mut set.state {
+				State::Closed { .. } => {}
+				State::Open { in_substreams, .. } => {
+					for (num, substream) in in_substreams.iter_mut().enumerate() {
+						match substream.as_mut().map(|s| Stream::poll_next(Pin::new(s), cx)) {
+							None | Some(Poll::Pending) => continue,
+							Some(Poll::Ready(Some(Ok(message)))) => {
+								let event = NotifsHandlerOut::Notification {
+									set_index,
+									message,
+									protocol_name: set.in_protocols[num].0.protocol_name().clone(),
+								};
+								return Poll::Ready(ProtocolsHandlerEvent::Custom(event))
+							},
+							Some(Poll::Ready(None)) | Some(Poll::Ready(Some(Err(_)))) =>
+								*substream = None,
+						}
 					}
 				}
-			}
 
-			State::OpenDesiredByRemote { in_substreams, .. } |
-			State::Opening { in_substreams, .. } => {
-				for substream in in_substreams {
-					match substream.as_mut().map(|s| NotificationsInSubstream::poll_process(Pin::new(s), cx)) {
-						None | Some(Poll::Pending) => continue,
-						Some(Poll::Ready(Ok(void))) => match void {},
-						Some(Poll::Ready(Err(_))) => *substream = None,
+				State::OpenDesiredByRemote { in_substreams, .. } |
+				State::Opening { in_substreams, .. } => {
+					for substream in in_substreams {
+						match substream.as_mut().map(|s| NotificationsInSubstream::poll_process(Pin::new(s), cx)) {
+							None | Some(Poll::Pending) => continue,
+							Some(Poll::Ready(Ok(void))) => match void {},
+							Some(Poll::Ready(Err(_))) => *substream = None,
+						}
 					}
 				}
 			}
-		}
 
-		// Since the previous block might have closed inbound substreams, make sure that we can
-		// stay in `OpenDesiredByRemote` state.
-		if let State::OpenDesiredByRemote { in_substreams, pending_opening } = &mut self.state {
-			if !in_substreams.iter().any(|s| s.is_some()) {
-				self.state = State::Closed {
-					pending_opening: mem::replace(pending_opening, Vec::new()),
-				};
-				return Poll::Ready(ProtocolsHandlerEvent::Custom(
-					NotifsHandlerOut::CloseDesired
-				))
+			// Since the previous block might have closed inbound substreams, make sure that we can
+			// stay in `OpenDesiredByRemote` state.
+			if let State::OpenDesiredByRemote { in_substreams, pending_opening } = &mut set.state {
+				if !in_substreams.iter().any(|s| s.is_some()) {
+					set.state = State::Closed {
+						pending_opening: mem::replace(pending_opening, Vec::new()),
+					};
+					return Poll::Ready(ProtocolsHandlerEvent::Custom(
+						NotifsHandlerOut::CloseDesired { set_index }
+					))
+				}
 			}
-		}
 
-		// Poll outbound substreams.
-		match &mut self.state {
-			State::Open { out_substreams, want_closed, .. } => {
-				let mut any_closed = false;
+			// Poll outbound substreams.
+			match &mut set.state {
+				State::Open { out_substreams, want_closed, .. } => {
+					let mut any_closed = false;
 
-				for substream in out_substreams.iter_mut() {
-					match substream.as_mut().map(|s| Sink::poll_flush(Pin::new(s), cx)) {
-						None | Some(Poll::Pending) | Some(Poll::Ready(Ok(()))) => continue,
-						Some(Poll::Ready(Err(_))) => {}
-					};
+					for substream in out_substreams.iter_mut() {
+						match substream.as_mut().map(|s| Sink::poll_flush(Pin::new(s), cx)) {
+							None | Some(Poll::Pending) | Some(Poll::Ready(Ok(()))) => continue,
+							Some(Poll::Ready(Err(_))) => {}
+						};
 
-					// Reached if the substream has been closed.
-					*substream = None;
-					any_closed = true;
-				}
+						// Reached if the substream has been closed.
+						*substream = None;
+						any_closed = true;
+					}
 
-				if any_closed {
-					if !*want_closed {
-						*want_closed = true;
-						return Poll::Ready(ProtocolsHandlerEvent::Custom(NotifsHandlerOut::CloseDesired));
+					if any_closed {
+						if !*want_closed {
+							*want_closed = true;
+							return Poll::Ready(ProtocolsHandlerEvent::Custom(
+								NotifsHandlerOut::CloseDesired { set_index }
+							));
+						}
 					}
 				}
-			}
 
-			State::Opening { out_substreams, pending_handshake, .. } => {
-				debug_assert!(out_substreams.iter().any(|s| s.is_none()));
+				State::Opening { out_substreams, pending_handshake, .. } => {
+					debug_assert!(out_substreams.iter().any(|s| s.is_none()));
 
-				for (num, substream) in out_substreams.iter_mut().enumerate() {
-					match substream {
-						None | Some(None) => continue,
-						Some(Some(substream)) => match Sink::poll_flush(Pin::new(substream), cx) {
-							Poll::Pending | Poll::Ready(Ok(())) => continue,
-							Poll::Ready(Err(_)) => {}
+					for (num, substream) in out_substreams.iter_mut().enumerate() {
+						match substream {
+							None | Some(None) => continue,
+							Some(Some(substream)) => match Sink::poll_flush(Pin::new(substream), cx) {
+								Poll::Pending | Poll::Ready(Ok(())) => continue,
+								Poll::Ready(Err(_)) => {}
+							}
 						}
-					}
 
-					// Reached if the substream has been closed.
-					*substream = Some(None);
-					if num == 0 {
-						// Cancel the handshake.
-						*pending_handshake = None;
+						// Reached if the substream has been closed.
+						*substream = Some(None);
+						if num == 0 {
+							// Cancel the handshake.
+							*pending_handshake = None;
+						}
 					}
 				}
-			}
 
-			State::Closed { .. } |
-			State::OpenDesiredByRemote { .. } => {}
-		}
+				State::Closed { .. } |
+				State::OpenDesiredByRemote { .. } => {}
+			}
 
-		if let State::Open { notifications_sink_rx, out_substreams, .. } = &mut self.state {
-			'poll_notifs_sink: loop {
-				// Before we poll the notifications sink receiver, check that all the notification
-				// channels are ready to send a message.
-				// TODO: it is planned that in the future we switch to one `NotificationsSink` per
-				// protocol, in which case each sink should wait only for its corresponding handler
-				// to be ready, and not all handlers
-				// see https://github.com/paritytech/substrate/issues/5670
-				for substream in out_substreams.iter_mut() {
-					match substream.as_mut().map(|s| s.poll_ready_unpin(cx)) {
-						None | Some(Poll::Ready(_)) => {},
-						Some(Poll::Pending) => break 'poll_notifs_sink
+			if let State::Open { notifications_sink_rx, out_substreams, .. } = &mut set.state {
+				'poll_notifs_sink: loop {
+					// Before we poll the notifications sink receiver, check that all the notification
+					// channels of this set are ready to send a message.
+					// TODO: it is planned that in the future we switch to one `NotificationsSink` per
+					// protocol, in which case each sink should wait only for its corresponding handler
+					// to be ready, and not all handlers of the set
+					// see https://github.com/paritytech/substrate/issues/5670
+					for substream in out_substreams.iter_mut() {
+						match substream.as_mut().map(|s| s.poll_ready_unpin(cx)) {
+							None | Some(Poll::Ready(_)) => {},
+							Some(Poll::Pending) => break 'poll_notifs_sink
+						}
 					}
-				}
 
-				// Now that all substreams are ready for a message, grab what to send.
-				let message = match notifications_sink_rx.poll_next_unpin(cx) {
-					Poll::Ready(Some(msg)) => msg,
-					Poll::Ready(None) | Poll::Pending => break,
-				};
+					// Now that all substreams are ready for a message, grab what to send.
+					let message = match notifications_sink_rx.poll_next_unpin(cx) {
+						Poll::Ready(Some(msg)) => msg,
+						Poll::Ready(None) | Poll::Pending => break,
+					};
 
-				match message {
-					NotificationsSinkMessage::Notification {
-						protocol_name,
-						message
-					} => {
-						if let Some(pos) = self.out_protocols.iter().position(|(n, _)| *n == protocol_name) {
-							if let Some(substream) = out_substreams[pos].as_mut() {
-								let _ = substream.start_send_unpin(message);
-								// Calling `start_send_unpin` only queues the message. Actually
-								// emitting the message is done with `poll_flush`. In order to
-								// not introduce too much complexity, this flushing is done earlier
-								// in the body of this `poll()` method. As such, we schedule a task
-								// wake-up now in order to guarantee that `poll()` will be called
-								// again and the flush happening.
-								// At the time of the writing of this comment, a rewrite of this
-								// code is being planned. If you find this comment in the wild and
-								// the rewrite didn't happen, please consider a refactor.
-								cx.waker().wake_by_ref();
-								continue 'poll_notifs_sink;
-							}
+					match message {
+						NotificationsSinkMessage::Notification {
+							protocol_name,
+							message
+						} => {
+							if let Some(pos) = set.out_protocols.iter().position(|(n, _)| *n == protocol_name) {
+								if let Some(substream) = out_substreams[pos].as_mut() {
+									let _ = substream.start_send_unpin(message);
+									// Calling `start_send_unpin` only queues the message. Actually
+									// emitting the message is done with `poll_flush`. In order to
+									// not introduce too much complexity, this flushing is done earlier
+									// in the body of this `poll()` method. As such, we schedule a task
+									// wake-up now in order to guarantee that `poll()` will be called
+									// again and the flush happening.
+									// At the time of the writing of this comment, a rewrite of this
+									// code is being planned. If you find this comment in the wild and
+									// the rewrite didn't happen, please consider a refactor.
+									cx.waker().wake_by_ref();
+									continue 'poll_notifs_sink;
+								}
 
-						} else {
-							log::warn!(
-								target: "sub-libp2p",
-								"Tried to send a notification on non-registered protocol: {:?}",
-								protocol_name
+							} else {
+								log::warn!(
+									target: "sub-libp2p",
+									"Tried to send a notification on non-registered protocol: {:?}",
+									protocol_name
+								);
+							}
+						}
+						NotificationsSinkMessage::ForceClose => {
+							return Poll::Ready(
+								ProtocolsHandlerEvent::Close(NotifsHandlerError::SyncNotificationsClogged)
 							);
 						}
 					}
-					NotificationsSinkMessage::ForceClose => {
-						return Poll::Ready(
-							ProtocolsHandlerEvent::Close(NotifsHandlerError::SyncNotificationsClogged)
-						);
-					}
 				}
 			}
 		}
 
-		// The legacy substreams are polled only if the state is `Open`. Otherwise, it would be
-		// possible to receive notifications that would need to get silently discarded.
-		if matches!(self.state, State::Open { .. }) {
+		// The legacy substreams are polled only if the state of the first set is `Open`.
+		// Otherwise, it would be possible to receive notifications that would need to get
+		// silently discarded.
+		if matches!(self.sets[0].state, State::Open { .. }) {
 			for n in (0..self.legacy_substreams.len()).rev() {
 				let mut substream = self.legacy_substreams.swap_remove(n);
 				let poll_outcome = Pin::new(&mut substream).poll_next(cx);
@@ -1046,11 +1107,11 @@ impl ProtocolsHandler for NotifsHandler {
 							self.legacy_shutdown.push(substream);
 						}
 
-						if let State::Open { want_closed, .. } = &mut self.state {
+						if let State::Open { want_closed, .. } = &mut self.sets[0].state {
 							if !*want_closed {
 								*want_closed = true;
 								return Poll::Ready(ProtocolsHandlerEvent::Custom(
-									NotifsHandlerOut::CloseDesired
+									NotifsHandlerOut::CloseDesired { set_index: 0 }
 								))
 							}
 						}
@@ -34,15 +34,21 @@ use sp_consensus::{BlockOrigin, BlockStatus,
 	block_validation::{BlockAnnounceValidator, Validation},
 	import_queue::{IncomingBlock, BlockImportResult, BlockImportError}
 };
-use crate::protocol::message::{
-	self, BlockAnnounce, BlockAttributes, BlockRequest, BlockResponse, Roles,
+use crate::{
+	config::SyncMode,
+	protocol::message::{self, BlockAnnounce, BlockAttributes, BlockRequest, BlockResponse, Roles},
+	schema::v1::{StateRequest, StateResponse},
+	warp_request_handler::{EncodedProof, WarpProofRequest, WarpSyncProvider},
 };
 use either::Either;
 use extra_requests::ExtraRequests;
+use history::HistorySync;
+use state::StateSync;
+use warp::{WarpProofImportResult, WarpSync};
 use libp2p::PeerId;
 use log::{debug, trace, warn, info, error};
 use sp_runtime::{
-	Justification,
+	Justifications,
 	generic::BlockId,
 	traits::{
 		Block as BlockT, Header as HeaderT, NumberFor, Zero, One, CheckedSub, SaturatedConversion,
@@ -57,6 +63,9 @@ use futures::{task::Poll, Future, stream::FuturesUnordered, FutureExt, StreamExt
 
 mod blocks;
 mod extra_requests;
+mod history;
+mod state;
+mod warp;
 
 /// Maximum blocks to request in a single packet.
 const MAX_BLOCKS_TO_REQUEST: usize = 128;
@@ -85,6 +94,14 @@ const MAX_CONCURRENT_BLOCK_ANNOUNCE_VALIDATIONS_PER_PEER: usize = 4;
 /// so far behind.
 const MAJOR_SYNC_BLOCKS: u8 = 5;
 
+/// Number of blocks behind the best block of most peers that a finalized block may be at, for
+/// fast sync to start downloading its state.
+const STATE_SYNC_FINALITY_THRESHOLD: u32 = 8;
+
+/// Minimum number of peers to be connected to before asking for warp proofs, so that the proven
+/// target is a block most of the network agrees on.
+const MIN_PEERS_TO_START_WARP_SYNC: usize = 3;
+
 mod rep {
 	use sc_peerset::ReputationChange as Rep;
 	/// Reputation change when a peer sent us a message that led to a
@@ -118,6 +135,12 @@ mod rep {
 
 	/// Peer response data does not have requested bits.
 	pub const BAD_RESPONSE: Rep = Rep::new(-(1 << 12), "Incomplete response");
+
+	/// Reputation change for peers which send us a warp proof that fails verification.
+	pub const BAD_WARP_PROOF: Rep = Rep::new(-(1 << 29), "Bad warp proof");
+
+	/// Reputation change for peers which send us a state response that fails verification.
+	pub const BAD_STATE: Rep = Rep::new(-(1 << 29), "Bad state");
 }
 
 enum PendingRequests {
@@ -188,7 +211,7 @@ pub struct ChainSync<B: BlockT> {
 	/// downloaded and are queued for import.
 	queue_blocks: HashSet<B::Hash>,
 	/// The best block number that was successfully imported into the chain.
-	/// This can not decrease.
+	/// This can not decrease, except when switching from fast to full sync.
 	best_imported_number: NumberFor<B>,
 	/// Fork sync targets.
 	fork_targets: HashMap<B::Hash, ForkTarget<B>>,
@@ -206,6 +229,22 @@ pub struct ChainSync<B: BlockT> {
 	>,
 	/// Stats per peer about the number of concurrent block announce validations.
 	block_announce_validation_per_peer_stats: HashMap<PeerId, usize>,
+	/// Current sync mode. Switches from `Fast` or `Warp` to `Full` once the state has been
+	/// imported.
+	mode: SyncMode,
+	/// State download of the finalized block, when fast syncing.
+	state_sync: Option<StateSync<B>>,
+	/// Warp proofs and state download, when warp syncing.
+	warp_sync: Option<WarpSync<B>>,
+	/// Provider of warp proofs verification, required for warp sync.
+	warp_sync_provider: Option<Arc<dyn WarpSyncProvider<B>>>,
+	/// Headers above the imported state were downloaded without bodies up to this number, and
+	/// their blocks have to be downloaded and imported again.
+	existing_headers_end: Option<NumberFor<B>>,
+	/// Download of the block history missing below the imported state.
+	history_sync: Option<HistorySync<B>>,
+	/// Maximum number of history blocks downloaded per second.
+	block_history_rate: u32,
 }
 
 /// All the data we have about a Peer that we are trying to sync with
@@ -260,6 +299,12 @@ pub enum PeerSyncState<B: BlockT> {
 	DownloadingStale(B::Hash),
 	/// Downloading justification for given block hash.
 	DownloadingJustification(B::Hash),
+	/// Downloading state of the fast or warp sync target.
+	DownloadingState,
+	/// Downloading warp proofs.
+	DownloadingWarpProof,
+	/// Downloading blocks of the history missing below the imported state.
+	DownloadingHistory,
 }
 
 impl<B: BlockT> PeerSyncState<B> {
@@ -315,6 +360,15 @@ pub enum OnBlockData<B: BlockT> {
 	Request(PeerId, BlockRequest<B>)
 }
 
+/// Result of [`ChainSync::on_state_data`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum OnStateData<B: BlockT> {
+	/// The block and state should be imported.
+	Import(BlockOrigin, IncomingBlock<B>),
+	/// More state needs to be downloaded.
+	Continue,
+}
+
 /// Result of [`ChainSync::poll_block_announce_validation`].
 #[derive(Debug, Clone, PartialEq, Eq)]
 pub enum PollBlockAnnounceValidation<H> {
@@ -386,7 +440,7 @@ pub enum OnBlockJustification<B: BlockT> {
 		peer: PeerId,
 		hash: B::Hash,
 		number: NumberFor<B>,
-		justification: Justification
+		justifications: Justifications
 	}
 }
 
@@ -403,25 +457,62 @@ enum HasSlotForBlockAnnounceValidation {
 impl<B: BlockT> ChainSync<B> {
 	/// Create a new instance.
 	pub fn new(
+		mode: SyncMode,
 		role: Roles,
 		client: Arc<dyn crate::chain::Client<B>>,
 		info: &BlockchainInfo<B>,
 		block_announce_validator: Box<dyn BlockAnnounceValidator<B> + Send>,
 		max_parallel_downloads: u32,
+		warp_sync_provider: Option<Arc<dyn WarpSyncProvider<B>>>,
+		block_history_rate: u32,
 	) -> Self {
+		let has_state = |hash: B::Hash| client.block_status(&BlockId::Hash(hash))
+			.map_or(false, |status| status == BlockStatus::InChainWithState);
+
+		// Fast sync is only possible until the state of a finalized block has been imported.
+		let finalized_has_state = !info.finalized_number.is_zero() && has_state(info.finalized_hash);
+		let mode = match mode {
+			SyncMode::Fast if role.is_full() && !finalized_has_state => SyncMode::Fast,
+			// Warp sync starts from genesis, so it can't resume an interrupted sync.
+			SyncMode::Warp
+				if role.is_full() && warp_sync_provider.is_some() && info.best_number.is_zero() =>
+				SyncMode::Warp,
+			_ => SyncMode::Full,
+		};
+
+		// Blocks above the finalized one might still lack their bodies and state if we were
+		// interrupted right after the state was imported.
+		let (best_queued_hash, best_queued_number, existing_headers_end) =
+			if role.is_full()
+				&& finalized_has_state
+				&& info.best_number > info.finalized_number
+				&& !has_state(info.best_hash)
+			{
+				(info.finalized_hash, info.finalized_number, Some(info.best_number))
+			} else {
+				(info.best_hash, info.best_number, None)
+			};
+
 		let mut required_block_attributes = BlockAttributes::HEADER | BlockAttributes::JUSTIFICATION;
 
-		if role.is_full() {
+		if role.is_full() && mode == SyncMode::Full {
 			required_block_attributes |= BlockAttributes::BODY
 		}
 
+		// Resume the download of the history missing below a previously imported state.
+		let history_sync = if role.is_full() {
+			HistorySync::new(client.clone(), block_history_rate)
+		} else {
+			None
+		};
+
 		ChainSync {
 			client,
 			peers: HashMap::new(),
 			blocks: BlockCollection::new(),
-			best_queued_hash: info.best_hash,
-			best_queued_number: info.best_number,
-			best_imported_number: info.best_number,
+			best_queued_hash,
+			best_queued_number,
+			best_imported_number: best_queued_number,
 			extra_justifications: ExtraRequests::new("justification"),
 			role,
 			required_block_attributes,
@@ -433,6 +524,13 @@ impl<B: BlockT> ChainSync<B> {
 			downloaded_blocks: 0,
 			block_announce_validation: Default::default(),
 			block_announce_validation_per_peer_stats: Default::default(),
+			mode,
+			state_sync: None,
+			warp_sync: None,
+			warp_sync_provider,
+			existing_headers_end,
+			history_sync,
+			block_history_rate,
 		}
 	}
 
@@ -447,7 +545,9 @@ impl<B: BlockT> ChainSync<B> {
 	pub fn status(&self) -> Status<B> {
 		let best_seen = self.peers.values().map(|p| p.best_number).max();
 		let sync_state =
-			if let Some(n) = best_seen {
+			if self.state_sync.is_some() || self.mode == SyncMode::Warp {
+				SyncState::Downloading
+			} else if let Some(n) = best_seen {
 				// A chain is classified as downloading if the provided best block is
 				// more than `MAJOR_SYNC_BLOCKS` behind the best queued block.
 				if n > self.best_queued_number && n - self.best_queued_number > MAJOR_SYNC_BLOCKS.into() {
@@ -554,8 +654,13 @@ impl<B: BlockT> ChainSync<B> {
 			}
 			Ok(BlockStatus::Queued) | Ok(BlockStatus::InChainWithState) | Ok(BlockStatus::InChainPruned) => {
 				debug!(target:"sync", "New peer with known best hash {} ({}).", best_hash, best_number);
+				// Blocks above the imported state are only known by their header.
+				let common_number = match self.existing_headers_end {
+					Some(_) => std::cmp::min(best_number, self.best_queued_number),
+					None => best_number,
+				};
 				self.peers.insert(who.clone(), PeerSync {
-					common_number: best_number,
+					common_number,
 					best_hash,
 					best_number,
 					state: PeerSyncState::Available,
@@ -661,7 +766,10 @@ impl<B: BlockT> ChainSync<B> {
 
 	/// Get an iterator over all block requests of all peers.
 	pub fn block_requests(&mut self) -> impl Iterator<Item = (&PeerId, BlockRequest<B>)> + '_ {
-		if self.pending_requests.is_empty() {
+		if self.pending_requests.is_empty()
+			|| self.state_sync.is_some()
+			|| self.mode == SyncMode::Warp
+		{
 			return Either::Left(std::iter::empty())
 		}
 		if self.queue_blocks.len() > MAX_IMPORTING_BLOCKS {
@@ -724,6 +832,95 @@ impl<B: BlockT> ChainSync<B> {
 		Either::Right(iter)
 	}
 
+	/// Get a state request, if any.
+	pub fn state_request(&mut self) -> Option<(PeerId, StateRequest)> {
+		if self.peers.values().any(|peer| peer.state == PeerSyncState::DownloadingState) {
+			// Only one pending state request is allowed.
+			return None
+		}
+		if let Some(sync) = &self.state_sync {
+			if sync.is_complete() {
+				return None
+			}
+			for (id, peer) in self.peers.iter_mut() {
+				if peer.state.is_available() && peer.common_number >= sync.target_block_num() {
+					trace!(target: "sync", "New state request to {}", id);
+					peer.state = PeerSyncState::DownloadingState;
+					return Some((id.clone(), sync.next_request()))
+				}
+			}
+		}
+		if let Some(sync) = &self.warp_sync {
+			let next = (sync.next_state_request(), sync.target_block_number());
+			if let (Some(request), Some(target)) = next {
+				// Peers only know their best block, any peer above the target has its state.
+				for (id, peer) in self.peers.iter_mut() {
+					if peer.state.is_available() && peer.best_number >= target {
+						trace!(target: "sync", "New state request to {}", id);
+						peer.state = PeerSyncState::DownloadingState;
+						return Some((id.clone(), request))
+					}
+				}
+			}
+		}
+		None
+	}
+
+	/// Get a warp proof request, if any.
+	pub fn warp_sync_request(&mut self) -> Option<(PeerId, WarpProofRequest<B>)> {
+		if self.mode != SyncMode::Warp {
+			return None
+		}
+		if self.peers.values().any(|peer| peer.state == PeerSyncState::DownloadingWarpProof) {
+			// Only one pending warp proof request is allowed.
+			return None
+		}
+		if self.warp_sync.is_none() {
+			if self.peers.len() < MIN_PEERS_TO_START_WARP_SYNC {
+				return None
+			}
+			if let Some(provider) = &self.warp_sync_provider {
+				info!("⏩ Starting warp sync with {} peers", self.peers.len());
+				self.warp_sync = Some(WarpSync::new(self.client.clone(), provider.clone()));
+			}
+		}
+		let request = self.warp_sync.as_ref()?.next_warp_proof_request()?;
+		// Ask a peer that is at least at the median best block, so that the proof ends at a
+		// recent block.
+		let mut heads: Vec<_> = self.peers.values().map(|peer| peer.best_number).collect();
+		heads.sort();
+		let median = *heads.get(heads.len() / 2)?;
+		for (id, peer) in self.peers.iter_mut() {
+			if peer.state.is_available() && peer.best_number >= median {
+				trace!(target: "sync", "New warp proof request to {}", id);
+				peer.state = PeerSyncState::DownloadingWarpProof;
+				return Some((id.clone(), request))
+			}
+		}
+		None
+	}
+
+	/// Get a request for blocks of the history missing below the imported state, if any.
+	pub fn history_request(&mut self) -> Option<(PeerId, BlockRequest<B>)> {
+		if self.peers.values().any(|peer| peer.state == PeerSyncState::DownloadingHistory) {
+			// Only one pending history request is allowed.
+			return None
+		}
+		let sync = self.history_sync.as_ref()?;
+		let request = sync.next_request()?;
+		for (id, peer) in self.peers.iter_mut() {
+			if peer.state.is_available()
+				&& peer.common_number > sync.next_number()
+				&& sync.is_available(id)
+			{
+				trace!(target: "sync", "New history request to {} from #{}", id, sync.next_number());
+				peer.state = PeerSyncState::DownloadingHistory;
+				return Some((id.clone(), request))
+			}
+		}
+		None
+	}
+
 	/// Handle a response from the remote to a block request that we made.
 	///
 	/// `request` must be the original request that triggered `response`.
@@ -738,6 +935,7 @@ impl<B: BlockT> ChainSync<B> {
 		response: BlockResponse<B>
 	) -> Result<OnBlockData<B>, BadPeer> {
 		self.downloaded_blocks += response.blocks.len();
+		let num_peers = self.peers.len();
 		let mut new_blocks: Vec<IncomingBlock<B>> =
 			if let Some(peer) = self.peers.get_mut(who) {
 				let mut blocks = response.blocks;
@@ -754,18 +952,25 @@ impl<B: BlockT> ChainSync<B> {
 							peer.state = PeerSyncState::Available;
 							validate_blocks::<B>(&blocks, who, Some(request))?;
 							self.blocks.insert(start_block, blocks, who.clone());
+							let existing_headers_end = self.existing_headers_end;
 							self.blocks
 								.drain(self.best_queued_number + One::one())
 								.into_iter()
 								.map(|block_data| {
+									// Blocks only known by their header are imported again.
+									let import_existing = match (existing_headers_end, &block_data.block.header) {
+										(Some(end), Some(header)) => *header.number() <= end,
+										_ => false,
+									};
 									IncomingBlock {
 										hash: block_data.block.hash,
 										header: block_data.block.header,
 										body: block_data.block.body,
-										justification: block_data.block.justification,
+										justifications: block_data.block.justifications,
 										origin: block_data.origin,
 										allow_missing_state: true,
-										import_existing: false,
+										import_existing,
+										state: None,
 									}
 								}).collect()
 						}
@@ -781,10 +986,11 @@ impl<B: BlockT> ChainSync<B> {
 									hash: b.hash,
 									header: b.header,
 									body: b.body,
-									justification: b.justification,
+									justifications: b.justifications,
 									origin: Some(who.clone()),
 									allow_missing_state: true,
 									import_existing: false,
+									state: None,
 								}
 							}).collect()
 						}
@@ -854,8 +1060,35 @@ impl<B: BlockT> ChainSync<B> {
 							}
 						}
 
+						PeerSyncState::DownloadingHistory => {
+							peer.state = PeerSyncState::Available;
+							if let Some(sync) = &mut self.history_sync {
+								if blocks.is_empty() {
+									trace!(target: "sync", "Empty history response from {}", who);
+									sync.on_empty_response(who, num_peers);
+								} else {
+									validate_blocks::<B>(&blocks, who, Some(request))?;
+									match sync.import(blocks) {
+										history::ImportResult::Continue => {},
+										history::ImportResult::Complete => {
+											info!("✅ Block history downloaded down to genesis");
+											self.history_sync = None;
+										},
+										history::ImportResult::BadResponse => {
+											debug!(target: "sync", "Bad history blocks received from {}", who);
+											return Err(BadPeer(who.clone(), rep::BAD_BLOCK))
+										},
+									}
+								}
+							}
+							// History blocks are stored directly, without going through the import queue.
+							Vec::new()
+						}
+
 						| PeerSyncState::Available
-						| PeerSyncState::DownloadingJustification(..) => Vec::new()
+						| PeerSyncState::DownloadingJustification(..)
+						| PeerSyncState::DownloadingState
+						| PeerSyncState::DownloadingWarpProof => Vec::new()
 					}
 				} else {
 					// When request.is_none() this is a block announcement. Just accept blocks.
@@ -865,10 +1098,11 @@ impl<B: BlockT> ChainSync<B> {
 							hash: b.hash,
 							header: b.header,
 							body: b.body,
-							justification: b.justification,
+							justifications: b.justifications,
 							origin: Some(who.clone()),
 							allow_missing_state: true,
 							import_existing: false,
+							state: None,
 						}
 					}).collect()
 				}
@@ -927,7 +1161,7 @@ impl<B: BlockT> ChainSync<B> {
 			peer.state = PeerSyncState::Available;
 
 			// We only request one justification at a time
-			let justification = if let Some(block) = response.blocks.into_iter().next() {
+			let justifications = if let Some(block) = response.blocks.into_iter().next() {
 				if hash != block.hash {
 					info!(
 						target: "sync",
@@ -936,7 +1170,7 @@ impl<B: BlockT> ChainSync<B> {
 					return Err(BadPeer(who, rep::BAD_JUSTIFICATION));
 				}
 
-				block.justification
+				block.justifications
 			} else {
 				// we might have asked the peer for a justification on a block that we assumed it
 				// had but didn't (regardless of whether it had a justification for it or not).
@@ -949,14 +1183,124 @@ impl<B: BlockT> ChainSync<B> {
 				None
 			};
 
-			if let Some((peer, hash, number, j)) = self.extra_justifications.on_response(who, justification) {
-				return Ok(OnBlockJustification::Import { peer, hash, number, justification: j })
+			if let Some((peer, hash, number, j)) = self.extra_justifications.on_response(who, justifications) {
+				return Ok(OnBlockJustification::Import { peer, hash, number, justifications: j })
 			}
 		}
 
 		Ok(OnBlockJustification::Nothing)
 	}
 
+	/// Handle a response from the remote to a state request that we made.
+	///
+	/// Returns the block that carries the downloaded state once the state is complete.
+	pub fn on_state_data(
+		&mut self,
+		who: &PeerId,
+		response: StateResponse,
+	) -> Result<OnStateData<B>, BadPeer> {
+		if let Some(peer) = self.peers.get_mut(who) {
+			if let PeerSyncState::DownloadingState = peer.state {
+				peer.state = PeerSyncState::Available;
+			}
+		}
+
+		let import_result = if let Some(sync) = &mut self.state_sync {
+			debug!(
+				target: "sync",
+				"Importing state data from {} with {} bytes of proof",
+				who,
+				response.proof.len(),
+			);
+			sync.import(response)
+		} else if let Some(sync) = &mut self.warp_sync {
+			debug!(
+				target: "sync",
+				"Importing warp sync state data from {} with {} bytes of proof",
+				who,
+				response.proof.len(),
+			);
+			sync.import_state(response)
+		} else {
+			debug!(target: "sync", "Ignored obsolete state response from {}", who);
+			return Err(BadPeer(who.clone(), rep::NOT_REQUESTED));
+		};
+
+		match import_result {
+			state::ImportResult::Import(hash, header, state) => {
+				debug!(target: "sync", "State download of {} is complete, queueing import", hash);
+				let block = IncomingBlock {
+					hash,
+					header: Some(header),
+					body: None,
+					justifications: None,
+					origin: None,
+					allow_missing_state: true,
+					// Fast sync already imported the header, warp sync only knows it from the proof.
+					import_existing: self.warp_sync.is_none(),
+					state: Some(state),
+				};
+				Ok(OnStateData::Import(BlockOrigin::NetworkInitialSync, block))
+			}
+			state::ImportResult::Continue => Ok(OnStateData::Continue),
+			state::ImportResult::BadResponse => {
+				debug!(target: "sync", "Bad state data received from {}", who);
+				Err(BadPeer(who.clone(), rep::BAD_STATE))
+			}
+		}
+	}
+
+	/// Call this when a state request to the given peer has failed.
+	pub fn on_state_request_failed(&mut self, who: &PeerId) {
+		if let Some(peer) = self.peers.get_mut(who) {
+			if let PeerSyncState::DownloadingState = peer.state {
+				peer.state = PeerSyncState::Available;
+			}
+		}
+	}
+
+	/// Handle a response from the remote to a warp proof request that we made.
+	pub fn on_warp_sync_data(&mut self, who: &PeerId, response: EncodedProof) -> Result<(), BadPeer> {
+		if let Some(peer) = self.peers.get_mut(who) {
+			if let PeerSyncState::DownloadingWarpProof = peer.state {
+				peer.state = PeerSyncState::Available;
+			}
+		}
+
+		let import_result = if let Some(sync) = &mut self.warp_sync {
+			debug!(
+				target: "sync",
+				"Importing warp proof data from {}, {} bytes.",
+				who,
+				response.0.len(),
+			);
+			sync.import_warp_proof(response)
+		} else {
+			debug!(target: "sync", "Ignored obsolete warp sync response from {}", who);
+			return Err(BadPeer(who.clone(), rep::NOT_REQUESTED));
+		};
+
+		match import_result {
+			WarpProofImportResult::WarpProofRequest(_) | WarpProofImportResult::StateRequest(_) => {
+				// The next request is made by `warp_sync_request` or `state_request`.
+				Ok(())
+			},
+			WarpProofImportResult::BadResponse => {
+				debug!(target: "sync", "Bad warp proof data received from {}", who);
+				Err(BadPeer(who.clone(), rep::BAD_WARP_PROOF))
+			},
+		}
+	}
+
+	/// Call this when a warp proof request to the given peer has failed.
+	pub fn on_warp_sync_request_failed(&mut self, who: &PeerId) {
+		if let Some(peer) = self.peers.get_mut(who) {
+			if let PeerSyncState::DownloadingWarpProof = peer.state {
+				peer.state = PeerSyncState::Available;
+			}
+		}
+	}
+
 	/// A batch of blocks have been processed, with or without errors.
 	///
 	/// Call this when a batch of blocks have been processed by the import
@@ -986,6 +1330,26 @@ impl<B: BlockT> ChainSync<B> {
 				has_error = true;
 			}
 
+			let is_state_target =
+				self.state_sync.as_ref().map_or(false, |sync| sync.target() == hash)
+				|| self.warp_sync.as_ref().map_or(false, |sync| sync.target_block_hash() == Some(hash));
+			if is_state_target {
+				match &result {
+					Ok(BlockImportResult::ImportedKnown(number)) |
+					Ok(BlockImportResult::ImportedUnknown(number, _, _)) => {
+						let number = *number;
+						self.on_state_imported(hash, number);
+					},
+					Err(e) => {
+						// Start over with the next finalized block.
+						warn!(target: "sync", "💔 Error importing downloaded state of {:?}: {:?}", hash, e);
+						self.state_sync = None;
+						// Warp sync starts over with new proofs.
+						self.warp_sync = None;
+					},
+				}
+			}
+
 			match result {
 				Ok(BlockImportResult::ImportedKnown(_number)) => {}
 				Ok(BlockImportResult::ImportedUnknown(number, aux, who)) => {
@@ -1014,6 +1378,11 @@ impl<B: BlockT> ChainSync<B> {
 					if number > self.best_imported_number {
 						self.best_imported_number = number;
 					}
+
+					if self.existing_headers_end.map_or(false, |end| number >= end) {
+						trace!(target: "sync", "Blocks above the imported state are complete at {}", number);
+						self.existing_headers_end = None;
+					}
 				},
 				Err(BlockImportError::IncompleteHeader(who)) => {
 					if let Some(peer) = who {
@@ -1072,6 +1441,63 @@ impl<B: BlockT> ChainSync<B> {
 		if let Err(err) = r {
 			warn!(target: "sync", "💔 Error cleaning up pending extra justification data requests: {:?}", err);
 		}
+
+		if self.mode == SyncMode::Fast && self.state_sync.is_none() && !self.peers.is_empty() {
+			// Download the state once a recent enough block is finalized.
+			let mut heads: Vec<_> = self.peers.values().map(|peer| peer.best_number).collect();
+			heads.sort();
+			let median = heads[heads.len() / 2];
+			if number + STATE_SYNC_FINALITY_THRESHOLD.saturated_into() >= median {
+				if let Ok(Some(header)) = self.client.header(BlockId::Hash(*hash)) {
+					info!("⏩ Starting state download of #{} ({})", number, hash);
+					self.state_sync = Some(StateSync::new(self.client.clone(), header));
+				}
+			}
+		}
+	}
+
+	/// Called when the downloaded state of a block has been imported.
+	///
+	/// Switches to full sync, downloading and importing again the blocks above it that are
+	/// only known by their header.
+	fn on_state_imported(&mut self, hash: B::Hash, number: NumberFor<B>) {
+		let imported_bytes = self.state_sync.take().map(|sync| sync.progress().1)
+			.or_else(|| self.warp_sync.take().map(|sync| sync.downloaded_bytes()))
+			.unwrap_or(0);
+		let was_warp_syncing = self.mode == SyncMode::Warp;
+		info!(
+			"✅ State of #{} ({}) imported, {} KiB downloaded. Continuing with full sync.",
+			number,
+			hash,
+			imported_bytes / 1024,
+		);
+
+		self.mode = SyncMode::Full;
+		self.required_block_attributes |= BlockAttributes::BODY;
+
+		let headers_end = std::cmp::max(self.client.info().best_number, self.best_queued_number);
+		self.existing_headers_end = if headers_end > number { Some(headers_end) } else { None };
+		self.best_queued_hash = hash;
+		self.best_queued_number = number;
+		self.best_imported_number = number;
+		self.blocks.clear();
+		if self.role.is_full() {
+			self.history_sync = HistorySync::new(self.client.clone(), self.block_history_rate);
+		}
+		for peer in self.peers.values_mut() {
+			if let PeerSyncState::DownloadingNew(_) | PeerSyncState::DownloadingStale(_) = peer.state {
+				// Headers still in flight are not useful anymore.
+				peer.state = PeerSyncState::Available;
+			}
+			if peer.common_number > number {
+				peer.common_number = number;
+			}
+			if was_warp_syncing && peer.best_number >= number {
+				// The target is finalized, so every peer above it has it in its chain.
+				peer.common_number = number;
+			}
+		}
+		self.pending_requests.set_all();
 	}
 
 	/// Called when a block has been queued for import.
@@ -1382,17 +1808,28 @@ impl<B: BlockT> ChainSync<B> {
 	) -> impl Iterator<Item = Result<(PeerId, BlockRequest<B>), BadPeer>> + 'a {
 		self.blocks.clear();
 		let info = self.client.info();
-		self.best_queued_hash = info.best_hash;
-		self.best_queued_number = std::cmp::max(info.best_number, self.best_imported_number);
+		if self.existing_headers_end.is_some() {
+			// Blocks above the best imported one are only known by their header.
+			if let Ok(Some(hash)) = self.client.hash(self.best_imported_number) {
+				self.best_queued_hash = hash;
+			}
+			self.best_queued_number = self.best_imported_number;
+		} else {
+			self.best_queued_hash = info.best_hash;
+			self.best_queued_number = std::cmp::max(info.best_number, self.best_imported_number);
+		}
 		self.pending_requests.set_all();
 		debug!(target:"sync", "Restarted with {} ({})", self.best_queued_number, self.best_queued_hash);
 		let old_peers = std::mem::take(&mut self.peers);
 
 		old_peers.into_iter().filter_map(move |(id, p)| {
-			// peers that were downloading justifications
+			// peers that were downloading justifications or state
 			// should be kept in that state.
 			match p.state {
-				PeerSyncState::DownloadingJustification(_) => {
+				PeerSyncState::DownloadingJustification(_)
+				| PeerSyncState::DownloadingState
+				| PeerSyncState::DownloadingWarpProof
+				| PeerSyncState::DownloadingHistory => {
 					self.peers.insert(id, p);
 					return None;
 				}
@@ -1762,11 +2199,14 @@ mod test {
 		let peer_id = PeerId::random();
 
 		let mut sync = ChainSync::new(
+			SyncMode::Full,
 			Roles::AUTHORITY,
 			client.clone(),
 			&info,
 			block_announce_validator,
 			1,
+			None,
+			0,
 		);
 
 		let (a1_hash, a1_number) = {
@@ -1833,11 +2273,14 @@ mod test {
 		let info = client.info();
 
 		let mut sync = ChainSync::new(
+			SyncMode::Full,
 			Roles::AUTHORITY,
 			client.clone(),
 			&info,
 			Box::new(DefaultBlockAnnounceValidator),
 			1,
+			None,
+			0,
 		);
 
 		let peer_id1 = PeerId::random();
@@ -1935,7 +2378,7 @@ mod test {
 					body: Some(b.deconstruct().1),
 					receipt: None,
 					message_queue: None,
-					justification: None,
+					justifications: None,
 				}
 			).collect(),
 		}
@@ -1976,11 +2419,14 @@ mod test {
 		let info = client.info();
 
 		let mut sync = ChainSync::new(
+			SyncMode::Full,
 			Roles::AUTHORITY,
 			client.clone(),
 			&info,
 			Box::new(DefaultBlockAnnounceValidator),
 			5,
+			None,
+			0,
 		);
 
 		let peer_id1 = PeerId::random();
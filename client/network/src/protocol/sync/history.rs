@@ -0,0 +1,189 @@
+// Copyright 2020 Parity Technologies (UK) Ltd.
+// This file is part of Substrate.
+//
+// Substrate is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// Substrate is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with Substrate.  If not, see <http://www.gnu.org/licenses/>.
+
+//! Background download of the block history missing below a block imported with its state.
+//!
+//! Blocks are downloaded in reverse order, starting with the parent of the lowest block above
+//! the gap. Each block must hash to the parent hash of the block downloaded before it, so the
+//! whole history is linked down to genesis without being executed.
+
+use crate::{
+	chain::Client,
+	protocol::message::{self, BlockAttributes, BlockData, BlockRequest},
+};
+use libp2p::PeerId;
+use log::{debug, warn};
+use sc_client_api::HistoryBlock;
+use sp_runtime::{
+	generic::BlockId,
+	traits::{Block as BlockT, Header as HeaderT, NumberFor, One, SaturatedConversion},
+};
+use std::{collections::HashSet, sync::Arc, time::Duration};
+use wasm_timer::Instant;
+
+/// Import history blocks result.
+pub enum ImportResult {
+	/// More blocks need to be downloaded.
+	Continue,
+	/// The history is complete down to genesis.
+	Complete,
+	/// Bad blocks.
+	BadResponse,
+}
+
+/// History sync state machine.
+pub struct HistorySync<B: BlockT> {
+	client: Arc<dyn Client<B>>,
+	/// Lowest missing block number.
+	start: NumberFor<B>,
+	/// Number of the next block to download, the highest missing one.
+	next_number: NumberFor<B>,
+	/// Hash of the next block to download.
+	next_hash: B::Hash,
+	/// Maximum number of blocks downloaded per second.
+	blocks_per_second: u32,
+	/// No request is made before this time, to respect the download rate.
+	next_request_at: Instant,
+	/// Peers that don't have the next blocks.
+	unavailable_peers: HashSet<PeerId>,
+}
+
+impl<B: BlockT> HistorySync<B> {
+	/// Create a new instance if blocks are missing from the database.
+	///
+	/// Returns `None` if the history is complete or if `blocks_per_second` is zero.
+	pub fn new(client: Arc<dyn Client<B>>, blocks_per_second: u32) -> Option<Self> {
+		if blocks_per_second == 0 {
+			return None
+		}
+		let (start, end) = client.info().block_gap?;
+		let next_hash = match client.header(BlockId::Number(end + One::one())) {
+			Ok(Some(header)) => *header.parent_hash(),
+			Ok(None) => {
+				warn!(target: "sync", "Missing header above the block history gap #{}", end);
+				return None
+			},
+			Err(e) => {
+				warn!(target: "sync", "Error reading the header above the block history gap: {:?}", e);
+				return None
+			},
+		};
+		Some(HistorySync {
+			client,
+			start,
+			next_number: end,
+			next_hash,
+			blocks_per_second,
+			next_request_at: Instant::now(),
+			unavailable_peers: Default::default(),
+		})
+	}
+
+	/// Returns the number of the next block to download.
+	pub fn next_number(&self) -> NumberFor<B> {
+		self.next_number
+	}
+
+	/// Returns `true` if the peer may have the next blocks.
+	pub fn is_available(&self, who: &PeerId) -> bool {
+		!self.unavailable_peers.contains(who)
+	}
+
+	/// Produce the next request, if the download rate allows it.
+	pub fn next_request(&self) -> Option<BlockRequest<B>> {
+		if Instant::now() < self.next_request_at {
+			return None
+		}
+		let remaining = (self.next_number - self.start + One::one()).saturated_into::<u32>();
+		let max = std::cmp::min(
+			std::cmp::min(super::MAX_BLOCKS_TO_REQUEST as u32, self.blocks_per_second),
+			remaining,
+		);
+		Some(message::generic::BlockRequest {
+			id: 0,
+			fields: BlockAttributes::HEADER | BlockAttributes::BODY | BlockAttributes::JUSTIFICATION,
+			from: message::FromBlock::Hash(self.next_hash),
+			to: None,
+			direction: message::Direction::Descending,
+			max: Some(max),
+		})
+	}
+
+	/// Note that the peer answered with no blocks. It is not asked again until every other peer
+	/// out of `num_peers` did the same.
+	pub fn on_empty_response(&mut self, who: &PeerId, num_peers: usize) {
+		self.unavailable_peers.insert(who.clone());
+		if self.unavailable_peers.len() >= num_peers {
+			self.unavailable_peers.clear();
+			self.delay(1);
+		}
+	}
+
+	/// Validate and store downloaded blocks, in ascending order.
+	///
+	/// The blocks must have been checked against the request and their headers.
+	pub fn import(&mut self, blocks: Vec<BlockData<B>>) -> ImportResult {
+		let mut expected_hash = self.next_hash;
+		let mut expected_number = self.next_number;
+		let mut history = Vec::with_capacity(blocks.len());
+		for block in blocks.into_iter().rev() {
+			let header = match block.header {
+				Some(header) => header,
+				None => return ImportResult::BadResponse,
+			};
+			if block.hash != expected_hash || *header.number() != expected_number {
+				debug!(
+					target: "sync",
+					"Unexpected history block #{} ({}), expected #{} ({})",
+					header.number(),
+					block.hash,
+					expected_number,
+					expected_hash,
+				);
+				return ImportResult::BadResponse
+			}
+			if expected_number < self.start {
+				debug!(target: "sync", "History block #{} is below the gap", expected_number);
+				return ImportResult::BadResponse
+			}
+			expected_hash = *header.parent_hash();
+			expected_number = expected_number - One::one();
+			history.push(HistoryBlock { header, body: block.body, justifications: block.justifications });
+		}
+
+		let count = history.len();
+		if let Err(e) = self.client.import_block_history(history) {
+			warn!(target: "sync", "💔 Error importing block history: {:?}", e);
+			return ImportResult::BadResponse
+		}
+		debug!(target: "sync", "Imported {} history blocks down to #{}", count, expected_number + One::one());
+
+		if expected_number < self.start {
+			return ImportResult::Complete
+		}
+		self.next_hash = expected_hash;
+		self.next_number = expected_number;
+		self.unavailable_peers.clear();
+		self.delay(count as u32);
+		ImportResult::Continue
+	}
+
+	/// Wait long enough for the given number of blocks to respect the download rate.
+	fn delay(&mut self, blocks: u32) {
+		let delay = Duration::from_millis(blocks as u64 * 1000 / self.blocks_per_second as u64);
+		self.next_request_at = Instant::now() + delay;
+	}
+}
@@ -0,0 +1,164 @@
+// Copyright 2020 Parity Technologies (UK) Ltd.
+// This file is part of Substrate.
+//
+// Substrate is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// Substrate is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with Substrate.  If not, see <http://www.gnu.org/licenses/>.
+
+//! State download of a single block, as used by fast sync.
+//!
+//! The state is downloaded in ranges of key-value pairs, each of them coming with a proof that is
+//! checked against the state root of the target header before the entries are accepted.
+
+use codec::{Encode, Decode};
+use crate::{chain::Client, schema::v1::{StateRequest, StateResponse}};
+use log::debug;
+use sc_client_api::StorageProof;
+use sp_consensus::ImportedState;
+use sp_core::{hexdisplay::HexDisplay, storage::{Storage, StorageChild}};
+use sp_runtime::traits::{Block as BlockT, Header as HeaderT, NumberFor};
+use std::sync::Arc;
+
+/// State sync support.
+pub struct StateSync<B: BlockT> {
+	target_block: B::Hash,
+	target_header: B::Header,
+	target_root: B::Hash,
+	/// Position of the last imported entry, the next range starts right after it.
+	last_key: Vec<Vec<u8>>,
+	state: Storage,
+	complete: bool,
+	client: Arc<dyn Client<B>>,
+	imported_bytes: u64,
+}
+
+/// Import state chunk result.
+pub enum ImportResult<B: BlockT> {
+	/// State is complete and ready for import.
+	Import(B::Hash, B::Header, ImportedState<B>),
+	/// Continue downloading.
+	Continue,
+	/// Bad state chunk.
+	BadResponse,
+}
+
+impl<B: BlockT> StateSync<B> {
+	/// Create a new instance downloading the state of the block with the given header.
+	pub fn new(client: Arc<dyn Client<B>>, target: B::Header) -> Self {
+		StateSync {
+			client,
+			target_block: target.hash(),
+			target_root: *target.state_root(),
+			target_header: target,
+			last_key: Vec::new(),
+			state: Default::default(),
+			complete: false,
+			imported_bytes: 0,
+		}
+	}
+
+	/// Validate and import a state response.
+	pub fn import(&mut self, response: StateResponse) -> ImportResult<B> {
+		let proof = match StorageProof::decode(&mut response.proof.as_ref()) {
+			Ok(proof) => proof,
+			Err(e) => {
+				debug!(target: "sync", "Error decoding state proof: {:?}", e);
+				return ImportResult::BadResponse
+			}
+		};
+
+		let range = match self.client.verify_range_proof(self.target_root, proof, &self.last_key) {
+			Ok(range) => range,
+			Err(e) => {
+				debug!(target: "sync", "StateResponse failed proof verification: {:?}", e);
+				return ImportResult::BadResponse
+			}
+		};
+
+		if range.entries.is_empty() && range.next_start.is_some() {
+			debug!(target: "sync", "StateResponse made no progress");
+			return ImportResult::BadResponse
+		}
+
+		debug!(
+			target: "sync",
+			"Importing state from {:?} to {:?}",
+			self.last_key.last().map(HexDisplay::from),
+			range.next_start.as_ref().and_then(|k| k.last()).map(HexDisplay::from),
+		);
+
+		for (child_info, key, value) in range.entries {
+			self.imported_bytes += (key.len() + value.len()) as u64;
+			match child_info {
+				Some(child_info) => {
+					self.state.children_default
+						.entry(child_info.storage_key().to_vec())
+						.or_insert_with(|| StorageChild { data: Default::default(), child_info })
+						.data
+						.insert(key, value);
+				},
+				None => {
+					self.state.top.insert(key, value);
+				},
+			}
+		}
+
+		match range.next_start {
+			Some(next_start) => {
+				self.last_key = next_start;
+				ImportResult::Continue
+			},
+			None => {
+				self.complete = true;
+				ImportResult::Import(
+					self.target_block,
+					self.target_header.clone(),
+					ImportedState { block: self.target_block, state: std::mem::take(&mut self.state) },
+				)
+			},
+		}
+	}
+
+	/// Produce the next state request.
+	pub fn next_request(&self) -> StateRequest {
+		StateRequest {
+			block: self.target_block.encode(),
+			start: self.last_key.clone(),
+		}
+	}
+
+	/// Check if the state is complete.
+	pub fn is_complete(&self) -> bool {
+		self.complete
+	}
+
+	/// Returns target block number.
+	pub fn target_block_num(&self) -> NumberFor<B> {
+		*self.target_header.number()
+	}
+
+	/// Returns target block hash.
+	pub fn target(&self) -> B::Hash {
+		self.target_block
+	}
+
+	/// Returns an estimate of the download progress in percent, along with the number of
+	/// downloaded bytes.
+	pub fn progress(&self) -> (u32, u64) {
+		// Keys are roughly uniformly distributed, so the first byte is a fair estimate.
+		let percentage = self.last_key.first()
+			.and_then(|key| key.first())
+			.map_or(0, |byte| *byte as u32 * 100 / 256);
+		let percentage = if self.complete { 100 } else { percentage };
+		(percentage, self.imported_bytes)
+	}
+}
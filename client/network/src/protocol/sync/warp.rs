@@ -0,0 +1,159 @@
+// Copyright 2020 Parity Technologies (UK) Ltd.
+// This file is part of Substrate.
+//
+// Substrate is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// Substrate is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with Substrate.  If not, see <http://www.gnu.org/licenses/>.
+
+//! Warp sync support.
+//!
+//! Warp sync first downloads proofs of the finality gadget's authority set changes, starting at
+//! genesis, until a recently finalized header is proven. The state of that header is then
+//! downloaded the same way as for fast sync.
+
+use crate::{
+	chain::Client,
+	schema::v1::{StateRequest, StateResponse},
+	warp_request_handler::{EncodedProof, VerificationResult, WarpProofRequest, WarpSyncProvider},
+};
+use super::state::{ImportResult, StateSync};
+use log::debug;
+use sp_finality_grandpa::{AuthorityList, SetId};
+use sp_runtime::traits::{Block as BlockT, NumberFor, Zero};
+use std::sync::Arc;
+
+enum Phase<B: BlockT> {
+	WarpProof { set_id: SetId, authorities: AuthorityList, last_hash: B::Hash },
+	State(StateSync<B>),
+}
+
+/// Import warp proof result.
+pub enum WarpProofImportResult<B: BlockT> {
+	/// More proofs need to be downloaded, starting at the given block.
+	WarpProofRequest(WarpProofRequest<B>),
+	/// The target is proven and its state can be downloaded.
+	StateRequest(StateRequest),
+	/// Bad proof.
+	BadResponse,
+}
+
+/// Warp sync state machine. Accumulates warp proofs and state.
+pub struct WarpSync<B: BlockT> {
+	phase: Phase<B>,
+	client: Arc<dyn Client<B>>,
+	warp_sync_provider: Arc<dyn WarpSyncProvider<B>>,
+	total_proof_bytes: u64,
+}
+
+impl<B: BlockT> WarpSync<B> {
+	/// Create a new instance, starting from the genesis authority set.
+	pub fn new(client: Arc<dyn Client<B>>, warp_sync_provider: Arc<dyn WarpSyncProvider<B>>) -> Self {
+		let last_hash = client.hash(Zero::zero()).ok().flatten().unwrap_or_default();
+		let phase = Phase::WarpProof {
+			set_id: 0,
+			authorities: warp_sync_provider.current_authorities(),
+			last_hash,
+		};
+		WarpSync { client, warp_sync_provider, phase, total_proof_bytes: 0 }
+	}
+
+	/// Validate and import a state response.
+	pub fn import_state(&mut self, response: StateResponse) -> ImportResult<B> {
+		match &mut self.phase {
+			Phase::WarpProof { .. } => {
+				debug!(target: "sync", "Unexpected state response");
+				ImportResult::BadResponse
+			},
+			Phase::State(sync) => sync.import(response),
+		}
+	}
+
+	/// Validate and import a warp proof response.
+	pub fn import_warp_proof(&mut self, response: EncodedProof) -> WarpProofImportResult<B> {
+		match &mut self.phase {
+			Phase::State(_) => {
+				debug!(target: "sync", "Unexpected warp proof response");
+				WarpProofImportResult::BadResponse
+			},
+			Phase::WarpProof { set_id, authorities, last_hash } => {
+				match self.warp_sync_provider.verify(&response, *set_id, authorities.clone()) {
+					Err(e) => {
+						debug!(target: "sync", "Bad warp proof response: {}", e);
+						WarpProofImportResult::BadResponse
+					},
+					Ok(VerificationResult::Partial(new_set_id, new_authorities, new_last_hash)) => {
+						debug!(target: "sync", "Verified partial proof, set_id={:?}", new_set_id);
+						*set_id = new_set_id;
+						*authorities = new_authorities;
+						*last_hash = new_last_hash;
+						self.total_proof_bytes += response.0.len() as u64;
+						WarpProofImportResult::WarpProofRequest(WarpProofRequest { begin: *last_hash })
+					},
+					Ok(VerificationResult::Complete(new_set_id, _, header)) => {
+						debug!(
+							target: "sync",
+							"Verified complete proof, set_id={:?}, target={:?}",
+							new_set_id,
+							header,
+						);
+						self.total_proof_bytes += response.0.len() as u64;
+						let state_sync = StateSync::new(self.client.clone(), header);
+						let request = state_sync.next_request();
+						self.phase = Phase::State(state_sync);
+						WarpProofImportResult::StateRequest(request)
+					},
+				}
+			},
+		}
+	}
+
+	/// Produce the next state request, if the target is known.
+	pub fn next_state_request(&self) -> Option<StateRequest> {
+		match &self.phase {
+			Phase::WarpProof { .. } => None,
+			Phase::State(sync) if sync.is_complete() => None,
+			Phase::State(sync) => Some(sync.next_request()),
+		}
+	}
+
+	/// Produce the next warp proof request, if the target is still unknown.
+	pub fn next_warp_proof_request(&self) -> Option<WarpProofRequest<B>> {
+		match &self.phase {
+			Phase::WarpProof { last_hash, .. } => Some(WarpProofRequest { begin: *last_hash }),
+			Phase::State(_) => None,
+		}
+	}
+
+	/// Returns the target block number, once it is proven.
+	pub fn target_block_number(&self) -> Option<NumberFor<B>> {
+		match &self.phase {
+			Phase::WarpProof { .. } => None,
+			Phase::State(sync) => Some(sync.target_block_num()),
+		}
+	}
+
+	/// Returns the target block hash, once it is proven.
+	pub fn target_block_hash(&self) -> Option<B::Hash> {
+		match &self.phase {
+			Phase::WarpProof { .. } => None,
+			Phase::State(sync) => Some(sync.target()),
+		}
+	}
+
+	/// Returns the number of downloaded bytes of warp proofs and state.
+	pub fn downloaded_bytes(&self) -> u64 {
+		match &self.phase {
+			Phase::WarpProof { .. } => self.total_proof_bytes,
+			Phase::State(sync) => self.total_proof_bytes + sync.progress().1,
+		}
+	}
+}
@@ -33,6 +33,8 @@
 //! is used to handle incoming requests.
 //!
 
+use crate::traffic::ProtocolsTraffic;
+
 use futures::{channel::{mpsc, oneshot}, prelude::*};
 use libp2p::{
 	core::{
@@ -50,7 +52,7 @@ use libp2p::{
 };
 use std::{
 	borrow::Cow, collections::{hash_map::Entry, HashMap}, convert::TryFrom as _, io, iter,
-	pin::Pin, task::{Context, Poll}, time::Duration,
+	pin::Pin, sync::Arc, task::{Context, Poll}, time::Duration,
 };
 
 pub use libp2p::request_response::{InboundFailure, OutboundFailure, RequestId};
@@ -174,7 +176,12 @@ enum RequestProcessingOutcome {
 impl RequestResponsesBehaviour {
 	/// Creates a new behaviour. Must be passed a list of supported protocols. Returns an error if
 	/// the same protocol is passed twice.
-	pub fn new(list: impl Iterator<Item = ProtocolConfig>) -> Result<Self, RegisterError> {
+	///
+	/// The size of the requests and responses is reported to `traffic`.
+	pub fn new(
+		list: impl Iterator<Item = ProtocolConfig>,
+		traffic: Arc<ProtocolsTraffic>,
+	) -> Result<Self, RegisterError> {
 		let mut protocols = HashMap::new();
 		for protocol in list {
 			let mut cfg = RequestResponseConfig::default();
@@ -190,6 +197,8 @@ impl RequestResponsesBehaviour {
 			let rq_rp = RequestResponse::new(GenericCodec {
 				max_request_size: protocol.max_request_size,
 				max_response_size: protocol.max_response_size,
+				protocol_name: protocol.name.clone(),
+				traffic: traffic.clone(),
 			}, iter::once((protocol.name.as_bytes().to_vec(), protocol_support)), cfg);
 
 			match protocols.entry(protocol.name) {
@@ -534,6 +543,9 @@ pub enum ResponseFailure {
 pub struct GenericCodec {
 	max_request_size: u64,
 	max_response_size: u64,
+	/// Name of the protocol, used to report the traffic.
+	protocol_name: Cow<'static, str>,
+	traffic: Arc<ProtocolsTraffic>,
 }
 
 #[async_trait::async_trait]
@@ -563,6 +575,7 @@ impl RequestResponseCodec for GenericCodec {
 		// Read the payload.
 		let mut buffer = vec![0; length];
 		io.read_exact(&mut buffer).await?;
+		self.traffic.report_inbound(&self.protocol_name, buffer.len());
 		Ok(buffer)
 	}
 
@@ -600,6 +613,7 @@ impl RequestResponseCodec for GenericCodec {
 		// Read the payload.
 		let mut buffer = vec![0; length];
 		io.read_exact(&mut buffer).await?;
+		self.traffic.report_inbound(&self.protocol_name, buffer.len());
 		Ok(Ok(buffer))
 	}
 
@@ -621,6 +635,7 @@ impl RequestResponseCodec for GenericCodec {
 
 		// Write the payload.
 		io.write_all(&req).await?;
+		self.traffic.report_outbound(&self.protocol_name, req.len());
 
 		io.close().await?;
 		Ok(())
@@ -646,6 +661,7 @@ impl RequestResponseCodec for GenericCodec {
 
 			// Write the payload.
 			io.write_all(&res).await?;
+			self.traffic.report_outbound(&self.protocol_name, res.len());
 		}
 
 		io.close().await?;
@@ -662,11 +678,12 @@ mod tests {
 	use libp2p::core::transport::{Transport, MemoryTransport};
 	use libp2p::noise;
 	use libp2p::swarm::{Swarm, SwarmEvent};
-	use std::{iter, time::Duration};
+	use std::{iter, sync::Arc, time::Duration};
 
 	#[test]
 	fn basic_request_response_works() {
 		let protocol_name = "/test/req-rep/1";
+		let mut traffics = Vec::new();
 
 		// Build swarms whose behaviour is `RequestResponsesBehaviour`.
 		let mut swarms = (0..2)
@@ -683,6 +700,9 @@ mod tests {
 					.multiplex(libp2p::yamux::YamuxConfig::default())
 					.boxed();
 
+				let traffic = Arc::new(super::ProtocolsTraffic::default());
+				traffics.push(traffic.clone());
+
 				let behaviour = {
 					let (tx, mut rx) = mpsc::channel(64);
 
@@ -692,7 +712,7 @@ mod tests {
 						max_response_size: 1024 * 1024,
 						request_timeout: Duration::from_secs(30),
 						inbound_queue: Some(tx),
-					})).unwrap();
+					}), traffic).unwrap();
 
 					async_std::task::spawn(async move {
 						while let Some(rq) = rx.next().await {
@@ -765,6 +785,13 @@ mod tests {
 				}
 			}
 		});
+
+		// The payloads sent and received by the requester have been accounted for.
+		let mut totals = Vec::new();
+		traffics[1].for_each(|protocol, inbound, outbound| {
+			totals.push((protocol.to_owned(), inbound, outbound))
+		});
+		assert_eq!(totals, vec![(protocol_name.to_owned(), 18, 17)]);
 	}
 
 	#[test]
@@ -795,7 +822,7 @@ mod tests {
 						max_response_size: 8,  // <-- important for the test
 						request_timeout: Duration::from_secs(30),
 						inbound_queue: Some(tx),
-					})).unwrap();
+					}), Arc::new(Default::default())).unwrap();
 
 					async_std::task::spawn(async move {
 						while let Some(rq) = rx.next().await {
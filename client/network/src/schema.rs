@@ -24,3 +24,7 @@ pub mod v1 {
 		include!(concat!(env!("OUT_DIR"), "/api.v1.light.rs"));
 	}
 }
+
+pub mod v2 {
+	include!(concat!(env!("OUT_DIR"), "/api.v2.rs"));
+}
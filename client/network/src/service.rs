@@ -30,7 +30,7 @@
 use crate::{
 	ExHashT, NetworkStateInfo, NetworkStatus,
 	behaviour::{self, Behaviour, BehaviourOut},
-	config::{parse_str_addr, NonReservedPeerMode, Params, Role, TransportConfig},
+	config::{parse_str_addr, IpNetwork, NonReservedPeerMode, Params, Role, TransportConfig},
 	DhtEvent,
 	discovery::DiscoveryConfig,
 	error::Error,
@@ -38,9 +38,12 @@ use crate::{
 		NetworkState, NotConnectedPeer as NetworkStateNotConnectedPeer, Peer as NetworkStatePeer,
 	},
 	on_demand_layer::AlwaysBadChecker,
-	light_client_handler, block_requests,
+	peer_store::{self, PeerRecord, PeerStore},
+	light_client_handler, block_request_handler, block_requests, state_request_handler,
+	warp_request_handler,
 	protocol::{
 		self,
+		HARDCODED_PEERSETS_SYNC,
 		NotifsHandlerError,
 		NotificationsSink,
 		PeerInfo,
@@ -49,7 +52,8 @@ use crate::{
 		event::Event,
 		sync::SyncState,
 	},
-	transport, ReputationChange,
+	traffic::ProtocolsTraffic,
+	transport, utils::interval, ReputationChange,
 };
 use futures::{channel::oneshot, prelude::*};
 use libp2p::{PeerId, multiaddr, Multiaddr};
@@ -93,6 +97,7 @@ use std::{
 		Arc,
 	},
 	task::Poll,
+	time::Duration,
 };
 use wasm_timer::Instant;
 
@@ -115,6 +120,8 @@ pub struct NetworkService<B: BlockT + 'static, H: ExHashT> {
 	local_peer_id: PeerId,
 	/// Bandwidth logging system. Can be queried to know the average bandwidth consumed.
 	bandwidth: Arc<transport::BandwidthSinks>,
+	/// IP ranges whose connections are refused by the transport.
+	banned_ip_ranges: transport::BannedIpRanges,
 	/// Peerset manager (PSM); manages the reputation of nodes and indicates the network which
 	/// nodes it should be connected to or not.
 	peerset: PeersetHandle,
@@ -126,6 +133,8 @@ pub struct NetworkService<B: BlockT + 'static, H: ExHashT> {
 	/// Field extracted from the [`Metrics`] struct and necessary to report the
 	/// notifications-related metrics.
 	notifications_sizes_metric: Option<HistogramVec>,
+	/// Number of bytes sent and received on each protocol.
+	protocols_traffic: Arc<ProtocolsTraffic>,
 	/// Marker to pin the `H` generic. Serves no purpose except to not break backwards
 	/// compatibility.
 	_marker: PhantomData<H>,
@@ -158,9 +167,13 @@ impl<B: BlockT + 'static, H: ExHashT> NetworkWorker<B, H> {
 
 		let (to_worker, from_service) = tracing_unbounded("mpsc_network_worker");
 
-		if let Some(path) = params.network_config.net_config_path {
-			fs::create_dir_all(&path)?;
-		}
+		// Load the peers saved during the previous runs.
+		let peer_store = if let Some(path) = &params.network_config.net_config_path {
+			fs::create_dir_all(path)?;
+			Some(PeerStore::load(path))
+		} else {
+			None
+		};
 
 		// List of multiaddresses that we know in the network.
 		let mut known_addresses = Vec::new();
@@ -193,8 +206,21 @@ impl<B: BlockT + 'static, H: ExHashT> NetworkWorker<B, H> {
 				}
 			)?;
 
-		// Initialize the peers we should always be connected to.
-		let priority_groups = {
+		// The peers of the store are added after the check above, so that a saved address that
+		// is now used by a different peer doesn't prevent the node from starting. They are not
+		// considered as bootnodes, but the peerset is told about them in the same way.
+		if let Some(peer_store) = &peer_store {
+			for (peer_id, addr) in peer_store.known_addresses() {
+				if !boot_node_ids.contains(peer_id) && !bootnodes.contains(peer_id) {
+					bootnodes.push(peer_id.clone());
+				}
+				known_addresses.push((peer_id.clone(), addr.clone()));
+			}
+		}
+
+		// Initialize the reserved peers of the main set and the peers we should always be
+		// connected to.
+		let (reserved_nodes, priority_groups) = {
 			let mut reserved_nodes = HashSet::new();
 			for reserved in params.network_config.reserved_nodes.iter() {
 				reserved_nodes.insert(reserved.peer_id.clone());
@@ -235,18 +261,60 @@ impl<B: BlockT + 'static, H: ExHashT> NetworkWorker<B, H> {
 				_ => {}
 			}
 
-			vec![
-				("reserved".to_owned(), reserved_nodes),
-				("sentries_and_validators".to_owned(), sentries_and_validators),
-			]
+			(
+				reserved_nodes,
+				vec![("sentries_and_validators".to_owned(), sentries_and_validators)],
+			)
 		};
 
-		let peerset_config = sc_peerset::PeersetConfig {
+		// The first set is the one of the nodes we sync with. Each notifications protocol with a
+		// set configuration of its own is then given the next set.
+		let mut sets = vec![sc_peerset::SetConfig {
 			in_peers: params.network_config.in_peers,
 			out_peers: params.network_config.out_peers,
 			bootnodes,
+			reserved_nodes,
 			reserved_only: params.network_config.non_reserved_mode == NonReservedPeerMode::Deny,
+		}];
+		let mut extra_sets = Vec::new();
+
+		for protocol in params.network_config.notifications_protocols.iter() {
+			let set_config = match &protocol.set_config {
+				Some(set_config) => set_config,
+				None => continue,
+			};
+
+			ensure_addresses_consistent_with_transport(
+				set_config.reserved_nodes.iter().map(|x| &x.multiaddr),
+				&params.network_config.transport,
+			)?;
+
+			let mut reserved_nodes = HashSet::new();
+			for reserved in set_config.reserved_nodes.iter() {
+				reserved_nodes.insert(reserved.peer_id.clone());
+				known_addresses.push((reserved.peer_id.clone(), reserved.multiaddr.clone()));
+			}
+
+			sets.push(sc_peerset::SetConfig {
+				in_peers: set_config.in_peers,
+				out_peers: set_config.out_peers,
+				bootnodes: Vec::new(),
+				reserved_nodes,
+				reserved_only: set_config.non_reserved_mode == NonReservedPeerMode::Deny,
+			});
+			extra_sets.push(protocol.name.clone());
+		}
+
+		// The permanent bans made at runtime are restored from the peer store.
+		let configured_banned_peers =
+			params.network_config.banned_peers.iter().cloned().collect::<HashSet<_>>();
+		let peerset_config = sc_peerset::PeersetConfig {
+			sets,
 			priority_groups,
+			banned_peers: configured_banned_peers.iter()
+				.chain(peer_store.iter().flat_map(|peer_store| peer_store.banned_peers()))
+				.cloned()
+				.collect(),
 		};
 
 		// Private and public keys configuration.
@@ -265,23 +333,49 @@ impl<B: BlockT + 'static, H: ExHashT> NetworkWorker<B, H> {
 
 		let num_connected = Arc::new(AtomicUsize::new(0));
 		let is_major_syncing = Arc::new(AtomicBool::new(false));
+		let protocols_traffic = Arc::new(ProtocolsTraffic::default());
 		let (protocol, peerset_handle) = Protocol::new(
 			protocol::ProtocolConfig {
 				roles: From::from(&params.role),
 				max_parallel_downloads: params.network_config.max_parallel_downloads,
+				sync_mode: params.network_config.sync_mode,
+				block_history_rate: params.network_config.block_history_rate,
 			},
 			local_peer_id.clone(),
 			params.chain.clone(),
 			params.transaction_pool,
 			params.protocol_id.clone(),
 			peerset_config,
+			extra_sets,
 			params.block_announce_validator,
 			params.metrics_registry.as_ref(),
+			protocols_traffic.clone(),
 			boot_node_ids.clone(),
+			params.warp_sync.clone(),
 		)?;
 
+		// Restore the reputations of the peers of the store, so that banned peers stay banned.
+		if let Some(peer_store) = &peer_store {
+			for (peer_id, reputation) in peer_store.reputations() {
+				peerset_handle.report_peer(
+					peer_id.clone(),
+					ReputationChange::new(reputation, "Restored from the peer store"),
+				);
+			}
+		}
+
+		let banned_ip_ranges = transport::BannedIpRanges::new(
+			params.network_config.banned_ip_ranges.iter().cloned(),
+		);
+
 		// Build the swarm.
-		let (mut swarm, bandwidth): (Swarm<B, H>, _) = {
+		let (
+			mut swarm,
+			bandwidth,
+			block_request_handler,
+			state_request_handler,
+			warp_sync_handler,
+		): (Swarm<B, H>, _, _, _, _) = {
 			let user_agent = format!(
 				"{} ({})",
 				params.network_config.client_version,
@@ -291,6 +385,47 @@ impl<B: BlockT + 'static, H: ExHashT> NetworkWorker<B, H> {
 				let config = block_requests::Config::new(&params.protocol_id);
 				block_requests::BlockRequests::new(config, params.chain.clone())
 			};
+			let (block_request_handler, block_request_protocol_config) =
+				block_request_handler::BlockRequestHandler::new(
+					&params.protocol_id,
+					params.chain.clone(),
+				);
+
+			// Part of the bandwidth limits is reserved for block announces and block requests,
+			// so that other protocols can't prevent us from keeping up with the chain.
+			let priority_protocols = vec![
+				protocol.block_announces_protocol().to_string(),
+				block_requests.protocol_name().to_owned(),
+				block_request_protocol_config.name.to_string(),
+			];
+
+			// Only full nodes have the state to answer state requests with.
+			let (state_request_handler, state_request_protocol_config) = match params.role {
+				Role::Light =>
+					(None, state_request_handler::generate_protocol_config(&params.protocol_id)),
+				_ => {
+					let (handler, config) = state_request_handler::StateRequestHandler::new(
+						&params.protocol_id,
+						params.chain.clone(),
+					);
+					(Some(handler), config)
+				},
+			};
+			// Warp proofs can only be served if the finality gadget provides them.
+			let (warp_sync_handler, warp_sync_protocol_config) = match params.warp_sync {
+				Some(provider) => {
+					let (handler, config) = warp_request_handler::WarpSyncRequestHandler::new(
+						&params.protocol_id,
+						provider,
+					);
+					(Some(handler), config)
+				},
+				None => (None, warp_request_handler::generate_protocol_config(&params.protocol_id)),
+			};
+			let mut request_response_protocols = params.network_config.request_response_protocols;
+			request_response_protocols.push(block_request_protocol_config);
+			request_response_protocols.push(state_request_protocol_config);
+			request_response_protocols.push(warp_sync_protocol_config);
 			let light_client_handler = {
 				let config = light_client_handler::Config::new(&params.protocol_id);
 				light_client_handler::LightClientHandler::new(
@@ -332,7 +467,11 @@ impl<B: BlockT + 'static, H: ExHashT> NetworkWorker<B, H> {
 					block_requests,
 					light_client_handler,
 					discovery_config,
-					params.network_config.request_response_protocols,
+					request_response_protocols,
+					protocols_traffic.clone(),
+					block_request_handler::generate_protocol_name(&params.protocol_id),
+					state_request_handler::generate_protocol_name(&params.protocol_id),
+					warp_request_handler::generate_protocol_name(&params.protocol_id),
 				);
 
 				match result {
@@ -346,7 +485,9 @@ impl<B: BlockT + 'static, H: ExHashT> NetworkWorker<B, H> {
 			};
 
 			for protocol in &params.network_config.notifications_protocols {
-				behaviour.register_notifications_protocol(protocol.clone());
+				if protocol.set_config.is_none() {
+					behaviour.register_notifications_protocol(protocol.name.clone());
+				}
 			}
 			let (transport, bandwidth) = {
 				let (config_mem, config_wasm) = match params.network_config.transport {
@@ -354,7 +495,14 @@ impl<B: BlockT + 'static, H: ExHashT> NetworkWorker<B, H> {
 					TransportConfig::Normal { wasm_external_transport, .. } =>
 						(false, wasm_external_transport)
 				};
-				transport::build_transport(local_identity, config_mem, config_wasm)
+				transport::build_transport(
+					local_identity,
+					config_mem,
+					config_wasm,
+					&params.network_config.bandwidth_limits,
+					priority_protocols,
+					banned_ip_ranges.clone(),
+				)
 			};
 			let mut builder = SwarmBuilder::new(transport, behaviour, local_peer_id.clone())
 				.connection_limits(ConnectionLimits::default()
@@ -373,7 +521,7 @@ impl<B: BlockT + 'static, H: ExHashT> NetworkWorker<B, H> {
 				}
 				builder = builder.executor(Box::new(SpawnImpl(spawner)));
 			}
-			(builder.build(), bandwidth)
+			(builder.build(), bandwidth, block_request_handler, state_request_handler, warp_sync_handler)
 		};
 
 		// Initialize the metrics.
@@ -381,6 +529,7 @@ impl<B: BlockT + 'static, H: ExHashT> NetworkWorker<B, H> {
 			Some(registry) => {
 				Some(metrics::register(registry, MetricSources {
 					bandwidth: bandwidth.clone(),
+					protocols_traffic: protocols_traffic.clone(),
 					major_syncing: is_major_syncing.clone(),
 					connected_peers: num_connected.clone(),
 				})?)
@@ -405,6 +554,7 @@ impl<B: BlockT + 'static, H: ExHashT> NetworkWorker<B, H> {
 
 		let service = Arc::new(NetworkService {
 			bandwidth,
+			banned_ip_ranges,
 			external_addresses: external_addresses.clone(),
 			num_connected: num_connected.clone(),
 			is_major_syncing: is_major_syncing.clone(),
@@ -414,6 +564,7 @@ impl<B: BlockT + 'static, H: ExHashT> NetworkWorker<B, H> {
 			peers_notifications_sinks: peers_notifications_sinks.clone(),
 			notifications_sizes_metric:
 				metrics.as_ref().map(|metrics| metrics.notifications_sizes.clone()),
+			protocols_traffic,
 			_marker: PhantomData,
 		});
 
@@ -430,7 +581,13 @@ impl<B: BlockT + 'static, H: ExHashT> NetworkWorker<B, H> {
 			peers_notifications_sinks,
 			metrics,
 			boot_node_ids,
+			peer_store,
+			peer_store_flush: Box::pin(interval(peer_store::FLUSH_INTERVAL)),
+			configured_banned_peers,
 			pending_requests: HashMap::with_capacity(128),
+			block_request_handler,
+			state_request_handler,
+			warp_sync_handler,
 		})
 	}
 
@@ -597,6 +754,57 @@ impl<B: BlockT + 'static, H: ExHashT> NetworkWorker<B, H> {
 		}
 	}
 
+	/// Returns the content of the peer store, updated with the current state of the network.
+	///
+	/// The store is empty if there isn't any network configuration directory.
+	pub fn peer_store(&mut self) -> Vec<(PeerId, PeerRecord)> {
+		self.update_peer_store();
+		self.peer_store.iter()
+			.flat_map(|peer_store| peer_store.peers())
+			.map(|(peer_id, record)| (peer_id.clone(), record.clone()))
+			.collect()
+	}
+
+	/// Returns the peers that are explicitly banned, and for how long they remain banned. `None`
+	/// means that the ban is permanent.
+	pub fn banned_peers(&mut self) -> Vec<(PeerId, Option<Duration>)> {
+		self.network_service.user_protocol_mut().banned_peers()
+	}
+
+	/// Updates the peer store with the addresses and reputations known to the network.
+	fn update_peer_store(&mut self) {
+		let peer_store = match self.peer_store.as_mut() {
+			Some(peer_store) => peer_store,
+			None => return,
+		};
+
+		let swarm = &mut self.network_service;
+		let peers = peer_store.peers().map(|(peer_id, _)| peer_id.clone()).collect::<Vec<_>>();
+		for peer_id in peers {
+			let addresses = NetworkBehaviour::addresses_of_peer(&mut **swarm, &peer_id);
+			peer_store.add_addresses(&peer_id, addresses);
+		}
+
+		peer_store.set_reputations(swarm.user_protocol_mut().peers_reputations());
+
+		let configured_banned_peers = &self.configured_banned_peers;
+		peer_store.set_banned_peers(swarm.user_protocol_mut().banned_peers().into_iter()
+			.filter(|(peer_id, remaining)| {
+				remaining.is_none() && !configured_banned_peers.contains(peer_id)
+			})
+			.map(|(peer_id, _)| peer_id));
+	}
+
+	/// Updates the peer store and hands it over to its background thread to write it to the disk.
+	fn flush_peer_store(&mut self) {
+		self.update_peer_store();
+		if let Some(peer_store) = self.peer_store.as_mut() {
+			if let Err(err) = peer_store.flush() {
+				warn!(target: "sub-libp2p", "Failed to save the peer store: {}", err);
+			}
+		}
+	}
+
 	/// Get currently connected peers.
 	pub fn peers_debug_info(&mut self) -> Vec<(PeerId, PeerInfo<B>)> {
 		self.network_service.user_protocol_mut()
@@ -615,6 +823,32 @@ impl<B: BlockT + 'static, H: ExHashT> NetworkWorker<B, H> {
 	pub fn add_reserved_peer(&self, peer: String) -> Result<(), String> {
 		self.service.add_reserved_peer(peer)
 	}
+
+	/// Bans a `PeerId` for the given duration, or permanently if `duration` is `None`.
+	pub fn ban_peer(&self, peer: PeerId, duration: Option<Duration>) {
+		self.service.ban_peer(peer, duration);
+	}
+
+	/// Lifts the ban of a `PeerId`.
+	pub fn unban_peer(&self, peer: PeerId) {
+		self.service.unban_peer(peer);
+	}
+
+	/// Bans an IP range for the given duration, or permanently if `duration` is `None`.
+	pub fn ban_ip_range(&self, range: IpNetwork, duration: Option<Duration>) {
+		self.service.ban_ip_range(range, duration);
+	}
+
+	/// Lifts the ban of an IP range. Returns false if the range wasn't banned.
+	pub fn unban_ip_range(&self, range: &IpNetwork) -> bool {
+		self.service.unban_ip_range(range)
+	}
+
+	/// Returns the banned IP ranges, and for how long they remain banned. `None` means that the
+	/// ban is permanent.
+	pub fn banned_ip_ranges(&self) -> Vec<(IpNetwork, Option<Duration>)> {
+		self.service.banned_ip_ranges()
+	}
 }
 
 impl<B: BlockT + 'static, H: ExHashT> NetworkService<B, H> {
@@ -628,7 +862,7 @@ impl<B: BlockT + 'static, H: ExHashT> NetworkService<B, H> {
 	/// Need a better solution to manage authorized peers, but now just use reserved peers for
 	/// prototyping.
 	pub fn set_authorized_peers(&self, peers: HashSet<PeerId>) {
-		self.peerset.set_reserved_peers(peers)
+		self.peerset.set_reserved_peers(HARDCODED_PEERSETS_SYNC, peers)
 	}
 
 	/// Set authorized_only flag.
@@ -636,7 +870,7 @@ impl<B: BlockT + 'static, H: ExHashT> NetworkService<B, H> {
 	/// Need a better solution to decide authorized_only, but now just use reserved_only flag for
 	/// prototyping.
 	pub fn set_authorized_only(&self, reserved_only: bool) {
-		self.peerset.set_reserved_only(reserved_only)
+		self.peerset.set_reserved_only(HARDCODED_PEERSETS_SYNC, reserved_only)
 	}
 
 	/// Appends a notification to the buffer of pending outgoing notifications with the given peer.
@@ -682,6 +916,7 @@ impl<B: BlockT + 'static, H: ExHashT> NetworkService<B, H> {
 				.with_label_values(&["out", &protocol])
 				.observe(message.len() as f64);
 		}
+		self.protocols_traffic.report_outbound(&protocol, message.len());
 
 		// Sending is communicated to the `NotificationsSink`.
 		trace!(
@@ -786,6 +1021,7 @@ impl<B: BlockT + 'static, H: ExHashT> NetworkService<B, H> {
 			sink,
 			protocol_name: protocol,
 			notification_size_metric,
+			protocols_traffic: self.protocols_traffic.clone(),
 		})
 	}
 
@@ -881,6 +1117,41 @@ impl<B: BlockT + 'static, H: ExHashT> NetworkService<B, H> {
 		let _ = self.to_worker.unbounded_send(ServiceToWorkerMsg::DisconnectPeer(who));
 	}
 
+	/// Bans a peer for the given duration, or permanently if `duration` is `None`.
+	///
+	/// The peer is disconnected, and we neither connect to it nor accept its connections until
+	/// the ban expires or is lifted with [`NetworkService::unban_peer`].
+	pub fn ban_peer(&self, who: PeerId, duration: Option<Duration>) {
+		self.peerset.ban_peer(who, duration);
+	}
+
+	/// Lifts the ban of a peer. Has no effect if the peer isn't banned.
+	pub fn unban_peer(&self, who: PeerId) {
+		self.peerset.unban_peer(who);
+	}
+
+	/// Bans an IP range for the given duration, or permanently if `duration` is `None`.
+	///
+	/// The peers connected from or to an address of the range are disconnected, and connections
+	/// from and to the range are refused until the ban expires or is lifted with
+	/// [`NetworkService::unban_ip_range`]. Banning a range that is already banned replaces the
+	/// previous expiry time.
+	pub fn ban_ip_range(&self, range: IpNetwork, duration: Option<Duration>) {
+		self.banned_ip_ranges.ban(range, duration);
+		let _ = self.to_worker.unbounded_send(ServiceToWorkerMsg::DisconnectBannedAddresses);
+	}
+
+	/// Lifts the ban of an IP range. Returns false if the range wasn't banned.
+	pub fn unban_ip_range(&self, range: &IpNetwork) -> bool {
+		self.banned_ip_ranges.unban(range)
+	}
+
+	/// Returns the banned IP ranges, and for how long they remain banned. `None` means that the
+	/// ban is permanent.
+	pub fn banned_ip_ranges(&self) -> Vec<(IpNetwork, Option<Duration>)> {
+		self.banned_ip_ranges.banned()
+	}
+
 	/// Request a justification for the given block from the network.
 	///
 	/// On success, the justification will be passed to the import queue that was part at
@@ -918,17 +1189,17 @@ impl<B: BlockT + 'static, H: ExHashT> NetworkService<B, H> {
 
 	/// Connect to unreserved peers and allow unreserved peers to connect.
 	pub fn accept_unreserved_peers(&self) {
-		self.peerset.set_reserved_only(false);
+		self.peerset.set_reserved_only(HARDCODED_PEERSETS_SYNC, false);
 	}
 
 	/// Disconnect from unreserved peers and deny new unreserved peers to connect.
 	pub fn deny_unreserved_peers(&self) {
-		self.peerset.set_reserved_only(true);
+		self.peerset.set_reserved_only(HARDCODED_PEERSETS_SYNC, true);
 	}
 
 	/// Removes a `PeerId` from the list of reserved peers.
 	pub fn remove_reserved_peer(&self, peer: PeerId) {
-		self.peerset.remove_reserved_peer(peer);
+		self.peerset.remove_reserved_peer(HARDCODED_PEERSETS_SYNC, peer);
 	}
 
 	/// Adds a `PeerId` and its address as reserved. The string should encode the address
@@ -942,7 +1213,7 @@ impl<B: BlockT + 'static, H: ExHashT> NetworkService<B, H> {
 		if peer_id == self.local_peer_id {
 			return Err("Local peer ID cannot be added as a reserved peer.".to_string())
 		}
-		self.peerset.add_reserved_peer(peer_id.clone());
+		self.peerset.add_reserved_peer(HARDCODED_PEERSETS_SYNC, peer_id.clone());
 		let _ = self
 			.to_worker
 			.unbounded_send(ServiceToWorkerMsg::AddKnownAddress(peer_id, addr));
@@ -1115,6 +1386,9 @@ pub struct NotificationSender {
 	/// Field extracted from the [`Metrics`] struct and necessary to report the
 	/// notifications-related metrics.
 	notification_size_metric: Option<Histogram>,
+
+	/// Number of bytes sent and received on each protocol.
+	protocols_traffic: Arc<ProtocolsTraffic>,
 }
 
 impl NotificationSender {
@@ -1126,7 +1400,9 @@ impl NotificationSender {
 				Err(()) => return Err(NotificationSenderError::Closed),
 			},
 			peer_id: self.sink.peer_id(),
+			protocol_name: &self.protocol_name,
 			notification_size_metric: self.notification_size_metric.clone(),
+			protocols_traffic: &self.protocols_traffic,
 		})
 	}
 }
@@ -1139,9 +1415,15 @@ pub struct NotificationSenderReady<'a> {
 	/// Target of the notification.
 	peer_id: &'a PeerId,
 
+	/// Name of the protocol on the wire.
+	protocol_name: &'a Cow<'static, str>,
+
 	/// Field extracted from the [`Metrics`] struct and necessary to report the
 	/// notifications-related metrics.
 	notification_size_metric: Option<Histogram>,
+
+	/// Number of bytes sent and received on each protocol.
+	protocols_traffic: &'a ProtocolsTraffic,
 }
 
 impl<'a> NotificationSenderReady<'a> {
@@ -1152,6 +1434,7 @@ impl<'a> NotificationSenderReady<'a> {
 		if let Some(notification_size_metric) = &self.notification_size_metric {
 			notification_size_metric.observe(notification.len() as f64);
 		}
+		self.protocols_traffic.report_outbound(self.protocol_name, notification.len());
 
 		trace!(
 			target: "sub-libp2p",
@@ -1201,6 +1484,7 @@ enum ServiceToWorkerMsg<B: BlockT, H: ExHashT> {
 		pending_response: oneshot::Sender<Result<Vec<u8>, RequestFailure>>,
 	},
 	DisconnectPeer(PeerId),
+	DisconnectBannedAddresses,
 	NewBestBlockImported(B::Hash, NumberFor<B>),
 }
 
@@ -1231,6 +1515,12 @@ pub struct NetworkWorker<B: BlockT + 'static, H: ExHashT> {
 	metrics: Option<Metrics>,
 	/// The `PeerId`'s of all boot nodes.
 	boot_node_ids: Arc<HashSet<PeerId>>,
+	/// Peers persisted across restarts. `None` if there is no network configuration directory.
+	peer_store: Option<PeerStore>,
+	/// Peers banned by the configuration. Their bans aren't saved in the peer store.
+	configured_banned_peers: HashSet<PeerId>,
+	/// Stream that fires when the peer store must be flushed to the disk.
+	peer_store_flush: Pin<Box<dyn Stream<Item = ()> + Send>>,
 	/// Requests started using [`NetworkService::request`]. Includes the channel to send back the
 	/// response, when the request has started, and the name of the protocol for diagnostic
 	/// purposes.
@@ -1238,6 +1528,12 @@ pub struct NetworkWorker<B: BlockT + 'static, H: ExHashT> {
 		behaviour::RequestId,
 		(oneshot::Sender<Result<Vec<u8>, RequestFailure>>, Instant, String)
 	>,
+	/// Answers incoming block requests of the second version of the protocol.
+	block_request_handler: block_request_handler::BlockRequestHandler<B>,
+	/// Answers incoming state requests. `None` for light clients.
+	state_request_handler: Option<state_request_handler::StateRequestHandler<B>>,
+	/// Answers incoming warp proof requests. `None` if no warp sync provider was given.
+	warp_sync_handler: Option<warp_request_handler::WarpSyncRequestHandler<B>>,
 	/// For each peer and protocol combination, an object that allows sending notifications to
 	/// that peer. Shared with the [`NetworkService`].
 	peers_notifications_sinks: Arc<Mutex<HashMap<(PeerId, Cow<'static, str>), NotificationsSink>>>,
@@ -1254,6 +1550,19 @@ impl<B: BlockT + 'static, H: ExHashT> Future for NetworkWorker<B, H> {
 			protocol: &mut this.network_service,
 		});
 
+		// Answer incoming block requests.
+		this.block_request_handler.poll(cx);
+
+		// Answer incoming state requests.
+		if let Some(state_request_handler) = this.state_request_handler.as_mut() {
+			state_request_handler.poll(cx);
+		}
+
+		// Answer incoming warp proof requests.
+		if let Some(warp_sync_handler) = this.warp_sync_handler.as_mut() {
+			warp_sync_handler.poll(cx);
+		}
+
 		// Check for new incoming light client requests.
 		if let Some(light_client_rqs) = this.light_client_rqs.as_mut() {
 			while let Poll::Ready(Some(rq)) = light_client_rqs.poll_next_unpin(cx) {
@@ -1336,6 +1645,19 @@ impl<B: BlockT + 'static, H: ExHashT> Future for NetworkWorker<B, H> {
 				},
 				ServiceToWorkerMsg::DisconnectPeer(who) =>
 					this.network_service.user_protocol_mut().disconnect_peer(&who),
+				ServiceToWorkerMsg::DisconnectBannedAddresses => {
+					let banned_ip_ranges = &this.service.banned_ip_ranges;
+					let swarm = &mut this.network_service;
+					let banned = swarm.user_protocol().open_peers()
+						.filter(|peer_id| swarm.node(peer_id).map_or(false, |info| {
+							banned_ip_ranges.is_banned(info.endpoint().get_remote_address())
+						}))
+						.cloned()
+						.collect::<Vec<_>>();
+					for peer_id in banned {
+						swarm.user_protocol_mut().disconnect_peer(&peer_id);
+					}
+				},
 				ServiceToWorkerMsg::NewBestBlockImported(hash, number) =>
 					this.network_service.user_protocol_mut().new_best_block_imported(hash, number),
 			}
@@ -1364,11 +1686,11 @@ impl<B: BlockT + 'static, H: ExHashT> Future for NetworkWorker<B, H> {
 					}
 					this.import_queue.import_blocks(origin, blocks);
 				},
-				Poll::Ready(SwarmEvent::Behaviour(BehaviourOut::JustificationImport(origin, hash, nb, justification))) => {
+				Poll::Ready(SwarmEvent::Behaviour(BehaviourOut::JustificationImport(origin, hash, nb, justifications))) => {
 					if let Some(metrics) = this.metrics.as_ref() {
 						metrics.import_queue_justifications_submitted.inc();
 					}
-					this.import_queue.import_justification(origin, hash, nb, justification);
+					this.import_queue.import_justifications(origin, hash, nb, justifications);
 				},
 				Poll::Ready(SwarmEvent::Behaviour(BehaviourOut::InboundRequest { protocol, result, .. })) => {
 					if let Some(metrics) = this.metrics.as_ref() {
@@ -1544,6 +1866,14 @@ impl<B: BlockT + 'static, H: ExHashT> Future for NetworkWorker<B, H> {
 				Poll::Ready(SwarmEvent::ConnectionEstablished { peer_id, endpoint, num_established }) => {
 					trace!(target: "sub-libp2p", "Libp2p => Connected({:?})", peer_id);
 
+					if let Some(peer_store) = this.peer_store.as_mut() {
+						let address = match &endpoint {
+							ConnectedPoint::Dialer { address } => Some(address),
+							ConnectedPoint::Listener { .. } => None,
+						};
+						peer_store.on_connected(&peer_id, address);
+					}
+
 					if let Some(metrics) = this.metrics.as_ref() {
 						let direction = match endpoint {
 							ConnectedPoint::Dialer { .. } => "out",
@@ -1558,6 +1888,9 @@ impl<B: BlockT + 'static, H: ExHashT> Future for NetworkWorker<B, H> {
 				},
 				Poll::Ready(SwarmEvent::ConnectionClosed { peer_id, cause, endpoint, num_established }) => {
 					trace!(target: "sub-libp2p", "Libp2p => Disconnected({:?}, {:?})", peer_id, cause);
+					if let Some(peer_store) = this.peer_store.as_mut() {
+						peer_store.on_connected(&peer_id, None);
+					}
 					if let Some(metrics) = this.metrics.as_ref() {
 						let direction = match endpoint {
 							ConnectedPoint::Dialer { .. } => "out",
@@ -1704,6 +2037,11 @@ impl<B: BlockT + 'static, H: ExHashT> Future for NetworkWorker<B, H> {
 
 		this.is_major_syncing.store(is_major_syncing, Ordering::Relaxed);
 
+		// Periodically save the peer store.
+		while let Poll::Ready(Some(())) = this.peer_store_flush.poll_next_unpin(cx) {
+			this.flush_peer_store();
+		}
+
 		if let Some(metrics) = this.metrics.as_ref() {
 			for (proto, buckets) in this.network_service.num_entries_per_kbucket() {
 				for (lower_ilog2_bucket_bound, num_entries) in buckets {
@@ -1732,6 +2070,12 @@ impl<B: BlockT + 'static, H: ExHashT> Future for NetworkWorker<B, H> {
 impl<B: BlockT + 'static, H: ExHashT> Unpin for NetworkWorker<B, H> {
 }
 
+impl<B: BlockT + 'static, H: ExHashT> Drop for NetworkWorker<B, H> {
+	fn drop(&mut self) {
+		self.flush_peer_store();
+	}
+}
+
 /// The libp2p swarm, customized for our needs.
 type Swarm<B, H> = libp2p::swarm::Swarm<Behaviour<B, H>>;
 
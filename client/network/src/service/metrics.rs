@@ -16,7 +16,7 @@
 // You should have received a copy of the GNU General Public License
 // along with this program. If not, see <https://www.gnu.org/licenses/>.
 
-use crate::transport::BandwidthSinks;
+use crate::{traffic::ProtocolsTraffic, transport::BandwidthSinks};
 use prometheus_endpoint::{
 	self as prometheus,
 	Counter, CounterVec, Gauge, GaugeVec, HistogramOpts,
@@ -36,6 +36,7 @@ pub use prometheus_endpoint::{Histogram, HistogramVec};
 /// Registers all networking metrics with the given registry.
 pub fn register(registry: &Registry, sources: MetricSources) -> Result<Metrics, PrometheusError> {
 	BandwidthCounters::register(registry, sources.bandwidth)?;
+	ProtocolsTrafficCounters::register(registry, sources.protocols_traffic)?;
 	MajorSyncingGauge::register(registry, sources.major_syncing)?;
 	NumConnectedGauge::register(registry, sources.connected_peers)?;
 	Metrics::register(registry)
@@ -44,6 +45,7 @@ pub fn register(registry: &Registry, sources: MetricSources) -> Result<Metrics,
 /// Predefined metric sources that are fed directly into prometheus.
 pub struct MetricSources {
 	pub bandwidth: Arc<BandwidthSinks>,
+	pub protocols_traffic: Arc<ProtocolsTraffic>,
 	pub major_syncing: Arc<AtomicBool>,
 	pub connected_peers: Arc<AtomicUsize>,
 }
@@ -295,6 +297,37 @@ impl MetricSource for BandwidthCounters {
 	}
 }
 
+/// The per-protocol bandwidth counter metric.
+#[derive(Clone)]
+pub struct ProtocolsTrafficCounters(Arc<ProtocolsTraffic>);
+
+impl ProtocolsTrafficCounters {
+	/// Registers the `ProtocolsTrafficCounters` metric whose values are
+	/// obtained from the given `ProtocolsTraffic`.
+	fn register(registry: &Registry, traffic: Arc<ProtocolsTraffic>) -> Result<(), PrometheusError> {
+		prometheus::register(SourcedCounter::new(
+			&Opts::new(
+				"sub_libp2p_protocol_bytes_total",
+				"Total size of the notifications, requests and responses, by protocol"
+			).variable_label("direction").variable_label("protocol"),
+			ProtocolsTrafficCounters(traffic),
+		)?, registry)?;
+
+		Ok(())
+	}
+}
+
+impl MetricSource for ProtocolsTrafficCounters {
+	type N = u64;
+
+	fn collect(&self, mut set: impl FnMut(&[&str], Self::N)) {
+		self.0.for_each(|protocol, inbound, outbound| {
+			set(&["in", protocol], inbound);
+			set(&["out", protocol], outbound);
+		});
+	}
+}
+
 /// The "major syncing" metric.
 #[derive(Clone)]
 pub struct MajorSyncingGauge(Arc<AtomicBool>);
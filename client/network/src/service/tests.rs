@@ -50,7 +50,7 @@ fn build_test_full_node(config: config::NetworkConfiguration)
 			&mut self,
 			origin: sp_consensus::BlockOrigin,
 			header: B::Header,
-			justification: Option<sp_runtime::Justification>,
+			justifications: Option<sp_runtime::Justifications>,
 			body: Option<Vec<B::Extrinsic>>,
 		) -> Result<
 			(
@@ -77,7 +77,7 @@ fn build_test_full_node(config: config::NetworkConfiguration)
 			let mut import = sp_consensus::BlockImportParams::new(origin, header);
 			import.body = body;
 			import.finalized = self.0;
-			import.justification = justification;
+			import.justifications = justifications;
 			import.fork_choice = Some(sp_consensus::ForkChoiceStrategy::LongestChain);
 			Ok((import, maybe_keys))
 		}
@@ -104,6 +104,7 @@ fn build_test_full_node(config: config::NetworkConfiguration)
 			sp_consensus::block_validation::DefaultBlockAnnounceValidator,
 		),
 		metrics_registry: None,
+		warp_sync: None,
 	})
 	.unwrap();
 
@@ -124,18 +125,26 @@ const PROTOCOL_NAME: Cow<'static, str> = Cow::Borrowed("/foo");
 /// The nodes are connected together and have the `PROTOCOL_NAME` protocol registered.
 fn build_nodes_one_proto()
 	-> (Arc<TestNetworkService>, impl Stream<Item = Event>, Arc<TestNetworkService>, impl Stream<Item = Event>)
+{
+	build_nodes_one_proto_with_limits(Default::default())
+}
+
+/// Same as [`build_nodes_one_proto`], but the bandwidth of the first node is limited.
+fn build_nodes_one_proto_with_limits(bandwidth_limits: config::BandwidthLimits)
+	-> (Arc<TestNetworkService>, impl Stream<Item = Event>, Arc<TestNetworkService>, impl Stream<Item = Event>)
 {
 	let listen_addr = config::build_multiaddr![Memory(rand::random::<u64>())];
 
 	let (node1, events_stream1) = build_test_full_node(config::NetworkConfiguration {
-		notifications_protocols: vec![PROTOCOL_NAME],
+		notifications_protocols: vec![PROTOCOL_NAME.into()],
 		listen_addresses: vec![listen_addr.clone()],
 		transport: config::TransportConfig::MemoryOnly,
+		bandwidth_limits,
 		.. config::NetworkConfiguration::new_local()
 	});
 
 	let (node2, events_stream2) = build_test_full_node(config::NetworkConfiguration {
-		notifications_protocols: vec![PROTOCOL_NAME],
+		notifications_protocols: vec![PROTOCOL_NAME.into()],
 		listen_addresses: vec![],
 		reserved_nodes: vec![config::MultiaddrWithPeerId {
 			multiaddr: listen_addr,
@@ -278,7 +287,7 @@ fn lots_of_incoming_peers_works() {
 	let listen_addr = config::build_multiaddr![Memory(rand::random::<u64>())];
 
 	let (main_node, _) = build_test_full_node(config::NetworkConfiguration {
-		notifications_protocols: vec![PROTOCOL_NAME],
+		notifications_protocols: vec![PROTOCOL_NAME.into()],
 		listen_addresses: vec![listen_addr.clone()],
 		in_peers: u32::max_value(),
 		transport: config::TransportConfig::MemoryOnly,
@@ -295,7 +304,7 @@ fn lots_of_incoming_peers_works() {
 		let main_node_peer_id = main_node_peer_id.clone();
 
 		let (_dialing_node, event_stream) = build_test_full_node(config::NetworkConfiguration {
-			notifications_protocols: vec![PROTOCOL_NAME],
+			notifications_protocols: vec![PROTOCOL_NAME.into()],
 			listen_addresses: vec![],
 			reserved_nodes: vec![config::MultiaddrWithPeerId {
 				multiaddr: listen_addr.clone(),
@@ -394,6 +403,61 @@ fn notifications_back_pressure() {
 	});
 }
 
+#[test]
+fn upload_limit_slows_down_notifications() {
+	// Node 1 sends 128 KiB of notifications to node 2 with an upload limit of 32 KiB/s. Since
+	// at most one second worth of bytes can be sent at once, this takes at least three seconds.
+
+	const NOTIF_SIZE: usize = 1024;
+	const TOTAL_NOTIFS: usize = 128;
+
+	let (node1, mut events_stream1, node2, mut events_stream2) =
+		build_nodes_one_proto_with_limits(config::BandwidthLimits {
+			max_upload_rate: std::num::NonZeroU64::new(32 * 1024),
+			..Default::default()
+		});
+	let node2_id = node2.local_peer_id();
+
+	let receiver = async_std::task::spawn(async move {
+		let mut received_notifications = 0;
+
+		while received_notifications < TOTAL_NOTIFS {
+			match events_stream2.next().await.unwrap() {
+				Event::NotificationStreamClosed { .. } => panic!(),
+				Event::NotificationsReceived { messages, .. } => {
+					for message in messages {
+						assert_eq!(message.0, PROTOCOL_NAME);
+						assert_eq!(message.1.len(), NOTIF_SIZE);
+						received_notifications += 1;
+					}
+				}
+				_ => {}
+			};
+		}
+	});
+
+	async_std::task::block_on(async move {
+		// Wait for the `NotificationStreamOpened`.
+		loop {
+			match events_stream1.next().await.unwrap() {
+				Event::NotificationStreamOpened { .. } => break,
+				_ => {}
+			};
+		}
+
+		let started = std::time::Instant::now();
+		for _ in 0..TOTAL_NOTIFS {
+			let notif = node1.notification_sender(node2_id.clone(), PROTOCOL_NAME).unwrap();
+			notif.ready().await.unwrap().send(vec![0; NOTIF_SIZE]).unwrap();
+		}
+
+		receiver.await;
+		let elapsed = started.elapsed();
+		assert!(elapsed >= Duration::from_secs(3), "took {:?}", elapsed);
+		assert!(elapsed < Duration::from_secs(30), "took {:?}", elapsed);
+	});
+}
+
 #[test]
 #[should_panic(expected = "don't match the transport")]
 fn ensure_listen_addresses_consistent_with_transport_memory() {
@@ -0,0 +1,141 @@
+// Copyright 2020 Parity Technologies (UK) Ltd.
+// This file is part of Substrate.
+//
+// Substrate is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// Substrate is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with Substrate.  If not, see <http://www.gnu.org/licenses/>.
+
+//! Helper for handling (i.e. answering) state requests from a remote peer via the
+//! [`crate::request_responses::RequestResponsesBehaviour`].
+//!
+//! Incoming requests are encoded as protocol buffers (cf. `api.v1.proto`). Every response
+//! carries a storage proof of a range of key-value pairs of the state at the requested block,
+//! starting right after the requested key.
+
+use codec::{Encode, Decode};
+use crate::{
+	chain::Client,
+	config::ProtocolId,
+	request_responses::{IncomingRequest, ProtocolConfig},
+	schema,
+};
+use futures::{channel::mpsc, prelude::*};
+use prost::Message;
+use sp_core::hexdisplay::HexDisplay;
+use sp_runtime::{generic::BlockId, traits::Block as BlockT};
+use std::{
+	sync::Arc,
+	task::{Context, Poll},
+	time::Duration,
+};
+
+const LOG_TARGET: &str = "sync";
+/// Maximum size of a proof we put in a single response.
+const MAX_RESPONSE_BYTES: usize = 2 * 1024 * 1024;
+/// Maximum number of requests buffered before new ones are rejected.
+const MAX_NUMBER_OF_CONCURRENT_REQUESTS: usize = 20;
+
+/// Generates a [`ProtocolConfig`] for the state request protocol, refusing incoming requests.
+pub fn generate_protocol_config(protocol_id: &ProtocolId) -> ProtocolConfig {
+	ProtocolConfig {
+		name: generate_protocol_name(protocol_id).into(),
+		max_request_size: 1024 * 1024,
+		max_response_size: 16 * 1024 * 1024,
+		request_timeout: Duration::from_secs(40),
+		inbound_queue: None,
+	}
+}
+
+/// Generate the state protocol name from chain specific protocol identifier.
+pub fn generate_protocol_name(protocol_id: &ProtocolId) -> String {
+	format!("/{}/state/1", protocol_id.as_ref())
+}
+
+/// Handler for incoming state requests from a remote peer.
+pub struct StateRequestHandler<B: BlockT> {
+	client: Arc<dyn Client<B>>,
+	request_receiver: mpsc::Receiver<IncomingRequest>,
+}
+
+impl<B: BlockT> StateRequestHandler<B> {
+	/// Create a new [`StateRequestHandler`], along with the protocol configuration it answers
+	/// requests for.
+	pub fn new(protocol_id: &ProtocolId, client: Arc<dyn Client<B>>) -> (Self, ProtocolConfig) {
+		let (tx, request_receiver) = mpsc::channel(MAX_NUMBER_OF_CONCURRENT_REQUESTS);
+
+		let mut protocol_config = generate_protocol_config(protocol_id);
+		protocol_config.inbound_queue = Some(tx);
+
+		(Self { client, request_receiver }, protocol_config)
+	}
+
+	/// Answer all the requests that are currently queued.
+	pub fn poll(&mut self, cx: &mut Context) {
+		while let Poll::Ready(Some(request)) = self.request_receiver.poll_next_unpin(cx) {
+			let IncomingRequest { peer, payload, pending_response } = request;
+
+			match self.handle_request(&payload) {
+				Ok(response) => {
+					// The remote might have stopped waiting for the response in the meantime.
+					let _ = pending_response.send(response);
+				}
+				Err(e) => log::debug!(
+					target: LOG_TARGET,
+					"Failed to handle state request from {}: {}",
+					peer, e,
+				),
+			}
+		}
+	}
+
+	fn handle_request(&self, payload: &[u8]) -> Result<Vec<u8>, HandleRequestError> {
+		let request = schema::v1::StateRequest::decode(payload)?;
+		let block: B::Hash = Decode::decode(&mut request.block.as_ref())?;
+
+		log::trace!(
+			target: LOG_TARGET,
+			"Handling state request for {:?}, starting at {:?}",
+			block,
+			request.start.iter().map(HexDisplay::from).collect::<Vec<_>>(),
+		);
+
+		let (proof, count) = self.client.read_proof_collection(
+			&BlockId::hash(block),
+			&request.start,
+			MAX_RESPONSE_BYTES,
+		)?;
+
+		log::trace!(
+			target: LOG_TARGET,
+			"Sending state response with {} entries for {:?}",
+			count,
+			block,
+		);
+
+		let response = schema::v1::StateResponse { proof: proof.encode() };
+		let mut data = Vec::with_capacity(response.encoded_len());
+		response.encode(&mut data)?;
+		Ok(data)
+	}
+}
+
+#[derive(derive_more::Display, derive_more::From)]
+enum HandleRequestError {
+	#[display(fmt = "Failed to decode request: {}.", _0)]
+	DecodeProto(prost::DecodeError),
+	#[display(fmt = "Failed to encode response: {}.", _0)]
+	EncodeProto(prost::EncodeError),
+	#[display(fmt = "Failed to decode block hash: {}.", _0)]
+	DecodeScale(codec::Error),
+	#[display(fmt = "Failed to read the state: {}.", _0)]
+	Client(sp_blockchain::Error),
+}
@@ -0,0 +1,732 @@
+// This file is part of Substrate.
+
+// Copyright (C) 2021 Parity Technologies (UK) Ltd.
+// SPDX-License-Identifier: GPL-3.0-or-later WITH Classpath-exception-2.0
+
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with this program. If not, see <https://www.gnu.org/licenses/>.
+
+//! Bandwidth limiting and per-protocol traffic accounting.
+//!
+//! Limits are enforced by [`ThrottledMuxer`], which wraps the stream muxer of every connection
+//! and only lets a substream read or write as many bytes as the global and per-peer token
+//! buckets allow. Since the muxer is configured to only send window updates when data is
+//! consumed, not reading from a substream also slows down the remote.
+//!
+//! A share of every bucket is reserved for the substreams of the priority protocols, such as
+//! block announces and block requests: the other substreams can't empty a bucket below
+//! [`PRIORITY_RESERVE`] of its capacity. A flood on another protocol therefore can't prevent
+//! the priority protocols from getting through. The protocol of a substream is found out by
+//! looking at the multistream-select messages exchanged when the substream is opened. Until
+//! then, the substream isn't a priority one.
+
+use crate::config::BandwidthLimits;
+
+use futures::prelude::*;
+use futures_timer::Delay;
+use libp2p::{PeerId, core::muxing::{StreamMuxer, StreamMuxerEvent}};
+use parking_lot::Mutex;
+use std::{
+	borrow::Cow, collections::{HashMap, HashSet}, num::NonZeroU64, pin::Pin, sync::{Arc, Weak},
+	task::{Context, Poll}, time::Duration,
+};
+use wasm_timer::Instant;
+
+/// Minimum number of bytes we wait for before waking up a throttled substream, unless the
+/// limit itself is lower. Avoids waking up for every single byte on very busy connections.
+const MIN_CHUNK_SIZE: f64 = 1024.0;
+
+/// Share of the capacity of the buckets that only the substreams of the priority protocols can
+/// use.
+const PRIORITY_RESERVE: f64 = 0.25;
+
+/// Number of bytes exchanged in each direction after which we stop looking for the protocol
+/// negotiated on a substream.
+const MAX_NEGOTIATION_BYTES: usize = 1024;
+
+/// First message of multistream-select, sent by both sides.
+const MULTISTREAM_HEADER: &[u8] = b"/multistream/1.0.0\n";
+
+/// Direction of a transfer.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+enum Direction {
+	Download,
+	Upload,
+}
+
+/// Classic token bucket. Holds at most one second worth of bytes.
+struct TokenBucket {
+	/// Number of bytes added per second.
+	rate: f64,
+	/// Number of bytes that can currently be transferred. Can go below zero, as bytes are only
+	/// accounted for after they have been transferred.
+	available: f64,
+	/// Last time `available` has been updated.
+	last_refill: Instant,
+}
+
+impl TokenBucket {
+	fn new(rate: NonZeroU64) -> Self {
+		TokenBucket {
+			rate: rate.get() as f64,
+			available: rate.get() as f64,
+			last_refill: Instant::now(),
+		}
+	}
+
+	fn refill(&mut self) {
+		let now = Instant::now();
+		let elapsed = now - self.last_refill;
+		self.last_refill = now;
+		self.available = (self.available + elapsed.as_secs_f64() * self.rate).min(self.rate);
+	}
+
+	/// Returns the number of bytes that can be transferred right now, or how long to wait for
+	/// more bytes to become available. Only `priority` transfers can use the reserved share of
+	/// the bucket.
+	fn allowance(&mut self, priority: bool) -> Result<usize, Duration> {
+		self.refill();
+		let reserved = if priority { 0.0 } else { self.rate * PRIORITY_RESERVE };
+		let available = self.available - reserved;
+		if available >= 1.0 {
+			Ok(available as usize)
+		} else {
+			let target = MIN_CHUNK_SIZE.min(self.rate - reserved);
+			Err(Duration::from_secs_f64((target - available) / self.rate))
+		}
+	}
+
+	fn consume(&mut self, bytes: usize) {
+		self.available -= bytes as f64;
+	}
+}
+
+/// Pair of buckets limiting downloads and uploads.
+struct Buckets {
+	download: Option<Mutex<TokenBucket>>,
+	upload: Option<Mutex<TokenBucket>>,
+}
+
+impl Buckets {
+	fn new(download: Option<NonZeroU64>, upload: Option<NonZeroU64>) -> Self {
+		Buckets {
+			download: download.map(|rate| Mutex::new(TokenBucket::new(rate))),
+			upload: upload.map(|rate| Mutex::new(TokenBucket::new(rate))),
+		}
+	}
+
+	fn get(&self, direction: Direction) -> Option<&Mutex<TokenBucket>> {
+		match direction {
+			Direction::Download => self.download.as_ref(),
+			Direction::Upload => self.upload.as_ref(),
+		}
+	}
+}
+
+/// Bandwidth limits shared by all the connections of the node.
+pub struct BandwidthThrottle {
+	/// Limits applying to all the connections combined.
+	global: Buckets,
+	/// Limits applying to each peer individually.
+	per_peer: (Option<NonZeroU64>, Option<NonZeroU64>),
+	/// Buckets of the peers we have a connection with. Multiple connections to the same peer
+	/// share the same buckets. Entries are cleaned up when new connections are opened.
+	peers: Mutex<HashMap<PeerId, Weak<Buckets>>>,
+	/// Names of the protocols that can use the reserved share of the buckets.
+	priority_protocols: HashSet<Vec<u8>>,
+}
+
+impl BandwidthThrottle {
+	/// Builds a new throttle. Returns `None` if none of the limits is set.
+	///
+	/// The substreams of the `priority_protocols` can use the share of the limits that the other
+	/// substreams can't.
+	pub fn new(
+		limits: &BandwidthLimits,
+		priority_protocols: impl IntoIterator<Item = String>,
+	) -> Option<Arc<Self>> {
+		if *limits == BandwidthLimits::default() {
+			return None
+		}
+
+		Some(Arc::new(BandwidthThrottle {
+			global: Buckets::new(limits.max_download_rate, limits.max_upload_rate),
+			per_peer: (limits.max_download_rate_per_peer, limits.max_upload_rate_per_peer),
+			peers: Mutex::new(HashMap::new()),
+			priority_protocols: priority_protocols.into_iter().map(String::into_bytes).collect(),
+		}))
+	}
+
+	/// Wraps the muxer of a newly-opened connection with the given peer.
+	pub fn throttle<M>(self: &Arc<Self>, peer_id: &PeerId, muxer: M) -> ThrottledMuxer<M> {
+		let peer = {
+			let mut peers = self.peers.lock();
+			peers.retain(|_, buckets| buckets.strong_count() != 0);
+			match peers.get(peer_id).and_then(Weak::upgrade) {
+				Some(buckets) => buckets,
+				None => {
+					let buckets = Arc::new(Buckets::new(self.per_peer.0, self.per_peer.1));
+					peers.insert(peer_id.clone(), Arc::downgrade(&buckets));
+					buckets
+				}
+			}
+		};
+
+		ThrottledMuxer {
+			inner: muxer,
+			throttle: self.clone(),
+			peer,
+		}
+	}
+
+	/// Returns the number of bytes that can be transferred with the given peer, or how long to
+	/// wait before trying again.
+	fn allowance(&self, peer: &Buckets, direction: Direction, wanted: usize, priority: bool)
+		-> Result<usize, Duration>
+	{
+		let mut allowed = wanted;
+		let mut wait = None;
+		for bucket in self.global.get(direction).into_iter().chain(peer.get(direction)) {
+			match bucket.lock().allowance(priority) {
+				Ok(n) => allowed = allowed.min(n),
+				Err(w) => wait = Some(wait.map_or(w, |other: Duration| other.max(w))),
+			}
+		}
+
+		match wait {
+			Some(wait) => Err(wait),
+			None => Ok(allowed),
+		}
+	}
+
+	/// Accounts for bytes that have been transferred with the given peer.
+	fn consume(&self, peer: &Buckets, direction: Direction, bytes: usize) {
+		for bucket in self.global.get(direction).into_iter().chain(peer.get(direction)) {
+			bucket.lock().consume(bytes);
+		}
+	}
+}
+
+/// Stream muxer whose substreams are subject to a [`BandwidthThrottle`].
+///
+/// Only the data of the substreams is accounted for, not the overhead of the encryption and
+/// multiplexing layers.
+pub struct ThrottledMuxer<M> {
+	inner: M,
+	throttle: Arc<BandwidthThrottle>,
+	/// Buckets of the peer this connection is with.
+	peer: Arc<Buckets>,
+}
+
+/// Substream of a [`ThrottledMuxer`].
+pub struct ThrottledSubstream<S> {
+	inner: S,
+	/// Messages exchanged so far, if we don't know yet which protocol the substream is for.
+	negotiation: Option<Negotiation>,
+	/// True if the substream is for one of the priority protocols.
+	priority: bool,
+	/// Timer before trying to read again, if reading is throttled. Every substream has its own
+	/// timer, as a timer only wakes up the last task that polled it.
+	download_delay: Option<Delay>,
+	/// Timer before trying to write again, if writing is throttled.
+	upload_delay: Option<Delay>,
+}
+
+impl<M> ThrottledMuxer<M> {
+	fn wrap<S>(&self, substream: S) -> ThrottledSubstream<S> {
+		let negotiation = if self.throttle.priority_protocols.is_empty() {
+			None
+		} else {
+			Some(Negotiation::default())
+		};
+
+		ThrottledSubstream {
+			inner: substream,
+			negotiation,
+			priority: false,
+			download_delay: None,
+			upload_delay: None,
+		}
+	}
+
+	/// Waits until at least one byte can be transferred on the substream in the given direction,
+	/// and returns the maximum number of bytes that can be transferred.
+	fn poll_allowance<S>(
+		&self,
+		cx: &mut Context,
+		substream: &mut ThrottledSubstream<S>,
+		direction: Direction,
+		wanted: usize,
+	) -> Poll<usize> {
+		let delay = match direction {
+			Direction::Download => &mut substream.download_delay,
+			Direction::Upload => &mut substream.upload_delay,
+		};
+
+		loop {
+			if let Some(timer) = delay.as_mut() {
+				if Pin::new(timer).poll(cx).is_pending() {
+					return Poll::Pending
+				}
+				*delay = None;
+			}
+
+			match self.throttle.allowance(&self.peer, direction, wanted, substream.priority) {
+				Ok(allowed) => return Poll::Ready(allowed),
+				Err(wait) => *delay = Some(Delay::new(wait)),
+			}
+		}
+	}
+
+	/// Accounts for bytes that have been transferred on the substream.
+	fn consume<S>(
+		&self,
+		substream: &mut ThrottledSubstream<S>,
+		direction: Direction,
+		bytes: &[u8],
+	) {
+		self.throttle.consume(&self.peer, direction, bytes.len());
+
+		if let Some(negotiation) = substream.negotiation.as_mut() {
+			if let Some(priority) =
+				negotiation.record(direction, bytes, &self.throttle.priority_protocols)
+			{
+				substream.priority = priority;
+				substream.negotiation = None;
+			}
+		}
+	}
+}
+
+impl<M: StreamMuxer> StreamMuxer for ThrottledMuxer<M> {
+	type Substream = ThrottledSubstream<M::Substream>;
+	type OutboundSubstream = M::OutboundSubstream;
+	type Error = M::Error;
+
+	fn poll_event(&self, cx: &mut Context)
+		-> Poll<Result<StreamMuxerEvent<Self::Substream>, Self::Error>>
+	{
+		let event = match futures::ready!(self.inner.poll_event(cx))? {
+			StreamMuxerEvent::InboundSubstream(substream) =>
+				StreamMuxerEvent::InboundSubstream(self.wrap(substream)),
+			StreamMuxerEvent::AddressChange(address) => StreamMuxerEvent::AddressChange(address),
+		};
+		Poll::Ready(Ok(event))
+	}
+
+	fn open_outbound(&self) -> Self::OutboundSubstream {
+		self.inner.open_outbound()
+	}
+
+	fn poll_outbound(&self, cx: &mut Context, s: &mut Self::OutboundSubstream)
+		-> Poll<Result<Self::Substream, Self::Error>>
+	{
+		self.inner.poll_outbound(cx, s).map_ok(|substream| self.wrap(substream))
+	}
+
+	fn destroy_outbound(&self, s: Self::OutboundSubstream) {
+		self.inner.destroy_outbound(s)
+	}
+
+	fn read_substream(&self, cx: &mut Context, s: &mut Self::Substream, buf: &mut [u8])
+		-> Poll<Result<usize, Self::Error>>
+	{
+		if buf.is_empty() {
+			return self.inner.read_substream(cx, &mut s.inner, buf)
+		}
+
+		let allowed = futures::ready!(self.poll_allowance(cx, s, Direction::Download, buf.len()));
+		let read =
+			futures::ready!(self.inner.read_substream(cx, &mut s.inner, &mut buf[..allowed]))?;
+		self.consume(s, Direction::Download, &buf[..read]);
+		Poll::Ready(Ok(read))
+	}
+
+	fn write_substream(&self, cx: &mut Context, s: &mut Self::Substream, buf: &[u8])
+		-> Poll<Result<usize, Self::Error>>
+	{
+		if buf.is_empty() {
+			return self.inner.write_substream(cx, &mut s.inner, buf)
+		}
+
+		let allowed = futures::ready!(self.poll_allowance(cx, s, Direction::Upload, buf.len()));
+		let written =
+			futures::ready!(self.inner.write_substream(cx, &mut s.inner, &buf[..allowed]))?;
+		self.consume(s, Direction::Upload, &buf[..written]);
+		Poll::Ready(Ok(written))
+	}
+
+	fn flush_substream(&self, cx: &mut Context, s: &mut Self::Substream)
+		-> Poll<Result<(), Self::Error>>
+	{
+		self.inner.flush_substream(cx, &mut s.inner)
+	}
+
+	fn shutdown_substream(&self, cx: &mut Context, s: &mut Self::Substream)
+		-> Poll<Result<(), Self::Error>>
+	{
+		self.inner.shutdown_substream(cx, &mut s.inner)
+	}
+
+	fn destroy_substream(&self, s: Self::Substream) {
+		self.inner.destroy_substream(s.inner)
+	}
+
+	fn is_remote_acknowledged(&self) -> bool {
+		self.inner.is_remote_acknowledged()
+	}
+
+	fn close(&self, cx: &mut Context) -> Poll<Result<(), Self::Error>> {
+		self.inner.close(cx)
+	}
+
+	fn flush_all(&self, cx: &mut Context) -> Poll<Result<(), Self::Error>> {
+		self.inner.flush_all(cx)
+	}
+}
+
+/// Multistream-select messages exchanged on a substream.
+#[derive(Default)]
+struct Negotiation {
+	received: Vec<u8>,
+	sent: Vec<u8>,
+}
+
+impl Negotiation {
+	/// Records bytes transferred in the given direction. Returns whether the substream is for
+	/// one of the `priority_protocols`, once that is known.
+	///
+	/// A protocol has been negotiated once its name has been sent by one side and echoed by the
+	/// other. We give up if that hasn't happened after `MAX_NEGOTIATION_BYTES`.
+	fn record(
+		&mut self,
+		direction: Direction,
+		bytes: &[u8],
+		priority_protocols: &HashSet<Vec<u8>>,
+	) -> Option<bool> {
+		match direction {
+			Direction::Download => self.received.extend_from_slice(bytes),
+			Direction::Upload => self.sent.extend_from_slice(bytes),
+		}
+
+		let sent = protocol_names(&self.sent);
+		if let Some(name) = protocol_names(&self.received).into_iter().find(|n| sent.contains(n)) {
+			return Some(priority_protocols.contains(name))
+		}
+
+		if self.received.len() > MAX_NEGOTIATION_BYTES || self.sent.len() > MAX_NEGOTIATION_BYTES {
+			return Some(false)
+		}
+
+		None
+	}
+}
+
+/// Returns the protocol names proposed or accepted in the multistream-select messages found at
+/// the start of `bytes`. Each message is prefixed with its length and ends with a new line.
+fn protocol_names(mut bytes: &[u8]) -> Vec<&[u8]> {
+	let mut names = Vec::new();
+	while let Ok((len, rest)) = unsigned_varint::decode::usize(bytes) {
+		if len == 0 || rest.len() < len {
+			break
+		}
+
+		let (message, rest) = rest.split_at(len);
+		if message.starts_with(b"/") && message.ends_with(b"\n") && message != MULTISTREAM_HEADER {
+			names.push(&message[..len - 1]);
+		}
+		bytes = rest;
+	}
+	names
+}
+
+/// Number of bytes sent and received on each notifications and request-response protocol.
+///
+/// Only the payloads are accounted for, not the framing of the protocols.
+#[derive(Debug, Default)]
+pub struct ProtocolsTraffic {
+	/// Inbound and outbound bytes, by protocol name.
+	protocols: Mutex<HashMap<Cow<'static, str>, (u64, u64)>>,
+}
+
+impl ProtocolsTraffic {
+	/// Reports bytes received on the given protocol.
+	pub fn report_inbound(&self, protocol: &Cow<'static, str>, bytes: usize) {
+		self.entry(protocol, |(inbound, _)| *inbound += bytes as u64);
+	}
+
+	/// Reports bytes sent on the given protocol.
+	pub fn report_outbound(&self, protocol: &Cow<'static, str>, bytes: usize) {
+		self.entry(protocol, |(_, outbound)| *outbound += bytes as u64);
+	}
+
+	/// Calls `f` with the name of each protocol and its total inbound and outbound bytes.
+	pub fn for_each(&self, mut f: impl FnMut(&str, u64, u64)) {
+		for (protocol, (inbound, outbound)) in self.protocols.lock().iter() {
+			f(protocol, *inbound, *outbound);
+		}
+	}
+
+	fn entry(&self, protocol: &Cow<'static, str>, update: impl FnOnce(&mut (u64, u64))) {
+		let mut protocols = self.protocols.lock();
+		if let Some(totals) = protocols.get_mut(&**protocol) {
+			update(totals);
+		} else {
+			update(protocols.entry(protocol.clone()).or_default());
+		}
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use futures::task::{ArcWake, noop_waker, waker};
+	use std::{io, sync::atomic::{AtomicBool, Ordering}, task::Waker};
+
+	/// Muxer whose substreams accept all the bytes written to them, and return the bytes put in
+	/// them when read from.
+	struct MockMuxer;
+
+	struct MockSubstream(Vec<u8>);
+
+	impl StreamMuxer for MockMuxer {
+		type Substream = MockSubstream;
+		type OutboundSubstream = ();
+		type Error = io::Error;
+
+		fn poll_event(&self, _: &mut Context)
+			-> Poll<Result<StreamMuxerEvent<Self::Substream>, Self::Error>>
+		{
+			Poll::Pending
+		}
+
+		fn open_outbound(&self) -> Self::OutboundSubstream {}
+
+		fn poll_outbound(&self, _: &mut Context, _: &mut Self::OutboundSubstream)
+			-> Poll<Result<Self::Substream, Self::Error>>
+		{
+			Poll::Ready(Ok(MockSubstream(Vec::new())))
+		}
+
+		fn destroy_outbound(&self, _: Self::OutboundSubstream) {}
+
+		fn read_substream(&self, _: &mut Context, s: &mut Self::Substream, buf: &mut [u8])
+			-> Poll<Result<usize, Self::Error>>
+		{
+			let read = buf.len().min(s.0.len());
+			buf[..read].copy_from_slice(&s.0[..read]);
+			s.0.drain(..read);
+			Poll::Ready(Ok(read))
+		}
+
+		fn write_substream(&self, _: &mut Context, _: &mut Self::Substream, buf: &[u8])
+			-> Poll<Result<usize, Self::Error>>
+		{
+			Poll::Ready(Ok(buf.len()))
+		}
+
+		fn flush_substream(&self, _: &mut Context, _: &mut Self::Substream)
+			-> Poll<Result<(), Self::Error>>
+		{
+			Poll::Ready(Ok(()))
+		}
+
+		fn shutdown_substream(&self, _: &mut Context, _: &mut Self::Substream)
+			-> Poll<Result<(), Self::Error>>
+		{
+			Poll::Ready(Ok(()))
+		}
+
+		fn destroy_substream(&self, _: Self::Substream) {}
+
+		fn is_remote_acknowledged(&self) -> bool {
+			true
+		}
+
+		fn close(&self, _: &mut Context) -> Poll<Result<(), Self::Error>> {
+			Poll::Ready(Ok(()))
+		}
+
+		fn flush_all(&self, _: &mut Context) -> Poll<Result<(), Self::Error>> {
+			Poll::Ready(Ok(()))
+		}
+	}
+
+	/// Waker that remembers whether it has been woken up.
+	#[derive(Default)]
+	struct WokenUp(AtomicBool);
+
+	impl ArcWake for WokenUp {
+		fn wake_by_ref(arc_self: &Arc<Self>) {
+			arc_self.0.store(true, Ordering::SeqCst);
+		}
+	}
+
+	/// Encodes multistream-select messages.
+	fn messages(messages: &[&[u8]]) -> Vec<u8> {
+		let mut encoded = Vec::new();
+		for message in messages {
+			let mut buf = unsigned_varint::encode::usize_buffer();
+			encoded.extend_from_slice(unsigned_varint::encode::usize(message.len(), &mut buf));
+			encoded.extend_from_slice(message);
+		}
+		encoded
+	}
+
+	/// Opens an outbound substream and negotiates `protocol` on it.
+	fn open_substream(muxer: &ThrottledMuxer<MockMuxer>, protocol: &[u8])
+		-> ThrottledSubstream<MockSubstream>
+	{
+		let mut cx = Context::from_waker(futures::task::noop_waker_ref());
+		let mut substream = match muxer.poll_outbound(&mut cx, &mut muxer.open_outbound()) {
+			Poll::Ready(Ok(substream)) => substream,
+			_ => panic!("mock substreams are opened immediately"),
+		};
+
+		let negotiation = messages(&[MULTISTREAM_HEADER, &[protocol, &b"\n"[..]].concat()]);
+		match muxer.write_substream(&mut cx, &mut substream, &negotiation) {
+			Poll::Ready(Ok(written)) => assert_eq!(written, negotiation.len()),
+			_ => panic!("negotiation isn't throttled"),
+		}
+
+		substream.inner.0 = negotiation.clone();
+		let mut echo = vec![0; negotiation.len()];
+		match muxer.read_substream(&mut cx, &mut substream, &mut echo) {
+			Poll::Ready(Ok(read)) => assert_eq!(read, negotiation.len()),
+			_ => panic!("negotiation isn't throttled"),
+		}
+
+		substream
+	}
+
+	fn poll_write(
+		muxer: &ThrottledMuxer<MockMuxer>,
+		substream: &mut ThrottledSubstream<MockSubstream>,
+		len: usize,
+		waker: &Waker,
+	) -> Poll<usize> {
+		let mut cx = Context::from_waker(waker);
+		muxer.write_substream(&mut cx, substream, &vec![0; len]).map(Result::unwrap)
+	}
+
+	#[test]
+	fn token_bucket_caps_transfers() {
+		let mut bucket = TokenBucket::new(NonZeroU64::new(2048).unwrap());
+		assert_eq!(bucket.allowance(true), Ok(2048));
+		assert_eq!(bucket.allowance(false), Ok(1536));
+
+		bucket.consume(4096);
+		let wait = bucket.allowance(true).unwrap_err();
+		assert!(wait > Duration::from_millis(1000) && wait <= Duration::from_millis(1500));
+		let wait = bucket.allowance(false).unwrap_err();
+		assert!(wait > Duration::from_millis(1250) && wait <= Duration::from_millis(1750));
+	}
+
+	#[test]
+	fn throttled_substreams_are_all_woken_up() {
+		let throttle = BandwidthThrottle::new(&BandwidthLimits {
+			max_upload_rate: NonZeroU64::new(1024 * 1024),
+			..Default::default()
+		}, Vec::new()).unwrap();
+		let muxer = throttle.throttle(&PeerId::random(), MockMuxer);
+		let mut first = open_substream(&muxer, b"/foo");
+		let mut second = open_substream(&muxer, b"/foo");
+
+		// Use up the bucket, then make both substreams wait for it to refill.
+		assert!(poll_write(&muxer, &mut first, 1024 * 1024, &noop_waker()).is_ready());
+		let first_woken_up = Arc::new(WokenUp::default());
+		let second_woken_up = Arc::new(WokenUp::default());
+		let first_waker = waker(first_woken_up.clone());
+		let second_waker = waker(second_woken_up.clone());
+		assert!(poll_write(&muxer, &mut first, 1024, &first_waker).is_pending());
+		assert!(poll_write(&muxer, &mut second, 1024, &second_waker).is_pending());
+
+		std::thread::sleep(Duration::from_millis(100));
+		assert!(first_woken_up.0.load(Ordering::SeqCst));
+		assert!(second_woken_up.0.load(Ordering::SeqCst));
+		assert!(poll_write(&muxer, &mut first, 1024, &noop_waker()).is_ready());
+		assert!(poll_write(&muxer, &mut second, 1024, &noop_waker()).is_ready());
+	}
+
+	#[test]
+	fn priority_protocols_get_through_a_flood() {
+		let throttle = BandwidthThrottle::new(&BandwidthLimits {
+			max_upload_rate: NonZeroU64::new(4096),
+			..Default::default()
+		}, vec!["/block-announces".to_owned()]).unwrap();
+		let muxer = throttle.throttle(&PeerId::random(), MockMuxer);
+		let mut flood = open_substream(&muxer, b"/flood");
+		let mut announces = open_substream(&muxer, b"/block-announces");
+		assert!(!flood.priority);
+		assert!(announces.priority);
+
+		// The flood stops at the reserved share of the bucket, which block announces can use.
+		while poll_write(&muxer, &mut flood, 4096, &noop_waker()).is_ready() {}
+		assert_eq!(poll_write(&muxer, &mut announces, 1000, &noop_waker()), Poll::Ready(1000));
+		assert!(poll_write(&muxer, &mut flood, 4096, &noop_waker()).is_pending());
+	}
+
+	#[test]
+	fn negotiated_protocol_is_found() {
+		let priority = vec![b"/b".to_vec()].into_iter().collect();
+		let mut negotiation = Negotiation::default();
+		let header_and_a = messages(&[MULTISTREAM_HEADER, b"/a\n"]);
+		assert_eq!(negotiation.record(Direction::Upload, &header_and_a, &priority), None);
+		let header_and_na = messages(&[MULTISTREAM_HEADER, b"na\n"]);
+		assert_eq!(negotiation.record(Direction::Download, &header_and_na, &priority), None);
+		let b = messages(&[b"/b\n"]);
+		assert_eq!(negotiation.record(Direction::Upload, &b, &priority), None);
+		assert_eq!(negotiation.record(Direction::Download, &b, &priority), Some(true));
+
+		// We give up on substreams that don't look like multistream-select.
+		let mut negotiation = Negotiation::default();
+		let garbage = [0; MAX_NEGOTIATION_BYTES + 1];
+		assert_eq!(negotiation.record(Direction::Upload, &garbage, &priority), Some(false));
+	}
+
+	#[test]
+	fn connections_to_same_peer_share_buckets() {
+		let throttle = BandwidthThrottle::new(&BandwidthLimits {
+			max_upload_rate_per_peer: NonZeroU64::new(1024),
+			..Default::default()
+		}, Vec::new()).unwrap();
+
+		let peer_id = PeerId::random();
+		let first = throttle.throttle(&peer_id, ());
+		let second = throttle.throttle(&peer_id, ());
+		let other = throttle.throttle(&PeerId::random(), ());
+		assert!(Arc::ptr_eq(&first.peer, &second.peer));
+		assert!(!Arc::ptr_eq(&first.peer, &other.peer));
+
+		throttle.consume(&first.peer, Direction::Upload, 2048);
+		assert!(throttle.allowance(&second.peer, Direction::Upload, 10, true).is_err());
+		assert_eq!(throttle.allowance(&other.peer, Direction::Upload, 10, false), Ok(10));
+		assert_eq!(throttle.allowance(&first.peer, Direction::Download, 10, false), Ok(10));
+	}
+
+	#[test]
+	fn protocols_traffic_totals() {
+		let traffic = ProtocolsTraffic::default();
+		traffic.report_inbound(&Cow::Borrowed("/foo"), 10);
+		traffic.report_outbound(&Cow::Borrowed("/foo"), 3);
+		traffic.report_inbound(&Cow::Owned("/foo".to_owned()), 5);
+		traffic.report_outbound(&Cow::Borrowed("/bar"), 7);
+
+		let mut totals = Vec::new();
+		traffic.for_each(|protocol, inbound, outbound| {
+			totals.push((protocol.to_owned(), inbound, outbound))
+		});
+		totals.sort();
+		assert_eq!(totals, vec![("/bar".to_owned(), 0, 7), ("/foo".to_owned(), 15, 3)]);
+	}
+}
@@ -16,17 +16,23 @@
 // You should have received a copy of the GNU General Public License
 // along with this program. If not, see <https://www.gnu.org/licenses/>.
 
+use crate::{config::BandwidthLimits, traffic::BandwidthThrottle};
+
+use futures::future;
+use ip_network::IpNetwork;
 use libp2p::{
-	PeerId, Transport,
+	Multiaddr, PeerId, Transport,
 	core::{
 		self, either::EitherTransport, muxing::StreamMuxerBox,
 		transport::{Boxed, OptionalTransport}, upgrade
 	},
-	mplex, identity, bandwidth, wasm_ext, noise
+	mplex, identity, bandwidth, wasm_ext, noise, multiaddr::Protocol
 };
 #[cfg(not(target_os = "unknown"))]
 use libp2p::{tcp, dns, websocket};
-use std::{sync::Arc, time::Duration};
+use parking_lot::RwLock;
+use std::{io, net::IpAddr, sync::Arc, time::Duration};
+use wasm_timer::Instant;
 
 pub use self::bandwidth::BandwidthSinks;
 
@@ -35,12 +41,19 @@ pub use self::bandwidth::BandwidthSinks;
 /// If `memory_only` is true, then only communication within the same process are allowed. Only
 /// addresses with the format `/memory/...` are allowed.
 ///
+/// The substreams of the connections are subject to the given `bandwidth_limits`, part of which
+/// is reserved for the substreams of the `priority_protocols`. Connections from and to the
+/// addresses that belong to a range of `banned_ip_ranges` are refused.
+///
 /// Returns a `BandwidthSinks` object that allows querying the average bandwidth produced by all
 /// the connections spawned with this transport.
 pub fn build_transport(
 	keypair: identity::Keypair,
 	memory_only: bool,
 	wasm_external_transport: Option<wasm_ext::ExtTransport>,
+	bandwidth_limits: &BandwidthLimits,
+	priority_protocols: Vec<String>,
+	banned_ip_ranges: BannedIpRanges,
 ) -> (Boxed<(PeerId, StreamMuxerBox)>, Arc<BandwidthSinks>) {
 	// Build the base layer of the transport.
 	let transport = if let Some(t) = wasm_external_transport {
@@ -68,6 +81,15 @@ pub fn build_transport(
 		OptionalTransport::none()
 	});
 
+	// Refuse the connections of banned IP addresses before spending any resource on them.
+	let transport = transport.and_then(move |socket, endpoint| {
+		future::ready(if banned_ip_ranges.is_banned(endpoint.get_remote_address()) {
+			Err(io::Error::new(io::ErrorKind::PermissionDenied, "IP address is banned"))
+		} else {
+			Ok(socket)
+		})
+	});
+
 	let (transport, bandwidth) = bandwidth::BandwidthLogging::new(transport);
 
 	let authentication_config = {
@@ -104,8 +126,131 @@ pub fn build_transport(
 	let transport = transport.upgrade(upgrade::Version::V1Lazy)
 		.authenticate(authentication_config)
 		.multiplex(multiplexing_config)
-		.timeout(Duration::from_secs(20))
-		.boxed();
+		.timeout(Duration::from_secs(20));
+
+	let transport = if let Some(throttle) = BandwidthThrottle::new(bandwidth_limits, priority_protocols) {
+		transport
+			.map(move |(peer_id, muxer), _| {
+				let muxer = StreamMuxerBox::new(throttle.throttle(&peer_id, muxer));
+				(peer_id, muxer)
+			})
+			.boxed()
+	} else {
+		transport.boxed()
+	};
 
 	(transport, bandwidth)
 }
+
+/// List of banned IP ranges, shared between the transport and the
+/// [`NetworkService`](crate::NetworkService) so that it can be changed at runtime.
+#[derive(Debug, Clone, Default)]
+pub struct BannedIpRanges {
+	/// Banned ranges, and when their ban expires. `None` means that the ban is permanent.
+	ranges: Arc<RwLock<Vec<(IpNetwork, Option<Instant>)>>>,
+}
+
+impl BannedIpRanges {
+	/// Creates a list in which the given ranges are permanently banned.
+	pub fn new(permanent: impl IntoIterator<Item = IpNetwork>) -> Self {
+		BannedIpRanges {
+			ranges: Arc::new(RwLock::new(permanent.into_iter().map(|range| (range, None)).collect())),
+		}
+	}
+
+	/// Bans a range for the given duration, or permanently if `duration` is `None`. Banning a
+	/// range that is already banned replaces the previous expiry time.
+	pub fn ban(&self, range: IpNetwork, duration: Option<Duration>) {
+		let until = duration.map(|duration| Instant::now() + duration);
+		let mut ranges = self.ranges.write();
+		ranges.retain(|(banned, _)| *banned != range);
+		ranges.push((range, until));
+	}
+
+	/// Lifts the ban of a range. Returns false if the range wasn't banned.
+	pub fn unban(&self, range: &IpNetwork) -> bool {
+		let mut ranges = self.ranges.write();
+		let len = ranges.len();
+		ranges.retain(|(banned, _)| banned != range);
+		ranges.len() != len
+	}
+
+	/// Returns the banned ranges, and for how long they remain banned. `None` means that the ban
+	/// is permanent. Expired bans are removed.
+	pub fn banned(&self) -> Vec<(IpNetwork, Option<Duration>)> {
+		let now = Instant::now();
+		let mut ranges = self.ranges.write();
+		ranges.retain(|(_, until)| until.map_or(true, |until| until > now));
+		ranges.iter()
+			.map(|(range, until)| (*range, until.map(|until| until - now)))
+			.collect()
+	}
+
+	/// Returns true if the IP address of the given multiaddress belongs to a range whose ban
+	/// hasn't expired.
+	///
+	/// Addresses that don't start with an IP address, such as DNS or memory addresses, are never
+	/// considered banned.
+	pub fn is_banned(&self, address: &Multiaddr) -> bool {
+		let ip: IpAddr = match address.iter().next() {
+			Some(Protocol::Ip4(ip)) => ip.into(),
+			Some(Protocol::Ip6(ip)) => ip.into(),
+			_ => return false,
+		};
+		let now = Instant::now();
+		self.ranges.read().iter().any(|(range, until)| {
+			until.map_or(true, |until| until > now) && range.contains(ip)
+		})
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	#[test]
+	fn banned_addresses() {
+		let ranges = BannedIpRanges::new(vec![
+			"192.0.2.0/24".parse().unwrap(),
+			"2001:db8::/32".parse().unwrap(),
+		]);
+		let banned = |address: &str| ranges.is_banned(&address.parse().unwrap());
+
+		assert!(banned("/ip4/192.0.2.1/tcp/30333"));
+		assert!(banned("/ip6/2001:db8::1/tcp/30333/ws"));
+		assert!(!banned("/ip4/192.0.3.1/tcp/30333"));
+		assert!(!banned("/ip6/2001:db9::1/tcp/30333"));
+		assert!(!banned("/dns4/example.com/tcp/30333"));
+		assert!(!banned("/memory/1234"));
+	}
+
+	#[test]
+	fn banned_ranges_change_at_runtime() {
+		let ranges = BannedIpRanges::default();
+		let range: IpNetwork = "192.0.2.0/24".parse().unwrap();
+		let address = "/ip4/192.0.2.1/tcp/30333".parse().unwrap();
+		assert!(!ranges.is_banned(&address));
+
+		ranges.ban(range, Some(Duration::from_secs(60)));
+		assert!(ranges.is_banned(&address));
+		assert_matches::assert_matches!(&ranges.banned()[..], [(r, Some(_))] if *r == range);
+
+		// Banning again replaces the expiry time.
+		ranges.ban(range, None);
+		assert_eq!(ranges.banned(), vec![(range, None)]);
+
+		assert!(ranges.unban(&range));
+		assert!(!ranges.unban(&range));
+		assert!(!ranges.is_banned(&address));
+	}
+
+	#[test]
+	fn expired_range_bans_are_lifted() {
+		let ranges = BannedIpRanges::default();
+		let address = "/ip4/192.0.2.1/tcp/30333".parse().unwrap();
+		ranges.ban("192.0.2.0/24".parse().unwrap(), Some(Duration::from_secs(0)));
+
+		assert!(!ranges.is_banned(&address));
+		assert!(ranges.banned().is_empty());
+	}
+}
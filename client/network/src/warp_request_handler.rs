@@ -0,0 +1,166 @@
+// Copyright 2020 Parity Technologies (UK) Ltd.
+// This file is part of Substrate.
+//
+// Substrate is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// Substrate is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with Substrate.  If not, see <http://www.gnu.org/licenses/>.
+
+//! Helper for handling (i.e. answering) warp sync requests from a remote peer via the
+//! [`crate::request_responses::RequestResponsesBehaviour`].
+//!
+//! Warp sync lets a node skip downloading the headers of the whole chain by downloading a proof
+//! of the finality gadget's authority set changes instead. The proof itself is opaque to the
+//! network: it is generated and verified by a [`WarpSyncProvider`] implemented by the finality
+//! gadget.
+
+use codec::{Encode, Decode};
+use crate::{
+	config::ProtocolId,
+	request_responses::{IncomingRequest, ProtocolConfig},
+};
+use futures::{channel::mpsc, prelude::*};
+use sp_runtime::traits::Block as BlockT;
+use std::{
+	sync::Arc,
+	task::{Context, Poll},
+	time::Duration,
+};
+
+pub use sp_finality_grandpa::{AuthorityList, SetId};
+
+const LOG_TARGET: &str = "sync";
+/// Maximum number of requests buffered before new ones are rejected.
+const MAX_NUMBER_OF_CONCURRENT_REQUESTS: usize = 20;
+/// Maximum size of a response, generated proofs must fit in it.
+const MAX_RESPONSE_SIZE: u64 = 16 * 1024 * 1024;
+
+/// Scale-encoded warp sync proof response.
+pub struct EncodedProof(pub Vec<u8>);
+
+/// Warp sync request.
+#[derive(Encode, Decode, Debug, Clone)]
+pub struct WarpProofRequest<B: BlockT> {
+	/// Start collecting proofs from this block.
+	pub begin: B::Hash,
+}
+
+/// Proof verification result.
+pub enum VerificationResult<B: BlockT> {
+	/// Proof is valid, but the target was not reached.
+	Partial(SetId, AuthorityList, B::Hash),
+	/// Target finality is proved.
+	Complete(SetId, AuthorityList, B::Header),
+}
+
+/// Warp sync backend. Handles retrieving and verifying warp sync proofs.
+pub trait WarpSyncProvider<B: BlockT>: Send + Sync {
+	/// Generate a proof starting at the given block hash. The proof is accumulated until the
+	/// maximum proof size is reached.
+	fn generate(
+		&self,
+		start: B::Hash,
+	) -> Result<EncodedProof, Box<dyn std::error::Error + Send + Sync>>;
+	/// Verify a warp sync proof, starting at the given authority set.
+	fn verify(
+		&self,
+		proof: &EncodedProof,
+		set_id: SetId,
+		authorities: AuthorityList,
+	) -> Result<VerificationResult<B>, Box<dyn std::error::Error + Send + Sync>>;
+	/// Get the current list of authorities. This is supposed to be the genesis authorities when
+	/// starting warp sync.
+	fn current_authorities(&self) -> AuthorityList;
+}
+
+/// Generates a [`ProtocolConfig`] for the warp sync protocol, refusing incoming requests.
+pub fn generate_protocol_config(protocol_id: &ProtocolId) -> ProtocolConfig {
+	ProtocolConfig {
+		name: generate_protocol_name(protocol_id).into(),
+		max_request_size: 128,
+		max_response_size: MAX_RESPONSE_SIZE,
+		request_timeout: Duration::from_secs(10),
+		inbound_queue: None,
+	}
+}
+
+/// Generate the warp sync protocol name from chain specific protocol identifier.
+pub fn generate_protocol_name(protocol_id: &ProtocolId) -> String {
+	format!("/{}/sync/warp", protocol_id.as_ref())
+}
+
+/// Handler for incoming warp sync requests from a remote peer.
+pub struct WarpSyncRequestHandler<B: BlockT> {
+	backend: Arc<dyn WarpSyncProvider<B>>,
+	request_receiver: mpsc::Receiver<IncomingRequest>,
+}
+
+impl<B: BlockT> WarpSyncRequestHandler<B> {
+	/// Create a new [`WarpSyncRequestHandler`], along with the protocol configuration it answers
+	/// requests for.
+	pub fn new(
+		protocol_id: &ProtocolId,
+		backend: Arc<dyn WarpSyncProvider<B>>,
+	) -> (Self, ProtocolConfig) {
+		let (tx, request_receiver) = mpsc::channel(MAX_NUMBER_OF_CONCURRENT_REQUESTS);
+
+		let mut protocol_config = generate_protocol_config(protocol_id);
+		protocol_config.inbound_queue = Some(tx);
+
+		(Self { backend, request_receiver }, protocol_config)
+	}
+
+	/// Answer all the requests that are currently queued.
+	pub fn poll(&mut self, cx: &mut Context) {
+		while let Poll::Ready(Some(request)) = self.request_receiver.poll_next_unpin(cx) {
+			let IncomingRequest { peer, payload, pending_response } = request;
+
+			match self.handle_request(&payload) {
+				Ok(response) => {
+					// The remote might have stopped waiting for the response in the meantime.
+					let _ = pending_response.send(response);
+				}
+				Err(e) => log::debug!(
+					target: LOG_TARGET,
+					"Failed to handle warp sync request from {}: {}",
+					peer, e,
+				),
+			}
+		}
+	}
+
+	fn handle_request(&self, payload: &[u8]) -> Result<Vec<u8>, HandleRequestError> {
+		let request = WarpProofRequest::<B>::decode(&mut &payload[..])?;
+
+		log::trace!(target: LOG_TARGET, "Handling warp sync request starting at {:?}", request.begin);
+
+		let EncodedProof(proof) = self.backend.generate(request.begin)
+			.map_err(HandleRequestError::InvalidRequest)?;
+
+		if proof.len() as u64 > MAX_RESPONSE_SIZE {
+			return Err(HandleRequestError::TooLarge(proof.len()));
+		}
+
+		Ok(proof)
+	}
+}
+
+#[derive(derive_more::Display, derive_more::From)]
+enum HandleRequestError {
+	#[display(fmt = "Failed to decode request: {}.", _0)]
+	DecodeScale(codec::Error),
+	#[display(fmt = "Failed to generate proof: {}.", _0)]
+	#[from(ignore)]
+	InvalidRequest(Box<dyn std::error::Error + Send + Sync>),
+	#[display(fmt = "Generated proof of {} bytes is too large.", _0)]
+	#[from(ignore)]
+	TooLarge(usize),
+}
@@ -50,14 +50,15 @@ use sp_consensus::Error as ConsensusError;
 use sp_consensus::{BlockOrigin, ForkChoiceStrategy, BlockImportParams, BlockCheckParams, JustificationImport};
 use futures::prelude::*;
 use sc_network::{NetworkWorker, NetworkService, config::ProtocolId};
-use sc_network::config::{NetworkConfiguration, TransportConfig};
+use sc_network::config::{NetworkConfiguration, SyncMode, TransportConfig};
+use sc_network::warp_request_handler::WarpSyncProvider;
 use libp2p::PeerId;
 use parking_lot::Mutex;
 use sp_core::H256;
 use sc_network::config::ProtocolConfig;
 use sp_runtime::generic::{BlockId, OpaqueDigestItemId};
 use sp_runtime::traits::{Block as BlockT, Header as HeaderT, NumberFor};
-use sp_runtime::Justification;
+use sp_runtime::{Justification, Justifications};
 use substrate_test_runtime_client::{self, AccountKeyring};
 use sc_service::client::Client;
 pub use sc_network::config::EmptyTransactionPool;
@@ -103,7 +104,7 @@ impl<B: BlockT> Verifier<B> for PassThroughVerifier {
 		&mut self,
 		origin: BlockOrigin,
 		header: B::Header,
-		justification: Option<Justification>,
+		justifications: Option<Justifications>,
 		body: Option<Vec<B::Extrinsic>>
 	) -> Result<(BlockImportParams<B, ()>, Option<Vec<(CacheKeyId, Vec<u8>)>>), String> {
 		let maybe_keys = header.digest()
@@ -114,7 +115,7 @@ impl<B: BlockT> Verifier<B> for PassThroughVerifier {
 		let mut import = BlockImportParams::new(origin, header);
 		import.body = body;
 		import.finalized = self.finalized;
-		import.justification = justification;
+		import.justifications = justifications;
 		import.fork_choice = Some(self.fork_choice.clone());
 
 		Ok((import, maybe_keys))
@@ -178,10 +179,10 @@ impl PeersClient {
 		}
 	}
 
-	pub fn justification(&self, block: &BlockId<Block>) -> ClientResult<Option<Justification>> {
+	pub fn justifications(&self, block: &BlockId<Block>) -> ClientResult<Option<Justifications>> {
 		match *self {
-			PeersClient::Full(ref client, ref _backend) => client.justification(block),
-			PeersClient::Light(ref client, ref _backend) => client.justification(block),
+			PeersClient::Full(ref client, ref _backend) => client.justifications(block),
+			PeersClient::Light(ref client, ref _backend) => client.justifications(block),
 		}
 	}
 
@@ -554,11 +555,11 @@ impl<B: BlockT> Verifier<B> for VerifierAdapter<B> {
 		&mut self,
 		origin: BlockOrigin,
 		header: B::Header,
-		justification: Option<Justification>,
+		justifications: Option<Justifications>,
 		body: Option<Vec<B::Extrinsic>>
 	) -> Result<(BlockImportParams<B, ()>, Option<Vec<(CacheKeyId, Vec<u8>)>>), String> {
 		let hash = header.hash();
-		self.verifier.lock().verify(origin, header, justification, body).map_err(|e| {
+		self.verifier.lock().verify(origin, header, justifications, body).map_err(|e| {
 			self.failed_verifications.lock().insert(hash, e.clone());
 			e
 		})
@@ -583,6 +584,12 @@ pub struct FullPeerConfig {
 	pub block_announce_validator: Option<Box<dyn BlockAnnounceValidator<Block> + Send + Sync>>,
 	/// List of notification protocols that the network must support.
 	pub notifications_protocols: Vec<Cow<'static, str>>,
+	/// Initial syncing mode.
+	pub sync_mode: SyncMode,
+	/// Warp sync provider, used to serve warp proofs and to warp sync.
+	pub warp_sync: Option<Arc<dyn WarpSyncProvider<Block>>>,
+	/// Maximum number of history blocks downloaded per second, if not the default.
+	pub block_history_rate: Option<u32>,
 }
 
 pub trait TestNetFactory: Sized {
@@ -680,7 +687,14 @@ pub trait TestNetFactory: Sized {
 		network_config.transport = TransportConfig::MemoryOnly;
 		network_config.listen_addresses = vec![listen_addr.clone()];
 		network_config.allow_non_globals_in_dht = true;
-		network_config.notifications_protocols = config.notifications_protocols;
+		network_config.notifications_protocols = config.notifications_protocols
+			.into_iter()
+			.map(From::from)
+			.collect();
+		network_config.sync_mode = config.sync_mode;
+		if let Some(rate) = config.block_history_rate {
+			network_config.block_history_rate = rate;
+		}
 
 		let network = NetworkWorker::new(sc_network::config::Params {
 			role: Role::Full,
@@ -694,6 +708,7 @@ pub trait TestNetFactory: Sized {
 			block_announce_validator: config.block_announce_validator
 				.unwrap_or_else(|| Box::new(DefaultBlockAnnounceValidator)),
 			metrics_registry: None,
+			warp_sync: config.warp_sync,
 		}).unwrap();
 
 		self.mut_peers(|peers| {
@@ -766,6 +781,7 @@ pub trait TestNetFactory: Sized {
 			import_queue,
 			block_announce_validator: Box::new(DefaultBlockAnnounceValidator),
 			metrics_registry: None,
+			warp_sync: None,
 		}).unwrap();
 
 		self.mut_peers(|peers| {
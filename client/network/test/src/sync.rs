@@ -16,12 +16,14 @@
 // You should have received a copy of the GNU General Public License
 // along with this program. If not, see <https://www.gnu.org/licenses/>.
 
-use sp_consensus::BlockOrigin;
+use sp_consensus::{BlockOrigin, BlockStatus};
 use std::time::Duration;
 use futures::{Future, executor::block_on};
 use super::*;
 use sp_consensus::block_validation::Validation;
 use substrate_test_runtime::Header;
+use sc_network::warp_request_handler::{AuthorityList, EncodedProof, SetId, VerificationResult};
+use sp_runtime::codec::{Decode, Encode};
 
 fn test_ancestor_search_when_common_is(n: usize) {
 	sp_tracing::try_init_simple();
@@ -248,13 +250,14 @@ fn sync_justifications() {
 	net.block_until_sync();
 
 	// there's currently no justification for block #10
-	assert_eq!(net.peer(0).client().justification(&BlockId::Number(10)).unwrap(), None);
-	assert_eq!(net.peer(1).client().justification(&BlockId::Number(10)).unwrap(), None);
+	assert_eq!(net.peer(0).client().justifications(&BlockId::Number(10)).unwrap(), None);
+	assert_eq!(net.peer(1).client().justifications(&BlockId::Number(10)).unwrap(), None);
 
 	// we finalize block #10, #15 and #20 for peer 0 with a justification
-	net.peer(0).client().finalize_block(BlockId::Number(10), Some(Vec::new()), true).unwrap();
-	net.peer(0).client().finalize_block(BlockId::Number(15), Some(Vec::new()), true).unwrap();
-	net.peer(0).client().finalize_block(BlockId::Number(20), Some(Vec::new()), true).unwrap();
+	let just = (*b"FRNK", Vec::new());
+	net.peer(0).client().finalize_block(BlockId::Number(10), Some(just.clone()), true).unwrap();
+	net.peer(0).client().finalize_block(BlockId::Number(15), Some(just.clone()), true).unwrap();
+	net.peer(0).client().finalize_block(BlockId::Number(20), Some(just.clone()), true).unwrap();
 
 	let h1 = net.peer(1).client().header(&BlockId::Number(10)).unwrap().unwrap();
 	let h2 = net.peer(1).client().header(&BlockId::Number(15)).unwrap().unwrap();
@@ -269,10 +272,14 @@ fn sync_justifications() {
 		net.poll(cx);
 
 		for height in (10..21).step_by(5) {
-			if net.peer(0).client().justification(&BlockId::Number(height)).unwrap() != Some(Vec::new()) {
+			if net.peer(0).client().justifications(&BlockId::Number(height)).unwrap()
+				!= Some(Justifications::from(just.clone()))
+			{
 				return Poll::Pending;
 			}
-			if net.peer(1).client().justification(&BlockId::Number(height)).unwrap() != Some(Vec::new()) {
+			if net.peer(1).client().justifications(&BlockId::Number(height)).unwrap()
+				!= Some(Justifications::from(just.clone()))
+			{
 				return Poll::Pending;
 			}
 		}
@@ -295,7 +302,8 @@ fn sync_justifications_across_forks() {
 	// for both and finalize the small fork instead.
 	net.block_until_sync();
 
-	net.peer(0).client().finalize_block(BlockId::Hash(f1_best), Some(Vec::new()), true).unwrap();
+	let just = (*b"FRNK", Vec::new());
+	net.peer(0).client().finalize_block(BlockId::Hash(f1_best), Some(just.clone()), true).unwrap();
 
 	net.peer(1).request_justification(&f1_best, 10);
 	net.peer(1).request_justification(&f2_best, 11);
@@ -303,8 +311,10 @@ fn sync_justifications_across_forks() {
 	block_on(futures::future::poll_fn::<(), _>(|cx| {
 		net.poll(cx);
 
-		if net.peer(0).client().justification(&BlockId::Number(10)).unwrap() == Some(Vec::new()) &&
-			net.peer(1).client().justification(&BlockId::Number(10)).unwrap() == Some(Vec::new())
+		if net.peer(0).client().justifications(&BlockId::Number(10)).unwrap()
+			== Some(Justifications::from(just.clone())) &&
+			net.peer(1).client().justifications(&BlockId::Number(10)).unwrap()
+			== Some(Justifications::from(just.clone()))
 		{
 			Poll::Ready(())
 		} else {
@@ -696,8 +706,9 @@ fn can_sync_to_peers_with_wrong_common_block() {
 	net.block_until_connected();
 
 	// both peers re-org to the same fork without notifying each other
-	net.peer(0).client().finalize_block(BlockId::Hash(fork_hash), Some(Vec::new()), true).unwrap();
-	net.peer(1).client().finalize_block(BlockId::Hash(fork_hash), Some(Vec::new()), true).unwrap();
+	let just = (*b"FRNK", Vec::new());
+	net.peer(0).client().finalize_block(BlockId::Hash(fork_hash), Some(just.clone()), true).unwrap();
+	net.peer(1).client().finalize_block(BlockId::Hash(fork_hash), Some(just), true).unwrap();
 	let final_hash = net.peer(0).push_blocks(1, false);
 
 	net.block_until_sync();
@@ -814,3 +825,148 @@ fn sync_to_tip_requires_that_sync_protocol_is_informed_about_best_block() {
 	// However peer 1 should still not have the block.
 	assert!(!net.peer(1).has_block(&block_hash));
 }
+
+#[test]
+fn fast_sync_downloads_finalized_state() {
+	sp_tracing::try_init_simple();
+	let mut net = TestNet::new(1);
+	net.peer(0).push_blocks(64, true);
+
+	net.add_full_peer_with_config(FullPeerConfig {
+		sync_mode: SyncMode::Fast,
+		..Default::default()
+	});
+
+	// Headers are downloaded without executing the blocks.
+	while net.peer(1).client().info().best_number < 64 {
+		net.block_until_idle();
+	}
+	let client = net.peer(1).client().as_full().unwrap();
+	assert_eq!(client.block_status(&BlockId::Number(64)).unwrap(), BlockStatus::InChainPruned);
+	assert!(client.body(&BlockId::Number(64)).unwrap().is_none());
+
+	// Finalizing a recent block triggers the state download, after which the blocks above it
+	// are downloaded and executed again.
+	net.peer(1).client().finalize_block(BlockId::Number(60), None, true).unwrap();
+	while client.block_status(&BlockId::Number(64)).unwrap() != BlockStatus::InChainWithState {
+		net.block_until_idle();
+	}
+
+	assert_eq!(client.block_status(&BlockId::Number(60)).unwrap(), BlockStatus::InChainWithState);
+	assert_eq!(client.block_status(&BlockId::Number(59)).unwrap(), BlockStatus::InChainPruned);
+	assert!(client.body(&BlockId::Number(64)).unwrap().is_some());
+	let peer1 = &net.peers()[1];
+	assert!(net.peers()[0].blockchain_canon_equals(peer1));
+
+	// New blocks are imported normally.
+	let hash = net.peer(0).push_blocks(1, true);
+	while !net.peer(1).has_block(&hash) {
+		net.block_until_idle();
+	}
+	assert_eq!(client.block_status(&BlockId::Hash(hash)).unwrap(), BlockStatus::InChainWithState);
+}
+
+/// Warp sync provider proving the finality of a fixed header, without any authority set change.
+#[derive(Default)]
+struct TestWarpSyncProvider(parking_lot::Mutex<Option<Header>>);
+
+impl WarpSyncProvider<Block> for TestWarpSyncProvider {
+	fn generate(
+		&self,
+		_start: <Block as BlockT>::Hash,
+	) -> Result<EncodedProof, Box<dyn std::error::Error + Send + Sync>> {
+		let header = self.0.lock().clone().ok_or("No warp sync target")?;
+		Ok(EncodedProof(header.encode()))
+	}
+
+	fn verify(
+		&self,
+		proof: &EncodedProof,
+		set_id: SetId,
+		authorities: AuthorityList,
+	) -> Result<VerificationResult<Block>, Box<dyn std::error::Error + Send + Sync>> {
+		let header = Header::decode(&mut proof.0.as_slice())?;
+		Ok(VerificationResult::Complete(set_id, authorities, header))
+	}
+
+	fn current_authorities(&self) -> AuthorityList {
+		Vec::new()
+	}
+}
+
+#[test]
+fn warp_sync_jumps_to_proven_block() {
+	sp_tracing::try_init_simple();
+	let provider = Arc::new(TestWarpSyncProvider::default());
+	let mut net = TestNet::new(0);
+	for _ in 0..3 {
+		net.add_full_peer_with_config(FullPeerConfig {
+			warp_sync: Some(provider.clone()),
+			..Default::default()
+		});
+	}
+	net.peer(0).push_blocks(64, false);
+	net.block_until_sync();
+
+	let target = net.peer(0).client().header(&BlockId::Number(60)).unwrap().unwrap();
+	*provider.0.lock() = Some(target.clone());
+
+	net.add_full_peer_with_config(FullPeerConfig {
+		sync_mode: SyncMode::Warp,
+		warp_sync: Some(provider.clone()),
+		block_history_rate: Some(0),
+		..Default::default()
+	});
+
+	// The state of the proven block is downloaded, then the blocks above it are imported.
+	let client = net.peer(3).client().as_full().unwrap();
+	while client.block_status(&BlockId::Number(64)).unwrap() != BlockStatus::InChainWithState {
+		net.block_until_idle();
+	}
+
+	assert_eq!(client.block_status(&BlockId::Hash(target.hash())).unwrap(), BlockStatus::InChainWithState);
+	assert!(client.header(&BlockId::Hash(*target.parent_hash())).unwrap().is_none());
+	assert!(client.body(&BlockId::Number(64)).unwrap().is_some());
+}
+
+#[test]
+fn warp_sync_downloads_block_history() {
+	sp_tracing::try_init_simple();
+	let provider = Arc::new(TestWarpSyncProvider::default());
+	let mut net = TestNet::new(0);
+	for _ in 0..3 {
+		net.add_full_peer_with_config(FullPeerConfig {
+			warp_sync: Some(provider.clone()),
+			..Default::default()
+		});
+	}
+	net.peer(0).push_blocks(64, false);
+	net.block_until_sync();
+
+	let target = net.peer(0).client().header(&BlockId::Number(60)).unwrap().unwrap();
+	*provider.0.lock() = Some(target.clone());
+
+	net.add_full_peer_with_config(FullPeerConfig {
+		sync_mode: SyncMode::Warp,
+		warp_sync: Some(provider.clone()),
+		block_history_rate: Some(32),
+		..Default::default()
+	});
+
+	let client = net.peer(3).client().as_full().unwrap();
+	while client.block_status(&BlockId::Number(60)).unwrap() != BlockStatus::InChainWithState {
+		net.block_until_idle();
+	}
+
+	// The blocks below the proven one are downloaded in the background, down to genesis.
+	while client.chain_info().block_gap.is_some() {
+		net.block_until_idle();
+	}
+
+	let genesis = net.peer(0).client().header(&BlockId::Number(0)).unwrap().unwrap();
+	let first = client.header(&BlockId::Number(1)).unwrap().unwrap();
+	assert_eq!(*first.parent_hash(), genesis.hash());
+	assert_eq!(client.header(&BlockId::Hash(*target.parent_hash())).unwrap().unwrap().number, 59);
+	assert!(client.body(&BlockId::Number(30)).unwrap().is_some());
+	assert_eq!(client.block_status(&BlockId::Number(30)).unwrap(), BlockStatus::InChainPruned);
+}
@@ -18,6 +18,22 @@
 
 //! Peer Set Manager (PSM). Contains the strategy for choosing which nodes the network should be
 //! connected to.
+//!
+//! The PSM handles *sets* of nodes. A set of nodes is defined as the nodes that are believed to
+//! support a certain capability, such as handling blocks and transactions of a specific chain,
+//! or collating a certain parachain.
+//!
+//! For each node in each set, the peerset holds a flag specifying whether the node is
+//! connected to us or not.
+//!
+//! This connected/disconnected status is specific to the node and set combination, and it is for
+//! example possible for a node to be connected through a specific set but not another.
+//!
+//! In addition, for each, set, the peerset also holds a list of reserved nodes towards which it
+//! will at all time try to maintain a connection with, and a flag indicating whether only these
+//! reserved nodes are accepted.
+//!
+//! The reputation of each node is shared between all the sets.
 
 mod peersstate;
 
@@ -26,7 +42,7 @@ use futures::prelude::*;
 use log::{debug, error, trace};
 use serde_json::json;
 use std::{pin::Pin, task::{Context, Poll}, time::Duration};
-use wasm_timer::Instant;
+use wasm_timer::{Delay, Instant};
 use sp_utils::mpsc::{tracing_unbounded, TracingUnboundedSender, TracingUnboundedReceiver};
 
 pub use libp2p::PeerId;
@@ -35,22 +51,95 @@ pub use libp2p::PeerId;
 const BANNED_THRESHOLD: i32 = 82 * (i32::min_value() / 100);
 /// Reputation change for a node when we get disconnected from it.
 const DISCONNECT_REPUTATION_CHANGE: i32 = -256;
-/// Reserved peers group ID
-const RESERVED_NODES: &str = "reserved";
 /// Amount of time between the moment we disconnect from a node and the moment we remove it from
 /// the list.
 const FORGET_AFTER: Duration = Duration::from_secs(3600);
 
+/// Moves a reputation one second closer to zero.
+///
+/// If we multiply each second the reputation by `k` (where `k` is between 0 and 1), it takes
+/// `ln(0.5) / ln(k)` seconds to reduce the reputation by half. Use this formula to empirically
+/// determine a value of `k` that looks correct.
+fn reput_tick(reput: i32) -> i32 {
+	// We use `k = 0.98`, so we divide by `50`. With that value, it takes 34.3 seconds to reduce
+	// the reputation by half.
+	let mut diff = reput / 50;
+	if diff == 0 && reput < 0 {
+		diff = -1;
+	} else if diff == 0 && reput > 0 {
+		diff = 1;
+	}
+	reput.saturating_sub(diff)
+}
+
+/// Returns the reputation that a node with the given reputation has after `elapsed` time without
+/// any report.
+pub fn decayed_reputation(mut reputation: i32, elapsed: Duration) -> i32 {
+	for _ in 0..elapsed.as_secs() {
+		if reputation == 0 {
+			break;
+		}
+		reputation = reput_tick(reputation);
+	}
+	reputation
+}
+
+/// Returns how long a node with the given reputation stays banned if it doesn't receive any
+/// other report, or `None` if it isn't banned.
+pub fn ban_duration(mut reputation: i32) -> Option<Duration> {
+	let mut secs = 0;
+	while reputation < BANNED_THRESHOLD {
+		reputation = reput_tick(reputation);
+		secs += 1;
+	}
+
+	if secs == 0 {
+		None
+	} else {
+		Some(Duration::from_secs(secs))
+	}
+}
+
 #[derive(Debug)]
 enum Action {
-	AddReservedPeer(PeerId),
-	RemoveReservedPeer(PeerId),
-	SetReservedPeers(HashSet<PeerId>),
-	SetReservedOnly(bool),
+	AddReservedPeer(SetId, PeerId),
+	RemoveReservedPeer(SetId, PeerId),
+	SetReservedPeers(SetId, HashSet<PeerId>),
+	SetReservedOnly(SetId, bool),
 	ReportPeer(PeerId, ReputationChange),
 	SetPriorityGroup(String, HashSet<PeerId>),
 	AddToPriorityGroup(String, PeerId),
 	RemoveFromPriorityGroup(String, PeerId),
+	BanPeer(PeerId, Option<Duration>),
+	UnbanPeer(PeerId),
+}
+
+/// Identifier of a set in the peerset.
+///
+/// Can be constructed using the `From<usize>` trait implementation based on the index of the set
+/// within [`PeersetConfig::sets`]. For example, the first element of [`PeersetConfig::sets`] is
+/// later referred to with `SetId::from(0)`. It is intended that the code responsible for building
+/// the [`PeersetConfig`] is also responsible for constructing the [`SetId`]s.
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct SetId(usize);
+
+impl SetId {
+	/// Builds a `SetId` from the index of the set within [`PeersetConfig::sets`].
+	pub const fn from(id: usize) -> Self {
+		SetId(id)
+	}
+}
+
+impl From<usize> for SetId {
+	fn from(id: usize) -> Self {
+		SetId(id)
+	}
+}
+
+impl From<SetId> for usize {
+	fn from(id: SetId) -> Self {
+		id.0
+	}
 }
 
 /// Description of a reputation adjustment for a node.
@@ -88,25 +177,26 @@ impl PeersetHandle {
 	///
 	/// > **Note**: Keep in mind that the networking has to know an address for this node,
 	/// >           otherwise it will not be able to connect to it.
-	pub fn add_reserved_peer(&self, peer_id: PeerId) {
-		let _ = self.tx.unbounded_send(Action::AddReservedPeer(peer_id));
+	pub fn add_reserved_peer(&self, set_id: SetId, peer_id: PeerId) {
+		let _ = self.tx.unbounded_send(Action::AddReservedPeer(set_id, peer_id));
 	}
 
 	/// Remove a previously-added reserved peer.
 	///
 	/// Has no effect if the node was not a reserved peer.
-	pub fn remove_reserved_peer(&self, peer_id: PeerId) {
-		let _ = self.tx.unbounded_send(Action::RemoveReservedPeer(peer_id));
+	pub fn remove_reserved_peer(&self, set_id: SetId, peer_id: PeerId) {
+		let _ = self.tx.unbounded_send(Action::RemoveReservedPeer(set_id, peer_id));
 	}
 
-	/// Sets whether or not the peerset only has connections .
-	pub fn set_reserved_only(&self, reserved: bool) {
-		let _ = self.tx.unbounded_send(Action::SetReservedOnly(reserved));
+	/// Sets whether or not the peerset only has connections with nodes marked as reserved for
+	/// the given set.
+	pub fn set_reserved_only(&self, set_id: SetId, reserved: bool) {
+		let _ = self.tx.unbounded_send(Action::SetReservedOnly(set_id, reserved));
 	}
 
 	/// Set reserved peers to the new set.
-	pub fn set_reserved_peers(&self, peer_ids: HashSet<PeerId>) {
-		let _ = self.tx.unbounded_send(Action::SetReservedPeers(peer_ids));
+	pub fn set_reserved_peers(&self, set_id: SetId, peer_ids: HashSet<PeerId>) {
+		let _ = self.tx.unbounded_send(Action::SetReservedPeers(set_id, peer_ids));
 	}
 
 	/// Reports an adjustment to the reputation of the given peer.
@@ -128,6 +218,22 @@ impl PeersetHandle {
 	pub fn remove_from_priority_group(&self, group_id: String, peer_id: PeerId) {
 		let _ = self.tx.unbounded_send(Action::RemoveFromPriorityGroup(group_id, peer_id));
 	}
+
+	/// Bans a peer for the given duration, or permanently if `duration` is `None`. The peer is
+	/// disconnected from every set, and we neither connect to it nor accept its connections until
+	/// the ban expires, even if it is a reserved node.
+	///
+	/// Banning a peer that is already banned replaces the previous expiry time.
+	pub fn ban_peer(&self, peer_id: PeerId, duration: Option<Duration>) {
+		let _ = self.tx.unbounded_send(Action::BanPeer(peer_id, duration));
+	}
+
+	/// Lifts the ban of a peer before it expires.
+	///
+	/// Has no effect if the peer isn't banned.
+	pub fn unban_peer(&self, peer_id: PeerId) {
+		let _ = self.tx.unbounded_send(Action::UnbanPeer(peer_id));
+	}
 }
 
 /// Message that can be sent by the peer set manager (PSM).
@@ -135,10 +241,20 @@ impl PeersetHandle {
 pub enum Message {
 	/// Request to open a connection to the given peer. From the point of view of the PSM, we are
 	/// immediately connected.
-	Connect(PeerId),
+	Connect {
+		/// Set the connection belongs to.
+		set_id: SetId,
+		/// Peer to connect to.
+		peer_id: PeerId,
+	},
 
 	/// Drop the connection to the given peer, or cancel the connection attempt after a `Connect`.
-	Drop(PeerId),
+	Drop {
+		/// Set the connection belongs to.
+		set_id: SetId,
+		/// Peer to disconnect from.
+		peer_id: PeerId,
+	},
 
 	/// Equivalent to `Connect` for the peer corresponding to this incoming index.
 	Accept(IncomingIndex),
@@ -160,26 +276,43 @@ impl From<u64> for IncomingIndex {
 /// Configuration to pass when creating the peer set manager.
 #[derive(Debug)]
 pub struct PeersetConfig {
+	/// List of sets of nodes the peerset manages.
+	pub sets: Vec<SetConfig>,
+
+	/// Lists of nodes we should always be connected to, in every set that isn't in reserved-only
+	/// mode.
+	///
+	/// > **Note**: Keep in mind that the networking has to know an address for these nodes,
+	/// >           otherwise it will not be able to connect to them.
+	pub priority_groups: Vec<(String, HashSet<PeerId>)>,
+
+	/// List of nodes that are permanently banned. See [`PeersetHandle::ban_peer`].
+	pub banned_peers: HashSet<PeerId>,
+}
+
+/// Configuration for a single set of nodes.
+#[derive(Debug)]
+pub struct SetConfig {
 	/// Maximum number of ingoing links to peers.
 	pub in_peers: u32,
 
 	/// Maximum number of outgoing links to peers.
 	pub out_peers: u32,
 
-	/// List of bootstrap nodes to initialize the peer with.
+	/// List of bootstrap nodes to initialize the set with.
 	///
 	/// > **Note**: Keep in mind that the networking has to know an address for these nodes,
 	/// >           otherwise it will not be able to connect to them.
 	pub bootnodes: Vec<PeerId>,
 
-	/// If true, we only accept nodes in [`PeersetConfig::priority_groups`].
-	pub reserved_only: bool,
-
 	/// Lists of nodes we should always be connected to.
 	///
 	/// > **Note**: Keep in mind that the networking has to know an address for these nodes,
 	/// >           otherwise it will not be able to connect to them.
-	pub priority_groups: Vec<(String, HashSet<PeerId>)>,
+	pub reserved_nodes: HashSet<PeerId>,
+
+	/// If true, we only accept nodes in [`SetConfig::reserved_nodes`].
+	pub reserved_only: bool,
 }
 
 /// Side of the peer set manager owned by the network. In other words, the "receiving" side.
@@ -190,11 +323,17 @@ pub struct PeersetConfig {
 pub struct Peerset {
 	/// Underlying data structure for the nodes's states.
 	data: peersstate::PeersState,
-	/// If true, we only accept reserved nodes.
-	reserved_only: bool,
-	/// Lists of nodes that don't occupy slots and that we should try to always be connected to.
-	/// Is kept in sync with the list of reserved nodes in [`Peerset::data`].
+	/// For each set, lists of nodes that don't occupy slots and that we should try to always be
+	/// connected to, and whether only reserved nodes are accepted. Is kept in sync with the list
+	/// of non-slot-occupying nodes in [`Peerset::data`].
+	reserved_nodes: Vec<(HashSet<PeerId>, bool)>,
+	/// Lists of nodes that don't occupy slots and that we should try to always be connected to in
+	/// every set that isn't in reserved-only mode. Is kept in sync with the list of
+	/// non-slot-occupying nodes in [`Peerset::data`].
 	priority_groups: HashMap<String, HashSet<PeerId>>,
+	/// Nodes that are explicitly banned, and when their ban expires. `None` means that the ban
+	/// is permanent. Banned nodes are kept at the lowest possible reputation.
+	banned: HashMap<PeerId, Option<Instant>>,
 	/// Receiver for messages from the `PeersetHandle` and from `tx`.
 	rx: TracingUnboundedReceiver<Action>,
 	/// Sending side of `rx`.
@@ -205,6 +344,9 @@ pub struct Peerset {
 	created: Instant,
 	/// Last time when we updated the reputations of connected nodes.
 	latest_time_update: Instant,
+	/// Fires when the earliest temporary ban expires, along with that expiry time. Lets us
+	/// connect again to a node whose ban has expired without waiting for another action.
+	ban_expiry_timer: Option<(Instant, Delay)>,
 }
 
 impl Peerset {
@@ -219,62 +361,122 @@ impl Peerset {
 		let now = Instant::now();
 
 		let mut peerset = Peerset {
-			data: peersstate::PeersState::new(config.in_peers, config.out_peers),
+			data: peersstate::PeersState::new(config.sets.iter().map(|set| peersstate::SetConfig {
+				in_peers: set.in_peers,
+				out_peers: set.out_peers,
+			})),
 			tx,
 			rx,
-			reserved_only: config.reserved_only,
+			reserved_nodes: config.sets.iter()
+				.map(|set| (set.reserved_nodes.clone(), set.reserved_only))
+				.collect(),
 			priority_groups: config.priority_groups.clone().into_iter().collect(),
+			banned: config.banned_peers.iter().map(|peer_id| (peer_id.clone(), None)).collect(),
 			message_queue: VecDeque::new(),
 			created: now,
 			latest_time_update: now,
+			ban_expiry_timer: None,
 		};
 
-		for node in config.priority_groups.into_iter().flat_map(|(_, l)| l) {
-			peerset.data.add_no_slot_node(node);
+		for peer_id in config.banned_peers {
+			peerset.data.peer_reputation(peer_id).set_reputation(i32::min_value());
 		}
 
-		for peer_id in config.bootnodes {
-			if let peersstate::Peer::Unknown(entry) = peerset.data.peer(&peer_id) {
-				entry.discover();
-			} else {
-				debug!(target: "peerset", "Duplicate bootnode in config: {:?}", peer_id);
+		for (set, set_config) in config.sets.into_iter().enumerate() {
+			for node in set_config.reserved_nodes {
+				peerset.data.add_no_slot_node(set, node);
+			}
+
+			for node in config.priority_groups.iter().flat_map(|(_, l)| l) {
+				peerset.data.add_no_slot_node(set, node.clone());
+			}
+
+			for peer_id in set_config.bootnodes {
+				if let peersstate::Peer::Unknown(entry) = peerset.data.peer(set, &peer_id) {
+					entry.discover();
+				} else {
+					debug!(target: "peerset", "Duplicate bootnode in config: {:?}", peer_id);
+				}
 			}
 		}
 
-		peerset.alloc_slots();
+		for set_index in 0..peerset.data.num_sets() {
+			peerset.alloc_slots(SetId(set_index));
+		}
+
 		(peerset, handle)
 	}
 
-	fn on_add_reserved_peer(&mut self, peer_id: PeerId) {
-		self.on_add_to_priority_group(RESERVED_NODES, peer_id);
+	fn on_add_reserved_peer(&mut self, set_id: SetId, peer_id: PeerId) {
+		let newly_inserted = self.reserved_nodes[set_id.0].0.insert(peer_id.clone());
+		if !newly_inserted {
+			return;
+		}
+
+		self.data.add_no_slot_node(set_id.0, peer_id);
+		self.alloc_slots(set_id);
 	}
 
-	fn on_remove_reserved_peer(&mut self, peer_id: PeerId) {
-		self.on_remove_from_priority_group(RESERVED_NODES, peer_id);
+	fn on_remove_reserved_peer(&mut self, set_id: SetId, peer_id: PeerId) {
+		if !self.reserved_nodes[set_id.0].0.remove(&peer_id) {
+			return;
+		}
+
+		// The node is still a no-slot node if it belongs to a priority group.
+		if !self.priority_groups.values().any(|l| l.contains(&peer_id)) {
+			self.data.remove_no_slot_node(set_id.0, &peer_id);
+		}
+
+		// Nothing more to do if not in reserved-only mode.
+		if !self.reserved_nodes[set_id.0].1 {
+			return;
+		}
+
+		// If, however, the peerset is in reserved-only mode, then the removed node needs to be
+		// disconnected.
+		if let peersstate::Peer::Connected(peer) = self.data.peer(set_id.0, &peer_id) {
+			peer.disconnect();
+			self.message_queue.push_back(Message::Drop { set_id, peer_id });
+		}
 	}
 
-	fn on_set_reserved_peers(&mut self, peer_ids: HashSet<PeerId>) {
-		self.on_set_priority_group(RESERVED_NODES, peer_ids);
+	fn on_set_reserved_peers(&mut self, set_id: SetId, peer_ids: HashSet<PeerId>) {
+		// Determine the difference between the current group and the new list.
+		let (to_insert, to_remove) = {
+			let to_insert = peer_ids.difference(&self.reserved_nodes[set_id.0].0)
+				.cloned().collect::<Vec<_>>();
+			let to_remove = self.reserved_nodes[set_id.0].0.difference(&peer_ids)
+				.cloned().collect::<Vec<_>>();
+			(to_insert, to_remove)
+		};
+
+		for node in to_insert {
+			self.on_add_reserved_peer(set_id, node);
+		}
+
+		for node in to_remove {
+			self.on_remove_reserved_peer(set_id, node);
+		}
 	}
 
-	fn on_set_reserved_only(&mut self, reserved_only: bool) {
-		self.reserved_only = reserved_only;
+	fn on_set_reserved_only(&mut self, set_id: SetId, reserved_only: bool) {
+		self.reserved_nodes[set_id.0].1 = reserved_only;
 
-		if self.reserved_only {
+		if reserved_only {
 			// Disconnect all the nodes that aren't reserved.
-			for peer_id in self.data.connected_peers().cloned().collect::<Vec<_>>().into_iter() {
-				if self.priority_groups.get(RESERVED_NODES).map_or(false, |g| g.contains(&peer_id)) {
+			for peer_id in self.data.connected_peers(set_id.0).cloned().collect::<Vec<_>>().into_iter() {
+				if self.reserved_nodes[set_id.0].0.contains(&peer_id) {
 					continue;
 				}
 
-				let peer = self.data.peer(&peer_id).into_connected()
+				let peer = self.data.peer(set_id.0, &peer_id).into_connected()
 					.expect("We are enumerating connected peers, therefore the peer is connected; qed");
 				peer.disconnect();
-				self.message_queue.push_back(Message::Drop(peer_id));
+				self.message_queue.push_back(Message::Drop { set_id, peer_id });
 			}
 
 		} else {
-			self.alloc_slots();
+			self.alloc_slots(set_id);
 		}
 	}
 
@@ -294,7 +496,9 @@ impl Peerset {
 			// We don't call `on_add_to_priority_group` here in order to avoid calling
 			// `alloc_slots` all the time.
 			self.priority_groups.entry(group_id.to_owned()).or_default().insert(peer_id.clone());
-			self.data.add_no_slot_node(peer_id.clone());
+			for set_index in 0..self.data.num_sets() {
+				self.data.add_no_slot_node(set_index, peer_id.clone());
+			}
 		}
 
 		// Enumerate elements in `current_group` not in `peers`.
@@ -303,14 +507,18 @@ impl Peerset {
 		}
 
 		if !to_insert.is_empty() {
-			self.alloc_slots();
+			for set_index in 0..self.data.num_sets() {
+				self.alloc_slots(SetId(set_index));
+			}
 		}
 	}
 
 	fn on_add_to_priority_group(&mut self, group_id: &str, peer_id: PeerId) {
 		self.priority_groups.entry(group_id.to_owned()).or_default().insert(peer_id.clone());
-		self.data.add_no_slot_node(peer_id);
-		self.alloc_slots();
+		for set_index in 0..self.data.num_sets() {
+			self.data.add_no_slot_node(set_index, peer_id.clone());
+			self.alloc_slots(SetId(set_index));
+		}
 	}
 
 	fn on_remove_from_priority_group(&mut self, group_id: &str, peer_id: PeerId) {
@@ -324,16 +532,26 @@ impl Peerset {
 			return;
 		}
 
-		// If that `PeerId` isn't in any other group, then it is no longer no-slot-occupying.
-		if !self.priority_groups.values().any(|l| l.contains(&peer_id)) {
-			self.data.remove_no_slot_node(&peer_id);
-		}
+		for set_index in 0..self.data.num_sets() {
+			let (reserved_nodes, reserved_only) = &self.reserved_nodes[set_index];
+			if reserved_nodes.contains(&peer_id) {
+				continue;
+			}
 
-		// Disconnect the peer if necessary.
-		if group_id != RESERVED_NODES && self.reserved_only {
-			if let peersstate::Peer::Connected(peer) = self.data.peer(&peer_id) {
-				peer.disconnect();
-				self.message_queue.push_back(Message::Drop(peer_id));
+			// If that `PeerId` isn't in any other group, then it is no longer no-slot-occupying.
+			if !self.priority_groups.values().any(|l| l.contains(&peer_id)) {
+				self.data.remove_no_slot_node(set_index, &peer_id);
+			}
+
+			// Disconnect the peer if necessary.
+			if *reserved_only {
+				if let peersstate::Peer::Connected(peer) = self.data.peer(set_index, &peer_id) {
+					peer.disconnect();
+					self.message_queue.push_back(Message::Drop {
+						set_id: SetId(set_index),
+						peer_id: peer_id.clone(),
+					});
+				}
 			}
 		}
 	}
@@ -342,33 +560,65 @@ impl Peerset {
 		// We want reputations to be up-to-date before adjusting them.
 		self.update_time();
 
-		match self.data.peer(&peer_id) {
-			peersstate::Peer::Connected(mut peer) => {
-				peer.add_reputation(change.value);
-				if peer.reputation() < BANNED_THRESHOLD {
-					debug!(target: "peerset", "Report {}: {:+} to {}. Reason: {}, Disconnecting",
-						peer_id, change.value, peer.reputation(), change.reason
-					);
-					peer.disconnect();
-					self.message_queue.push_back(Message::Drop(peer_id));
-				} else {
-					trace!(target: "peerset", "Report {}: {:+} to {}. Reason: {}",
-						peer_id, change.value, peer.reputation(), change.reason
-					);
-				}
-			},
-			peersstate::Peer::NotConnected(mut peer) => {
-				trace!(target: "peerset", "Report {}: {:+} to {}. Reason: {}",
-					peer_id, change.value, peer.reputation(), change.reason
-				);
-				peer.add_reputation(change.value)
-			},
-			peersstate::Peer::Unknown(peer) => {
-				trace!(target: "peerset", "Discover {}: {:+}. Reason: {}",
-					peer_id, change.value, change.reason
-				);
-				peer.discover().add_reputation(change.value)
-			},
+		// Banned nodes keep the lowest reputation until their ban expires.
+		if self.banned.contains_key(&peer_id) {
+			trace!(target: "peerset", "Ignoring report of banned {}. Reason: {}",
+				peer_id, change.reason
+			);
+			return;
+		}
+
+		let mut reputation = self.data.peer_reputation(peer_id.clone());
+		reputation.add_reputation(change.value);
+		if reputation.reputation() >= BANNED_THRESHOLD {
+			trace!(target: "peerset", "Report {}: {:+} to {}. Reason: {}",
+				peer_id, change.value, reputation.reputation(), change.reason
+			);
+			return;
+		}
+
+		debug!(target: "peerset", "Report {}: {:+} to {}. Reason: {}, Disconnecting",
+			peer_id, change.value, reputation.reputation(), change.reason
+		);
+
+		drop(reputation);
+		self.disconnect_from_all_sets(&peer_id);
+	}
+
+	fn on_ban_peer(&mut self, peer_id: PeerId, duration: Option<Duration>) {
+		self.update_time();
+
+		debug!(target: "peerset", "Banning {} for {:?}, Disconnecting", peer_id, duration);
+		self.data.peer_reputation(peer_id.clone()).set_reputation(i32::min_value());
+		self.banned.insert(peer_id.clone(), duration.map(|duration| Instant::now() + duration));
+		self.disconnect_from_all_sets(&peer_id);
+	}
+
+	fn on_unban_peer(&mut self, peer_id: PeerId) {
+		if self.banned.remove(&peer_id).is_none() {
+			return;
+		}
+
+		debug!(target: "peerset", "Unbanning {}", peer_id);
+		self.data.peer_reputation(peer_id).set_reputation(0);
+		for set_index in 0..self.data.num_sets() {
+			self.alloc_slots(SetId(set_index));
+		}
+	}
+
+	/// Disconnects the given peer from all the sets it is connected to, and tries to fill the
+	/// freed slots.
+	fn disconnect_from_all_sets(&mut self, peer_id: &PeerId) {
+		for set_index in 0..self.data.num_sets() {
+			if let peersstate::Peer::Connected(peer) = self.data.peer(set_index, peer_id) {
+				let peer = peer.disconnect();
+				self.message_queue.push_back(Message::Drop {
+					set_id: SetId(set_index),
+					peer_id: peer.into_peer_id(),
+				});
+
+				self.alloc_slots(SetId(set_index));
+			}
 		}
 	}
 
@@ -387,87 +637,99 @@ impl Peerset {
 		};
 
 		// For each elapsed second, move the node reputation towards zero.
-		// If we multiply each second the reputation by `k` (where `k` is between 0 and 1), it
-		// takes `ln(0.5) / ln(k)` seconds to reduce the reputation by half. Use this formula to
-		// empirically determine a value of `k` that looks correct.
 		for _ in 0..secs_diff {
 			for peer_id in self.data.peers().cloned().collect::<Vec<_>>() {
-				// We use `k = 0.98`, so we divide by `50`. With that value, it takes 34.3 seconds
-				// to reduce the reputation by half.
-				fn reput_tick(reput: i32) -> i32 {
-					let mut diff = reput / 50;
-					if diff == 0 && reput < 0 {
-						diff = -1;
-					} else if diff == 0 && reput > 0 {
-						diff = 1;
-					}
-					reput.saturating_sub(diff)
+				let mut peer_reputation = self.data.peer_reputation(peer_id.clone());
+
+				let before = peer_reputation.reputation();
+				let after = reput_tick(before);
+				trace!(target: "peerset", "Fleeting {}: {} -> {}", peer_id, before, after);
+				peer_reputation.set_reputation(after);
+
+				if after != 0 {
+					continue;
 				}
-				match self.data.peer(&peer_id) {
-					peersstate::Peer::Connected(mut peer) => {
-						let before = peer.reputation();
-						let after = reput_tick(before);
-						trace!(target: "peerset", "Fleeting {}: {} -> {}", peer_id, before, after);
-						peer.set_reputation(after)
-					}
-					peersstate::Peer::NotConnected(mut peer) => {
-						if peer.reputation() == 0 &&
-							peer.last_connected_or_discovered() + FORGET_AFTER < now
-						{
-							peer.forget_peer();
-						} else {
-							let before = peer.reputation();
-							let after = reput_tick(before);
-							trace!(target: "peerset", "Fleeting {}: {} -> {}", peer_id, before, after);
-							peer.set_reputation(after)
+
+				drop(peer_reputation);
+
+				// If the peer reaches a reputation of 0, and there is no connection to it,
+				// forget it.
+				for set_index in 0..self.data.num_sets() {
+					match self.data.peer(set_index, &peer_id) {
+						peersstate::Peer::Connected(_) => {}
+						peersstate::Peer::NotConnected(peer) => {
+							if peer.last_connected_or_discovered() + FORGET_AFTER < now {
+								peer.forget_peer();
+							}
+						}
+						peersstate::Peer::Unknown(_) => {
+							// Happens if this peer does not belong to this set.
 						}
 					}
-					peersstate::Peer::Unknown(_) => unreachable!("We iterate over known peers; qed")
-				};
+				}
+			}
+		}
+
+		// Lift the bans that have expired, and keep the other banned nodes at the lowest
+		// reputation. Nodes whose ban is lifted start again from a neutral reputation.
+		let expired = self.banned.iter()
+			.filter(|(_, until)| until.map_or(false, |until| until <= now))
+			.map(|(peer_id, _)| peer_id.clone())
+			.collect::<Vec<_>>();
+		let bans_expired = !expired.is_empty();
+		for peer_id in expired {
+			debug!(target: "peerset", "Ban of {} has expired", peer_id);
+			self.banned.remove(&peer_id);
+			self.data.peer_reputation(peer_id).set_reputation(0);
+		}
+		for peer_id in self.banned.keys() {
+			self.data.peer_reputation(peer_id.clone()).set_reputation(i32::min_value());
+		}
+
+		// Nodes whose ban has expired, reserved nodes in particular, can be connected to again.
+		if bans_expired {
+			for set_index in 0..self.data.num_sets() {
+				self.alloc_slots(SetId(set_index));
 			}
 		}
 	}
 
-	/// Try to fill available out slots with nodes.
-	fn alloc_slots(&mut self) {
+	/// Try to fill available out slots with nodes for the given set.
+	fn alloc_slots(&mut self, set_id: SetId) {
 		self.update_time();
 
 		// Try to connect to all the reserved nodes that we are not connected to.
-		loop {
-			let next = {
-				let data = &mut self.data;
-				self.priority_groups
-					.get(RESERVED_NODES)
-					.into_iter()
-					.flatten()
-					.find(move |n| {
-						data.peer(n).into_connected().is_none()
-					})
-					.cloned()
-			};
-
-			let next = match next {
-				Some(n) => n,
-				None => break,
-			};
+		for reserved_node in &self.reserved_nodes[set_id.0].0 {
+			if self.banned.contains_key(reserved_node) {
+				continue;
+			}
 
-			let next = match self.data.peer(&next) {
+			let entry = match self.data.peer(set_id.0, reserved_node) {
 				peersstate::Peer::Unknown(n) => n.discover(),
 				peersstate::Peer::NotConnected(n) => n,
-				peersstate::Peer::Connected(_) => {
-					debug_assert!(false, "State inconsistency: not connected state");
-					break;
-				}
+				peersstate::Peer::Connected(_) => continue,
 			};
 
-			match next.try_outgoing() {
-				Ok(conn) => self.message_queue.push_back(Message::Connect(conn.into_peer_id())),
-				Err(_) => break,	// No more slots available.
+			match entry.try_outgoing() {
+				Ok(conn) => self.message_queue.push_back(Message::Connect {
+					set_id,
+					peer_id: conn.into_peer_id()
+				}),
+				Err(_) => {
+					// An error is returned only if no slot is available. Reserved nodes are
+					// marked in the state machine with a flag saying "doesn't occupy a slot",
+					// and as such this should never happen.
+					debug_assert!(false);
+					log::error!(
+						target: "peerset",
+						"Not enough slots to connect to reserved node"
+					);
+				}
 			}
 		}
 
 		// Nothing more to do if we're in reserved mode.
-		if self.reserved_only {
+		if self.reserved_nodes[set_id.0].1 {
 			return;
 		}
 
@@ -475,11 +737,12 @@ impl Peerset {
 		loop {
 			let next = {
 				let data = &mut self.data;
+				let banned = &self.banned;
 				self.priority_groups
 					.values()
 					.flatten()
 					.find(move |n| {
-						data.peer(n).into_connected().is_none()
+						!banned.contains_key(*n) && data.peer(set_id.0, n).into_connected().is_none()
 					})
 					.cloned()
 			};
@@ -489,7 +752,7 @@ impl Peerset {
 				None => break,
 			};
 
-			let next = match self.data.peer(&next) {
+			let next = match self.data.peer(set_id.0, &next) {
 				peersstate::Peer::Unknown(n) => n.discover(),
 				peersstate::Peer::NotConnected(n) => n,
 				peersstate::Peer::Connected(_) => {
@@ -499,22 +762,26 @@ impl Peerset {
 			};
 
 			match next.try_outgoing() {
-				Ok(conn) => self.message_queue.push_back(Message::Connect(conn.into_peer_id())),
+				Ok(conn) => self.message_queue.push_back(Message::Connect {
+					set_id,
+					peer_id: conn.into_peer_id()
+				}),
 				Err(_) => break,	// No more slots available.
 			}
 		}
 
-		// Now, we try to connect to non-priority nodes.
-		while let Some(next) = self.data.highest_not_connected_peer() {
+		// Now, we try to connect to other nodes.
+		while let Some(next) = self.data.highest_not_connected_peer(set_id.0) {
 			// Don't connect to nodes with an abysmal reputation.
 			if next.reputation() < BANNED_THRESHOLD {
 				break;
 			}
 
 			match next.try_outgoing() {
-				Ok(conn) => self
-					.message_queue
-					.push_back(Message::Connect(conn.into_peer_id())),
+				Ok(conn) => self.message_queue.push_back(Message::Connect {
+					set_id,
+					peer_id: conn.into_peer_id()
+				}),
 				Err(_) => break, // No more slots available.
 			}
 		}
@@ -530,16 +797,16 @@ impl Peerset {
 	// Implementation note: because of concurrency issues, it is possible that we push a `Connect`
 	// message to the output channel with a `PeerId`, and that `incoming` gets called with the same
 	// `PeerId` before that message has been read by the user. In this situation we must not answer.
-	pub fn incoming(&mut self, peer_id: PeerId, index: IncomingIndex) {
-		trace!(target: "peerset", "Incoming {:?}", peer_id);
+	pub fn incoming(&mut self, set_id: SetId, peer_id: PeerId, index: IncomingIndex) {
+		trace!(target: "peerset", "Incoming {:?} in {:?}", peer_id, set_id);
 		self.update_time();
 
-		if self.reserved_only && !self.priority_groups.get(RESERVED_NODES).map_or(false, |n| n.contains(&peer_id)) {
+		if self.reserved_nodes[set_id.0].1 && !self.reserved_nodes[set_id.0].0.contains(&peer_id) {
 			self.message_queue.push_back(Message::Reject(index));
 			return;
 		}
 
-		let not_connected = match self.data.peer(&peer_id) {
+		let not_connected = match self.data.peer(set_id.0, &peer_id) {
 			// If we're already connected, don't answer, as the docs mention.
 			peersstate::Peer::Connected(_) => return,
 			peersstate::Peer::NotConnected(mut entry) => {
@@ -549,7 +816,7 @@ impl Peerset {
 			peersstate::Peer::Unknown(entry) => entry.discover(),
 		};
 
-		if not_connected.reputation() < BANNED_THRESHOLD {
+		if not_connected.reputation() < BANNED_THRESHOLD || self.banned.contains_key(&peer_id) {
 			self.message_queue.push_back(Message::Reject(index));
 			return
 		}
@@ -563,42 +830,42 @@ impl Peerset {
 	/// Indicate that we dropped an active connection with a peer, or that we failed to connect.
 	///
 	/// Must only be called after the PSM has either generated a `Connect` message with this
-	/// `PeerId`, or accepted an incoming connection with this `PeerId`.
-	pub fn dropped(&mut self, peer_id: PeerId) {
+	/// `PeerId` and set, or accepted an incoming connection with this `PeerId` and set.
+	pub fn dropped(&mut self, set_id: SetId, peer_id: PeerId) {
 		// We want reputations to be up-to-date before adjusting them.
 		self.update_time();
 
-		match self.data.peer(&peer_id) {
+		match self.data.peer(set_id.0, &peer_id) {
 			peersstate::Peer::Connected(mut entry) => {
 				// Decrease the node's reputation so that we don't try it again and again and again.
 				entry.add_reputation(DISCONNECT_REPUTATION_CHANGE);
-				trace!(target: "peerset", "Dropping {}: {:+} to {}",
-					peer_id, DISCONNECT_REPUTATION_CHANGE, entry.reputation());
+				trace!(target: "peerset", "Dropping {} from {:?}: {:+} to {}",
+					peer_id, set_id, DISCONNECT_REPUTATION_CHANGE, entry.reputation());
 				entry.disconnect();
 			}
 			peersstate::Peer::NotConnected(_) | peersstate::Peer::Unknown(_) =>
 				error!(target: "peerset", "Received dropped() for non-connected node"),
 		}
 
-		self.alloc_slots();
+		self.alloc_slots(set_id);
 	}
 
-	/// Adds discovered peer ids to the PSM.
+	/// Adds discovered peer ids to the given set of the PSM.
 	///
 	/// > **Note**: There is no equivalent "expired" message, meaning that it is the responsibility
 	/// >           of the PSM to remove `PeerId`s that fail to dial too often.
-	pub fn discovered<I: IntoIterator<Item = PeerId>>(&mut self, peer_ids: I) {
+	pub fn discovered<I: IntoIterator<Item = PeerId>>(&mut self, set_id: SetId, peer_ids: I) {
 		let mut discovered_any = false;
 
 		for peer_id in peer_ids {
-			if let peersstate::Peer::Unknown(entry) = self.data.peer(&peer_id) {
+			if let peersstate::Peer::Unknown(entry) = self.data.peer(set_id.0, &peer_id) {
 				entry.discover();
 				discovered_any = true;
 			}
 		}
 
 		if discovered_any {
-			self.alloc_slots();
+			self.alloc_slots(set_id);
 		}
 	}
 
@@ -615,27 +882,59 @@ impl Peerset {
 		self.update_time();
 
 		json!({
-			"nodes": self.data.peers().cloned().collect::<Vec<_>>().into_iter().map(|peer_id| {
-				let state = match self.data.peer(&peer_id) {
-					peersstate::Peer::Connected(entry) => json!({
-						"connected": true,
-						"reputation": entry.reputation()
-					}),
-					peersstate::Peer::NotConnected(entry) => json!({
-						"connected": false,
-						"reputation": entry.reputation()
-					}),
-					peersstate::Peer::Unknown(_) =>
-						unreachable!("We iterate over the known peers; QED")
-				};
-
-				(peer_id.to_base58(), state)
-			}).collect::<HashMap<_, _>>(),
-			"reserved_only": self.reserved_only,
+			"sets": (0..self.data.num_sets()).map(|set_index| {
+				json!({
+					"nodes": self.data.peers().cloned().collect::<Vec<_>>().into_iter().filter_map(|peer_id| {
+						let state = match self.data.peer(set_index, &peer_id) {
+							peersstate::Peer::Connected(entry) => json!({
+								"connected": true,
+								"reputation": entry.reputation()
+							}),
+							peersstate::Peer::NotConnected(entry) => json!({
+								"connected": false,
+								"reputation": entry.reputation()
+							}),
+							peersstate::Peer::Unknown(_) => return None,
+						};
+
+						Some((peer_id.to_base58(), state))
+					}).collect::<HashMap<_, _>>(),
+					"reserved_nodes": self.reserved_nodes[set_index].0.iter().map(|peer_id| {
+						peer_id.to_base58()
+					}).collect::<HashSet<_>>(),
+					"reserved_only": self.reserved_nodes[set_index].1,
+				})
+			}).collect::<Vec<_>>(),
 			"message_queue": self.message_queue.len(),
 		})
 	}
 
+	/// Returns the reputation of all the nodes we know about.
+	pub fn reputations(&mut self) -> Vec<(PeerId, i32)> {
+		self.update_time();
+
+		self.data.peers().cloned().collect::<Vec<_>>().into_iter().map(|peer_id| {
+			let reputation = self.data.peer_reputation(peer_id.clone()).reputation();
+			(peer_id, reputation)
+		}).collect()
+	}
+
+	/// Returns the nodes that are explicitly banned, and for how long they remain banned. `None`
+	/// means that the ban is permanent.
+	pub fn banned_peers(&mut self) -> Vec<(PeerId, Option<Duration>)> {
+		self.update_time();
+
+		let now = Instant::now();
+		self.banned.iter()
+			.map(|(peer_id, until)| {
+				let remaining = until.map(|until| {
+					if until > now { until - now } else { Duration::from_secs(0) }
+				});
+				(peer_id.clone(), remaining)
+			})
+			.collect()
+	}
+
 	/// Returns the number of peers that we have discovered.
 	pub fn num_discovered_peers(&self) -> usize {
 		self.data.peers().len()
@@ -656,6 +955,23 @@ impl Stream for Peerset {
 				return Poll::Ready(Some(message));
 			}
 
+			let next_ban_expiry = self.banned.values().filter_map(|until| *until).min();
+			match next_ban_expiry {
+				Some(expiry) => {
+					if self.ban_expiry_timer.as_ref().map_or(true, |(at, _)| *at != expiry) {
+						self.ban_expiry_timer = Some((expiry, Delay::new_at(expiry)));
+					}
+				}
+				None => self.ban_expiry_timer = None,
+			}
+			if let Some((_, timer)) = self.ban_expiry_timer.as_mut() {
+				if let Poll::Ready(_) = Future::poll(Pin::new(timer), cx) {
+					self.ban_expiry_timer = None;
+					self.update_time();
+					continue;
+				}
+			}
+
 			let action = match Stream::poll_next(Pin::new(&mut self.rx), cx) {
 				Poll::Pending => return Poll::Pending,
 				Poll::Ready(Some(event)) => event,
@@ -663,14 +979,14 @@ impl Stream for Peerset {
 			};
 
 			match action {
-				Action::AddReservedPeer(peer_id) =>
-					self.on_add_reserved_peer(peer_id),
-				Action::RemoveReservedPeer(peer_id) =>
-					self.on_remove_reserved_peer(peer_id),
-				Action::SetReservedPeers(peer_ids) =>
-					self.on_set_reserved_peers(peer_ids),
-				Action::SetReservedOnly(reserved) =>
-					self.on_set_reserved_only(reserved),
+				Action::AddReservedPeer(set_id, peer_id) =>
+					self.on_add_reserved_peer(set_id, peer_id),
+				Action::RemoveReservedPeer(set_id, peer_id) =>
+					self.on_remove_reserved_peer(set_id, peer_id),
+				Action::SetReservedPeers(set_id, peer_ids) =>
+					self.on_set_reserved_peers(set_id, peer_ids),
+				Action::SetReservedOnly(set_id, reserved) =>
+					self.on_set_reserved_only(set_id, reserved),
 				Action::ReportPeer(peer_id, score_diff) =>
 					self.on_report_peer(peer_id, score_diff),
 				Action::SetPriorityGroup(group_id, peers) =>
@@ -679,6 +995,10 @@ impl Stream for Peerset {
 					self.on_add_to_priority_group(&group_id, peer_id),
 				Action::RemoveFromPriorityGroup(group_id, peer_id) =>
 					self.on_remove_from_priority_group(&group_id, peer_id),
+				Action::BanPeer(peer_id, duration) =>
+					self.on_ban_peer(peer_id, duration),
+				Action::UnbanPeer(peer_id) =>
+					self.on_unban_peer(peer_id),
 			}
 		}
 	}
@@ -688,8 +1008,12 @@ impl Stream for Peerset {
 mod tests {
 	use libp2p::PeerId;
 	use futures::prelude::*;
-	use super::{PeersetConfig, Peerset, Message, IncomingIndex, ReputationChange, BANNED_THRESHOLD};
-	use std::{pin::Pin, task::Poll, thread, time::Duration};
+	use super::{
+		PeersetConfig, Peerset, Message, IncomingIndex, ReputationChange, SetConfig, SetId,
+		BANNED_THRESHOLD, ban_duration, decayed_reputation,
+	};
+	use std::{collections::HashSet, pin::Pin, task::Poll, thread, time::Duration};
+	use wasm_timer::Instant;
 
 	fn assert_messages(mut peerset: Peerset, messages: Vec<Message>) -> Peerset {
 		for expected_message in messages {
@@ -712,20 +1036,24 @@ mod tests {
 		let reserved_peer = PeerId::random();
 		let reserved_peer2 = PeerId::random();
 		let config = PeersetConfig {
-			in_peers: 0,
-			out_peers: 2,
-			bootnodes: vec![bootnode],
-			reserved_only: true,
+			sets: vec![SetConfig {
+				in_peers: 0,
+				out_peers: 2,
+				bootnodes: vec![bootnode],
+				reserved_nodes: HashSet::new(),
+				reserved_only: true,
+			}],
 			priority_groups: Vec::new(),
+			banned_peers: HashSet::new(),
 		};
 
 		let (peerset, handle) = Peerset::from_config(config);
-		handle.add_reserved_peer(reserved_peer.clone());
-		handle.add_reserved_peer(reserved_peer2.clone());
+		handle.add_reserved_peer(SetId::from(0), reserved_peer.clone());
+		handle.add_reserved_peer(SetId::from(0), reserved_peer2.clone());
 
 		assert_messages(peerset, vec![
-			Message::Connect(reserved_peer),
-			Message::Connect(reserved_peer2)
+			Message::Connect { set_id: SetId::from(0), peer_id: reserved_peer },
+			Message::Connect { set_id: SetId::from(0), peer_id: reserved_peer2 }
 		]);
 	}
 
@@ -740,21 +1068,25 @@ mod tests {
 		let ii3 = IncomingIndex(3);
 		let ii4 = IncomingIndex(3);
 		let config = PeersetConfig {
-			in_peers: 2,
-			out_peers: 1,
-			bootnodes: vec![bootnode.clone()],
-			reserved_only: false,
+			sets: vec![SetConfig {
+				in_peers: 2,
+				out_peers: 1,
+				bootnodes: vec![bootnode.clone()],
+				reserved_nodes: HashSet::new(),
+				reserved_only: false,
+			}],
 			priority_groups: Vec::new(),
+			banned_peers: HashSet::new(),
 		};
 
 		let (mut peerset, _handle) = Peerset::from_config(config);
-		peerset.incoming(incoming.clone(), ii);
-		peerset.incoming(incoming, ii4);
-		peerset.incoming(incoming2, ii2);
-		peerset.incoming(incoming3, ii3);
+		peerset.incoming(SetId::from(0), incoming.clone(), ii);
+		peerset.incoming(SetId::from(0), incoming, ii4);
+		peerset.incoming(SetId::from(0), incoming2, ii2);
+		peerset.incoming(SetId::from(0), incoming3, ii3);
 
 		assert_messages(peerset, vec![
-			Message::Connect(bootnode),
+			Message::Connect { set_id: SetId::from(0), peer_id: bootnode },
 			Message::Accept(ii),
 			Message::Accept(ii2),
 			Message::Reject(ii3),
@@ -766,15 +1098,19 @@ mod tests {
 		let incoming = PeerId::random();
 		let ii = IncomingIndex(1);
 		let config = PeersetConfig {
-			in_peers: 50,
-			out_peers: 50,
-			bootnodes: vec![],
-			reserved_only: true,
+			sets: vec![SetConfig {
+				in_peers: 50,
+				out_peers: 50,
+				bootnodes: vec![],
+				reserved_nodes: HashSet::new(),
+				reserved_only: true,
+			}],
 			priority_groups: vec![],
+			banned_peers: HashSet::new(),
 		};
 
 		let (mut peerset, _) = Peerset::from_config(config);
-		peerset.incoming(incoming, ii);
+		peerset.incoming(SetId::from(0), incoming, ii);
 
 		assert_messages(peerset, vec![
 			Message::Reject(ii),
@@ -787,32 +1123,40 @@ mod tests {
 		let discovered = PeerId::random();
 		let discovered2 = PeerId::random();
 		let config = PeersetConfig {
-			in_peers: 0,
-			out_peers: 2,
-			bootnodes: vec![bootnode.clone()],
-			reserved_only: false,
+			sets: vec![SetConfig {
+				in_peers: 0,
+				out_peers: 2,
+				bootnodes: vec![bootnode.clone()],
+				reserved_nodes: HashSet::new(),
+				reserved_only: false,
+			}],
 			priority_groups: vec![],
+			banned_peers: HashSet::new(),
 		};
 
 		let (mut peerset, _handle) = Peerset::from_config(config);
-		peerset.discovered(Some(discovered.clone()));
-		peerset.discovered(Some(discovered.clone()));
-		peerset.discovered(Some(discovered2));
+		peerset.discovered(SetId::from(0), Some(discovered.clone()));
+		peerset.discovered(SetId::from(0), Some(discovered.clone()));
+		peerset.discovered(SetId::from(0), Some(discovered2));
 
 		assert_messages(peerset, vec![
-			Message::Connect(bootnode),
-			Message::Connect(discovered),
+			Message::Connect { set_id: SetId::from(0), peer_id: bootnode },
+			Message::Connect { set_id: SetId::from(0), peer_id: discovered },
 		]);
 	}
 
 	#[test]
 	fn test_peerset_banned() {
 		let (mut peerset, handle) = Peerset::from_config(PeersetConfig {
-			in_peers: 25,
-			out_peers: 25,
-			bootnodes: vec![],
-			reserved_only: false,
+			sets: vec![SetConfig {
+				in_peers: 25,
+				out_peers: 25,
+				bootnodes: vec![],
+				reserved_nodes: HashSet::new(),
+				reserved_only: false,
+			}],
 			priority_groups: vec![],
+			banned_peers: HashSet::new(),
 		});
 
 		// We ban a node by setting its reputation under the threshold.
@@ -824,7 +1168,7 @@ mod tests {
 			assert_eq!(Stream::poll_next(Pin::new(&mut peerset), cx), Poll::Pending);
 
 			// Check that an incoming connection from that node gets refused.
-			peerset.incoming(peer_id.clone(), IncomingIndex(1));
+			peerset.incoming(SetId::from(0), peer_id.clone(), IncomingIndex(1));
 			if let Poll::Ready(msg) = Stream::poll_next(Pin::new(&mut peerset), cx) {
 				assert_eq!(msg.unwrap(), Message::Reject(IncomingIndex(1)));
 			} else {
@@ -835,7 +1179,7 @@ mod tests {
 			thread::sleep(Duration::from_millis(1500));
 
 			// Try again. This time the node should be accepted.
-			peerset.incoming(peer_id.clone(), IncomingIndex(2));
+			peerset.incoming(SetId::from(0), peer_id.clone(), IncomingIndex(2));
 			while let Poll::Ready(msg) = Stream::poll_next(Pin::new(&mut peerset), cx) {
 				assert_eq!(msg.unwrap(), Message::Accept(IncomingIndex(2)));
 			}
@@ -845,4 +1189,162 @@ mod tests {
 
 		futures::executor::block_on(fut);
 	}
+
+	#[test]
+	fn test_peerset_sets_are_independent() {
+		let bootnode = PeerId::random();
+		let reserved_peer = PeerId::random();
+		let incoming = PeerId::random();
+		let config = PeersetConfig {
+			sets: vec![
+				SetConfig {
+					in_peers: 1,
+					out_peers: 1,
+					bootnodes: vec![bootnode.clone()],
+					reserved_nodes: HashSet::new(),
+					reserved_only: false,
+				},
+				SetConfig {
+					in_peers: 1,
+					out_peers: 0,
+					bootnodes: vec![],
+					reserved_nodes: vec![reserved_peer.clone()].into_iter().collect(),
+					reserved_only: true,
+				},
+			],
+			priority_groups: Vec::new(),
+			banned_peers: HashSet::new(),
+		};
+
+		let (mut peerset, _handle) = Peerset::from_config(config);
+		peerset.incoming(SetId::from(1), incoming.clone(), IncomingIndex(1));
+		peerset.incoming(SetId::from(0), incoming, IncomingIndex(2));
+
+		assert_messages(peerset, vec![
+			Message::Connect { set_id: SetId::from(0), peer_id: bootnode },
+			Message::Connect { set_id: SetId::from(1), peer_id: reserved_peer },
+			Message::Reject(IncomingIndex(1)),
+			Message::Accept(IncomingIndex(2)),
+		]);
+	}
+
+	#[test]
+	fn test_peerset_banned_peers() {
+		let bootnode = PeerId::random();
+		let banned_bootnode = PeerId::random();
+		let banned_reserved = PeerId::random();
+		let config = PeersetConfig {
+			sets: vec![SetConfig {
+				in_peers: 1,
+				out_peers: 2,
+				bootnodes: vec![banned_bootnode.clone(), bootnode.clone()],
+				reserved_nodes: vec![banned_reserved.clone()].into_iter().collect(),
+				reserved_only: false,
+			}],
+			priority_groups: Vec::new(),
+			banned_peers: vec![banned_bootnode.clone(), banned_reserved.clone()].into_iter().collect(),
+		};
+
+		let (mut peerset, handle) = Peerset::from_config(config);
+		assert_eq!(peerset.banned_peers().len(), 2);
+		assert!(peerset.banned_peers().iter().all(|(_, remaining)| remaining.is_none()));
+
+		peerset.incoming(SetId::from(0), banned_bootnode, IncomingIndex(1));
+		handle.unban_peer(banned_reserved.clone());
+
+		let mut peerset = assert_messages(peerset, vec![
+			Message::Connect { set_id: SetId::from(0), peer_id: bootnode },
+			Message::Reject(IncomingIndex(1)),
+			Message::Connect { set_id: SetId::from(0), peer_id: banned_reserved },
+		]);
+		assert_eq!(peerset.banned_peers().len(), 1);
+	}
+
+	#[test]
+	fn test_peerset_ban_expires() {
+		let bootnode = PeerId::random();
+		let config = PeersetConfig {
+			sets: vec![SetConfig {
+				in_peers: 1,
+				out_peers: 1,
+				bootnodes: vec![bootnode.clone()],
+				reserved_nodes: HashSet::new(),
+				reserved_only: false,
+			}],
+			priority_groups: Vec::new(),
+			banned_peers: HashSet::new(),
+		};
+
+		let (peerset, handle) = Peerset::from_config(config);
+		handle.ban_peer(bootnode.clone(), Some(Duration::from_secs(3600)));
+
+		let mut peerset = assert_messages(peerset, vec![
+			Message::Connect { set_id: SetId::from(0), peer_id: bootnode.clone() },
+			Message::Drop { set_id: SetId::from(0), peer_id: bootnode.clone() },
+		]);
+		assert_eq!(peerset.banned_peers().len(), 1);
+
+		// Reports don't lift the ban.
+		peerset.report_peer(bootnode.clone(), ReputationChange::new(i32::max_value(), ""));
+		peerset.incoming(SetId::from(0), bootnode.clone(), IncomingIndex(1));
+		let mut peerset = assert_messages(peerset, vec![Message::Reject(IncomingIndex(1))]);
+
+		// Pretend that the ban has reached its expiry time. The freed slot goes to the node again.
+		*peerset.banned.get_mut(&bootnode).unwrap() = Some(Instant::now());
+
+		peerset.incoming(SetId::from(0), bootnode.clone(), IncomingIndex(2));
+		let mut peerset = assert_messages(peerset, vec![
+			Message::Connect { set_id: SetId::from(0), peer_id: bootnode },
+		]);
+		assert!(peerset.banned_peers().is_empty());
+	}
+
+	#[test]
+	fn reserved_node_is_reconnected_when_its_ban_expires() {
+		let reserved = PeerId::random();
+		let config = PeersetConfig {
+			sets: vec![SetConfig {
+				in_peers: 1,
+				out_peers: 1,
+				bootnodes: vec![],
+				reserved_nodes: vec![reserved.clone()].into_iter().collect(),
+				reserved_only: true,
+			}],
+			priority_groups: Vec::new(),
+			banned_peers: HashSet::new(),
+		};
+
+		let (peerset, handle) = Peerset::from_config(config);
+		handle.ban_peer(reserved.clone(), Some(Duration::from_secs(3600)));
+
+		let mut peerset = assert_messages(peerset, vec![
+			Message::Connect { set_id: SetId::from(0), peer_id: reserved.clone() },
+			Message::Drop { set_id: SetId::from(0), peer_id: reserved.clone() },
+		]);
+
+		// Pretend that the ban has reached its expiry time. No action is sent to the peerset, the
+		// expiry alone makes it connect to the node again.
+		*peerset.banned.get_mut(&reserved).unwrap() = Some(Instant::now());
+
+		let mut peerset = assert_messages(peerset, vec![
+			Message::Connect { set_id: SetId::from(0), peer_id: reserved },
+		]);
+		assert!(peerset.banned_peers().is_empty());
+	}
+
+	#[test]
+	fn ban_duration_matches_reputation_decay() {
+		assert_eq!(ban_duration(0), None);
+		assert_eq!(ban_duration(BANNED_THRESHOLD), None);
+		assert_eq!(ban_duration(BANNED_THRESHOLD - 1), Some(Duration::from_secs(1)));
+
+		let duration = ban_duration(i32::min_value()).unwrap();
+		assert!(decayed_reputation(i32::min_value(), duration) >= BANNED_THRESHOLD);
+		assert!(
+			decayed_reputation(i32::min_value(), duration - Duration::from_secs(1)) < BANNED_THRESHOLD
+		);
+
+		assert_eq!(decayed_reputation(-100, Duration::from_secs(3600)), 0);
+		assert_eq!(decayed_reputation(100, Duration::from_secs(3600)), 0);
+	}
 }
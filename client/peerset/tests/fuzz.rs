@@ -20,8 +20,10 @@ use futures::prelude::*;
 use libp2p::PeerId;
 use rand::distributions::{Distribution, Uniform, WeightedIndex};
 use rand::seq::IteratorRandom;
-use std::{collections::HashMap, collections::HashSet, iter, pin::Pin, task::Poll};
-use sc_peerset::{IncomingIndex, Message, PeersetConfig, Peerset, ReputationChange};
+use std::{collections::HashMap, collections::HashSet, iter, pin::Pin, task::Poll, time::Duration};
+use sc_peerset::{
+	IncomingIndex, Message, PeersetConfig, Peerset, ReputationChange, SetConfig, SetId,
+};
 
 #[test]
 fn run() {
@@ -40,11 +42,17 @@ fn test_once() {
 	let mut reserved_nodes = HashSet::<PeerId>::new();
 
 	let (mut peerset, peerset_handle) = Peerset::from_config(PeersetConfig {
-		bootnodes: (0 .. Uniform::new_inclusive(0, 4).sample(&mut rng)).map(|_| {
-			let id = PeerId::random();
-			known_nodes.insert(id.clone());
-			id
-		}).collect(),
+		sets: vec![SetConfig {
+			bootnodes: (0 .. Uniform::new_inclusive(0, 4).sample(&mut rng)).map(|_| {
+				let id = PeerId::random();
+				known_nodes.insert(id.clone());
+				id
+			}).collect(),
+			reserved_nodes: HashSet::new(),
+			reserved_only: Uniform::new_inclusive(0, 10).sample(&mut rng) == 0,
+			in_peers: Uniform::new_inclusive(0, 25).sample(&mut rng),
+			out_peers: Uniform::new_inclusive(0, 25).sample(&mut rng),
+		}],
 		priority_groups: {
 			let nodes = (0 .. Uniform::new_inclusive(0, 2).sample(&mut rng)).map(|_| {
 				let id = PeerId::random();
@@ -54,9 +62,7 @@ fn test_once() {
 			}).collect();
 			vec![("foo".to_string(), nodes)]
 		},
-		reserved_only: Uniform::new_inclusive(0, 10).sample(&mut rng) == 0,
-		in_peers: Uniform::new_inclusive(0, 25).sample(&mut rng),
-		out_peers: Uniform::new_inclusive(0, 25).sample(&mut rng),
+		banned_peers: HashSet::new(),
 	});
 
 	futures::executor::block_on(futures::future::poll_fn(move |cx| {
@@ -73,17 +79,17 @@ fn test_once() {
 		// reach the end of the loop, the run has succeeded.
 		for _ in 0 .. 2500 {
 			// Each of these weights corresponds to an action that we may perform.
-			let action_weights = [150, 90, 90, 30, 30, 1, 1, 4, 4];
+			let action_weights = [150, 90, 90, 30, 30, 1, 1, 4, 4, 4, 4];
 			match WeightedIndex::new(&action_weights).unwrap().sample(&mut rng) {
 				// If we generate 0, poll the peerset.
 				0 => match Stream::poll_next(Pin::new(&mut peerset), cx) {
-					Poll::Ready(Some(Message::Connect(id))) => {
+					Poll::Ready(Some(Message::Connect { peer_id: id, .. })) => {
 						if let Some(id) = incoming_nodes.iter().find(|(_, v)| **v == id).map(|(&id, _)| id) {
 							incoming_nodes.remove(&id);
 						}
 						assert!(connected_nodes.insert(id));
 					}
-					Poll::Ready(Some(Message::Drop(id))) => { connected_nodes.remove(&id); }
+					Poll::Ready(Some(Message::Drop { peer_id: id, .. })) => { connected_nodes.remove(&id); }
 					Poll::Ready(Some(Message::Accept(n))) =>
 						assert!(connected_nodes.insert(incoming_nodes.remove(&n).unwrap())),
 					Poll::Ready(Some(Message::Reject(n))) =>
@@ -96,7 +102,7 @@ fn test_once() {
 				1 => {
 					let new_id = PeerId::random();
 					known_nodes.insert(new_id.clone());
-					peerset.discovered(iter::once(new_id));
+					peerset.discovered(SetId::from(0), iter::once(new_id));
 				}
 
 				// If we generate 2, adjust a random reputation.
@@ -108,30 +114,39 @@ fn test_once() {
 				// If we generate 3, disconnect from a random node.
 				3 => if let Some(id) = connected_nodes.iter().choose(&mut rng).cloned() {
 					connected_nodes.remove(&id);
-					peerset.dropped(id);
+					peerset.dropped(SetId::from(0), id);
 				}
 
 				// If we generate 4, connect to a random node.
 				4 => if let Some(id) = known_nodes.iter()
 					.filter(|n| incoming_nodes.values().all(|m| m != *n) && !connected_nodes.contains(*n))
 					.choose(&mut rng) {
-					peerset.incoming(id.clone(), next_incoming_id);
+					peerset.incoming(SetId::from(0), id.clone(), next_incoming_id);
 					incoming_nodes.insert(next_incoming_id, id.clone());
 					next_incoming_id.0 += 1;
 				}
 
 				// 5 and 6 are the reserved-only mode.
-				5 => peerset_handle.set_reserved_only(true),
-				6 => peerset_handle.set_reserved_only(false),
+				5 => peerset_handle.set_reserved_only(SetId::from(0), true),
+				6 => peerset_handle.set_reserved_only(SetId::from(0), false),
 
 				// 7 and 8 are about switching a random node in or out of reserved mode.
 				7 => if let Some(id) = known_nodes.iter().filter(|n| !reserved_nodes.contains(*n)).choose(&mut rng) {
-					peerset_handle.add_reserved_peer(id.clone());
+					peerset_handle.add_reserved_peer(SetId::from(0), id.clone());
 					reserved_nodes.insert(id.clone());
 				}
 				8 => if let Some(id) = reserved_nodes.iter().choose(&mut rng).cloned() {
 					reserved_nodes.remove(&id);
-					peerset_handle.remove_reserved_peer(id);
+					peerset_handle.remove_reserved_peer(SetId::from(0), id);
+				}
+
+				// 9 and 10 are about banning and unbanning a random node.
+				9 => if let Some(id) = known_nodes.iter().choose(&mut rng) {
+					let duration = Uniform::new_inclusive(0, 2).sample(&mut rng);
+					peerset_handle.ban_peer(id.clone(), Some(Duration::from_secs(duration)));
+				}
+				10 => if let Some(id) = known_nodes.iter().choose(&mut rng) {
+					peerset_handle.unban_peer(id.clone());
 				}
 
 				_ => unreachable!()
@@ -0,0 +1,79 @@
+// This file is part of Substrate.
+
+// Copyright (C) 2020 Parity Technologies (UK) Ltd.
+// SPDX-License-Identifier: GPL-3.0-or-later WITH Classpath-exception-2.0
+
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with this program. If not, see <https://www.gnu.org/licenses/>.
+
+//! Substrate events API.
+
+pub mod error;
+
+use jsonrpc_derive::rpc;
+use serde::{Deserialize, Serialize};
+use self::error::Result;
+
+pub use self::gen_client::Client as EventsClient;
+
+/// Position of an event within the finalized chain.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct EventPosition<Number> {
+	/// Number of the block that deposited the event.
+	pub block_number: Number,
+	/// Index of the event within the block's events.
+	pub event_index: u32,
+}
+
+/// An event deposited with the queried topic.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct TopicEvent<Hash, Number> {
+	/// Number of the block that deposited the event.
+	pub block_number: Number,
+	/// Hash of the block that deposited the event.
+	pub block_hash: Hash,
+	/// Index of the event within the block's events.
+	pub event_index: u32,
+}
+
+/// A page of events returned by `events_byTopic`.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct EventsPage<Hash, Number> {
+	/// Matching events, in the order they were deposited.
+	pub events: Vec<TopicEvent<Hash, Number>>,
+	/// Position to pass as `from` to fetch the next page, if there are more events.
+	pub next: Option<EventPosition<Number>>,
+}
+
+/// Substrate events RPC API.
+#[rpc]
+pub trait EventsApi<Hash, Number> {
+	/// Get the events deposited with `topic` in finalized blocks.
+	///
+	/// Events are returned starting at `from` (the genesis block by default) up to and
+	/// including block `to` (the last finalized block by default). At most `limit` events
+	/// are returned; `next` points at the first event that did not fit into the page.
+	///
+	/// Only available on nodes started with `--event-topics-index`.
+	#[rpc(name = "events_byTopic")]
+	fn events_by_topic(
+		&self,
+		topic: Hash,
+		from: Option<EventPosition<Number>>,
+		to: Option<Number>,
+		limit: Option<u32>,
+	) -> Result<EventsPage<Hash, Number>>;
+}
@@ -32,6 +32,8 @@ pub enum Error {
 	NotHealthy(Health),
 	/// Peer argument is malformatted.
 	MalformattedPeerArg(String),
+	/// IP range argument is malformatted.
+	MalformattedIpRangeArg(String),
 }
 
 impl std::error::Error for Error {}
@@ -51,7 +53,12 @@ impl From<Error> for rpc::Error {
 				code :rpc::ErrorCode::ServerError(BASE_ERROR + 2),
 				message: e.clone(),
 				data: None,
-			}
+			},
+			Error::MalformattedIpRangeArg(ref e) => rpc::Error {
+				code: rpc::ErrorCode::ServerError(BASE_ERROR + 3),
+				message: e.clone(),
+				data: None,
+			},
 		}
 	}
 }
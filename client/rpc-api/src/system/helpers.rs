@@ -73,6 +73,42 @@ pub struct PeerInfo<Hash, Number> {
 	pub best_number: Number,
 }
 
+/// Peer saved in the peer store of the node
+#[derive(Debug, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct StoredPeer {
+	/// Peer ID
+	pub peer_id: String,
+	/// Addresses the peer is known to be reachable at
+	pub addresses: Vec<String>,
+	/// Last time we were connected to the peer, in seconds since the UNIX epoch
+	pub last_seen: u64,
+	/// Reputation of the peer
+	pub reputation: i32,
+	/// Time until which the peer is banned, in seconds since the UNIX epoch
+	pub banned_until: Option<u64>,
+}
+
+/// Peer that is explicitly banned
+#[derive(Debug, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct BannedPeer {
+	/// Peer ID
+	pub peer_id: String,
+	/// Number of seconds until the ban expires, or `None` if the ban is permanent
+	pub expires_in: Option<u64>,
+}
+
+/// IP range that is banned
+#[derive(Debug, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct BannedIpRange {
+	/// IP range in CIDR notation
+	pub ip_range: String,
+	/// Number of seconds until the ban expires, or `None` if the ban is permanent
+	pub expires_in: Option<u64>,
+}
+
 /// The role the node is running as
 #[derive(Debug, PartialEq, Serialize, Deserialize)]
 pub enum NodeRole {
@@ -97,6 +133,10 @@ pub struct SyncState<Number> {
 	/// Height of the highest block learned from the network. Missing if no block is known yet.
 	#[serde(default = "Default::default", skip_serializing_if = "Option::is_none")]
 	pub highest_block: Option<Number>,
+	/// Lowest and highest heights of the blocks missing below a block imported with its state,
+	/// while they are downloaded in the background. Missing if no block is missing.
+	#[serde(default = "Default::default", skip_serializing_if = "Option::is_none")]
+	pub block_gap: Option<(Number, Number)>,
 }
 #[cfg(test)]
 mod tests {
@@ -134,6 +174,7 @@ mod tests {
 				starting_block: 12u32,
 				current_block: 50u32,
 				highest_block: Some(128u32),
+				block_gap: None,
 			}).unwrap(),
 			r#"{"startingBlock":12,"currentBlock":50,"highestBlock":128}"#,
 		);
@@ -143,8 +184,19 @@ mod tests {
 				starting_block: 12u32,
 				current_block: 50u32,
 				highest_block: None,
+				block_gap: None,
 			}).unwrap(),
 			r#"{"startingBlock":12,"currentBlock":50}"#,
 		);
+
+		assert_eq!(
+			::serde_json::to_string(&SyncState {
+				starting_block: 0u32,
+				current_block: 50u32,
+				highest_block: Some(128u32),
+				block_gap: Some((1u32, 39u32)),
+			}).unwrap(),
+			r#"{"startingBlock":0,"currentBlock":50,"highestBlock":128,"blockGap":[1,39]}"#,
+		);
 	}
 }
@@ -27,7 +27,9 @@ use futures::{future::BoxFuture, compat::Compat};
 
 use self::error::Result as SystemResult;
 
-pub use self::helpers::{SystemInfo, Health, PeerInfo, NodeRole, SyncState};
+pub use self::helpers::{
+	SystemInfo, Health, PeerInfo, NodeRole, SyncState, StoredPeer, BannedPeer, BannedIpRange,
+};
 pub use self::gen_client::Client as SystemClient;
 
 /// Substrate system RPC API
@@ -85,6 +87,12 @@ pub trait SystemApi<Hash, Number> {
 	fn system_network_state(&self)
 		-> Compat<BoxFuture<'static, jsonrpc_core::Result<jsonrpc_core::Value>>>;
 
+	/// Returns the peers saved in the peer store of the node, which persists the known addresses
+	/// and reputations of the peers across restarts.
+	#[rpc(name = "system_peerStore", returns = "Vec<StoredPeer>")]
+	fn system_peer_store(&self)
+		-> Compat<BoxFuture<'static, jsonrpc_core::Result<Vec<StoredPeer>>>>;
+
 	/// Adds a reserved peer. Returns the empty string or an error. The string
 	/// parameter should encode a `p2p` multiaddr.
 	///
@@ -100,6 +108,48 @@ pub trait SystemApi<Hash, Number> {
 	fn system_remove_reserved_peer(&self, peer_id: String)
 		-> Compat<BoxFuture<'static, Result<(), jsonrpc_core::Error>>>;
 
+	/// Bans a peer. Returns the empty string or an error. The string should encode only the
+	/// PeerId e.g. `QmSk5HQbn6LhUwDiNMseVUjuRYhEtYj4aUZ6WfWoGURpdV`.
+	///
+	/// The peer is disconnected and its connections are refused until the ban expires after
+	/// `duration` seconds. The ban is permanent if no duration is given.
+	#[rpc(name = "system_banPeer", returns = "()")]
+	fn system_ban_peer(&self, peer_id: String, duration: Option<u64>)
+		-> Compat<BoxFuture<'static, Result<(), jsonrpc_core::Error>>>;
+
+	/// Lifts the ban of a peer. Returns the empty string or an error. The string should encode
+	/// only the PeerId.
+	#[rpc(name = "system_unbanPeer", returns = "()")]
+	fn system_unban_peer(&self, peer_id: String)
+		-> Compat<BoxFuture<'static, Result<(), jsonrpc_core::Error>>>;
+
+	/// Returns the peers that are banned with `system_banPeer` or `--banned-peers`, and when
+	/// their ban expires.
+	#[rpc(name = "system_bannedPeers", returns = "Vec<BannedPeer>")]
+	fn system_banned_peers(&self)
+		-> Compat<BoxFuture<'static, jsonrpc_core::Result<Vec<BannedPeer>>>>;
+
+	/// Bans an IP range given in CIDR notation, e.g. `192.0.2.0/24` or `2001:db8::/32`. Returns
+	/// the empty string or an error.
+	///
+	/// The peers connected from or to an address of the range are disconnected, and connections
+	/// from and to the range are refused until the ban expires after `duration` seconds. The ban
+	/// is permanent if no duration is given.
+	#[rpc(name = "system_banIpRange", returns = "()")]
+	fn system_ban_ip_range(&self, ip_range: String, duration: Option<u64>)
+		-> Compat<BoxFuture<'static, Result<(), jsonrpc_core::Error>>>;
+
+	/// Lifts the ban of an IP range given in CIDR notation. Returns the empty string or an error.
+	#[rpc(name = "system_unbanIpRange", returns = "()")]
+	fn system_unban_ip_range(&self, ip_range: String)
+		-> Compat<BoxFuture<'static, Result<(), jsonrpc_core::Error>>>;
+
+	/// Returns the IP ranges that are banned with `system_banIpRange` or `--banned-ip-ranges`,
+	/// and when their ban expires.
+	#[rpc(name = "system_bannedIpRanges", returns = "Vec<BannedIpRange>")]
+	fn system_banned_ip_ranges(&self)
+		-> Compat<BoxFuture<'static, jsonrpc_core::Result<Vec<BannedIpRange>>>>;
+
 	/// Returns the roles the node is running as.
 	#[rpc(name = "system_nodeRoles", returns = "Vec<NodeRole>")]
 	fn system_node_roles(&self) -> Receiver<Vec<NodeRole>>;
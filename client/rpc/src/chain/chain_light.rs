@@ -104,7 +104,7 @@ impl<Block, Client, F> ChainBackend<Client, Block> for LightChain<Block, Client,
 					.compat()
 					.map(move |body| Some(SignedBlock {
 						block: Block::new(header, body),
-						justification: None,
+						justifications: None,
 					}))
 					.map_err(client_err)
 				),
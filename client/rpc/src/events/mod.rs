@@ -0,0 +1,89 @@
+// This file is part of Substrate.
+
+// Copyright (C) 2020 Parity Technologies (UK) Ltd.
+// SPDX-License-Identifier: GPL-3.0-or-later WITH Classpath-exception-2.0
+
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with this program. If not, see <https://www.gnu.org/licenses/>.
+
+//! Substrate events API.
+
+#[cfg(test)]
+mod tests;
+
+use std::sync::Arc;
+
+use sc_client_api::backend::EventTopicsIndex;
+use sp_blockchain::HeaderBackend;
+use sp_runtime::traits::{Block as BlockT, NumberFor, Zero};
+
+use self::error::{Error, Result};
+
+pub use sc_rpc_api::events::*;
+
+/// Number of events returned when the query does not specify a limit.
+const DEFAULT_LIMIT: u32 = 100;
+
+/// Maximum number of events returned by a single query.
+const MAX_LIMIT: u32 = 1000;
+
+/// Events API backed by the client's event topics index.
+pub struct Events<Block: BlockT, Client> {
+	client: Arc<Client>,
+	index: Arc<dyn EventTopicsIndex<Block>>,
+}
+
+impl<Block: BlockT, Client> Events<Block, Client> {
+	/// Create new instance of Events API.
+	pub fn new(client: Arc<Client>, index: Arc<dyn EventTopicsIndex<Block>>) -> Self {
+		Events { client, index }
+	}
+}
+
+impl<Block, Client> EventsApi<Block::Hash, NumberFor<Block>> for Events<Block, Client>
+	where
+		Block: BlockT + 'static,
+		Client: HeaderBackend<Block> + Send + Sync + 'static,
+{
+	fn events_by_topic(
+		&self,
+		topic: Block::Hash,
+		from: Option<EventPosition<NumberFor<Block>>>,
+		to: Option<NumberFor<Block>>,
+		limit: Option<u32>,
+	) -> Result<EventsPage<Block::Hash, NumberFor<Block>>> {
+		let limit = limit.unwrap_or(DEFAULT_LIMIT);
+		if limit > MAX_LIMIT {
+			return Err(Error::LimitTooLarge { requested: limit, max: MAX_LIMIT });
+		}
+
+		let from = from
+			.map(|position| (position.block_number, position.event_index))
+			.unwrap_or_else(|| (Zero::zero(), 0));
+		let finalized = self.client.info().finalized_number;
+		let to = to.map_or(finalized, |to| to.min(finalized));
+
+		let (events, next) = self.index
+			.events_by_topic(&topic, from, to, limit as usize)
+			.map_err(|e| Error::Client(Box::new(e)))?;
+
+		Ok(EventsPage {
+			events: events.into_iter().map(|event| TopicEvent {
+				block_number: event.block_number,
+				block_hash: event.block_hash,
+				event_index: event.event_index,
+			}).collect(),
+			next: next.map(|(block_number, event_index)| EventPosition { block_number, event_index }),
+		})
+	}
+}
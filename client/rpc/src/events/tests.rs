@@ -0,0 +1,93 @@
+// This file is part of Substrate.
+
+// Copyright (C) 2020 Parity Technologies (UK) Ltd.
+// SPDX-License-Identifier: GPL-3.0-or-later WITH Classpath-exception-2.0
+
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with this program. If not, see <https://www.gnu.org/licenses/>.
+
+use super::*;
+use assert_matches::assert_matches;
+use parking_lot::Mutex;
+use sc_client_api::backend::IndexedEvent;
+use substrate_test_runtime_client::runtime::{Block, H256};
+
+/// Index returning a fixed set of events and recording the queries it receives.
+#[derive(Default)]
+struct TestIndex {
+	queries: Mutex<Vec<((u64, u32), u64, usize)>>,
+}
+
+impl EventTopicsIndex<Block> for TestIndex {
+	fn events_by_topic(
+		&self,
+		_topic: &H256,
+		from: (u64, u32),
+		to: u64,
+		limit: usize,
+	) -> sp_blockchain::Result<(Vec<IndexedEvent<H256, u64>>, Option<(u64, u32)>)> {
+		self.queries.lock().push((from, to, limit));
+		let event = IndexedEvent {
+			block_number: 0,
+			block_hash: H256::from_low_u64_be(1),
+			event_index: 2,
+		};
+		Ok((vec![event], Some((0, 3))))
+	}
+}
+
+fn api(index: Arc<TestIndex>) -> Events<Block, substrate_test_runtime_client::TestClient> {
+	Events::new(Arc::new(substrate_test_runtime_client::new()), index)
+}
+
+#[test]
+fn should_return_events_page() {
+	let index = Arc::new(TestIndex::default());
+	let api = api(index.clone());
+
+	assert_eq!(
+		api.events_by_topic(Default::default(), None, None, None).unwrap(),
+		EventsPage {
+			events: vec![TopicEvent {
+				block_number: 0,
+				block_hash: H256::from_low_u64_be(1),
+				event_index: 2,
+			}],
+			next: Some(EventPosition { block_number: 0, event_index: 3 }),
+		},
+	);
+	assert_eq!(*index.queries.lock(), vec![((0, 0), 0, DEFAULT_LIMIT as usize)]);
+}
+
+#[test]
+fn should_not_query_beyond_finalized_block() {
+	let index = Arc::new(TestIndex::default());
+	let api = api(index.clone());
+
+	let from = EventPosition { block_number: 0, event_index: 3 };
+	api.events_by_topic(Default::default(), Some(from), Some(10), Some(5)).unwrap();
+
+	assert_eq!(*index.queries.lock(), vec![((0, 3), 0, 5)]);
+}
+
+#[test]
+fn should_reject_too_large_limit() {
+	let index = Arc::new(TestIndex::default());
+	let api = api(index.clone());
+
+	assert_matches!(
+		api.events_by_topic(Default::default(), None, None, Some(MAX_LIMIT + 1)),
+		Err(Error::LimitTooLarge { requested, max: MAX_LIMIT }) if requested == MAX_LIMIT + 1
+	);
+	assert!(index.queries.lock().is_empty());
+}
@@ -92,6 +92,15 @@ fn api<T: Into<Option<Status>>>(sync: T) -> System<Block> {
 						peerset: serde_json::Value::Null,
 					}).unwrap());
 				},
+				Request::PeerStore(sender) => {
+					let _ = sender.send(vec![StoredPeer {
+						peer_id: status.peer_id.to_base58(),
+						addresses: vec!["/ip4/198.51.100.19/tcp/30333".to_string()],
+						last_seen: 1_600_000_000,
+						reputation: -10,
+						banned_until: None,
+					}]);
+				},
 				Request::NetworkAddReservedPeer(peer, sender) => {
 					let _ = match sc_network::config::parse_str_addr(&peer) {
 						Ok(_) => sender.send(Ok(())),
@@ -104,6 +113,31 @@ fn api<T: Into<Option<Status>>>(sync: T) -> System<Block> {
 						Err(s) => sender.send(Err(error::Error::MalformattedPeerArg(s.to_string()))),
 					};
 				}
+				Request::NetworkBanPeer(peer, _, sender) | Request::NetworkUnbanPeer(peer, sender) => {
+					let _ = match peer.parse::<PeerId>() {
+						Ok(_) => sender.send(Ok(())),
+						Err(s) => sender.send(Err(error::Error::MalformattedPeerArg(s.to_string()))),
+					};
+				}
+				Request::BannedPeers(sender) => {
+					let _ = sender.send(vec![BannedPeer {
+						peer_id: status.peer_id.to_base58(),
+						expires_in: Some(60),
+					}]);
+				}
+				Request::NetworkBanIpRange(ip_range, _, sender) |
+				Request::NetworkUnbanIpRange(ip_range, sender) => {
+					let _ = match ip_range.parse::<sc_network::config::IpNetwork>() {
+						Ok(_) => sender.send(Ok(())),
+						Err(s) => sender.send(Err(error::Error::MalformattedIpRangeArg(s.to_string()))),
+					};
+				}
+				Request::BannedIpRanges(sender) => {
+					let _ = sender.send(vec![BannedIpRange {
+						ip_range: "192.0.2.0/24".to_string(),
+						expires_in: None,
+					}]);
+				}
 				Request::NodeRoles(sender) => {
 					let _ = sender.send(vec![NodeRole::Authority]);
 				}
@@ -112,6 +146,7 @@ fn api<T: Into<Option<Status>>>(sync: T) -> System<Block> {
 						starting_block: 1,
 						current_block: 2,
 						highest_block: Some(3),
+						block_gap: None,
 					});
 				}
 			};
@@ -293,6 +328,29 @@ fn system_network_state() {
 	);
 }
 
+#[test]
+fn system_peer_store() {
+	let mut runtime = tokio::runtime::current_thread::Runtime::new().unwrap();
+
+	let peer_id = PeerId::random();
+	let req = api(Status {
+		peer_id: peer_id.clone(),
+		..Default::default()
+	}).system_peer_store();
+	let res = runtime.block_on(req).unwrap();
+
+	assert_eq!(
+		res,
+		vec![StoredPeer {
+			peer_id: peer_id.to_base58(),
+			addresses: vec!["/ip4/198.51.100.19/tcp/30333".to_string()],
+			last_seen: 1_600_000_000,
+			reputation: -10,
+			banned_until: None,
+		}]
+	);
+}
+
 #[test]
 fn system_node_roles() {
 	assert_eq!(
@@ -309,6 +367,7 @@ fn system_sync_state() {
 			starting_block: 1,
 			current_block: 2,
 			highest_block: Some(3),
+			block_gap: None,
 		}
 	);
 }
@@ -337,6 +396,78 @@ fn system_network_remove_reserved() {
 	assert!(runtime.block_on(bad_fut).is_err());
 }
 
+#[test]
+fn system_network_ban_peer() {
+	let good_peer_id = "QmSk5HQbn6LhUwDiNMseVUjuRYhEtYj4aUZ6WfWoGURpdV";
+	let bad_peer_id = "/ip4/198.51.100.19/tcp/30333/p2p/QmSk5HQbn6LhUwDiNMseVUjuRYhEtYj4aUZ6WfWoGURpdV";
+	let mut runtime = tokio::runtime::current_thread::Runtime::new().unwrap();
+
+	let good_fut = api(None).system_ban_peer(good_peer_id.into(), Some(60));
+	let permanent_fut = api(None).system_ban_peer(good_peer_id.into(), None);
+	let bad_fut = api(None).system_ban_peer(bad_peer_id.into(), None);
+	assert_eq!(runtime.block_on(good_fut), Ok(()));
+	assert_eq!(runtime.block_on(permanent_fut), Ok(()));
+	assert!(runtime.block_on(bad_fut).is_err());
+
+	let good_fut = api(None).system_unban_peer(good_peer_id.into());
+	let bad_fut = api(None).system_unban_peer(bad_peer_id.into());
+	assert_eq!(runtime.block_on(good_fut), Ok(()));
+	assert!(runtime.block_on(bad_fut).is_err());
+}
+
+#[test]
+fn system_banned_peers() {
+	let mut runtime = tokio::runtime::current_thread::Runtime::new().unwrap();
+
+	let peer_id = PeerId::random();
+	let req = api(Status {
+		peer_id: peer_id.clone(),
+		..Default::default()
+	}).system_banned_peers();
+	let res = runtime.block_on(req).unwrap();
+
+	assert_eq!(
+		res,
+		vec![BannedPeer {
+			peer_id: peer_id.to_base58(),
+			expires_in: Some(60),
+		}]
+	);
+}
+
+#[test]
+fn system_network_ban_ip_range() {
+	let good_ip_range = "192.0.2.0/24";
+	let bad_ip_range = "192.0.2.0";
+	let mut runtime = tokio::runtime::current_thread::Runtime::new().unwrap();
+
+	let good_fut = api(None).system_ban_ip_range(good_ip_range.into(), Some(60));
+	let permanent_fut = api(None).system_ban_ip_range("2001:db8::/32".into(), None);
+	let bad_fut = api(None).system_ban_ip_range(bad_ip_range.into(), None);
+	assert_eq!(runtime.block_on(good_fut), Ok(()));
+	assert_eq!(runtime.block_on(permanent_fut), Ok(()));
+	assert!(runtime.block_on(bad_fut).is_err());
+
+	let good_fut = api(None).system_unban_ip_range(good_ip_range.into());
+	let bad_fut = api(None).system_unban_ip_range(bad_ip_range.into());
+	assert_eq!(runtime.block_on(good_fut), Ok(()));
+	assert!(runtime.block_on(bad_fut).is_err());
+}
+
+#[test]
+fn system_banned_ip_ranges() {
+	let mut runtime = tokio::runtime::current_thread::Runtime::new().unwrap();
+
+	let res = runtime.block_on(api(None).system_banned_ip_ranges()).unwrap();
+	assert_eq!(
+		res,
+		vec![BannedIpRange {
+			ip_range: "192.0.2.0/24".to_string(),
+			expires_in: None,
+		}]
+	);
+}
+
 #[test]
 fn test_add_reset_log_filter() {
 	const EXPECTED_BEFORE_ADD: &'static str = "EXPECTED_BEFORE_ADD";
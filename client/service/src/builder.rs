@@ -43,6 +43,7 @@ use sc_keystore::LocalKeystore;
 use log::{info, warn};
 use sc_network::config::{Role, OnDemand};
 use sc_network::NetworkService;
+use sc_network::warp_request_handler::WarpSyncProvider;
 use sp_runtime::generic::BlockId;
 use sp_runtime::traits::{
 	Block as BlockT, SaturatedConversion, HashFor, Zero, BlockIdTo,
@@ -301,6 +302,7 @@ pub fn new_full_parts<TBl, TRtApi, TExecDisp>(
 			config.state_cache_child_ratio.map(|v| (v, 100)),
 			pruning: config.pruning.clone(),
 			source: config.database.clone(),
+			event_topics_index: config.event_topics_index,
 		};
 
 		let extensions = sc_client_api::execution_extensions::ExecutionExtensions::new(
@@ -359,6 +361,7 @@ pub fn new_light_parts<TBl, TRtApi, TExecDisp>(
 				config.state_cache_child_ratio.map(|v| (v, 100)),
 			pruning: config.pruning.clone(),
 			source: config.database.clone(),
+			event_topics_index: false,
 		};
 		sc_client_db::light::LightStorage::new(db_settings)?
 	};
@@ -601,7 +604,7 @@ pub fn spawn_tasks<TBl, TBackend, TExPool, TRpc, TCl>(
 		deny_unsafe, rpc_middleware, &config, task_manager.spawn_handle(),
 		client.clone(), transaction_pool.clone(), keystore.clone(),
 		on_demand.clone(), remote_blockchain.clone(), &*rpc_extensions_builder,
-		backend.offchain_storage(), system_rpc_tx.clone()
+		backend.offchain_storage(), backend.event_topics_index(), system_rpc_tx.clone()
 	);
 	let rpc_metrics = sc_rpc_server::RpcMetrics::new(config.prometheus_registry())?;
 	let rpc = start_rpc_servers(&config, gen_handler, rpc_metrics.clone())?;
@@ -727,6 +730,7 @@ fn gen_handler<TBl, TBackend, TExPool, TRpc, TCl>(
 	remote_blockchain: Option<Arc<dyn RemoteBlockchain<TBl>>>,
 	rpc_extensions_builder: &(dyn RpcExtensionBuilder<Output = TRpc> + Send),
 	offchain_storage: Option<<TBackend as sc_client_api::backend::Backend<TBl>>::OffchainStorage>,
+	event_topics_index: Option<Arc<dyn sc_client_api::backend::EventTopicsIndex<TBl>>>,
 	system_rpc_tx: TracingUnboundedSender<sc_rpc::system::Request<TBl>>
 ) -> sc_rpc_server::RpcHandler<sc_rpc::Metadata>
 	where
@@ -742,7 +746,7 @@ fn gen_handler<TBl, TBackend, TExPool, TRpc, TCl>(
 			sp_session::SessionKeys<TBl> +
 			sp_api::Metadata<TBl, Error = sp_blockchain::Error>,
 {
-	use sc_rpc::{chain, state, author, system, offchain};
+	use sc_rpc::{chain, state, author, system, offchain, events};
 
 	let system_info = sc_rpc::system::SystemInfo {
 		chain_name: config.chain_spec.name().into(),
@@ -784,6 +788,11 @@ fn gen_handler<TBl, TBackend, TExPool, TRpc, TCl>(
 		(chain, state, child_state)
 	};
 
+	let maybe_events_rpc = event_topics_index.map(|index| {
+		let events = sc_rpc::events::Events::new(client.clone(), index);
+		events::EventsApi::to_delegate(events)
+	});
+
 	let author = sc_rpc::author::Author::new(
 		client,
 		transaction_pool,
@@ -804,6 +813,7 @@ fn gen_handler<TBl, TBackend, TExPool, TRpc, TCl>(
 			state::ChildStateApi::to_delegate(child_state),
 			chain::ChainApi::to_delegate(chain),
 			maybe_offchain_rpc,
+			maybe_events_rpc,
 			author::AuthorApi::to_delegate(author),
 			system::SystemApi::to_delegate(system),
 			rpc_extensions_builder.build(deny_unsafe, task_executor),
@@ -830,6 +840,8 @@ pub struct BuildNetworkParams<'a, TBl: BlockT, TExPool, TImpQu, TCl> {
 	pub block_announce_validator_builder: Option<Box<
 		dyn FnOnce(Arc<TCl>) -> Box<dyn BlockAnnounceValidator<TBl> + Send> + Send
 	>>,
+	/// An optional warp sync provider, used to serve and verify warp proofs.
+	pub warp_sync: Option<Arc<dyn WarpSyncProvider<TBl>>>,
 }
 
 /// Build the network service, the network status sinks and an RPC sender.
@@ -854,7 +866,7 @@ pub fn build_network<TBl, TExPool, TImpQu, TCl>(
 {
 	let BuildNetworkParams {
 		config, client, transaction_pool, spawn_handle, import_queue, on_demand,
-		block_announce_validator_builder,
+		block_announce_validator_builder, warp_sync,
 	} = params;
 
 	let transaction_pool_adapter = Arc::new(TransactionPoolAdapter {
@@ -897,7 +909,8 @@ pub fn build_network<TBl, TExPool, TImpQu, TCl>(
 		import_queue: Box::new(import_queue),
 		protocol_id,
 		block_announce_validator,
-		metrics_registry: config.prometheus_config.as_ref().map(|config| config.registry.clone())
+		metrics_registry: config.prometheus_config.as_ref().map(|config| config.registry.clone()),
+		warp_sync,
 	};
 
 	let has_bootnodes = !network_params.network_config.boot_nodes.is_empty();
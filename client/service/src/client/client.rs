@@ -37,7 +37,7 @@ use sp_core::{
 use sp_keystore::SyncCryptoStorePtr;
 use sc_telemetry::{telemetry, SUBSTRATE_INFO};
 use sp_runtime::{
-	Justification, BuildStorage,
+	Justification, Justifications, BuildStorage,
 	generic::{BlockId, SignedBlock, DigestItem},
 	traits::{
 		Block as BlockT, Header as HeaderT, Zero, NumberFor,
@@ -48,11 +48,12 @@ use sp_state_machine::{
 	DBValue, Backend as StateBackend, ChangesTrieAnchorBlockId,
 	prove_read, prove_child_read, ChangesTrieRootsStorage, ChangesTrieStorage,
 	ChangesTrieConfigurationRange, key_changes, key_changes_proof,
+	prove_range_read_with_child_with_size, read_range_proof_check_with_child, ProvenStateRange,
 };
 use sc_executor::RuntimeVersion;
 use sp_consensus::{
 	Error as ConsensusError, BlockStatus, BlockImportParams, BlockCheckParams,
-	ImportResult, BlockOrigin, ForkChoiceStrategy, RecordProof,
+	ImportResult, BlockOrigin, ForkChoiceStrategy, RecordProof, ImportedState,
 };
 use sp_blockchain::{
 	self as blockchain,
@@ -77,7 +78,7 @@ use sc_client_api::{
 	client::{
 		ImportNotifications, FinalityNotification, FinalityNotifications, BlockImportNotification,
 		ClientInfo, BlockchainEvents, BlockBackend, ProvideUncles, BadBlocks, ForkBlocks,
-		BlockOf,
+		BlockOf, BlockHistoryImport, HistoryBlock,
 	},
 	execution_extensions::ExecutionExtensions,
 	notifications::{StorageNotifications, StorageEventStream},
@@ -595,7 +596,7 @@ impl<B, E, Block, RA> Client<B, E, Block, RA> where
 		Ok((storage, configs))
 	}
 
-	/// Apply a checked and validated block to an operation. If a justification is provided
+	/// Apply a checked and validated block to an operation. If justifications are provided
 	/// then `finalized` *must* be true.
 	fn apply_block(
 		&self,
@@ -610,7 +611,7 @@ impl<B, E, Block, RA> Client<B, E, Block, RA> where
 		let BlockImportParams {
 			origin,
 			header,
-			justification,
+			justifications,
 			post_digests,
 			body,
 			storage_changes,
@@ -619,10 +620,11 @@ impl<B, E, Block, RA> Client<B, E, Block, RA> where
 			fork_choice,
 			intermediates,
 			import_existing,
+			importing_state,
 			..
 		} = import_block;
 
-		assert!(justification.is_some() && finalized || justification.is_none());
+		assert!(justifications.is_some() && finalized || justifications.is_none());
 
 		if !intermediates.is_empty() {
 			return Err(Error::IncompletePipeline)
@@ -650,7 +652,7 @@ impl<B, E, Block, RA> Client<B, E, Block, RA> where
 			origin,
 			hash,
 			import_headers,
-			justification,
+			justifications,
 			body,
 			storage_changes,
 			new_cache,
@@ -658,6 +660,7 @@ impl<B, E, Block, RA> Client<B, E, Block, RA> where
 			auxiliary,
 			fork_choice,
 			import_existing,
+			importing_state,
 		);
 
 		if let Ok(ImportResult::Imported(ref aux)) = result {
@@ -686,7 +689,7 @@ impl<B, E, Block, RA> Client<B, E, Block, RA> where
 		origin: BlockOrigin,
 		hash: Block::Hash,
 		import_headers: PrePostHeader<Block::Header>,
-		justification: Option<Justification>,
+		justifications: Option<Justifications>,
 		body: Option<Vec<Block::Extrinsic>>,
 		storage_changes: Option<sp_api::StorageChanges<backend::StateBackendFor<B, Block>, Block>>,
 		new_cache: HashMap<CacheKeyId, Vec<u8>>,
@@ -694,6 +697,7 @@ impl<B, E, Block, RA> Client<B, E, Block, RA> where
 		aux: Vec<(Vec<u8>, Option<Vec<u8>>)>,
 		fork_choice: ForkChoiceStrategy,
 		import_existing: bool,
+		importing_state: Option<ImportedState<Block>>,
 	) -> sp_blockchain::Result<ImportResult> where
 		Self: ProvideRuntimeApi<Block>,
 		<Self as ProvideRuntimeApi<Block>>::Api: CoreApi<Block, Error = Error> +
@@ -712,9 +716,15 @@ impl<B, E, Block, RA> Client<B, E, Block, RA> where
 		let info = self.backend.blockchain().info();
 
 		// the block is lower than our last finalized block so it must revert
-		// finality, refusing import.
-		if *import_headers.post().number() <= info.finalized_number {
-			return Err(sp_blockchain::Error::NotInFinalizedChain);
+		// finality, refusing import. An existing block may be re-imported
+		// to add its body or state, but only if it is part of the finalized chain.
+		let number = *import_headers.post().number();
+		if number <= info.finalized_number {
+			let is_canonical = import_existing &&
+				self.backend.blockchain().hash(number)? == Some(hash);
+			if !is_canonical {
+				return Err(sp_blockchain::Error::NotInFinalizedChain);
+			}
 		}
 
 		// this is a fairly arbitrary choice of where to draw the line on making notifications,
@@ -764,7 +774,16 @@ impl<B, E, Block, RA> Client<B, E, Block, RA> where
 
 				Some((main_sc, child_sc))
 			},
-			None => None,
+			None => {
+				if let Some(ImportedState { state, .. }) = importing_state {
+					let state_root = operation.op.reset_storage(state)?;
+					if state_root != *import_headers.post().state_root() {
+						warn!("Imported state with invalid root for block {:?}", hash);
+						return Err(Error::InvalidStateRoot);
+					}
+				}
+				None
+			},
 		};
 
 		let is_new_best = finalized || match fork_choice {
@@ -780,7 +799,10 @@ impl<B, E, Block, RA> Client<B, E, Block, RA> where
 			NewBlockState::Normal
 		};
 
-		let tree_route = if is_new_best && info.best_hash != parent_hash {
+		// a block imported with its state may have no known ancestors to route through.
+		let parent_known = self.backend.blockchain().status(BlockId::Hash(parent_hash))?
+			== blockchain::BlockStatus::InChain;
+		let tree_route = if is_new_best && info.best_hash != parent_hash && parent_known {
 			let route_from_best = sp_blockchain::tree_route(
 				self.backend.blockchain(),
 				info.best_hash,
@@ -802,7 +824,7 @@ impl<B, E, Block, RA> Client<B, E, Block, RA> where
 		operation.op.set_block_data(
 			import_headers.post().clone(),
 			body,
-			justification,
+			justifications,
 			leaf_state,
 		)?;
 
@@ -844,9 +866,12 @@ impl<B, E, Block, RA> Client<B, E, Block, RA> where
 		let parent_hash = import_block.header.parent_hash();
 		let at = BlockId::Hash(*parent_hash);
 		let enact_state = match self.block_status(&at)? {
+			// a block imported with its state doesn't need its ancestors.
+			BlockStatus::Unknown if import_block.importing_state.is_some() => false,
 			BlockStatus::Unknown => return Ok(Some(ImportResult::UnknownParent)),
 			BlockStatus::InChainWithState | BlockStatus::Queued => true,
-			BlockStatus::InChainPruned if import_block.allow_missing_state => false,
+			BlockStatus::InChainPruned
+				if import_block.allow_missing_state || import_block.importing_state.is_some() => false,
 			BlockStatus::InChainPruned => return Ok(Some(ImportResult::MissingState)),
 			BlockStatus::KnownBad => return Ok(Some(ImportResult::KnownBad)),
 		};
@@ -1262,6 +1287,27 @@ impl<B, E, Block, RA> ProofProvider<Block> for Client<B, E, Block, RA> where
 			cht::size(),
 		)
 	}
+
+	fn read_proof_collection(
+		&self,
+		id: &BlockId<Block>,
+		start_at: &[Vec<u8>],
+		size_limit: usize,
+	) -> sp_blockchain::Result<(StorageProof, u32)> {
+		let state = self.state_at(id)?;
+		prove_range_read_with_child_with_size::<_, HashFor<Block>>(state, size_limit, start_at)
+			.map_err(Into::into)
+	}
+
+	fn verify_range_proof(
+		&self,
+		root: Block::Hash,
+		proof: StorageProof,
+		start_at: &[Vec<u8>],
+	) -> sp_blockchain::Result<ProvenStateRange> {
+		read_range_proof_check_with_child::<HashFor<Block>>(root, proof, start_at)
+			.map_err(Into::into)
+	}
 }
 
 
@@ -1720,7 +1766,14 @@ impl<B, E, Block, RA> sp_consensus::BlockImport<Block> for &Client<B, E, Block,
 		&mut self,
 		block: BlockCheckParams<Block>,
 	) -> Result<ImportResult, Self::Error> {
-		let BlockCheckParams { hash, number, parent_hash, allow_missing_state, import_existing } = block;
+		let BlockCheckParams {
+			hash,
+			number,
+			parent_hash,
+			allow_missing_state,
+			import_existing,
+			allow_missing_parent,
+		} = block;
 
 		// Check the block against white and black lists if any are defined
 		// (i.e. fork blocks and bad blocks respectively)
@@ -1752,7 +1805,8 @@ impl<B, E, Block, RA> sp_consensus::BlockImport<Block> for &Client<B, E, Block,
 		{
 			BlockStatus::InChainWithState | BlockStatus::Queued if !import_existing  => return Ok(ImportResult::AlreadyInChain),
 			BlockStatus::InChainWithState | BlockStatus::Queued => {},
-			BlockStatus::InChainPruned => return Ok(ImportResult::AlreadyInChain),
+			BlockStatus::InChainPruned if !import_existing => return Ok(ImportResult::AlreadyInChain),
+			BlockStatus::InChainPruned => {},
 			BlockStatus::Unknown => {},
 			BlockStatus::KnownBad => return Ok(ImportResult::KnownBad),
 		}
@@ -1761,6 +1815,7 @@ impl<B, E, Block, RA> sp_consensus::BlockImport<Block> for &Client<B, E, Block,
 			.map_err(|e| ConsensusError::ClientImport(e.to_string()))?
 			{
 				BlockStatus::InChainWithState | BlockStatus::Queued => {},
+				BlockStatus::Unknown if allow_missing_parent => {},
 				BlockStatus::Unknown => return Ok(ImportResult::UnknownParent),
 				BlockStatus::InChainPruned if allow_missing_state => {},
 				BlockStatus::InChainPruned => return Ok(ImportResult::MissingState),
@@ -1903,9 +1958,9 @@ impl<B, E, Block, RA> BlockBackend<Block> for Client<B, E, Block, RA>
 
 	fn block(&self, id: &BlockId<Block>) -> sp_blockchain::Result<Option<SignedBlock<Block>>>
 	{
-		Ok(match (self.header(id)?, self.body(id)?, self.justification(id)?) {
-			(Some(header), Some(extrinsics), justification) =>
-				Some(SignedBlock { block: Block::new(header, extrinsics), justification }),
+		Ok(match (self.header(id)?, self.body(id)?, self.justifications(id)?) {
+			(Some(header), Some(extrinsics), justifications) =>
+				Some(SignedBlock { block: Block::new(header, extrinsics), justifications }),
 			_ => None,
 		})
 	}
@@ -1933,8 +1988,8 @@ impl<B, E, Block, RA> BlockBackend<Block> for Client<B, E, Block, RA>
 		}
 	}
 
-	fn justification(&self, id: &BlockId<Block>) -> sp_blockchain::Result<Option<Justification>> {
-		self.backend.blockchain().justification(*id)
+	fn justifications(&self, id: &BlockId<Block>) -> sp_blockchain::Result<Option<Justifications>> {
+		self.backend.blockchain().justifications(*id)
 	}
 
 	fn block_hash(&self, number: NumberFor<Block>) -> sp_blockchain::Result<Option<Block::Hash>> {
@@ -1942,6 +1997,29 @@ impl<B, E, Block, RA> BlockBackend<Block> for Client<B, E, Block, RA>
 	}
 }
 
+impl<B, E, Block, RA> BlockHistoryImport<Block> for Client<B, E, Block, RA>
+	where
+		B: backend::Backend<Block>,
+		E: CallExecutor<Block>,
+		Block: BlockT,
+{
+	fn import_block_history(&self, blocks: Vec<HistoryBlock<Block>>) -> sp_blockchain::Result<()> {
+		for block in blocks {
+			trace!("Importing history block #{} ({})", block.header.number(), block.header.hash());
+			// history blocks are stored as they are, without execution nor notifications.
+			self.lock_import_and_run(|operation| {
+				operation.op.set_block_data(
+					block.header,
+					block.body,
+					block.justifications,
+					NewBlockState::Normal,
+				)
+			})?;
+		}
+		Ok(())
+	}
+}
+
 impl<B, E, Block, RA> backend::AuxStore for Client<B, E, Block, RA>
 	where
 		B: backend::Backend<Block>,
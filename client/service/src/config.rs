@@ -58,6 +58,8 @@ pub struct Configuration {
 	pub state_cache_child_ratio: Option<usize>,
 	/// Pruning settings.
 	pub pruning: PruningMode,
+	/// Maintain an index from event topics to the events of finalized blocks.
+	pub event_topics_index: bool,
 	/// Chain configuration.
 	pub chain_spec: Box<dyn ChainSpec>,
 	/// Wasm execution method.
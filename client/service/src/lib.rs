@@ -299,6 +299,17 @@ async fn build_network_future<
 							let _ = sender.send(network_state);
 						}
 					}
+					sc_rpc::system::Request::PeerStore(sender) => {
+						let _ = sender.send(network.peer_store().into_iter().map(|(peer_id, p)|
+							sc_rpc::system::StoredPeer {
+								peer_id: peer_id.to_base58(),
+								addresses: p.addresses.iter().map(|addr| addr.to_string()).collect(),
+								last_seen: p.last_seen,
+								reputation: p.reputation,
+								banned_until: p.banned_until,
+							}
+						).collect());
+					}
 					sc_rpc::system::Request::NetworkAddReservedPeer(peer_addr, sender) => {
 						let x = network.add_reserved_peer(peer_addr)
 							.map_err(sc_rpc::system::error::Error::MalformattedPeerArg);
@@ -315,6 +326,66 @@ async fn build_network_future<
 							))),
 						};
 					}
+					sc_rpc::system::Request::NetworkBanPeer(peer_id, duration, sender) => {
+						let _ = match peer_id.parse::<PeerId>() {
+							Ok(peer_id) => {
+								network.ban_peer(peer_id, duration.map(Duration::from_secs));
+								sender.send(Ok(()))
+							}
+							Err(e) => sender.send(Err(sc_rpc::system::error::Error::MalformattedPeerArg(
+								e.to_string(),
+							))),
+						};
+					}
+					sc_rpc::system::Request::NetworkUnbanPeer(peer_id, sender) => {
+						let _ = match peer_id.parse::<PeerId>() {
+							Ok(peer_id) => {
+								network.unban_peer(peer_id);
+								sender.send(Ok(()))
+							}
+							Err(e) => sender.send(Err(sc_rpc::system::error::Error::MalformattedPeerArg(
+								e.to_string(),
+							))),
+						};
+					}
+					sc_rpc::system::Request::BannedPeers(sender) => {
+						let _ = sender.send(network.banned_peers().into_iter().map(|(peer_id, remaining)|
+							sc_rpc::system::BannedPeer {
+								peer_id: peer_id.to_base58(),
+								expires_in: remaining.map(|remaining| remaining.as_secs()),
+							}
+						).collect());
+					}
+					sc_rpc::system::Request::NetworkBanIpRange(ip_range, duration, sender) => {
+						let _ = match ip_range.parse::<sc_network::config::IpNetwork>() {
+							Ok(ip_range) => {
+								network.ban_ip_range(ip_range, duration.map(Duration::from_secs));
+								sender.send(Ok(()))
+							}
+							Err(e) => sender.send(Err(sc_rpc::system::error::Error::MalformattedIpRangeArg(
+								e.to_string(),
+							))),
+						};
+					}
+					sc_rpc::system::Request::NetworkUnbanIpRange(ip_range, sender) => {
+						let _ = match ip_range.parse::<sc_network::config::IpNetwork>() {
+							Ok(ip_range) => {
+								network.unban_ip_range(&ip_range);
+								sender.send(Ok(()))
+							}
+							Err(e) => sender.send(Err(sc_rpc::system::error::Error::MalformattedIpRangeArg(
+								e.to_string(),
+							))),
+						};
+					}
+					sc_rpc::system::Request::BannedIpRanges(sender) => {
+						let _ = sender.send(network.banned_ip_ranges().into_iter().map(|(ip_range, remaining)|
+							sc_rpc::system::BannedIpRange {
+								ip_range: ip_range.to_string(),
+								expires_in: remaining.map(|remaining| remaining.as_secs()),
+							}
+						).collect());
+					}
 					sc_rpc::system::Request::NodeRoles(sender) => {
 						use sc_rpc::system::NodeRole;
 
@@ -330,10 +401,12 @@ async fn build_network_future<
 					sc_rpc::system::Request::SyncState(sender) => {
 						use sc_rpc::system::SyncState;
 
+						let info = client.info();
 						let _ = sender.send(SyncState {
 							starting_block: starting_block,
-							current_block: client.info().best_number,
+							current_block: info.best_number,
 							highest_block: network.best_seen_block(),
+							block_gap: info.block_gap,
 						});
 					}
 				}
@@ -49,12 +49,14 @@ use sp_consensus::{
 };
 use sp_storage::StorageKey;
 use sp_trie::{TrieConfiguration, trie_types::Layout};
-use sp_runtime::{generic::BlockId, DigestItem};
+use sp_runtime::{generic::BlockId, DigestItem, Justifications};
 use hex_literal::hex;
 
 mod light;
 mod db;
 
+const TEST_ENGINE_ID: sp_runtime::ConsensusEngineId = *b"TEST";
+
 native_executor_instance!(
 	Executor,
 	substrate_test_runtime_client::runtime::api::dispatch,
@@ -1021,7 +1023,7 @@ fn import_with_justification() {
 	client.import(BlockOrigin::Own, a2.clone()).unwrap();
 
 	// A2 -> A3
-	let justification = vec![1, 2, 3];
+	let justification = Justifications::from((TEST_ENGINE_ID, vec![1, 2, 3]));
 	let a3 = client.new_block_at(
 		&BlockId::Hash(a2.hash()),
 		Default::default(),
@@ -1035,17 +1037,17 @@ fn import_with_justification() {
 	);
 
 	assert_eq!(
-		client.justification(&BlockId::Hash(a3.hash())).unwrap(),
+		client.justifications(&BlockId::Hash(a3.hash())).unwrap(),
 		Some(justification),
 	);
 
 	assert_eq!(
-		client.justification(&BlockId::Hash(a1.hash())).unwrap(),
+		client.justifications(&BlockId::Hash(a1.hash())).unwrap(),
 		None,
 	);
 
 	assert_eq!(
-		client.justification(&BlockId::Hash(a2.hash())).unwrap(),
+		client.justifications(&BlockId::Hash(a2.hash())).unwrap(),
 		None,
 	);
 }
@@ -1093,7 +1095,7 @@ fn importing_diverged_finalized_block_should_trigger_reorg() {
 	);
 
 	// importing B1 as finalized should trigger a re-org and set it as new best
-	let justification = vec![1, 2, 3];
+	let justification = Justifications::from((TEST_ENGINE_ID, vec![1, 2, 3]));
 	client.import_justified(BlockOrigin::Own, b1.clone(), justification).unwrap();
 
 	assert_eq!(
@@ -1280,6 +1282,7 @@ fn doesnt_import_blocks_that_revert_finality() {
 				path: tmp.path().into(),
 				cache_size: 1024,
 			},
+			event_topics_index: false,
 		},
 		u64::max_value(),
 	).unwrap());
@@ -1365,6 +1368,54 @@ fn doesnt_import_blocks_that_revert_finality() {
 	);
 }
 
+#[test]
+fn doesnt_reimport_stale_fork_blocks_below_finality() {
+	sp_tracing::try_init_simple();
+	let mut client = TestClientBuilder::new().build();
+
+	// G -> A1 -> A2
+	//   \
+	//    -> B1
+	let a1 = client.new_block_at(&BlockId::Number(0), Default::default(), false)
+		.unwrap().build().unwrap().block;
+	client.import(BlockOrigin::Own, a1.clone()).unwrap();
+
+	let a2 = client.new_block_at(&BlockId::Hash(a1.hash()), Default::default(), false)
+		.unwrap().build().unwrap().block;
+	client.import(BlockOrigin::Own, a2.clone()).unwrap();
+
+	let mut b1 = client.new_block_at(&BlockId::Number(0), Default::default(), false).unwrap();
+	// needed to make sure B1 gets a different hash from A1
+	b1.push_transfer(Transfer {
+		from: AccountKeyring::Alice.into(),
+		to: AccountKeyring::Ferdie.into(),
+		amount: 1,
+		nonce: 0,
+	}).unwrap();
+	let b1 = b1.build().unwrap().block;
+	client.import(BlockOrigin::Own, b1.clone()).unwrap();
+
+	ClientExt::finalize_block(&client, BlockId::Hash(a2.hash()), None).unwrap();
+
+	// B1 is already known, but re-importing it would attach data to a block that isn't part of
+	// the finalized chain.
+	let (header, extrinsics) = b1.deconstruct();
+	let mut import = BlockImportParams::new(BlockOrigin::File, header);
+	import.body = Some(extrinsics);
+	import.fork_choice = Some(ForkChoiceStrategy::LongestChain);
+	import.import_existing = true;
+
+	let import_err = client.import_block(import, Default::default()).err().unwrap();
+	let expected_err = ConsensusError::ClientImport(
+		sp_blockchain::Error::NotInFinalizedChain.to_string()
+	);
+
+	assert_eq!(
+		import_err.to_string(),
+		expected_err.to_string(),
+	);
+}
+
 
 #[test]
 fn respects_block_rules() {
@@ -1393,6 +1444,7 @@ fn respects_block_rules() {
 			parent_hash: block_ok.header().parent_hash().clone(),
 			allow_missing_state: false,
 			import_existing: false,
+			allow_missing_parent: false,
 		};
 		assert_eq!(client.check_block(params).unwrap(), ImportResult::imported(false));
 
@@ -1408,6 +1460,7 @@ fn respects_block_rules() {
 			parent_hash: block_not_ok.header().parent_hash().clone(),
 			allow_missing_state: false,
 			import_existing: false,
+			allow_missing_parent: false,
 		};
 		if record_only {
 			known_bad.insert(block_not_ok.hash());
@@ -1430,6 +1483,7 @@ fn respects_block_rules() {
 			parent_hash: block_ok.header().parent_hash().clone(),
 			allow_missing_state: false,
 			import_existing: false,
+			allow_missing_parent: false,
 		};
 		if record_only {
 			fork_rules.push((1, block_ok.hash().clone()));
@@ -1448,6 +1502,7 @@ fn respects_block_rules() {
 			parent_hash: block_not_ok.header().parent_hash().clone(),
 			allow_missing_state: false,
 			import_existing: false,
+			allow_missing_parent: false,
 		};
 
 		if !record_only {
@@ -1481,6 +1536,7 @@ fn returns_status_for_pruned_blocks() {
 				path: tmp.path().into(),
 				cache_size: 1024,
 			},
+			event_topics_index: false,
 		},
 		u64::max_value(),
 	).unwrap());
@@ -1507,6 +1563,7 @@ fn returns_status_for_pruned_blocks() {
 		parent_hash: a1.header().parent_hash().clone(),
 		allow_missing_state: false,
 		import_existing: false,
+		allow_missing_parent: false,
 	};
 
 	assert_eq!(client.check_block(check_block_a1.clone()).unwrap(), ImportResult::imported(false));
@@ -1527,6 +1584,7 @@ fn returns_status_for_pruned_blocks() {
 		parent_hash: a1.header().parent_hash().clone(),
 		allow_missing_state: false,
 		import_existing: false,
+		allow_missing_parent: false,
 	};
 
 	assert_eq!(client.check_block(check_block_a1.clone()).unwrap(), ImportResult::AlreadyInChain);
@@ -1544,6 +1602,7 @@ fn returns_status_for_pruned_blocks() {
 		parent_hash: a2.header().parent_hash().clone(),
 		allow_missing_state: false,
 		import_existing: false,
+		allow_missing_parent: false,
 	};
 
 	// a1 and a2 are both pruned at this point
@@ -1560,6 +1619,7 @@ fn returns_status_for_pruned_blocks() {
 		parent_hash: b1.header().parent_hash().clone(),
 		allow_missing_state: false,
 		import_existing: false,
+		allow_missing_parent: false,
 	};
 	assert_eq!(client.check_block(check_block_b1.clone()).unwrap(), ImportResult::MissingState);
 	check_block_b1.allow_missing_state = true;
@@ -274,6 +274,26 @@ impl<BlockHash: Hash + MallocSizeOf, Key: Hash + MallocSizeOf> StateDbSync<Block
 		}
 	}
 
+	fn insert_block_with_state<E: fmt::Debug>(
+		&mut self,
+		hash: &BlockHash,
+		number: u64,
+		parent_hash: &BlockHash,
+		changeset: ChangeSet<Key>,
+	) -> Result<CommitSet<Key>, Error<E>> {
+		let mut commit = match self.mode {
+			PruningMode::ArchiveAll => CommitSet::default(),
+			PruningMode::Constrained(_) | PruningMode::ArchiveCanonical => {
+				self.non_canonical.set_canonical_parent(number, parent_hash)?
+			},
+		};
+		let insertion = self.insert_block(hash, number, parent_hash, changeset)?;
+		commit.data = insertion.data;
+		commit.meta.inserted.extend(insertion.meta.inserted);
+		commit.meta.deleted.extend(insertion.meta.deleted);
+		Ok(commit)
+	}
+
 	fn canonicalize_block<E: fmt::Debug>(
 		&mut self,
 		hash: &BlockHash,
@@ -463,6 +483,18 @@ impl<BlockHash: Hash + MallocSizeOf, Key: Hash + MallocSizeOf> StateDb<BlockHash
 		self.db.write().insert_block(hash, number, parent_hash, changeset)
 	}
 
+	/// Add a new non-canonical block with a complete state, whose ancestors do not have
+	/// their state in the database. Only possible when there are no other non-canonical blocks.
+	pub fn insert_block_with_state<E: fmt::Debug>(
+		&self,
+		hash: &BlockHash,
+		number: u64,
+		parent_hash: &BlockHash,
+		changeset: ChangeSet<Key>,
+	) -> Result<CommitSet<Key>, Error<E>> {
+		self.db.write().insert_block_with_state(hash, number, parent_hash, changeset)
+	}
+
 	/// Finalize a previously inserted block.
 	pub fn canonicalize_block<E: fmt::Debug>(
 		&self,
@@ -266,6 +266,21 @@ impl<BlockHash: Hash, Key: Hash> NonCanonicalOverlay<BlockHash, Key> {
 		Ok(commit)
 	}
 
+	/// Make the parent of a block at `number` the last canonicalized block, so that the block
+	/// can be inserted even though the state of its ancestors was never inserted. Used when
+	/// importing the state of a block downloaded from the network. Fails if there are any
+	/// non-canonical blocks.
+	pub fn set_canonical_parent<E: fmt::Debug>(&mut self, number: u64, parent_hash: &BlockHash) -> Result<CommitSet<Key>, Error<E>> {
+		if number == 0 || !self.levels.is_empty() || !self.pending_canonicalizations.is_empty() {
+			return Err(Error::InvalidBlockNumber);
+		}
+		let mut commit = CommitSet::default();
+		let last_canonicalized = (parent_hash.clone(), number - 1);
+		commit.meta.inserted.push((to_meta_key(LAST_CANONICAL, &()), last_canonicalized.encode()));
+		self.last_canonicalized = Some(last_canonicalized);
+		Ok(commit)
+	}
+
 	fn discard_journals(
 		&self,
 		level_index: usize,
@@ -583,6 +598,34 @@ mod tests {
 		overlay.canonicalize::<io::Error>(&h2, &mut commit).unwrap();
 	}
 
+	#[test]
+	fn insert_after_set_canonical_parent() {
+		let h1 = H256::random();
+		let h2 = H256::random();
+		let h3 = H256::random();
+		let mut db = make_db(&[1, 2]);
+		let mut overlay = NonCanonicalOverlay::<H256, H256>::new(&db).unwrap();
+		db.commit(&overlay.insert::<io::Error>(&h1, 1, &H256::default(), make_changeset(&[3], &[])).unwrap());
+		let mut commit = CommitSet::default();
+		overlay.canonicalize::<io::Error>(&h1, &mut commit).unwrap();
+		db.commit(&commit);
+		overlay.apply_pending();
+
+		// Block 10 can not be inserted on top of block 1.
+		assert!(overlay.insert::<io::Error>(&h3, 10, &h2, make_changeset(&[4], &[])).is_err());
+		db.commit(&overlay.set_canonical_parent::<io::Error>(10, &h2).unwrap());
+		db.commit(&overlay.insert::<io::Error>(&h3, 10, &h2, make_changeset(&[4], &[])).unwrap());
+		assert!(contains(&overlay, 4));
+		assert_eq!(overlay.last_canonicalized, Some((h2, 9)));
+
+		// Not possible while there are non-canonical blocks.
+		assert!(overlay.set_canonical_parent::<io::Error>(11, &h3).is_err());
+
+		let overlay2 = NonCanonicalOverlay::<H256, H256>::new(&db).unwrap();
+		assert_eq!(overlay.levels, overlay2.levels);
+		assert_eq!(overlay.last_canonicalized, overlay2.last_canonicalized);
+	}
+
 	#[test]
 	fn insert_canonicalize_one() {
 		let h1 = H256::random();
@@ -20,42 +20,155 @@
 
 use bitflags::bitflags;
 use codec::{Decode, Encode};
-use sp_runtime::{DispatchError, RuntimeDebug};
+use sp_runtime::{
+	DispatchError, RuntimeDebug,
+	traits::{Saturating, Zero},
+};
 use sp_std::prelude::*;
 
 /// Result type of a `bare_call` call.
 ///
-/// The result of a contract execution along with a gas consumed.
+/// The result of a contract execution along with information about its side effects. Contract
+/// events and debug messages are only collected when the call is executed as a dry run.
 #[derive(Eq, PartialEq, Encode, Decode, RuntimeDebug)]
-pub struct ContractExecResult {
+pub struct ContractExecResult<AccountId, Balance, Hash> {
 	pub exec_result: ExecResult,
+	/// How much gas was consumed.
 	pub gas_consumed: u64,
+	/// How much gas is needed for the execution to succeed.
+	///
+	/// This can be more than `gas_consumed` because gas that was refunded during the
+	/// execution still needs to be available up front.
+	pub gas_required: u64,
+	/// The storage deposit that was charged from or refunded to the origin.
+	pub storage_deposit: StorageDeposit<Balance>,
+	/// By how many bytes the storage of all involved contracts grew (or shrank if negative).
+	pub storage_bytes: i64,
+	/// The events deposited by contracts. Empty unless executed as a dry run.
+	pub events: Vec<ContractEvent<AccountId, Hash>>,
+	/// The messages contracts passed to `seal_debug_message`. Empty unless executed as a
+	/// dry run.
+	pub debug_message: Vec<u8>,
+}
+
+/// Result type of a `bare_instantiate` call.
+///
+/// The result of a contract instantiation along with information about its side effects. The
+/// storage deposit includes the deposit for uploading the code if it was not already on-chain.
+/// Contract events and debug messages are only collected when the instantiation is executed
+/// as a dry run.
+#[derive(Eq, PartialEq, Encode, Decode, RuntimeDebug)]
+pub struct ContractInstantiateResult<AccountId, Balance, Hash> {
+	pub result: Result<InstantiateReturnValue<AccountId>, ExecError>,
+	/// How much gas was consumed.
+	pub gas_consumed: u64,
+	/// How much gas is needed for the execution to succeed.
+	///
+	/// This can be more than `gas_consumed` because gas that was refunded during the
+	/// execution still needs to be available up front.
+	pub gas_required: u64,
+	/// The storage deposit that was charged from or refunded to the origin.
+	pub storage_deposit: StorageDeposit<Balance>,
+	/// By how many bytes the storage of all involved contracts grew (or shrank if negative).
+	pub storage_bytes: i64,
+	/// The events deposited by contracts. Empty unless executed as a dry run.
+	pub events: Vec<ContractEvent<AccountId, Hash>>,
+	/// The messages contracts passed to `seal_debug_message`. Empty unless executed as a
+	/// dry run.
+	pub debug_message: Vec<u8>,
 }
 
+/// An event that was deposited by a contract through `seal_deposit_event`.
+#[derive(Eq, PartialEq, Encode, Decode, RuntimeDebug, Clone)]
+pub struct ContractEvent<AccountId, Hash> {
+	/// The contract that deposited the event.
+	pub contract: AccountId,
+	/// The topics the event was deposited with.
+	pub topics: Vec<Hash>,
+	/// The data of the event as supplied by the contract.
+	pub data: Vec<u8>,
+}
+
+/// Result type of a `bare_upload_code` call.
+pub type CodeUploadResult<CodeHash, Balance> =
+	Result<CodeUploadReturnValue<CodeHash, Balance>, DispatchError>;
+
 /// Result type of a `get_storage` call.
 pub type GetStorageResult = Result<Option<Vec<u8>>, ContractAccessError>;
 
-/// Result type of a `rent_projection` call.
-pub type RentProjectionResult<BlockNumber> =
-	Result<RentProjection<BlockNumber>, ContractAccessError>;
-
 /// The possible errors that can happen querying the storage of a contract.
 #[derive(Eq, PartialEq, Encode, Decode, RuntimeDebug)]
 pub enum ContractAccessError {
 	/// The given address doesn't point to a contract.
 	DoesntExist,
-	/// The specified contract is a tombstone and thus cannot have any storage.
-	IsTombstone,
 }
 
-#[derive(Eq, PartialEq, Encode, Decode, RuntimeDebug)]
-pub enum RentProjection<BlockNumber> {
-	/// Eviction is projected to happen at the specified block number.
-	EvictionAt(BlockNumber),
-	/// No eviction is scheduled.
+/// The amount of balance that was either charged or refunded in order to pay for storage.
+#[derive(Eq, PartialEq, Ord, PartialOrd, Encode, Decode, RuntimeDebug, Clone, Copy)]
+pub enum StorageDeposit<Balance> {
+	/// The transaction reduced storage consumption.
+	///
+	/// This means that the specified amount of balance was transferred from the involved
+	/// contracts to the origin.
+	Refund(Balance),
+	/// The transaction increased storage consumption.
 	///
-	/// E.g. Contract accumulated enough funds to offset the rent storage costs.
-	NoEviction,
+	/// This means that the specified amount of balance was transferred from the origin
+	/// to the involved contracts.
+	Charge(Balance),
+}
+
+impl<Balance: Zero> Default for StorageDeposit<Balance> {
+	fn default() -> Self {
+		Self::Charge(Zero::zero())
+	}
+}
+
+impl<Balance: Zero + Copy> StorageDeposit<Balance> {
+	/// Returns how much balance is charged or `0` in case of a refund.
+	pub fn charge_or_zero(&self) -> Balance {
+		match self {
+			Self::Charge(amount) => *amount,
+			Self::Refund(_) => Zero::zero(),
+		}
+	}
+
+	/// Returns `true` if neither a charge nor a refund happened.
+	pub fn is_zero(&self) -> bool {
+		match self {
+			Self::Charge(amount) => amount.is_zero(),
+			Self::Refund(amount) => amount.is_zero(),
+		}
+	}
+}
+
+impl<Balance> StorageDeposit<Balance>
+where
+	Balance: Saturating + Ord + Copy,
+{
+	/// Combine two deposits into one by netting a charge against a refund.
+	pub fn saturating_add(&self, rhs: &Self) -> Self {
+		use StorageDeposit::*;
+		match (self, rhs) {
+			(Charge(lhs), Charge(rhs)) => Charge(lhs.saturating_add(*rhs)),
+			(Refund(lhs), Refund(rhs)) => Refund(lhs.saturating_add(*rhs)),
+			(Charge(lhs), Refund(rhs)) | (Refund(rhs), Charge(lhs)) => if lhs >= rhs {
+				Charge(lhs.saturating_sub(*rhs))
+			} else {
+				Refund(rhs.saturating_sub(*lhs))
+			},
+		}
+	}
+
+	/// How much of `limit` is still available after this deposit was taken.
+	///
+	/// A refund increases the available balance.
+	pub fn available(&self, limit: &Balance) -> Balance {
+		match self {
+			Self::Charge(amount) => limit.saturating_sub(*amount),
+			Self::Refund(amount) => limit.saturating_add(*amount),
+		}
+	}
 }
 
 bitflags! {
@@ -67,6 +180,34 @@ bitflags! {
 	}
 }
 
+bitflags! {
+	/// Flags used by a contract to customize the behaviour of `seal_call`.
+	#[derive(Encode, Decode)]
+	pub struct CallFlags: u32 {
+		/// Forward the input of the current contract to the callee.
+		///
+		/// Supplied input pointers are ignored when set. The input is consumed by the call:
+		/// Any attempt to access it afterwards fails with `InputForwarded`.
+		const FORWARD_INPUT = 0x0000_0001;
+		/// Identical to `FORWARD_INPUT` but without consuming the input.
+		///
+		/// This implies `FORWARD_INPUT` and takes precedence when both are set.
+		const CLONE_INPUT = 0x0000_0002;
+		/// Do not return from the call but rather return the output of the callee to the
+		/// caller of the current contract. Nothing after the call is executed.
+		///
+		/// This makes the current contract transparent to its caller. It is a no-op if
+		/// the callee traps.
+		const TAIL_CALL = 0x0000_0004;
+		/// Allow the callee to reenter into the current contract.
+		///
+		/// Without this flag any reentrancy into the current contract that originates from
+		/// the callee (or any of its callees) is denied. This includes the first callee:
+		/// A contract cannot call into itself without setting this flag.
+		const ALLOW_REENTRY = 0x0000_0008;
+	}
+}
+
 /// Output of a contract call or instantiation which ran to completion.
 #[derive(PartialEq, Eq, Encode, Decode, RuntimeDebug)]
 pub struct ExecReturnValue {
@@ -83,6 +224,55 @@ impl ExecReturnValue {
 	}
 }
 
+/// The result of a successful contract instantiation.
+#[derive(PartialEq, Eq, Encode, Decode, RuntimeDebug)]
+pub struct InstantiateReturnValue<AccountId> {
+	/// The output of the called constructor.
+	pub result: ExecReturnValue,
+	/// The account id of the new contract.
+	pub account_id: AccountId,
+}
+
+/// The result of successfully uploading a contract.
+#[derive(PartialEq, Eq, Encode, Decode, RuntimeDebug)]
+pub struct CodeUploadReturnValue<CodeHash, Balance> {
+	/// The key under which the new code is stored.
+	pub code_hash: CodeHash,
+	/// The deposit that was reserved at the caller. Is zero when the code already existed.
+	pub deposit: Balance,
+}
+
+/// Reference to an existing code hash or a new wasm module.
+#[derive(Eq, PartialEq, Encode, Decode, RuntimeDebug)]
+pub enum Code<Hash> {
+	/// A wasm module as raw bytes.
+	Upload(Vec<u8>),
+	/// The code hash of an on-chain wasm blob.
+	Existing(Hash),
+}
+
+/// Determines whether the execution of a contract's code is required to be deterministic.
+///
+/// The flag is supplied when the code is uploaded and stored alongside of it.
+#[derive(Clone, Copy, Eq, PartialEq, Encode, Decode, RuntimeDebug)]
+pub enum Determinism {
+	/// The code is only allowed to use instructions whose results are bit-exact on every
+	/// platform. This is the only mode in which code can be executed on-chain.
+	Enforced,
+	/// The code may use instructions whose results can differ between platforms, e.g.
+	/// floating point arithmetic.
+	///
+	/// Such code can only be executed off-chain by calling `bare_call` directly. Executing
+	/// it on-chain fails with `Indeterministic`.
+	Relaxed,
+}
+
+impl Default for Determinism {
+	fn default() -> Self {
+		Self::Enforced
+	}
+}
+
 /// Origin of the error.
 ///
 /// Call or instantiate both called into other contracts and pass through errors happening
@@ -0,0 +1,26 @@
+;; This passes a debug message to the host and copies the return code
+;; of this call to the output buffer.
+(module
+	(import "seal0" "seal_debug_message" (func $seal_debug_message (param i32 i32) (result i32)))
+	(import "seal0" "seal_return" (func $seal_return (param i32 i32 i32)))
+	(import "env" "memory" (memory 1 1))
+
+	;; [0, 12) the debug message
+	(data (i32.const 0) "Hello World!")
+
+	;; [12, 16) here we store the return code of the debug message call
+
+	(func (export "deploy"))
+
+	(func (export "call")
+		(i32.store
+			(i32.const 12)
+			(call $seal_debug_message
+				(i32.const 0) ;; ptr to the debug message
+				(i32.const 12) ;; length of the debug message
+			)
+		)
+		;; exit with success and take the return code to the output buffer
+		(call $seal_return (i32.const 0) (i32.const 12) (i32.const 4))
+	)
+)
@@ -0,0 +1,39 @@
+(module
+	(import "seal0" "seal_input" (func $seal_input (param i32 i32)))
+	(import "seal0" "seal_delegate_call" (func $seal_delegate_call (param i32 i32 i64 i32 i32 i32 i32) (result i32)))
+	(import "env" "memory" (memory 1 1))
+
+	;; [0, 32) buffer where the code hash to execute is copied
+
+	;; [32, 36) length of the input buffer
+	(data (i32.const 32) "\20")
+
+	(func $assert (param i32)
+		(block $ok
+			(br_if $ok
+				(get_local 0)
+			)
+			(unreachable)
+		)
+	)
+
+	(func (export "deploy"))
+
+	;; Execute the code hash supplied as input in the context of this contract.
+	(func (export "call")
+		(call $seal_input (i32.const 0) (i32.const 32))
+		(call $assert
+			(i32.eqz
+				(call $seal_delegate_call
+					(i32.const 0)	;; Pointer to the code hash.
+					(i32.const 32)	;; Length of the code hash.
+					(i64.const 0)	;; How much gas to devote for the execution. 0 = all.
+					(i32.const 0)	;; Pointer to input data buffer address
+					(i32.const 0)	;; Length of input data buffer
+					(i32.const 4294967295)	;; u32 max value is the sentinel value: do not copy output
+					(i32.const 0)	;; Length is ignored in this case
+				)
+			)
+		)
+	)
+)
@@ -0,0 +1,46 @@
+(module
+	(import "seal0" "seal_value_transferred" (func $seal_value_transferred (param i32 i32)))
+	(import "seal0" "seal_caller" (func $seal_caller (param i32 i32)))
+	(import "seal0" "seal_set_storage" (func $seal_set_storage (param i32 i32 i32)))
+	(import "env" "memory" (memory 1 1))
+
+	;; [0, 32) buffer where the transferred value is copied
+
+	;; [32, 36) length of the transferred value buffer
+	(data (i32.const 32) "\20")
+
+	;; [64, 96) storage key of the transferred value
+	(data (i32.const 64)
+		"\01\01\01\01\01\01\01\01\01\01\01\01\01\01\01\01"
+		"\01\01\01\01\01\01\01\01\01\01\01\01\01\01\01\01"
+	)
+
+	;; [100, 132) buffer where the caller is copied
+
+	;; [132, 136) length of the caller buffer
+	(data (i32.const 132) "\20")
+
+	;; [136, 168) storage key of the caller
+	(data (i32.const 136)
+		"\02\02\02\02\02\02\02\02\02\02\02\02\02\02\02\02"
+		"\02\02\02\02\02\02\02\02\02\02\02\02\02\02\02\02"
+	)
+
+	(func (export "deploy"))
+
+	;; Store the transferred value and the caller into the storage of the executing contract.
+	(func (export "call")
+		(call $seal_value_transferred (i32.const 0) (i32.const 32))
+		(call $seal_set_storage
+			(i32.const 64)	;; Pointer to the storage key.
+			(i32.const 0)	;; Pointer to the value.
+			(i32.load (i32.const 32))	;; Length of the value.
+		)
+		(call $seal_caller (i32.const 100) (i32.const 132))
+		(call $seal_set_storage
+			(i32.const 136)	;; Pointer to the storage key.
+			(i32.const 100)	;; Pointer to the value.
+			(i32.load (i32.const 132))	;; Length of the value.
+		)
+	)
+)
@@ -0,0 +1,50 @@
+(module
+	(import "seal0" "seal_input" (func $seal_input (param i32 i32)))
+	(import "seal0" "seal_add_delegate_dependency"
+		(func $seal_add_delegate_dependency (param i32 i32)))
+	(import "seal0" "seal_remove_delegate_dependency"
+		(func $seal_remove_delegate_dependency (param i32 i32)))
+	(import "seal0" "seal_terminate" (func $seal_terminate (param i32 i32)))
+	(import "env" "memory" (memory 1 1))
+
+	;; [0, 1) action: 0 adds a dependency, 1 removes it and 2 terminates the contract
+
+	;; [1, 33) code hash of the dependency
+
+	;; [36, 40) length of the input buffer
+	(data (i32.const 36) "\21")
+
+	;; [40, 72) Address of django
+	(data (i32.const 40)
+		"\04\04\04\04\04\04\04\04\04\04\04\04\04\04\04\04"
+		"\04\04\04\04\04\04\04\04\04\04\04\04\04\04\04\04"
+	)
+
+	(func (export "deploy"))
+
+	(func (export "call")
+		(call $seal_input (i32.const 0) (i32.const 36))
+		(if (i32.eqz (i32.load8_u (i32.const 0)))
+			(then
+				(call $seal_add_delegate_dependency
+					(i32.const 1)	;; Pointer to the code hash.
+					(i32.const 32)	;; Length of the code hash.
+				)
+				(return)
+			)
+		)
+		(if (i32.eq (i32.load8_u (i32.const 0)) (i32.const 1))
+			(then
+				(call $seal_remove_delegate_dependency
+					(i32.const 1)	;; Pointer to the code hash.
+					(i32.const 32)	;; Length of the code hash.
+				)
+				(return)
+			)
+		)
+		(call $seal_terminate
+			(i32.const 40)	;; Pointer to the beneficiary address.
+			(i32.const 32)	;; Length of the beneficiary address.
+		)
+	)
+)
@@ -0,0 +1,36 @@
+;; Recovers the public key from the signature and message hash passed as input and
+;; copies the return code of the call followed by the recovered key to the output buffer.
+(module
+	(import "seal0" "seal_input" (func $seal_input (param i32 i32)))
+	(import "seal0" "seal_ecdsa_recover" (func $seal_ecdsa_recover (param i32 i32 i32) (result i32)))
+	(import "seal0" "seal_return" (func $seal_return (param i32 i32 i32)))
+	(import "env" "memory" (memory 1 1))
+
+	;; [0, 4) length of the input buffer
+	(data (i32.const 0) "\61")
+
+	;; [4, 69) the signature
+	;; [69, 101) the message hash
+	;; [104, 108) here we store the return code of the recover call
+	;; [108, 141) the recovered compressed public key
+
+	(func (export "deploy"))
+
+	(func (export "call")
+		(call $seal_input (i32.const 4) (i32.const 0))
+		;; the input must consist of the signature and the message hash
+		(if (i32.ne (i32.load (i32.const 0)) (i32.const 97))
+			(then (unreachable))
+		)
+		(i32.store
+			(i32.const 104)
+			(call $seal_ecdsa_recover
+				(i32.const 4) ;; ptr to the signature
+				(i32.const 69) ;; ptr to the message hash
+				(i32.const 108) ;; ptr to the output buffer
+			)
+		)
+		;; exit with success and take the return code and the key to the output buffer
+		(call $seal_return (i32.const 0) (i32.const 104) (i32.const 37))
+	)
+)
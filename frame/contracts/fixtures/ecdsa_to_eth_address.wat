@@ -0,0 +1,34 @@
+;; Calculates the Ethereum address of the compressed public key passed as input and
+;; copies the return code of the call followed by the address to the output buffer.
+(module
+	(import "seal0" "seal_input" (func $seal_input (param i32 i32)))
+	(import "seal0" "seal_ecdsa_to_eth_address" (func $seal_ecdsa_to_eth_address (param i32 i32) (result i32)))
+	(import "seal0" "seal_return" (func $seal_return (param i32 i32 i32)))
+	(import "env" "memory" (memory 1 1))
+
+	;; [0, 4) length of the input buffer
+	(data (i32.const 0) "\21")
+
+	;; [4, 37) the compressed public key
+	;; [40, 44) here we store the return code of the conversion
+	;; [44, 64) the Ethereum address
+
+	(func (export "deploy"))
+
+	(func (export "call")
+		(call $seal_input (i32.const 4) (i32.const 0))
+		;; the input must consist of exactly one compressed public key
+		(if (i32.ne (i32.load (i32.const 0)) (i32.const 33))
+			(then (unreachable))
+		)
+		(i32.store
+			(i32.const 40)
+			(call $seal_ecdsa_to_eth_address
+				(i32.const 4) ;; ptr to the compressed public key
+				(i32.const 44) ;; ptr to the output buffer
+			)
+		)
+		;; exit with success and take the return code and the address to the output buffer
+		(call $seal_return (i32.const 0) (i32.const 40) (i32.const 24))
+	)
+)
@@ -0,0 +1,34 @@
+;; Verifies the signature of the message passed as input and copies the
+;; return code of the verification to the output buffer.
+(module
+	(import "seal0" "seal_input" (func $seal_input (param i32 i32)))
+	(import "seal0" "seal_sr25519_verify" (func $seal_sr25519_verify (param i32 i32 i32 i32) (result i32)))
+	(import "seal0" "seal_return" (func $seal_return (param i32 i32 i32)))
+	(import "env" "memory" (memory 1 1))
+
+	;; [0, 4) length of the input buffer
+	(data (i32.const 0) "\00\01")
+
+	;; [4, 68) the signature
+	;; [68, 100) the public key
+	;; [100, 260) the message
+	;; [260, 264) here we store the return code of the verification
+
+	(func (export "deploy"))
+
+	(func (export "call")
+		(call $seal_input (i32.const 4) (i32.const 0))
+		(i32.store
+			(i32.const 260)
+			(call $seal_sr25519_verify
+				(i32.const 4) ;; ptr to the signature
+				(i32.const 68) ;; ptr to the public key
+				;; the message is everything after the signature and the public key
+				(i32.sub (i32.load (i32.const 0)) (i32.const 96))
+				(i32.const 100) ;; ptr to the message
+			)
+		)
+		;; exit with success and take the return code to the output buffer
+		(call $seal_return (i32.const 0) (i32.const 260) (i32.const 4))
+	)
+)
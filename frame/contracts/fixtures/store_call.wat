@@ -2,7 +2,6 @@
 	(import "seal0" "seal_transfer" (func $seal_transfer (param i32 i32 i32 i32) (result i32)))
 	(import "seal0" "seal_set_storage" (func $seal_set_storage (param i32 i32 i32)))
 	(import "seal0" "seal_clear_storage" (func $seal_clear_storage (param i32)))
-	(import "seal0" "seal_set_rent_allowance" (func $seal_set_rent_allowance (param i32 i32)))
 	(import "seal0" "seal_input" (func $seal_input (param i32 i32)))
 	(import "env" "memory" (memory 1 1))
 
@@ -22,7 +21,7 @@
 		)
 	)
 
-	;; transfer 50 to CHARLIE
+	;; transfer 50 to the account supplied in the input
 	(func $call_2
 		(call $assert
 			(i32.eq
@@ -75,22 +74,12 @@
 	)
 
 	;; Set into storage a 4 bytes value
-	;; Set call set_rent_allowance with input
 	(func (export "deploy")
 		(call $seal_set_storage
 			(i32.const 0)
 			(i32.const 0)
 			(i32.const 4)
 		)
-		(i32.store (i32.const 128) (i32.const 64))
-		(call $seal_input
-			(i32.const 104)
-			(i32.const 100)
-		)
-		(call $seal_set_rent_allowance
-			(i32.const 104)
-			(i32.load (i32.const 128))
-		)
 	)
 
 	;; Encoding of 10 in balance
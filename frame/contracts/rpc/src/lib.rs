@@ -22,7 +22,6 @@ use std::sync::Arc;
 use codec::Codec;
 use jsonrpc_core::{Error, ErrorCode, Result};
 use jsonrpc_derive::rpc;
-use pallet_contracts_primitives::RentProjection;
 use serde::{Deserialize, Serialize};
 use sp_api::ProvideRuntimeApi;
 use sp_blockchain::HeaderBackend;
@@ -34,13 +33,14 @@ use sp_runtime::{
 	DispatchError,
 };
 use std::convert::TryInto;
-use pallet_contracts_primitives::ContractExecResult;
+use pallet_contracts_primitives::{
+	CodeUploadResult, ContractEvent, ContractExecResult, ContractInstantiateResult, StorageDeposit,
+};
 
 pub use pallet_contracts_rpc_runtime_api::ContractsApi as ContractsRuntimeApi;
 
 const RUNTIME_ERROR: i64 = 1;
 const CONTRACT_DOESNT_EXIST: i64 = 2;
-const CONTRACT_IS_A_TOMBSTONE: i64 = 3;
 
 /// A rough estimate of how much gas a decent hardware consumes per second,
 /// using native execution.
@@ -63,11 +63,6 @@ impl From<ContractAccessError> for Error {
 				message: "The specified contract doesn't exist.".into(),
 				data: None,
 			},
-			IsTombstone => Error {
-				code: ErrorCode::ServerError(CONTRACT_IS_A_TOMBSTONE),
-				message: "The contract is a tombstone and doesn't have any storage.".into(),
-				data: None,
-			},
 		}
 	}
 }
@@ -81,7 +76,84 @@ pub struct CallRequest<AccountId, Balance> {
 	dest: AccountId,
 	value: Balance,
 	gas_limit: number::NumberOrHex,
+	storage_deposit_limit: Option<Balance>,
 	input_data: Bytes,
+	#[serde(default)]
+	determinism: Determinism,
+}
+
+/// Reference to an existing code hash or a new wasm module.
+#[derive(Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+#[serde(deny_unknown_fields)]
+pub enum Code<Hash> {
+	/// A wasm module as raw bytes.
+	Upload(Bytes),
+	/// The code hash of an on-chain wasm blob.
+	Existing(Hash),
+}
+
+impl<Hash> From<Code<Hash>> for pallet_contracts_primitives::Code<Hash> {
+	fn from(code: Code<Hash>) -> Self {
+		match code {
+			Code::Upload(wasm) => pallet_contracts_primitives::Code::Upload(wasm.to_vec()),
+			Code::Existing(hash) => pallet_contracts_primitives::Code::Existing(hash),
+		}
+	}
+}
+
+/// Whether code that is not guaranteed to be deterministic may be uploaded or executed.
+///
+/// See `pallet_contracts_primitives::Determinism`.
+#[derive(Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+#[serde(deny_unknown_fields)]
+pub enum Determinism {
+	/// Only deterministic code is accepted.
+	Enforced,
+	/// Code that uses non-deterministic instructions is accepted.
+	Relaxed,
+}
+
+impl Default for Determinism {
+	fn default() -> Self {
+		Determinism::Enforced
+	}
+}
+
+impl From<Determinism> for pallet_contracts_primitives::Determinism {
+	fn from(determinism: Determinism) -> Self {
+		match determinism {
+			Determinism::Enforced => pallet_contracts_primitives::Determinism::Enforced,
+			Determinism::Relaxed => pallet_contracts_primitives::Determinism::Relaxed,
+		}
+	}
+}
+
+/// A struct that encodes RPC parameters required to instantiate a new smart-contract.
+#[derive(Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+#[serde(deny_unknown_fields)]
+pub struct InstantiateRequest<AccountId, Hash, Balance> {
+	origin: AccountId,
+	endowment: Balance,
+	gas_limit: number::NumberOrHex,
+	storage_deposit_limit: Option<Balance>,
+	code: Code<Hash>,
+	data: Bytes,
+	salt: Bytes,
+}
+
+/// A struct that encodes RPC parameters required for uploading new code.
+#[derive(Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+#[serde(deny_unknown_fields)]
+pub struct CodeUploadRequest<AccountId, Balance> {
+	origin: AccountId,
+	code: Bytes,
+	storage_deposit_limit: Option<Balance>,
+	#[serde(default)]
+	determinism: Determinism,
 }
 
 #[derive(Serialize, Deserialize)]
@@ -94,44 +166,190 @@ struct RpcContractExecSuccess {
 	data: Bytes,
 }
 
+/// The storage deposit that was charged or refunded by a contract execution.
+///
+/// See `pallet_contracts_primitives::StorageDeposit`.
+#[derive(Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+#[serde(rename_all = "camelCase")]
+enum RpcStorageDeposit<Balance> {
+	/// The origin got the specified amount refunded.
+	Refund(Balance),
+	/// The specified amount was charged from the origin.
+	Charge(Balance),
+}
+
+impl<Balance> From<StorageDeposit<Balance>> for RpcStorageDeposit<Balance> {
+	fn from(d: StorageDeposit<Balance>) -> Self {
+		match d {
+			StorageDeposit::Refund(amount) => RpcStorageDeposit::Refund(amount),
+			StorageDeposit::Charge(amount) => RpcStorageDeposit::Charge(amount),
+		}
+	}
+}
+
+/// An event that was deposited by a contract.
+///
+/// See `pallet_contracts_primitives::ContractEvent`.
+#[derive(Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+#[serde(rename_all = "camelCase")]
+pub struct RpcContractEvent<AccountId, Hash> {
+	/// The contract that deposited the event.
+	contract: AccountId,
+	/// The topics the event was deposited with.
+	topics: Vec<Hash>,
+	/// The data of the event as supplied by the contract.
+	data: Bytes,
+}
+
+impl<AccountId, Hash> From<ContractEvent<AccountId, Hash>> for RpcContractEvent<AccountId, Hash> {
+	fn from(e: ContractEvent<AccountId, Hash>) -> Self {
+		RpcContractEvent {
+			contract: e.contract,
+			topics: e.topics,
+			data: e.data.into(),
+		}
+	}
+}
+
 /// An RPC serializable result of contract execution
 #[derive(Serialize, Deserialize)]
 #[serde(deny_unknown_fields)]
 #[serde(rename_all = "camelCase")]
-pub struct RpcContractExecResult {
+pub struct RpcContractExecResult<AccountId, Balance, Hash> {
 	/// How much gas was consumed by the call. In case of an error this is the amount
 	/// that was used up until the error occurred.
 	gas_consumed: u64,
-	/// Additional dynamic human readable error information for debugging. An empty string
-	/// indicates that no additional information is available.
+	/// How much gas must be supplied as `gas_limit` for the call to succeed.
+	gas_required: u64,
+	/// How much balance was charged from or refunded to the origin in order to pay for the
+	/// storage the execution occupied or freed.
+	storage_deposit: RpcStorageDeposit<Balance>,
+	/// By how many bytes the storage of all involved contracts changed.
+	storage_bytes: i64,
+	/// The events deposited by contracts during the call.
+	events: Vec<RpcContractEvent<AccountId, Hash>>,
+	/// The messages passed to `seal_debug_message` during the call. An empty string
+	/// indicates that no messages were emitted.
 	debug_message: String,
 	/// Indicates whether the contract execution was successful or not.
 	result: std::result::Result<RpcContractExecSuccess, DispatchError>,
 }
 
-impl From<ContractExecResult> for RpcContractExecResult {
-	fn from(r: ContractExecResult) -> Self {
-		match r.exec_result {
-			Ok(val) => RpcContractExecResult {
-				gas_consumed: r.gas_consumed,
-				debug_message: String::new(),
-				result: Ok(RpcContractExecSuccess {
+impl<AccountId, Balance, Hash> From<ContractExecResult<AccountId, Balance, Hash>>
+	for RpcContractExecResult<AccountId, Balance, Hash>
+{
+	fn from(r: ContractExecResult<AccountId, Balance, Hash>) -> Self {
+		RpcContractExecResult {
+			gas_consumed: r.gas_consumed,
+			gas_required: r.gas_required,
+			storage_deposit: r.storage_deposit.into(),
+			storage_bytes: r.storage_bytes,
+			events: r.events.into_iter().map(Into::into).collect(),
+			debug_message: String::from_utf8_lossy(&r.debug_message).into_owned(),
+			result: r.exec_result
+				.map(|val| RpcContractExecSuccess {
 					flags: val.flags.bits(),
 					data: val.data.into(),
-				}),
-			},
-			Err(err) => RpcContractExecResult {
-				gas_consumed: r.gas_consumed,
-				debug_message: String::new(),
-				result: Err(err.error),
-			},
+				})
+				.map_err(|err| err.error),
+		}
+	}
+}
+
+#[derive(Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+#[serde(rename_all = "camelCase")]
+struct RpcInstantiateSuccess<AccountId> {
+	/// The output of the constructor.
+	result: RpcContractExecSuccess,
+	/// The account id of the new contract.
+	account_id: AccountId,
+}
+
+/// An RPC serializable result of contract instantiation.
+#[derive(Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+#[serde(rename_all = "camelCase")]
+pub struct RpcContractInstantiateResult<AccountId, Balance, Hash> {
+	/// How much gas was consumed by the instantiation. In case of an error this is the amount
+	/// that was used up until the error occurred.
+	gas_consumed: u64,
+	/// How much gas must be supplied as `gas_limit` for the instantiation to succeed.
+	gas_required: u64,
+	/// How much balance was charged from or refunded to the origin in order to pay for the
+	/// storage of the new contract and its code.
+	storage_deposit: RpcStorageDeposit<Balance>,
+	/// By how many bytes the storage of all involved contracts changed.
+	storage_bytes: i64,
+	/// The events deposited by contracts during the instantiation.
+	events: Vec<RpcContractEvent<AccountId, Hash>>,
+	/// The messages passed to `seal_debug_message` during the instantiation. An empty string
+	/// indicates that no messages were emitted.
+	debug_message: String,
+	/// Indicates whether the instantiation was successful or not.
+	result: std::result::Result<RpcInstantiateSuccess<AccountId>, DispatchError>,
+}
+
+impl<AccountId, Balance, Hash> From<ContractInstantiateResult<AccountId, Balance, Hash>>
+	for RpcContractInstantiateResult<AccountId, Balance, Hash>
+{
+	fn from(r: ContractInstantiateResult<AccountId, Balance, Hash>) -> Self {
+		RpcContractInstantiateResult {
+			gas_consumed: r.gas_consumed,
+			gas_required: r.gas_required,
+			storage_deposit: r.storage_deposit.into(),
+			storage_bytes: r.storage_bytes,
+			events: r.events.into_iter().map(Into::into).collect(),
+			debug_message: String::from_utf8_lossy(&r.debug_message).into_owned(),
+			result: r.result
+				.map(|val| RpcInstantiateSuccess {
+					result: RpcContractExecSuccess {
+						flags: val.result.flags.bits(),
+						data: val.result.data.into(),
+					},
+					account_id: val.account_id,
+				})
+				.map_err(|err| err.error),
+		}
+	}
+}
+
+/// An RPC serializable result of a code upload.
+#[derive(Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+#[serde(rename_all = "camelCase")]
+pub struct RpcCodeUploadResult<Hash, Balance> {
+	/// The hash of the uploaded code and the deposit reserved from the origin or the reason
+	/// why the upload failed.
+	result: std::result::Result<RpcCodeUploadSuccess<Hash, Balance>, DispatchError>,
+}
+
+#[derive(Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+#[serde(rename_all = "camelCase")]
+struct RpcCodeUploadSuccess<Hash, Balance> {
+	/// The key under which the code is stored.
+	code_hash: Hash,
+	/// The deposit reserved from the origin. Zero if the code was already stored.
+	deposit: Balance,
+}
+
+impl<Hash, Balance> From<CodeUploadResult<Hash, Balance>> for RpcCodeUploadResult<Hash, Balance> {
+	fn from(r: CodeUploadResult<Hash, Balance>) -> Self {
+		RpcCodeUploadResult {
+			result: r.map(|val| RpcCodeUploadSuccess {
+				code_hash: val.code_hash,
+				deposit: val.deposit,
+			}),
 		}
 	}
 }
 
 /// Contracts RPC methods.
 #[rpc]
-pub trait ContractsApi<BlockHash, BlockNumber, AccountId, Balance> {
+pub trait ContractsApi<BlockHash, BlockNumber, AccountId, Balance, Hash> {
 	/// Executes a call to a contract.
 	///
 	/// This call is performed locally without submitting any transactions. Thus executing this
@@ -143,7 +361,33 @@ pub trait ContractsApi<BlockHash, BlockNumber, AccountId, Balance> {
 		&self,
 		call_request: CallRequest<AccountId, Balance>,
 		at: Option<BlockHash>,
-	) -> Result<RpcContractExecResult>;
+	) -> Result<RpcContractExecResult<AccountId, Balance, Hash>>;
+
+	/// Instantiate a new contract.
+	///
+	/// This instantiate is performed locally without submitting any transactions. Thus the
+	/// contract is not actually created.
+	///
+	/// This method is useful for UIs to dry-run contract instantiations.
+	#[rpc(name = "contracts_instantiate")]
+	fn instantiate(
+		&self,
+		instantiate_request: InstantiateRequest<AccountId, Hash, Balance>,
+		at: Option<BlockHash>,
+	) -> Result<RpcContractInstantiateResult<AccountId, Balance, Hash>>;
+
+	/// Upload new code without instantiating a contract from it.
+	///
+	/// This upload is performed locally without submitting any transactions. Thus the code
+	/// is not actually stored.
+	///
+	/// This method is useful for UIs to determine the deposit required to upload code.
+	#[rpc(name = "contracts_uploadCode")]
+	fn upload_code(
+		&self,
+		upload_request: CodeUploadRequest<AccountId, Balance>,
+		at: Option<BlockHash>,
+	) -> Result<RpcCodeUploadResult<Hash, Balance>>;
 
 	/// Returns the value under a specified storage `key` in a contract given by `address` param,
 	/// or `None` if it is not set.
@@ -154,19 +398,6 @@ pub trait ContractsApi<BlockHash, BlockNumber, AccountId, Balance> {
 		key: H256,
 		at: Option<BlockHash>,
 	) -> Result<Option<Bytes>>;
-
-	/// Returns the projected time a given contract will be able to sustain paying its rent.
-	///
-	/// The returned projection is relevant for the given block, i.e. it is as if the contract was
-	/// accessed at the beginning of that block.
-	///
-	/// Returns `None` if the contract is exempted from rent.
-	#[rpc(name = "contracts_rentProjection")]
-	fn rent_projection(
-		&self,
-		address: AccountId,
-		at: Option<BlockHash>,
-	) -> Result<Option<BlockNumber>>;
 }
 
 /// An implementation of contract specific RPC methods.
@@ -184,12 +415,13 @@ impl<C, B> Contracts<C, B> {
 		}
 	}
 }
-impl<C, Block, AccountId, Balance>
+impl<C, Block, AccountId, Balance, Hash>
 	ContractsApi<
 		<Block as BlockT>::Hash,
 		<<Block as BlockT>::Header as HeaderT>::Number,
 		AccountId,
 		Balance,
+		Hash,
 	> for Contracts<C, Block>
 where
 	Block: BlockT,
@@ -199,15 +431,17 @@ where
 		AccountId,
 		Balance,
 		<<Block as BlockT>::Header as HeaderT>::Number,
+		Hash,
 	>,
 	AccountId: Codec,
 	Balance: Codec,
+	Hash: Codec,
 {
 	fn call(
 		&self,
 		call_request: CallRequest<AccountId, Balance>,
 		at: Option<<Block as BlockT>::Hash>,
-	) -> Result<RpcContractExecResult> {
+	) -> Result<RpcContractExecResult<AccountId, Balance, Hash>> {
 		let api = self.client.runtime_api();
 		let at = BlockId::hash(at.unwrap_or_else(||
 			// If the block hash is not supplied assume the best block.
@@ -218,77 +452,130 @@ where
 			dest,
 			value,
 			gas_limit,
+			storage_deposit_limit,
 			input_data,
+			determinism,
 		} = call_request;
 
-		// Make sure that gas_limit fits into 64 bits.
-		let gas_limit: u64 = gas_limit.try_into().map_err(|_| Error {
-			code: ErrorCode::InvalidParams,
-			message: format!("{:?} doesn't fit in 64 bit unsigned value", gas_limit),
-			data: None,
-		})?;
-
-		let max_gas_limit = 5 * GAS_PER_SECOND;
-		if gas_limit > max_gas_limit {
-			return Err(Error {
-				code: ErrorCode::InvalidParams,
-				message: format!(
-					"Requested gas limit is greater than maximum allowed: {} > {}",
-					gas_limit, max_gas_limit
-				),
-				data: None,
-			});
-		}
+		let gas_limit = limit_gas(gas_limit)?;
 
 		let exec_result = api
-			.call(&at, origin, dest, value, gas_limit, input_data.to_vec())
+			.call(
+				&at,
+				origin,
+				dest,
+				value,
+				gas_limit,
+				storage_deposit_limit,
+				input_data.to_vec(),
+				determinism.into(),
+			)
 			.map_err(runtime_error_into_rpc_err)?;
 
 		Ok(exec_result.into())
 	}
 
-	fn get_storage(
+	fn instantiate(
 		&self,
-		address: AccountId,
-		key: H256,
+		instantiate_request: InstantiateRequest<AccountId, Hash, Balance>,
 		at: Option<<Block as BlockT>::Hash>,
-	) -> Result<Option<Bytes>> {
+	) -> Result<RpcContractInstantiateResult<AccountId, Balance, Hash>> {
 		let api = self.client.runtime_api();
 		let at = BlockId::hash(at.unwrap_or_else(||
 			// If the block hash is not supplied assume the best block.
 			self.client.info().best_hash));
 
+		let InstantiateRequest {
+			origin,
+			endowment,
+			gas_limit,
+			storage_deposit_limit,
+			code,
+			data,
+			salt,
+		} = instantiate_request;
+
+		let gas_limit = limit_gas(gas_limit)?;
+
 		let result = api
-			.get_storage(&at, address, key.into())
-			.map_err(runtime_error_into_rpc_err)?
-			.map_err(ContractAccessError)?
-			.map(Bytes);
+			.instantiate(
+				&at,
+				origin,
+				endowment,
+				gas_limit,
+				storage_deposit_limit,
+				code.into(),
+				data.to_vec(),
+				salt.to_vec(),
+			)
+			.map_err(runtime_error_into_rpc_err)?;
 
-		Ok(result)
+		Ok(result.into())
 	}
 
-	fn rent_projection(
+	fn upload_code(
+		&self,
+		upload_request: CodeUploadRequest<AccountId, Balance>,
+		at: Option<<Block as BlockT>::Hash>,
+	) -> Result<RpcCodeUploadResult<Hash, Balance>> {
+		let api = self.client.runtime_api();
+		let at = BlockId::hash(at.unwrap_or_else(||
+			// If the block hash is not supplied assume the best block.
+			self.client.info().best_hash));
+
+		let CodeUploadRequest { origin, code, storage_deposit_limit, determinism } = upload_request;
+
+		let result = api
+			.upload_code(&at, origin, code.to_vec(), storage_deposit_limit, determinism.into())
+			.map_err(runtime_error_into_rpc_err)?;
+
+		Ok(result.into())
+	}
+
+	fn get_storage(
 		&self,
 		address: AccountId,
+		key: H256,
 		at: Option<<Block as BlockT>::Hash>,
-	) -> Result<Option<<<Block as BlockT>::Header as HeaderT>::Number>> {
+	) -> Result<Option<Bytes>> {
 		let api = self.client.runtime_api();
 		let at = BlockId::hash(at.unwrap_or_else(||
 			// If the block hash is not supplied assume the best block.
 			self.client.info().best_hash));
 
 		let result = api
-			.rent_projection(&at, address)
+			.get_storage(&at, address, key.into())
 			.map_err(runtime_error_into_rpc_err)?
-			.map_err(ContractAccessError)?;
+			.map_err(ContractAccessError)?
+			.map(Bytes);
 
-		Ok(match result {
-			RentProjection::NoEviction => None,
-			RentProjection::EvictionAt(block_num) => Some(block_num),
-		})
+		Ok(result)
 	}
 }
 
+/// Make sure that `gas_limit` fits into 64 bits and doesn't exceed the maximum allowed by the RPC.
+fn limit_gas(gas_limit: number::NumberOrHex) -> Result<u64> {
+	let gas_limit: u64 = gas_limit.try_into().map_err(|_| Error {
+		code: ErrorCode::InvalidParams,
+		message: format!("{:?} doesn't fit in 64 bit unsigned value", gas_limit),
+		data: None,
+	})?;
+
+	let max_gas_limit = 5 * GAS_PER_SECOND;
+	if gas_limit > max_gas_limit {
+		return Err(Error {
+			code: ErrorCode::InvalidParams,
+			message: format!(
+				"Requested gas limit is greater than maximum allowed: {} > {}",
+				gas_limit, max_gas_limit
+			),
+			data: None,
+		});
+	}
+
+	Ok(gas_limit)
+}
+
 /// Converts a runtime trap into an RPC error.
 fn runtime_error_into_rpc_err(err: impl std::fmt::Debug) -> Error {
 	Error {
@@ -312,20 +599,84 @@ mod tests {
 			"dest": "5DRakbLVnjVrW6niwLfHGW24EeCEvDAFGEXrtaYS5M4ynoom",
 			"value": 0,
 			"gasLimit": 1000000000000,
+			"storageDepositLimit": 5000,
 			"inputData": "0x8c97db39"
 		}
 		"#).unwrap();
 		assert_eq!(req.gas_limit.into_u256(), U256::from(0xe8d4a51000u64));
+		assert_eq!(req.storage_deposit_limit, Some(5000));
+		assert!(matches!(req.determinism, Determinism::Enforced));
+	}
+
+	#[test]
+	fn instantiate_request_should_serialize_deserialize_properly() {
+		type Req = InstantiateRequest<String, String, u128>;
+		let req: Req = serde_json::from_str(r#"
+		{
+			"origin": "5CiPPseXPECbkjWCa6MnjNokrgYjMqmKndv2rSnekmSK2DjL",
+			"endowment": 100,
+			"gasLimit": 1000000000000,
+			"storageDepositLimit": null,
+			"code": { "existing": "0x1122" },
+			"data": "0x4299",
+			"salt": "0x9988"
+		}
+		"#).unwrap();
+		assert_eq!(req.endowment, 100);
+		assert_eq!(req.storage_deposit_limit, None);
+		assert!(matches!(req.code, Code::Existing(hash) if hash == "0x1122"));
+		assert_eq!(req.data.0, vec![0x42, 0x99]);
+		assert_eq!(req.salt.0, vec![0x99, 0x88]);
+	}
+
+	#[test]
+	fn code_upload_request_should_serialize_deserialize_properly() {
+		type Req = CodeUploadRequest<String, u128>;
+		let req: Req = serde_json::from_str(r#"
+		{
+			"origin": "5CiPPseXPECbkjWCa6MnjNokrgYjMqmKndv2rSnekmSK2DjL",
+			"code": "0x0061736d01000000",
+			"storageDepositLimit": 5000,
+			"determinism": "relaxed"
+		}
+		"#).unwrap();
+		assert_eq!(req.code.0, vec![0x00, 0x61, 0x73, 0x6d, 0x01, 0x00, 0x00, 0x00]);
+		assert_eq!(req.storage_deposit_limit, Some(5000));
+		assert!(matches!(req.determinism, Determinism::Relaxed));
 	}
 
 	#[test]
 	fn result_should_serialize_deserialize_properly() {
 		fn test(expected: &str) {
-			let res: RpcContractExecResult = serde_json::from_str(expected).unwrap();
+			let res: RpcContractExecResult<String, u128, String> =
+				serde_json::from_str(expected).unwrap();
+			let actual = serde_json::to_string(&res).unwrap();
+			assert_eq!(actual, expected);
+		}
+		test(r#"{"gasConsumed":5000,"gasRequired":6000,"storageDeposit":{"charge":100},"storageBytes":18,"events":[{"contract":"5CiPP","topics":["0x1122"],"data":"0x4299"}],"debugMessage":"helpOk","result":{"Ok":{"flags":5,"data":"0x1234"}}}"#);
+		test(r#"{"gasConsumed":3400,"gasRequired":3400,"storageDeposit":{"refund":20},"storageBytes":-4,"events":[],"debugMessage":"helpErr","result":{"Err":"BadOrigin"}}"#);
+	}
+
+	#[test]
+	fn instantiate_result_should_serialize_deserialize_properly() {
+		fn test(expected: &str) {
+			let res: RpcContractInstantiateResult<String, u128, String> =
+				serde_json::from_str(expected).unwrap();
+			let actual = serde_json::to_string(&res).unwrap();
+			assert_eq!(actual, expected);
+		}
+		test(r#"{"gasConsumed":5000,"gasRequired":5000,"storageDeposit":{"charge":100},"storageBytes":0,"events":[],"debugMessage":"","result":{"Ok":{"result":{"flags":0,"data":"0x"},"accountId":"5CiPP"}}}"#);
+		test(r#"{"gasConsumed":3400,"gasRequired":4000,"storageDeposit":{"charge":0},"storageBytes":0,"events":[],"debugMessage":"","result":{"Err":"BadOrigin"}}"#);
+	}
+
+	#[test]
+	fn code_upload_result_should_serialize_deserialize_properly() {
+		fn test(expected: &str) {
+			let res: RpcCodeUploadResult<String, u128> = serde_json::from_str(expected).unwrap();
 			let actual = serde_json::to_string(&res).unwrap();
 			assert_eq!(actual, expected);
 		}
-		test(r#"{"gasConsumed":5000,"debugMessage":"helpOk","result":{"Ok":{"flags":5,"data":"0x1234"}}}"#);
-		test(r#"{"gasConsumed":3400,"debugMessage":"helpErr","result":{"Err":"BadOrigin"}}"#);
+		test(r#"{"result":{"Ok":{"codeHash":"0x1122","deposit":300}}}"#);
+		test(r#"{"result":{"Err":"BadOrigin"}}"#);
 	}
 }
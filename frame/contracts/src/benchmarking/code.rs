@@ -98,12 +98,13 @@ impl ImportedMemory {
 }
 
 pub struct ImportedFunction {
+	pub module: &'static str,
 	pub name: &'static str,
 	pub params: Vec<ValueType>,
 	pub return_type: Option<ValueType>,
 }
 
-/// A wasm module ready to be put on chain with `put_code`.
+/// A wasm module ready to be put on chain with `upload_code`.
 #[derive(Clone)]
 pub struct WasmModule<T:Config> {
 	pub code: Vec<u8>,
@@ -166,7 +167,7 @@ where
 				.build_sig();
 			let sig = contract.push_signature(sig);
 			contract = contract.import()
-				.module("seal0")
+				.module(func.module)
 				.field(func.name)
 				.with_external(parity_wasm::elements::External::Function(sig))
 				.build();
@@ -245,7 +246,7 @@ where
 	}
 
 	/// Creates a wasm module of `target_bytes` size. Used to benchmark the performance of
-	/// `put_code` for different sizes of wasm modules. The generated module maximizes
+	/// `upload_code` for different sizes of wasm modules. The generated module maximizes
 	/// instrumentation runtime by nesting blocks as deeply as possible given the byte budget.
 	pub fn sized(target_bytes: u32) -> Self {
 		use parity_wasm::elements::Instruction::{If, I32Const, Return, End};
@@ -253,7 +254,7 @@ where
 		// We do one expansion less to account for the code section and function body
 		// size fields inside the binary wasm module representation which are leb128 encoded
 		// and therefore grow in size when the contract grows. We are not allowed to overshoot
-		// because of the maximum code size that is enforced by `put_code`.
+		// because of the maximum code size that is enforced by `upload_code`.
 		let expansions = (target_bytes.saturating_sub(47) / 6).saturating_sub(1);
 		const EXPANSION: [Instruction; 4] = [
 			I32Const(0),
@@ -276,6 +277,7 @@ where
 		ModuleDefinition {
 			memory: Some(ImportedMemory::max::<T>()),
 			imported_functions: vec![ImportedFunction {
+				module: "seal0",
 				name: getter_name,
 				params: vec![ValueType::I32, ValueType::I32],
 				return_type: None,
@@ -305,6 +307,7 @@ where
 		ModuleDefinition {
 			memory: Some(ImportedMemory::max::<T>()),
 			imported_functions: vec![ImportedFunction {
+				module: "seal0",
 				name,
 				params: vec![ValueType::I32, ValueType::I32, ValueType::I32],
 				return_type: None,
@@ -16,19 +16,21 @@
 
 use crate::{
 	CodeHash, ConfigCache, Event, RawEvent, Config, Module as Contracts,
-	TrieId, BalanceOf, ContractInfo, gas::GasMeter, rent::Rent, storage::{self, Storage},
-	Error, ContractInfoOf
+	TrieId, BalanceOf, gas::GasMeter, storage::{self, Storage, meter::Meter},
+	Error, ContractInfoOf, DelegateDependencies,
 };
-use sp_core::crypto::UncheckedFrom;
+use sp_core::{crypto::UncheckedFrom, sr25519};
 use sp_std::prelude::*;
-use sp_runtime::traits::{Bounded, Zero, Convert, Saturating};
+use sp_runtime::traits::{Zero, Convert, Saturating};
 use frame_support::{
-	dispatch::DispatchError,
+	dispatch::{DispatchError, DispatchResult},
 	traits::{ExistenceRequirement, Currency, Time, Randomness},
 	weights::Weight,
-	ensure, StorageMap,
+	ensure, StorageMap, StorageDoubleMap, IterableStorageDoubleMap,
+};
+use pallet_contracts_primitives::{
+	ContractEvent, ErrorOrigin, ExecError, ExecReturnValue, ExecResult, ReturnFlags,
 };
-use pallet_contracts_primitives::{ErrorOrigin, ExecError, ExecReturnValue, ExecResult, ReturnFlags};
 
 pub type AccountIdOf<T> = <T as frame_system::Config>::AccountId;
 pub type MomentOf<T> = <<T as Config>::Time as Time>::Moment;
@@ -99,32 +101,69 @@ pub trait Ext {
 	) -> Result<(), DispatchError>;
 
 	/// Call (possibly transferring some amount of funds) into the specified account.
+	///
+	/// Unless `allows_reentry` is `true` the current contract cannot be reentered by the
+	/// callee or any contract further down the call stack. This includes the callee itself.
 	fn call(
 		&mut self,
 		to: &AccountIdOf<Self::T>,
 		value: BalanceOf<Self::T>,
 		gas_meter: &mut GasMeter<Self::T>,
 		input_data: Vec<u8>,
+		allows_reentry: bool,
 	) -> ExecResult;
 
-	/// Restores the given destination contract sacrificing the current one.
+	/// Execute code in the current frame.
 	///
-	/// Since this function removes the self contract eagerly, if succeeded, no further actions should
-	/// be performed on this `Ext` instance.
+	/// The code identified by `code_hash` is executed in the context of the current contract:
+	/// It operates on the storage of the current contract and sees the same caller and
+	/// transferred value as the current frame.
+	fn delegate_call(
+		&mut self,
+		code_hash: CodeHash<Self::T>,
+		gas_meter: &mut GasMeter<Self::T>,
+		input_data: Vec<u8>,
+	) -> ExecResult;
+
+	/// Replace the code of the current contract by the code identified by `hash`.
+	///
+	/// The storage and the balance of the contract are kept.
+	fn set_code_hash(&mut self, hash: CodeHash<Self::T>) -> Result<(), DispatchError>;
+
+	/// Make the current contract a user of the code identified by `code_hash`.
 	///
-	/// This function will fail if the same contract is present
-	/// on the contract call stack.
-	fn restore_to(
+	/// The code can't be removed as long as the contract depends on it, which makes it safe
+	/// to delegate calls to it. The dependency is removed when the contract is terminated.
+	fn add_delegate_dependency(
 		&mut self,
-		dest: AccountIdOf<Self::T>,
 		code_hash: CodeHash<Self::T>,
-		rent_allowance: BalanceOf<Self::T>,
-		delta: Vec<StorageKey>,
 	) -> Result<(), DispatchError>;
 
+	/// Remove a dependency previously added by `add_delegate_dependency`.
+	///
+	/// The code is removed if no contract uses it anymore.
+	fn remove_delegate_dependency(
+		&mut self,
+		code_hash: CodeHash<Self::T>,
+	) -> Result<(), DispatchError>;
+
+	/// Returns the number of codes the current contract depends on.
+	fn delegate_dependencies_count(&self) -> u32;
+
 	/// Returns a reference to the account id of the caller.
 	fn caller(&self) -> &AccountIdOf<Self::T>;
 
+	/// Returns `true` if the caller is the origin of the call stack (the signer of the
+	/// transaction) and not a contract.
+	fn caller_is_origin(&self) -> bool;
+
+	/// Returns `true` if the specified account is a contract.
+	fn is_contract(&self, address: &AccountIdOf<Self::T>) -> bool;
+
+	/// Returns the code hash of the contract at the specified address or `None` if
+	/// `address` is not a contract.
+	fn code_hash(&self, address: &AccountIdOf<Self::T>) -> Option<CodeHash<Self::T>>;
+
 	/// Returns a reference to the account id of the current contract.
 	fn address(&self) -> &AccountIdOf<Self::T>;
 
@@ -142,9 +181,6 @@ pub trait Ext {
 	/// Returns the minimum balance that is required for creating an account.
 	fn minimum_balance(&self) -> BalanceOf<Self::T>;
 
-	/// Returns the deposit required to create a tombstone upon contract eviction.
-	fn tombstone_deposit(&self) -> BalanceOf<Self::T>;
-
 	/// Returns a random number for the current block with the given subject.
 	fn random(&self, subject: &[u8]) -> SeedOf<Self::T>;
 
@@ -153,12 +189,6 @@ pub trait Ext {
 	/// There should not be any duplicates in `topics`.
 	fn deposit_event(&mut self, topics: Vec<TopicOf<Self::T>>, data: Vec<u8>);
 
-	/// Set rent allowance of the contract
-	fn set_rent_allowance(&mut self, rent_allowance: BalanceOf<Self::T>);
-
-	/// Rent allowance of the contract
-	fn rent_allowance(&self) -> BalanceOf<Self::T>;
-
 	/// Returns the current block number.
 	fn block_number(&self) -> BlockNumberOf<Self::T>;
 
@@ -167,6 +197,29 @@ pub trait Ext {
 
 	/// Returns the price for the specified amount of weight.
 	fn get_weight_price(&self, weight: Weight) -> BalanceOf<Self::T>;
+
+	/// Append a string to the debug buffer.
+	///
+	/// It is added as-is without any additional new line.
+	///
+	/// This is a no-op if debug message recording is disabled which is always the case
+	/// when the code is executing on-chain.
+	///
+	/// Returns `true` if debug message recording is enabled. Otherwise `false` is returned.
+	fn append_debug_buffer(&mut self, msg: &str) -> bool;
+
+	/// Recovers the compressed ECDSA public key from a signature and a message hash.
+	///
+	/// The signature is expected in RSV format. Returns `Err` if the recovery failed.
+	fn ecdsa_recover(&self, signature: &[u8; 65], message_hash: &[u8; 32]) -> Result<[u8; 33], ()>;
+
+	/// Returns the Ethereum address of the given compressed ECDSA public key.
+	///
+	/// Returns `Err` if the supplied bytes do not represent a valid public key.
+	fn ecdsa_to_eth_address(&self, pk: &[u8; 33]) -> Result<[u8; 20], ()>;
+
+	/// Verifies a sr25519 signature of the given message.
+	fn sr25519_verify(&self, signature: &[u8; 64], message: &[u8], pub_key: &[u8; 32]) -> bool;
 }
 
 /// Loader is a companion of the `Vm` trait. It loads an appropriate abstract
@@ -180,6 +233,14 @@ pub trait Loader<T: Config> {
 	/// Load the main portion of the code specified by the `code_hash`. This executable
 	/// is called for each call to a contract.
 	fn load_main(&self, code_hash: &CodeHash<T>) -> Result<Self::Executable, &'static str>;
+	/// Register a new contract as user of the code specified by `code_hash`.
+	///
+	/// Fails if no code is stored under `code_hash`.
+	fn add_user(&self, code_hash: &CodeHash<T>) -> DispatchResult;
+	/// Unregister a terminated contract as user of the code specified by `code_hash`.
+	///
+	/// The code is removed when it has no users left.
+	fn remove_user(&self, code_hash: &CodeHash<T>);
 }
 
 /// A trait that represent a virtual machine.
@@ -212,6 +273,14 @@ pub struct ExecutionContext<'a, T: Config + 'a, V, L> {
 	pub loader: &'a L,
 	pub timestamp: MomentOf<T>,
 	pub block_number: T::BlockNumber,
+	pub storage_meter: Meter<T>,
+	/// Messages passed to `seal_debug_message`. `None` if recording them is disabled.
+	pub debug_message: Option<Vec<u8>>,
+	/// Events deposited by contracts. `None` if recording them is disabled.
+	pub events: Option<Vec<ContractEvent<T::AccountId, T::Hash>>>,
+	/// Whether the call this context is currently performing allows reentry into
+	/// `self_account`. Only `false` while such a call is in progress.
+	allows_reentry: bool,
 }
 
 impl<'a, T, E, V, L> ExecutionContext<'a, T, V, L>
@@ -224,11 +293,19 @@ where
 	/// Create the top level execution context.
 	///
 	/// The specified `origin` address will be used as `sender` for. The `origin` must be a regular
-	/// account (not a contract).
-	pub fn top_level(origin: T::AccountId, cfg: &'a ConfigCache<T>, vm: &'a V, loader: &'a L) -> Self {
+	/// account (not a contract). It pays the storage deposit required by the execution up to
+	/// `storage_deposit_limit`.
+	pub fn top_level(
+		origin: T::AccountId,
+		storage_deposit_limit: Option<BalanceOf<T>>,
+		cfg: &'a ConfigCache<T>,
+		vm: &'a V,
+		loader: &'a L,
+	) -> Self {
 		ExecutionContext {
 			caller: None,
 			self_trie_id: None,
+			storage_meter: Meter::new(origin.clone(), storage_deposit_limit),
 			self_account: origin,
 			depth: 0,
 			config: &cfg,
@@ -236,6 +313,9 @@ where
 			loader: &loader,
 			timestamp: T::Time::now(),
 			block_number: <frame_system::Module<T>>::block_number(),
+			debug_message: None,
+			events: None,
+			allows_reentry: true,
 		}
 	}
 
@@ -252,10 +332,17 @@ where
 			loader: self.loader,
 			timestamp: self.timestamp.clone(),
 			block_number: self.block_number.clone(),
+			storage_meter: self.storage_meter.nested(),
+			debug_message: self.debug_message.as_ref().map(|_| Vec::new()),
+			events: self.events.as_ref().map(|_| Vec::new()),
+			allows_reentry: true,
 		}
 	}
 
 	/// Make a call to the specified address, optionally transferring some funds.
+	///
+	/// Fails with `ReentranceDenied` if `dest` is on the call stack and did not allow to be
+	/// reentered when making the call that is currently in progress.
 	pub fn call(
 		&mut self,
 		dest: T::AccountId,
@@ -267,16 +354,13 @@ where
 			Err(Error::<T>::MaxCallDepthReached)?
 		}
 
-		// Assumption: `collect` doesn't collide with overlay because
-		// `collect` will be done on first call and destination contract and balance
-		// cannot be changed before the first call
 		// We do not allow 'calling' plain accounts. For transfering value
 		// `seal_transfer` must be used.
-		let contract = if let Some(ContractInfo::Alive(info)) = Rent::<T>::collect(&dest) {
-			info
-		} else {
-			Err(Error::<T>::NotCallable)?
-		};
+		let contract = ContractInfoOf::<T>::get(&dest).ok_or(Error::<T>::NotCallable)?;
+
+		if !self.is_reentry_allowed(&dest) {
+			Err(Error::<T>::ReentranceDenied)?
+		}
 
 		let transactor_kind = self.transactor_kind();
 		let caller = self.self_account.clone();
@@ -335,6 +419,7 @@ where
 					.expect("the nested context always has to have self_trie_id"),
 				code_hash.clone()
 			)?;
+			nested.loader.add_user(code_hash)?;
 
 			// Send funds unconditionally here. If the `endowment` is below existential_deposit
 			// then error will be returned here.
@@ -358,7 +443,6 @@ where
 				).map_err(|e| ExecError { error: e.error, origin: ErrorOrigin::Callee })?;
 
 			// We need each contract that exists to be above the subsistence threshold
-			// in order to keep up the guarantuee that we always leave a tombstone behind
 			// with the exception of a contract that called `seal_terminate`.
 			if T::Currency::total_balance(&dest) < nested.config.subsistence_threshold() {
 				Err(Error::<T>::NewContractNotFunded)?
@@ -373,6 +457,38 @@ where
 		Ok((dest, output))
 	}
 
+	/// Execute the code identified by `code_hash` in the context of the current contract.
+	///
+	/// `caller` and `value` are passed through unchanged from the calling frame. No funds are
+	/// transferred.
+	pub fn delegate_call(
+		&mut self,
+		caller: T::AccountId,
+		value: BalanceOf<T>,
+		gas_meter: &mut GasMeter<T>,
+		code_hash: CodeHash<T>,
+		input_data: Vec<u8>,
+	) -> ExecResult {
+		if self.depth == self.config.max_depth as usize {
+			Err(Error::<T>::MaxCallDepthReached)?
+		}
+
+		let dest = self.self_account.clone();
+		let trie_id = self.self_trie_id.clone().ok_or(Error::<T>::NotCallable)?;
+
+		self.with_nested_context(dest, trie_id, |nested| {
+			let executable = nested.loader.load_main(&code_hash)
+				.map_err(|_| Error::<T>::CodeNotFound)?;
+			let output = nested.vm.execute(
+				&executable,
+				nested.new_call_context(caller, value),
+				input_data,
+				gas_meter,
+			).map_err(|e| ExecError { error: e.error, origin: ErrorOrigin::Callee })?;
+			Ok(output)
+		})
+	}
+
 	fn new_call_context<'b>(
 		&'b mut self,
 		caller: T::AccountId,
@@ -390,19 +506,45 @@ where
 	}
 
 	/// Execute the given closure within a nested execution context.
+	///
+	/// When the closure succeeds the storage deposit of `dest` is settled and the storage
+	/// meter and the recorded events of the nested context are absorbed into this context.
+	/// Debug messages are kept even if the nested context is rolled back.
 	fn with_nested_context<F>(&mut self, dest: T::AccountId, trie_id: TrieId, func: F)
 		-> ExecResult
 		where F: FnOnce(&mut ExecutionContext<T, V, L>) -> ExecResult
 	{
 		use frame_support::storage::TransactionOutcome::*;
-		let mut nested = self.nested(dest, trie_id);
-		frame_support::storage::with_transaction(|| {
-			let output = func(&mut nested);
+		let mut nested = self.nested(dest.clone(), trie_id);
+		let (output, committed) = frame_support::storage::with_transaction(|| {
+			let output = func(&mut nested).and_then(|output| {
+				if !output.flags.contains(ReturnFlags::REVERT) {
+					nested.storage_meter.charge(&dest)?;
+				}
+				Ok(output)
+			});
 			match output {
-				Ok(ref rv) if !rv.flags.contains(ReturnFlags::REVERT) => Commit(output),
-				_ => Rollback(output),
+				Ok(ref rv) if !rv.flags.contains(ReturnFlags::REVERT) => {
+					let deposit = nested.storage_meter.deposit();
+					let storage_bytes = nested.storage_meter.storage_bytes();
+					Commit((output, Some((deposit, storage_bytes))))
+				},
+				_ => Rollback((output, None)),
 			}
-		})
+		});
+		let debug_message = nested.debug_message.take();
+		let events = nested.events.take();
+		if let Some((deposit, storage_bytes)) = committed {
+			self.storage_meter.absorb(deposit);
+			self.storage_meter.record_storage_bytes(storage_bytes);
+			if let (Some(buffer), Some(events)) = (self.events.as_mut(), events) {
+				buffer.extend(events);
+			}
+		}
+		if let (Some(buffer), Some(message)) = (self.debug_message.as_mut(), debug_message) {
+			buffer.extend(message);
+		}
+		output
 	}
 
 	/// Returns whether a contract, identified by address, is currently live in the execution
@@ -412,6 +554,21 @@ where
 			self.caller.map_or(false, |caller| caller.is_live(account))
 	}
 
+	/// Returns whether `account` can be called without violating the reentrancy
+	/// restrictions of any context in the execution stack.
+	fn is_reentry_allowed(&self, account: &T::AccountId) -> bool {
+		(&self.self_account != account || self.allows_reentry) &&
+			self.caller.map_or(true, |caller| caller.is_reentry_allowed(account))
+	}
+
+	/// Returns whether `account` is the origin of the execution stack.
+	fn is_origin(&self, account: &T::AccountId) -> bool {
+		match self.caller {
+			Some(caller) => caller.is_origin(account),
+			None => &self.self_account == account,
+		}
+	}
+
 	fn transactor_kind(&self) -> TransactorKind {
 		if self.depth == 0 {
 			debug_assert!(self.self_trie_id.is_none());
@@ -435,8 +592,7 @@ enum TransferCause {
 ///
 /// We only allow allow for draining all funds of the sender if `cause` is
 /// is specified as `Terminate`. Otherwise, any transfer that would bring the sender below the
-/// subsistence threshold (for contracts) or the existential deposit (for plain accounts)
-/// results in an error.
+/// subsistence threshold results in an error.
 fn transfer<'a, T: Config, V: Vm<T>, L: Loader<T>>(
 	cause: TransferCause,
 	origin: TransactorKind,
@@ -452,7 +608,7 @@ where
 	use self::TransactorKind::*;
 
 	// Only seal_terminate is allowed to bring the sender below the subsistence
-	// threshold or even existential deposit.
+	// threshold.
 	let existence_requirement = match (cause, origin) {
 		(Terminate, _) => ExistenceRequirement::AllowDeath,
 		(_, Contract) => {
@@ -517,15 +673,14 @@ where
 				expect can't fail;\
 				qed",
 		);
-		if let Err(storage::ContractAbsentError) =
-			Storage::<T>::write(&self.ctx.self_account, trie_id, &key, value)
-		{
-			panic!(
+		match Storage::<T>::write(&self.ctx.self_account, trie_id, &key, value) {
+			Ok(storage_bytes) => self.ctx.storage_meter.record_storage_bytes(storage_bytes),
+			Err(storage::ContractAbsentError) => panic!(
 				"the contract must be in the alive state within the `CallContext`;\
 				the contract cannot be absent in storage;
 				write cannot return `None`;
 				qed"
-			);
+			),
 		}
 	}
 
@@ -560,12 +715,20 @@ where
 		beneficiary: &AccountIdOf<Self::T>,
 	) -> Result<(), DispatchError> {
 		let self_id = self.ctx.self_account.clone();
-		let value = T::Currency::free_balance(&self_id);
 		if let Some(caller_ctx) = self.ctx.caller {
 			if caller_ctx.is_live(&self_id) {
 				return Err(Error::<T>::ReentranceDenied.into());
 			}
 		}
+		let info = ContractInfoOf::<T>::get(&self_id).expect(
+			"this function is only invoked by in the context of a contract;\
+				a contract has a contract info;\
+				this can't be None; qed",
+		);
+		// The deposit is refunded to the origin first so that it is not transferred to the
+		// beneficiary together with the rest of the contract's balance.
+		self.ctx.storage_meter.terminate(&self_id, &info)?;
+		let value = T::Currency::free_balance(&self_id);
 		transfer(
 			TransferCause::Terminate,
 			TransactorKind::Contract,
@@ -579,7 +742,13 @@ where
 				a contract has a trie id;\
 				this can't be None; qed",
 		);
-		Storage::<T>::destroy_contract(&self_id, self_trie_id);
+		Storage::<T>::queue_trie_for_deletion(self_trie_id)?;
+		self.ctx.storage_meter.record_storage_bytes(-i64::from(info.storage_size));
+		ContractInfoOf::<T>::remove(&self_id);
+		self.ctx.loader.remove_user(&info.code_hash);
+		for (code_hash, ()) in DelegateDependencies::<T>::drain_prefix(&self_id) {
+			self.ctx.loader.remove_user(&code_hash);
+		}
 		Ok(())
 	}
 
@@ -589,42 +758,76 @@ where
 		value: BalanceOf<T>,
 		gas_meter: &mut GasMeter<T>,
 		input_data: Vec<u8>,
+		allows_reentry: bool,
 	) -> ExecResult {
-		self.ctx.call(to.clone(), value, gas_meter, input_data)
+		self.ctx.allows_reentry = allows_reentry;
+		let result = self.ctx.call(to.clone(), value, gas_meter, input_data);
+		self.ctx.allows_reentry = true;
+		result
 	}
 
-	fn restore_to(
+	fn delegate_call(
 		&mut self,
-		dest: AccountIdOf<Self::T>,
-		code_hash: CodeHash<Self::T>,
-		rent_allowance: BalanceOf<Self::T>,
-		delta: Vec<StorageKey>,
-	) -> Result<(), DispatchError> {
-		if let Some(caller_ctx) = self.ctx.caller {
-			if caller_ctx.is_live(&self.ctx.self_account) {
-				return Err(Error::<T>::ReentranceDenied.into());
-			}
-		}
+		code_hash: CodeHash<T>,
+		gas_meter: &mut GasMeter<T>,
+		input_data: Vec<u8>,
+	) -> ExecResult {
+		let caller = self.caller.clone();
+		self.ctx.delegate_call(caller, self.value_transferred, gas_meter, code_hash, input_data)
+	}
 
-		let result = Rent::<T>::restore_to(
-			self.ctx.self_account.clone(),
-			dest.clone(),
-			code_hash.clone(),
-			rent_allowance,
-			delta,
+	fn set_code_hash(&mut self, hash: CodeHash<T>) -> Result<(), DispatchError> {
+		let self_id = self.ctx.self_account.clone();
+		let mut info = ContractInfoOf::<T>::get(&self_id).expect(
+			"this function is only invoked by in the context of a contract;\
+				a contract has a contract info;\
+				this can't be None; qed",
 		);
-		if let Ok(_) = result {
-			deposit_event::<Self::T>(
-				vec![],
-				RawEvent::Restored(
-					self.ctx.self_account.clone(),
-					dest,
-					code_hash,
-					rent_allowance,
-				),
-			);
-		}
-		result
+		// The new code is pinned before the old one is released so that setting the
+		// current code hash again cannot remove the code.
+		self.ctx.loader.add_user(&hash)?;
+		self.ctx.loader.remove_user(&info.code_hash);
+		let prev_hash = sp_std::mem::replace(&mut info.code_hash, hash.clone());
+		ContractInfoOf::<T>::insert(&self_id, info);
+		deposit_event::<T>(vec![], RawEvent::ContractCodeUpdated(self_id, hash, prev_hash));
+		Ok(())
+	}
+
+	fn add_delegate_dependency(&mut self, code_hash: CodeHash<T>) -> Result<(), DispatchError> {
+		let self_id = &self.ctx.self_account;
+		let info = ContractInfoOf::<T>::get(self_id).expect(
+			"this function is only invoked by in the context of a contract;\
+				a contract has a contract info;\
+				this can't be None; qed",
+		);
+		ensure!(info.code_hash != code_hash, Error::<T>::CannotAddSelfAsDelegateDependency);
+		ensure!(
+			!DelegateDependencies::<T>::contains_key(self_id, &code_hash),
+			Error::<T>::DelegateDependencyAlreadyExists,
+		);
+		let limit = self.ctx.config.schedule.limits.delegate_dependencies;
+		ensure!(
+			self.delegate_dependencies_count() < limit,
+			Error::<T>::MaxDelegateDependenciesReached,
+		);
+		self.ctx.loader.add_user(&code_hash)?;
+		DelegateDependencies::<T>::insert(&self.ctx.self_account, &code_hash, ());
+		Ok(())
+	}
+
+	fn remove_delegate_dependency(&mut self, code_hash: CodeHash<T>) -> Result<(), DispatchError> {
+		let self_id = &self.ctx.self_account;
+		ensure!(
+			DelegateDependencies::<T>::contains_key(self_id, &code_hash),
+			Error::<T>::DelegateDependencyNotFound,
+		);
+		DelegateDependencies::<T>::remove(self_id, &code_hash);
+		self.ctx.loader.remove_user(&code_hash);
+		Ok(())
+	}
+
+	fn delegate_dependencies_count(&self) -> u32 {
+		DelegateDependencies::<T>::iter_prefix_values(&self.ctx.self_account).count() as u32
 	}
 
 	fn address(&self) -> &T::AccountId {
@@ -635,6 +838,18 @@ where
 		&self.caller
 	}
 
+	fn caller_is_origin(&self) -> bool {
+		self.ctx.is_origin(&self.caller)
+	}
+
+	fn is_contract(&self, address: &T::AccountId) -> bool {
+		ContractInfoOf::<T>::contains_key(address)
+	}
+
+	fn code_hash(&self, address: &T::AccountId) -> Option<CodeHash<T>> {
+		ContractInfoOf::<T>::get(address).map(|info| info.code_hash)
+	}
+
 	fn balance(&self) -> BalanceOf<T> {
 		T::Currency::free_balance(&self.ctx.self_account)
 	}
@@ -655,33 +870,20 @@ where
 		self.ctx.config.existential_deposit
 	}
 
-	fn tombstone_deposit(&self) -> BalanceOf<T> {
-		self.ctx.config.tombstone_deposit
-	}
-
 	fn deposit_event(&mut self, topics: Vec<T::Hash>, data: Vec<u8>) {
+		if let Some(events) = &mut self.ctx.events {
+			events.push(ContractEvent {
+				contract: self.ctx.self_account.clone(),
+				topics: topics.clone(),
+				data: data.clone(),
+			});
+		}
 		deposit_event::<Self::T>(
 			topics,
 			RawEvent::ContractExecution(self.ctx.self_account.clone(), data)
 		);
 	}
 
-	fn set_rent_allowance(&mut self, rent_allowance: BalanceOf<T>) {
-		if let Err(storage::ContractAbsentError) =
-			Storage::<T>::set_rent_allowance(&self.ctx.self_account, rent_allowance)
-		{
-			panic!(
-				"`self_account` points to an alive contract within the `CallContext`;
-					set_rent_allowance cannot return `Err`; qed"
-			);
-		}
-	}
-
-	fn rent_allowance(&self) -> BalanceOf<T> {
-		Storage::<T>::rent_allowance(&self.ctx.self_account)
-			.unwrap_or_else(|_| <BalanceOf<T>>::max_value()) // Must never be triggered actually
-	}
-
 	fn block_number(&self) -> T::BlockNumber { self.block_number }
 
 	fn max_value_size(&self) -> u32 {
@@ -691,6 +893,36 @@ where
 	fn get_weight_price(&self, weight: Weight) -> BalanceOf<Self::T> {
 		T::WeightPrice::convert(weight)
 	}
+
+	fn append_debug_buffer(&mut self, msg: &str) -> bool {
+		if let Some(buffer) = &mut self.ctx.debug_message {
+			buffer.extend(msg.as_bytes());
+			true
+		} else {
+			false
+		}
+	}
+
+	fn ecdsa_recover(&self, signature: &[u8; 65], message_hash: &[u8; 32]) -> Result<[u8; 33], ()> {
+		sp_io::crypto::secp256k1_ecdsa_recover_compressed(signature, message_hash).map_err(|_| ())
+	}
+
+	fn ecdsa_to_eth_address(&self, pk: &[u8; 33]) -> Result<[u8; 20], ()> {
+		let pk = secp256k1::PublicKey::parse_compressed(pk).map_err(|_| ())?;
+		// The uncompressed key is prefixed by a tag byte which is not part of the hash.
+		let hash = sp_io::hashing::keccak_256(&pk.serialize()[1..]);
+		let mut address = [0u8; 20];
+		address.copy_from_slice(&hash[12..]);
+		Ok(address)
+	}
+
+	fn sr25519_verify(&self, signature: &[u8; 64], message: &[u8], pub_key: &[u8; 32]) -> bool {
+		sp_io::crypto::sr25519_verify(
+			&sr25519::Signature::from_raw(*signature),
+			message,
+			&sr25519::Public::from_raw(*pub_key),
+		)
+	}
 }
 
 fn deposit_event<T: Config>(
@@ -711,7 +943,7 @@ fn deposit_event<T: Config>(
 #[cfg(test)]
 mod tests {
 	use super::{
-		BalanceOf, Event, ExecResult, ExecutionContext, Ext, Loader,
+		Event, ExecResult, ExecutionContext, Ext, Loader,
 		RawEvent, Vm, ReturnFlags, ExecError, ErrorOrigin, AccountIdOf,
 	};
 	use crate::{
@@ -724,6 +956,7 @@ mod tests {
 	};
 	use crate::tests::test_utils::{place_contract, set_balance, get_balance};
 	use sp_runtime::DispatchError;
+	use frame_support::{dispatch::DispatchResult, ensure};
 	use assert_matches::assert_matches;
 	use std::{cell::RefCell, collections::HashMap, marker::PhantomData, rc::Rc};
 
@@ -802,6 +1035,11 @@ mod tests {
 				.cloned()
 				.ok_or_else(|| "code not found")
 		}
+		fn add_user(&self, code_hash: &CodeHash<Test>) -> DispatchResult {
+			ensure!(self.map.contains_key(code_hash), Error::<Test>::CodeNotFound);
+			Ok(())
+		}
+		fn remove_user(&self, _code_hash: &CodeHash<Test>) {}
 	}
 
 	impl<'a> Vm<Test> for MockVm<'a> {
@@ -844,7 +1082,7 @@ mod tests {
 
 		ExtBuilder::default().build().execute_with(|| {
 			let cfg = ConfigCache::preload();
-			let mut ctx = ExecutionContext::top_level(ALICE, &cfg, &vm, &loader);
+			let mut ctx = ExecutionContext::top_level(ALICE, None, &cfg, &vm, &loader);
 			place_contract(&BOB, exec_ch);
 
 			assert_matches!(
@@ -868,7 +1106,7 @@ mod tests {
 
 		ExtBuilder::default().build().execute_with(|| {
 			let cfg = ConfigCache::preload();
-			let mut ctx = ExecutionContext::top_level(origin.clone(), &cfg, &vm, &loader);
+			let mut ctx = ExecutionContext::top_level(origin.clone(), None, &cfg, &vm, &loader);
 			set_balance(&origin, 100);
 			set_balance(&dest, 0);
 
@@ -901,7 +1139,7 @@ mod tests {
 
 		ExtBuilder::default().build().execute_with(|| {
 			let cfg = ConfigCache::preload();
-			let mut ctx = ExecutionContext::top_level(origin.clone(), &cfg, &vm, &loader);
+			let mut ctx = ExecutionContext::top_level(origin.clone(), None, &cfg, &vm, &loader);
 			place_contract(&BOB, return_ch);
 			set_balance(&origin, 100);
 			set_balance(&dest, 0);
@@ -931,7 +1169,7 @@ mod tests {
 
 		ExtBuilder::default().build().execute_with(|| {
 			let cfg = ConfigCache::preload();
-			let mut ctx = ExecutionContext::top_level(origin.clone(), &cfg, &vm, &loader);
+			let mut ctx = ExecutionContext::top_level(origin.clone(), None, &cfg, &vm, &loader);
 			set_balance(&origin, 0);
 
 			let result = super::transfer(
@@ -967,7 +1205,7 @@ mod tests {
 
 		ExtBuilder::default().build().execute_with(|| {
 			let cfg = ConfigCache::preload();
-			let mut ctx = ExecutionContext::top_level(origin, &cfg, &vm, &loader);
+			let mut ctx = ExecutionContext::top_level(origin, None, &cfg, &vm, &loader);
 			place_contract(&BOB, return_ch);
 
 			let result = ctx.call(
@@ -998,7 +1236,7 @@ mod tests {
 
 		ExtBuilder::default().build().execute_with(|| {
 			let cfg = ConfigCache::preload();
-			let mut ctx = ExecutionContext::top_level(origin, &cfg, &vm, &loader);
+			let mut ctx = ExecutionContext::top_level(origin, None, &cfg, &vm, &loader);
 			place_contract(&BOB, return_ch);
 
 			let result = ctx.call(
@@ -1026,7 +1264,7 @@ mod tests {
 		// This one tests passing the input data into a contract via call.
 		ExtBuilder::default().build().execute_with(|| {
 			let cfg = ConfigCache::preload();
-			let mut ctx = ExecutionContext::top_level(ALICE, &cfg, &vm, &loader);
+			let mut ctx = ExecutionContext::top_level(ALICE, None, &cfg, &vm, &loader);
 			place_contract(&BOB, input_data_ch);
 
 			let result = ctx.call(
@@ -1051,7 +1289,7 @@ mod tests {
 		// This one tests passing the input data into a contract via instantiate.
 		ExtBuilder::default().build().execute_with(|| {
 			let cfg = ConfigCache::preload();
-			let mut ctx = ExecutionContext::top_level(ALICE, &cfg, &vm, &loader);
+			let mut ctx = ExecutionContext::top_level(ALICE, None, &cfg, &vm, &loader);
 
 			set_balance(&ALICE, 100);
 
@@ -1077,7 +1315,7 @@ mod tests {
 		let mut loader = MockLoader::empty();
 		let recurse_ch = loader.insert(|ctx| {
 			// Try to call into yourself.
-			let r = ctx.ext.call(&BOB, 0, ctx.gas_meter, vec![]);
+			let r = ctx.ext.call(&BOB, 0, ctx.gas_meter, vec![], true);
 
 			let mut reached_bottom = reached_bottom.borrow_mut();
 			if !*reached_bottom {
@@ -1098,7 +1336,7 @@ mod tests {
 
 		ExtBuilder::default().build().execute_with(|| {
 			let cfg = ConfigCache::preload();
-			let mut ctx = ExecutionContext::top_level(ALICE, &cfg, &vm, &loader);
+			let mut ctx = ExecutionContext::top_level(ALICE, None, &cfg, &vm, &loader);
 			set_balance(&BOB, 1);
 			place_contract(&BOB, recurse_ch);
 
@@ -1130,7 +1368,7 @@ mod tests {
 
 			// Call into CHARLIE contract.
 			assert_matches!(
-				ctx.ext.call(&CHARLIE, 0, ctx.gas_meter, vec![]),
+				ctx.ext.call(&CHARLIE, 0, ctx.gas_meter, vec![], false),
 				Ok(_)
 			);
 			exec_success()
@@ -1144,7 +1382,7 @@ mod tests {
 		ExtBuilder::default().build().execute_with(|| {
 			let cfg = ConfigCache::preload();
 
-			let mut ctx = ExecutionContext::top_level(origin.clone(), &cfg, &vm, &loader);
+			let mut ctx = ExecutionContext::top_level(origin.clone(), None, &cfg, &vm, &loader);
 			place_contract(&dest, bob_ch);
 			place_contract(&CHARLIE, charlie_ch);
 
@@ -1173,7 +1411,7 @@ mod tests {
 
 			// Call into charlie contract.
 			assert_matches!(
-				ctx.ext.call(&CHARLIE, 0, ctx.gas_meter, vec![]),
+				ctx.ext.call(&CHARLIE, 0, ctx.gas_meter, vec![], false),
 				Ok(_)
 			);
 			exec_success()
@@ -1185,7 +1423,7 @@ mod tests {
 
 		ExtBuilder::default().build().execute_with(|| {
 			let cfg = ConfigCache::preload();
-			let mut ctx = ExecutionContext::top_level(ALICE, &cfg, &vm, &loader);
+			let mut ctx = ExecutionContext::top_level(ALICE, None, &cfg, &vm, &loader);
 			place_contract(&BOB, bob_ch);
 			place_contract(&CHARLIE, charlie_ch);
 
@@ -1209,7 +1447,7 @@ mod tests {
 
 		ExtBuilder::default().existential_deposit(15).build().execute_with(|| {
 			let cfg = ConfigCache::preload();
-			let mut ctx = ExecutionContext::top_level(ALICE, &cfg, &vm, &loader);
+			let mut ctx = ExecutionContext::top_level(ALICE, None, &cfg, &vm, &loader);
 
 			assert_matches!(
 				ctx.instantiate(
@@ -1235,7 +1473,7 @@ mod tests {
 
 		ExtBuilder::default().existential_deposit(15).build().execute_with(|| {
 			let cfg = ConfigCache::preload();
-			let mut ctx = ExecutionContext::top_level(ALICE, &cfg, &vm, &loader);
+			let mut ctx = ExecutionContext::top_level(ALICE, None, &cfg, &vm, &loader);
 			set_balance(&ALICE, 1000);
 
 			let instantiated_contract_address = assert_matches!(
@@ -1269,7 +1507,7 @@ mod tests {
 
 		ExtBuilder::default().existential_deposit(15).build().execute_with(|| {
 			let cfg = ConfigCache::preload();
-			let mut ctx = ExecutionContext::top_level(ALICE, &cfg, &vm, &loader);
+			let mut ctx = ExecutionContext::top_level(ALICE, None, &cfg, &vm, &loader);
 			set_balance(&ALICE, 1000);
 
 			let instantiated_contract_address = assert_matches!(
@@ -1316,7 +1554,7 @@ mod tests {
 
 		ExtBuilder::default().existential_deposit(15).build().execute_with(|| {
 			let cfg = ConfigCache::preload();
-			let mut ctx = ExecutionContext::top_level(ALICE, &cfg, &vm, &loader);
+			let mut ctx = ExecutionContext::top_level(ALICE, None, &cfg, &vm, &loader);
 			set_balance(&ALICE, 1000);
 			set_balance(&BOB, 100);
 			place_contract(&BOB, instantiator_ch);
@@ -1369,7 +1607,7 @@ mod tests {
 
 		ExtBuilder::default().existential_deposit(15).build().execute_with(|| {
 			let cfg = ConfigCache::preload();
-			let mut ctx = ExecutionContext::top_level(ALICE, &cfg, &vm, &loader);
+			let mut ctx = ExecutionContext::top_level(ALICE, None, &cfg, &vm, &loader);
 			set_balance(&ALICE, 1000);
 			set_balance(&BOB, 100);
 			place_contract(&BOB, instantiator_ch);
@@ -1401,7 +1639,7 @@ mod tests {
 			.build()
 			.execute_with(|| {
 				let cfg = ConfigCache::preload();
-				let mut ctx = ExecutionContext::top_level(ALICE, &cfg, &vm, &loader);
+				let mut ctx = ExecutionContext::top_level(ALICE, None, &cfg, &vm, &loader);
 				set_balance(&ALICE, 1000);
 
 				assert_eq!(
@@ -1423,29 +1661,344 @@ mod tests {
 	}
 
 	#[test]
-	fn rent_allowance() {
+	fn delegate_call_runs_in_the_context_of_the_caller() {
 		let vm = MockVm::new();
+		let witnessed = RefCell::new(None::<(AccountIdOf<Test>, AccountIdOf<Test>, u64)>);
+
 		let mut loader = MockLoader::empty();
-		let rent_allowance_ch = loader.insert(|ctx| {
-			assert_eq!(ctx.ext.rent_allowance(), <BalanceOf<Test>>::max_value());
-			ctx.ext.set_rent_allowance(10);
-			assert_eq!(ctx.ext.rent_allowance(), 10);
+		let lib_ch = loader.insert(|ctx| {
+			*witnessed.borrow_mut() = Some((
+				ctx.ext.caller().clone(),
+				ctx.ext.address().clone(),
+				ctx.ext.value_transferred(),
+			));
+			ctx.ext.set_storage([1; 32], Some(vec![1, 2, 3]));
+			exec_success()
+		});
+		let bob_ch = loader.insert(move |ctx| {
+			assert_matches!(
+				ctx.ext.delegate_call(lib_ch, ctx.gas_meter, vec![]),
+				Ok(_)
+			);
+			assert_eq!(ctx.ext.get_storage(&[1; 32]), Some(vec![1, 2, 3]));
 			exec_success()
 		});
 
 		ExtBuilder::default().build().execute_with(|| {
 			let cfg = ConfigCache::preload();
-			let mut ctx = ExecutionContext::top_level(ALICE, &cfg, &vm, &loader);
+			let mut ctx = ExecutionContext::top_level(ALICE, None, &cfg, &vm, &loader);
 			set_balance(&ALICE, 100);
+			place_contract(&BOB, bob_ch);
 
-			let result = ctx.instantiate(
-				cfg.subsistence_threshold(),
-				&mut GasMeter::<Test>::new(GAS_LIMIT),
-				&rent_allowance_ch,
-				vec![],
-				&[],
+			assert_matches!(
+				ctx.call(BOB, 55, &mut GasMeter::<Test>::new(GAS_LIMIT), vec![]),
+				Ok(_)
 			);
-			assert_matches!(result, Ok(_));
+			assert_eq!(get_balance(&BOB), 55);
+		});
+
+		assert_eq!(&*witnessed.borrow(), &Some((ALICE, BOB, 55)));
+	}
+
+	#[test]
+	fn delegate_call_to_missing_code_fails() {
+		let vm = MockVm::new();
+
+		let mut loader = MockLoader::empty();
+		let bob_ch = loader.insert(|ctx| {
+			assert_eq!(
+				ctx.ext.delegate_call(
+					<Test as frame_system::Config>::Hash::repeat_byte(0xff),
+					ctx.gas_meter,
+					vec![],
+				),
+				Err(Error::<Test>::CodeNotFound.into())
+			);
+			exec_success()
+		});
+
+		ExtBuilder::default().build().execute_with(|| {
+			let cfg = ConfigCache::preload();
+			let mut ctx = ExecutionContext::top_level(ALICE, None, &cfg, &vm, &loader);
+			place_contract(&BOB, bob_ch);
+
+			assert_matches!(
+				ctx.call(BOB, 0, &mut GasMeter::<Test>::new(GAS_LIMIT), vec![]),
+				Ok(_)
+			);
+		});
+	}
+
+	#[test]
+	fn delegated_code_cannot_terminate() {
+		let vm = MockVm::new();
+
+		let mut loader = MockLoader::empty();
+		let lib_ch = loader.insert(|ctx| {
+			assert_eq!(
+				ctx.ext.terminate(&CHARLIE),
+				Err(Error::<Test>::ReentranceDenied.into())
+			);
+			exec_success()
+		});
+		let bob_ch = loader.insert(move |ctx| {
+			ctx.ext.delegate_call(lib_ch, ctx.gas_meter, vec![])
+		});
+
+		ExtBuilder::default().build().execute_with(|| {
+			let cfg = ConfigCache::preload();
+			let mut ctx = ExecutionContext::top_level(ALICE, None, &cfg, &vm, &loader);
+			place_contract(&BOB, bob_ch);
+
+			assert_matches!(
+				ctx.call(BOB, 0, &mut GasMeter::<Test>::new(GAS_LIMIT), vec![]),
+				Ok(_)
+			);
+			assert!(crate::ContractInfoOf::<Test>::contains_key(&BOB));
+		});
+	}
+
+	#[test]
+	fn set_code_hash_replaces_code() {
+		let vm = MockVm::new();
+
+		let mut loader = MockLoader::empty();
+		let new_ch = loader.insert(|_ctx| exec_success());
+		let bob_ch = loader.insert(move |ctx| {
+			assert_matches!(ctx.ext.set_code_hash(new_ch), Ok(()));
+			assert_eq!(
+				ctx.ext.set_code_hash(<Test as frame_system::Config>::Hash::repeat_byte(0xff)),
+				Err(Error::<Test>::CodeNotFound.into())
+			);
+			exec_success()
+		});
+
+		ExtBuilder::default().build().execute_with(|| {
+			let cfg = ConfigCache::preload();
+			let mut ctx = ExecutionContext::top_level(ALICE, None, &cfg, &vm, &loader);
+			place_contract(&BOB, bob_ch);
+
+			assert_matches!(
+				ctx.call(BOB, 0, &mut GasMeter::<Test>::new(GAS_LIMIT), vec![]),
+				Ok(_)
+			);
+			assert_eq!(crate::ContractInfoOf::<Test>::get(&BOB).unwrap().code_hash, new_ch);
+			assert_eq!(
+				&events(),
+				&[RawEvent::ContractCodeUpdated(BOB, new_ch, bob_ch)]
+			);
+		});
+	}
+
+	#[test]
+	fn reentrance_is_denied_unless_allowed() {
+		let vm = MockVm::new();
+		let reentered = RefCell::new(Vec::new());
+
+		let mut loader = MockLoader::empty();
+		let bob_ch = loader.insert(|ctx| {
+			// The reentrant call is done.
+			if ctx.input_data.is_empty() {
+				return exec_success();
+			}
+			let allows_reentry = ctx.input_data[0] == 1;
+			assert_matches!(
+				ctx.ext.call(&CHARLIE, 0, ctx.gas_meter, vec![], allows_reentry),
+				Ok(_)
+			);
+			exec_success()
+		});
+		let charlie_ch = loader.insert(|ctx| {
+			let result = ctx.ext.call(&BOB, 0, ctx.gas_meter, vec![], true);
+			reentered.borrow_mut().push(result.map(|_| ()).map_err(|e| e.error));
+			exec_success()
+		});
+
+		ExtBuilder::default().build().execute_with(|| {
+			let cfg = ConfigCache::preload();
+			let mut ctx = ExecutionContext::top_level(ALICE, None, &cfg, &vm, &loader);
+			place_contract(&BOB, bob_ch);
+			place_contract(&CHARLIE, charlie_ch);
+
+			assert_matches!(
+				ctx.call(BOB, 0, &mut GasMeter::<Test>::new(GAS_LIMIT), vec![0]),
+				Ok(_)
+			);
+			assert_matches!(
+				ctx.call(BOB, 0, &mut GasMeter::<Test>::new(GAS_LIMIT), vec![1]),
+				Ok(_)
+			);
+		});
+
+		assert_eq!(
+			&*reentered.borrow(),
+			&[Err(Error::<Test>::ReentranceDenied.into()), Ok(())],
+		);
+	}
+
+	#[test]
+	fn calling_self_is_denied_unless_reentry_allowed() {
+		let vm = MockVm::new();
+
+		let mut loader = MockLoader::empty();
+		let bob_ch = loader.insert(|ctx| {
+			// The recursive call is done.
+			if ctx.input_data.is_empty() {
+				return exec_success();
+			}
+			assert_eq!(
+				ctx.ext.call(&BOB, 0, ctx.gas_meter, vec![], false),
+				Err(Error::<Test>::ReentranceDenied.into())
+			);
+			assert_matches!(ctx.ext.call(&BOB, 0, ctx.gas_meter, vec![], true), Ok(_));
+			exec_success()
+		});
+
+		ExtBuilder::default().build().execute_with(|| {
+			let cfg = ConfigCache::preload();
+			let mut ctx = ExecutionContext::top_level(ALICE, None, &cfg, &vm, &loader);
+			place_contract(&BOB, bob_ch);
+
+			assert_matches!(
+				ctx.call(BOB, 0, &mut GasMeter::<Test>::new(GAS_LIMIT), vec![1]),
+				Ok(_)
+			);
+		});
+	}
+
+	#[test]
+	fn caller_is_origin_returns_proper_values() {
+		let vm = MockVm::new();
+
+		let mut loader = MockLoader::empty();
+		let charlie_ch = loader.insert(|ctx| {
+			// BOB is not the origin of the stack.
+			assert!(!ctx.ext.caller_is_origin());
+			exec_success()
+		});
+		let bob_ch = loader.insert(|ctx| {
+			// ALICE is the origin of the stack.
+			assert!(ctx.ext.caller_is_origin());
+			assert_matches!(ctx.ext.call(&CHARLIE, 0, ctx.gas_meter, vec![], false), Ok(_));
+			exec_success()
+		});
+
+		ExtBuilder::default().build().execute_with(|| {
+			let cfg = ConfigCache::preload();
+			let mut ctx = ExecutionContext::top_level(ALICE, None, &cfg, &vm, &loader);
+			place_contract(&BOB, bob_ch);
+			place_contract(&CHARLIE, charlie_ch);
+
+			assert_matches!(
+				ctx.call(BOB, 0, &mut GasMeter::<Test>::new(GAS_LIMIT), vec![]),
+				Ok(_)
+			);
+		});
+	}
+
+	#[test]
+	fn is_contract_and_code_hash_return_proper_values() {
+		let vm = MockVm::new();
+		let witnessed_code_hash = RefCell::new(None);
+
+		let mut loader = MockLoader::empty();
+		let bob_ch = loader.insert(|ctx| {
+			assert!(ctx.ext.is_contract(&BOB));
+			assert!(!ctx.ext.is_contract(&ALICE));
+			assert_eq!(ctx.ext.code_hash(&ALICE), None);
+			*witnessed_code_hash.borrow_mut() = ctx.ext.code_hash(&BOB);
+			exec_success()
+		});
+
+		ExtBuilder::default().build().execute_with(|| {
+			let cfg = ConfigCache::preload();
+			let mut ctx = ExecutionContext::top_level(ALICE, None, &cfg, &vm, &loader);
+			place_contract(&BOB, bob_ch);
+
+			assert_matches!(
+				ctx.call(BOB, 0, &mut GasMeter::<Test>::new(GAS_LIMIT), vec![]),
+				Ok(_)
+			);
+		});
+
+		assert_eq!(*witnessed_code_hash.borrow(), Some(bob_ch));
+	}
+
+	#[test]
+	fn delegate_dependencies_are_added_and_removed() {
+		let vm = MockVm::new();
+
+		let mut loader = MockLoader::empty();
+		let lib_ch = loader.insert(|_ctx| exec_success());
+		let bob_ch = loader.insert(move |ctx| {
+			assert_eq!(
+				ctx.ext.add_delegate_dependency(
+					<Test as frame_system::Config>::Hash::repeat_byte(0xff),
+				),
+				Err(Error::<Test>::CodeNotFound.into())
+			);
+			let own_ch = ctx.ext.code_hash(&BOB).unwrap();
+			assert_eq!(
+				ctx.ext.add_delegate_dependency(own_ch),
+				Err(Error::<Test>::CannotAddSelfAsDelegateDependency.into())
+			);
+
+			assert_eq!(ctx.ext.add_delegate_dependency(lib_ch), Ok(()));
+			assert_eq!(
+				ctx.ext.add_delegate_dependency(lib_ch),
+				Err(Error::<Test>::DelegateDependencyAlreadyExists.into())
+			);
+			assert_eq!(ctx.ext.delegate_dependencies_count(), 1);
+
+			assert_eq!(ctx.ext.remove_delegate_dependency(lib_ch), Ok(()));
+			assert_eq!(
+				ctx.ext.remove_delegate_dependency(lib_ch),
+				Err(Error::<Test>::DelegateDependencyNotFound.into())
+			);
+			assert_eq!(ctx.ext.delegate_dependencies_count(), 0);
+			exec_success()
+		});
+
+		ExtBuilder::default().build().execute_with(|| {
+			let cfg = ConfigCache::preload();
+			let mut ctx = ExecutionContext::top_level(ALICE, None, &cfg, &vm, &loader);
+			place_contract(&BOB, bob_ch);
+
+			assert_matches!(
+				ctx.call(BOB, 0, &mut GasMeter::<Test>::new(GAS_LIMIT), vec![]),
+				Ok(_)
+			);
+		});
+	}
+
+	#[test]
+	fn delegate_dependencies_are_limited() {
+		let vm = MockVm::new();
+
+		let mut loader = MockLoader::empty();
+		let first_ch = loader.insert(|_ctx| exec_success());
+		let second_ch = loader.insert(|_ctx| exec_success());
+		let bob_ch = loader.insert(move |ctx| {
+			assert_eq!(ctx.ext.add_delegate_dependency(first_ch), Ok(()));
+			assert_eq!(
+				ctx.ext.add_delegate_dependency(second_ch),
+				Err(Error::<Test>::MaxDelegateDependenciesReached.into())
+			);
+			exec_success()
+		});
+
+		ExtBuilder::default().build().execute_with(|| {
+			let mut cfg = ConfigCache::preload();
+			cfg.schedule.limits.delegate_dependencies = 1;
+			let mut ctx = ExecutionContext::top_level(ALICE, None, &cfg, &vm, &loader);
+			place_contract(&BOB, bob_ch);
+
+			assert_matches!(
+				ctx.call(BOB, 0, &mut GasMeter::<Test>::new(GAS_LIMIT), vec![]),
+				Ok(_)
+			);
+			assert!(crate::DelegateDependencies::<Test>::contains_key(&BOB, &first_ch));
+			assert!(!crate::DelegateDependencies::<Test>::contains_key(&BOB, &second_ch));
 		});
 	}
 }
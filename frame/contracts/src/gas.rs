@@ -88,6 +88,8 @@ pub struct GasMeter<T: Config> {
 	gas_limit: Gas,
 	/// Amount of gas left from initial gas limit. Can reach zero.
 	gas_left: Gas,
+	/// The lowest value `gas_left` ever had. Used to determine the gas required by a call.
+	gas_left_lowest: Gas,
 	_phantom: PhantomData<T>,
 	#[cfg(test)]
 	tokens: Vec<ErasedToken>,
@@ -97,6 +99,7 @@ impl<T: Config> GasMeter<T> {
 		GasMeter {
 			gas_limit,
 			gas_left: gas_limit,
+			gas_left_lowest: gas_limit,
 			_phantom: PhantomData,
 			#[cfg(test)]
 			tokens: Vec::new(),
@@ -136,6 +139,7 @@ impl<T: Config> GasMeter<T> {
 
 		// We always consume the gas even if there is not enough gas.
 		self.gas_left = new_value.unwrap_or_else(Zero::zero);
+		self.gas_left_lowest = self.gas_left_lowest.min(self.gas_left);
 
 		match new_value {
 			Some(_) => GasMeterResult::Proceed,
@@ -173,6 +177,9 @@ impl<T: Config> GasMeter<T> {
 
 			let r = f(Some(&mut nested));
 
+			// The nested meter only needed what it actually used and not the whole `amount`.
+			self.gas_left_lowest = self.gas_left_lowest
+				.min((self.gas_left + amount).saturating_sub(nested.gas_required()));
 			self.gas_left = self.gas_left + nested.gas_left;
 
 			r
@@ -184,6 +191,14 @@ impl<T: Config> GasMeter<T> {
 		self.gas_limit - self.gas_left
 	}
 
+	/// Returns how much gas is needed for the execution to succeed.
+	///
+	/// This can be more than `gas_spent` because gas that was refunded after it was used
+	/// up is included. Gas that was allotted to a nested meter but not used by it is not.
+	pub fn gas_required(&self) -> Gas {
+		self.gas_limit - self.gas_left_lowest
+	}
+
 	/// Returns how much gas left from the initial budget.
 	pub fn gas_left(&self) -> Gas {
 		self.gas_left
@@ -337,4 +352,17 @@ mod tests {
 		let mut gas_meter = GasMeter::<Test>::new(25);
 		assert!(!gas_meter.charge(&(), SimpleToken(25)).is_out_of_gas());
 	}
+
+	// Gas that is allotted to a nested meter but not used by it is not required.
+	#[test]
+	fn gas_required_tracks_peak_usage() {
+		let mut gas_meter = GasMeter::<Test>::new(1000);
+		assert!(!gas_meter.charge(&(), SimpleToken(100)).is_out_of_gas());
+		gas_meter.with_nested(500, |nested| {
+			let nested = nested.unwrap();
+			assert!(!nested.charge(&(), SimpleToken(300)).is_out_of_gas());
+		});
+		assert_eq!(gas_meter.gas_spent(), 400);
+		assert_eq!(gas_meter.gas_required(), 400);
+	}
 }
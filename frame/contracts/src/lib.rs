@@ -48,6 +48,19 @@
 //! then all of B's calls are reverted. Assuming correct error handling by contract A, A's other calls and state
 //! changes still persist.
 //!
+//! ### Storage Deposit
+//!
+//! Contracts pay for the storage they occupy by holding a deposit that is reserved on their account.
+//! The deposit consists of a fixed amount per contract and an amount per storage item and byte.
+//! Whenever a call increases the storage used by a contract the difference is transferred from the
+//! origin of the transaction to the contract. When storage is freed the difference is refunded to
+//! the origin. Terminating a contract refunds its whole deposit. The origin can limit how much
+//! deposit it is willing to pay by supplying a `storage_deposit_limit`.
+//!
+//! Code is stored separately from the contracts that use it and reference counted. Its uploader
+//! pays a deposit based on the size of the code. The code is removed and the deposit refunded
+//! when the last contract using it is terminated or when the uploader removes the unused code.
+//!
 //! ### Notable Scenarios
 //!
 //! Contract call failures are not always cascading. When failures occur in a sub-call, they do not "bubble up",
@@ -58,7 +71,12 @@
 //!
 //! ### Dispatchable functions
 //!
-//! * `put_code` - Stores the given binary Wasm code into the chain's storage and returns its `code_hash`.
+//! * `upload_code` - Stores the given binary Wasm code into the chain's storage. The uploader
+//! pays a deposit for the code.
+//! * `remove_code` - Removes code that is not used by any contract and refunds its deposit. Only
+//! the uploader of the code can remove it.
+//! * `instantiate_with_code` - Uploads the given code and deploys a new contract from it in one
+//! atomic step, optionally transferring some balance.
 //! * `instantiate` - Deploys a new contract from the given `code_hash`, optionally transferring some balance.
 //! This instantiates a new smart contract account and calls its contract deploy handler to
 //! initialize the contract.
@@ -85,9 +103,10 @@ mod gas;
 mod storage;
 mod exec;
 mod wasm;
-mod rent;
 mod benchmarking;
 mod schedule;
+mod migration;
+pub mod chain_extension;
 pub mod weights;
 
 #[cfg(test)]
@@ -97,214 +116,121 @@ pub use crate::{
 	gas::{Gas, GasMeter},
 	wasm::ReturnCode as RuntimeReturnCode,
 	weights::WeightInfo,
-	schedule::{Schedule, HostFnWeights, InstructionWeights, Limits},
+	schedule::{Schedule, HostFnWeights, InstructionWeights, Limits, WasmFeatures},
+	storage::meter::DepositOf,
 };
 use crate::{
 	exec::ExecutionContext,
 	wasm::{WasmLoader, WasmVm},
-	rent::Rent,
 	storage::Storage,
 };
 use sp_core::crypto::UncheckedFrom;
-use sp_std::{prelude::*, marker::PhantomData, fmt::Debug};
-use codec::{Codec, Encode, Decode};
+use sp_std::prelude::*;
+use codec::{Encode, Decode};
 use sp_runtime::{
-	traits::{
-		Hash, StaticLookup, Zero, MaybeSerializeDeserialize, Member, Convert, Saturating,
-	},
+	traits::{Hash, StaticLookup, Convert, Saturating, Zero},
 	RuntimeDebug,
 };
 use frame_support::{
-	decl_module, decl_event, decl_storage, decl_error, ensure,
+	decl_module, decl_event, decl_storage, decl_error, ensure, transactional,
 	storage::child::ChildInfo,
 	dispatch::{DispatchResult, DispatchResultWithPostInfo},
-	traits::{OnUnbalanced, Currency, Get, Time, Randomness},
+	traits::{Currency, ReservableCurrency, Get, Time, Randomness},
 };
 use frame_system::{ensure_signed, ensure_root};
 use pallet_contracts_primitives::{
-	RentProjectionResult, GetStorageResult, ContractAccessError, ContractExecResult, ExecResult,
+	GetStorageResult, ContractAccessError, ContractExecResult, ContractInstantiateResult,
+	ExecError, ExecReturnValue, InstantiateReturnValue, Code, CodeUploadResult,
+	CodeUploadReturnValue, StorageDeposit, ContractEvent, Determinism,
 };
 use frame_support::weights::Weight;
 
 pub type CodeHash<T> = <T as frame_system::Config>::Hash;
 pub type TrieId = Vec<u8>;
 
-/// Information for managing an account and its sub trie abstraction.
-/// This is the required info to cache for an account
-#[derive(Encode, Decode, RuntimeDebug)]
-pub enum ContractInfo<T: Config> {
-	Alive(AliveContractInfo<T>),
-	Tombstone(TombstoneContractInfo<T>),
-}
-
-impl<T: Config> ContractInfo<T> {
-	/// If contract is alive then return some alive info
-	pub fn get_alive(self) -> Option<AliveContractInfo<T>> {
-		if let ContractInfo::Alive(alive) = self {
-			Some(alive)
-		} else {
-			None
-		}
-	}
-	/// If contract is alive then return some reference to alive info
-	pub fn as_alive(&self) -> Option<&AliveContractInfo<T>> {
-		if let ContractInfo::Alive(ref alive) = self {
-			Some(alive)
-		} else {
-			None
-		}
-	}
-	/// If contract is alive then return some mutable reference to alive info
-	pub fn as_alive_mut(&mut self) -> Option<&mut AliveContractInfo<T>> {
-		if let ContractInfo::Alive(ref mut alive) = self {
-			Some(alive)
-		} else {
-			None
-		}
-	}
-
-	/// If contract is tombstone then return some tombstone info
-	pub fn get_tombstone(self) -> Option<TombstoneContractInfo<T>> {
-		if let ContractInfo::Tombstone(tombstone) = self {
-			Some(tombstone)
-		} else {
-			None
-		}
-	}
-	/// If contract is tombstone then return some reference to tombstone info
-	pub fn as_tombstone(&self) -> Option<&TombstoneContractInfo<T>> {
-		if let ContractInfo::Tombstone(ref tombstone) = self {
-			Some(tombstone)
-		} else {
-			None
-		}
-	}
-	/// If contract is tombstone then return some mutable reference to tombstone info
-	pub fn as_tombstone_mut(&mut self) -> Option<&mut TombstoneContractInfo<T>> {
-		if let ContractInfo::Tombstone(ref mut tombstone) = self {
-			Some(tombstone)
-		} else {
-			None
-		}
-	}
-}
-
-pub type AliveContractInfo<T> =
-	RawAliveContractInfo<CodeHash<T>, BalanceOf<T>, <T as frame_system::Config>::BlockNumber>;
+pub type ContractInfo<T> = RawContractInfo<CodeHash<T>, BalanceOf<T>>;
 
 /// Information for managing an account and its sub trie abstraction.
 /// This is the required info to cache for an account.
 #[derive(Encode, Decode, Clone, PartialEq, Eq, RuntimeDebug)]
-pub struct RawAliveContractInfo<CodeHash, Balance, BlockNumber> {
+pub struct RawContractInfo<CodeHash, Balance> {
 	/// Unique ID for the subtree encoded as a bytes vector.
 	pub trie_id: TrieId,
 	/// The total number of bytes used by this contract.
 	///
 	/// It is a sum of each key-value pair stored by this contract.
 	pub storage_size: u32,
-	/// The number of key-value pairs that have values of zero length.
-	/// The condition `empty_pair_count ≤ total_pair_count` always holds.
-	pub empty_pair_count: u32,
 	/// The total number of key-value pairs in storage of this contract.
-	pub total_pair_count: u32,
+	pub pair_count: u32,
 	/// The code associated with a given account.
 	pub code_hash: CodeHash,
-	/// Pay rent at most up to this value.
-	pub rent_allowance: Balance,
-	/// Last block rent has been payed.
-	pub deduct_block: BlockNumber,
-	/// Last block child storage has been written.
-	pub last_write: Option<BlockNumber>,
+	/// The amount of balance that is currently reserved on the contract's account as
+	/// deposit for the storage it occupies.
+	pub storage_deposit: Balance,
+	/// The part of the deposit for the storage that existed before `V2_0_0` which the
+	/// contract could not afford during the migration.
+	///
+	/// It is never charged from anyone and shrinks as the contract frees its storage.
+	pub deposit_written_off: Balance,
 }
 
-impl<CodeHash, Balance, BlockNumber> RawAliveContractInfo<CodeHash, Balance, BlockNumber> {
+impl<CodeHash, Balance> RawContractInfo<CodeHash, Balance> {
 	/// Associated child trie unique id is built from the hash part of the trie id.
 	pub fn child_trie_info(&self) -> ChildInfo {
 		child_trie_info(&self.trie_id[..])
 	}
 }
 
-/// Associated child trie unique id is built from the hash part of the trie id.
-pub(crate) fn child_trie_info(trie_id: &[u8]) -> ChildInfo {
-	ChildInfo::new_default(trie_id)
-}
-
-pub type TombstoneContractInfo<T> =
-	RawTombstoneContractInfo<<T as frame_system::Config>::Hash, <T as frame_system::Config>::Hashing>;
-
-#[derive(Encode, Decode, PartialEq, Eq, RuntimeDebug)]
-pub struct RawTombstoneContractInfo<H, Hasher>(H, PhantomData<Hasher>);
+pub type OwnerInfo<T> = RawOwnerInfo<<T as frame_system::Config>::AccountId, BalanceOf<T>>;
 
-impl<H, Hasher> RawTombstoneContractInfo<H, Hasher>
-where
-	H: Member + MaybeSerializeDeserialize+ Debug
-		+ AsRef<[u8]> + AsMut<[u8]> + Copy + Default
-		+ sp_std::hash::Hash + Codec,
-	Hasher: Hash<Output=H>,
-{
-	fn new(storage_root: &[u8], code_hash: H) -> Self {
-		let mut buf = Vec::new();
-		storage_root.using_encoded(|encoded| buf.extend_from_slice(encoded));
-		buf.extend_from_slice(code_hash.as_ref());
-		RawTombstoneContractInfo(<Hasher as Hash>::hash(&buf[..]), PhantomData)
-	}
+/// Information about the uploader of a code and the contracts that use it.
+#[derive(Encode, Decode, Clone, PartialEq, Eq, RuntimeDebug)]
+pub struct RawOwnerInfo<AccountId, Balance> {
+	/// The account that uploaded the code and paid the deposit for it.
+	///
+	/// `None` for code that was stored before `V3_0_0` because its uploader was never recorded.
+	/// Such code has no deposit and can't be removed by `remove_code`.
+	pub owner: Option<AccountId>,
+	/// The amount of balance that is reserved on the owner's account for storing the code.
+	pub deposit: Balance,
+	/// The number of contracts that use this code.
+	pub refcount: u64,
 }
 
-impl<T: Config> From<AliveContractInfo<T>> for ContractInfo<T> {
-	fn from(alive_info: AliveContractInfo<T>) -> Self {
-		Self::Alive(alive_info)
-	}
+/// Associated child trie unique id is built from the hash part of the trie id.
+pub(crate) fn child_trie_info(trie_id: &[u8]) -> ChildInfo {
+	ChildInfo::new_default(trie_id)
 }
 
 pub type BalanceOf<T> =
 	<<T as Config>::Currency as Currency<<T as frame_system::Config>::AccountId>>::Balance;
-pub type NegativeImbalanceOf<T> =
-	<<T as Config>::Currency as Currency<<T as frame_system::Config>::AccountId>>::NegativeImbalance;
 
 pub trait Config: frame_system::Config {
 	type Time: Time;
 	type Randomness: Randomness<Self::Hash>;
 
 	/// The currency in which fees are paid and contract balances are held.
-	type Currency: Currency<Self::AccountId>;
+	///
+	/// Storage deposits are reserved on the contract's account.
+	type Currency: ReservableCurrency<Self::AccountId>;
 
 	/// The overarching event type.
 	type Event: From<Event<Self>> + Into<<Self as frame_system::Config>::Event>;
 
-	/// Handler for rent payments.
-	type RentPayment: OnUnbalanced<NegativeImbalanceOf<Self>>;
-
-	/// Number of block delay an extrinsic claim surcharge has.
+	/// The deposit that every contract needs to hold for its own existence.
 	///
-	/// When claim surcharge is called by an extrinsic the rent is checked
-	/// for current_block - delay
-	type SignedClaimHandicap: Get<Self::BlockNumber>;
-
-	/// The minimum amount required to generate a tombstone.
-	type TombstoneDeposit: Get<BalanceOf<Self>>;
+	/// It covers the storage used by the contract's bookkeeping information.
+	type ContractDeposit: Get<BalanceOf<Self>>;
 
-	/// A size offset for an contract. A just created account with untouched storage will have that
-	/// much of storage from the perspective of the state rent.
+	/// The deposit a contract needs to hold for every byte of its storage values.
 	///
-	/// This is a simple way to ensure that contracts with empty storage eventually get deleted by
-	/// making them pay rent. This creates an incentive to remove them early in order to save rent.
-	type StorageSizeOffset: Get<u32>;
+	/// The uploader of a code pays the same deposit for every byte of the stored code.
+	type DepositPerByte: Get<BalanceOf<Self>>;
 
-	/// Price of a byte of storage per one block interval. Should be greater than 0.
-	type RentByteFee: Get<BalanceOf<Self>>;
-
-	/// The amount of funds a contract should deposit in order to offset
-	/// the cost of one byte.
+	/// The deposit a contract needs to hold for every key-value pair in its storage.
 	///
-	/// Let's suppose the deposit is 1,000 BU (balance units)/byte and the rent is 1 BU/byte/day,
-	/// then a contract with 1,000,000 BU that uses 1,000 bytes of storage would pay no rent.
-	/// But if the balance reduced to 500,000 BU and the storage stayed the same at 1,000,
-	/// then it would pay 500 BU/day.
-	type RentDepositOffset: Get<BalanceOf<Self>>;
-
-	/// Reward that is received by the party whose touch has led
-	/// to removal of a contract.
-	type SurchargeReward: Get<BalanceOf<Self>>;
+	/// The uploader of a code pays the same deposit for every storage item of the code.
+	type DepositPerItem: Get<BalanceOf<Self>>;
 
 	/// The maximum nesting level of a call/instantiate stack.
 	type MaxDepth: Get<u32>;
@@ -319,6 +245,15 @@ pub trait Config: frame_system::Config {
 	/// Describes the weights of the dispatchables of this module and is also used to
 	/// construct a default cost schedule.
 	type WeightInfo: WeightInfo;
+
+	/// Type that allows the runtime authors to add new host functions for a contract to call.
+	type ChainExtension: chain_extension::ChainExtension<Self>;
+
+	/// The maximum number of tries that can be queued for deletion.
+	type DeletionQueueDepth: Get<u32>;
+
+	/// The maximum amount of weight that can be consumed per block for lazy trie removal.
+	type DeletionWeightLimit: Get<Weight>;
 }
 
 decl_error! {
@@ -330,24 +265,13 @@ decl_error! {
 	{
 		/// A new schedule must have a greater version than the current one.
 		InvalidScheduleVersion,
-		/// An origin must be signed or inherent and auxiliary sender only provided on inherent.
-		InvalidSurchargeClaim,
-		/// Cannot restore from nonexisting or tombstone contract.
-		InvalidSourceContract,
-		/// Cannot restore to nonexisting or alive contract.
-		InvalidDestinationContract,
-		/// Tombstones don't match.
-		InvalidTombstone,
-		/// An origin TrieId written in the current block.
-		InvalidContractOrigin,
 		/// The executed contract exhausted its gas limit.
 		OutOfGas,
 		/// The output buffer supplied to a contract API call was too small.
 		OutputBufferTooSmall,
 		/// Performing the requested transfer would have brought the contract below
-		/// the subsistence threshold. No transfer is allowed to do this in order to allow
-		/// for a tombstone to be created. Use `seal_terminate` to remove a contract without
-		/// leaving a tombstone behind.
+		/// the subsistence threshold. No transfer is allowed to do this. Use `seal_terminate`
+		/// to remove a contract and transfer all of its balance.
 		BelowSubsistenceThreshold,
 		/// The newly created contract is below the subsistence threshold after executing
 		/// its contructor. No contracts are allowed to exist below that threshold.
@@ -359,13 +283,14 @@ decl_error! {
 		/// Performing a call was denied because the calling depth reached the limit
 		/// of what is specified in the schedule.
 		MaxCallDepthReached,
-		/// The contract that was called is either no contract at all (a plain account)
-		/// or is a tombstone.
+		/// The contract that was called is no contract at all (a plain account).
 		NotCallable,
-		/// The code supplied to `put_code` exceeds the limit specified in the current schedule.
+		/// The code supplied to `upload_code` exceeds the limit specified in the current schedule.
 		CodeTooLarge,
 		/// No code could be found at the supplied code hash.
 		CodeNotFound,
+		/// The code can't be removed because it is still used by at least one contract.
+		CodeInUse,
 		/// A buffer outside of sandbox memory was passed to a contract API function.
 		OutOfBounds,
 		/// Input passed to a contract API function failed to decode as expected type.
@@ -375,9 +300,38 @@ decl_error! {
 		/// The size defined in `T::MaxValueSize` was exceeded.
 		ValueTooLarge,
 		/// The action performed is not allowed while the contract performing it is already
-		/// on the call stack. Those actions are contract self destruction and restoration
-		/// of a tombstone.
+		/// on the call stack. Those actions are contract self destruction and calling into a
+		/// contract that did not allow reentry (see `ALLOW_REENTRY` flag of `seal_call`).
 		ReentranceDenied,
+		/// The contract called into the chain extension but the chain does not provide
+		/// an extension for the requested function.
+		NoChainExtension,
+		/// Removal of a contract failed because the deletion queue is full.
+		///
+		/// This can happen when calling `seal_terminate`. The queue is filled by deleting
+		/// contracts and emptied by a fixed amount each block. Trying again during another
+		/// block is the only way to resolve this issue.
+		DeletionQueueFull,
+		/// The origin does not have enough free balance to pay the storage deposit that
+		/// the execution requires.
+		StorageDepositNotEnoughFunds,
+		/// More storage deposit was required than the `storage_deposit_limit` supplied
+		/// by the origin allows.
+		StorageDepositLimitExhausted,
+		/// The contract tried to access its input after it was forwarded to a callee
+		/// (see `FORWARD_INPUT` flag of `seal_call`).
+		InputForwarded,
+		/// The contract's code was uploaded with relaxed determinism and can therefore only
+		/// be executed off-chain (see `Determinism::Relaxed`).
+		Indeterministic,
+		/// The contract already depends on `Limits::delegate_dependencies` codes.
+		MaxDelegateDependenciesReached,
+		/// The contract already depends on the given code.
+		DelegateDependencyAlreadyExists,
+		/// The contract does not depend on the given code.
+		DelegateDependencyNotFound,
+		/// A contract can't depend on its own code.
+		CannotAddSelfAsDelegateDependency,
 	}
 }
 
@@ -391,38 +345,14 @@ decl_module! {
 	{
 		type Error = Error<T>;
 
-		/// Number of block delay an extrinsic claim surcharge has.
-		///
-		/// When claim surcharge is called by an extrinsic the rent is checked
-		/// for current_block - delay
-		const SignedClaimHandicap: T::BlockNumber = T::SignedClaimHandicap::get();
+		/// The deposit that every contract needs to hold for its own existence.
+		const ContractDeposit: BalanceOf<T> = T::ContractDeposit::get();
 
-		/// The minimum amount required to generate a tombstone.
-		const TombstoneDeposit: BalanceOf<T> = T::TombstoneDeposit::get();
+		/// The deposit a contract needs to hold for every byte of its storage values.
+		const DepositPerByte: BalanceOf<T> = T::DepositPerByte::get();
 
-		/// A size offset for an contract. A just created account with untouched storage will have that
-		/// much of storage from the perspective of the state rent.
-		///
-		/// This is a simple way to ensure that contracts with empty storage eventually get deleted
-		/// by making them pay rent. This creates an incentive to remove them early in order to save
-		/// rent.
-		const StorageSizeOffset: u32 = T::StorageSizeOffset::get();
-
-		/// Price of a byte of storage per one block interval. Should be greater than 0.
-		const RentByteFee: BalanceOf<T> = T::RentByteFee::get();
-
-		/// The amount of funds a contract should deposit in order to offset
-		/// the cost of one byte.
-		///
-		/// Let's suppose the deposit is 1,000 BU (balance units)/byte and the rent is 1 BU/byte/day,
-		/// then a contract with 1,000,000 BU that uses 1,000 bytes of storage would pay no rent.
-		/// But if the balance reduced to 500,000 BU and the storage stayed the same at 1,000,
-		/// then it would pay 500 BU/day.
-		const RentDepositOffset: BalanceOf<T> = T::RentDepositOffset::get();
-
-		/// Reward that is received by the party whose touch has led
-		/// to removal of a contract.
-		const SurchargeReward: BalanceOf<T> = T::SurchargeReward::get();
+		/// The deposit a contract needs to hold for every key-value pair in its storage.
+		const DepositPerItem: BalanceOf<T> = T::DepositPerItem::get();
 
 		/// The maximum nesting level of a call/instantiate stack. A reasonable default
 		/// value is 100.
@@ -431,8 +361,35 @@ decl_module! {
 		/// The maximum size of a storage value in bytes. A reasonable default is 16 KiB.
 		const MaxValueSize: u32 = T::MaxValueSize::get();
 
+		/// The maximum number of tries that can be queued for deletion.
+		const DeletionQueueDepth: u32 = T::DeletionQueueDepth::get();
+
+		/// The maximum amount of weight that can be consumed per block for lazy trie removal.
+		const DeletionWeightLimit: Weight = T::DeletionWeightLimit::get();
+
 		fn deposit_event() = default;
 
+		fn on_runtime_upgrade() -> Weight {
+			let mut weight = 0;
+			if StorageVersion::get() == Releases::V1_0_0 {
+				weight = weight.saturating_add(migration::v2::migrate::<T>());
+			}
+			if StorageVersion::get() == Releases::V2_0_0 {
+				weight = weight.saturating_add(migration::v3::migrate::<T>());
+			}
+			weight
+		}
+
+		fn on_initialize() -> Weight {
+			// We do not want to go above the block limit and rather avoid lazy deletion
+			// in that case. This should only happen on runtime upgrades.
+			let weight_limit = T::MaximumBlockWeight::get()
+				.saturating_sub(<frame_system::Module<T>>::block_weight().total())
+				.min(T::DeletionWeightLimit::get());
+			Storage::<T>::process_deletion_queue_batch(weight_limit)
+				.saturating_add(T::WeightInfo::on_initialize())
+		}
+
 		/// Updates the schedule for metering contracts.
 		///
 		/// The schedule must have a greater version than the stored schedule.
@@ -449,21 +406,37 @@ decl_module! {
 			Ok(())
 		}
 
-		/// Stores the given binary Wasm code into the chain's storage and returns its `codehash`.
+		/// Stores the given binary Wasm code into the chain's storage.
 		/// You can instantiate contracts only with stored code.
-		#[weight = T::WeightInfo::put_code(code.len() as u32 / 1024)]
-		pub fn put_code(
+		///
+		/// The origin becomes the owner of the code and a deposit proportional to the size of
+		/// the code is reserved from it. The deposit is unreserved when the code is removed:
+		/// Either by calling `remove_code` or when the last contract using it is terminated.
+		/// The `storage_deposit_limit` is the maximum deposit the origin is willing to pay.
+		/// `None` means that the whole free balance of the origin can be used.
+		///
+		/// Uploading code that is already stored does nothing and charges no deposit.
+		///
+		/// Code uploaded with `Determinism::Relaxed` may use instructions whose results are not
+		/// bit-exact (e.g. floating point). It can only be executed off-chain.
+		#[weight = T::WeightInfo::upload_code(code.len() as u32 / 1024)]
+		pub fn upload_code(
 			origin,
-			code: Vec<u8>
+			code: Vec<u8>,
+			storage_deposit_limit: Option<BalanceOf<T>>,
+			determinism: Determinism,
 		) -> DispatchResult {
-			ensure_signed(origin)?;
-			let schedule = <Module<T>>::current_schedule();
-			ensure!(code.len() as u32 <= schedule.limits.code_size, Error::<T>::CodeTooLarge);
-			let result = wasm::save_code::<T>(code, &schedule);
-			if let Ok(code_hash) = result {
-				Self::deposit_event(RawEvent::CodeStored(code_hash));
-			}
-			result.map(|_| ()).map_err(Into::into)
+			let origin = ensure_signed(origin)?;
+			Self::bare_upload_code(origin, code, storage_deposit_limit, determinism).map(|_| ())
+		}
+
+		/// Removes the code stored under `code_hash` and refunds the deposit to its owner.
+		///
+		/// Only the owner of the code can remove it and only while no contract uses it.
+		#[weight = T::WeightInfo::remove_code()]
+		pub fn remove_code(origin, code_hash: CodeHash<T>) -> DispatchResult {
+			let origin = ensure_signed(origin)?;
+			wasm::remove_code::<T>(&origin, code_hash)
 		}
 
 		/// Makes a call to an account, optionally transferring some balance.
@@ -473,25 +446,77 @@ decl_module! {
 		/// * If the account is a regular account, any value will be transferred.
 		/// * If no account exists and the call value is not less than `existential_deposit`,
 		/// a regular account will be created and any value will be transferred.
+		///
+		/// The `storage_deposit_limit` is the maximum amount of balance that can be charged
+		/// from the origin in order to pay for the storage the call occupies. `None` means that
+		/// the whole free balance of the origin can be used.
 		#[weight = T::WeightInfo::call().saturating_add(*gas_limit)]
 		pub fn call(
 			origin,
 			dest: <T::Lookup as StaticLookup>::Source,
 			#[compact] value: BalanceOf<T>,
 			#[compact] gas_limit: Gas,
+			storage_deposit_limit: Option<BalanceOf<T>>,
 			data: Vec<u8>
 		) -> DispatchResultWithPostInfo {
 			let origin = ensure_signed(origin)?;
 			let dest = T::Lookup::lookup(dest)?;
 			let mut gas_meter = GasMeter::new(gas_limit);
 
-			let result = Self::execute_wasm(origin, &mut gas_meter, |ctx, gas_meter| {
-				ctx.call(dest, value, gas_meter, data)
-			});
+			let (result, _) = Self::execute_wasm(
+				origin,
+				storage_deposit_limit,
+				&mut gas_meter,
+				false,
+				Determinism::Enforced,
+				|ctx, gas_meter| ctx.call(dest, value, gas_meter, data),
+			);
 			gas_meter.into_dispatch_result(result)
 		}
 
-		/// Instantiates a new contract from the `code_hash` generated by `put_code`,
+		/// Instantiates a new contract from the supplied `code` optionally transferring
+		/// some balance.
+		///
+		/// The code is uploaded as by `upload_code` unless it is already stored. Uploading the
+		/// code and instantiating the contract happens atomically: The code is not stored when
+		/// the instantiation fails. The `storage_deposit_limit` applies to the sum of the code
+		/// deposit and the deposit of the new contract.
+		///
+		/// See `instantiate` for the remaining parameters.
+		#[weight =
+			T::WeightInfo::instantiate_with_code(
+				code.len() as u32 / 1024,
+				data.len() as u32 / 1024,
+				salt.len() as u32 / 1024,
+			).saturating_add(*gas_limit)
+		]
+		#[transactional]
+		pub fn instantiate_with_code(
+			origin,
+			#[compact] endowment: BalanceOf<T>,
+			#[compact] gas_limit: Gas,
+			storage_deposit_limit: Option<BalanceOf<T>>,
+			code: Vec<u8>,
+			data: Vec<u8>,
+			salt: Vec<u8>,
+		) -> DispatchResultWithPostInfo {
+			let origin = ensure_signed(origin)?;
+			let mut gas_meter = GasMeter::new(gas_limit);
+
+			let (result, _) = Self::internal_instantiate(
+				origin,
+				endowment,
+				&mut gas_meter,
+				storage_deposit_limit,
+				Code::Upload(code),
+				data,
+				salt,
+				false,
+			);
+			gas_meter.into_dispatch_result(result)
+		}
+
+		/// Instantiates a new contract from the `code_hash` generated by `upload_code`,
 		/// optionally transferring some balance.
 		///
 		/// The supplied `salt` is used for contract address deriviation. See `fn contract_address`.
@@ -504,6 +529,9 @@ decl_module! {
 		///   after the execution is saved as the `code` of the account. That code will be invoked
 		///   upon any call received by this account.
 		/// - The contract is initialized.
+		///
+		/// The origin pays the storage deposit of the new contract. See `call` for the meaning
+		/// of `storage_deposit_limit`.
 		#[weight =
 			T::WeightInfo::instantiate(
 				data.len() as u32 / 1024,
@@ -514,6 +542,7 @@ decl_module! {
 			origin,
 			#[compact] endowment: BalanceOf<T>,
 			#[compact] gas_limit: Gas,
+			storage_deposit_limit: Option<BalanceOf<T>>,
 			code_hash: CodeHash<T>,
 			data: Vec<u8>,
 			salt: Vec<u8>,
@@ -521,45 +550,18 @@ decl_module! {
 			let origin = ensure_signed(origin)?;
 			let mut gas_meter = GasMeter::new(gas_limit);
 
-			let result = Self::execute_wasm(origin, &mut gas_meter, |ctx, gas_meter| {
-				ctx.instantiate(endowment, gas_meter, &code_hash, data, &salt)
-					.map(|(_address, output)| output)
-			});
+			let (result, _) = Self::internal_instantiate(
+				origin,
+				endowment,
+				&mut gas_meter,
+				storage_deposit_limit,
+				Code::Existing(code_hash),
+				data,
+				salt,
+				false,
+			);
 			gas_meter.into_dispatch_result(result)
 		}
-
-		/// Allows block producers to claim a small reward for evicting a contract. If a block producer
-		/// fails to do so, a regular users will be allowed to claim the reward.
-		///
-		/// If contract is not evicted as a result of this call, no actions are taken and
-		/// the sender is not eligible for the reward.
-		#[weight = T::WeightInfo::claim_surcharge()]
-		fn claim_surcharge(origin, dest: T::AccountId, aux_sender: Option<T::AccountId>) {
-			let origin = origin.into();
-			let (signed, rewarded) = match (origin, aux_sender) {
-				(Ok(frame_system::RawOrigin::Signed(account)), None) => {
-					(true, account)
-				},
-				(Ok(frame_system::RawOrigin::None), Some(aux_sender)) => {
-					(false, aux_sender)
-				},
-				_ => Err(Error::<T>::InvalidSurchargeClaim)?,
-			};
-
-			// Add some advantage for block producers (who send unsigned extrinsics) by
-			// adding a handicap: for signed extrinsics we use a slightly older block number
-			// for the eviction check. This can be viewed as if we pushed regular users back in past.
-			let handicap = if signed {
-				T::SignedClaimHandicap::get()
-			} else {
-				Zero::zero()
-			};
-
-			// If poking the contract has lead to eviction of the contract, give out the rewards.
-			if Rent::<T>::snitch_contract_should_be_evicted(&dest, handicap) {
-				T::Currency::deposit_into_existing(&rewarded, T::SurchargeReward::get())?;
-			}
-		}
 	}
 }
 
@@ -573,46 +575,134 @@ where
 	/// This function is similar to `Self::call`, but doesn't perform any address lookups and better
 	/// suitable for calling directly from Rust.
 	///
-	/// It returns the exection result and the amount of used weight.
+	/// It returns the exection result, the amount of used and required weight, the storage
+	/// deposit that was charged from or refunded to the origin and the change in storage size.
+	///
+	/// When `debug` is `true` the events deposited by contracts and the messages passed to
+	/// `seal_debug_message` are recorded and returned, too. This must only be enabled for
+	/// dry runs (RPC calls) and never for calls that are executed on-chain.
+	///
+	/// Code uploaded with `Determinism::Relaxed` can only be executed when `determinism` is
+	/// `Determinism::Relaxed`. The same restriction as for `debug` applies.
 	pub fn bare_call(
 		origin: T::AccountId,
 		dest: T::AccountId,
 		value: BalanceOf<T>,
 		gas_limit: Gas,
+		storage_deposit_limit: Option<BalanceOf<T>>,
 		input_data: Vec<u8>,
-	) -> ContractExecResult {
+		debug: bool,
+		determinism: Determinism,
+	) -> ContractExecResult<T::AccountId, BalanceOf<T>, T::Hash> {
 		let mut gas_meter = GasMeter::new(gas_limit);
-		let exec_result = Self::execute_wasm(origin, &mut gas_meter, |ctx, gas_meter| {
-			ctx.call(dest, value, gas_meter, input_data)
-		});
-		let gas_consumed = gas_meter.gas_spent();
+		let (exec_result, report) = Self::execute_wasm(
+			origin,
+			storage_deposit_limit,
+			&mut gas_meter,
+			debug,
+			determinism,
+			|ctx, gas_meter| ctx.call(dest, value, gas_meter, input_data),
+		);
 		ContractExecResult {
 			exec_result,
-			gas_consumed,
+			gas_consumed: gas_meter.gas_spent(),
+			gas_required: gas_meter.gas_required(),
+			storage_deposit: report.storage_deposit,
+			storage_bytes: report.storage_bytes,
+			events: report.events,
+			debug_message: report.debug_message,
+		}
+	}
+
+	/// Instantiate a new contract.
+	///
+	/// This function is similar to `Self::instantiate` and `Self::instantiate_with_code` but
+	/// is better suited for calling directly from Rust. Unlike `Self::instantiate_with_code`
+	/// it does not roll back the upload of the code when the instantiation fails.
+	///
+	/// It returns the address of the new contract and the output of its constructor along with
+	/// the same information as `bare_call`. The storage deposit includes the deposit for the
+	/// uploaded code, even when the instantiation fails. See `bare_call` for the meaning of
+	/// `debug`.
+	///
+	/// Instantiation always enforces determinism: Uploaded code is stored with
+	/// `Determinism::Enforced` and code stored with `Determinism::Relaxed` cannot be
+	/// instantiated.
+	pub fn bare_instantiate(
+		origin: T::AccountId,
+		endowment: BalanceOf<T>,
+		gas_limit: Gas,
+		storage_deposit_limit: Option<BalanceOf<T>>,
+		code: Code<CodeHash<T>>,
+		data: Vec<u8>,
+		salt: Vec<u8>,
+		debug: bool,
+	) -> ContractInstantiateResult<T::AccountId, BalanceOf<T>, T::Hash> {
+		let mut gas_meter = GasMeter::new(gas_limit);
+		let (result, report) = Self::internal_instantiate(
+			origin,
+			endowment,
+			&mut gas_meter,
+			storage_deposit_limit,
+			code,
+			data,
+			salt,
+			debug,
+		);
+		ContractInstantiateResult {
+			result: result.map(|(account_id, result)| InstantiateReturnValue { result, account_id }),
+			gas_consumed: gas_meter.gas_spent(),
+			gas_required: gas_meter.gas_required(),
+			storage_deposit: report.storage_deposit,
+			storage_bytes: report.storage_bytes,
+			events: report.events,
+			debug_message: report.debug_message,
 		}
 	}
 
+	/// Upload new code without instantiating a contract from it.
+	///
+	/// This function is similar to `Self::upload_code` but returns the hash of the code and
+	/// the deposit that was reserved from the origin.
+	pub fn bare_upload_code(
+		origin: T::AccountId,
+		code: Vec<u8>,
+		storage_deposit_limit: Option<BalanceOf<T>>,
+		determinism: Determinism,
+	) -> CodeUploadResult<CodeHash<T>, BalanceOf<T>> {
+		let schedule = <Module<T>>::current_schedule();
+		ensure!(code.len() as u32 <= schedule.limits.code_size, Error::<T>::CodeTooLarge);
+		let (code_hash, deposit) =
+			wasm::save_code::<T>(code, origin, storage_deposit_limit, &schedule, determinism)?;
+		Ok(CodeUploadReturnValue { code_hash, deposit })
+	}
+
 	/// Query storage of a specified contract under a specified key.
 	pub fn get_storage(address: T::AccountId, key: [u8; 32]) -> GetStorageResult {
 		let contract_info = ContractInfoOf::<T>::get(&address)
-			.ok_or(ContractAccessError::DoesntExist)?
-			.get_alive()
-			.ok_or(ContractAccessError::IsTombstone)?;
+			.ok_or(ContractAccessError::DoesntExist)?;
 
 		let maybe_value = Storage::<T>::read(&contract_info.trie_id, &key);
 		Ok(maybe_value)
 	}
 
-	pub fn rent_projection(address: T::AccountId) -> RentProjectionResult<T::BlockNumber> {
-		Rent::<T>::compute_projection(&address)
+	/// Process the deletion queue of terminated contracts.
+	///
+	/// This is called from `on_initialize` with the weight configured by `DeletionWeightLimit`.
+	/// Runtime authors can call it with any leftover block weight (e.g. in `on_finalize`) in
+	/// order to speed up the removal. Returns the weight that was actually consumed.
+	pub fn process_deletion_queue_batch(weight_limit: Weight) -> Weight {
+		Storage::<T>::process_deletion_queue_batch(weight_limit)
 	}
 
-	/// Put code for benchmarks which does not check or instrument the code.
+	/// Store code for benchmarks which does not check or instrument the code.
+	///
+	/// The code is owned by `owner` but no deposit is reserved for it.
 	#[cfg(feature = "runtime-benchmarks")]
-	pub fn put_code_raw(code: Vec<u8>) -> DispatchResult {
+	pub fn store_code_raw(code: Vec<u8>, owner: T::AccountId) -> DispatchResult {
 		let schedule = <Module<T>>::current_schedule();
-		let result = wasm::save_code_raw::<T>(code, &schedule);
-		result.map(|_| ()).map_err(Into::into)
+		wasm::save_code_raw::<T>(code, owner, &schedule)?;
+		Ok(())
 	}
 
 	/// Determine the address of a contract,
@@ -642,59 +732,123 @@ impl<T: Config> Module<T>
 where
 	T::AccountId: UncheckedFrom<T::Hash> + AsRef<[u8]>,
 {
-	fn execute_wasm(
+	/// Upload `code` unless it is already stored and instantiate a contract from it.
+	///
+	/// The returned storage deposit includes the deposit for the code. The upload is not rolled
+	/// back when the instantiation fails and its deposit is therefore always reported.
+	fn internal_instantiate(
+		origin: T::AccountId,
+		endowment: BalanceOf<T>,
+		gas_meter: &mut GasMeter<T>,
+		storage_deposit_limit: Option<BalanceOf<T>>,
+		code: Code<CodeHash<T>>,
+		data: Vec<u8>,
+		salt: Vec<u8>,
+		debug: bool,
+	) -> (Result<(T::AccountId, ExecReturnValue), ExecError>, ExecReport<T>) {
+		let (code_hash, code_deposit) = match code {
+			Code::Upload(binary) => {
+				let upload = Self::bare_upload_code(
+					origin.clone(), binary, storage_deposit_limit, Determinism::Enforced,
+				);
+				match upload {
+					Ok(CodeUploadReturnValue { code_hash, deposit }) => (code_hash, deposit),
+					Err(err) => return (Err(err.into()), ExecReport::default()),
+				}
+			},
+			Code::Existing(code_hash) => (code_hash, Zero::zero()),
+		};
+		let storage_deposit_limit = storage_deposit_limit
+			.map(|limit| limit.saturating_sub(code_deposit));
+		let (result, mut report) = Self::execute_wasm(
+			origin,
+			storage_deposit_limit,
+			gas_meter,
+			debug,
+			Determinism::Enforced,
+			|ctx, gas_meter| ctx.instantiate(endowment, gas_meter, &code_hash, data, &salt),
+		);
+		report.storage_deposit =
+			StorageDeposit::Charge(code_deposit).saturating_add(&report.storage_deposit);
+		(result, report)
+	}
+
+	fn execute_wasm<R>(
 		origin: T::AccountId,
+		storage_deposit_limit: Option<BalanceOf<T>>,
 		gas_meter: &mut GasMeter<T>,
-		func: impl FnOnce(&mut ExecutionContext<T, WasmVm<T>, WasmLoader<T>>, &mut GasMeter<T>) -> ExecResult,
-	) -> ExecResult {
+		debug: bool,
+		determinism: Determinism,
+		func: impl FnOnce(
+			&mut ExecutionContext<T, WasmVm<T>, WasmLoader<T>>,
+			&mut GasMeter<T>,
+		) -> Result<R, ExecError>,
+	) -> (Result<R, ExecError>, ExecReport<T>) {
 		let cfg = ConfigCache::preload();
-		let vm = WasmVm::new(&cfg.schedule);
+		let vm = WasmVm::new(&cfg.schedule, determinism);
 		let loader = WasmLoader::new(&cfg.schedule);
-		let mut ctx = ExecutionContext::top_level(origin, &cfg, &vm, &loader);
-		func(&mut ctx, gas_meter)
+		let mut ctx = ExecutionContext::top_level(origin, storage_deposit_limit, &cfg, &vm, &loader);
+		if debug {
+			ctx.debug_message = Some(Vec::new());
+			ctx.events = Some(Vec::new());
+		}
+		let result = func(&mut ctx, gas_meter);
+		let report = ExecReport {
+			storage_deposit: ctx.storage_meter.deposit(),
+			storage_bytes: ctx.storage_meter.storage_bytes(),
+			events: ctx.events.unwrap_or_default(),
+			debug_message: ctx.debug_message.unwrap_or_default(),
+		};
+		(result, report)
+	}
+}
+
+/// The side effects of an execution which are reported by `bare_call` and `bare_instantiate`.
+struct ExecReport<T: Config> {
+	storage_deposit: DepositOf<T>,
+	storage_bytes: i64,
+	events: Vec<ContractEvent<T::AccountId, T::Hash>>,
+	debug_message: Vec<u8>,
+}
+
+impl<T: Config> Default for ExecReport<T> {
+	fn default() -> Self {
+		Self {
+			storage_deposit: Default::default(),
+			storage_bytes: 0,
+			events: Vec::new(),
+			debug_message: Vec::new(),
+		}
 	}
 }
 
 decl_event! {
 	pub enum Event<T>
 	where
-		Balance = BalanceOf<T>,
 		<T as frame_system::Config>::AccountId,
 		<T as frame_system::Config>::Hash
 	{
 		/// Contract deployed by address at the specified address. \[owner, contract\]
 		Instantiated(AccountId, AccountId),
 
-		/// Contract has been evicted and is now in tombstone state.
-		/// \[contract, tombstone\]
-		///
-		/// # Params
-		///
-		/// - `contract`: `AccountId`: The account ID of the evicted contract.
-		/// - `tombstone`: `bool`: True if the evicted contract left behind a tombstone.
-		Evicted(AccountId, bool),
-
-		/// Restoration for a contract has been successful.
-		/// \[donor, dest, code_hash, rent_allowance\]
-		///
-		/// # Params
-		///
-		/// - `donor`: `AccountId`: Account ID of the restoring contract
-		/// - `dest`: `AccountId`: Account ID of the restored contract
-		/// - `code_hash`: `Hash`: Code hash of the restored contract
-		/// - `rent_allowance: `Balance`: Rent allowance of the restored contract
-		Restored(AccountId, AccountId, Hash, Balance),
-
 		/// Code with the specified hash has been stored.
 		/// \[code_hash\]
 		CodeStored(Hash),
 
+		/// Code with the specified hash has been removed and its deposit was refunded.
+		/// \[code_hash\]
+		CodeRemoved(Hash),
+
 		/// Triggered when the current \[schedule\] is updated.
 		ScheduleUpdated(u32),
 
 		/// An event deposited upon execution of a contract from the account.
 		/// \[account, data\]
 		ContractExecution(AccountId, Vec<u8>),
+
+		/// A contract replaced its code by calling `seal_set_code_hash`.
+		/// \[contract, new_code_hash, old_code_hash\]
+		ContractCodeUpdated(AccountId, Hash, Hash),
 	}
 }
 
@@ -709,12 +863,28 @@ decl_storage! {
 		pub PristineCode: map hasher(identity) CodeHash<T> => Option<Vec<u8>>;
 		/// A mapping between an original code hash and instrumented wasm code, ready for execution.
 		pub CodeStorage: map hasher(identity) CodeHash<T> => Option<wasm::PrefabWasmModule>;
+		/// The owner, deposit and number of users of every stored code.
+		pub OwnerInfoOf: map hasher(identity) CodeHash<T> => Option<OwnerInfo<T>>;
 		/// The subtrie counter.
 		pub AccountCounter: u64 = 0;
 		/// The code associated with a given account.
 		///
 		/// TWOX-NOTE: SAFE since `AccountId` is a secure hash.
 		pub ContractInfoOf: map hasher(twox_64_concat) T::AccountId => Option<ContractInfo<T>>;
+		/// The codes each contract delegates calls to and that can't be removed while the
+		/// contract exists (see `seal_add_delegate_dependency`).
+		///
+		/// TWOX-NOTE: SAFE since `AccountId` is a secure hash.
+		pub DelegateDependencies:
+			double_map hasher(twox_64_concat) T::AccountId, hasher(identity) CodeHash<T> => Option<()>;
+		/// Terminated contracts that await child trie deletion.
+		///
+		/// Child trie deletion is a heavy operation depending on the amount of storage items
+		/// stored in said trie. Therefore this operation is performed lazily in `on_initialize`.
+		pub DeletionQueue: Vec<storage::DeletedContract>;
+		/// The layout version of the pallet's storage. Used to determine which migrations
+		/// need to be applied.
+		StorageVersion build(|_: &GenesisConfig<T>| Releases::V3_0_0): Releases;
 	}
 }
 
@@ -725,7 +895,6 @@ decl_storage! {
 pub struct ConfigCache<T: Config> {
 	pub schedule: Schedule<T>,
 	pub existential_deposit: BalanceOf<T>,
-	pub tombstone_deposit: BalanceOf<T>,
 	pub max_depth: u32,
 	pub max_value_size: u32,
 }
@@ -738,28 +907,42 @@ where
 		ConfigCache {
 			schedule: <Module<T>>::current_schedule(),
 			existential_deposit: T::Currency::minimum_balance(),
-			tombstone_deposit: T::TombstoneDeposit::get(),
 			max_depth: T::MaxDepth::get(),
 			max_value_size: T::MaxValueSize::get(),
 		}
 	}
 
-	/// Subsistence threshold is the extension of the minimum balance (aka existential deposit) by the
-	/// tombstone deposit, required for leaving a tombstone.
-	///
-	/// Rent or any contract initiated balance transfer mechanism cannot make the balance lower
-	/// than the subsistence threshold in order to guarantee that a tombstone is created.
+	/// Subsistence threshold is the minimum free balance (aka existential deposit) a contract
+	/// needs to keep. The storage deposit is reserved and therefore comes on top of it.
 	///
-	/// The only way to completely kill a contract without a tombstone is calling `seal_terminate`.
+	/// No contract initiated balance transfer mechanism can make the balance lower than the
+	/// subsistence threshold. The only way to completely remove a contract is calling
+	/// `seal_terminate`.
 	pub fn subsistence_threshold(&self) -> BalanceOf<T> {
-		self.existential_deposit.saturating_add(self.tombstone_deposit)
+		self.existential_deposit
 	}
 
 	/// The same as `subsistence_threshold` but without the need for a preloaded instance.
-	///
-	/// This is for cases where this value is needed in rent calculation rather than
-	/// during contract execution.
 	pub fn subsistence_threshold_uncached() -> BalanceOf<T> {
-		T::Currency::minimum_balance().saturating_add(T::TombstoneDeposit::get())
+		T::Currency::minimum_balance()
+	}
+}
+
+/// The storage layout versions of this pallet.
+///
+/// Used by `on_runtime_upgrade` to determine whether storage migrations need to run.
+#[derive(Encode, Decode, Clone, Copy, PartialEq, Eq, RuntimeDebug)]
+enum Releases {
+	/// Contracts pay rent and can be evicted into tombstones.
+	V1_0_0,
+	/// Contracts hold a storage deposit instead of paying rent.
+	V2_0_0,
+	/// Code is owned by its uploader and reference counted.
+	V3_0_0,
+}
+
+impl Default for Releases {
+	fn default() -> Self {
+		Releases::V1_0_0
 	}
 }
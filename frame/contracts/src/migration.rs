@@ -0,0 +1,172 @@
+// Copyright 2018-2020 Parity Technologies (UK) Ltd.
+// This file is part of Substrate.
+
+// Substrate is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// Substrate is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with Substrate. If not, see <http://www.gnu.org/licenses/>.
+
+//! Storage migrations for the contracts module.
+
+/// Migration to `Releases::V2_0_0` which replaces the storage rent by a storage deposit.
+pub mod v2 {
+	use crate::{
+		BalanceOf, CodeHash, Config, ContractInfo, ContractInfoOf, Releases, StorageVersion,
+		TrieId, storage::meter::required_deposit,
+	};
+	use codec::Decode;
+	use sp_core::crypto::UncheckedFrom;
+	use sp_runtime::traits::{Saturating, Zero};
+	use frame_support::{
+		storage::{IterableStorageMap, StorageValue},
+		traits::{Currency, Get, ReservableCurrency},
+		weights::Weight,
+	};
+
+	/// The layout of `RawAliveContractInfo` prior to `V2_0_0`.
+	#[derive(Decode)]
+	struct OldAliveContractInfo<CodeHash, Balance, BlockNumber> {
+		trie_id: TrieId,
+		storage_size: u32,
+		_empty_pair_count: u32,
+		total_pair_count: u32,
+		code_hash: CodeHash,
+		_rent_allowance: Balance,
+		_deduct_block: BlockNumber,
+		_last_write: Option<BlockNumber>,
+	}
+
+	/// The layout of `ContractInfo` prior to `V2_0_0`.
+	#[derive(Decode)]
+	enum OldContractInfo<CodeHash, Balance, BlockNumber, Hash> {
+		Alive(OldAliveContractInfo<CodeHash, Balance, BlockNumber>),
+		Tombstone(Hash),
+	}
+
+	type OldContractInfoOf<T> = OldContractInfo<
+		CodeHash<T>,
+		BalanceOf<T>,
+		<T as frame_system::Config>::BlockNumber,
+		<T as frame_system::Config>::Hash,
+	>;
+
+	/// Translate all `ContractInfoOf` entries to the deposit based layout.
+	///
+	/// Tombstones are removed because they can no longer be restored. Their storage was already
+	/// removed when they were evicted. Alive contracts reserve their storage deposit from their
+	/// own balance as far as they can afford it without going below the existential deposit.
+	/// Those contracts were created before any deposit existed and have no other owner who
+	/// could be charged. The remainder is therefore written off: It is recorded in
+	/// `deposit_written_off` and not charged from the origin of later calls.
+	pub fn migrate<T: Config>() -> Weight
+	where
+		T::AccountId: UncheckedFrom<T::Hash> + AsRef<[u8]>,
+	{
+		let count = sp_std::cell::Cell::new(0 as Weight);
+
+		<ContractInfoOf<T>>::translate::<OldContractInfoOf<T>, _>(|account, old| {
+			count.set(count.get() + 1);
+			let old = match old {
+				OldContractInfo::Alive(old) => old,
+				OldContractInfo::Tombstone(_) => return None,
+			};
+			let mut info = ContractInfo::<T> {
+				trie_id: old.trie_id,
+				storage_size: old.storage_size,
+				pair_count: old.total_pair_count,
+				code_hash: old.code_hash,
+				storage_deposit: Zero::zero(),
+				deposit_written_off: Zero::zero(),
+			};
+			let required = required_deposit::<T>(&info);
+			let affordable = T::Currency::free_balance(&account)
+				.saturating_sub(T::Currency::minimum_balance());
+			let deposit = required.min(affordable);
+			if T::Currency::reserve(&account, deposit).is_ok() {
+				info.storage_deposit = deposit;
+			}
+			info.deposit_written_off = required.saturating_sub(info.storage_deposit);
+			Some(info)
+		});
+
+		StorageVersion::put(Releases::V2_0_0);
+		let count = count.get();
+
+		// Every contract requires a read and write of its info and its account.
+		T::DbWeight::get().reads_writes(
+			count.saturating_mul(2).saturating_add(1),
+			count.saturating_mul(2).saturating_add(1),
+		)
+	}
+}
+
+/// Migration to `Releases::V3_0_0` which introduces owners and reference counts for code.
+pub mod v3 {
+	use crate::{
+		CodeHash, CodeStorage, Config, ContractInfoOf, OwnerInfo, OwnerInfoOf, PristineCode,
+		Releases, StorageVersion,
+	};
+	use sp_core::crypto::UncheckedFrom;
+	use sp_runtime::traits::Zero;
+	use sp_std::{collections::btree_map::BTreeMap, vec::Vec};
+	use frame_support::{
+		storage::{IterableStorageMap, StorageMap, StorageValue},
+		traits::Get,
+		weights::Weight,
+	};
+
+	/// Create an `OwnerInfoOf` entry for every stored code that is used by a contract, and
+	/// remove the code that isn't.
+	///
+	/// The previous layout never recorded who uploaded a code and nobody paid a deposit for it.
+	/// Code stored before this migration is therefore recorded without owner and its deposit is
+	/// written off. It can't be removed by `remove_code` but is still removed once the last
+	/// contract using it is terminated. Unused code would never be removed, hence it is removed
+	/// here.
+	pub fn migrate<T: Config>() -> Weight
+	where
+		T::AccountId: UncheckedFrom<T::Hash> + AsRef<[u8]>,
+	{
+		let mut refcounts = BTreeMap::<CodeHash<T>, u64>::new();
+		let mut contracts: Weight = 0;
+		for (_, info) in <ContractInfoOf<T>>::iter() {
+			*refcounts.entry(info.code_hash).or_default() += 1;
+			contracts += 1;
+		}
+
+		let mut codes: Weight = 0;
+		let mut unused = Vec::new();
+		for (code_hash, _) in <CodeStorage<T>>::iter() {
+			match refcounts.get(&code_hash) {
+				Some(refcount) => <OwnerInfoOf<T>>::insert(code_hash, OwnerInfo::<T> {
+					owner: None,
+					deposit: Zero::zero(),
+					refcount: *refcount,
+				}),
+				None => unused.push(code_hash),
+			}
+			codes += 1;
+		}
+
+		let removed = unused.len() as Weight;
+		for code_hash in unused {
+			<CodeStorage<T>>::remove(code_hash);
+			<PristineCode<T>>::remove(code_hash);
+		}
+
+		StorageVersion::put(Releases::V3_0_0);
+
+		T::DbWeight::get().reads_writes(
+			contracts.saturating_add(codes).saturating_add(1),
+			codes.saturating_add(removed).saturating_add(1),
+		)
+	}
+}
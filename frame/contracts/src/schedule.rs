@@ -17,7 +17,7 @@
 //! This module contains the cost schedule and supporting code that constructs a
 //! sane default schedule from a `WeightInfo` implementation.
 
-use crate::{Config, weights::WeightInfo};
+use crate::{Config, weights::WeightInfo, wasm::{classify, InstructionSet}};
 
 #[cfg(feature = "std")]
 use serde::{Serialize, Deserialize};
@@ -53,6 +53,9 @@ pub struct Schedule<T: Config> {
 	/// Describes the upper limits on various metrics.
 	pub limits: Limits,
 
+	/// The wasm proposals that contracts are allowed to use in addition to the MVP.
+	pub wasm_features: WasmFeatures,
+
 	/// The weights for individual wasm instructions.
 	pub instruction_weights: InstructionWeights<T>,
 
@@ -105,8 +108,29 @@ pub struct Limits {
 	pub subject_len: u32,
 
 	/// The maximum length of a contract code in bytes. This limit applies to the uninstrumented
-	/// and pristine form of the code as supplied to `put_code`.
+	/// and pristine form of the code as supplied to `upload_code`.
 	pub code_size: u32,
+
+	/// The maximum number of codes a contract can depend on through
+	/// `seal_add_delegate_dependency`.
+	pub delegate_dependencies: u32,
+}
+
+/// Describes which wasm proposals beyond the MVP contracts are allowed to use.
+///
+/// Code using instructions of a proposal that is not enabled is rejected when it is uploaded
+/// or re-instrumented. Enabling a proposal requires that the wasm executor of every node
+/// supports it. By default no proposal is enabled.
+#[cfg_attr(feature = "std", derive(Serialize, Deserialize))]
+#[derive(Clone, Default, Encode, Decode, PartialEq, Eq, RuntimeDebug)]
+pub struct WasmFeatures {
+	/// Allow the sign-extension operators (`i32.extend8_s` and friends).
+	pub sign_extension: bool,
+
+	/// Allow `memory.copy` and `memory.fill` from the bulk memory proposal.
+	///
+	/// The remaining instructions of this proposal are not supported.
+	pub bulk_memory: bool,
 }
 
 /// Describes the weight for all categories of supported wasm instructions.
@@ -194,6 +218,15 @@ pub struct HostFnWeights<T: Config> {
 	/// Weight of calling `seal_caller`.
 	pub caller: Weight,
 
+	/// Weight of calling `seal_caller_is_origin`.
+	pub caller_is_origin: Weight,
+
+	/// Weight of calling `seal_is_contract`.
+	pub is_contract: Weight,
+
+	/// Weight of calling `seal_code_hash`.
+	pub code_hash: Weight,
+
 	/// Weight of calling `seal_address`.
 	pub address: Weight,
 
@@ -242,12 +275,6 @@ pub struct HostFnWeights<T: Config> {
 	/// Weight of calling `seal_terminate`.
 	pub terminate: Weight,
 
-	/// Weight of calling `seal_restore_to`.
-	pub restore_to: Weight,
-
-	/// Weight per delta key supplied to `seal_restore_to`.
-	pub restore_to_per_delta: Weight,
-
 	/// Weight of calling `seal_random`.
 	pub random: Weight,
 
@@ -260,6 +287,15 @@ pub struct HostFnWeights<T: Config> {
 	/// Weight per byte of an event deposited through `seal_deposit_event`.
 	pub deposit_event_per_byte: Weight,
 
+	/// Weight of calling `seal_debug_message`.
+	pub debug_message: Weight,
+
+	/// Weight of calling `seal_call_chain_extension`.
+	///
+	/// This only covers entering the host function. The chain extension charges the weight of
+	/// the work it does itself.
+	pub call_chain_extension: Weight,
+
 	/// Weight of calling `seal_set_rent_allowance`.
 	pub set_rent_allowance: Weight,
 
@@ -293,6 +329,20 @@ pub struct HostFnWeights<T: Config> {
 	/// Weight per output byte received through `seal_call`.
 	pub call_per_output_byte: Weight,
 
+	/// Weight of calling `seal_delegate_call`.
+	///
+	/// Input and output bytes are charged with the same weights as for `seal_call`.
+	pub delegate_call: Weight,
+
+	/// Weight of calling `seal_set_code_hash`.
+	pub set_code_hash: Weight,
+
+	/// Weight of calling `seal_add_delegate_dependency`.
+	pub add_delegate_dependency: Weight,
+
+	/// Weight of calling `seal_remove_delegate_dependency`.
+	pub remove_delegate_dependency: Weight,
+
 	/// Weight of calling `seal_instantiate`.
 	pub instantiate: Weight,
 
@@ -329,6 +379,18 @@ pub struct HostFnWeights<T: Config> {
 	/// Weight per byte hashed by `seal_hash_blake2_128`.
 	pub hash_blake2_128_per_byte: Weight,
 
+	/// Weight of calling `seal_ecdsa_recover`.
+	pub ecdsa_recover: Weight,
+
+	/// Weight of calling `seal_ecdsa_to_eth_address`.
+	pub ecdsa_to_eth_address: Weight,
+
+	/// Weight of calling `seal_sr25519_verify`.
+	pub sr25519_verify: Weight,
+
+	/// Weight per byte of the message verified by `seal_sr25519_verify`.
+	pub sr25519_verify_per_byte: Weight,
+
 	/// The type parameter is used in the default implementation.
 	pub _phantom: PhantomData<T>
 }
@@ -416,12 +478,20 @@ impl<T: Config> Default for Schedule<T> {
 			version: 0,
 			enable_println: false,
 			limits: Default::default(),
+			wasm_features: Default::default(),
 			instruction_weights: Default::default(),
 			host_fn_weights: Default::default(),
 		}
 	}
 }
 
+impl Limits {
+	/// The maximum memory size in bytes that a contract can occupy.
+	pub fn max_memory_size(&self) -> u32 {
+		self.memory_pages * 64 * 1024
+	}
+}
+
 impl Default for Limits {
 	fn default() -> Self {
 		Self {
@@ -436,6 +506,7 @@ impl Default for Limits {
 			br_table_size: 256,
 			subject_len: 32,
 			code_size: 512 * 1024,
+			delegate_dependencies: 32,
 		}
 	}
 }
@@ -504,6 +575,9 @@ impl<T: Config> Default for HostFnWeights<T> {
 	fn default() -> Self {
 		Self {
 			caller: cost_batched!(seal_caller),
+			caller_is_origin: cost_batched!(seal_caller_is_origin),
+			is_contract: cost_batched!(seal_is_contract),
+			code_hash: cost_batched!(seal_code_hash),
 			address: cost_batched!(seal_address),
 			gas_left: cost_batched!(seal_gas_left),
 			balance: cost_batched!(seal_balance),
@@ -520,12 +594,14 @@ impl<T: Config> Default for HostFnWeights<T> {
 			r#return: cost!(seal_return),
 			return_per_byte: cost_byte!(seal_return_per_kb),
 			terminate: cost!(seal_terminate),
-			restore_to: cost!(seal_restore_to),
-			restore_to_per_delta: cost_batched!(seal_restore_to_per_delta),
 			random: cost_batched!(seal_random),
 			deposit_event: cost_batched!(seal_deposit_event),
 			deposit_event_per_topic: cost_batched_args!(seal_deposit_event_per_topic_and_kb, 1, 0),
 			deposit_event_per_byte: cost_byte_batched_args!(seal_deposit_event_per_topic_and_kb, 0, 1),
+			debug_message: cost_batched!(seal_debug_message),
+			// There is no chain extension to benchmark in this pallet, so we use the cost of the
+			// simplest host function as the cost of entering one.
+			call_chain_extension: cost_batched!(seal_caller),
 			set_rent_allowance: cost_batched!(seal_set_rent_allowance),
 			set_storage: cost_batched!(seal_set_storage),
 			set_storage_per_byte: cost_byte_batched!(seal_set_storage_per_kb),
@@ -537,6 +613,10 @@ impl<T: Config> Default for HostFnWeights<T> {
 			call_transfer_surcharge: cost_batched_args!(seal_call_per_transfer_input_output_kb, 1, 0, 0),
 			call_per_input_byte: cost_byte_batched_args!(seal_call_per_transfer_input_output_kb, 0, 1, 0),
 			call_per_output_byte: cost_byte_batched_args!(seal_call_per_transfer_input_output_kb, 0, 0, 1),
+			delegate_call: cost_batched!(seal_delegate_call),
+			set_code_hash: cost_batched!(seal_set_code_hash),
+			add_delegate_dependency: cost!(seal_add_delegate_dependency),
+			remove_delegate_dependency: cost!(seal_remove_delegate_dependency),
 			instantiate: cost_batched!(seal_instantiate),
 			instantiate_per_input_byte: cost_byte_batched_args!(seal_instantiate_per_input_output_salt_kb, 1, 0, 0),
 			instantiate_per_output_byte: cost_byte_batched_args!(seal_instantiate_per_input_output_salt_kb, 0, 1, 0),
@@ -549,6 +629,10 @@ impl<T: Config> Default for HostFnWeights<T> {
 			hash_blake2_256_per_byte: cost_byte_batched!(seal_hash_blake2_256_per_kb),
 			hash_blake2_128: cost_batched!(seal_hash_blake2_128),
 			hash_blake2_128_per_byte: cost_byte_batched!(seal_hash_blake2_128_per_kb),
+			ecdsa_recover: cost_batched!(seal_ecdsa_recover),
+			ecdsa_to_eth_address: cost_batched!(seal_ecdsa_to_eth_address),
+			sr25519_verify: cost_batched!(seal_sr25519_verify),
+			sr25519_verify_per_byte: cost_byte_batched!(seal_sr25519_verify_per_kb),
 			_phantom: PhantomData,
 		}
 	}
@@ -578,7 +662,7 @@ impl<T: Config> Schedule<T> {
 
 impl<'a, T: Config> rules::Rules for ScheduleRules<'a, T> {
 	fn instruction_cost(&self, instruction: &elements::Instruction) -> Option<u32> {
-		use parity_wasm::elements::Instruction::*;
+		use parity_wasm::elements::{BulkInstruction::*, Instruction::*};
 		let w = &self.schedule.instruction_weights;
 		let max_params = self.schedule.limits.parameters;
 
@@ -641,6 +725,20 @@ impl<'a, T: Config> rules::Rules for ScheduleRules<'a, T> {
 			I32Rotl | I64Rotl => w.i64rotl,
 			I32Rotr | I64Rotr => w.i64rotr,
 
+			// Sign extension is as cheap as the MVP extension instructions.
+			SignExt(_) => w.i64extendsi32,
+
+			// The amount of bytes copied or filled is not known statically. We therefore
+			// charge the worst case which is touching the whole memory. This is what
+			// `memory.grow` was benchmarked with.
+			Bulk(MemoryCopy) | Bulk(MemoryFill) => w.memory_grow,
+
+			// Floating point instructions are only accepted by `prepare` for code with
+			// relaxed determinism. They were not benchmarked and we charge them like the
+			// most expensive integer arithmetic instruction.
+			ref instr if matches!(classify(instr), Some((InstructionSet::Float, _))) =>
+				w.i64divs,
+
 			// Returning None makes the gas instrumentation fail which we intend for
 			// unsupported or unknown instructions.
 			_ => return None,
@@ -0,0 +1,179 @@
+// Copyright 2018-2020 Parity Technologies (UK) Ltd.
+// This file is part of Substrate.
+
+// Substrate is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// Substrate is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with Substrate. If not, see <http://www.gnu.org/licenses/>.
+
+//! This module contains the accounting of the storage deposit.
+//!
+//! Every contract needs to hold a deposit that is proportional to the storage it occupies:
+//! [`Config::ContractDeposit`] for the contract itself, [`Config::DepositPerItem`] for every
+//! key-value pair and [`Config::DepositPerByte`] for every byte of the values. The deposit is
+//! reserved on the contract's account and therefore can't be spent by the contract.
+//!
+//! The origin of a transaction pays the deposit for all storage that was created during its
+//! execution and receives the deposit for all storage that was freed.
+
+use crate::{BalanceOf, Config, ContractInfo, ContractInfoOf, Error};
+use pallet_contracts_primitives::StorageDeposit;
+use sp_core::crypto::UncheckedFrom;
+use sp_runtime::traits::Saturating;
+use frame_support::{
+	dispatch::DispatchResult,
+	ensure,
+	traits::{Currency, ExistenceRequirement, Get, ReservableCurrency},
+	StorageMap,
+};
+
+/// The storage deposit expressed in the balance type of the configured currency.
+pub type DepositOf<T> = StorageDeposit<BalanceOf<T>>;
+
+/// The deposit a contract needs to hold for the storage described by `info`.
+pub fn required_deposit<T: Config>(info: &ContractInfo<T>) -> BalanceOf<T> {
+	T::DepositPerByte::get()
+		.saturating_mul(info.storage_size.into())
+		.saturating_add(T::DepositPerItem::get().saturating_mul(info.pair_count.into()))
+		.saturating_add(T::ContractDeposit::get())
+}
+
+/// Keeps track of the storage deposit that a call stack charges from or refunds to its origin.
+///
+/// Every call frame owns a meter. When a frame finishes successfully the deposit held by the
+/// contract executing in it is brought in line with the storage it occupies by calling
+/// [`Self::charge`]. Afterwards, the meter is absorbed into the meter of the calling frame.
+/// Meters of frames that are rolled back are simply dropped: The balance transfers they
+/// performed are reverted together with all the other changes of that frame.
+pub struct Meter<T: Config> {
+	/// The account that pays for all storage created by the call stack.
+	origin: T::AccountId,
+	/// The maximum net deposit this meter is allowed to charge.
+	///
+	/// `None` means that the free balance of the origin is the only limit.
+	limit: Option<BalanceOf<T>>,
+	/// The net deposit charged or refunded by this meter and all meters it absorbed.
+	total_deposit: DepositOf<T>,
+	/// The net number of bytes added to or removed from contract storage by the frame owning
+	/// this meter and all frames whose meters it absorbed.
+	storage_bytes: i64,
+}
+
+impl<T> Meter<T>
+where
+	T: Config,
+	T::AccountId: UncheckedFrom<T::Hash> + AsRef<[u8]>,
+{
+	/// Create the meter for the top level frame of a call stack started by `origin`.
+	pub fn new(origin: T::AccountId, limit: Option<BalanceOf<T>>) -> Self {
+		Self {
+			origin,
+			limit,
+			total_deposit: Default::default(),
+			storage_bytes: 0,
+		}
+	}
+
+	/// Create the meter for a frame that is nested into the frame owning this meter.
+	///
+	/// The nested meter can only charge what is still available to this meter.
+	pub fn nested(&self) -> Self {
+		Self {
+			origin: self.origin.clone(),
+			limit: self.available(),
+			total_deposit: Default::default(),
+			storage_bytes: 0,
+		}
+	}
+
+	/// Add the deposit of a nested meter whose frame finished successfully.
+	pub fn absorb(&mut self, deposit: DepositOf<T>) {
+		self.total_deposit = self.total_deposit.saturating_add(&deposit);
+	}
+
+	/// The net deposit charged or refunded by this meter so far.
+	pub fn deposit(&self) -> DepositOf<T> {
+		self.total_deposit
+	}
+
+	/// Record that the contract storage changed by `bytes`.
+	///
+	/// Only used to report the change to the caller of a dry run. The deposit is computed
+	/// from the storage info of the involved contracts.
+	pub fn record_storage_bytes(&mut self, bytes: i64) {
+		self.storage_bytes = self.storage_bytes.saturating_add(bytes);
+	}
+
+	/// The net number of bytes added to (positive) or removed from (negative) contract storage.
+	pub fn storage_bytes(&self) -> i64 {
+		self.storage_bytes
+	}
+
+	/// Bring the deposit held by `contract` in line with the storage it currently occupies.
+	///
+	/// Nothing happens if the contract no longer exists because it was terminated.
+	pub fn charge(&mut self, contract: &T::AccountId) -> DispatchResult {
+		let mut info = match ContractInfoOf::<T>::get(contract) {
+			Some(info) => info,
+			None => return Ok(()),
+		};
+		let required = required_deposit::<T>(&info);
+		// Freed storage uses up the written off deposit first. It is never refunded.
+		let written_off = info.deposit_written_off
+			.min(required.saturating_sub(info.storage_deposit));
+		let required = required.saturating_sub(written_off);
+
+		if required > info.storage_deposit {
+			let amount = required.saturating_sub(info.storage_deposit);
+			if let Some(available) = self.available() {
+				ensure!(amount <= available, Error::<T>::StorageDepositLimitExhausted);
+			}
+			T::Currency::transfer(&self.origin, contract, amount, ExistenceRequirement::KeepAlive)
+				.and_then(|_| T::Currency::reserve(contract, amount))
+				.map_err(|_| Error::<T>::StorageDepositNotEnoughFunds)?;
+			self.absorb(StorageDeposit::Charge(amount));
+		} else if required < info.storage_deposit {
+			let amount = info.storage_deposit.saturating_sub(required);
+			self.refund(contract, amount, ExistenceRequirement::KeepAlive)?;
+		} else if written_off == info.deposit_written_off {
+			return Ok(());
+		}
+
+		info.storage_deposit = required;
+		info.deposit_written_off = written_off;
+		ContractInfoOf::<T>::insert(contract, info);
+		Ok(())
+	}
+
+	/// Refund the whole deposit held by `contract` which is about to be terminated.
+	pub fn terminate(&mut self, contract: &T::AccountId, info: &ContractInfo<T>) -> DispatchResult {
+		self.refund(contract, info.storage_deposit, ExistenceRequirement::AllowDeath)
+	}
+
+	/// The amount of balance this meter is still allowed to charge.
+	fn available(&self) -> Option<BalanceOf<T>> {
+		self.limit.as_ref().map(|limit| self.total_deposit.available(limit))
+	}
+
+	/// Transfer `amount` from the deposit held by `contract` back to the origin.
+	fn refund(
+		&mut self,
+		contract: &T::AccountId,
+		amount: BalanceOf<T>,
+		existence_requirement: ExistenceRequirement,
+	) -> DispatchResult {
+		T::Currency::unreserve(contract, amount);
+		T::Currency::transfer(contract, &self.origin, amount, existence_requirement)
+			.map_err(|_| Error::<T>::TransferFailed)?;
+		self.absorb(StorageDeposit::Refund(amount));
+		Ok(())
+	}
+}
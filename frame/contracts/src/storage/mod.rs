@@ -0,0 +1,286 @@
+// Copyright 2019 Parity Technologies (UK) Ltd.
+// This file is part of Substrate.
+
+// Substrate is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// Substrate is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with Substrate. If not, see <http://www.gnu.org/licenses/>.
+
+//! This module contains routines for accessing and altering a contract related state.
+
+pub mod meter;
+
+use crate::{
+	exec::{AccountIdOf, StorageKey},
+	CodeHash, ContractInfo, ContractInfoOf, Config, TrieId,
+	AccountCounter, DeletionQueue, Error,
+	weights::WeightInfo,
+};
+use codec::{Encode, Decode};
+use sp_std::prelude::*;
+use sp_std::marker::PhantomData;
+use sp_io::hashing::blake2_256;
+use sp_runtime::{DispatchResult, traits::Zero};
+use sp_core::crypto::UncheckedFrom;
+use frame_support::{
+	storage::child,
+	StorageMap, StorageValue,
+	traits::Get,
+	weights::Weight,
+};
+
+/// An error that means that the account requested doesn't exist.
+#[cfg_attr(test, derive(PartialEq, Eq, Debug))]
+pub struct ContractAbsentError;
+
+/// The child trie of a removed contract that still awaits deletion.
+#[derive(Encode, Decode)]
+pub struct DeletedContract {
+	/// The trie that should be removed.
+	trie_id: TrieId,
+}
+
+pub struct Storage<T>(PhantomData<T>);
+
+impl<T> Storage<T>
+where
+	T: Config,
+	T::AccountId: UncheckedFrom<T::Hash> + AsRef<[u8]>
+{
+	/// Reads a storage kv pair of a contract.
+	///
+	/// The read is performed from the `trie_id` only. The `address` is not necessary. If the contract
+	/// doesn't store under the given `key` `None` is returned.
+	pub fn read(trie_id: &TrieId, key: &StorageKey) -> Option<Vec<u8>> {
+		child::get_raw(&crate::child_trie_info(&trie_id), &blake2_256(key))
+	}
+
+	/// Update a storage entry into a contract's kv storage.
+	///
+	/// If the `opt_new_value` is `None` then the kv pair is removed.
+	///
+	/// This function also updates the bookkeeping info such as: number of pairs a contract owns
+	/// and the total size of its values. That's why, in contrast to `read`, this function also
+	/// requires the `account` ID. The storage deposit is settled according to this info when
+	/// the call frame of the contract finishes. See [`meter::Meter`].
+	///
+	/// Returns by how many bytes the storage occupied by the contract changed. If the contract
+	/// specified by the id `account` doesn't exist `Err` is returned.`
+	pub fn write(
+		account: &AccountIdOf<T>,
+		trie_id: &TrieId,
+		key: &StorageKey,
+		opt_new_value: Option<Vec<u8>>,
+	) -> Result<i64, ContractAbsentError> {
+		let mut new_info = <ContractInfoOf<T>>::get(account).ok_or(ContractAbsentError)?;
+
+		let hashed_key = blake2_256(key);
+		let child_trie_info = &crate::child_trie_info(&trie_id);
+
+		// In order to correctly update the book keeping we need to fetch the previous
+		// value of the key-value pair.
+		//
+		// It might be a bit more clean if we had an API that supported getting the size
+		// of the value without going through the loading of it. But at the moment of
+		// writing, there is no such API.
+		//
+		// That's not a show stopper in any case, since the performance cost is
+		// dominated by the trie traversal anyway.
+		let opt_prev_value = child::get_raw(&child_trie_info, &hashed_key);
+
+		// Update the total number of KV pairs. Empty values still occupy an item.
+		match (&opt_prev_value, &opt_new_value) {
+			(Some(_), None) => new_info.pair_count -= 1,
+			(None, Some(_)) => new_info.pair_count += 1,
+			(Some(_), Some(_)) | (None, None) => {},
+		}
+
+		// Update the total storage size.
+		let prev_value_len = opt_prev_value
+			.as_ref()
+			.map(|old_value| old_value.len() as u32)
+			.unwrap_or(0);
+		let new_value_len = opt_new_value
+			.as_ref()
+			.map(|new_value| new_value.len() as u32)
+			.unwrap_or(0);
+		new_info.storage_size = new_info
+			.storage_size
+			.saturating_add(new_value_len)
+			.saturating_sub(prev_value_len);
+
+		<ContractInfoOf<T>>::insert(&account, new_info);
+
+		// Finally, perform the change on the storage.
+		match opt_new_value {
+			Some(new_value) => child::put_raw(&child_trie_info, &hashed_key, &new_value[..]),
+			None => child::kill(&child_trie_info, &hashed_key),
+		}
+
+		Ok(i64::from(new_value_len) - i64::from(prev_value_len))
+	}
+
+	/// Creates a new contract descriptor in the storage with the given code hash at the given address.
+	///
+	/// Returns `Err` if there is already a contract at the given address.
+	pub fn place_contract(
+		account: &AccountIdOf<T>,
+		trie_id: TrieId,
+		ch: CodeHash<T>,
+	) -> Result<(), &'static str> {
+		<ContractInfoOf<T>>::mutate(account, |maybe_contract_info| {
+			if maybe_contract_info.is_some() {
+				return Err("Contract already exists");
+			}
+
+			*maybe_contract_info = Some(ContractInfo::<T> {
+				code_hash: ch,
+				storage_size: 0,
+				trie_id,
+				pair_count: 0,
+				storage_deposit: Zero::zero(),
+				deposit_written_off: Zero::zero(),
+			});
+
+			Ok(())
+		})
+	}
+
+	/// Push a contract's trie to the deletion queue for lazy removal.
+	///
+	/// You must make sure that the contract is also removed when queuing the trie for deletion.
+	pub fn queue_trie_for_deletion(trie_id: &TrieId) -> DispatchResult {
+		if DeletionQueue::decode_len().unwrap_or(0) >= T::DeletionQueueDepth::get() as usize {
+			Err(Error::<T>::DeletionQueueFull.into())
+		} else {
+			DeletionQueue::append(DeletedContract {
+				trie_id: trie_id.clone(),
+			});
+			Ok(())
+		}
+	}
+
+	/// Calculates the weight that is necessary to remove one key from the trie and how many
+	/// keys can be deleted with the supplied amount of weight.
+	///
+	/// Called by the `on_initialize` hook in order to determine how much work it can perform.
+	pub fn deletion_budget(queue_len: usize, weight_limit: Weight) -> (u64, u32) {
+		let base_weight = T::WeightInfo::on_initialize();
+		let weight_per_queue_item = T::WeightInfo::on_initialize_per_queue_item(1) -
+			T::WeightInfo::on_initialize_per_queue_item(0);
+		let weight_per_key = T::WeightInfo::on_initialize_per_trie_key(1) -
+			T::WeightInfo::on_initialize_per_trie_key(0);
+		let decoding_weight = weight_per_queue_item.saturating_mul(queue_len as Weight);
+
+		// `weight_per_key` being zero makes no sense and would constitute a failure to
+		// benchmark properly. We opt for not removing any keys at all in this case.
+		let key_budget = weight_limit
+			.saturating_sub(base_weight)
+			.saturating_sub(decoding_weight)
+			.checked_div(weight_per_key)
+			.unwrap_or(0)
+			.min(u32::max_value().into()) as u32;
+
+		(weight_per_key, key_budget)
+	}
+
+	/// Delete as many keys from the queued tries as the supplied amount of weight allows.
+	///
+	/// It returns the amount of weight used for that task or `0` when no weight was used
+	/// apart from the base weight.
+	pub fn process_deletion_queue_batch(weight_limit: Weight) -> Weight {
+		let queue_len = DeletionQueue::decode_len().unwrap_or(0);
+		if queue_len == 0 {
+			return 0;
+		}
+
+		let (weight_per_key, mut remaining_key_budget) = Self::deletion_budget(
+			queue_len,
+			weight_limit,
+		);
+
+		// We want to check whether we have enough weight to decode the queue before
+		// proceeding. Too little weight for decoding might happen during runtime upgrades
+		// which consume the whole block before the other `on_initialize` blocks are called.
+		if remaining_key_budget == 0 {
+			return weight_limit;
+		}
+
+		let mut queue = DeletionQueue::get();
+
+		while !queue.is_empty() && remaining_key_budget > 0 {
+			let removed = Self::kill_child_storage(&queue[0].trie_id, remaining_key_budget);
+			// The trie is empty when fewer keys than allowed by the budget were removed.
+			if removed < remaining_key_budget {
+				queue.remove(0);
+			}
+			remaining_key_budget -= removed;
+		}
+
+		DeletionQueue::put(queue);
+		weight_limit.saturating_sub(weight_per_key.saturating_mul(remaining_key_budget as Weight))
+	}
+
+	/// Remove at most `limit` keys from the child trie identified by `trie_id`.
+	///
+	/// Returns the number of keys that were actually removed. A return value smaller than
+	/// `limit` means that the trie is now empty.
+	fn kill_child_storage(trie_id: &TrieId, limit: u32) -> u32 {
+		let child_info = crate::child_trie_info(trie_id);
+		let mut removed = 0;
+		while removed < limit {
+			match sp_io::default_child_storage::next_key(child_info.storage_key(), &[]) {
+				Some(key) => child::kill(&child_info, &key),
+				None => break,
+			}
+			removed += 1;
+		}
+		removed
+	}
+
+	/// This generator uses inner counter for account id and applies the hash over `AccountId +
+	/// accountid_counter`.
+	pub fn generate_trie_id(account_id: &AccountIdOf<T>) -> TrieId {
+		use frame_support::StorageValue;
+		use sp_runtime::traits::Hash;
+		// Note that skipping a value due to error is not an issue here.
+		// We only need uniqueness, not sequence.
+		let new_seed = AccountCounter::mutate(|v| {
+			*v = v.wrapping_add(1);
+			*v
+		});
+
+		let buf: Vec<_> = account_id.as_ref().iter()
+			.chain(&new_seed.to_le_bytes())
+			.cloned()
+			.collect();
+		T::Hashing::hash(&buf).as_ref().into()
+	}
+
+	/// Fill up the queue in order to exercise the limits during testing.
+	#[cfg(test)]
+	pub fn fill_queue_with_dummies() {
+		let queue: Vec<_> = (0..T::DeletionQueueDepth::get()).map(|_| DeletedContract {
+			trie_id: vec![],
+		})
+		.collect();
+		DeletionQueue::put(queue);
+	}
+
+	/// Returns the code hash of the contract specified by `account` ID.
+	#[cfg(test)]
+	pub fn code_hash(account: &AccountIdOf<T>) -> Result<CodeHash<T>, ContractAbsentError>
+	{
+		<ContractInfoOf<T>>::get(account)
+			.map(|i| i.code_hash)
+			.ok_or(ContractAbsentError)
+	}
+}
\ No newline at end of file
@@ -15,27 +15,38 @@
 // along with Substrate. If not, see <http://www.gnu.org/licenses/>.
 
 use crate::{
-	BalanceOf, ContractInfo, ContractInfoOf, GenesisConfig, Module,
-	RawAliveContractInfo, RawEvent, Config, Schedule, gas::Gas,
+	BalanceOf, ContractInfo, ContractInfoOf, GenesisConfig, Module, OwnerInfo, OwnerInfoOf,
+	CodeStorage, PristineCode, DelegateDependencies,
+	RawEvent, Config, Schedule, gas::Gas,
 	Error, ConfigCache, RuntimeReturnCode, storage::Storage,
-	exec::AccountIdOf,
+	exec::AccountIdOf, DeletionQueue, WeightInfo,
+	chain_extension::{
+		Result as ExtensionResult, Environment, ChainExtension, RegisteredChainExtension, Ext,
+		SysConfig, RetVal, ReturnFlags, InitState,
+	},
 };
 use assert_matches::assert_matches;
 use codec::Encode;
+use pallet_contracts_primitives::{Code, ContractEvent, Determinism, StorageDeposit};
+use sp_core::crypto::UncheckedFrom;
+use sp_io::hashing::blake2_256;
+use sp_keystore::{KeystoreExt, testing::KeyStore};
 use sp_runtime::{
 	Perbill,
 	traits::{BlakeTwo256, Hash, IdentityLookup, Convert},
 	testing::{Header, H256},
-	AccountId32,
+	AccountId32, DispatchError,
 };
 use frame_support::{
-	assert_ok, assert_err_ignore_postinfo, impl_outer_dispatch, impl_outer_event,
-	impl_outer_origin, parameter_types, StorageMap,
-	traits::{Currency, ReservableCurrency},
-	weights::{Weight, PostDispatchInfo},
+	assert_ok, assert_err, assert_err_ignore_postinfo, impl_outer_dispatch, impl_outer_event,
+	impl_outer_origin, parameter_types, StorageMap, StorageValue, StorageDoubleMap,
+	storage::{child::{self, ChildInfo}, unhashed},
+	traits::{Currency, ReservableCurrency, OnInitialize, OnRuntimeUpgrade},
+	weights::{Weight, PostDispatchInfo, DispatchClass, constants::WEIGHT_PER_SECOND},
 	dispatch::DispatchErrorWithPostInfo,
 };
 use frame_system::{self as system, EventRecord, Phase};
+use std::{cell::RefCell, sync::Arc};
 
 mod contracts {
 	// Re-export contents of the root. This basically
@@ -75,11 +86,11 @@ pub mod test_utils {
 	use frame_support::{StorageMap, traits::Currency};
 
 	pub fn set_storage(addr: &AccountIdOf<Test>, key: &StorageKey, value: Option<Vec<u8>>) {
-		let contract_info = <ContractInfoOf::<Test>>::get(&addr).unwrap().get_alive().unwrap();
+		let contract_info = <ContractInfoOf::<Test>>::get(&addr).unwrap();
 		Storage::<Test>::write(addr, &contract_info.trie_id, key, value).unwrap();
 	}
 	pub fn get_storage(addr: &AccountIdOf<Test>, key: &StorageKey) -> Option<Vec<u8>> {
-		let contract_info = <ContractInfoOf::<Test>>::get(&addr).unwrap().get_alive().unwrap();
+		let contract_info = <ContractInfoOf::<Test>>::get(&addr).unwrap();
 		Storage::<Test>::read(&contract_info.trie_id, key)
 	}
 	pub fn place_contract(address: &AccountIdOf<Test>, code_hash: CodeHash<Test>) {
@@ -105,7 +116,7 @@ pub mod test_utils {
 pub struct Test;
 parameter_types! {
 	pub const BlockHashCount: u64 = 250;
-	pub const MaximumBlockWeight: Weight = 1024;
+	pub const MaximumBlockWeight: Weight = 2 * WEIGHT_PER_SECOND;
 	pub const MaximumBlockLength: u32 = 2 * 1024;
 	pub const AvailableBlockRatio: Perbill = Perbill::one();
 	pub static ExistentialDeposit: u64 = 0;
@@ -156,14 +167,13 @@ impl pallet_timestamp::Config for Test {
 	type WeightInfo = ();
 }
 parameter_types! {
-	pub const SignedClaimHandicap: u64 = 2;
-	pub const TombstoneDeposit: u64 = 16;
-	pub const StorageSizeOffset: u32 = 8;
-	pub const RentByteFee: u64 = 4;
-	pub const RentDepositOffset: u64 = 10_000;
-	pub const SurchargeReward: u64 = 150;
+	pub static ContractDeposit: u64 = 0;
+	pub static DepositPerByte: u64 = 0;
+	pub static DepositPerItem: u64 = 0;
 	pub const MaxDepth: u32 = 100;
 	pub const MaxValueSize: u32 = 16_384;
+	pub const DeletionQueueDepth: u32 = 1024;
+	pub const DeletionWeightLimit: Weight = 500_000_000_000;
 }
 
 parameter_types! {
@@ -176,22 +186,123 @@ impl Convert<Weight, BalanceOf<Self>> for Test {
 	}
 }
 
+thread_local! {
+	static TEST_EXTENSION: RefCell<TestExtension> = Default::default();
+}
+
+/// A chain extension registered under id `0` that is used to test the chain extension API.
+pub struct TestExtension {
+	enabled: bool,
+	last_seen_buffer: Vec<u8>,
+	last_seen_inputs: (u32, u32, u32, u32),
+}
+
+/// A chain extension registered under id `1` that reverts with the encoded caller.
+pub struct RevertingExtension;
+
+impl TestExtension {
+	fn disable() {
+		TEST_EXTENSION.with(|e| e.borrow_mut().enabled = false)
+	}
+
+	fn last_seen_buffer() -> Vec<u8> {
+		TEST_EXTENSION.with(|e| e.borrow().last_seen_buffer.clone())
+	}
+
+	fn last_seen_inputs() -> (u32, u32, u32, u32) {
+		TEST_EXTENSION.with(|e| e.borrow().last_seen_inputs)
+	}
+}
+
+impl Default for TestExtension {
+	fn default() -> Self {
+		Self {
+			enabled: true,
+			last_seen_buffer: vec![],
+			last_seen_inputs: (0, 0, 0, 0),
+		}
+	}
+}
+
+impl ChainExtension<Test> for TestExtension {
+	fn call<E>(func_id: u32, env: Environment<E, InitState>) -> ExtensionResult<RetVal>
+	where
+		E: Ext<T = Test>,
+		<E::T as SysConfig>::AccountId: UncheckedFrom<<E::T as SysConfig>::Hash> + AsRef<[u8]>,
+	{
+		match func_id {
+			0 => {
+				let mut env = env.buf_in_buf_out();
+				let input = env.read(16)?;
+				env.write(&input, false, None)?;
+				TEST_EXTENSION.with(|e| e.borrow_mut().last_seen_buffer = input);
+				Ok(RetVal::Converging(func_id))
+			},
+			1 => {
+				let env = env.only_in();
+				TEST_EXTENSION.with(|e|
+					e.borrow_mut().last_seen_inputs = (
+						env.val0(), env.val1(), env.val2(), env.val3()
+					)
+				);
+				Ok(RetVal::Converging(func_id))
+			},
+			2 => {
+				let mut env = env.buf_in_buf_out();
+				let (_, weight): (u32, u32) = env.read_as()?;
+				env.charge_weight(weight.into())?;
+				Ok(RetVal::Converging(func_id))
+			},
+			_ => Err(Error::<Test>::NoChainExtension.into()),
+		}
+	}
+
+	fn enabled() -> bool {
+		TEST_EXTENSION.with(|e| e.borrow().enabled)
+	}
+}
+
+impl RegisteredChainExtension<Test> for TestExtension {
+	const ID: u16 = 0;
+}
+
+impl ChainExtension<Test> for RevertingExtension {
+	fn call<E>(_func_id: u32, env: Environment<E, InitState>) -> ExtensionResult<RetVal>
+	where
+		E: Ext<T = Test>,
+		<E::T as SysConfig>::AccountId: UncheckedFrom<<E::T as SysConfig>::Hash> + AsRef<[u8]>,
+	{
+		let mut env = env.only_in();
+		Ok(RetVal::Diverging {
+			flags: ReturnFlags::REVERT,
+			data: env.ext().caller().encode(),
+		})
+	}
+
+	fn enabled() -> bool {
+		TEST_EXTENSION.with(|e| e.borrow().enabled)
+	}
+}
+
+impl RegisteredChainExtension<Test> for RevertingExtension {
+	const ID: u16 = 1;
+}
+
 impl Config for Test {
 	type Time = Timestamp;
 	type Randomness = Randomness;
 	type Currency = Balances;
 	type Event = MetaEvent;
-	type RentPayment = ();
-	type SignedClaimHandicap = SignedClaimHandicap;
-	type TombstoneDeposit = TombstoneDeposit;
-	type StorageSizeOffset = StorageSizeOffset;
-	type RentByteFee = RentByteFee;
-	type RentDepositOffset = RentDepositOffset;
-	type SurchargeReward = SurchargeReward;
+	type ContractDeposit = ContractDeposit;
+	type DepositPerByte = DepositPerByte;
+	type DepositPerItem = DepositPerItem;
 	type MaxDepth = MaxDepth;
 	type MaxValueSize = MaxValueSize;
 	type WeightPrice = Self;
 	type WeightInfo = ();
+	type ChainExtension = (TestExtension, RevertingExtension);
+	type DeletionQueueDepth = DeletionQueueDepth;
+	type DeletionWeightLimit = DeletionWeightLimit;
 }
 
 type Balances = pallet_balances::Module<Test>;
@@ -209,11 +320,17 @@ const GAS_LIMIT: Gas = 10_000_000_000;
 
 pub struct ExtBuilder {
 	existential_deposit: u64,
+	contract_deposit: u64,
+	deposit_per_byte: u64,
+	deposit_per_item: u64,
 }
 impl Default for ExtBuilder {
 	fn default() -> Self {
 		Self {
 			existential_deposit: 1,
+			contract_deposit: 0,
+			deposit_per_byte: 0,
+			deposit_per_item: 0,
 		}
 	}
 }
@@ -222,8 +339,22 @@ impl ExtBuilder {
 		self.existential_deposit = existential_deposit;
 		self
 	}
+	pub fn storage_deposit(
+		mut self,
+		contract_deposit: u64,
+		deposit_per_byte: u64,
+		deposit_per_item: u64,
+	) -> Self {
+		self.contract_deposit = contract_deposit;
+		self.deposit_per_byte = deposit_per_byte;
+		self.deposit_per_item = deposit_per_item;
+		self
+	}
 	pub fn set_associated_consts(&self) {
 		EXISTENTIAL_DEPOSIT.with(|v| *v.borrow_mut() = self.existential_deposit);
+		CONTRACT_DEPOSIT.with(|v| *v.borrow_mut() = self.contract_deposit);
+		DEPOSIT_PER_BYTE.with(|v| *v.borrow_mut() = self.deposit_per_byte);
+		DEPOSIT_PER_ITEM.with(|v| *v.borrow_mut() = self.deposit_per_item);
 	}
 	pub fn build(self) -> sp_io::TestExternalities {
 		self.set_associated_consts();
@@ -238,6 +369,7 @@ impl ExtBuilder {
 			},
 		}.assimilate_storage(&mut t).unwrap();
 		let mut ext = sp_io::TestExternalities::new(t);
+		ext.register_extension(KeystoreExt(Arc::new(KeyStore::new())));
 		ext.execute_with(|| System::set_block_number(1));
 		ext
 	}
@@ -269,7 +401,7 @@ fn calling_plain_account_fails() {
 		let _ = Balances::deposit_creating(&ALICE, 100_000_000);
 
 		assert_eq!(
-			Contracts::call(Origin::signed(ALICE), BOB, 0, GAS_LIMIT, Vec::new()),
+			Contracts::call(Origin::signed(ALICE), BOB, 0, GAS_LIMIT, None, Vec::new()),
 			Err(
 				DispatchErrorWithPostInfo {
 					error: Error::<Test>::NotCallable.into(),
@@ -295,31 +427,27 @@ fn account_removal_does_not_remove_storage() {
 
 		// Set up two accounts with free balance above the existential threshold.
 		{
-			let alice_contract_info = ContractInfo::Alive(RawAliveContractInfo {
+			let alice_contract_info = ContractInfo::<Test> {
 				trie_id: trie_id1.clone(),
 				storage_size: 0,
-				empty_pair_count: 0,
-				total_pair_count: 0,
-				deduct_block: System::block_number(),
+				pair_count: 0,
 				code_hash: H256::repeat_byte(1),
-				rent_allowance: 40,
-				last_write: None,
-			});
+				storage_deposit: 0,
+				deposit_written_off: 0,
+			};
 			let _ = Balances::deposit_creating(&ALICE, 110);
 			ContractInfoOf::<Test>::insert(ALICE, &alice_contract_info);
 			set_storage(&ALICE, &key1, Some(b"1".to_vec()));
 			set_storage(&ALICE, &key2, Some(b"2".to_vec()));
 
-			let bob_contract_info = ContractInfo::Alive(RawAliveContractInfo {
+			let bob_contract_info = ContractInfo::<Test> {
 				trie_id: trie_id2.clone(),
 				storage_size: 0,
-				empty_pair_count: 0,
-				total_pair_count: 0,
-				deduct_block: System::block_number(),
+				pair_count: 0,
 				code_hash: H256::repeat_byte(2),
-				rent_allowance: 40,
-				last_write: None,
-			});
+				storage_deposit: 0,
+				deposit_written_off: 0,
+			};
 			let _ = Balances::deposit_creating(&BOB, 110);
 			ContractInfoOf::<Test>::insert(BOB, &bob_contract_info);
 			set_storage(&BOB, &key1, Some(b"3".to_vec()));
@@ -369,13 +497,16 @@ fn instantiate_and_call_and_deposit_event() {
 			let _ = Balances::deposit_creating(&ALICE, 1_000_000);
 			let subsistence = super::ConfigCache::<Test>::subsistence_threshold_uncached();
 
-			assert_ok!(Contracts::put_code(Origin::signed(ALICE), wasm));
+			assert_ok!(Contracts::upload_code(
+				Origin::signed(ALICE), wasm, None, Determinism::Enforced,
+			));
 
 			// Check at the end to get hash on error easily
 			let creation = Contracts::instantiate(
 				Origin::signed(ALICE),
 				subsistence,
 				GAS_LIMIT,
+				None,
 				code_hash.into(),
 				vec![],
 				vec![],
@@ -448,11 +579,14 @@ fn deposit_event_max_value_limit() {
 		.execute_with(|| {
 			// Create
 			let _ = Balances::deposit_creating(&ALICE, 1_000_000);
-			assert_ok!(Contracts::put_code(Origin::signed(ALICE), wasm));
+			assert_ok!(Contracts::upload_code(
+				Origin::signed(ALICE), wasm, None, Determinism::Enforced,
+			));
 			assert_ok!(Contracts::instantiate(
 				Origin::signed(ALICE),
 				30_000,
 				GAS_LIMIT,
+				None,
 				code_hash.into(),
 				vec![],
 				vec![],
@@ -460,11 +594,7 @@ fn deposit_event_max_value_limit() {
 			let addr = Contracts::contract_address(&ALICE, &code_hash, &[]);
 
 			// Check creation
-			let bob_contract = ContractInfoOf::<Test>::get(addr.clone())
-				.unwrap()
-				.get_alive()
-				.unwrap();
-			assert_eq!(bob_contract.rent_allowance, <BalanceOf<Test>>::max_value());
+			assert!(ContractInfoOf::<Test>::contains_key(&addr));
 
 			// Call contract with allowed storage value.
 			assert_ok!(Contracts::call(
@@ -472,6 +602,7 @@ fn deposit_event_max_value_limit() {
 				addr.clone(),
 				0,
 				GAS_LIMIT * 2, // we are copying a huge buffer,
+				None,
 				<Test as Config>::MaxValueSize::get().encode(),
 			));
 
@@ -482,6 +613,7 @@ fn deposit_event_max_value_limit() {
 					addr,
 					0,
 					GAS_LIMIT,
+					None,
 					(<Test as Config>::MaxValueSize::get() + 1).encode(),
 				),
 				Error::<Test>::ValueTooLarge,
@@ -499,12 +631,15 @@ fn run_out_of_gas() {
 		.execute_with(|| {
 			let _ = Balances::deposit_creating(&ALICE, 1_000_000);
 
-			assert_ok!(Contracts::put_code(Origin::signed(ALICE), wasm));
+			assert_ok!(Contracts::upload_code(
+				Origin::signed(ALICE), wasm, None, Determinism::Enforced,
+			));
 
 			assert_ok!(Contracts::instantiate(
 				Origin::signed(ALICE),
 				100,
 				GAS_LIMIT,
+				None,
 				code_hash.into(),
 				vec![],
 				vec![],
@@ -519,6 +654,7 @@ fn run_out_of_gas() {
 					addr, // newly created account
 					0,
 					67_500_000,
+					None,
 					vec![],
 				),
 				Error::<Test>::OutOfGas,
@@ -526,57 +662,16 @@ fn run_out_of_gas() {
 		});
 }
 
-/// Input data for each call in set_rent code
+/// Input data for each call in store_call code
 mod call {
-	use super::{AccountIdOf, Test};
 	pub fn set_storage_4_byte() -> Vec<u8> { 0u32.to_le_bytes().to_vec() }
 	pub fn remove_storage_4_byte() -> Vec<u8> { 1u32.to_le_bytes().to_vec() }
-	pub fn transfer(to: &AccountIdOf<Test>) -> Vec<u8> {
-		2u32.to_le_bytes().iter().chain(AsRef::<[u8]>::as_ref(to)).cloned().collect()
-	}
 	pub fn null() -> Vec<u8> { 3u32.to_le_bytes().to_vec() }
 }
 
-/// Test correspondence of set_rent code and its hash.
-/// Also test that encoded extrinsic in code correspond to the correct transfer
-#[test]
-fn test_set_rent_code_and_hash() {
-	let (wasm, code_hash) = compile_module::<Test>("set_rent").unwrap();
-
-	ExtBuilder::default()
-		.existential_deposit(50)
-		.build()
-		.execute_with(|| {
-			let _ = Balances::deposit_creating(&ALICE, 1_000_000);
-			assert_ok!(Contracts::put_code(Origin::signed(ALICE), wasm));
-
-			// If you ever need to update the wasm source this test will fail
-			// and will show you the actual hash.
-			assert_eq!(System::events(), vec![
-				EventRecord {
-					phase: Phase::Initialization,
-					event: MetaEvent::system(frame_system::RawEvent::NewAccount(ALICE)),
-					topics: vec![],
-				},
-				EventRecord {
-					phase: Phase::Initialization,
-					event: MetaEvent::balances(pallet_balances::RawEvent::Endowed(
-						ALICE, 1_000_000
-					)),
-					topics: vec![],
-				},
-				EventRecord {
-					phase: Phase::Initialization,
-					event: MetaEvent::contracts(RawEvent::CodeStored(code_hash.into())),
-					topics: vec![],
-				},
-			]);
-		});
-}
-
 #[test]
 fn storage_size() {
-	let (wasm, code_hash) = compile_module::<Test>("set_rent").unwrap();
+	let (wasm, code_hash) = compile_module::<Test>("store_call").unwrap();
 
 	// Storage size
 	ExtBuilder::default()
@@ -585,80 +680,67 @@ fn storage_size() {
 		.execute_with(|| {
 			// Create
 			let _ = Balances::deposit_creating(&ALICE, 1_000_000);
-			assert_ok!(Contracts::put_code(Origin::signed(ALICE), wasm));
+			assert_ok!(Contracts::upload_code(
+				Origin::signed(ALICE), wasm, None, Determinism::Enforced,
+			));
 			assert_ok!(Contracts::instantiate(
 				Origin::signed(ALICE),
 				30_000,
 				GAS_LIMIT,
+				None,
 				code_hash.into(),
-				<Test as pallet_balances::Config>::Balance::from(1_000u32).encode(), // rent allowance
+				vec![],
 				vec![],
 			));
 			let addr = Contracts::contract_address(&ALICE, &code_hash, &[]);
 			let bob_contract = ContractInfoOf::<Test>::get(&addr)
-				.unwrap()
-				.get_alive()
 				.unwrap();
 			assert_eq!(
 				bob_contract.storage_size,
 				4
 			);
 			assert_eq!(
-				bob_contract.total_pair_count,
+				bob_contract.pair_count,
 				1,
 			);
-			assert_eq!(
-				bob_contract.empty_pair_count,
-				0,
-			);
 
 			assert_ok!(Contracts::call(
 				Origin::signed(ALICE),
 				addr.clone(),
 				0,
 				GAS_LIMIT,
+				None,
 				call::set_storage_4_byte()
 			));
 			let bob_contract = ContractInfoOf::<Test>::get(&addr)
-				.unwrap()
-				.get_alive()
 				.unwrap();
 			assert_eq!(
 				bob_contract.storage_size,
 				4 + 4
 			);
 			assert_eq!(
-				bob_contract.total_pair_count,
+				bob_contract.pair_count,
 				2,
 			);
-			assert_eq!(
-				bob_contract.empty_pair_count,
-				0,
-			);
 
 			assert_ok!(Contracts::call(
 				Origin::signed(ALICE),
 				addr.clone(),
 				0,
 				GAS_LIMIT,
+				None,
 				call::remove_storage_4_byte()
 			));
 			let bob_contract = ContractInfoOf::<Test>::get(&addr)
-				.unwrap()
-				.get_alive()
 				.unwrap();
 			assert_eq!(
 				bob_contract.storage_size,
 				4
 			);
 			assert_eq!(
-				bob_contract.total_pair_count,
+				bob_contract.pair_count,
 				1,
 			);
-			assert_eq!(
-				bob_contract.empty_pair_count,
-				0,
-			);
 		});
 }
 
@@ -670,19 +752,20 @@ fn empty_kv_pairs() {
 		.build()
 		.execute_with(|| {
 			let _ = Balances::deposit_creating(&ALICE, 1_000_000);
-			assert_ok!(Contracts::put_code(Origin::signed(ALICE), wasm));
+			assert_ok!(Contracts::upload_code(
+				Origin::signed(ALICE), wasm, None, Determinism::Enforced,
+			));
 			assert_ok!(Contracts::instantiate(
 				Origin::signed(ALICE),
 				30_000,
 				GAS_LIMIT,
+				None,
 				code_hash.into(),
 				vec![],
 				vec![],
 			));
 			let addr = Contracts::contract_address(&ALICE, &code_hash, &[]);
 			let bob_contract = ContractInfoOf::<Test>::get(&addr)
-				.unwrap()
-				.get_alive()
 				.unwrap();
 
 			assert_eq!(
@@ -690,667 +773,1097 @@ fn empty_kv_pairs() {
 				0,
 			);
 			assert_eq!(
-				bob_contract.total_pair_count,
-				1,
-			);
-			assert_eq!(
-				bob_contract.empty_pair_count,
+				bob_contract.pair_count,
 				1,
 			);
 		});
 }
 
-fn initialize_block(number: u64) {
-	System::initialize(
-		&number,
-		&[0u8; 32].into(),
-		&[0u8; 32].into(),
-		&Default::default(),
-		Default::default(),
-	);
-}
-
 #[test]
-fn deduct_blocks() {
-	let (wasm, code_hash) = compile_module::<Test>("set_rent").unwrap();
+fn storage_deposit_is_charged_and_refunded() {
+	let (wasm, code_hash) = compile_module::<Test>("store_call").unwrap();
 
 	ExtBuilder::default()
 		.existential_deposit(50)
+		.storage_deposit(100, 2, 10)
 		.build()
 		.execute_with(|| {
-			// Create
 			let _ = Balances::deposit_creating(&ALICE, 1_000_000);
-			assert_ok!(Contracts::put_code(Origin::signed(ALICE), wasm));
+			// The code deposit is paid by someone else so that only the contract's deposit
+			// affects the balance of `ALICE`.
+			let _ = Balances::deposit_creating(&CHARLIE, 1_000_000);
+			assert_ok!(Contracts::upload_code(
+				Origin::signed(CHARLIE), wasm, None, Determinism::Enforced,
+			));
+
+			// The constructor stores one item of 4 bytes.
 			assert_ok!(Contracts::instantiate(
 				Origin::signed(ALICE),
 				30_000,
-				GAS_LIMIT, code_hash.into(),
-				<Test as pallet_balances::Config>::Balance::from(1_000u32).encode(), // rent allowance
+				GAS_LIMIT,
+				None,
+				code_hash.into(),
+				vec![],
 				vec![],
 			));
 			let addr = Contracts::contract_address(&ALICE, &code_hash, &[]);
+			let deposit = 100 + 4 * 2 + 10;
+			assert_eq!(ContractInfoOf::<Test>::get(&addr).unwrap().storage_deposit, deposit);
+			assert_eq!(Balances::reserved_balance(&addr), deposit);
+			assert_eq!(Balances::free_balance(&addr), 30_000);
+			assert_eq!(Balances::free_balance(&ALICE), 1_000_000 - 30_000 - deposit);
 
-			// Check creation
-			let bob_contract = ContractInfoOf::<Test>::get(&addr).unwrap().get_alive().unwrap();
-			assert_eq!(bob_contract.rent_allowance, 1_000);
-
-			// Advance 4 blocks
-			initialize_block(5);
+			// Adding another item charges the caller.
+			assert_ok!(Contracts::call(
+				Origin::signed(ALICE),
+				addr.clone(),
+				0,
+				GAS_LIMIT,
+				None,
+				call::set_storage_4_byte(),
+			));
+			let added = 4 * 2 + 10;
+			assert_eq!(ContractInfoOf::<Test>::get(&addr).unwrap().storage_deposit, deposit + added);
+			assert_eq!(Balances::reserved_balance(&addr), deposit + added);
+			assert_eq!(Balances::free_balance(&addr), 30_000);
+			assert_eq!(Balances::free_balance(&ALICE), 1_000_000 - 30_000 - deposit - added);
 
-			// Trigger rent through call
-			assert_ok!(
-				Contracts::call(Origin::signed(ALICE), addr.clone(), 0, GAS_LIMIT, call::null())
-			);
+			// Removing the item again refunds the caller.
+			assert_ok!(Contracts::call(
+				Origin::signed(ALICE),
+				addr.clone(),
+				0,
+				GAS_LIMIT,
+				None,
+				call::remove_storage_4_byte(),
+			));
+			assert_eq!(ContractInfoOf::<Test>::get(&addr).unwrap().storage_deposit, deposit);
+			assert_eq!(Balances::reserved_balance(&addr), deposit);
+			assert_eq!(Balances::free_balance(&addr), 30_000);
+			assert_eq!(Balances::free_balance(&ALICE), 1_000_000 - 30_000 - deposit);
+		});
+}
 
-			// Check result
-			let rent = (8 + 4 - 3) // storage size = size_offset + deploy_set_storage - deposit_offset
-				* 4 // rent byte price
-				* 4; // blocks to rent
-			let bob_contract = ContractInfoOf::<Test>::get(&addr).unwrap().get_alive().unwrap();
-			assert_eq!(bob_contract.rent_allowance, 1_000 - rent);
-			assert_eq!(bob_contract.deduct_block, 5);
-			assert_eq!(Balances::free_balance(&addr), 30_000 - rent);
+#[test]
+fn storage_deposit_is_reported_by_bare_call() {
+	let (wasm, code_hash) = compile_module::<Test>("store_call").unwrap();
 
-			// Advance 7 blocks more
-			initialize_block(12);
+	ExtBuilder::default()
+		.existential_deposit(50)
+		.storage_deposit(100, 2, 10)
+		.build()
+		.execute_with(|| {
+			let _ = Balances::deposit_creating(&ALICE, 1_000_000);
+			assert_ok!(Contracts::upload_code(
+				Origin::signed(ALICE), wasm, None, Determinism::Enforced,
+			));
+			assert_ok!(Contracts::instantiate(
+				Origin::signed(ALICE),
+				30_000,
+				GAS_LIMIT,
+				None,
+				code_hash.into(),
+				vec![],
+				vec![],
+			));
+			let addr = Contracts::contract_address(&ALICE, &code_hash, &[]);
 
-			// Trigger rent through call
-			assert_ok!(
-				Contracts::call(Origin::signed(ALICE), addr.clone(), 0, GAS_LIMIT, call::null())
+			let result = Contracts::bare_call(
+				ALICE, addr.clone(), 0, GAS_LIMIT, None, call::set_storage_4_byte(), false,
+				Determinism::Enforced,
 			);
+			assert_ok!(result.exec_result);
+			assert_eq!(result.storage_deposit, StorageDeposit::Charge(18));
 
-			// Check result
-			let rent_2 = (8 + 4 - 2) // storage size = size_offset + deploy_set_storage - deposit_offset
-				* 4 // rent byte price
-				* 7; // blocks to rent
-			let bob_contract = ContractInfoOf::<Test>::get(&addr).unwrap().get_alive().unwrap();
-			assert_eq!(bob_contract.rent_allowance, 1_000 - rent - rent_2);
-			assert_eq!(bob_contract.deduct_block, 12);
-			assert_eq!(Balances::free_balance(&addr), 30_000 - rent - rent_2);
-
-			// Second call on same block should have no effect on rent
-			assert_ok!(
-				Contracts::call(Origin::signed(ALICE), addr.clone(), 0, GAS_LIMIT, call::null())
+			let result = Contracts::bare_call(
+				ALICE, addr.clone(), 0, GAS_LIMIT, None, call::null(), false,
+				Determinism::Enforced,
 			);
+			assert_ok!(result.exec_result);
+			assert_eq!(result.storage_deposit, StorageDeposit::Charge(0));
 
-			let bob_contract = ContractInfoOf::<Test>::get(&addr).unwrap().get_alive().unwrap();
-			assert_eq!(bob_contract.rent_allowance, 1_000 - rent - rent_2);
-			assert_eq!(bob_contract.deduct_block, 12);
-			assert_eq!(Balances::free_balance(&addr), 30_000 - rent - rent_2);
+			let result = Contracts::bare_call(
+				ALICE, addr.clone(), 0, GAS_LIMIT, None, call::remove_storage_4_byte(), false,
+				Determinism::Enforced,
+			);
+			assert_ok!(result.exec_result);
+			assert_eq!(result.storage_deposit, StorageDeposit::Refund(18));
 		});
 }
 
 #[test]
-fn call_contract_removals() {
-	removals(|addr| {
-		// Call on already-removed account might fail, and this is fine.
-		let _ = Contracts::call(Origin::signed(ALICE), addr, 0, GAS_LIMIT, call::null());
-		true
-	});
-}
-
-#[test]
-fn inherent_claim_surcharge_contract_removals() {
-	removals(|addr| Contracts::claim_surcharge(Origin::none(), addr, Some(ALICE)).is_ok());
-}
-
-#[test]
-fn signed_claim_surcharge_contract_removals() {
-	removals(|addr| Contracts::claim_surcharge(Origin::signed(ALICE), addr, None).is_ok());
-}
-
-#[test]
-fn claim_surcharge_malus() {
-	// Test surcharge malus for inherent
-	claim_surcharge(4, |addr| Contracts::claim_surcharge(Origin::none(), addr, Some(ALICE)).is_ok(), true);
-	claim_surcharge(3, |addr| Contracts::claim_surcharge(Origin::none(), addr, Some(ALICE)).is_ok(), true);
-	claim_surcharge(2, |addr| Contracts::claim_surcharge(Origin::none(), addr, Some(ALICE)).is_ok(), true);
-	claim_surcharge(1, |addr| Contracts::claim_surcharge(Origin::none(), addr, Some(ALICE)).is_ok(), false);
-
-	// Test surcharge malus for signed
-	claim_surcharge(4, |addr| Contracts::claim_surcharge(Origin::signed(ALICE), addr, None).is_ok(), true);
-	claim_surcharge(3, |addr| Contracts::claim_surcharge(Origin::signed(ALICE), addr, None).is_ok(), false);
-	claim_surcharge(2, |addr| Contracts::claim_surcharge(Origin::signed(ALICE), addr, None).is_ok(), false);
-	claim_surcharge(1, |addr| Contracts::claim_surcharge(Origin::signed(ALICE), addr, None).is_ok(), false);
-}
-
-/// Claim surcharge with the given trigger_call at the given blocks.
-/// If `removes` is true then assert that the contract is a tombstone.
-fn claim_surcharge(blocks: u64, trigger_call: impl Fn(AccountIdOf<Test>) -> bool, removes: bool) {
-	let (wasm, code_hash) = compile_module::<Test>("set_rent").unwrap();
+fn storage_bytes_and_gas_required_are_reported_by_bare_call() {
+	let (wasm, code_hash) = compile_module::<Test>("store_call").unwrap();
 
 	ExtBuilder::default()
 		.existential_deposit(50)
 		.build()
 		.execute_with(|| {
-			// Create
 			let _ = Balances::deposit_creating(&ALICE, 1_000_000);
-			assert_ok!(Contracts::put_code(Origin::signed(ALICE), wasm));
+			assert_ok!(Contracts::upload_code(
+				Origin::signed(ALICE), wasm, None, Determinism::Enforced,
+			));
 			assert_ok!(Contracts::instantiate(
 				Origin::signed(ALICE),
-				100,
-				GAS_LIMIT, code_hash.into(),
-				<Test as pallet_balances::Config>::Balance::from(1_000u32).encode(), // rent allowance
+				100_000,
+				GAS_LIMIT,
+				None,
+				code_hash.into(),
+				vec![],
 				vec![],
 			));
 			let addr = Contracts::contract_address(&ALICE, &code_hash, &[]);
 
-			// Advance blocks
-			initialize_block(blocks);
+			let result = Contracts::bare_call(
+				ALICE, addr.clone(), 0, GAS_LIMIT, None, call::set_storage_4_byte(), false,
+				Determinism::Enforced,
+			);
+			assert_ok!(result.exec_result);
+			assert_eq!(result.storage_bytes, 4);
+			assert!(result.gas_required >= result.gas_consumed);
+			let gas_required = result.gas_required;
+
+			let result = Contracts::bare_call(
+				ALICE, addr.clone(), 0, GAS_LIMIT, None, call::null(), false,
+				Determinism::Enforced,
+			);
+			assert_ok!(result.exec_result);
+			assert_eq!(result.storage_bytes, 0);
 
-			// Trigger rent through call
-			assert!(trigger_call(addr.clone()));
+			let result = Contracts::bare_call(
+				ALICE, addr.clone(), 0, GAS_LIMIT, None, call::remove_storage_4_byte(), false,
+				Determinism::Enforced,
+			);
+			assert_ok!(result.exec_result);
+			assert_eq!(result.storage_bytes, -4);
 
-			if removes {
-				assert!(ContractInfoOf::<Test>::get(&addr).unwrap().get_tombstone().is_some());
-			} else {
-				assert!(ContractInfoOf::<Test>::get(&addr).unwrap().get_alive().is_some());
-			}
+			// The call does not succeed when supplied with less gas than was reported.
+			let result = Contracts::bare_call(
+				ALICE, addr, 0, gas_required - 1, None, call::set_storage_4_byte(), false,
+				Determinism::Enforced,
+			);
+			assert!(result.exec_result.is_err());
 		});
 }
 
-/// Test for all kind of removals for the given trigger:
-/// * if balance is reached and balance > subsistence threshold
-/// * if allowance is exceeded
-/// * if balance is reached and balance < subsistence threshold
-///	    * this case cannot be triggered by a contract: we check whether a tombstone is left
-fn removals(trigger_call: impl Fn(AccountIdOf<Test>) -> bool) {
-	let (wasm, code_hash) = compile_module::<Test>("set_rent").unwrap();
+#[test]
+fn events_are_reported_by_bare_call_in_debug_mode() {
+	let (wasm, code_hash) = compile_module::<Test>("event_size").unwrap();
 
-	// Balance reached and superior to subsistence threshold
 	ExtBuilder::default()
 		.existential_deposit(50)
 		.build()
 		.execute_with(|| {
-			// Create
 			let _ = Balances::deposit_creating(&ALICE, 1_000_000);
-			assert_ok!(Contracts::put_code(Origin::signed(ALICE), wasm.clone()));
+			assert_ok!(Contracts::upload_code(
+				Origin::signed(ALICE), wasm, None, Determinism::Enforced,
+			));
 			assert_ok!(Contracts::instantiate(
 				Origin::signed(ALICE),
-				100,
-				GAS_LIMIT, code_hash.into(),
-				<Test as pallet_balances::Config>::Balance::from(1_000u32).encode(), // rent allowance
+				30_000,
+				GAS_LIMIT,
+				None,
+				code_hash.into(),
+				vec![],
 				vec![],
 			));
 			let addr = Contracts::contract_address(&ALICE, &code_hash, &[]);
 
-			let subsistence_threshold = 50 /*existential_deposit*/ + 16 /*tombstone_deposit*/;
-
-			// Trigger rent must have no effect
-			assert!(trigger_call(addr.clone()));
-			assert_eq!(ContractInfoOf::<Test>::get(&addr).unwrap().get_alive().unwrap().rent_allowance, 1_000);
-			assert_eq!(Balances::free_balance(&addr), 100);
-
-			// Advance blocks
-			initialize_block(10);
-
-			// Trigger rent through call
-			assert!(trigger_call(addr.clone()));
-			assert!(ContractInfoOf::<Test>::get(&addr).unwrap().get_tombstone().is_some());
-			assert_eq!(Balances::free_balance(&addr), subsistence_threshold);
-
-			// Advance blocks
-			initialize_block(20);
+			let result = Contracts::bare_call(
+				ALICE, addr.clone(), 0, GAS_LIMIT, None, 4u32.encode(), true,
+				Determinism::Enforced,
+			);
+			assert_ok!(result.exec_result);
+			assert_eq!(
+				result.events,
+				vec![ContractEvent { contract: addr.clone(), topics: vec![], data: vec![4, 0, 0, 0] }],
+			);
 
-			// Trigger rent must have no effect
-			assert!(trigger_call(addr.clone()));
-			assert!(ContractInfoOf::<Test>::get(&addr).unwrap().get_tombstone().is_some());
-			assert_eq!(Balances::free_balance(&addr), subsistence_threshold);
+			// Events are only recorded in debug mode.
+			let result = Contracts::bare_call(
+				ALICE, addr, 0, GAS_LIMIT, None, 4u32.encode(), false,
+				Determinism::Enforced,
+			);
+			assert_ok!(result.exec_result);
+			assert!(result.events.is_empty());
 		});
+}
+
+#[test]
+fn debug_message_is_reported_by_bare_call_in_debug_mode() {
+	let (wasm, code_hash) = compile_module::<Test>("debug_message_works").unwrap();
 
-	// Allowance exceeded
 	ExtBuilder::default()
 		.existential_deposit(50)
 		.build()
 		.execute_with(|| {
-			// Create
 			let _ = Balances::deposit_creating(&ALICE, 1_000_000);
-			assert_ok!(Contracts::put_code(Origin::signed(ALICE), wasm.clone()));
+			assert_ok!(Contracts::upload_code(
+				Origin::signed(ALICE), wasm, None, Determinism::Enforced,
+			));
 			assert_ok!(Contracts::instantiate(
 				Origin::signed(ALICE),
-				1_000,
+				30_000,
 				GAS_LIMIT,
+				None,
 				code_hash.into(),
-				<Test as pallet_balances::Config>::Balance::from(100u32).encode(), // rent allowance
+				vec![],
 				vec![],
 			));
 			let addr = Contracts::contract_address(&ALICE, &code_hash, &[]);
 
-			// Trigger rent must have no effect
-			assert!(trigger_call(addr.clone()));
-			assert_eq!(
-				ContractInfoOf::<Test>::get(&addr)
-					.unwrap()
-					.get_alive()
-					.unwrap()
-					.rent_allowance,
-				100
+			let result = Contracts::bare_call(
+				ALICE, addr.clone(), 0, GAS_LIMIT, None, vec![], true,
+				Determinism::Enforced,
+			);
+			assert_return_code!(result.exec_result.unwrap(), RuntimeReturnCode::Success);
+			assert_eq!(std::str::from_utf8(&result.debug_message).unwrap(), "Hello World!");
+
+			let result = Contracts::bare_call(
+				ALICE, addr, 0, GAS_LIMIT, None, vec![], false,
+				Determinism::Enforced,
 			);
-			assert_eq!(Balances::free_balance(&addr), 1_000);
-
-			// Advance blocks
-			initialize_block(10);
-
-			// Trigger rent through call
-			assert!(trigger_call(addr.clone()));
-			assert!(ContractInfoOf::<Test>::get(&addr)
-				.unwrap()
-				.get_tombstone()
-				.is_some());
-			// Balance should be initial balance - initial rent_allowance
-			assert_eq!(Balances::free_balance(&addr), 900);
-
-			// Advance blocks
-			initialize_block(20);
-
-			// Trigger rent must have no effect
-			assert!(trigger_call(addr.clone()));
-			assert!(ContractInfoOf::<Test>::get(&addr)
-				.unwrap()
-				.get_tombstone()
-				.is_some());
-			assert_eq!(Balances::free_balance(&addr), 900);
+			assert_return_code!(result.exec_result.unwrap(), RuntimeReturnCode::LoggingDisabled);
+			assert!(result.debug_message.is_empty());
 		});
+}
+
+#[test]
+fn storage_deposit_limit_is_enforced() {
+	let (wasm, code_hash) = compile_module::<Test>("store_call").unwrap();
 
-	// Balance reached and inferior to subsistence threshold
 	ExtBuilder::default()
 		.existential_deposit(50)
+		.storage_deposit(100, 2, 10)
 		.build()
 		.execute_with(|| {
-			// Create
 			let _ = Balances::deposit_creating(&ALICE, 1_000_000);
-			let subsistence_threshold =
-				Balances::minimum_balance() + <Test as Config>::TombstoneDeposit::get();
-			assert_ok!(Contracts::put_code(Origin::signed(ALICE), wasm.clone()));
+			assert_ok!(Contracts::upload_code(
+				Origin::signed(ALICE), wasm, None, Determinism::Enforced,
+			));
+
+			// The instantiation requires a deposit of 118.
+			assert_err_ignore_postinfo!(
+				Contracts::instantiate(
+					Origin::signed(ALICE),
+					30_000,
+					GAS_LIMIT,
+					Some(117),
+					code_hash.into(),
+					vec![],
+					vec![],
+				),
+				Error::<Test>::StorageDepositLimitExhausted,
+			);
 			assert_ok!(Contracts::instantiate(
 				Origin::signed(ALICE),
-				50 + subsistence_threshold,
+				30_000,
 				GAS_LIMIT,
+				Some(118),
 				code_hash.into(),
-				<Test as pallet_balances::Config>::Balance::from(1_000u32).encode(), // rent allowance
+				vec![],
 				vec![],
 			));
 			let addr = Contracts::contract_address(&ALICE, &code_hash, &[]);
 
-			// Trigger rent must have no effect
-			assert!(trigger_call(addr.clone()));
-			assert_eq!(
-				ContractInfoOf::<Test>::get(&addr)
-					.unwrap()
-					.get_alive()
-					.unwrap()
-					.rent_allowance,
-				1_000
-			);
-			assert_eq!(
-				Balances::free_balance(&addr),
-				50 + subsistence_threshold,
+			// Adding an item requires a deposit of 18.
+			assert_err_ignore_postinfo!(
+				Contracts::call(
+					Origin::signed(ALICE),
+					addr.clone(),
+					0,
+					GAS_LIMIT,
+					Some(17),
+					call::set_storage_4_byte(),
+				),
+				Error::<Test>::StorageDepositLimitExhausted,
 			);
-
-			// Transfer funds
 			assert_ok!(Contracts::call(
 				Origin::signed(ALICE),
 				addr.clone(),
 				0,
 				GAS_LIMIT,
-				call::transfer(&BOB),
+				Some(18),
+				call::set_storage_4_byte(),
 			));
-			assert_eq!(
-				ContractInfoOf::<Test>::get(&addr)
-					.unwrap()
-					.get_alive()
-					.unwrap()
-					.rent_allowance,
-				1_000
-			);
-			assert_eq!(Balances::free_balance(&addr), subsistence_threshold);
 
-			// Advance blocks
-			initialize_block(10);
+			// A refund is always possible.
+			assert_ok!(Contracts::call(
+				Origin::signed(ALICE),
+				addr.clone(),
+				0,
+				GAS_LIMIT,
+				Some(0),
+				call::remove_storage_4_byte(),
+			));
+		});
+}
 
-			// Trigger rent through call
-			assert!(trigger_call(addr.clone()));
-			assert_matches!(ContractInfoOf::<Test>::get(&addr), Some(ContractInfo::Tombstone(_)));
-			assert_eq!(Balances::free_balance(&addr), subsistence_threshold);
+#[test]
+fn storage_deposit_requires_funds() {
+	let (wasm, code_hash) = compile_module::<Test>("store_call").unwrap();
 
-			// Advance blocks
-			initialize_block(20);
+	ExtBuilder::default()
+		.existential_deposit(50)
+		.storage_deposit(100, 2, 10)
+		.build()
+		.execute_with(|| {
+			// Enough for the endowment but not for the deposit of 118 on top of it.
+			let _ = Balances::deposit_creating(&ALICE, 30_000 + 150);
+			let _ = Balances::deposit_creating(&CHARLIE, 1_000_000);
+			assert_ok!(Contracts::upload_code(
+				Origin::signed(CHARLIE), wasm, None, Determinism::Enforced,
+			));
 
-			// Trigger rent must have no effect
-			assert!(trigger_call(addr.clone()));
-			assert_matches!(ContractInfoOf::<Test>::get(&addr), Some(ContractInfo::Tombstone(_)));
-			assert_eq!(Balances::free_balance(&addr), subsistence_threshold);
+			assert_err_ignore_postinfo!(
+				Contracts::instantiate(
+					Origin::signed(ALICE),
+					30_000,
+					GAS_LIMIT,
+					None,
+					code_hash.into(),
+					vec![],
+					vec![],
+				),
+				Error::<Test>::StorageDepositNotEnoughFunds,
+			);
+			assert_eq!(Balances::free_balance(&ALICE), 30_000 + 150);
 		});
 }
 
 #[test]
-fn call_removed_contract() {
-	let (wasm, code_hash) = compile_module::<Test>("set_rent").unwrap();
+fn storage_deposit_is_refunded_on_termination() {
+	let (wasm, code_hash) = compile_module::<Test>("self_destruct").unwrap();
 
-	// Balance reached and superior to subsistence threshold
 	ExtBuilder::default()
 		.existential_deposit(50)
+		.storage_deposit(100, 2, 10)
 		.build()
 		.execute_with(|| {
-			// Create
 			let _ = Balances::deposit_creating(&ALICE, 1_000_000);
-			assert_ok!(Contracts::put_code(Origin::signed(ALICE), wasm.clone()));
+			assert_ok!(Contracts::upload_code(
+				Origin::signed(ALICE), wasm, None, Determinism::Enforced,
+			));
 			assert_ok!(Contracts::instantiate(
 				Origin::signed(ALICE),
-				100,
-				GAS_LIMIT, code_hash.into(),
-				<Test as pallet_balances::Config>::Balance::from(1_000u32).encode(), // rent allowance
+				100_000,
+				GAS_LIMIT,
+				None,
+				code_hash.into(),
+				vec![],
 				vec![],
 			));
 			let addr = Contracts::contract_address(&ALICE, &code_hash, &[]);
+			let code_deposit = OwnerInfoOf::<Test>::get(&code_hash).unwrap().deposit;
 
-			// Calling contract should succeed.
-			assert_ok!(
-				Contracts::call(Origin::signed(ALICE), addr.clone(), 0, GAS_LIMIT, call::null())
-			);
-
-			// Advance blocks
-			initialize_block(10);
+			// The contract does not store anything.
+			assert_eq!(Balances::reserved_balance(&addr), 100);
+			assert_eq!(Balances::free_balance(&ALICE), 1_000_000 - 100_000 - 100 - code_deposit);
 
-			// Calling contract should remove contract and fail.
-			assert_err_ignore_postinfo!(
-				Contracts::call(Origin::signed(ALICE), addr.clone(), 0, GAS_LIMIT, call::null()),
-				Error::<Test>::NotCallable
+			// Call without input data which triggers termination.
+			let result = Contracts::bare_call(
+				ALICE, addr.clone(), 0, GAS_LIMIT, None, vec![], false, Determinism::Enforced,
 			);
-			// Calling a contract that is about to evict shall emit an event.
-			assert_eq!(System::events(), vec![
-				EventRecord {
-					phase: Phase::Initialization,
-					event: MetaEvent::contracts(RawEvent::Evicted(addr.clone(), true)),
-					topics: vec![],
-				},
-			]);
+			assert_ok!(result.exec_result);
+			assert_eq!(result.storage_deposit, StorageDeposit::Refund(100));
+			assert!(ContractInfoOf::<Test>::get(&addr).is_none());
 
-			// Subsequent contract calls should also fail.
-			assert_err_ignore_postinfo!(
-				Contracts::call(Origin::signed(ALICE), addr, 0, GAS_LIMIT, call::null()),
-				Error::<Test>::NotCallable
-			);
-		})
+			// The deposit goes back to the origin and the rest to the beneficiary. The code
+			// was used by this contract only and therefore is removed, too.
+			assert_eq!(Balances::total_balance(&addr), 0);
+			assert_eq!(Balances::free_balance(&ALICE), 1_000_000 - 100_000);
+			assert_eq!(Balances::reserved_balance(&ALICE), 0);
+			assert!(!OwnerInfoOf::<Test>::contains_key(&code_hash));
+			assert_eq!(Balances::free_balance(DJANGO), 100_000);
+		});
 }
 
 #[test]
-fn default_rent_allowance_on_instantiate() {
-	let (wasm, code_hash) = compile_module::<Test>("check_default_rent_allowance").unwrap();
-
+fn migration_from_rent_to_storage_deposit() {
 	ExtBuilder::default()
 		.existential_deposit(50)
+		.storage_deposit(100, 2, 10)
 		.build()
 		.execute_with(|| {
-			// Create
-			let _ = Balances::deposit_creating(&ALICE, 1_000_000);
-			assert_ok!(Contracts::put_code(Origin::signed(ALICE), wasm));
+			let trie_id = Storage::<Test>::generate_trie_id(&BOB);
+			let code_hash = H256::repeat_byte(1);
+
+			// `BOB` is an alive contract in the old layout:
+			// (variant, trie_id, storage_size, empty_pair_count, total_pair_count, code_hash,
+			// rent_allowance, deduct_block, last_write)
+			let _ = Balances::deposit_creating(&BOB, 1_000);
+			unhashed::put_raw(
+				&ContractInfoOf::<Test>::hashed_key_for(&BOB),
+				&(0u8, trie_id.clone(), 8u32, 0u32, 2u32, code_hash, 100u64, 1u64, None::<u64>)
+					.encode(),
+			);
+
+			// `CHARLIE` is a poor alive contract which can't afford its whole deposit.
+			let _ = Balances::deposit_creating(&CHARLIE, 100);
+			unhashed::put_raw(
+				&ContractInfoOf::<Test>::hashed_key_for(&CHARLIE),
+				&(0u8, trie_id.clone(), 8u32, 0u32, 2u32, code_hash, 100u64, 1u64, None::<u64>)
+					.encode(),
+			);
+
+			// `DJANGO` is a tombstone.
+			unhashed::put_raw(
+				&ContractInfoOf::<Test>::hashed_key_for(&DJANGO),
+				&(1u8, H256::repeat_byte(2)).encode(),
+			);
+
+			super::StorageVersion::put(super::Releases::V1_0_0);
+			Contracts::on_runtime_upgrade();
+
+			let deposit = 100 + 8 * 2 + 2 * 10;
+			assert_eq!(
+				ContractInfoOf::<Test>::get(&BOB),
+				Some(ContractInfo::<Test> {
+					trie_id: trie_id.clone(),
+					storage_size: 8,
+					pair_count: 2,
+					code_hash,
+					storage_deposit: deposit,
+					deposit_written_off: 0,
+				}),
+			);
+			assert_eq!(Balances::reserved_balance(&BOB), deposit);
+			assert_eq!(Balances::free_balance(&BOB), 1_000 - deposit);
+
+			// Only what keeps the account alive is reserved. The rest is written off.
+			let info = ContractInfoOf::<Test>::get(&CHARLIE).unwrap();
+			assert_eq!(info.storage_deposit, 50);
+			assert_eq!(info.deposit_written_off, deposit - 50);
+			assert_eq!(Balances::reserved_balance(&CHARLIE), 50);
+			assert_eq!(Balances::free_balance(&CHARLIE), 50);
+
+			// The written off deposit is not charged from the next caller.
+			let _ = Balances::deposit_creating(&ALICE, 1_000);
+			let mut meter = crate::storage::meter::Meter::<Test>::new(ALICE, None);
+			assert_ok!(meter.charge(&CHARLIE));
+			assert_eq!(meter.deposit(), StorageDeposit::Charge(0));
+			assert_eq!(Balances::free_balance(&ALICE), 1_000);
+
+			assert!(!ContractInfoOf::<Test>::contains_key(&DJANGO));
+			assert_eq!(super::StorageVersion::get(), super::Releases::V3_0_0);
+		});
+}
+
+#[test]
+fn migration_adds_owner_info_to_stored_code() {
+	let (wasm, code_hash) = compile_module::<Test>("self_destruct").unwrap();
+	let (unused_wasm, unused_code_hash) = compile_module::<Test>("return_from_start_fn").unwrap();
+	ExtBuilder::default().existential_deposit(50).build().execute_with(|| {
+		let _ = Balances::deposit_creating(&ALICE, 1_000_000);
+		assert_ok!(Contracts::upload_code(
+			Origin::signed(ALICE), wasm, None, Determinism::Enforced,
+		));
+		assert_ok!(Contracts::upload_code(
+			Origin::signed(ALICE), unused_wasm, None, Determinism::Enforced,
+		));
+		for salt in 0..2u8 {
 			assert_ok!(Contracts::instantiate(
 				Origin::signed(ALICE),
-				30_000,
+				100_000,
 				GAS_LIMIT,
+				None,
 				code_hash.into(),
 				vec![],
-				vec![],
+				vec![salt],
 			));
-			let addr = Contracts::contract_address(&ALICE, &code_hash, &[]);
+		}
 
-			// Check creation
-			let bob_contract = ContractInfoOf::<Test>::get(&addr).unwrap().get_alive().unwrap();
-			assert_eq!(bob_contract.rent_allowance, <BalanceOf<Test>>::max_value());
+		// Code stored prior to `V3_0_0` has no owner info.
+		OwnerInfoOf::<Test>::remove(&code_hash);
+		OwnerInfoOf::<Test>::remove(&unused_code_hash);
+		super::StorageVersion::put(super::Releases::V2_0_0);
+		Contracts::on_runtime_upgrade();
+
+		assert_eq!(
+			OwnerInfoOf::<Test>::get(&code_hash),
+			Some(OwnerInfo::<Test> {
+				owner: None,
+				deposit: 0,
+				refcount: 2,
+			}),
+		);
+		assert_err!(
+			Contracts::remove_code(Origin::signed(ALICE), code_hash),
+			sp_runtime::traits::BadOrigin,
+		);
 
-			// Advance blocks
-			initialize_block(5);
+		// Code that no contract uses could never be removed, so the migration removes it.
+		assert!(!OwnerInfoOf::<Test>::contains_key(&unused_code_hash));
+		assert!(!CodeStorage::<Test>::contains_key(&unused_code_hash));
+		assert!(!PristineCode::<Test>::contains_key(&unused_code_hash));
+		assert_eq!(super::StorageVersion::get(), super::Releases::V3_0_0);
+	});
+}
 
-			// Trigger rent through call
-			assert_ok!(
-				Contracts::call(Origin::signed(ALICE), addr.clone(), 0, GAS_LIMIT, call::null())
+#[test]
+fn upload_code_reserves_deposit() {
+	let (wasm, code_hash) = compile_module::<Test>("return_from_start_fn").unwrap();
+	ExtBuilder::default()
+		.existential_deposit(50)
+		.storage_deposit(100, 2, 10)
+		.build()
+		.execute_with(|| {
+			let _ = Balances::deposit_creating(&ALICE, 1_000_000);
+			let _ = Balances::deposit_creating(&BOB, 1_000_000);
+
+			let result = Contracts::bare_upload_code(
+				ALICE, wasm.clone(), None, Determinism::Enforced,
+			).unwrap();
+			assert_eq!(result.code_hash, code_hash);
+			// Both the pristine and the instrumented code are paid for.
+			assert!(result.deposit >= 2 * wasm.len() as u64 + 2 * 10);
+			assert_eq!(Balances::reserved_balance(&ALICE), result.deposit);
+			assert_eq!(
+				OwnerInfoOf::<Test>::get(&code_hash),
+				Some(OwnerInfo::<Test> {
+					owner: Some(ALICE),
+					deposit: result.deposit,
+					refcount: 0,
+				}),
 			);
 
-			// Check contract is still alive
-			let bob_contract = ContractInfoOf::<Test>::get(&addr).unwrap().get_alive();
-			assert!(bob_contract.is_some())
+			// Uploading the same code again does nothing.
+			assert_ok!(Contracts::upload_code(
+				Origin::signed(BOB), wasm, None, Determinism::Enforced,
+			));
+			assert_eq!(Balances::reserved_balance(&BOB), 0);
+			assert_eq!(OwnerInfoOf::<Test>::get(&code_hash).unwrap().owner, Some(ALICE));
 		});
 }
 
 #[test]
-fn restorations_dirty_storage_and_different_storage() {
-	restoration(true, true);
-}
+fn upload_code_deposit_is_limited() {
+	let (wasm, code_hash) = compile_module::<Test>("return_from_start_fn").unwrap();
+	ExtBuilder::default()
+		.existential_deposit(50)
+		.storage_deposit(100, 2, 10)
+		.build()
+		.execute_with(|| {
+			let _ = Balances::deposit_creating(&ALICE, 1_000_000);
+			assert_err!(
+				Contracts::upload_code(
+					Origin::signed(ALICE), wasm.clone(), Some(20), Determinism::Enforced,
+				),
+				Error::<Test>::StorageDepositLimitExhausted,
+			);
 
-#[test]
-fn restorations_dirty_storage() {
-	restoration(false, true);
+			// Only enough for the existential deposit.
+			let _ = Balances::deposit_creating(&BOB, 50);
+			assert_err!(
+				Contracts::upload_code(Origin::signed(BOB), wasm, None, Determinism::Enforced),
+				Error::<Test>::StorageDepositNotEnoughFunds,
+			);
+
+			assert!(!CodeStorage::<Test>::contains_key(&code_hash));
+			assert!(!PristineCode::<Test>::contains_key(&code_hash));
+			assert!(!OwnerInfoOf::<Test>::contains_key(&code_hash));
+		});
 }
 
 #[test]
-fn restoration_different_storage() {
-	restoration(true, false);
+fn remove_code_works() {
+	let (wasm, code_hash) = compile_module::<Test>("return_from_start_fn").unwrap();
+	ExtBuilder::default()
+		.existential_deposit(50)
+		.storage_deposit(100, 2, 10)
+		.build()
+		.execute_with(|| {
+			let _ = Balances::deposit_creating(&ALICE, 1_000_000);
+			assert_ok!(Contracts::upload_code(
+				Origin::signed(ALICE), wasm, None, Determinism::Enforced,
+			));
+
+			// Only the owner can remove the code.
+			assert_err!(
+				Contracts::remove_code(Origin::signed(BOB), code_hash),
+				sp_runtime::traits::BadOrigin,
+			);
+
+			assert_ok!(Contracts::remove_code(Origin::signed(ALICE), code_hash));
+			assert_eq!(Balances::reserved_balance(&ALICE), 0);
+			assert_eq!(Balances::free_balance(&ALICE), 1_000_000);
+			assert!(!CodeStorage::<Test>::contains_key(&code_hash));
+			assert!(!PristineCode::<Test>::contains_key(&code_hash));
+			assert!(!OwnerInfoOf::<Test>::contains_key(&code_hash));
+			assert!(System::events().iter().any(|record|
+				record.event == MetaEvent::contracts(RawEvent::CodeRemoved(code_hash))
+			));
+
+			assert_err!(
+				Contracts::remove_code(Origin::signed(ALICE), code_hash),
+				Error::<Test>::CodeNotFound,
+			);
+		});
 }
 
 #[test]
-fn restoration_success() {
-	restoration(false, false);
-}
+fn remove_code_in_use() {
+	let (wasm, code_hash) = compile_module::<Test>("return_from_start_fn").unwrap();
+	ExtBuilder::default().existential_deposit(50).build().execute_with(|| {
+		let _ = Balances::deposit_creating(&ALICE, 1_000_000);
+		assert_ok!(Contracts::upload_code(
+			Origin::signed(ALICE), wasm, None, Determinism::Enforced,
+		));
+		assert_ok!(Contracts::instantiate(
+			Origin::signed(ALICE),
+			100_000,
+			GAS_LIMIT,
+			None,
+			code_hash.into(),
+			vec![],
+			vec![],
+		));
 
-fn restoration(test_different_storage: bool, test_restore_to_with_dirty_storage: bool) {
-	let (set_rent_wasm, set_rent_code_hash) = compile_module::<Test>("set_rent").unwrap();
-	let (restoration_wasm, restoration_code_hash) = compile_module::<Test>("restoration").unwrap();
+		assert_err!(
+			Contracts::remove_code(Origin::signed(ALICE), code_hash),
+			Error::<Test>::CodeInUse,
+		);
+		assert!(CodeStorage::<Test>::contains_key(&code_hash));
+	});
+}
 
+#[test]
+fn code_is_removed_with_its_last_user() {
+	let (wasm, code_hash) = compile_module::<Test>("self_destruct").unwrap();
 	ExtBuilder::default()
 		.existential_deposit(50)
+		.storage_deposit(100, 2, 10)
 		.build()
 		.execute_with(|| {
 			let _ = Balances::deposit_creating(&ALICE, 1_000_000);
-			assert_ok!(Contracts::put_code(Origin::signed(ALICE), restoration_wasm));
-			assert_ok!(Contracts::put_code(Origin::signed(ALICE), set_rent_wasm));
+			let _ = Balances::deposit_creating(&BOB, 1_000_000);
+			assert_ok!(Contracts::upload_code(
+				Origin::signed(BOB), wasm, None, Determinism::Enforced,
+			));
+			let code_deposit = OwnerInfoOf::<Test>::get(&code_hash).unwrap().deposit;
 
-			// If you ever need to update the wasm source this test will fail
-			// and will show you the actual hash.
-			assert_eq!(System::events(), vec![
-				EventRecord {
-					phase: Phase::Initialization,
-					event: MetaEvent::system(frame_system::RawEvent::NewAccount(ALICE)),
-					topics: vec![],
-				},
-				EventRecord {
-					phase: Phase::Initialization,
-					event: MetaEvent::balances(pallet_balances::RawEvent::Endowed(ALICE, 1_000_000)),
-					topics: vec![],
-				},
-				EventRecord {
-					phase: Phase::Initialization,
-					event: MetaEvent::contracts(RawEvent::CodeStored(restoration_code_hash.into())),
-					topics: vec![],
-				},
-				EventRecord {
-					phase: Phase::Initialization,
-					event: MetaEvent::contracts(RawEvent::CodeStored(set_rent_code_hash.into())),
-					topics: vec![],
-				},
-			]);
+			let addrs: Vec<_> = (0..2u8).map(|salt| {
+				assert_ok!(Contracts::instantiate(
+					Origin::signed(ALICE),
+					100_000,
+					GAS_LIMIT,
+					None,
+					code_hash.into(),
+					vec![],
+					vec![salt],
+				));
+				Contracts::contract_address(&ALICE, &code_hash, &[salt])
+			}).collect();
+			assert_eq!(OwnerInfoOf::<Test>::get(&code_hash).unwrap().refcount, 2);
 
-			// Create an account with address `BOB` with code `CODE_SET_RENT`.
-			// The input parameter sets the rent allowance to 0.
-			assert_ok!(Contracts::instantiate(
+			// Terminate the first contract. The code is still in use by the second.
+			assert_ok!(Contracts::call(
+				Origin::signed(ALICE), addrs[0].clone(), 0, GAS_LIMIT, None, vec![],
+			));
+			assert_eq!(OwnerInfoOf::<Test>::get(&code_hash).unwrap().refcount, 1);
+			assert_eq!(Balances::reserved_balance(&BOB), code_deposit);
+
+			// Terminating the second contract removes the code and refunds its owner.
+			assert_ok!(Contracts::call(
+				Origin::signed(ALICE), addrs[1].clone(), 0, GAS_LIMIT, None, vec![],
+			));
+			assert!(!CodeStorage::<Test>::contains_key(&code_hash));
+			assert!(!PristineCode::<Test>::contains_key(&code_hash));
+			assert!(!OwnerInfoOf::<Test>::contains_key(&code_hash));
+			assert_eq!(Balances::reserved_balance(&BOB), 0);
+			assert_eq!(Balances::free_balance(&BOB), 1_000_000);
+		});
+}
+
+#[test]
+fn instantiate_with_code_works() {
+	let (wasm, code_hash) = compile_module::<Test>("return_from_start_fn").unwrap();
+	ExtBuilder::default()
+		.existential_deposit(50)
+		.storage_deposit(100, 2, 10)
+		.build()
+		.execute_with(|| {
+			let _ = Balances::deposit_creating(&ALICE, 1_000_000);
+			assert_ok!(Contracts::instantiate_with_code(
 				Origin::signed(ALICE),
-				30_000,
+				100_000,
 				GAS_LIMIT,
-				set_rent_code_hash.into(),
-				<Test as pallet_balances::Config>::Balance::from(0u32).encode(),
+				None,
+				wasm,
+				vec![],
 				vec![],
 			));
-			let addr_bob = Contracts::contract_address(&ALICE, &set_rent_code_hash, &[]);
+			let addr = Contracts::contract_address(&ALICE, &code_hash, &[]);
 
-			// Check if `BOB` was created successfully and that the rent allowance is
-			// set to 0.
-			let bob_contract = ContractInfoOf::<Test>::get(&addr_bob).unwrap().get_alive().unwrap();
-			assert_eq!(bob_contract.rent_allowance, 0);
+			let owner_info = OwnerInfoOf::<Test>::get(&code_hash).unwrap();
+			assert_eq!(owner_info.owner, Some(ALICE));
+			assert_eq!(owner_info.refcount, 1);
+			assert_eq!(Balances::reserved_balance(&ALICE), owner_info.deposit);
+			assert!(ContractInfoOf::<Test>::contains_key(&addr));
+		});
+}
 
-			if test_different_storage {
-				assert_ok!(Contracts::call(
+#[test]
+fn instantiate_with_code_is_atomic() {
+	let (wasm, code_hash) = compile_module::<Test>("self_destructing_constructor").unwrap();
+	ExtBuilder::default()
+		.existential_deposit(50)
+		.storage_deposit(100, 2, 10)
+		.build()
+		.execute_with(|| {
+			let _ = Balances::deposit_creating(&ALICE, 1_000_000);
+			assert_err_ignore_postinfo!(
+				Contracts::instantiate_with_code(
 					Origin::signed(ALICE),
-					addr_bob.clone(), 0, GAS_LIMIT,
-					call::set_storage_4_byte())
-				);
-			}
+					100_000,
+					GAS_LIMIT,
+					None,
+					wasm,
+					vec![],
+					vec![],
+				),
+				Error::<Test>::NewContractNotFunded,
+			);
 
-			// Advance 4 blocks, to the 5th.
-			initialize_block(5);
+			// The code was not stored and no deposit was taken.
+			assert!(!CodeStorage::<Test>::contains_key(&code_hash));
+			assert!(!OwnerInfoOf::<Test>::contains_key(&code_hash));
+			assert_eq!(Balances::reserved_balance(&ALICE), 0);
+			assert_eq!(Balances::free_balance(&ALICE), 1_000_000);
+		});
+}
 
-			// Call `BOB`, which makes it pay rent. Since the rent allowance is set to 0
-			// we expect that it will get removed leaving tombstone.
-			assert_err_ignore_postinfo!(
-				Contracts::call(
-					Origin::signed(ALICE), addr_bob.clone(), 0, GAS_LIMIT, call::null()
-				),
-				Error::<Test>::NotCallable
+#[test]
+fn bare_instantiate_reports_code_deposit() {
+	let (wasm, code_hash) = compile_module::<Test>("return_from_start_fn").unwrap();
+	ExtBuilder::default()
+		.existential_deposit(50)
+		.storage_deposit(100, 2, 10)
+		.build()
+		.execute_with(|| {
+			let _ = Balances::deposit_creating(&ALICE, 1_000_000);
+			let result = Contracts::bare_instantiate(
+				ALICE,
+				100_000,
+				GAS_LIMIT,
+				None,
+				Code::Upload(wasm),
+				vec![],
+				vec![],
+				false,
 			);
-			assert!(ContractInfoOf::<Test>::get(&addr_bob).unwrap().get_tombstone().is_some());
-			assert_eq!(System::events(), vec![
-				EventRecord {
-					phase: Phase::Initialization,
-					event: MetaEvent::contracts(
-						RawEvent::Evicted(addr_bob.clone(), true)
-					),
-					topics: vec![],
-				},
-			]);
+			let code_deposit = OwnerInfoOf::<Test>::get(&code_hash).unwrap().deposit;
+			let instantiated = result.result.unwrap();
+			assert!(instantiated.result.is_success());
+			assert_eq!(
+				instantiated.account_id,
+				Contracts::contract_address(&ALICE, &code_hash, &[]),
+			);
+			assert_eq!(result.storage_deposit, StorageDeposit::Charge(code_deposit + 100));
+
+			// The code is not charged again when it already exists.
+			let result = Contracts::bare_instantiate(
+				ALICE,
+				100_000,
+				GAS_LIMIT,
+				None,
+				Code::Existing(code_hash),
+				vec![],
+				vec![1],
+				false,
+			);
+			assert!(result.result.is_ok());
+			assert_eq!(result.storage_deposit, StorageDeposit::Charge(100));
+
+			// The upload is kept and reported when the constructor traps.
+			let (wasm, trap_code_hash) = compile_module::<Test>("ok_trap_revert").unwrap();
+			let result = Contracts::bare_instantiate(
+				ALICE,
+				100_000,
+				GAS_LIMIT,
+				None,
+				Code::Upload(wasm),
+				vec![2],
+				vec![],
+				false,
+			);
+			let trap_code_deposit = OwnerInfoOf::<Test>::get(&trap_code_hash).unwrap().deposit;
+			assert!(result.result.is_err());
+			assert_eq!(result.storage_deposit, StorageDeposit::Charge(trap_code_deposit));
+			assert_eq!(Balances::reserved_balance(&ALICE), code_deposit + trap_code_deposit);
+		});
+}
+
+#[test]
+fn set_code_hash_works() {
+	let (wasm, code_hash) = compile_module::<Test>("set_code_hash").unwrap();
+	let (new_wasm, new_code_hash) = compile_module::<Test>("return_with_data").unwrap();
+	ExtBuilder::default().existential_deposit(50).build().execute_with(|| {
+		let _ = Balances::deposit_creating(&ALICE, 1_000_000);
+		assert_ok!(Contracts::upload_code(
+			Origin::signed(ALICE), new_wasm, None, Determinism::Enforced,
+		));
+		assert_ok!(Contracts::instantiate_with_code(
+			Origin::signed(ALICE),
+			100_000,
+			GAS_LIMIT,
+			None,
+			wasm,
+			vec![],
+			vec![],
+		));
+		let addr = Contracts::contract_address(&ALICE, &code_hash, &[]);
+		test_utils::set_storage(&addr, &[1; 32], Some(vec![42]));
+
+		assert_ok!(Contracts::call(
+			Origin::signed(ALICE), addr.clone(), 0, GAS_LIMIT, None, new_code_hash.encode(),
+		));
+
+		// The contract now runs the new code but kept its storage.
+		assert_eq!(ContractInfoOf::<Test>::get(&addr).unwrap().code_hash, new_code_hash);
+		assert_eq!(OwnerInfoOf::<Test>::get(&new_code_hash).unwrap().refcount, 1);
+		assert!(!OwnerInfoOf::<Test>::contains_key(&code_hash));
+		assert_eq!(Contracts::get_storage(addr.clone(), [1; 32]), Ok(Some(vec![42])));
+		assert!(System::events().iter().any(|record|
+			record.event == MetaEvent::contracts(
+				RawEvent::ContractCodeUpdated(addr.clone(), new_code_hash, code_hash)
+			)
+		));
+
+		let result = Contracts::bare_call(
+			ALICE, addr, 0, GAS_LIMIT, None, vec![0, 0, 0, 0, 1, 2, 3], false,
+			Determinism::Enforced,
+		).exec_result.unwrap();
+		assert!(result.is_success());
+		assert_eq!(result.data, vec![1, 2, 3]);
+	});
+}
+
+#[test]
+fn set_code_hash_to_missing_code_fails() {
+	let (wasm, code_hash) = compile_module::<Test>("set_code_hash").unwrap();
+	ExtBuilder::default().existential_deposit(50).build().execute_with(|| {
+		let _ = Balances::deposit_creating(&ALICE, 1_000_000);
+		assert_ok!(Contracts::instantiate_with_code(
+			Origin::signed(ALICE),
+			100_000,
+			GAS_LIMIT,
+			None,
+			wasm,
+			vec![],
+			vec![],
+		));
+		let addr = Contracts::contract_address(&ALICE, &code_hash, &[]);
+
+		assert_err_ignore_postinfo!(
+			Contracts::call(
+				Origin::signed(ALICE), addr.clone(), 0, GAS_LIMIT, None, [0xff; 32].encode(),
+			),
+			Error::<Test>::ContractTrapped,
+		);
+		assert_eq!(ContractInfoOf::<Test>::get(&addr).unwrap().code_hash, code_hash);
+		assert_eq!(OwnerInfoOf::<Test>::get(&code_hash).unwrap().refcount, 1);
+	});
+}
+
+#[test]
+fn delegate_call_works() {
+	let (wasm, code_hash) = compile_module::<Test>("delegate_call").unwrap();
+	let (lib_wasm, lib_code_hash) = compile_module::<Test>("delegate_call_lib").unwrap();
+	ExtBuilder::default().existential_deposit(50).build().execute_with(|| {
+		let _ = Balances::deposit_creating(&ALICE, 1_000_000);
+		assert_ok!(Contracts::upload_code(
+			Origin::signed(ALICE), lib_wasm, None, Determinism::Enforced,
+		));
+		assert_ok!(Contracts::instantiate_with_code(
+			Origin::signed(ALICE),
+			100_000,
+			GAS_LIMIT,
+			None,
+			wasm,
+			vec![],
+			vec![],
+		));
+		let addr = Contracts::contract_address(&ALICE, &code_hash, &[]);
+
+		assert_ok!(Contracts::call(
+			Origin::signed(ALICE), addr.clone(), 1_000, GAS_LIMIT, None, lib_code_hash.encode(),
+		));
+
+		// The library wrote into the storage of the calling contract and saw its caller and
+		// transferred value.
+		assert_eq!(
+			Contracts::get_storage(addr.clone(), [1; 32]),
+			Ok(Some(1_000u64.encode())),
+		);
+		assert_eq!(Contracts::get_storage(addr.clone(), [2; 32]), Ok(Some(ALICE.encode())));
+		assert_eq!(Balances::free_balance(&addr), 101_000);
+
+		// Executing code does not make it a user of that code.
+		assert_eq!(OwnerInfoOf::<Test>::get(&lib_code_hash).unwrap().refcount, 0);
+		assert_eq!(OwnerInfoOf::<Test>::get(&code_hash).unwrap().refcount, 1);
+	});
+}
+
+#[test]
+fn delegate_dependencies_keep_the_code_alive() {
+	let (wasm, code_hash) = compile_module::<Test>("delegate_dependency").unwrap();
+	let (lib_wasm, lib_code_hash) = compile_module::<Test>("delegate_call_lib").unwrap();
+	ExtBuilder::default().existential_deposit(50).build().execute_with(|| {
+		let _ = Balances::deposit_creating(&ALICE, 1_000_000);
+		assert_ok!(Contracts::upload_code(
+			Origin::signed(ALICE), lib_wasm.clone(), None, Determinism::Enforced,
+		));
+		assert_ok!(Contracts::instantiate_with_code(
+			Origin::signed(ALICE),
+			100_000,
+			GAS_LIMIT,
+			None,
+			wasm,
+			vec![],
+			vec![],
+		));
+		let addr = Contracts::contract_address(&ALICE, &code_hash, &[]);
+		let input = |action: u8| (action, lib_code_hash).encode();
+
+		// The library can't be removed while the contract depends on it.
+		assert_ok!(Contracts::call(
+			Origin::signed(ALICE), addr.clone(), 0, GAS_LIMIT, None, input(0),
+		));
+		assert!(DelegateDependencies::<Test>::contains_key(&addr, &lib_code_hash));
+		assert_eq!(OwnerInfoOf::<Test>::get(&lib_code_hash).unwrap().refcount, 1);
+		assert_err!(
+			Contracts::remove_code(Origin::signed(ALICE), lib_code_hash),
+			Error::<Test>::CodeInUse,
+		);
 
-			// Create another account with the address `DJANGO` with `CODE_RESTORATION`.
-			//
-			// Note that we can't use `ALICE` for creating `DJANGO` so we create yet another
-			// account `CHARLIE` and create `DJANGO` with it.
-			let _ = Balances::deposit_creating(&CHARLIE, 1_000_000);
-			assert_ok!(Contracts::instantiate(
-				Origin::signed(CHARLIE),
-				30_000,
-				GAS_LIMIT,
-				restoration_code_hash.into(),
-				<Test as pallet_balances::Config>::Balance::from(0u32).encode(),
-				vec![],
-			));
-			let addr_django = Contracts::contract_address(&CHARLIE, &restoration_code_hash, &[]);
+		// A dependency can only be added once.
+		assert_err_ignore_postinfo!(
+			Contracts::call(Origin::signed(ALICE), addr.clone(), 0, GAS_LIMIT, None, input(0)),
+			Error::<Test>::ContractTrapped,
+		);
 
-			// Before performing a call to `DJANGO` save its original trie id.
-			let django_trie_id = ContractInfoOf::<Test>::get(&addr_django).unwrap()
-				.get_alive().unwrap().trie_id;
+		// Removing the dependency releases the library, which no contract uses anymore.
+		assert_ok!(Contracts::call(
+			Origin::signed(ALICE), addr.clone(), 0, GAS_LIMIT, None, input(1),
+		));
+		assert!(!DelegateDependencies::<Test>::contains_key(&addr, &lib_code_hash));
+		assert!(!OwnerInfoOf::<Test>::contains_key(&lib_code_hash));
+		assert!(!CodeStorage::<Test>::contains_key(&lib_code_hash));
+
+		// Terminating the contract releases its dependencies as well.
+		assert_ok!(Contracts::upload_code(
+			Origin::signed(ALICE), lib_wasm, None, Determinism::Enforced,
+		));
+		assert_ok!(Contracts::call(
+			Origin::signed(ALICE), addr.clone(), 0, GAS_LIMIT, None, input(0),
+		));
+		assert_eq!(OwnerInfoOf::<Test>::get(&lib_code_hash).unwrap().refcount, 1);
+		assert_ok!(Contracts::call(
+			Origin::signed(ALICE), addr.clone(), 0, GAS_LIMIT, None, input(2),
+		));
+		assert!(ContractInfoOf::<Test>::get(&addr).is_none());
+		assert!(!DelegateDependencies::<Test>::contains_key(&addr, &lib_code_hash));
+		assert!(!OwnerInfoOf::<Test>::contains_key(&lib_code_hash));
+	});
+}
 
-			// The trie is regarded as 'dirty' when it was written to in the current block.
-			if !test_restore_to_with_dirty_storage {
-				// Advance 1 block, to the 6th.
-				initialize_block(6);
-			}
+#[test]
+fn relaxed_code_can_only_be_executed_by_dry_runs() {
+	let (wasm, code_hash) = compile_module::<Test>("delegate_call").unwrap();
+	let (float_wasm, float_code_hash) = compile_module::<Test>("float_instruction").unwrap();
+	ExtBuilder::default().existential_deposit(50).build().execute_with(|| {
+		let _ = Balances::deposit_creating(&ALICE, 1_000_000);
 
-			// Perform a call to `DJANGO`. This should either perform restoration successfully or
-			// fail depending on the test parameters.
-			let perform_the_restoration = || {
-				Contracts::call(
-					Origin::signed(ALICE),
-					addr_django.clone(),
-					0,
-					GAS_LIMIT,
-					set_rent_code_hash
-						.as_ref()
-						.iter()
-						.chain(AsRef::<[u8]>::as_ref(&addr_bob))
-						.cloned()
-						.collect(),
-				)
-			};
+		// Float instructions are rejected when determinism is enforced.
+		assert_err!(
+			Contracts::upload_code(
+				Origin::signed(ALICE), float_wasm.clone(), None, Determinism::Enforced,
+			),
+			DispatchError::Other(
+				"floating point instruction `f32.const` is forbidden in deterministic code"
+			),
+		);
+		assert_ok!(Contracts::upload_code(
+			Origin::signed(ALICE), float_wasm, None, Determinism::Relaxed,
+		));
+		assert_ok!(Contracts::instantiate_with_code(
+			Origin::signed(ALICE),
+			100_000,
+			GAS_LIMIT,
+			None,
+			wasm,
+			vec![],
+			vec![],
+		));
+		let addr = Contracts::contract_address(&ALICE, &code_hash, &[]);
 
-			if test_different_storage || test_restore_to_with_dirty_storage {
-				// Parametrization of the test imply restoration failure. Check that `DJANGO` aka
-				// restoration contract is still in place and also that `BOB` doesn't exist.
-				let result = perform_the_restoration();
-				assert!(ContractInfoOf::<Test>::get(&addr_bob).unwrap().get_tombstone().is_some());
-				let django_contract = ContractInfoOf::<Test>::get(&addr_django).unwrap()
-					.get_alive().unwrap();
-				assert_eq!(django_contract.storage_size, 8);
-				assert_eq!(django_contract.trie_id, django_trie_id);
-				assert_eq!(django_contract.deduct_block, System::block_number());
-				match (test_different_storage, test_restore_to_with_dirty_storage) {
-					(true, false) => {
-						assert_err_ignore_postinfo!(
-							result, Error::<Test>::InvalidTombstone,
-						);
-						assert_eq!(System::events(), vec![]);
-					}
-					(_, true) => {
-						assert_err_ignore_postinfo!(
-							result, Error::<Test>::InvalidContractOrigin,
-						);
-						pretty_assertions::assert_eq!(System::events(), vec![
-							EventRecord {
-								phase: Phase::Initialization,
-								event: MetaEvent::contracts(RawEvent::Evicted(addr_bob, true)),
-								topics: vec![],
-							},
-							EventRecord {
-								phase: Phase::Initialization,
-								event: MetaEvent::system(frame_system::RawEvent::NewAccount(CHARLIE)),
-								topics: vec![],
-							},
-							EventRecord {
-								phase: Phase::Initialization,
-								event: MetaEvent::balances(pallet_balances::RawEvent::Endowed(CHARLIE, 1_000_000)),
-								topics: vec![],
-							},
-							EventRecord {
-								phase: Phase::Initialization,
-								event: MetaEvent::system(frame_system::RawEvent::NewAccount(addr_django.clone())),
-								topics: vec![],
-							},
-							EventRecord {
-								phase: Phase::Initialization,
-								event: MetaEvent::balances(pallet_balances::RawEvent::Endowed(addr_django.clone(), 30_000)),
-								topics: vec![],
-							},
-							EventRecord {
-								phase: Phase::Initialization,
-								event: MetaEvent::balances(
-									pallet_balances::RawEvent::Transfer(CHARLIE, addr_django.clone(), 30_000)
-								),
-								topics: vec![],
-							},
-							EventRecord {
-								phase: Phase::Initialization,
-								event: MetaEvent::contracts(RawEvent::Instantiated(CHARLIE, addr_django.clone())),
-								topics: vec![],
-							},
-						]);
-					}
-					_ => unreachable!(),
-				}
-			} else {
-				assert_ok!(perform_the_restoration());
-
-				// Here we expect that the restoration is succeeded. Check that the restoration
-				// contract `DJANGO` ceased to exist and that `BOB` returned back.
-				println!("{:?}", ContractInfoOf::<Test>::get(&addr_bob));
-				let bob_contract = ContractInfoOf::<Test>::get(&addr_bob).unwrap()
-					.get_alive().unwrap();
-				assert_eq!(bob_contract.rent_allowance, 50);
-				assert_eq!(bob_contract.storage_size, 4);
-				assert_eq!(bob_contract.trie_id, django_trie_id);
-				assert_eq!(bob_contract.deduct_block, System::block_number());
-				assert!(ContractInfoOf::<Test>::get(&addr_django).is_none());
-				assert_eq!(System::events(), vec![
-					EventRecord {
-						phase: Phase::Initialization,
-						event: MetaEvent::system(system::RawEvent::KilledAccount(addr_django.clone())),
-						topics: vec![],
-					},
-					EventRecord {
-						phase: Phase::Initialization,
-						event: MetaEvent::contracts(
-							RawEvent::Restored(addr_django, addr_bob, bob_contract.code_hash, 50)
-						),
-						topics: vec![],
-					},
-				]);
-			}
-		});
+		// On-chain execution of relaxed code is refused.
+		assert_err_ignore_postinfo!(
+			Contracts::call(
+				Origin::signed(ALICE), addr.clone(), 0, GAS_LIMIT, None, float_code_hash.encode(),
+			),
+			Error::<Test>::Indeterministic,
+		);
+
+		// So is a dry run that enforces determinism.
+		let result = Contracts::bare_call(
+			ALICE, addr.clone(), 0, GAS_LIMIT, None, float_code_hash.encode(), false,
+			Determinism::Enforced,
+		);
+		assert_err!(result.exec_result, Error::<Test>::Indeterministic);
+
+		// A dry run that relaxes determinism can execute it.
+		let result = Contracts::bare_call(
+			ALICE, addr, 0, GAS_LIMIT, None, float_code_hash.encode(), false,
+			Determinism::Relaxed,
+		);
+		assert_ok!(result.exec_result);
+	});
+}
+
+#[test]
+fn enforced_upload_of_relaxed_code_enforces_determinism() {
+	let (wasm, code_hash) = compile_module::<Test>("set_empty_storage").unwrap();
+	let (float_wasm, float_code_hash) = compile_module::<Test>("float_instruction").unwrap();
+	ExtBuilder::default().existential_deposit(50).build().execute_with(|| {
+		let _ = Balances::deposit_creating(&ALICE, 1_000_000);
+		let _ = Balances::deposit_creating(&BOB, 1_000_000);
+
+		// BOB uploads a relaxed copy of a deterministic code first.
+		assert_ok!(Contracts::upload_code(
+			Origin::signed(BOB), wasm.clone(), None, Determinism::Relaxed,
+		));
+		assert_ok!(Contracts::upload_code(
+			Origin::signed(ALICE), wasm, None, Determinism::Enforced,
+		));
+		assert_eq!(OwnerInfoOf::<Test>::get(&code_hash).unwrap().owner, Some(BOB));
+
+		// The code can be instantiated on-chain, which enforces determinism.
+		assert_ok!(Contracts::instantiate(
+			Origin::signed(ALICE),
+			100_000,
+			GAS_LIMIT,
+			None,
+			code_hash.into(),
+			vec![],
+			vec![],
+		));
+
+		// An indeterministic code can't be turned into an enforced one.
+		assert_ok!(Contracts::upload_code(
+			Origin::signed(BOB), float_wasm.clone(), None, Determinism::Relaxed,
+		));
+		assert_err!(
+			Contracts::upload_code(
+				Origin::signed(ALICE), float_wasm, None, Determinism::Enforced,
+			),
+			DispatchError::Other(
+				"floating point instruction `f32.const` is forbidden in deterministic code"
+			),
+		);
+		assert!(CodeStorage::<Test>::contains_key(&float_code_hash));
+	});
 }
 
 #[test]
@@ -1363,11 +1876,14 @@ fn storage_max_value_limit() {
 		.execute_with(|| {
 			// Create
 			let _ = Balances::deposit_creating(&ALICE, 1_000_000);
-			assert_ok!(Contracts::put_code(Origin::signed(ALICE), wasm));
+			assert_ok!(Contracts::upload_code(
+				Origin::signed(ALICE), wasm, None, Determinism::Enforced,
+			));
 			assert_ok!(Contracts::instantiate(
 				Origin::signed(ALICE),
 				30_000,
 				GAS_LIMIT,
+				None,
 				code_hash.into(),
 				vec![],
 				vec![],
@@ -1375,8 +1891,7 @@ fn storage_max_value_limit() {
 			let addr = Contracts::contract_address(&ALICE, &code_hash, &[]);
 
 			// Check creation
-			let bob_contract = ContractInfoOf::<Test>::get(&addr).unwrap().get_alive().unwrap();
-			assert_eq!(bob_contract.rent_allowance, <BalanceOf<Test>>::max_value());
+			assert!(ContractInfoOf::<Test>::contains_key(&addr));
 
 			// Call contract with allowed storage value.
 			assert_ok!(Contracts::call(
@@ -1384,6 +1899,7 @@ fn storage_max_value_limit() {
 				addr.clone(),
 				0,
 				GAS_LIMIT * 2, // we are copying a huge buffer
+				None,
 				<Test as Config>::MaxValueSize::get().encode(),
 			));
 
@@ -1394,6 +1910,7 @@ fn storage_max_value_limit() {
 					addr,
 					0,
 					GAS_LIMIT,
+					None,
 					(<Test as Config>::MaxValueSize::get() + 1).encode(),
 				),
 				Error::<Test>::ValueTooLarge,
@@ -1412,13 +1929,18 @@ fn deploy_and_call_other_contract() {
 		.execute_with(|| {
 			// Create
 			let _ = Balances::deposit_creating(&ALICE, 1_000_000);
-			assert_ok!(Contracts::put_code(Origin::signed(ALICE), callee_wasm));
-			assert_ok!(Contracts::put_code(Origin::signed(ALICE), caller_wasm));
+			assert_ok!(Contracts::upload_code(
+				Origin::signed(ALICE), callee_wasm, None, Determinism::Enforced,
+			));
+			assert_ok!(Contracts::upload_code(
+				Origin::signed(ALICE), caller_wasm, None, Determinism::Enforced,
+			));
 
 			assert_ok!(Contracts::instantiate(
 				Origin::signed(ALICE),
 				100_000,
 				GAS_LIMIT,
+				None,
 				caller_code_hash.into(),
 				vec![],
 				vec![],
@@ -1432,6 +1954,7 @@ fn deploy_and_call_other_contract() {
 				addr,
 				0,
 				GAS_LIMIT,
+				None,
 				callee_code_hash.as_ref().to_vec(),
 			));
 		});
@@ -1445,13 +1968,16 @@ fn cannot_self_destruct_through_draning() {
 		.build()
 		.execute_with(|| {
 			let _ = Balances::deposit_creating(&ALICE, 1_000_000);
-			assert_ok!(Contracts::put_code(Origin::signed(ALICE), wasm));
+			assert_ok!(Contracts::upload_code(
+				Origin::signed(ALICE), wasm, None, Determinism::Enforced,
+			));
 
 			// Instantiate the BOB contract.
 			assert_ok!(Contracts::instantiate(
 				Origin::signed(ALICE),
 				100_000,
 				GAS_LIMIT,
+				None,
 				code_hash.into(),
 				vec![],
 				vec![],
@@ -1461,7 +1987,7 @@ fn cannot_self_destruct_through_draning() {
 			// Check that the BOB contract has been instantiated.
 			assert_matches!(
 				ContractInfoOf::<Test>::get(&addr),
-				Some(ContractInfo::Alive(_))
+				Some(_)
 			);
 
 			// Call BOB which makes it send all funds to the zero address
@@ -1472,6 +1998,7 @@ fn cannot_self_destruct_through_draning() {
 					addr,
 					0,
 					GAS_LIMIT,
+					None,
 					vec![],
 				)
 			);
@@ -1486,13 +2013,16 @@ fn cannot_self_destruct_while_live() {
 		.build()
 		.execute_with(|| {
 			let _ = Balances::deposit_creating(&ALICE, 1_000_000);
-			assert_ok!(Contracts::put_code(Origin::signed(ALICE), wasm));
+			assert_ok!(Contracts::upload_code(
+				Origin::signed(ALICE), wasm, None, Determinism::Enforced,
+			));
 
 			// Instantiate the BOB contract.
 			assert_ok!(Contracts::instantiate(
 				Origin::signed(ALICE),
 				100_000,
 				GAS_LIMIT,
+				None,
 				code_hash.into(),
 				vec![],
 				vec![],
@@ -1502,7 +2032,7 @@ fn cannot_self_destruct_while_live() {
 			// Check that the BOB contract has been instantiated.
 			assert_matches!(
 				ContractInfoOf::<Test>::get(&addr),
-				Some(ContractInfo::Alive(_))
+				Some(_)
 			);
 
 			// Call BOB with input data, forcing it make a recursive call to itself to
@@ -1513,6 +2043,7 @@ fn cannot_self_destruct_while_live() {
 					addr.clone(),
 					0,
 					GAS_LIMIT,
+					None,
 					vec![0],
 				),
 				Error::<Test>::ContractTrapped,
@@ -1521,7 +2052,7 @@ fn cannot_self_destruct_while_live() {
 			// Check that BOB is still alive.
 			assert_matches!(
 				ContractInfoOf::<Test>::get(&addr),
-				Some(ContractInfo::Alive(_))
+				Some(_)
 			);
 		});
 }
@@ -1534,13 +2065,16 @@ fn self_destruct_works() {
 		.build()
 		.execute_with(|| {
 			let _ = Balances::deposit_creating(&ALICE, 1_000_000);
-			assert_ok!(Contracts::put_code(Origin::signed(ALICE), wasm));
+			assert_ok!(Contracts::upload_code(
+				Origin::signed(ALICE), wasm, None, Determinism::Enforced,
+			));
 
 			// Instantiate the BOB contract.
 			assert_ok!(Contracts::instantiate(
 				Origin::signed(ALICE),
 				100_000,
 				GAS_LIMIT,
+				None,
 				code_hash.into(),
 				vec![],
 				vec![],
@@ -1550,7 +2084,7 @@ fn self_destruct_works() {
 			// Check that the BOB contract has been instantiated.
 			assert_matches!(
 				ContractInfoOf::<Test>::get(&addr),
-				Some(ContractInfo::Alive(_))
+				Some(_)
 			);
 
 			// Call BOB without input data which triggers termination.
@@ -1560,6 +2094,7 @@ fn self_destruct_works() {
 					addr.clone(),
 					0,
 					GAS_LIMIT,
+					None,
 					vec![],
 				),
 				Ok(_)
@@ -1586,8 +2121,12 @@ fn destroy_contract_and_transfer_funds() {
 		.execute_with(|| {
 			// Create
 			let _ = Balances::deposit_creating(&ALICE, 1_000_000);
-			assert_ok!(Contracts::put_code(Origin::signed(ALICE), callee_wasm));
-			assert_ok!(Contracts::put_code(Origin::signed(ALICE), caller_wasm));
+			assert_ok!(Contracts::upload_code(
+				Origin::signed(ALICE), callee_wasm, None, Determinism::Enforced,
+			));
+			assert_ok!(Contracts::upload_code(
+				Origin::signed(ALICE), caller_wasm, None, Determinism::Enforced,
+			));
 
 			// This deploys the BOB contract, which in turn deploys the CHARLIE contract during
 			// construction.
@@ -1595,6 +2134,7 @@ fn destroy_contract_and_transfer_funds() {
 				Origin::signed(ALICE),
 				200_000,
 				GAS_LIMIT,
+				None,
 				caller_code_hash.into(),
 				callee_code_hash.as_ref().to_vec(),
 				vec![],
@@ -1607,7 +2147,7 @@ fn destroy_contract_and_transfer_funds() {
 			// Check that the CHARLIE contract has been instantiated.
 			assert_matches!(
 				ContractInfoOf::<Test>::get(&addr_charlie),
-				Some(ContractInfo::Alive(_))
+				Some(_)
 			);
 
 			// Call BOB, which calls CHARLIE, forcing CHARLIE to self-destruct.
@@ -1616,6 +2156,7 @@ fn destroy_contract_and_transfer_funds() {
 				addr_bob,
 				0,
 				GAS_LIMIT,
+				None,
 				addr_charlie.encode(),
 			));
 
@@ -1632,7 +2173,9 @@ fn cannot_self_destruct_in_constructor() {
 		.build()
 		.execute_with(|| {
 			let _ = Balances::deposit_creating(&ALICE, 1_000_000);
-			assert_ok!(Contracts::put_code(Origin::signed(ALICE), wasm));
+			assert_ok!(Contracts::upload_code(
+				Origin::signed(ALICE), wasm, None, Determinism::Enforced,
+			));
 
 			// Fail to instantiate the BOB because the contructor calls seal_terminate.
 			assert_err_ignore_postinfo!(
@@ -1640,6 +2183,7 @@ fn cannot_self_destruct_in_constructor() {
 					Origin::signed(ALICE),
 					100_000,
 					GAS_LIMIT,
+					None,
 					code_hash.into(),
 					vec![],
 					vec![],
@@ -1658,13 +2202,16 @@ fn crypto_hashes() {
 		.build()
 		.execute_with(|| {
 			let _ = Balances::deposit_creating(&ALICE, 1_000_000);
-			assert_ok!(Contracts::put_code(Origin::signed(ALICE), wasm));
+			assert_ok!(Contracts::upload_code(
+				Origin::signed(ALICE), wasm, None, Determinism::Enforced,
+			));
 
 			// Instantiate the CRYPTO_HASHES contract.
 			assert_ok!(Contracts::instantiate(
 				Origin::signed(ALICE),
 				100_000,
 				GAS_LIMIT,
+				None,
 				code_hash.into(),
 				vec![],
 				vec![],
@@ -1696,7 +2243,10 @@ fn crypto_hashes() {
 					addr.clone(),
 					0,
 					GAS_LIMIT,
+					None,
 					params,
+					false,
+					Determinism::Enforced,
 				).exec_result.unwrap();
 				assert!(result.is_success());
 				let expected = hash_fn(input.as_ref());
@@ -1711,13 +2261,16 @@ fn transfer_return_code() {
 	ExtBuilder::default().existential_deposit(50).build().execute_with(|| {
 		let subsistence = ConfigCache::<Test>::subsistence_threshold_uncached();
 		let _ = Balances::deposit_creating(&ALICE, 10 * subsistence);
-		assert_ok!(Contracts::put_code(Origin::signed(ALICE), wasm));
+		assert_ok!(Contracts::upload_code(
+			Origin::signed(ALICE), wasm, None, Determinism::Enforced,
+		));
 
 		assert_ok!(
 			Contracts::instantiate(
 				Origin::signed(ALICE),
 				subsistence,
 				GAS_LIMIT,
+				None,
 				code_hash.into(),
 				vec![],
 				vec![],
@@ -1731,7 +2284,10 @@ fn transfer_return_code() {
 			addr.clone(),
 			0,
 			GAS_LIMIT,
+			None,
 			vec![],
+			false,
+			Determinism::Enforced,
 		).exec_result.unwrap();
 		assert_return_code!(result, RuntimeReturnCode::BelowSubsistenceThreshold);
 
@@ -1745,7 +2301,10 @@ fn transfer_return_code() {
 			addr,
 			0,
 			GAS_LIMIT,
+			None,
 			vec![],
+			false,
+			Determinism::Enforced,
 		).exec_result.unwrap();
 		assert_return_code!(result, RuntimeReturnCode::TransferFailed);
 	});
@@ -1759,14 +2318,19 @@ fn call_return_code() {
 		let subsistence = ConfigCache::<Test>::subsistence_threshold_uncached();
 		let _ = Balances::deposit_creating(&ALICE, 10 * subsistence);
 		let _ = Balances::deposit_creating(&CHARLIE, 10 * subsistence);
-		assert_ok!(Contracts::put_code(Origin::signed(ALICE), caller_code));
-		assert_ok!(Contracts::put_code(Origin::signed(ALICE), callee_code));
+		assert_ok!(Contracts::upload_code(
+			Origin::signed(ALICE), caller_code, None, Determinism::Enforced,
+		));
+		assert_ok!(Contracts::upload_code(
+			Origin::signed(ALICE), callee_code, None, Determinism::Enforced,
+		));
 
 		assert_ok!(
 			Contracts::instantiate(
 				Origin::signed(ALICE),
 				subsistence,
 				GAS_LIMIT,
+				None,
 				caller_hash.into(),
 				vec![0],
 				vec![],
@@ -1780,7 +2344,10 @@ fn call_return_code() {
 			addr_bob.clone(),
 			0,
 			GAS_LIMIT,
+			None,
 			AsRef::<[u8]>::as_ref(&DJANGO).to_vec(),
+			false,
+			Determinism::Enforced,
 		).exec_result.unwrap();
 		assert_return_code!(result, RuntimeReturnCode::NotCallable);
 
@@ -1789,6 +2356,7 @@ fn call_return_code() {
 				Origin::signed(CHARLIE),
 				subsistence,
 				GAS_LIMIT,
+				None,
 				callee_hash.into(),
 				vec![0],
 				vec![],
@@ -1802,7 +2370,10 @@ fn call_return_code() {
 			addr_bob.clone(),
 			0,
 			GAS_LIMIT,
+			None,
 			AsRef::<[u8]>::as_ref(&addr_django).iter().chain(&0u32.to_le_bytes()).cloned().collect(),
+			false,
+			Determinism::Enforced,
 		).exec_result.unwrap();
 		assert_return_code!(result, RuntimeReturnCode::BelowSubsistenceThreshold);
 
@@ -1816,7 +2387,10 @@ fn call_return_code() {
 			addr_bob.clone(),
 			0,
 			GAS_LIMIT,
+			None,
 			AsRef::<[u8]>::as_ref(&addr_django).iter().chain(&0u32.to_le_bytes()).cloned().collect(),
+			false,
+			Determinism::Enforced,
 		).exec_result.unwrap();
 		assert_return_code!(result, RuntimeReturnCode::TransferFailed);
 
@@ -1827,7 +2401,10 @@ fn call_return_code() {
 			addr_bob.clone(),
 			0,
 			GAS_LIMIT,
+			None,
 			AsRef::<[u8]>::as_ref(&addr_django).iter().chain(&1u32.to_le_bytes()).cloned().collect(),
+			false,
+			Determinism::Enforced,
 		).exec_result.unwrap();
 		assert_return_code!(result, RuntimeReturnCode::CalleeReverted);
 
@@ -1837,7 +2414,10 @@ fn call_return_code() {
 			addr_bob,
 			0,
 			GAS_LIMIT,
+			None,
 			AsRef::<[u8]>::as_ref(&addr_django).iter().chain(&2u32.to_le_bytes()).cloned().collect(),
+			false,
+			Determinism::Enforced,
 		).exec_result.unwrap();
 		assert_return_code!(result, RuntimeReturnCode::CalleeTrapped);
 
@@ -1852,8 +2432,12 @@ fn instantiate_return_code() {
 		let subsistence = ConfigCache::<Test>::subsistence_threshold_uncached();
 		let _ = Balances::deposit_creating(&ALICE, 10 * subsistence);
 		let _ = Balances::deposit_creating(&CHARLIE, 10 * subsistence);
-		assert_ok!(Contracts::put_code(Origin::signed(ALICE), caller_code));
-		assert_ok!(Contracts::put_code(Origin::signed(ALICE), callee_code));
+		assert_ok!(Contracts::upload_code(
+			Origin::signed(ALICE), caller_code, None, Determinism::Enforced,
+		));
+		assert_ok!(Contracts::upload_code(
+			Origin::signed(ALICE), callee_code, None, Determinism::Enforced,
+		));
 		let callee_hash = callee_hash.as_ref().to_vec();
 
 		assert_ok!(
@@ -1861,6 +2445,7 @@ fn instantiate_return_code() {
 				Origin::signed(ALICE),
 				subsistence,
 				GAS_LIMIT,
+				None,
 				caller_hash.into(),
 				vec![],
 				vec![],
@@ -1874,7 +2459,10 @@ fn instantiate_return_code() {
 			addr.clone(),
 			0,
 			GAS_LIMIT,
+			None,
 			vec![0; 33],
+			false,
+			Determinism::Enforced,
 		).exec_result.unwrap();
 		assert_return_code!(result, RuntimeReturnCode::BelowSubsistenceThreshold);
 
@@ -1888,7 +2476,10 @@ fn instantiate_return_code() {
 			addr.clone(),
 			0,
 			GAS_LIMIT,
+			None,
 			vec![0; 33],
+			false,
+			Determinism::Enforced,
 		).exec_result.unwrap();
 		assert_return_code!(result, RuntimeReturnCode::TransferFailed);
 
@@ -1899,7 +2490,10 @@ fn instantiate_return_code() {
 			addr.clone(),
 			0,
 			GAS_LIMIT,
+			None,
 			vec![0; 33],
+			false,
+			Determinism::Enforced,
 		).exec_result.unwrap();
 		assert_return_code!(result, RuntimeReturnCode::CodeNotFound);
 
@@ -1909,7 +2503,10 @@ fn instantiate_return_code() {
 			addr.clone(),
 			0,
 			GAS_LIMIT,
+			None,
 			callee_hash.iter().chain(&1u32.to_le_bytes()).cloned().collect(),
+			false,
+			Determinism::Enforced,
 		).exec_result.unwrap();
 		assert_return_code!(result, RuntimeReturnCode::CalleeReverted);
 
@@ -1919,9 +2516,461 @@ fn instantiate_return_code() {
 			addr,
 			0,
 			GAS_LIMIT,
+			None,
 			callee_hash.iter().chain(&2u32.to_le_bytes()).cloned().collect(),
+			false,
+			Determinism::Enforced,
 		).exec_result.unwrap();
 		assert_return_code!(result, RuntimeReturnCode::CalleeTrapped);
 
 	});
 }
+
+#[test]
+fn disabled_chain_extension_wont_deploy() {
+	let (code, _hash) = compile_module::<Test>("chain_extension").unwrap();
+	ExtBuilder::default().existential_deposit(50).build().execute_with(|| {
+		let _ = Balances::deposit_creating(&ALICE, 1_000_000);
+		TestExtension::disable();
+		assert_err!(
+			Contracts::upload_code(Origin::signed(ALICE), code, None, Determinism::Enforced),
+			"module uses chain extensions but chain extensions are disabled",
+		);
+	});
+}
+
+#[test]
+fn chain_extension_works() {
+	let (code, hash) = compile_module::<Test>("chain_extension").unwrap();
+	ExtBuilder::default().existential_deposit(50).build().execute_with(|| {
+		let _ = Balances::deposit_creating(&ALICE, 1_000_000);
+		assert_ok!(Contracts::upload_code(
+			Origin::signed(ALICE), code, None, Determinism::Enforced,
+		));
+		assert_ok!(
+			Contracts::instantiate(
+				Origin::signed(ALICE),
+				100_000,
+				GAS_LIMIT,
+				None,
+				hash.into(),
+				vec![],
+				vec![],
+			),
+		);
+		let addr = Contracts::contract_address(&ALICE, &hash, &[]);
+
+		// The contract takes a up to 16 byte buffer where the first 4 bytes are the func_id
+		// and passes it to the chain extension.
+
+		// 0 = read input buffer and pass it through as output
+		let input: Vec<u8> = vec![0, 0, 0, 0, 42, 99];
+		let result = Contracts::bare_call(
+			ALICE,
+			addr.clone(),
+			0,
+			GAS_LIMIT,
+			None,
+			input.clone(),
+			false,
+			Determinism::Enforced,
+		).exec_result.unwrap();
+		assert!(result.is_success());
+		assert_eq!(TestExtension::last_seen_buffer(), input);
+		assert_eq!(result.data, input);
+
+		// 1 = treat inputs as integer primitives and store the supplied integers
+		Contracts::bare_call(
+			ALICE,
+			addr.clone(),
+			0,
+			GAS_LIMIT,
+			None,
+			vec![1, 0, 0, 0],
+			false,
+			Determinism::Enforced,
+		).exec_result.unwrap();
+		// those values passed in the fixture
+		assert_eq!(TestExtension::last_seen_inputs(), (4, 4, 24, 20));
+
+		// 2 = charge some extra weight (amount supplied in the second u32 of the input)
+		let gas_consumed = |weight: u32| {
+			let result = Contracts::bare_call(
+				ALICE,
+				addr.clone(),
+				0,
+				GAS_LIMIT,
+				None,
+				(2u32, weight).encode(),
+				false,
+				Determinism::Enforced,
+			);
+			assert!(result.exec_result.unwrap().is_success());
+			result.gas_consumed
+		};
+		assert_eq!(gas_consumed(1_000) - gas_consumed(0), 1_000);
+
+		// 0x10000 = the reverting extension is addressed by the upper 16 bits
+		let result = Contracts::bare_call(
+			ALICE,
+			addr.clone(),
+			0,
+			GAS_LIMIT,
+			None,
+			vec![0, 0, 1, 0],
+			false,
+			Determinism::Enforced,
+		).exec_result.unwrap();
+		assert_eq!(result.flags, ReturnFlags::REVERT);
+		assert_eq!(result.data, ALICE.encode());
+
+		// 0x20000 = no extension is registered under this id
+		let result = Contracts::bare_call(
+			ALICE,
+			addr.clone(),
+			0,
+			GAS_LIMIT,
+			None,
+			vec![0, 0, 2, 0],
+			false,
+			Determinism::Enforced,
+		).exec_result;
+		assert_eq!(result.unwrap_err().error, Error::<Test>::NoChainExtension.into());
+	});
+}
+
+#[test]
+fn deletion_queue_full() {
+	let (wasm, code_hash) = compile_module::<Test>("self_destruct").unwrap();
+	ExtBuilder::default().existential_deposit(50).build().execute_with(|| {
+		let _ = Balances::deposit_creating(&ALICE, 1_000_000);
+		assert_ok!(Contracts::upload_code(
+			Origin::signed(ALICE), wasm, None, Determinism::Enforced,
+		));
+		assert_ok!(Contracts::instantiate(
+			Origin::signed(ALICE),
+			100_000,
+			GAS_LIMIT,
+			None,
+			code_hash.into(),
+			vec![],
+			vec![],
+		));
+		let addr = Contracts::contract_address(&ALICE, &code_hash, &[]);
+
+		// fill the deletion queue up until its limit
+		Storage::<Test>::fill_queue_with_dummies();
+
+		// Terminate the contract should fail
+		assert_err_ignore_postinfo!(
+			Contracts::call(
+				Origin::signed(ALICE),
+				addr.clone(),
+				0,
+				GAS_LIMIT,
+				None,
+				vec![],
+			),
+			Error::<Test>::DeletionQueueFull,
+		);
+
+		// Contract should be alive because removal failed
+		assert_matches!(ContractInfoOf::<Test>::get(&addr), Some(_));
+
+		// make sure the beneficiary got nothing
+		assert_eq!(Balances::free_balance(&DJANGO), 0);
+	});
+}
+
+/// Instantiates the `self_destruct` fixture and stores `values` into its storage.
+fn instantiate_and_store(values: &[([u8; 32], Vec<u8>)]) -> (AccountId32, ChildInfo) {
+	let (wasm, code_hash) = compile_module::<Test>("self_destruct").unwrap();
+	let _ = Balances::deposit_creating(&ALICE, 1_000_000);
+	assert_ok!(Contracts::upload_code(Origin::signed(ALICE), wasm, None, Determinism::Enforced));
+	assert_ok!(Contracts::instantiate(
+		Origin::signed(ALICE),
+		100_000,
+		GAS_LIMIT,
+		None,
+		code_hash.into(),
+		vec![],
+		vec![],
+	));
+	let addr = Contracts::contract_address(&ALICE, &code_hash, &[]);
+	let info = ContractInfoOf::<Test>::get(&addr).unwrap();
+	for (key, value) in values {
+		Storage::<Test>::write(&addr, &info.trie_id, key, Some(value.clone())).unwrap();
+	}
+	(addr, info.child_trie_info())
+}
+
+#[test]
+fn lazy_removal_works() {
+	ExtBuilder::default().existential_deposit(50).build().execute_with(|| {
+		let (addr, trie) = instantiate_and_store(&[([99; 32], 42u32.encode())]);
+		let key = blake2_256(&[99; 32]);
+
+		// Terminate the contract
+		assert_ok!(Contracts::call(
+			Origin::signed(ALICE),
+			addr.clone(),
+			0,
+			GAS_LIMIT,
+			None,
+			vec![],
+		));
+
+		// Contract info should be gone
+		assert!(!ContractInfoOf::<Test>::contains_key(&addr));
+
+		// But value should be still there as the lazy removal did not run, yet.
+		assert_matches!(child::get(&trie, &key), Some(42u32));
+		assert_eq!(DeletionQueue::decode_len(), Some(1));
+
+		// Run the lazy removal
+		Contracts::on_initialize(0);
+
+		// Value should be gone now
+		assert_matches!(child::get::<u32>(&trie, &key), None);
+		assert_eq!(DeletionQueue::decode_len(), Some(0));
+	});
+}
+
+#[test]
+fn lazy_removal_partial_remove_works() {
+	// We create a contract with some extra keys above the weight limit
+	let extra_keys = 7u32;
+	let weight_limit = 5_000_000_000;
+	let (_, max_keys) = Storage::<Test>::deletion_budget(1, weight_limit);
+	let vals: Vec<_> = (0..max_keys + extra_keys)
+		.map(|i| (blake2_256(&i.encode()), i.encode()))
+		.collect();
+
+	ExtBuilder::default().existential_deposit(50).build().execute_with(|| {
+		let (addr, trie) = instantiate_and_store(&vals);
+
+		// Terminate the contract
+		assert_ok!(Contracts::call(
+			Origin::signed(ALICE),
+			addr.clone(),
+			0,
+			GAS_LIMIT,
+			None,
+			vec![],
+		));
+
+		// Run the lazy removal
+		let weight_used = Storage::<Test>::process_deletion_queue_batch(weight_limit);
+
+		// Weight should be exhausted because we could not even delete all keys
+		assert_eq!(weight_used, weight_limit);
+
+		let mut num_deleted = 0u32;
+		let mut num_remaining = 0u32;
+
+		for (i, (key, _)) in vals.iter().enumerate() {
+			match child::get::<u32>(&trie, &blake2_256(key)) {
+				None => num_deleted += 1,
+				Some(x) if x == i as u32 => num_remaining += 1,
+				Some(_) => panic!("Unexpected value in contract storage"),
+			}
+		}
+
+		// All but one key is removed and the trie is still queued
+		assert_eq!(num_deleted + num_remaining, vals.len() as u32);
+		assert_eq!(num_deleted, max_keys);
+		assert_eq!(num_remaining, extra_keys);
+		assert_eq!(DeletionQueue::decode_len(), Some(1));
+
+		// Another batch removes the rest and dequeues the trie
+		Storage::<Test>::process_deletion_queue_batch(weight_limit);
+		assert_eq!(DeletionQueue::decode_len(), Some(0));
+	});
+}
+
+#[test]
+fn lazy_removal_does_no_run_on_full_block() {
+	ExtBuilder::default().existential_deposit(50).build().execute_with(|| {
+		let (addr, trie) = instantiate_and_store(&[([99; 32], 42u32.encode())]);
+		let key = blake2_256(&[99; 32]);
+
+		// Terminate the contract
+		assert_ok!(Contracts::call(
+			Origin::signed(ALICE),
+			addr.clone(),
+			0,
+			GAS_LIMIT,
+			None,
+			vec![],
+		));
+
+		// Fill up the block which should prevent the lazy storage removal from running.
+		System::register_extra_weight_unchecked(
+			<Test as frame_system::Config>::MaximumBlockWeight::get(),
+			DispatchClass::Mandatory,
+		);
+
+		// Run the lazy removal which would remove all keys if there was weight left in the block.
+		let weight_used = Contracts::on_initialize(0);
+		let base = <<Test as Config>::WeightInfo as WeightInfo>::on_initialize();
+		assert_eq!(weight_used, base);
+
+		// The key is still in place
+		assert_matches!(child::get(&trie, &key), Some(42u32));
+
+		// Run the lazy removal directly which disregards the block limits
+		Storage::<Test>::process_deletion_queue_batch(Weight::max_value());
+
+		// Now the key should be gone
+		assert_matches!(child::get::<u32>(&trie, &key), None);
+	});
+}
+
+
+#[test]
+fn ecdsa_recover_works() {
+	let (wasm, code_hash) = compile_module::<Test>("ecdsa_recover").unwrap();
+
+	ExtBuilder::default()
+		.existential_deposit(50)
+		.build()
+		.execute_with(|| {
+			let _ = Balances::deposit_creating(&ALICE, 1_000_000);
+			assert_ok!(Contracts::instantiate_with_code(
+				Origin::signed(ALICE),
+				30_000,
+				GAS_LIMIT,
+				None,
+				wasm,
+				vec![],
+				vec![],
+			));
+			let addr = Contracts::contract_address(&ALICE, &code_hash, &[]);
+
+			let key_type = sp_core::crypto::KeyTypeId(*b"code");
+			let pub_key = sp_io::crypto::ecdsa_generate(key_type, None);
+			let message = b"Hello World!";
+			let signature = sp_io::crypto::ecdsa_sign(key_type, &pub_key, message).unwrap();
+			let signature: &[u8] = signature.as_ref();
+			let mut input = signature.to_vec();
+			input.extend_from_slice(&blake2_256(message));
+
+			let result = Contracts::bare_call(
+				ALICE, addr.clone(), 0, GAS_LIMIT, None, input.clone(), false,
+				Determinism::Enforced,
+			).exec_result.unwrap();
+			assert_eq!(&result.data[..4], &(RuntimeReturnCode::Success as u32).encode()[..]);
+			assert_eq!(&result.data[4..], pub_key.as_ref());
+
+			// A signature of a different message yields a different key.
+			input[65..].copy_from_slice(&blake2_256(b"Goodbye World!"));
+			let result = Contracts::bare_call(
+				ALICE, addr.clone(), 0, GAS_LIMIT, None, input, false,
+				Determinism::Enforced,
+			).exec_result.unwrap();
+			assert_ne!(&result.data[4..], pub_key.as_ref());
+
+			// An invalid signature cannot be recovered.
+			let result = Contracts::bare_call(
+				ALICE, addr, 0, GAS_LIMIT, None, [0u8; 97].to_vec(), false,
+				Determinism::Enforced,
+			).exec_result.unwrap();
+			assert_eq!(
+				&result.data[..4],
+				&(RuntimeReturnCode::EcdsaRecoverFailed as u32).encode()[..],
+			);
+		});
+}
+
+#[test]
+fn ecdsa_to_eth_address_works() {
+	let (wasm, code_hash) = compile_module::<Test>("ecdsa_to_eth_address").unwrap();
+
+	ExtBuilder::default()
+		.existential_deposit(50)
+		.build()
+		.execute_with(|| {
+			let _ = Balances::deposit_creating(&ALICE, 1_000_000);
+			assert_ok!(Contracts::instantiate_with_code(
+				Origin::signed(ALICE),
+				30_000,
+				GAS_LIMIT,
+				None,
+				wasm,
+				vec![],
+				vec![],
+			));
+			let addr = Contracts::contract_address(&ALICE, &code_hash, &[]);
+
+			// The compressed public key of the secret key `1` and its well known address.
+			let pub_key = hex_literal::hex!(
+				"0279be667ef9dcbbac55a06295ce870b07029bfcdb2dce28d959f2815b16f81798"
+			);
+			let result = Contracts::bare_call(
+				ALICE, addr.clone(), 0, GAS_LIMIT, None, pub_key.to_vec(), false,
+				Determinism::Enforced,
+			).exec_result.unwrap();
+			assert_eq!(&result.data[..4], &(RuntimeReturnCode::Success as u32).encode()[..]);
+			assert_eq!(
+				&result.data[4..],
+				&hex_literal::hex!("7e5f4552091a69125d5dfcb7b8c2659029395bdf")[..],
+			);
+
+			// An invalid prefix byte is rejected.
+			let mut invalid_key = pub_key;
+			invalid_key[0] = 0x05;
+			let result = Contracts::bare_call(
+				ALICE, addr, 0, GAS_LIMIT, None, invalid_key.to_vec(), false,
+				Determinism::Enforced,
+			).exec_result.unwrap();
+			assert_eq!(
+				&result.data[..4],
+				&(RuntimeReturnCode::EcdsaToEthAddressFailed as u32).encode()[..],
+			);
+		});
+}
+
+#[test]
+fn sr25519_verify_works() {
+	let (wasm, code_hash) = compile_module::<Test>("sr25519_verify").unwrap();
+
+	ExtBuilder::default()
+		.existential_deposit(50)
+		.build()
+		.execute_with(|| {
+			let _ = Balances::deposit_creating(&ALICE, 1_000_000);
+			assert_ok!(Contracts::instantiate_with_code(
+				Origin::signed(ALICE),
+				30_000,
+				GAS_LIMIT,
+				None,
+				wasm,
+				vec![],
+				vec![],
+			));
+			let addr = Contracts::contract_address(&ALICE, &code_hash, &[]);
+
+			let key_type = sp_core::crypto::KeyTypeId(*b"code");
+			let pub_key = sp_io::crypto::sr25519_generate(key_type, None);
+			let message = b"Hello World!";
+			let signature = sp_io::crypto::sr25519_sign(key_type, &pub_key, message).unwrap();
+			let input = |message: &[u8]| {
+				let mut input = signature.as_ref().to_vec();
+				input.extend_from_slice(pub_key.as_ref());
+				input.extend_from_slice(message);
+				input
+			};
+
+			let result = Contracts::bare_call(
+				ALICE, addr.clone(), 0, GAS_LIMIT, None, input(message), false,
+				Determinism::Enforced,
+			).exec_result.unwrap();
+			assert_return_code!(result, RuntimeReturnCode::Success);
+
+			let result = Contracts::bare_call(
+				ALICE, addr, 0, GAS_LIMIT, None, input(b"Goodbye World!"), false,
+				Determinism::Enforced,
+			).exec_result.unwrap();
+			assert_return_code!(result, RuntimeReturnCode::Sr25519VerifyFailed);
+		});
+}
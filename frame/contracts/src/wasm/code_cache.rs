@@ -25,29 +25,71 @@
 //! - When we update the schedule we want it to have strictly greater version than the current saved one:
 //! this guarantees that every instrumented contract code in cache cannot have the version equal to the current one.
 //! Thus, before executing a contract it should be reinstrument with new schedule.
+//! - Every stored code is owned by the account that uploaded it. The owner pays a deposit
+//! for the code which is refunded when the code is removed. Code is removed by its owner
+//! or when the last contract using it is terminated.
 
 use crate::wasm::{prepare, runtime::Env, PrefabWasmModule};
-use crate::{CodeHash, CodeStorage, PristineCode, Schedule, Config};
+use crate::{
+	BalanceOf, CodeHash, CodeStorage, Config, Error, Module, OwnerInfo, OwnerInfoOf,
+	PristineCode, RawEvent, Schedule,
+};
+use pallet_contracts_primitives::Determinism;
 use sp_std::prelude::*;
-use sp_runtime::traits::Hash;
+use sp_runtime::traits::{Hash, Saturating, BadOrigin, Zero};
 use sp_core::crypto::UncheckedFrom;
-use frame_support::StorageMap;
+use frame_support::{
+	dispatch::{DispatchError, DispatchResult},
+	ensure,
+	traits::{Get, ReservableCurrency},
+	StorageMap,
+};
 
 /// Put code in the storage. The hash of code is used as a key and is returned
-/// as a result of this function.
+/// as a result of this function together with the deposit reserved from `owner`.
 ///
-/// This function instruments the given code and caches it in the storage.
+/// This function instruments the given code and caches it in the storage. If the code is
+/// already stored nothing is reserved. Code that was stored with relaxed determinism is
+/// validated again when determinism is enforced, so that the first uploader can't prevent the
+/// code from being executed on-chain by storing a relaxed copy of it.
 pub fn save<T: Config>(
 	original_code: Vec<u8>,
+	owner: T::AccountId,
+	storage_deposit_limit: Option<BalanceOf<T>>,
 	schedule: &Schedule<T>,
-) -> Result<CodeHash<T>, &'static str> where T::AccountId: UncheckedFrom<T::Hash> + AsRef<[u8]> {
-	let prefab_module = prepare::prepare_contract::<Env, T>(&original_code, schedule)?;
+	determinism: Determinism,
+) -> Result<(CodeHash<T>, BalanceOf<T>), DispatchError>
+where
+	T::AccountId: UncheckedFrom<T::Hash> + AsRef<[u8]>
+{
 	let code_hash = T::Hashing::hash(&original_code);
+	if <OwnerInfoOf<T>>::contains_key(&code_hash) {
+		let stored = <CodeStorage<T>>::get(&code_hash).ok_or(Error::<T>::CodeNotFound)?;
+		if stored.determinism == Determinism::Relaxed && determinism == Determinism::Enforced {
+			// Fails if the code isn't deterministic. Otherwise the code can be executed with
+			// both determinism modes from now on.
+			let prefab_module =
+				prepare::prepare_contract::<Env, T>(&original_code, schedule, determinism)?;
+			<CodeStorage<T>>::insert(&code_hash, prefab_module);
+		}
+		return Ok((code_hash, Zero::zero()));
+	}
 
-	<CodeStorage<T>>::insert(code_hash, prefab_module);
-	<PristineCode<T>>::insert(code_hash, original_code);
+	let prefab_module =
+		prepare::prepare_contract::<Env, T>(&original_code, schedule, determinism)?;
+	let deposit = code_deposit::<T>(&original_code, &prefab_module);
+	if let Some(limit) = storage_deposit_limit {
+		ensure!(deposit <= limit, Error::<T>::StorageDepositLimitExhausted);
+	}
+	T::Currency::reserve(&owner, deposit)
+		.map_err(|_| Error::<T>::StorageDepositNotEnoughFunds)?;
+	store::<T>(code_hash, original_code, prefab_module, OwnerInfo::<T> {
+		owner: Some(owner),
+		deposit,
+		refcount: 0,
+	});
 
-	Ok(code_hash)
+	Ok((code_hash, deposit))
 }
 
 /// Version of `save` to be used in runtime benchmarks.
@@ -57,17 +99,66 @@ pub fn save<T: Config>(
 #[cfg(feature = "runtime-benchmarks")]
 pub fn save_raw<T: Config>(
 	original_code: Vec<u8>,
+	owner: T::AccountId,
 	schedule: &Schedule<T>,
 ) -> Result<CodeHash<T>, &'static str> where T::AccountId: UncheckedFrom<T::Hash> + AsRef<[u8]> {
 	let prefab_module = prepare::benchmarking::prepare_contract::<T>(&original_code, schedule)?;
 	let code_hash = T::Hashing::hash(&original_code);
 
-	<CodeStorage<T>>::insert(code_hash, prefab_module);
-	<PristineCode<T>>::insert(code_hash, original_code);
+	store::<T>(code_hash, original_code, prefab_module, OwnerInfo::<T> {
+		owner: Some(owner),
+		deposit: Zero::zero(),
+		refcount: 0,
+	});
 
 	Ok(code_hash)
 }
 
+/// Remove the code stored under `code_hash` on behalf of `origin` and refund its deposit.
+///
+/// Only the owner can remove a code and only while no contract is using it.
+pub fn try_remove<T: Config>(origin: &T::AccountId, code_hash: CodeHash<T>) -> DispatchResult
+where
+	T::AccountId: UncheckedFrom<T::Hash> + AsRef<[u8]>
+{
+	let owner_info = <OwnerInfoOf<T>>::get(&code_hash).ok_or(Error::<T>::CodeNotFound)?;
+	ensure!(owner_info.owner.as_ref() == Some(origin), BadOrigin);
+	ensure!(owner_info.refcount == 0, Error::<T>::CodeInUse);
+	remove::<T>(code_hash, owner_info);
+	Ok(())
+}
+
+/// Increment the number of contracts using the code stored under `code_hash`.
+///
+/// Fails if there is no code stored under `code_hash`.
+pub fn increment_refcount<T: Config>(code_hash: &CodeHash<T>) -> DispatchResult
+where
+	T::AccountId: UncheckedFrom<T::Hash> + AsRef<[u8]>
+{
+	<OwnerInfoOf<T>>::try_mutate(code_hash, |owner_info| -> DispatchResult {
+		let owner_info = owner_info.as_mut().ok_or(Error::<T>::CodeNotFound)?;
+		owner_info.refcount = owner_info.refcount.saturating_add(1);
+		Ok(())
+	})
+}
+
+/// Decrement the number of contracts using the code stored under `code_hash`.
+///
+/// The code is removed and its deposit refunded to its owner when no contract uses it anymore.
+pub fn decrement_refcount<T: Config>(code_hash: &CodeHash<T>)
+where
+	T::AccountId: UncheckedFrom<T::Hash> + AsRef<[u8]>
+{
+	if let Some(mut owner_info) = <OwnerInfoOf<T>>::get(code_hash) {
+		owner_info.refcount = owner_info.refcount.saturating_sub(1);
+		if owner_info.refcount == 0 {
+			remove::<T>(*code_hash, owner_info);
+		} else {
+			<OwnerInfoOf<T>>::insert(code_hash, owner_info);
+		}
+	}
+}
+
 /// Load code with the given code hash.
 ///
 /// If the module was instrumented with a lower version of schedule than
@@ -87,8 +178,51 @@ pub fn load<T: Config>(
 		// We need to re-instrument the code with the latest schedule here.
 		let original_code =
 			<PristineCode<T>>::get(code_hash).ok_or_else(|| "pristine code is not found")?;
-		prefab_module = prepare::prepare_contract::<Env, T>(&original_code, schedule)?;
+		prefab_module = prepare::prepare_contract::<Env, T>(
+			&original_code,
+			schedule,
+			prefab_module.determinism,
+		)?;
 		<CodeStorage<T>>::insert(&code_hash, &prefab_module);
 	}
 	Ok(prefab_module)
 }
+
+/// The deposit the owner of a code needs to pay for storing it.
+///
+/// The pristine and the instrumented code are both stored in separate storage items.
+fn code_deposit<T: Config>(original_code: &[u8], prefab_module: &PrefabWasmModule) -> BalanceOf<T> {
+	let bytes = original_code.len().saturating_add(prefab_module.code.len()) as u32;
+	T::DepositPerByte::get()
+		.saturating_mul(bytes.into())
+		.saturating_add(T::DepositPerItem::get().saturating_mul(2u32.into()))
+}
+
+/// Write a new code together with its owner information into the storage.
+fn store<T: Config>(
+	code_hash: CodeHash<T>,
+	original_code: Vec<u8>,
+	prefab_module: PrefabWasmModule,
+	owner_info: OwnerInfo<T>,
+) where
+	T::AccountId: UncheckedFrom<T::Hash> + AsRef<[u8]>
+{
+	<CodeStorage<T>>::insert(code_hash, prefab_module);
+	<PristineCode<T>>::insert(code_hash, original_code);
+	<OwnerInfoOf<T>>::insert(code_hash, owner_info);
+	<Module<T>>::deposit_event(RawEvent::CodeStored(code_hash));
+}
+
+/// Remove a code from the storage and refund its deposit to the owner.
+fn remove<T: Config>(code_hash: CodeHash<T>, owner_info: OwnerInfo<T>)
+where
+	T::AccountId: UncheckedFrom<T::Hash> + AsRef<[u8]>
+{
+	if let Some(owner) = &owner_info.owner {
+		T::Currency::unreserve(owner, owner_info.deposit);
+	}
+	<CodeStorage<T>>::remove(code_hash);
+	<PristineCode<T>>::remove(code_hash);
+	<OwnerInfoOf<T>>::remove(code_hash);
+	<Module<T>>::deposit_event(RawEvent::CodeRemoved(code_hash));
+}
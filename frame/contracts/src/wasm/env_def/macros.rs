@@ -45,23 +45,33 @@ macro_rules! gen_signature {
 #[macro_export]
 macro_rules! gen_signature_dispatch {
 	(
+		$needle_module:ident,
 		$needle_name:ident,
 		$needle_sig:ident ;
-		$name:ident
+		$( [$module:ident] )? $name:ident
 		( $ctx:ident $( , $names:ident : $params:ty )* ) $( -> $returns:ty )* , $($rest:tt)* ) => {
-		if stringify!($name).as_bytes() == $needle_name {
+		if import_module!($( $module )?).as_bytes() == $needle_module &&
+			stringify!($name).as_bytes() == $needle_name
+		{
 			let signature = gen_signature!( ( $( $params ),* ) $( -> $returns )* );
 			if $needle_sig == &signature {
 				return true;
 			}
 		} else {
-			gen_signature_dispatch!($needle_name, $needle_sig ; $($rest)*);
+			gen_signature_dispatch!($needle_module, $needle_name, $needle_sig ; $($rest)*);
 		}
 	};
-	( $needle_name:ident, $needle_sig:ident ; ) => {
+	( $needle_module:ident, $needle_name:ident, $needle_sig:ident ; ) => {
 	};
 }
 
+/// Resolves the module a host function is imported from. Functions that don't specify
+/// a module explicitly are placed into `IMPORT_MODULE_FN`.
+macro_rules! import_module {
+	() => { $crate::wasm::prepare::IMPORT_MODULE_FN };
+	( $module:ident ) => { stringify!($module) };
+}
+
 /// Unmarshall arguments and then execute `body` expression and return its result.
 macro_rules! unmarshall_then_body {
 	( $body:tt, $ctx:ident, $args_iter:ident, $( $names:ident : $params:ty ),* ) => ({
@@ -150,10 +160,11 @@ macro_rules! register_func {
 	( $reg_cb:ident, < E: $seal_ty:tt > ; ) => {};
 
 	( $reg_cb:ident, < E: $seal_ty:tt > ;
-		$name:ident ( $ctx:ident $( , $names:ident : $params:ty )* )
+		$( [$module:ident] )? $name:ident ( $ctx:ident $( , $names:ident : $params:ty )* )
 		$( -> $returns:ty )* => $body:tt $($rest:tt)*
 	) => {
 		$reg_cb(
+			import_module!($( $module )?).as_bytes(),
 			stringify!($name).as_bytes(),
 			{
 				define_func!(
@@ -168,6 +179,10 @@ macro_rules! register_func {
 
 /// Define a function set that can be imported by executing wasm code.
 ///
+/// A function can be prefixed with a module name in brackets (e.g. `[seal1] seal_call`) in order
+/// to define a new version of an existing function. Functions without a prefix are placed into
+/// the `seal0` module.
+///
 /// **NB**: Be advised that all functions defined by this macro
 /// will panic if called with unexpected arguments.
 ///
@@ -175,14 +190,21 @@ macro_rules! register_func {
 /// and reject the code if any imported function has a mismatched signature.
 macro_rules! define_env {
 	( $init_name:ident , < E: $seal_ty:tt > ,
-		$( $name:ident ( $ctx:ident $( , $names:ident : $params:ty )* )
+		$( $( [$module:ident] )? $name:ident ( $ctx:ident $( , $names:ident : $params:ty )* )
 			$( -> $returns:ty )* => $body:tt , )*
 	) => {
 		pub struct $init_name;
 
 		impl $crate::wasm::env_def::ImportSatisfyCheck for $init_name {
-			fn can_satisfy(name: &[u8], func_type: &parity_wasm::elements::FunctionType) -> bool {
-				gen_signature_dispatch!( name, func_type ; $( $name ( $ctx $(, $names : $params )* ) $( -> $returns )* , )* );
+			fn can_satisfy(
+				module: &[u8],
+				name: &[u8],
+				func_type: &parity_wasm::elements::FunctionType,
+			) -> bool {
+				gen_signature_dispatch!(
+					module, name, func_type ;
+					$( $( [$module] )? $name ( $ctx $(, $names : $params )* ) $( -> $returns )* , )*
+				);
 
 				return false;
 			}
@@ -194,8 +216,12 @@ macro_rules! define_env {
 				sp_core::crypto::UncheckedFrom<<E::T as frame_system::Config>::Hash> +
 					AsRef<[u8]>
 		{
-			fn impls<F: FnMut(&[u8], $crate::wasm::env_def::HostFunc<E>)>(f: &mut F) {
-				register_func!(f, < E: $seal_ty > ; $( $name ( $ctx $( , $names : $params )* ) $( -> $returns)* => $body )* );
+			fn impls<F: FnMut(&[u8], &[u8], $crate::wasm::env_def::HostFunc<E>)>(f: &mut F) {
+				register_func!(
+					f,
+					< E: $seal_ty > ;
+					$( $( [$module] )? $name ( $ctx $( , $names : $params )* ) $( -> $returns)* => $body )*
+				);
 			}
 		}
 	};
@@ -327,7 +353,16 @@ mod tests {
 			},
 		);
 
-		assert!(Env::can_satisfy(b"seal_gas", &FunctionType::new(vec![ValueType::I32], None)));
-		assert!(!Env::can_satisfy(b"not_exists", &FunctionType::new(vec![], None)));
+		assert!(Env::can_satisfy(
+			b"seal0",
+			b"seal_gas",
+			&FunctionType::new(vec![ValueType::I32], None),
+		));
+		assert!(!Env::can_satisfy(b"seal0", b"not_exists", &FunctionType::new(vec![], None)));
+		assert!(!Env::can_satisfy(
+			b"seal1",
+			b"seal_gas",
+			&FunctionType::new(vec![ValueType::I32], None),
+		));
 	}
 }
@@ -17,7 +17,7 @@
 //! This module provides a means for executing contracts
 //! represented in wasm.
 
-use crate::{CodeHash, Schedule, Config};
+use crate::{CodeHash, Schedule, Config, Error};
 use crate::wasm::env_def::FunctionImplProvider;
 use crate::exec::Ext;
 use crate::gas::GasMeter;
@@ -33,11 +33,13 @@ mod code_cache;
 mod prepare;
 mod runtime;
 
-use self::runtime::Runtime;
+pub(crate) use self::runtime::{Runtime, RuntimeToken};
+pub(crate) use self::prepare::{classify, InstructionSet};
 use self::code_cache::load as load_code;
-use pallet_contracts_primitives::ExecResult;
+use pallet_contracts_primitives::{ExecResult, Determinism};
+use frame_support::dispatch::DispatchResult;
 
-pub use self::code_cache::save as save_code;
+pub use self::code_cache::{save as save_code, try_remove as remove_code};
 #[cfg(feature = "runtime-benchmarks")]
 pub use self::code_cache::save_raw as save_code_raw;
 pub use self::runtime::ReturnCode;
@@ -52,11 +54,11 @@ pub struct PrefabWasmModule {
 	initial: u32,
 	#[codec(compact)]
 	maximum: u32,
-	/// This field is reserved for future evolution of format.
+	/// Whether the code is allowed to use indeterministic instructions.
 	///
-	/// Basically, for now this field will be serialized as `None`. In the future
-	/// we would be able to extend this structure with.
-	_reserved: Option<()>,
+	/// This field replaces a reserved `Option<()>` which was always `None`. Both encode
+	/// to the same byte for `Determinism::Enforced` which keeps old modules decodable.
+	determinism: Determinism,
 	/// Code instrumented with the latest schedule.
 	code: Vec<u8>,
 }
@@ -98,16 +100,24 @@ where
 			prefab_module,
 		})
 	}
+	fn add_user(&self, code_hash: &CodeHash<T>) -> DispatchResult {
+		code_cache::increment_refcount::<T>(code_hash)
+	}
+	fn remove_user(&self, code_hash: &CodeHash<T>) {
+		code_cache::decrement_refcount::<T>(code_hash)
+	}
 }
 
 /// Implementation of `Vm` that takes `WasmExecutable` and executes it.
 pub struct WasmVm<'a, T: Config> where T::AccountId: UncheckedFrom<T::Hash> + AsRef<[u8]> {
 	schedule: &'a Schedule<T>,
+	/// Code with relaxed determinism is only executed if this is `Determinism::Relaxed`.
+	determinism: Determinism,
 }
 
 impl<'a, T: Config> WasmVm<'a, T> where T::AccountId: UncheckedFrom<T::Hash> + AsRef<[u8]> {
-	pub fn new(schedule: &'a Schedule<T>) -> Self {
-		WasmVm { schedule }
+	pub fn new(schedule: &'a Schedule<T>, determinism: Determinism) -> Self {
+		WasmVm { schedule, determinism }
 	}
 }
 
@@ -124,6 +134,12 @@ where
 		input_data: Vec<u8>,
 		gas_meter: &mut GasMeter<E::T>,
 	) -> ExecResult {
+		if exec.prefab_module.determinism == Determinism::Relaxed &&
+			self.determinism == Determinism::Enforced
+		{
+			return Err(Error::<T>::Indeterministic.into());
+		}
+
 		let memory =
 			sp_sandbox::Memory::new(exec.prefab_module.initial, Some(exec.prefab_module.maximum))
 				.unwrap_or_else(|_| {
@@ -138,8 +154,8 @@ where
 
 		let mut imports = sp_sandbox::EnvironmentDefinitionBuilder::new();
 		imports.add_memory(self::prepare::IMPORT_MODULE_MEMORY, "memory", memory.clone());
-		runtime::Env::impls(&mut |name, func_ptr| {
-			imports.add_host_func(self::prepare::IMPORT_MODULE_FN, name, func_ptr);
+		runtime::Env::impls(&mut |module, name, func_ptr| {
+			imports.add_host_func(module, name, func_ptr);
 		});
 
 		let mut runtime = Runtime::new(
@@ -168,7 +184,7 @@ mod tests {
 		tests::{Test, Call, ALICE, BOB},
 		wasm::prepare::prepare_contract,
 	};
-	use std::collections::HashMap;
+	use std::{cell::RefCell, collections::HashMap};
 	use sp_core::H256;
 	use hex_literal::hex;
 	use sp_runtime::DispatchError;
@@ -181,14 +197,6 @@ mod tests {
 	#[derive(Debug, PartialEq, Eq)]
 	struct DispatchEntry(Call);
 
-	#[derive(Debug, PartialEq, Eq)]
-	struct RestoreEntry {
-		dest: AccountIdOf<Test>,
-		code_hash: H256,
-		rent_allowance: u64,
-		delta: Vec<StorageKey>,
-	}
-
 	#[derive(Debug, PartialEq, Eq)]
 	struct InstantiateEntry {
 		code_hash: H256,
@@ -210,16 +218,37 @@ mod tests {
 		data: Vec<u8>,
 	}
 
+	#[derive(Debug, PartialEq, Eq)]
+	struct CallEntry {
+		to: AccountIdOf<Test>,
+		value: u64,
+		data: Vec<u8>,
+		allows_reentry: bool,
+	}
+
+	#[derive(Debug, PartialEq, Eq)]
+	struct DelegateCallEntry {
+		code_hash: H256,
+		data: Vec<u8>,
+	}
+
 	#[derive(Default)]
 	pub struct MockExt {
 		storage: HashMap<StorageKey, Vec<u8>>,
-		rent_allowance: u64,
 		instantiates: Vec<InstantiateEntry>,
 		terminations: Vec<TerminationEntry>,
 		transfers: Vec<TransferEntry>,
-		restores: Vec<RestoreEntry>,
+		calls: Vec<CallEntry>,
+		delegate_calls: Vec<DelegateCallEntry>,
+		code_hashes: Vec<H256>,
+		delegate_dependencies: Vec<H256>,
+		debug_buffer: Vec<u8>,
 		// (topics, data)
 		events: Vec<(Vec<H256>, Vec<u8>)>,
+		// (signature, message_hash)
+		ecdsa_recover: RefCell<Vec<([u8; 65], [u8; 32])>>,
+		// (signature, message, pub_key)
+		sr25519_verify: RefCell<Vec<([u8; 64], Vec<u8>, [u8; 32])>>,
 	}
 
 	impl Ext for MockExt {
@@ -272,43 +301,76 @@ mod tests {
 			value: u64,
 			_gas_meter: &mut GasMeter<Test>,
 			data: Vec<u8>,
+			allows_reentry: bool,
 		) -> ExecResult {
-			self.transfers.push(TransferEntry {
+			self.calls.push(CallEntry {
 				to: to.clone(),
 				value,
-				data: data,
+				data: data.clone(),
+				allows_reentry,
 			});
-			// Assume for now that it was just a plain transfer.
+			// The callee echoes its input.
 			// TODO: Add tests for different call outcomes.
+			Ok(ExecReturnValue { flags: ReturnFlags::empty(), data })
+		}
+		fn delegate_call(
+			&mut self,
+			code_hash: CodeHash<Test>,
+			_gas_meter: &mut GasMeter<Test>,
+			data: Vec<u8>,
+		) -> ExecResult {
+			self.delegate_calls.push(DelegateCallEntry {
+				code_hash,
+				data,
+			});
 			Ok(ExecReturnValue { flags: ReturnFlags::empty(), data: Vec::new() })
 		}
-		fn terminate(
+		fn set_code_hash(&mut self, hash: CodeHash<Test>) -> Result<(), DispatchError> {
+			self.code_hashes.push(hash);
+			Ok(())
+		}
+		fn add_delegate_dependency(
 			&mut self,
-			beneficiary: &AccountIdOf<Self::T>,
+			code_hash: CodeHash<Test>,
 		) -> Result<(), DispatchError> {
-			self.terminations.push(TerminationEntry {
-				beneficiary: beneficiary.clone(),
-			});
+			self.delegate_dependencies.push(code_hash);
 			Ok(())
 		}
-		fn restore_to(
+		fn remove_delegate_dependency(
 			&mut self,
-			dest: AccountIdOf<Self::T>,
-			code_hash: H256,
-			rent_allowance: u64,
-			delta: Vec<StorageKey>,
+			code_hash: CodeHash<Test>,
 		) -> Result<(), DispatchError> {
-			self.restores.push(RestoreEntry {
-				dest,
-				code_hash,
-				rent_allowance,
-				delta,
+			self.delegate_dependencies.retain(|dependency| *dependency != code_hash);
+			Ok(())
+		}
+		fn delegate_dependencies_count(&self) -> u32 {
+			self.delegate_dependencies.len() as u32
+		}
+		fn terminate(
+			&mut self,
+			beneficiary: &AccountIdOf<Self::T>,
+		) -> Result<(), DispatchError> {
+			self.terminations.push(TerminationEntry {
+				beneficiary: beneficiary.clone(),
 			});
 			Ok(())
 		}
 		fn caller(&self) -> &AccountIdOf<Self::T> {
 			&ALICE
 		}
+		fn caller_is_origin(&self) -> bool {
+			false
+		}
+		fn is_contract(&self, address: &AccountIdOf<Self::T>) -> bool {
+			address == &BOB
+		}
+		fn code_hash(&self, address: &AccountIdOf<Self::T>) -> Option<CodeHash<Self::T>> {
+			if address == &BOB {
+				Some(H256::repeat_byte(0x11))
+			} else {
+				None
+			}
+		}
 		fn address(&self) -> &AccountIdOf<Self::T> {
 			&BOB
 		}
@@ -327,10 +389,6 @@ mod tests {
 			666
 		}
 
-		fn tombstone_deposit(&self) -> u64 {
-			16
-		}
-
 		fn random(&self, subject: &[u8]) -> H256 {
 			H256::from_slice(subject)
 		}
@@ -339,14 +397,6 @@ mod tests {
 			self.events.push((topics, data))
 		}
 
-		fn set_rent_allowance(&mut self, rent_allowance: u64) {
-			self.rent_allowance = rent_allowance;
-		}
-
-		fn rent_allowance(&self) -> u64 {
-			self.rent_allowance
-		}
-
 		fn block_number(&self) -> u64 { 121 }
 
 		fn max_value_size(&self) -> u32 { 16_384 }
@@ -354,6 +404,30 @@ mod tests {
 		fn get_weight_price(&self, weight: Weight) -> BalanceOf<Self::T> {
 			BalanceOf::<Self::T>::from(1312_u32).saturating_mul(weight.into())
 		}
+
+		fn append_debug_buffer(&mut self, msg: &str) -> bool {
+			self.debug_buffer.extend(msg.as_bytes());
+			true
+		}
+		fn ecdsa_recover(
+			&self,
+			signature: &[u8; 65],
+			message_hash: &[u8; 32],
+		) -> Result<[u8; 33], ()> {
+			self.ecdsa_recover.borrow_mut().push((*signature, *message_hash));
+			Ok([3; 33])
+		}
+		fn ecdsa_to_eth_address(&self, pk: &[u8; 33]) -> Result<[u8; 20], ()> {
+			if pk[0] == 3 {
+				Ok([2; 20])
+			} else {
+				Err(())
+			}
+		}
+		fn sr25519_verify(&self, signature: &[u8; 64], message: &[u8], pub_key: &[u8; 32]) -> bool {
+			self.sr25519_verify.borrow_mut().push((*signature, message.to_vec(), *pub_key));
+			true
+		}
 	}
 
 	impl Ext for &mut MockExt {
@@ -394,26 +468,48 @@ mod tests {
 			value: u64,
 			gas_meter: &mut GasMeter<Test>,
 			input_data: Vec<u8>,
+			allows_reentry: bool,
+		) -> ExecResult {
+			(**self).call(to, value, gas_meter, input_data, allows_reentry)
+		}
+		fn delegate_call(
+			&mut self,
+			code_hash: CodeHash<Test>,
+			gas_meter: &mut GasMeter<Test>,
+			input_data: Vec<u8>,
 		) -> ExecResult {
-			(**self).call(to, value, gas_meter, input_data)
+			(**self).delegate_call(code_hash, gas_meter, input_data)
 		}
-		fn restore_to(
+		fn set_code_hash(&mut self, hash: CodeHash<Test>) -> Result<(), DispatchError> {
+			(**self).set_code_hash(hash)
+		}
+		fn add_delegate_dependency(
 			&mut self,
-			dest: AccountIdOf<Self::T>,
-			code_hash: H256,
-			rent_allowance: u64,
-			delta: Vec<StorageKey>,
+			code_hash: CodeHash<Test>,
 		) -> Result<(), DispatchError> {
-			(**self).restore_to(
-				dest,
-				code_hash,
-				rent_allowance,
-				delta,
-			)
+			(**self).add_delegate_dependency(code_hash)
+		}
+		fn remove_delegate_dependency(
+			&mut self,
+			code_hash: CodeHash<Test>,
+		) -> Result<(), DispatchError> {
+			(**self).remove_delegate_dependency(code_hash)
+		}
+		fn delegate_dependencies_count(&self) -> u32 {
+			(**self).delegate_dependencies_count()
 		}
 		fn caller(&self) -> &AccountIdOf<Self::T> {
 			(**self).caller()
 		}
+		fn caller_is_origin(&self) -> bool {
+			(**self).caller_is_origin()
+		}
+		fn is_contract(&self, address: &AccountIdOf<Self::T>) -> bool {
+			(**self).is_contract(address)
+		}
+		fn code_hash(&self, address: &AccountIdOf<Self::T>) -> Option<CodeHash<Self::T>> {
+			(**self).code_hash(address)
+		}
 		fn address(&self) -> &AccountIdOf<Self::T> {
 			(**self).address()
 		}
@@ -429,21 +525,12 @@ mod tests {
 		fn minimum_balance(&self) -> u64 {
 			(**self).minimum_balance()
 		}
-		fn tombstone_deposit(&self) -> u64 {
-			(**self).tombstone_deposit()
-		}
 		fn random(&self, subject: &[u8]) -> H256 {
 			(**self).random(subject)
 		}
 		fn deposit_event(&mut self, topics: Vec<H256>, data: Vec<u8>) {
 			(**self).deposit_event(topics, data)
 		}
-		fn set_rent_allowance(&mut self, rent_allowance: u64) {
-			(**self).set_rent_allowance(rent_allowance)
-		}
-		fn rent_allowance(&self) -> u64 {
-			(**self).rent_allowance()
-		}
 		fn block_number(&self) -> u64 {
 			(**self).block_number()
 		}
@@ -453,6 +540,22 @@ mod tests {
 		fn get_weight_price(&self, weight: Weight) -> BalanceOf<Self::T> {
 			(**self).get_weight_price(weight)
 		}
+		fn append_debug_buffer(&mut self, msg: &str) -> bool {
+			(**self).append_debug_buffer(msg)
+		}
+		fn ecdsa_recover(
+			&self,
+			signature: &[u8; 65],
+			message_hash: &[u8; 32],
+		) -> Result<[u8; 33], ()> {
+			(**self).ecdsa_recover(signature, message_hash)
+		}
+		fn ecdsa_to_eth_address(&self, pk: &[u8; 33]) -> Result<[u8; 20], ()> {
+			(**self).ecdsa_to_eth_address(pk)
+		}
+		fn sr25519_verify(&self, signature: &[u8; 64], message: &[u8], pub_key: &[u8; 32]) -> bool {
+			(**self).sr25519_verify(signature, message, pub_key)
+		}
 	}
 
 	fn execute<E: Ext>(
@@ -470,7 +573,8 @@ mod tests {
 		let wasm = wat::parse_str(wat).unwrap();
 		let schedule = crate::Schedule::default();
 		let prefab_module =
-			prepare_contract::<super::runtime::Env, E::T>(&wasm, &schedule).unwrap();
+			prepare_contract::<super::runtime::Env, E::T>(&wasm, &schedule, Determinism::Enforced)
+				.unwrap();
 
 		let exec = WasmExecutable {
 			// Use a "call" convention.
@@ -479,7 +583,7 @@ mod tests {
 		};
 
 		let cfg = Default::default();
-		let vm = WasmVm::new(&cfg);
+		let vm = WasmVm::new(&cfg, Determinism::Enforced);
 
 		vm.execute(&exec, ext, input_data, gas_meter)
 	}
@@ -595,15 +699,416 @@ mod tests {
 		).unwrap();
 
 		assert_eq!(
-			&mock_ext.transfers,
-			&[TransferEntry {
+			&mock_ext.calls,
+			&[CallEntry {
 				to: ALICE,
 				value: 6,
 				data: vec![1, 2, 3, 4],
+				allows_reentry: true,
+			}]
+		);
+	}
+
+	const CODE_CALL_DENY_REENTRY: &str = r#"
+(module
+	(import "seal1" "seal_call" (func $seal_call (param i32 i32 i32 i64 i32 i32 i32 i32 i32 i32) (result i32)))
+	(import "env" "memory" (memory 1 1))
+	(func (export "call")
+		(drop
+			(call $seal_call
+				(i32.const 0) ;; No flags are set
+				(i32.const 4)  ;; Pointer to "callee" address.
+				(i32.const 32)  ;; Length of "callee" address.
+				(i64.const 0)  ;; How much gas to devote for the execution. 0 = all.
+				(i32.const 36) ;; Pointer to the buffer with value to transfer
+				(i32.const 8)  ;; Length of the buffer with value to transfer.
+				(i32.const 44) ;; Pointer to input data buffer address
+				(i32.const 4)  ;; Length of input data buffer
+				(i32.const 4294967295) ;; u32 max value is the sentinel value: do not copy output
+				(i32.const 0) ;; Length is ignored in this case
+			)
+		)
+	)
+	(func (export "deploy"))
+
+	;; Destination AccountId (ALICE)
+	(data (i32.const 4)
+		"\01\01\01\01\01\01\01\01\01\01\01\01\01\01\01\01"
+		"\01\01\01\01\01\01\01\01\01\01\01\01\01\01\01\01"
+	)
+
+	;; Amount of value to transfer.
+	;; Represented by u64 (8 bytes long) in little endian.
+	(data (i32.const 36) "\06\00\00\00\00\00\00\00")
+
+	(data (i32.const 44) "\01\02\03\04")
+)
+"#;
+
+	#[test]
+	fn contract_call_denies_reentry_by_default() {
+		let mut mock_ext = MockExt::default();
+		let _ = execute(
+			CODE_CALL_DENY_REENTRY,
+			vec![],
+			&mut mock_ext,
+			&mut GasMeter::new(GAS_LIMIT),
+		).unwrap();
+
+		assert_eq!(
+			&mock_ext.calls,
+			&[CallEntry {
+				to: ALICE,
+				value: 6,
+				data: vec![1, 2, 3, 4],
+				allows_reentry: false,
+			}]
+		);
+	}
+
+	const CODE_CALL_FORWARD_INPUT: &str = r#"
+(module
+	(import "seal1" "seal_call" (func $seal_call (param i32 i32 i32 i64 i32 i32 i32 i32 i32 i32) (result i32)))
+	(import "seal0" "seal_input" (func $seal_input (param i32 i32)))
+	(import "env" "memory" (memory 1 1))
+	(func (export "call")
+		(drop
+			(call $seal_call
+				(i32.const 1) ;; Set FORWARD_INPUT bit
+				(i32.const 4)  ;; Pointer to "callee" address.
+				(i32.const 32)  ;; Length of "callee" address.
+				(i64.const 0)  ;; How much gas to devote for the execution. 0 = all.
+				(i32.const 36) ;; Pointer to the buffer with value to transfer
+				(i32.const 8)  ;; Length of the buffer with value to transfer.
+				(i32.const 4294967295) ;; Input is ignored when it is forwarded
+				(i32.const 4294967295) ;; Input is ignored when it is forwarded
+				(i32.const 4294967295) ;; u32 max value is the sentinel value: do not copy output
+				(i32.const 0) ;; Length is ignored in this case
+			)
+		)
+
+		;; The input was consumed by the call. This must trap.
+		(call $seal_input (i32.const 48) (i32.const 44))
+	)
+	(func (export "deploy"))
+
+	;; Destination AccountId (ALICE)
+	(data (i32.const 4)
+		"\01\01\01\01\01\01\01\01\01\01\01\01\01\01\01\01"
+		"\01\01\01\01\01\01\01\01\01\01\01\01\01\01\01\01"
+	)
+
+	;; Amount of value to transfer.
+	;; Represented by u64 (8 bytes long) in little endian.
+	(data (i32.const 36) "\06\00\00\00\00\00\00\00")
+
+	;; Size of the input buffer
+	(data (i32.const 44) "\10")
+)
+"#;
+
+	#[test]
+	fn contract_call_forward_input() {
+		let mut mock_ext = MockExt::default();
+		let result = execute(
+			CODE_CALL_FORWARD_INPUT,
+			vec![0xff, 0x2a],
+			&mut mock_ext,
+			&mut GasMeter::new(GAS_LIMIT),
+		);
+
+		assert_eq!(
+			result,
+			Err(ExecError {
+				error: Error::<Test>::InputForwarded.into(),
+				origin: ErrorOrigin::Caller,
+			})
+		);
+		assert_eq!(
+			&mock_ext.calls,
+			&[CallEntry {
+				to: ALICE,
+				value: 6,
+				data: vec![0xff, 0x2a],
+				allows_reentry: false,
+			}]
+		);
+	}
+
+	const CODE_CALL_CLONE_INPUT: &str = r#"
+(module
+	(import "seal1" "seal_call" (func $seal_call (param i32 i32 i32 i64 i32 i32 i32 i32 i32 i32) (result i32)))
+	(import "seal0" "seal_input" (func $seal_input (param i32 i32)))
+	(import "seal0" "seal_return" (func $seal_return (param i32 i32 i32)))
+	(import "env" "memory" (memory 1 1))
+	(func (export "call")
+		(drop
+			(call $seal_call
+				(i32.const 10) ;; Set CLONE_INPUT | ALLOW_REENTRY bits
+				(i32.const 4)  ;; Pointer to "callee" address.
+				(i32.const 32)  ;; Length of "callee" address.
+				(i64.const 0)  ;; How much gas to devote for the execution. 0 = all.
+				(i32.const 36) ;; Pointer to the buffer with value to transfer
+				(i32.const 8)  ;; Length of the buffer with value to transfer.
+				(i32.const 4294967295) ;; Input is ignored when it is cloned
+				(i32.const 4294967295) ;; Input is ignored when it is cloned
+				(i32.const 4294967295) ;; u32 max value is the sentinel value: do not copy output
+				(i32.const 0) ;; Length is ignored in this case
+			)
+		)
+
+		;; The input is still available and is returned as output.
+		(call $seal_input (i32.const 48) (i32.const 44))
+		(call $seal_return (i32.const 0) (i32.const 48) (i32.load (i32.const 44)))
+	)
+	(func (export "deploy"))
+
+	;; Destination AccountId (ALICE)
+	(data (i32.const 4)
+		"\01\01\01\01\01\01\01\01\01\01\01\01\01\01\01\01"
+		"\01\01\01\01\01\01\01\01\01\01\01\01\01\01\01\01"
+	)
+
+	;; Amount of value to transfer.
+	;; Represented by u64 (8 bytes long) in little endian.
+	(data (i32.const 36) "\06\00\00\00\00\00\00\00")
+
+	;; Size of the input buffer
+	(data (i32.const 44) "\10")
+)
+"#;
+
+	#[test]
+	fn contract_call_clone_input() {
+		let mut mock_ext = MockExt::default();
+		let result = execute(
+			CODE_CALL_CLONE_INPUT,
+			vec![0xff, 0x2a],
+			&mut mock_ext,
+			&mut GasMeter::new(GAS_LIMIT),
+		).unwrap();
+
+		assert_eq!(result.data, vec![0xff, 0x2a]);
+		assert_eq!(
+			&mock_ext.calls,
+			&[CallEntry {
+				to: ALICE,
+				value: 6,
+				data: vec![0xff, 0x2a],
+				allows_reentry: true,
+			}]
+		);
+	}
+
+	const CODE_CALL_TAIL_CALL: &str = r#"
+(module
+	(import "seal1" "seal_call" (func $seal_call (param i32 i32 i32 i64 i32 i32 i32 i32 i32 i32) (result i32)))
+	(import "env" "memory" (memory 1 1))
+	(func (export "call")
+		(drop
+			(call $seal_call
+				(i32.const 4) ;; Set TAIL_CALL bit
+				(i32.const 4)  ;; Pointer to "callee" address.
+				(i32.const 32)  ;; Length of "callee" address.
+				(i64.const 0)  ;; How much gas to devote for the execution. 0 = all.
+				(i32.const 36) ;; Pointer to the buffer with value to transfer
+				(i32.const 8)  ;; Length of the buffer with value to transfer.
+				(i32.const 44) ;; Pointer to input data buffer address
+				(i32.const 4)  ;; Length of input data buffer
+				(i32.const 4294967295) ;; u32 max value is the sentinel value: do not copy output
+				(i32.const 0) ;; Length is ignored in this case
+			)
+		)
+
+		;; A tail call never returns.
+		(unreachable)
+	)
+	(func (export "deploy"))
+
+	;; Destination AccountId (ALICE)
+	(data (i32.const 4)
+		"\01\01\01\01\01\01\01\01\01\01\01\01\01\01\01\01"
+		"\01\01\01\01\01\01\01\01\01\01\01\01\01\01\01\01"
+	)
+
+	;; Amount of value to transfer.
+	;; Represented by u64 (8 bytes long) in little endian.
+	(data (i32.const 36) "\06\00\00\00\00\00\00\00")
+
+	(data (i32.const 44) "\01\02\03\04")
+)
+"#;
+
+	#[test]
+	fn contract_call_tail_call() {
+		let mut mock_ext = MockExt::default();
+		let result = execute(
+			CODE_CALL_TAIL_CALL,
+			vec![],
+			&mut mock_ext,
+			&mut GasMeter::new(GAS_LIMIT),
+		).unwrap();
+
+		// The mock callee echoes its input which becomes our output.
+		assert_eq!(result, ExecReturnValue { flags: ReturnFlags::empty(), data: vec![1, 2, 3, 4] });
+		assert_eq!(mock_ext.calls.len(), 1);
+	}
+
+	const CODE_DELEGATE_CALL: &str = r#"
+(module
+	;; seal_delegate_call(
+	;;    code_hash_ptr: u32,
+	;;    code_hash_len: u32,
+	;;    gas: u64,
+	;;    input_data_ptr: u32,
+	;;    input_data_len: u32,
+	;;    output_ptr: u32,
+	;;    output_len_ptr: u32
+	;;) -> u32
+	(import "seal0" "seal_delegate_call" (func $seal_delegate_call (param i32 i32 i64 i32 i32 i32 i32) (result i32)))
+	(import "env" "memory" (memory 1 1))
+	(func (export "call")
+		(drop
+			(call $seal_delegate_call
+				(i32.const 4)  ;; Pointer to the code hash.
+				(i32.const 32)  ;; Length of the code hash.
+				(i64.const 0)  ;; How much gas to devote for the execution. 0 = all.
+				(i32.const 36) ;; Pointer to input data buffer address
+				(i32.const 4)  ;; Length of input data buffer
+				(i32.const 4294967295) ;; u32 max value is the sentinel value: do not copy output
+				(i32.const 0) ;; Length is ignored in this case
+			)
+		)
+	)
+	(func (export "deploy"))
+
+	;; Code hash of the code to execute
+	(data (i32.const 4)
+		"\11\11\11\11\11\11\11\11\11\11\11\11\11\11\11\11"
+		"\11\11\11\11\11\11\11\11\11\11\11\11\11\11\11\11"
+	)
+
+	(data (i32.const 36) "\01\02\03\04")
+)
+"#;
+
+	#[test]
+	fn contract_delegate_call() {
+		let mut mock_ext = MockExt::default();
+		let _ = execute(
+			CODE_DELEGATE_CALL,
+			vec![],
+			&mut mock_ext,
+			&mut GasMeter::new(GAS_LIMIT),
+		).unwrap();
+
+		assert_eq!(
+			&mock_ext.delegate_calls,
+			&[DelegateCallEntry {
+				code_hash: [0x11; 32].into(),
+				data: vec![1, 2, 3, 4],
 			}]
 		);
 	}
 
+	const CODE_SET_CODE_HASH: &str = r#"
+(module
+	;; seal_set_code_hash(code_hash_ptr: u32, code_hash_len: u32) -> u32
+	(import "seal0" "seal_set_code_hash" (func $seal_set_code_hash (param i32 i32) (result i32)))
+	(import "env" "memory" (memory 1 1))
+	(func $assert (param i32)
+		(block $ok
+			(br_if $ok
+				(get_local 0)
+			)
+			(unreachable)
+		)
+	)
+	(func (export "call")
+		(call $assert
+			(i32.eqz
+				(call $seal_set_code_hash
+					(i32.const 0)  ;; Pointer to the code hash.
+					(i32.const 32)  ;; Length of the code hash.
+				)
+			)
+		)
+	)
+	(func (export "deploy"))
+
+	;; New code hash
+	(data (i32.const 0)
+		"\11\11\11\11\11\11\11\11\11\11\11\11\11\11\11\11"
+		"\11\11\11\11\11\11\11\11\11\11\11\11\11\11\11\11"
+	)
+)
+"#;
+
+	#[test]
+	fn contract_set_code_hash() {
+		let mut mock_ext = MockExt::default();
+		let _ = execute(
+			CODE_SET_CODE_HASH,
+			vec![],
+			&mut mock_ext,
+			&mut GasMeter::new(GAS_LIMIT),
+		).unwrap();
+
+		assert_eq!(&mock_ext.code_hashes, &[H256::from([0x11; 32])]);
+	}
+
+	const CODE_DELEGATE_DEPENDENCIES: &str = r#"
+(module
+	;; seal_add_delegate_dependency(code_hash_ptr: u32, code_hash_len: u32)
+	(import "seal0" "seal_add_delegate_dependency"
+		(func $seal_add_delegate_dependency (param i32 i32)))
+	;; seal_remove_delegate_dependency(code_hash_ptr: u32, code_hash_len: u32)
+	(import "seal0" "seal_remove_delegate_dependency"
+		(func $seal_remove_delegate_dependency (param i32 i32)))
+	(import "env" "memory" (memory 1 1))
+	(func (export "call")
+		(call $seal_add_delegate_dependency
+			(i32.const 0)  ;; Pointer to the first code hash.
+			(i32.const 32)  ;; Length of the code hash.
+		)
+		(call $seal_add_delegate_dependency
+			(i32.const 32)  ;; Pointer to the second code hash.
+			(i32.const 32)  ;; Length of the code hash.
+		)
+		(call $seal_remove_delegate_dependency
+			(i32.const 0)  ;; Pointer to the first code hash.
+			(i32.const 32)  ;; Length of the code hash.
+		)
+	)
+	(func (export "deploy"))
+
+	;; First code hash
+	(data (i32.const 0)
+		"\11\11\11\11\11\11\11\11\11\11\11\11\11\11\11\11"
+		"\11\11\11\11\11\11\11\11\11\11\11\11\11\11\11\11"
+	)
+
+	;; Second code hash
+	(data (i32.const 32)
+		"\22\22\22\22\22\22\22\22\22\22\22\22\22\22\22\22"
+		"\22\22\22\22\22\22\22\22\22\22\22\22\22\22\22\22"
+	)
+)
+"#;
+
+	#[test]
+	fn contract_delegate_dependencies() {
+		let mut mock_ext = MockExt::default();
+		let _ = execute(
+			CODE_DELEGATE_DEPENDENCIES,
+			vec![],
+			&mut mock_ext,
+			&mut GasMeter::new(GAS_LIMIT),
+		).unwrap();
+
+		assert_eq!(&mock_ext.delegate_dependencies, &[H256::from([0x22; 32])]);
+	}
+
 	const CODE_INSTANTIATE: &str = r#"
 (module
 	;; seal_instantiate(
@@ -783,11 +1288,12 @@ mod tests {
 		).unwrap();
 
 		assert_eq!(
-			&mock_ext.transfers,
-			&[TransferEntry {
+			&mock_ext.calls,
+			&[CallEntry {
 				to: ALICE,
 				value: 6,
 				data: vec![1, 2, 3, 4],
+				allows_reentry: true,
 			}]
 		);
 	}
@@ -830,26 +1336,145 @@ mod tests {
 					(i32.const 32)		;; Pointer to the size of the buffer
 				)
 
-				;; Return value 0 means that the value is found and there were
-				;; no errors.
-				(i32.const 0)
-			)
-		)
+				;; Return value 0 means that the value is found and there were
+				;; no errors.
+				(i32.const 0)
+			)
+		)
+
+		;; Find out the size of the buffer
+		(set_local $buf_size
+			(i32.load (i32.const 32))
+		)
+
+		;; Return the contents of the buffer
+		(call $seal_return
+			(i32.const 0)
+			(i32.const 36)
+			(get_local $buf_size)
+		)
+
+		;; env:seal_return doesn't return, so this is effectively unreachable.
+		(unreachable)
+	)
+
+	(func (export "deploy"))
+)
+"#;
+
+	#[test]
+	fn get_storage_puts_data_into_buf() {
+		let mut mock_ext = MockExt::default();
+		mock_ext
+			.storage
+			.insert([0x11; 32], [0x22; 32].to_vec());
+
+		let output = execute(
+			CODE_GET_STORAGE,
+			vec![],
+			mock_ext,
+			&mut GasMeter::new(GAS_LIMIT),
+		).unwrap();
+
+		assert_eq!(output, ExecReturnValue { flags: ReturnFlags::empty(), data: [0x22; 32].to_vec() });
+	}
+
+	/// calls `seal_caller` and compares the result with the constant 42.
+	const CODE_CALLER: &str = r#"
+(module
+	(import "seal0" "seal_caller" (func $seal_caller (param i32 i32)))
+	(import "env" "memory" (memory 1 1))
+
+	;; size of our buffer is 32 bytes
+	(data (i32.const 32) "\20")
+
+	(func $assert (param i32)
+		(block $ok
+			(br_if $ok
+				(get_local 0)
+			)
+			(unreachable)
+		)
+	)
+
+	(func (export "call")
+		;; fill the buffer with the caller.
+		(call $seal_caller (i32.const 0) (i32.const 32))
+
+		;; assert len == 32
+		(call $assert
+			(i32.eq
+				(i32.load (i32.const 32))
+				(i32.const 32)
+			)
+		)
+
+		;; assert that the first 64 byte are the beginning of "ALICE"
+		(call $assert
+			(i64.eq
+				(i64.load (i32.const 0))
+				(i64.const 0x0101010101010101)
+			)
+		)
+	)
+
+	(func (export "deploy"))
+)
+"#;
+
+	#[test]
+	fn caller() {
+		let _ = execute(
+			CODE_CALLER,
+			vec![],
+			MockExt::default(),
+			&mut GasMeter::new(GAS_LIMIT),
+		).unwrap();
+	}
+
+	const CODE_CALLER_IS_ORIGIN: &str = r#"
+(module
+	(import "seal0" "seal_caller_is_origin" (func $seal_caller_is_origin (result i32)))
+	(import "seal0" "seal_return" (func $seal_return (param i32 i32 i32)))
+	(import "env" "memory" (memory 1 1))
+
+	(func (export "call")
+		(i32.store (i32.const 0) (call $seal_caller_is_origin))
+		(call $seal_return (i32.const 0) (i32.const 0) (i32.const 4))
+	)
+
+	(func (export "deploy"))
+)
+"#;
 
-		;; Find out the size of the buffer
-		(set_local $buf_size
-			(i32.load (i32.const 32))
-		)
+	#[test]
+	fn caller_is_origin() {
+		let output = execute(
+			CODE_CALLER_IS_ORIGIN,
+			vec![],
+			MockExt::default(),
+			&mut GasMeter::new(GAS_LIMIT),
+		).unwrap();
 
-		;; Return the contents of the buffer
-		(call $seal_return
-			(i32.const 0)
-			(i32.const 36)
-			(get_local $buf_size)
-		)
+		// The mock returns `false` which is encoded as `0`.
+		assert_eq!(output.data, 0u32.to_le_bytes());
+	}
 
-		;; env:seal_return doesn't return, so this is effectively unreachable.
-		(unreachable)
+	const CODE_IS_CONTRACT: &str = r#"
+(module
+	(import "seal0" "seal_is_contract" (func $seal_is_contract (param i32 i32) (result i32)))
+	(import "seal0" "seal_input" (func $seal_input (param i32 i32)))
+	(import "seal0" "seal_return" (func $seal_return (param i32 i32 i32)))
+	(import "env" "memory" (memory 1 1))
+
+	;; size of the input buffer
+	(data (i32.const 32) "\20")
+
+	(func (export "call")
+		;; [0, 32) the address to check
+		(call $seal_input (i32.const 0) (i32.const 32))
+		(i32.store (i32.const 36) (call $seal_is_contract (i32.const 0) (i32.load (i32.const 32))))
+		(call $seal_return (i32.const 0) (i32.const 36) (i32.const 4))
 	)
 
 	(func (export "deploy"))
@@ -857,31 +1482,37 @@ mod tests {
 "#;
 
 	#[test]
-	fn get_storage_puts_data_into_buf() {
-		let mut mock_ext = MockExt::default();
-		mock_ext
-			.storage
-			.insert([0x11; 32], [0x22; 32].to_vec());
-
+	fn is_contract() {
 		let output = execute(
-			CODE_GET_STORAGE,
-			vec![],
-			mock_ext,
+			CODE_IS_CONTRACT,
+			BOB.encode(),
+			MockExt::default(),
 			&mut GasMeter::new(GAS_LIMIT),
 		).unwrap();
+		assert_eq!(output.data, 1u32.to_le_bytes());
 
-		assert_eq!(output, ExecReturnValue { flags: ReturnFlags::empty(), data: [0x22; 32].to_vec() });
+		let output = execute(
+			CODE_IS_CONTRACT,
+			ALICE.encode(),
+			MockExt::default(),
+			&mut GasMeter::new(GAS_LIMIT),
+		).unwrap();
+		assert_eq!(output.data, 0u32.to_le_bytes());
 	}
 
-	/// calls `seal_caller` and compares the result with the constant 42.
-	const CODE_CALLER: &str = r#"
+	const CODE_CODE_HASH: &str = r#"
 (module
-	(import "seal0" "seal_caller" (func $seal_caller (param i32 i32)))
+	(import "seal0" "seal_code_hash" (func $seal_code_hash (param i32 i32 i32 i32) (result i32)))
+	(import "seal0" "seal_input" (func $seal_input (param i32 i32)))
+	(import "seal0" "seal_return" (func $seal_return (param i32 i32 i32)))
 	(import "env" "memory" (memory 1 1))
 
-	;; size of our buffer is 32 bytes
+	;; size of the input buffer
 	(data (i32.const 32) "\20")
 
+	;; size of the output buffer
+	(data (i32.const 36) "\20")
+
 	(func $assert (param i32)
 		(block $ok
 			(br_if $ok
@@ -892,24 +1523,21 @@ mod tests {
 	)
 
 	(func (export "call")
-		;; fill the buffer with the caller.
-		(call $seal_caller (i32.const 0) (i32.const 32))
-
-		;; assert len == 32
-		(call $assert
-			(i32.eq
-				(i32.load (i32.const 32))
-				(i32.const 32)
-			)
-		)
+		;; [0, 32) the address of the contract
+		(call $seal_input (i32.const 0) (i32.const 32))
 
-		;; assert that the first 64 byte are the beginning of "ALICE"
+		;; [40, 72) the code hash is written here
 		(call $assert
-			(i64.eq
-				(i64.load (i32.const 0))
-				(i64.const 0x0101010101010101)
+			(i32.eqz
+				(call $seal_code_hash
+					(i32.const 0) ;; Pointer to the address
+					(i32.load (i32.const 32)) ;; Length of the address
+					(i32.const 40) ;; Pointer to the output buffer
+					(i32.const 36) ;; Pointer to the output buffer length
+				)
 			)
 		)
+		(call $seal_return (i32.const 0) (i32.const 40) (i32.load (i32.const 36)))
 	)
 
 	(func (export "deploy"))
@@ -917,13 +1545,29 @@ mod tests {
 "#;
 
 	#[test]
-	fn caller() {
-		let _ = execute(
-			CODE_CALLER,
-			vec![],
+	fn code_hash() {
+		let output = execute(
+			CODE_CODE_HASH,
+			BOB.encode(),
 			MockExt::default(),
 			&mut GasMeter::new(GAS_LIMIT),
 		).unwrap();
+		assert_eq!(output.data, H256::repeat_byte(0x11).encode());
+
+		// Not a contract: `KeyNotFound` is returned and the assertion traps.
+		let result = execute(
+			CODE_CODE_HASH,
+			ALICE.encode(),
+			MockExt::default(),
+			&mut GasMeter::new(GAS_LIMIT),
+		);
+		assert_eq!(
+			result,
+			Err(ExecError {
+				error: Error::<Test>::ContractTrapped.into(),
+				origin: ErrorOrigin::Caller,
+			})
+		);
 	}
 
 	/// calls `seal_address` and compares the result with the constant 69.
@@ -1357,11 +2001,12 @@ mod tests {
 			)
 		)
 
-		;; assert that contents of the buffer is equal to the i64 value of 16.
+		;; assert that contents of the buffer is equal to the i64 value of 0
+		;; because tombstones no longer exist.
 		(call $assert
 			(i64.eq
 				(i64.load (i32.const 0))
-				(i64.const 16)
+				(i64.const 0)
 			)
 		)
 	)
@@ -1734,6 +2379,53 @@ mod tests {
 		);
 	}
 
+	const CODE_CHAIN_EXTENSION_HUGE_INPUT: &str = r#"
+(module
+	(import "seal0" "seal_call_chain_extension"
+		(func $seal_call_chain_extension (param i32 i32 i32 i32 i32) (result i32))
+	)
+	(import "env" "memory" (memory 1 1))
+
+	(func (export "deploy"))
+
+	(func (export "call")
+		(drop
+			(call $seal_call_chain_extension
+				(i32.const 2) ;; func_id: decode the input with `read_as`
+				(i32.const 0) ;; input_ptr
+				(i32.const 0xffffffff) ;; input_len: way larger than the memory
+				(i32.const 0) ;; output_ptr
+				(i32.const 0) ;; output_len_ptr
+			)
+		)
+	)
+)
+"#;
+
+	#[test]
+	fn chain_extension_huge_input_is_out_of_bounds() {
+		let mut mock_ext = MockExt::default();
+		let mut gas_meter = GasMeter::new(GAS_LIMIT);
+		let result = execute(
+			CODE_CHAIN_EXTENSION_HUGE_INPUT,
+			vec![],
+			&mut mock_ext,
+			&mut gas_meter,
+		);
+
+		assert_eq!(
+			result,
+			Err(ExecError {
+				error: Error::<Test>::OutOfBounds.into(),
+				origin: ErrorOrigin::Caller,
+			})
+		);
+
+		// The base weight of the host function is charged before the extension runs.
+		let schedule = crate::Schedule::<Test>::default();
+		assert!(GAS_LIMIT - gas_meter.gas_left() >= schedule.host_fn_weights.call_chain_extension);
+	}
+
 	const CODE_DECODE_FAILURE: &str = r#"
 (module
 	(import "seal0" "seal_terminate" (func $seal_terminate (param i32 i32)))
@@ -1769,4 +2461,220 @@ mod tests {
 		);
 	}
 
+	const CODE_DEBUG_MESSAGE: &str = r#"
+(module
+	(import "seal0" "seal_debug_message" (func $seal_debug_message (param i32 i32) (result i32)))
+	(import "env" "memory" (memory 1 1))
+
+	(data (i32.const 0) "Hello World!")
+
+	(func (export "call")
+		(call $seal_debug_message
+			(i32.const 0)	;; Pointer to the text buffer
+			(i32.const 12)	;; The size of the buffer
+		)
+		drop
+	)
+
+	(func (export "deploy"))
+)
+"#;
+
+	#[test]
+	fn debug_message_works() {
+		let mut mock_ext = MockExt::default();
+		execute(
+			CODE_DEBUG_MESSAGE,
+			vec![],
+			&mut mock_ext,
+			&mut GasMeter::new(GAS_LIMIT),
+		).unwrap();
+
+		assert_eq!(std::str::from_utf8(&mock_ext.debug_buffer).unwrap(), "Hello World!");
+	}
+
+	const CODE_DEBUG_MESSAGE_FAIL: &str = r#"
+(module
+	(import "seal0" "seal_debug_message" (func $seal_debug_message (param i32 i32) (result i32)))
+	(import "env" "memory" (memory 1 1))
+
+	(data (i32.const 0) "\fc")
+
+	(func (export "call")
+		(call $seal_debug_message
+			(i32.const 0)	;; Pointer to the text buffer
+			(i32.const 1)	;; The size of the buffer
+		)
+		drop
+	)
+
+	(func (export "deploy"))
+)
+"#;
+
+	#[test]
+	fn debug_message_invalid_utf8_fails() {
+		let mut mock_ext = MockExt::default();
+		let result = execute(
+			CODE_DEBUG_MESSAGE_FAIL,
+			vec![],
+			&mut mock_ext,
+			&mut GasMeter::new(GAS_LIMIT),
+		);
+		assert_eq!(
+			result,
+			Err(ExecError {
+				error: "Debug message is invalid UTF-8".into(),
+				origin: ErrorOrigin::Caller,
+			})
+		);
+	}
+
+	const CODE_ECDSA_RECOVER: &str = r#"
+(module
+	(import "seal0" "seal_ecdsa_recover" (func $seal_ecdsa_recover (param i32 i32 i32) (result i32)))
+	(import "seal0" "seal_return" (func $seal_return (param i32 i32 i32)))
+	(import "env" "memory" (memory 1 1))
+
+	;; [0, 65) the signature
+	(data (i32.const 0) "\01\01\01\01\01\01\01\01\01\01\01\01\01\01\01\01\01\01\01\01\01\01\01\01\01\01\01\01\01\01\01\01\01\01\01\01\01\01\01\01\01\01\01\01\01\01\01\01\01\01\01\01\01\01\01\01\01\01\01\01\01\01\01\01\01")
+	;; [65, 97) the message hash
+	(data (i32.const 65) "\02\02\02\02\02\02\02\02\02\02\02\02\02\02\02\02\02\02\02\02\02\02\02\02\02\02\02\02\02\02\02\02")
+
+	(func $assert (param i32)
+		(block $ok
+			(br_if $ok
+				(get_local 0)
+			)
+			(unreachable)
+		)
+	)
+
+	(func (export "call")
+		;; [100, 133) the recovered public key is written here
+		(call $assert
+			(i32.eqz
+				(call $seal_ecdsa_recover
+					(i32.const 0) ;; Pointer to the signature
+					(i32.const 65) ;; Pointer to the message hash
+					(i32.const 100) ;; Pointer to the output buffer
+				)
+			)
+		)
+		(call $seal_return (i32.const 0) (i32.const 100) (i32.const 33))
+	)
+
+	(func (export "deploy"))
+)
+"#;
+
+	#[test]
+	fn ecdsa_recover() {
+		let mut mock_ext = MockExt::default();
+		let output = execute(
+			CODE_ECDSA_RECOVER,
+			vec![],
+			&mut mock_ext,
+			&mut GasMeter::new(GAS_LIMIT),
+		).unwrap();
+		assert_eq!(output.data, [3; 33]);
+		assert_eq!(mock_ext.ecdsa_recover.into_inner(), [([1; 65], [2; 32])]);
+	}
+
+	const CODE_ECDSA_TO_ETH_ADDRESS: &str = r#"
+(module
+	(import "seal0" "seal_ecdsa_to_eth_address" (func $seal_ecdsa_to_eth_address (param i32 i32) (result i32)))
+	(import "seal0" "seal_input" (func $seal_input (param i32 i32)))
+	(import "seal0" "seal_return" (func $seal_return (param i32 i32 i32)))
+	(import "env" "memory" (memory 1 1))
+
+	;; size of the input buffer
+	(data (i32.const 36) "\21")
+
+	(func (export "call")
+		;; [0, 33) the compressed public key
+		(call $seal_input (i32.const 0) (i32.const 36))
+
+		;; [40, 44) the return code
+		;; [44, 64) the address is written here
+		(i32.store
+			(i32.const 40)
+			(call $seal_ecdsa_to_eth_address
+				(i32.const 0) ;; Pointer to the compressed public key
+				(i32.const 44) ;; Pointer to the output buffer
+			)
+		)
+		(call $seal_return (i32.const 0) (i32.const 40) (i32.const 24))
+	)
+
+	(func (export "deploy"))
+)
+"#;
+
+	#[test]
+	fn ecdsa_to_eth_address() {
+		let output = execute(
+			CODE_ECDSA_TO_ETH_ADDRESS,
+			[3; 33].to_vec(),
+			MockExt::default(),
+			&mut GasMeter::new(GAS_LIMIT),
+		).unwrap();
+		assert_eq!(output.data[..4], (ReturnCode::Success as u32).to_le_bytes());
+		assert_eq!(output.data[4..], [2; 20]);
+
+		let output = execute(
+			CODE_ECDSA_TO_ETH_ADDRESS,
+			[4; 33].to_vec(),
+			MockExt::default(),
+			&mut GasMeter::new(GAS_LIMIT),
+		).unwrap();
+		assert_eq!(output.data[..4], (ReturnCode::EcdsaToEthAddressFailed as u32).to_le_bytes());
+	}
+
+	const CODE_SR25519_VERIFY: &str = r#"
+(module
+	(import "seal0" "seal_sr25519_verify" (func $seal_sr25519_verify (param i32 i32 i32 i32) (result i32)))
+	(import "seal0" "seal_return" (func $seal_return (param i32 i32 i32)))
+	(import "env" "memory" (memory 1 1))
+
+	;; [0, 64) the signature
+	(data (i32.const 0) "\01\01\01\01\01\01\01\01\01\01\01\01\01\01\01\01\01\01\01\01\01\01\01\01\01\01\01\01\01\01\01\01\01\01\01\01\01\01\01\01\01\01\01\01\01\01\01\01\01\01\01\01\01\01\01\01\01\01\01\01\01\01\01\01")
+	;; [64, 96) the public key
+	(data (i32.const 64) "\02\02\02\02\02\02\02\02\02\02\02\02\02\02\02\02\02\02\02\02\02\02\02\02\02\02\02\02\02\02\02\02")
+	;; [96, 108) the message
+	(data (i32.const 96) "Hello World!")
+
+	(func (export "call")
+		;; [108, 112) the return code
+		(i32.store
+			(i32.const 108)
+			(call $seal_sr25519_verify
+				(i32.const 0) ;; Pointer to the signature
+				(i32.const 64) ;; Pointer to the public key
+				(i32.const 12) ;; Length of the message
+				(i32.const 96) ;; Pointer to the message
+			)
+		)
+		(call $seal_return (i32.const 0) (i32.const 108) (i32.const 4))
+	)
+
+	(func (export "deploy"))
+)
+"#;
+
+	#[test]
+	fn sr25519_verify() {
+		let mut mock_ext = MockExt::default();
+		let output = execute(
+			CODE_SR25519_VERIFY,
+			vec![],
+			&mut mock_ext,
+			&mut GasMeter::new(GAS_LIMIT),
+		).unwrap();
+		assert_eq!(output.data, (ReturnCode::Success as u32).to_le_bytes());
+		assert_eq!(
+			mock_ext.sr25519_verify.into_inner(),
+			[([1; 64], b"Hello World!".to_vec(), [2; 32])],
+		);
+	}
 }
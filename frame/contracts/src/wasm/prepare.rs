@@ -20,20 +20,153 @@
 
 use crate::wasm::env_def::ImportSatisfyCheck;
 use crate::wasm::PrefabWasmModule;
-use crate::{Schedule, Config};
+use crate::{Schedule, Config, chain_extension::ChainExtension};
 
-use parity_wasm::elements::{self, Internal, External, MemoryType, Type, ValueType};
+use pallet_contracts_primitives::Determinism;
+use parity_wasm::elements::{
+	self, Internal, External, MemoryType, Type, ValueType, Instruction, BulkInstruction,
+	SignExtInstruction,
+};
 use pwasm_utils;
 use sp_std::prelude::*;
 
-/// Currently, all imported functions must be located inside this module. We might support
-/// additional modules for versioning later.
+/// Imported functions that don't specify a version are located inside this module. It also
+/// hosts the gas metering function that is injected during instrumentation.
 pub const IMPORT_MODULE_FN: &str = "seal0";
 
+/// All imported functions must be located inside a module starting with this prefix. The
+/// suffix denotes the version of the function (`seal0`, `seal1`, ...).
+pub const IMPORT_MODULE_FN_PREFIX: &str = "seal";
+
 /// Imported memory must be located inside this module. The reason for that is that current
 /// compiler toolchains might not support specifying other modules than "env" for memory imports.
 pub const IMPORT_MODULE_MEMORY: &str = "env";
 
+/// Groups of instructions that are not part of the deterministic subset of the wasm MVP.
+#[derive(Clone, Copy, PartialEq, Eq)]
+pub enum InstructionSet {
+	/// Floating point instructions. Only allowed with `Determinism::Relaxed`.
+	Float,
+	/// Instructions of the sign-extension proposal. See `WasmFeatures::sign_extension`.
+	SignExtension,
+	/// The supported instructions of the bulk memory proposal. See `WasmFeatures::bulk_memory`.
+	BulkMemory,
+	/// Instructions that are never allowed.
+	Unsupported,
+}
+
+macro_rules! define_classify {
+	( $( $set:ident: $prefix:literal, $suffix:literal { $( $instr:pat => $name:literal, )* } )* ) => {
+		/// Returns the set a non-MVP or indeterministic instruction belongs to.
+		///
+		/// The returned error message names the instruction and is used when the set is not
+		/// allowed. Deterministic MVP instructions return `None`.
+		pub fn classify(instruction: &Instruction) -> Option<(InstructionSet, &'static str)> {
+			use parity_wasm::elements::Instruction::*;
+			match instruction {
+				$( $(
+					$instr => Some((
+						InstructionSet::$set,
+						concat!($prefix, " `", $name, "` ", $suffix),
+					)),
+				)* )*
+				_ => None,
+			}
+		}
+	}
+}
+
+define_classify! {
+	Float: "floating point instruction", "is forbidden in deterministic code" {
+		F32Load(..) => "f32.load",
+		F64Load(..) => "f64.load",
+		F32Store(..) => "f32.store",
+		F64Store(..) => "f64.store",
+		F32Const(_) => "f32.const",
+		F64Const(_) => "f64.const",
+		F32Eq => "f32.eq",
+		F32Ne => "f32.ne",
+		F32Lt => "f32.lt",
+		F32Gt => "f32.gt",
+		F32Le => "f32.le",
+		F32Ge => "f32.ge",
+		F64Eq => "f64.eq",
+		F64Ne => "f64.ne",
+		F64Lt => "f64.lt",
+		F64Gt => "f64.gt",
+		F64Le => "f64.le",
+		F64Ge => "f64.ge",
+		F32Abs => "f32.abs",
+		F32Neg => "f32.neg",
+		F32Ceil => "f32.ceil",
+		F32Floor => "f32.floor",
+		F32Trunc => "f32.trunc",
+		F32Nearest => "f32.nearest",
+		F32Sqrt => "f32.sqrt",
+		F32Add => "f32.add",
+		F32Sub => "f32.sub",
+		F32Mul => "f32.mul",
+		F32Div => "f32.div",
+		F32Min => "f32.min",
+		F32Max => "f32.max",
+		F32Copysign => "f32.copysign",
+		F64Abs => "f64.abs",
+		F64Neg => "f64.neg",
+		F64Ceil => "f64.ceil",
+		F64Floor => "f64.floor",
+		F64Trunc => "f64.trunc",
+		F64Nearest => "f64.nearest",
+		F64Sqrt => "f64.sqrt",
+		F64Add => "f64.add",
+		F64Sub => "f64.sub",
+		F64Mul => "f64.mul",
+		F64Div => "f64.div",
+		F64Min => "f64.min",
+		F64Max => "f64.max",
+		F64Copysign => "f64.copysign",
+		I32TruncSF32 => "i32.trunc_f32_s",
+		I32TruncUF32 => "i32.trunc_f32_u",
+		I32TruncSF64 => "i32.trunc_f64_s",
+		I32TruncUF64 => "i32.trunc_f64_u",
+		I64TruncSF32 => "i64.trunc_f32_s",
+		I64TruncUF32 => "i64.trunc_f32_u",
+		I64TruncSF64 => "i64.trunc_f64_s",
+		I64TruncUF64 => "i64.trunc_f64_u",
+		F32ConvertSI32 => "f32.convert_i32_s",
+		F32ConvertUI32 => "f32.convert_i32_u",
+		F32ConvertSI64 => "f32.convert_i64_s",
+		F32ConvertUI64 => "f32.convert_i64_u",
+		F32DemoteF64 => "f32.demote_f64",
+		F64ConvertSI32 => "f64.convert_i32_s",
+		F64ConvertUI32 => "f64.convert_i32_u",
+		F64ConvertSI64 => "f64.convert_i64_s",
+		F64ConvertUI64 => "f64.convert_i64_u",
+		F64PromoteF32 => "f64.promote_f32",
+		I32ReinterpretF32 => "i32.reinterpret_f32",
+		I64ReinterpretF64 => "i64.reinterpret_f64",
+		F32ReinterpretI32 => "f32.reinterpret_i32",
+		F64ReinterpretI64 => "f64.reinterpret_i64",
+	}
+	SignExtension: "sign extension instruction", "is not enabled in the schedule" {
+		SignExt(SignExtInstruction::I32Extend8S) => "i32.extend8_s",
+		SignExt(SignExtInstruction::I32Extend16S) => "i32.extend16_s",
+		SignExt(SignExtInstruction::I64Extend8S) => "i64.extend8_s",
+		SignExt(SignExtInstruction::I64Extend16S) => "i64.extend16_s",
+		SignExt(SignExtInstruction::I64Extend32S) => "i64.extend32_s",
+	}
+	BulkMemory: "bulk memory instruction", "is not enabled in the schedule" {
+		Bulk(BulkInstruction::MemoryCopy) => "memory.copy",
+		Bulk(BulkInstruction::MemoryFill) => "memory.fill",
+	}
+	Unsupported: "instruction", "is not supported" {
+		Bulk(BulkInstruction::MemoryInit(_)) => "memory.init",
+		Bulk(BulkInstruction::MemoryDrop(_)) => "data.drop",
+		Bulk(BulkInstruction::TableInit(_)) => "table.init",
+		Bulk(BulkInstruction::TableDrop(_)) => "elem.drop",
+		Bulk(BulkInstruction::TableCopy) => "table.copy",
+	}
+}
+
 struct ContractModule<'a, T: Config> {
 	/// A deserialized module. The module is valid (this is Guaranteed by `new` method).
 	module: elements::Module,
@@ -126,6 +259,35 @@ impl<'a, T: Config> ContractModule<'a, T> {
 		Ok(())
 	}
 
+	/// Ensures that every instruction is allowed by the given `determinism` and the wasm
+	/// features enabled in the schedule.
+	///
+	/// The error names the first offending instruction.
+	fn ensure_instructions_allowed(&self, determinism: Determinism) -> Result<(), &'static str> {
+		let code_section = if let Some(code_section) = self.module.code_section() {
+			code_section
+		} else {
+			return Ok(());
+		};
+		let features = &self.schedule.wasm_features;
+		for instr in code_section.bodies().iter().flat_map(|body| body.code().elements()) {
+			let (set, err) = match classify(instr) {
+				Some(class) => class,
+				None => continue,
+			};
+			let allowed = match set {
+				InstructionSet::Float => determinism == Determinism::Relaxed,
+				InstructionSet::SignExtension => features.sign_extension,
+				InstructionSet::BulkMemory => features.bulk_memory,
+				InstructionSet::Unsupported => false,
+			};
+			if !allowed {
+				return Err(err);
+			}
+		}
+		Ok(())
+	}
+
 	/// Ensures that no floating point types are in use.
 	fn ensure_no_floating_types(&self) -> Result<(), &'static str> {
 		if let Some(global_section) = self.module.global_section() {
@@ -324,7 +486,7 @@ impl<'a, T: Config> ContractModule<'a, T> {
 				&External::Table(_) => return Err("Cannot import tables"),
 				&External::Global(_) => return Err("Cannot import globals"),
 				&External::Function(ref type_idx) => {
-					if import.module() != IMPORT_MODULE_FN {
+					if !import.module().starts_with(IMPORT_MODULE_FN_PREFIX) {
 						return Err("Invalid module for imported function");
 					}
 					type_idx
@@ -354,8 +516,14 @@ impl<'a, T: Config> ContractModule<'a, T> {
 				return Err("module imports `seal_println` but debug features disabled");
 			}
 
+			if !T::ChainExtension::enabled() &&
+				import.field().as_bytes() == b"seal_call_chain_extension"
+			{
+				return Err("module uses chain extensions but chain extensions are disabled");
+			}
+
 			if import_fn_banlist.iter().any(|f| import.field().as_bytes() == *f)
-				|| !C::can_satisfy(import.field().as_bytes(), func_ty)
+				|| !C::can_satisfy(import.module().as_bytes(), import.field().as_bytes(), func_ty)
 			{
 				return Err("module imports a non-existent function");
 			}
@@ -408,18 +576,24 @@ fn get_memory_limits<T: Config>(module: Option<&MemoryType>, schedule: &Schedule
 /// - the module doesn't define an internal memory instance,
 /// - imported memory (if any) doesn't reserve more memory than permitted by the `schedule`,
 /// - all imported functions from the external environment matches defined by `env` module,
+/// - only instructions allowed by `determinism` and the wasm features of the `schedule` are
+///   used.
 ///
 /// The preprocessing includes injecting code for gas metering and metering the height of stack.
 pub fn prepare_contract<C: ImportSatisfyCheck, T: Config>(
 	original_code: &[u8],
 	schedule: &Schedule<T>,
+	determinism: Determinism,
 ) -> Result<PrefabWasmModule, &'static str> {
 	let mut contract_module = ContractModule::new(original_code, schedule)?;
 	contract_module.scan_exports()?;
 	contract_module.ensure_no_internal_memory()?;
 	contract_module.ensure_table_size_limit(schedule.limits.table_size)?;
 	contract_module.ensure_global_variable_limit(schedule.limits.globals)?;
-	contract_module.ensure_no_floating_types()?;
+	contract_module.ensure_instructions_allowed(determinism)?;
+	if determinism == Determinism::Enforced {
+		contract_module.ensure_no_floating_types()?;
+	}
 	contract_module.ensure_parameter_limit(schedule.limits.parameters)?;
 	contract_module.ensure_br_table_size_limit(schedule.limits.br_table_size)?;
 
@@ -438,7 +612,7 @@ pub fn prepare_contract<C: ImportSatisfyCheck, T: Config>(
 		schedule_version: schedule.version,
 		initial: memory_limits.0,
 		maximum: memory_limits.1,
-		_reserved: None,
+		determinism,
 		code: contract_module.into_wasm_code()?,
 	})
 }
@@ -452,12 +626,13 @@ pub fn prepare_contract<C: ImportSatisfyCheck, T: Config>(
 #[cfg(feature = "runtime-benchmarks")]
 pub mod benchmarking {
 	use super::{
-		Config, ContractModule, PrefabWasmModule, ImportSatisfyCheck, Schedule, get_memory_limits
+		Config, ContractModule, PrefabWasmModule, ImportSatisfyCheck, Schedule, Determinism,
+		get_memory_limits,
 	};
 	use parity_wasm::elements::FunctionType;
 
 	impl ImportSatisfyCheck for () {
-		fn can_satisfy(_name: &[u8], _func_type: &FunctionType) -> bool {
+		fn can_satisfy(_module: &[u8], _name: &[u8], _func_type: &FunctionType) -> bool {
 			true
 		}
 	}
@@ -472,7 +647,7 @@ pub mod benchmarking {
 			schedule_version: schedule.version,
 			initial: memory_limits.0,
 			maximum: memory_limits.1,
-			_reserved: None,
+			determinism: Determinism::Enforced,
 			code: contract_module.into_wasm_code()?,
 		})
 	}
@@ -501,6 +676,9 @@ mod tests {
 
 		nop(_ctx, _unused: u64) => { unreachable!(); },
 
+		// a newer version of `nop` with a different signature.
+		[seal1] nop(_ctx, _unused: i32) => { unreachable!(); },
+
 		seal_println(_ctx, _ptr: u32, _len: u32) => { unreachable!(); },
 	);
 
@@ -520,7 +698,9 @@ mod tests {
 					},
 					.. Default::default()
 				};
-				let r = prepare_contract::<TestEnv, crate::tests::Test>(wasm.as_ref(), &schedule);
+				let r = prepare_contract::<TestEnv, crate::tests::Test>(
+					wasm.as_ref(), &schedule, Determinism::Enforced,
+				);
 				assert_matches!(r, $($expected)*);
 			}
 		};
@@ -539,9 +719,87 @@ mod tests {
 			)
 			(func (export "deploy"))
 		)"#,
-		Err("gas instrumentation failed")
+		Err("floating point instruction `f32.const` is forbidden in deterministic code")
 	);
 
+	mod instruction_sets {
+		use super::*;
+
+		fn prepare(
+			wat: &str,
+			schedule: &Schedule<crate::tests::Test>,
+			determinism: Determinism,
+		) -> Result<PrefabWasmModule, &'static str> {
+			let wasm = wat::parse_str(wat).unwrap();
+			prepare_contract::<TestEnv, crate::tests::Test>(wasm.as_ref(), schedule, determinism)
+		}
+
+		const FLOATS: &str = r#"
+			(module
+				(func (export "call")
+					(local f64)
+					(drop
+						(f32.add
+							(f32.const 0)
+							(f32.const 1)
+						)
+					)
+				)
+				(func (export "deploy"))
+			)"#;
+
+		const SIGN_EXT: &str = r#"
+			(module
+				(func (export "call")
+					(drop (i32.extend8_s (i32.const 255)))
+				)
+				(func (export "deploy"))
+			)"#;
+
+		const BULK_MEMORY: &str = r#"
+			(module
+				(import "env" "memory" (memory 1 1))
+				(func (export "call")
+					(memory.fill (i32.const 0) (i32.const 0) (i32.const 16))
+					(memory.copy (i32.const 16) (i32.const 0) (i32.const 16))
+				)
+				(func (export "deploy"))
+			)"#;
+
+		#[test]
+		fn floats_allowed_when_relaxed() {
+			let schedule = Schedule::default();
+			assert_matches!(
+				prepare(FLOATS, &schedule, Determinism::Enforced),
+				Err("floating point instruction `f32.const` is forbidden in deterministic code")
+			);
+			let module = prepare(FLOATS, &schedule, Determinism::Relaxed).unwrap();
+			assert_eq!(module.determinism, Determinism::Relaxed);
+		}
+
+		#[test]
+		fn sign_extension_requires_feature() {
+			let mut schedule = Schedule::default();
+			assert_matches!(
+				prepare(SIGN_EXT, &schedule, Determinism::Enforced),
+				Err("sign extension instruction `i32.extend8_s` is not enabled in the schedule")
+			);
+			schedule.wasm_features.sign_extension = true;
+			assert_matches!(prepare(SIGN_EXT, &schedule, Determinism::Enforced), Ok(_));
+		}
+
+		#[test]
+		fn bulk_memory_requires_feature() {
+			let mut schedule = Schedule::default();
+			assert_matches!(
+				prepare(BULK_MEMORY, &schedule, Determinism::Enforced),
+				Err("bulk memory instruction `memory.fill` is not enabled in the schedule")
+			);
+			schedule.wasm_features.bulk_memory = true;
+			assert_matches!(prepare(BULK_MEMORY, &schedule, Determinism::Enforced), Ok(_));
+		}
+	}
+
 	mod functions {
 		use super::*;
 
@@ -814,6 +1072,44 @@ mod tests {
 			Ok(_)
 		);
 
+		prepare_test!(can_import_legit_function_from_newer_module,
+			r#"
+			(module
+				(import "seal1" "nop" (func (param i32)))
+
+				(func (export "call"))
+				(func (export "deploy"))
+			)
+			"#,
+			Ok(_)
+		);
+
+		// every version of a function has its own signature.
+		prepare_test!(signature_is_checked_per_module,
+			r#"
+			(module
+				(import "seal1" "nop" (func (param i64)))
+
+				(func (export "call"))
+				(func (export "deploy"))
+			)
+			"#,
+			Err("module imports a non-existent function")
+		);
+
+		// functions only exist in the modules they were defined for.
+		prepare_test!(function_not_in_unknown_version,
+			r#"
+			(module
+				(import "seal2" "nop" (func (param i64)))
+
+				(func (export "call"))
+				(func (export "deploy"))
+			)
+			"#,
+			Err("module imports a non-existent function")
+		);
+
 		// even though gas is defined the contract can't import it since
 		// it is an implementation defined.
 		prepare_test!(can_not_import_gas_function,
@@ -931,7 +1227,9 @@ mod tests {
 			).unwrap();
 			let mut schedule = Schedule::default();
 			schedule.enable_println = true;
-			let r = prepare_contract::<TestEnv, crate::tests::Test>(wasm.as_ref(), &schedule);
+			let r = prepare_contract::<TestEnv, crate::tests::Test>(
+				wasm.as_ref(), &schedule, Determinism::Enforced,
+			);
 			assert_matches!(r, Ok(_));
 		}
 	}
@@ -28,7 +28,7 @@ use frame_system;
 use frame_support::dispatch::DispatchError;
 use sp_std::prelude::*;
 use codec::{Decode, Encode};
-use sp_runtime::traits::SaturatedConversion;
+use sp_runtime::traits::{Bounded, SaturatedConversion, Zero};
 use sp_core::crypto::UncheckedFrom;
 use sp_io::hashing::{
 	keccak_256,
@@ -36,7 +36,7 @@ use sp_io::hashing::{
 	blake2_128,
 	sha2_256,
 };
-use pallet_contracts_primitives::{ExecResult, ExecReturnValue, ReturnFlags, ExecError};
+use pallet_contracts_primitives::{CallFlags, ExecResult, ExecReturnValue, ReturnFlags, ExecError};
 
 /// Every error that can be returned to a contract when it calls any of the host functions.
 #[repr(u32)]
@@ -62,9 +62,17 @@ pub enum ReturnCode {
 	NewContractNotFunded = 6,
 	/// No code could be found at the supplied code hash.
 	CodeNotFound = 7,
-	/// The contract that was called is either no contract at all (a plain account)
-	/// or is a tombstone.
+	/// The contract that was called is no contract (a plain account).
 	NotCallable = 8,
+	/// The call to `seal_debug_message` had no effect because debug message
+	/// recording was disabled.
+	LoggingDisabled = 9,
+	/// ECDSA public key recovery failed. Most probably wrong recovery id or signature.
+	EcdsaRecoverFailed = 10,
+	/// The supplied bytes are not a valid compressed ECDSA public key.
+	EcdsaToEthAddressFailed = 11,
+	/// sr25519 signature verification failed.
+	Sr25519VerifyFailed = 12,
 }
 
 impl ConvertibleToWasm for ReturnCode {
@@ -113,8 +121,6 @@ enum TrapReason {
 	/// Signals that a trap was generated in response to a successful call to the
 	/// `seal_terminate` host function.
 	Termination,
-	/// Signals that a trap was generated because of a successful restoration.
-	Restoration,
 }
 
 #[cfg_attr(test, derive(Debug, PartialEq, Eq))]
@@ -125,6 +131,12 @@ pub enum RuntimeToken {
 	MeteringBlock(u32),
 	/// Weight of calling `seal_caller`.
 	Caller,
+	/// Weight of calling `seal_caller_is_origin`.
+	CallerIsOrigin,
+	/// Weight of calling `seal_is_contract`.
+	IsContract,
+	/// Weight of calling `seal_code_hash`.
+	CodeHash,
 	/// Weight of calling `seal_address`.
 	Address,
 	/// Weight of calling `seal_gas_left`.
@@ -151,10 +163,9 @@ pub enum RuntimeToken {
 	InputCopyOut(u32),
 	/// Weight of calling `seal_return` for the given output size.
 	Return(u32),
-	/// Weight of calling `seal_terminate`.
-	Terminate,
-	/// Weight of calling `seal_restore_to` per number of supplied delta entries.
-	RestoreTo(u32),
+	/// Weight of calling `seal_terminate` for a contract with the given number of delegate
+	/// dependencies.
+	Terminate(u32),
 	/// Weight of calling `seal_random`. It includes the weight for copying the subject.
 	Random,
 	/// Weight of calling `seal_reposit_event` with the given number of topics and event size.
@@ -177,6 +188,14 @@ pub enum RuntimeToken {
 	CallSurchargeTransfer,
 	/// Weight of output received through `seal_call` for the given size.
 	CallCopyOut(u32),
+	/// Weight of calling `seal_delegate_call` for the given input size.
+	DelegateCallBase(u32),
+	/// Weight of calling `seal_set_code_hash`.
+	SetCodeHash,
+	/// Weight of calling `seal_add_delegate_dependency`.
+	AddDelegateDependency,
+	/// Weight of calling `seal_remove_delegate_dependency`.
+	RemoveDelegateDependency,
 	/// Weight of calling `seal_instantiate` for the given input and salt without output weight.
 	/// This includes the transfer as an instantiate without a value will always be below
 	/// the existential deposit and is disregarded as corner case.
@@ -191,6 +210,18 @@ pub enum RuntimeToken {
 	HashBlake256(u32),
 	/// Weight of calling `seal_hash_blake2_128` for the given input size.
 	HashBlake128(u32),
+	/// Weight charged by a chain extension through its `Environment`.
+	ChainExtension(u64),
+	/// Weight of calling `seal_debug_message`.
+	DebugMessage,
+	/// Weight of calling `seal_call_chain_extension`.
+	CallChainExtension,
+	/// Weight of calling `seal_ecdsa_recover`.
+	EcdsaRecovery,
+	/// Weight of calling `seal_ecdsa_to_eth_address`.
+	EcdsaToEthAddress,
+	/// Weight of calling `seal_sr25519_verify` for the given message size.
+	Sr25519Verify(u32),
 }
 
 impl<T: Config> Token<T> for RuntimeToken
@@ -204,6 +235,9 @@ where
 		match *self {
 			MeteringBlock(amount) => s.gas.saturating_add(amount.into()),
 			Caller => s.caller,
+			CallerIsOrigin => s.caller_is_origin,
+			IsContract => s.is_contract,
+			CodeHash => s.code_hash,
 			Address => s.address,
 			GasLeft => s.gas_left,
 			Balance => s.balance,
@@ -218,9 +252,8 @@ where
 			InputCopyOut(len) => s.input_per_byte.saturating_mul(len.into()),
 			Return(len) => s.r#return
 				.saturating_add(s.return_per_byte.saturating_mul(len.into())),
-			Terminate => s.terminate,
-			RestoreTo(delta) => s.restore_to
-				.saturating_add(s.restore_to_per_delta.saturating_mul(delta.into())),
+			Terminate(dependencies) => s.terminate
+				.saturating_add(s.remove_delegate_dependency.saturating_mul(dependencies.into())),
 			Random => s.random,
 			DepositEvent{num_topic, len} => s.deposit_event
 				.saturating_add(s.deposit_event_per_topic.saturating_mul(num_topic.into()))
@@ -236,6 +269,11 @@ where
 				.saturating_add(s.call_per_input_byte.saturating_mul(len.into())),
 			CallSurchargeTransfer => s.call_transfer_surcharge,
 			CallCopyOut(len) => s.call_per_output_byte.saturating_mul(len.into()),
+			DelegateCallBase(len) => s.delegate_call
+				.saturating_add(s.call_per_input_byte.saturating_mul(len.into())),
+			SetCodeHash => s.set_code_hash,
+			AddDelegateDependency => s.add_delegate_dependency,
+			RemoveDelegateDependency => s.remove_delegate_dependency,
 			InstantiateBase{input_data_len, salt_len} => s.instantiate
 				.saturating_add(s.instantiate_per_input_byte.saturating_mul(input_data_len.into()))
 				.saturating_add(s.instantiate_per_salt_byte.saturating_mul(salt_len.into())),
@@ -249,6 +287,13 @@ where
 				.saturating_add(s.hash_blake2_256_per_byte.saturating_mul(len.into())),
 			HashBlake128(len) => s.hash_blake2_128
 				.saturating_add(s.hash_blake2_128_per_byte.saturating_mul(len.into())),
+			ChainExtension(amount) => amount,
+			DebugMessage => s.debug_message,
+			CallChainExtension => s.call_chain_extension,
+			EcdsaRecovery => s.ecdsa_recover,
+			EcdsaToEthAddress => s.ecdsa_to_eth_address,
+			Sr25519Verify(len) => s.sr25519_verify
+				.saturating_add(s.sr25519_verify_per_byte.saturating_mul(len.into())),
 		}
 	}
 }
@@ -339,12 +384,6 @@ where
 						data: Vec::new(),
 					})
 				},
-				TrapReason::Restoration => {
-					Ok(ExecReturnValue {
-						flags: ReturnFlags::empty(),
-						data: Vec::new(),
-					})
-				},
 				TrapReason::SupervisorError(error) => Err(error)?,
 			}
 		}
@@ -369,35 +408,56 @@ where
 		}
 	}
 
+	/// Get a mutable reference to the inner `Ext`.
+	///
+	/// This is mainly for the chain extension to have access to the environment the
+	/// contract is executing in.
+	pub(crate) fn ext(&mut self) -> &mut E {
+		self.ext
+	}
+
+	/// Returns the gas that is left for the currently executing contract.
+	pub(crate) fn gas_left(&self) -> Gas {
+		self.gas_meter.gas_left()
+	}
+
 	/// Charge the gas meter with the specified token.
 	///
-	/// Returns `Err(HostError)` if there is not enough gas.
-	fn charge_gas<Tok>(&mut self, token: Tok) -> Result<(), sp_sandbox::HostError>
+	/// Returns `Err` if there is not enough gas. Unlike `charge_gas` the error is not
+	/// stored as trap reason but handed to the caller.
+	pub(crate) fn charge<Tok>(&mut self, token: Tok) -> Result<(), DispatchError>
 	where
 		Tok: Token<E::T, Metadata=HostFnWeights<E::T>>,
 	{
 		match self.gas_meter.charge(&self.schedule.host_fn_weights, token) {
 			GasMeterResult::Proceed => Ok(()),
-			GasMeterResult::OutOfGas =>  {
-				self.trap_reason = Some(
-					TrapReason::SupervisorError(Error::<E::T>::OutOfGas.into())
-				);
-				Err(sp_sandbox::HostError)
-			},
+			GasMeterResult::OutOfGas => Err(Error::<E::T>::OutOfGas.into()),
 		}
 	}
 
+	/// Charge the gas meter with the specified token.
+	///
+	/// Returns `Err(HostError)` if there is not enough gas.
+	fn charge_gas<Tok>(&mut self, token: Tok) -> Result<(), sp_sandbox::HostError>
+	where
+		Tok: Token<E::T, Metadata=HostFnWeights<E::T>>,
+	{
+		self.charge(token).map_err(|err| self.store_err(err))
+	}
+
 	/// Read designated chunk from the sandbox memory.
 	///
 	/// Returns `Err` if one of the following conditions occurs:
 	///
 	/// - requested buffer is not within the bounds of the sandbox memory.
-	fn read_sandbox_memory(&mut self, ptr: u32, len: u32)
-	-> Result<Vec<u8>, sp_sandbox::HostError>
-	{
+	pub(crate) fn read_memory(&self, ptr: u32, len: u32) -> Result<Vec<u8>, DispatchError> {
+		// The memory of a contract can't be larger than this, so a larger read is out of bounds
+		// and we refuse it before allocating the buffer.
+		if len > self.schedule.limits.max_memory_size() {
+			return Err(Error::<E::T>::OutOfBounds.into());
+		}
 		let mut buf = vec![0u8; len as usize];
-		self.memory.get(ptr, buf.as_mut_slice())
-			.map_err(|_| self.store_err(Error::<E::T>::OutOfBounds))?;
+		self.read_memory_into_buf(ptr, buf.as_mut_slice())?;
 		Ok(buf)
 	}
 
@@ -406,10 +466,28 @@ where
 	/// Returns `Err` if one of the following conditions occurs:
 	///
 	/// - requested buffer is not within the bounds of the sandbox memory.
+	pub(crate) fn read_memory_into_buf(&self, ptr: u32, buf: &mut [u8])
+	-> Result<(), DispatchError>
+	{
+		self.memory.get(ptr, buf).map_err(|_| Error::<E::T>::OutOfBounds.into())
+	}
+
+	/// Read designated chunk from the sandbox memory.
+	///
+	/// Same as `read_memory` but stores the error as trap reason.
+	fn read_sandbox_memory(&mut self, ptr: u32, len: u32)
+	-> Result<Vec<u8>, sp_sandbox::HostError>
+	{
+		self.read_memory(ptr, len).map_err(|err| self.store_err(err))
+	}
+
+	/// Read designated chunk from the sandbox memory into the supplied buffer.
+	///
+	/// Same as `read_memory_into_buf` but stores the error as trap reason.
 	fn read_sandbox_memory_into_buf(&mut self, ptr: u32, buf: &mut [u8])
 	-> Result<(), sp_sandbox::HostError>
 	{
-		self.memory.get(ptr, buf).map_err(|_| self.store_err(Error::<E::T>::OutOfBounds))
+		self.read_memory_into_buf(ptr, buf).map_err(|err| self.store_err(err))
 	}
 
 	/// Read designated chunk from the sandbox memory and attempt to decode into the specified type.
@@ -453,38 +531,55 @@ where
 	///
 	/// In addition to the error conditions of `write_sandbox_memory` this functions returns
 	/// `Err` if the size of the buffer located at `out_ptr` is too small to fit `buf`.
-	fn write_sandbox_output(
+	pub(crate) fn write_output(
 		&mut self,
 		out_ptr: u32,
 		out_len_ptr: u32,
 		buf: &[u8],
 		allow_skip: bool,
 		create_token: impl FnOnce(u32) -> Option<RuntimeToken>,
-	) -> Result<(), sp_sandbox::HostError>
+	) -> Result<(), DispatchError>
 	{
 		if allow_skip && out_ptr == u32::max_value() {
 			return Ok(());
 		}
 
 		let buf_len = buf.len() as u32;
-		let len: u32 = self.read_sandbox_memory_as(out_len_ptr, 4)?;
+		let mut len = [0u8; 4];
+		self.read_memory_into_buf(out_len_ptr, &mut len)?;
 
-		if len < buf_len {
-			Err(self.store_err(Error::<E::T>::OutputBufferTooSmall))?
+		if u32::from_le_bytes(len) < buf_len {
+			Err(Error::<E::T>::OutputBufferTooSmall)?
 		}
 
 		if let Some(token) = create_token(buf_len) {
-			self.charge_gas(token)?;
+			self.charge(token)?;
 		}
 
 		self.memory.set(out_ptr, buf).and_then(|_| {
 			self.memory.set(out_len_ptr, &buf_len.encode())
 		})
-		.map_err(|_| self.store_err(Error::<E::T>::OutOfBounds))?;
+		.map_err(|_| Error::<E::T>::OutOfBounds)?;
 
 		Ok(())
 	}
 
+	/// Write the given buffer and its length to the designated locations in sandbox memory.
+	///
+	/// Same as `write_output` but stores the error as trap reason.
+	fn write_sandbox_output(
+		&mut self,
+		out_ptr: u32,
+		out_len_ptr: u32,
+		buf: &[u8],
+		allow_skip: bool,
+		create_token: impl FnOnce(u32) -> Option<RuntimeToken>,
+	) -> Result<(), sp_sandbox::HostError>
+	{
+		self.write_output(out_ptr, out_len_ptr, buf, allow_skip, create_token)
+			.map_err(|err| self.store_err(err))
+	}
+
 	/// Computes the given hash function on the supplied input.
 	///
 	/// Reads from the sandboxed input buffer into an intermediate buffer.
@@ -528,6 +623,84 @@ where
 		sp_sandbox::HostError
 	}
 
+	/// Implements both versions of `seal_call`.
+	///
+	/// The older version behaves as if `ALLOW_REENTRY` was the only flag set.
+	fn call(
+		&mut self,
+		flags: CallFlags,
+		callee_ptr: u32,
+		callee_len: u32,
+		gas: u64,
+		value_ptr: u32,
+		value_len: u32,
+		input_data_ptr: u32,
+		input_data_len: u32,
+		output_ptr: u32,
+		output_len_ptr: u32,
+	) -> Result<ReturnCode, sp_sandbox::HostError> {
+		let input_data = if flags.contains(CallFlags::CLONE_INPUT) {
+			let input = self.input_data.clone()
+				.ok_or_else(|| self.store_err(Error::<E::T>::InputForwarded))?;
+			self.charge_gas(RuntimeToken::CallBase(input.len() as u32))?;
+			input
+		} else if flags.contains(CallFlags::FORWARD_INPUT) {
+			self.charge_gas(RuntimeToken::CallBase(0))?;
+			self.input_data.take().ok_or_else(|| self.store_err(Error::<E::T>::InputForwarded))?
+		} else {
+			self.charge_gas(RuntimeToken::CallBase(input_data_len))?;
+			self.read_sandbox_memory(input_data_ptr, input_data_len)?
+		};
+		let callee: <<E as Ext>::T as frame_system::Config>::AccountId =
+			self.read_sandbox_memory_as(callee_ptr, callee_len)?;
+		let value: BalanceOf<<E as Ext>::T> = self.read_sandbox_memory_as(value_ptr, value_len)?;
+
+		if value > 0u32.into() {
+			self.charge_gas(RuntimeToken::CallSurchargeTransfer)?;
+		}
+
+		let nested_gas_limit = if gas == 0 {
+			self.gas_meter.gas_left()
+		} else {
+			gas.saturated_into()
+		};
+		let ext = &mut self.ext;
+		let call_outcome = self.gas_meter.with_nested(nested_gas_limit, |nested_meter| {
+			match nested_meter {
+				Some(nested_meter) => {
+					ext.call(
+						&callee,
+						value,
+						nested_meter,
+						input_data,
+						flags.contains(CallFlags::ALLOW_REENTRY),
+					)
+				}
+				// there is not enough gas to allocate for the nested call.
+				None => Err(Error::<<E as Ext>::T>::OutOfGas.into()),
+			}
+		});
+
+		// A tail call returns the output of the callee as our own output. Nothing after
+		// the call is executed.
+		if flags.contains(CallFlags::TAIL_CALL) {
+			if let Ok(output) = call_outcome {
+				self.trap_reason = Some(TrapReason::Return(ReturnData {
+					flags: output.flags.bits(),
+					data: output.data,
+				}));
+				return Err(sp_sandbox::HostError);
+			}
+		}
+
+		if let Ok(output) = &call_outcome {
+			self.write_sandbox_output(output_ptr, output_len_ptr, &output.data, true, |len| {
+				Some(RuntimeToken::CallCopyOut(len))
+			})?;
+		}
+		self.map_exec_result(call_outcome)
+	}
+
 	/// Used by Runtime API that calls into other contracts.
 	///
 	/// Those need to transform the the `ExecResult` returned from the execution into
@@ -718,6 +891,36 @@ define_env!(Env, <E: Ext>,
 		ctx.map_dispatch_result(result)
 	},
 
+	// Make a call to another contract.
+	//
+	// This is equivalent to calling the newer version of this function with
+	// `flags` set to `ALLOW_REENTRY`. See the newer version for documentation.
+	seal_call(
+		ctx,
+		callee_ptr: u32,
+		callee_len: u32,
+		gas: u64,
+		value_ptr: u32,
+		value_len: u32,
+		input_data_ptr: u32,
+		input_data_len: u32,
+		output_ptr: u32,
+		output_len_ptr: u32
+	) -> ReturnCode => {
+		ctx.call(
+			CallFlags::ALLOW_REENTRY,
+			callee_ptr,
+			callee_len,
+			gas,
+			value_ptr,
+			value_len,
+			input_data_ptr,
+			input_data_len,
+			output_ptr,
+			output_len_ptr,
+		)
+	},
+
 	// Make a call to another contract.
 	//
 	// The callees output buffer is copied to `output_ptr` and its length to `output_len_ptr`.
@@ -726,6 +929,7 @@ define_env!(Env, <E: Ext>,
 	//
 	// # Parameters
 	//
+	// - flags: See [`CallFlags`] for a documenation of the supported flags.
 	// - callee_ptr: a pointer to the address of the callee contract.
 	//   Should be decodable as an `T::AccountId`. Traps otherwise.
 	// - callee_len: length of the address buffer.
@@ -734,6 +938,7 @@ define_env!(Env, <E: Ext>,
 	//   Should be decodable as a `T::Balance`. Traps otherwise.
 	// - value_len: length of the value buffer.
 	// - input_data_ptr: a pointer to a buffer to be used as input data to the callee.
+	//   Ignored when `FORWARD_INPUT` or `CLONE_INPUT` is set.
 	// - input_data_len: length of the input data buffer.
 	// - output_ptr: a pointer where the output buffer is copied to.
 	// - output_len_ptr: in-out pointer to where the length of the buffer is read from
@@ -749,8 +954,15 @@ define_env!(Env, <E: Ext>,
 	// `ReturnCode::BelowSubsistenceThreshold`
 	// `ReturnCode::TransferFailed`
 	// `ReturnCode::NotCallable`
-	seal_call(
+	//
+	// # Traps
+	//
+	// - The callee is on the call stack and did not allow reentry.
+	// - `FORWARD_INPUT` or `CLONE_INPUT` is set but the input was already forwarded.
+	// - A reserved bit is set in `flags`.
+	[seal1] seal_call(
 		ctx,
+		flags: u32,
 		callee_ptr: u32,
 		callee_len: u32,
 		gas: u64,
@@ -761,15 +973,69 @@ define_env!(Env, <E: Ext>,
 		output_ptr: u32,
 		output_len_ptr: u32
 	) -> ReturnCode => {
-		ctx.charge_gas(RuntimeToken::CallBase(input_data_len))?;
-		let callee: <<E as Ext>::T as frame_system::Config>::AccountId =
-			ctx.read_sandbox_memory_as(callee_ptr, callee_len)?;
-		let value: BalanceOf<<E as Ext>::T> = ctx.read_sandbox_memory_as(value_ptr, value_len)?;
-		let input_data = ctx.read_sandbox_memory(input_data_ptr, input_data_len)?;
+		let flags = CallFlags::from_bits(flags).ok_or_else(||
+			ctx.store_err("used reserved bit in call flags")
+		)?;
+		ctx.call(
+			flags,
+			callee_ptr,
+			callee_len,
+			gas,
+			value_ptr,
+			value_len,
+			input_data_ptr,
+			input_data_len,
+			output_ptr,
+			output_len_ptr,
+		)
+	},
 
-		if value > 0u32.into() {
-			ctx.charge_gas(RuntimeToken::CallSurchargeTransfer)?;
-		}
+	// Execute code in the context of the current contract.
+	//
+	// The code identified by `code_hash` is executed with the storage, the balance and the
+	// address of the current contract. `seal_caller` and `seal_value_transferred` return the
+	// same values as in the calling code. No value is transferred.
+	//
+	// The callees output buffer is copied to `output_ptr` and its length to `output_len_ptr`.
+	// The copy of the output buffer can be skipped by supplying the sentinel value
+	// of `u32::max_value()` to `output_ptr`.
+	//
+	// Executing code doesn't keep it from being removed by its owner. A contract that relies on
+	// a library should depend on it with `seal_add_delegate_dependency`.
+	//
+	// # Parameters
+	//
+	// - code_hash_ptr: a pointer to the hash of the code that should be executed.
+	// - code_hash_len: length of the code hash buffer.
+	// - gas: how much gas to devote to the execution.
+	// - input_data_ptr: a pointer to a buffer to be used as input data to the callee.
+	// - input_data_len: length of the input data buffer.
+	// - output_ptr: a pointer where the output buffer is copied to.
+	// - output_len_ptr: in-out pointer to where the length of the buffer is read from
+	//   and the actual length is written to.
+	//
+	// # Errors
+	//
+	// An error means that the call wasn't successful output buffer is returned unless
+	// stated otherwise.
+	//
+	// `ReturnCode::CalleeReverted`: Output buffer is returned.
+	// `ReturnCode::CalleeTrapped`
+	// `ReturnCode::CodeNotFound`
+	seal_delegate_call(
+		ctx,
+		code_hash_ptr: u32,
+		code_hash_len: u32,
+		gas: u64,
+		input_data_ptr: u32,
+		input_data_len: u32,
+		output_ptr: u32,
+		output_len_ptr: u32
+	) -> ReturnCode => {
+		ctx.charge_gas(RuntimeToken::DelegateCallBase(input_data_len))?;
+		let code_hash: CodeHash<<E as Ext>::T> =
+			ctx.read_sandbox_memory_as(code_hash_ptr, code_hash_len)?;
+		let input_data = ctx.read_sandbox_memory(input_data_ptr, input_data_len)?;
 
 		let nested_gas_limit = if gas == 0 {
 			ctx.gas_meter.gas_left()
@@ -779,14 +1045,7 @@ define_env!(Env, <E: Ext>,
 		let ext = &mut ctx.ext;
 		let call_outcome = ctx.gas_meter.with_nested(nested_gas_limit, |nested_meter| {
 			match nested_meter {
-				Some(nested_meter) => {
-					ext.call(
-						&callee,
-						value,
-						nested_meter,
-						input_data,
-					)
-				}
+				Some(nested_meter) => ext.delegate_call(code_hash, nested_meter, input_data),
 				// there is not enough gas to allocate for the nested call.
 				None => Err(Error::<<E as Ext>::T>::OutOfGas.into()),
 			}
@@ -921,7 +1180,8 @@ define_env!(Env, <E: Ext>,
 		beneficiary_ptr: u32,
 		beneficiary_len: u32
 	) => {
-		ctx.charge_gas(RuntimeToken::Terminate)?;
+		let dependencies = ctx.ext.delegate_dependencies_count();
+		ctx.charge_gas(RuntimeToken::Terminate(dependencies))?;
 		let beneficiary: <<E as Ext>::T as frame_system::Config>::AccountId =
 			ctx.read_sandbox_memory_as(beneficiary_ptr, beneficiary_len)?;
 
@@ -931,6 +1191,73 @@ define_env!(Env, <E: Ext>,
 		Err(sp_sandbox::HostError)
 	},
 
+	// Replace the code of the calling contract with the code identified by `code_hash`.
+	//
+	// The storage and the balance of the contract are kept. The new code is used starting
+	// with the next call to the contract: The currently running code continues to execute
+	// until it returns.
+	//
+	// # Parameters
+	//
+	// - code_hash_ptr: a pointer to the hash of the new code.
+	// - code_hash_len: length of the code hash buffer.
+	//
+	// # Errors
+	//
+	// `ReturnCode::CodeNotFound`
+	seal_set_code_hash(ctx, code_hash_ptr: u32, code_hash_len: u32) -> ReturnCode => {
+		ctx.charge_gas(RuntimeToken::SetCodeHash)?;
+		let code_hash: CodeHash<<E as Ext>::T> =
+			ctx.read_sandbox_memory_as(code_hash_ptr, code_hash_len)?;
+		let result = ctx.ext.set_code_hash(code_hash);
+		ctx.map_dispatch_result(result)
+	},
+
+	// Make the calling contract depend on the code identified by `code_hash`.
+	//
+	// The code can't be removed by `remove_code` as long as the contract depends on it, which
+	// makes it safe to execute it with `seal_delegate_call`. A contract can depend on at most
+	// `Limits::delegate_dependencies` codes. The dependencies of a contract are removed when
+	// it is terminated.
+	//
+	// # Parameters
+	//
+	// - code_hash_ptr: a pointer to the hash of the code.
+	// - code_hash_len: length of the code hash buffer.
+	//
+	// # Traps
+	//
+	// - No code is stored under `code_hash`.
+	// - `code_hash` is the code of the calling contract.
+	// - The contract already depends on the code or on the maximum number of codes.
+	seal_add_delegate_dependency(ctx, code_hash_ptr: u32, code_hash_len: u32) => {
+		ctx.charge_gas(RuntimeToken::AddDelegateDependency)?;
+		let code_hash: CodeHash<<E as Ext>::T> =
+			ctx.read_sandbox_memory_as(code_hash_ptr, code_hash_len)?;
+		ctx.ext.add_delegate_dependency(code_hash).map_err(|e| ctx.store_err(e))?;
+		Ok(())
+	},
+
+	// Remove a dependency added by `seal_add_delegate_dependency`.
+	//
+	// The code is removed if no contract uses it anymore.
+	//
+	// # Parameters
+	//
+	// - code_hash_ptr: a pointer to the hash of the code.
+	// - code_hash_len: length of the code hash buffer.
+	//
+	// # Traps
+	//
+	// - The contract doesn't depend on the code.
+	seal_remove_delegate_dependency(ctx, code_hash_ptr: u32, code_hash_len: u32) => {
+		ctx.charge_gas(RuntimeToken::RemoveDelegateDependency)?;
+		let code_hash: CodeHash<<E as Ext>::T> =
+			ctx.read_sandbox_memory_as(code_hash_ptr, code_hash_len)?;
+		ctx.ext.remove_delegate_dependency(code_hash).map_err(|e| ctx.store_err(e))?;
+		Ok(())
+	},
+
 	seal_input(ctx, buf_ptr: u32, buf_len_ptr: u32) => {
 		ctx.charge_gas(RuntimeToken::InputBase)?;
 		if let Some(input) = ctx.input_data.take() {
@@ -938,7 +1265,7 @@ define_env!(Env, <E: Ext>,
 				Some(RuntimeToken::InputCopyOut(len))
 			})
 		} else {
-			Err(sp_sandbox::HostError)
+			Err(ctx.store_err(Error::<E::T>::InputForwarded))
 		}
 	},
 
@@ -989,6 +1316,74 @@ define_env!(Env, <E: Ext>,
 		)
 	},
 
+	// Checks whether the caller of the current contract is the origin of the whole call stack.
+	//
+	// Prefer this over `seal_is_contract` when checking whether your contract is being called
+	// by a contract or a plain account. The reason is that it performs better since it does
+	// not need to do any storage lookups.
+	//
+	// A return value of `1` means that the caller is the origin and hence a plain account
+	// and `0` means that the caller is a contract.
+	seal_caller_is_origin(ctx) -> u32 => {
+		ctx.charge_gas(RuntimeToken::CallerIsOrigin)?;
+		Ok(ctx.ext.caller_is_origin() as u32)
+	},
+
+	// Checks whether a specified address belongs to a contract.
+	//
+	// # Parameters
+	//
+	// - account_ptr: a pointer to the address of the account to check.
+	//   Should be decodable as an `T::AccountId`. Traps otherwise.
+	// - account_len: length of the address buffer.
+	//
+	// Returns `1` if the address belongs to a contract and `0` otherwise.
+	seal_is_contract(ctx, account_ptr: u32, account_len: u32) -> u32 => {
+		ctx.charge_gas(RuntimeToken::IsContract)?;
+		let address: <<E as Ext>::T as frame_system::Config>::AccountId =
+			ctx.read_sandbox_memory_as(account_ptr, account_len)?;
+		Ok(ctx.ext.is_contract(&address) as u32)
+	},
+
+	// Stores the code hash of the contract at the specified address into the supplied buffer.
+	//
+	// The value is stored to linear memory at the address pointed to by `out_ptr`.
+	// `out_len_ptr` must point to a u32 value that describes the available space at
+	// `out_ptr`. This call overwrites it with the size of the value. If the available
+	// space at `out_ptr` is less than the size of the value a trap is triggered.
+	//
+	// # Parameters
+	//
+	// - account_ptr: a pointer to the address of the contract.
+	//   Should be decodable as an `T::AccountId`. Traps otherwise.
+	// - account_len: length of the address buffer.
+	// - out_ptr: pointer to the linear memory where the returning value is written to.
+	// - out_len_ptr: in-out pointer into linear memory where the buffer length
+	//   is read from and the value length is written to.
+	//
+	// # Errors
+	//
+	// `ReturnCode::KeyNotFound`: The address does not belong to a contract.
+	seal_code_hash(
+		ctx,
+		account_ptr: u32,
+		account_len: u32,
+		out_ptr: u32,
+		out_len_ptr: u32
+	) -> ReturnCode => {
+		ctx.charge_gas(RuntimeToken::CodeHash)?;
+		let address: <<E as Ext>::T as frame_system::Config>::AccountId =
+			ctx.read_sandbox_memory_as(account_ptr, account_len)?;
+		if let Some(code_hash) = ctx.ext.code_hash(&address) {
+			ctx.write_sandbox_output(
+				out_ptr, out_len_ptr, &code_hash.encode(), false, already_charged
+			)?;
+			Ok(ReturnCode::Success)
+		} else {
+			Ok(ReturnCode::KeyNotFound)
+		}
+	},
+
 	// Stores the address of the current contract into the supplied buffer.
 	//
 	// The value is stored to linear memory at the address pointed to by `out_ptr`.
@@ -1120,90 +1515,37 @@ define_env!(Env, <E: Ext>,
 	//
 	// # Note
 	//
-	// The tombstone deposit is on top of the existential deposit. So in order for
-	// a contract to leave a tombstone the balance of the contract must not go
-	// below the sum of existential deposit and the tombstone deposit. The sum
-	// is commonly referred as subsistence threshold in code.
+	// Tombstones were removed together with the storage rent. This function is only kept
+	// for compatibility with already deployed contracts and always returns zero.
 	seal_tombstone_deposit(ctx, out_ptr: u32, out_len_ptr: u32) => {
 		ctx.charge_gas(RuntimeToken::TombstoneDeposit)?;
 		ctx.write_sandbox_output(
-			out_ptr, out_len_ptr, &ctx.ext.tombstone_deposit().encode(), false, already_charged
+			out_ptr, out_len_ptr, &<BalanceOf<E::T>>::zero().encode(), false, already_charged
 		)
 	},
 
-	// Try to restore the given destination contract sacrificing the caller.
-	//
-	// This function will compute a tombstone hash from the caller's storage and the given code hash
-	// and if the hash matches the hash found in the tombstone at the specified address - kill
-	// the caller contract and restore the destination contract and set the specified `rent_allowance`.
-	// All caller's funds are transfered to the destination.
-	//
-	// If there is no tombstone at the destination address, the hashes don't match or this contract
-	// instance is already present on the contract call stack, a trap is generated.
+	// Was used to restore an evicted contract from its tombstone.
 	//
-	// Otherwise, the destination contract is restored. This function is diverging and stops execution
-	// even on success.
+	// # Note
 	//
-	// `dest_ptr`, `dest_len` - the pointer and the length of a buffer that encodes `T::AccountId`
-	// with the address of the to be restored contract.
-	// `code_hash_ptr`, `code_hash_len` - the pointer and the length of a buffer that encodes
-	// a code hash of the to be restored contract.
-	// `rent_allowance_ptr`, `rent_allowance_len` - the pointer and the length of a buffer that
-	// encodes the rent allowance that must be set in the case of successful restoration.
-	// `delta_ptr` is the pointer to the start of a buffer that has `delta_count` storage keys
-	// laid out sequentially.
+	// Tombstones were removed together with the storage rent. This function is only kept
+	// so that already deployed contracts which import it can still be instantiated.
 	//
 	// # Traps
 	//
-	// - Tombstone hashes do not match
-	// - Calling cantract is live i.e is already on the call stack.
+	// - Always
 	seal_restore_to(
 		ctx,
-		dest_ptr: u32,
-		dest_len: u32,
-		code_hash_ptr: u32,
-		code_hash_len: u32,
-		rent_allowance_ptr: u32,
-		rent_allowance_len: u32,
-		delta_ptr: u32,
-		delta_count: u32
+		_dest_ptr: u32,
+		_dest_len: u32,
+		_code_hash_ptr: u32,
+		_code_hash_len: u32,
+		_rent_allowance_ptr: u32,
+		_rent_allowance_len: u32,
+		_delta_ptr: u32,
+		_delta_count: u32
 	) => {
-		ctx.charge_gas(RuntimeToken::RestoreTo(delta_count))?;
-		let dest: <<E as Ext>::T as frame_system::Config>::AccountId =
-			ctx.read_sandbox_memory_as(dest_ptr, dest_len)?;
-		let code_hash: CodeHash<<E as Ext>::T> =
-			ctx.read_sandbox_memory_as(code_hash_ptr, code_hash_len)?;
-		let rent_allowance: BalanceOf<<E as Ext>::T> =
-			ctx.read_sandbox_memory_as(rent_allowance_ptr, rent_allowance_len)?;
-		let delta = {
-			// We can eagerly allocate because we charged for the complete delta count already
-			let mut delta = Vec::with_capacity(delta_count as usize);
-			let mut key_ptr = delta_ptr;
-
-			for _ in 0..delta_count {
-				const KEY_SIZE: usize = 32;
-
-				// Read the delta into the provided buffer and collect it into the buffer.
-				let mut delta_key: StorageKey = [0; KEY_SIZE];
-				ctx.read_sandbox_memory_into_buf(key_ptr, &mut delta_key)?;
-				delta.push(delta_key);
-
-				// Offset key_ptr to the next element.
-				key_ptr = key_ptr.checked_add(KEY_SIZE as u32).ok_or_else(|| sp_sandbox::HostError)?;
-			}
-
-			delta
-		};
-
-		if let Ok(()) = ctx.ext.restore_to(
-			dest,
-			code_hash,
-			rent_allowance,
-			delta,
-		).map_err(|e| ctx.store_err(e)) {
-			ctx.trap_reason = Some(TrapReason::Restoration);
-		}
-		Err(sp_sandbox::HostError)
+		Err(ctx.store_err("Restoring contracts is no longer supported"))
 	},
 
 	// Deposit a contract event with the data buffer and optional list of topics. There is a limit
@@ -1248,17 +1590,15 @@ define_env!(Env, <E: Ext>,
 		Ok(())
 	},
 
-	// Set rent allowance of the contract
+	// Was used to set the rent allowance of the contract.
 	//
-	// - value_ptr: a pointer to the buffer with value, how much to allow for rent
-	//   Should be decodable as a `T::Balance`. Traps otherwise.
-	// - value_len: length of the value buffer.
+	// # Note
+	//
+	// The storage rent was replaced by a storage deposit. This function is only kept for
+	// compatibility with already deployed contracts and does nothing.
 	seal_set_rent_allowance(ctx, value_ptr: u32, value_len: u32) => {
 		ctx.charge_gas(RuntimeToken::SetRentAllowance)?;
-		let value: BalanceOf<<E as Ext>::T> =
-			ctx.read_sandbox_memory_as(value_ptr, value_len)?;
-		ctx.ext.set_rent_allowance(value);
-
+		let _: BalanceOf<<E as Ext>::T> = ctx.read_sandbox_memory_as(value_ptr, value_len)?;
 		Ok(())
 	},
 
@@ -1270,16 +1610,21 @@ define_env!(Env, <E: Ext>,
 	// space at `out_ptr` is less than the size of the value a trap is triggered.
 	//
 	// The data is encoded as T::Balance.
+	//
+	// # Note
+	//
+	// The storage rent was replaced by a storage deposit. This function is only kept for
+	// compatibility with already deployed contracts and always returns the maximum balance.
 	seal_rent_allowance(ctx, out_ptr: u32, out_len_ptr: u32) => {
 		ctx.charge_gas(RuntimeToken::RentAllowance)?;
 		ctx.write_sandbox_output(
-			out_ptr, out_len_ptr, &ctx.ext.rent_allowance().encode(), false, already_charged
+			out_ptr, out_len_ptr, &<BalanceOf<E::T>>::max_value().encode(), false, already_charged
 		)
 	},
 
 	// Prints utf8 encoded string from the data buffer.
 	// Only available on `--dev` chains.
-	// This function may be removed at any time, superseded by a more general contract debugging feature.
+	// This function may be removed at any time, superseded by `seal_debug_message`.
 	seal_println(ctx, str_ptr: u32, str_len: u32) => {
 		let data = ctx.read_sandbox_memory(str_ptr, str_len)?;
 		if let Ok(utf8) = core::str::from_utf8(&data) {
@@ -1288,6 +1633,35 @@ define_env!(Env, <E: Ext>,
 		Ok(())
 	},
 
+	// Emit a custom debug message.
+	//
+	// No newlines are added to the supplied message.
+	// Specifying invalid UTF-8 triggers a trap.
+	//
+	// This is a no-op if debug message recording is disabled which is always the case
+	// when the code is executing on-chain. The messages are returned to the caller of
+	// an RPC dry run.
+	//
+	// # Return Value
+	//
+	// Returns `ReturnCode::Success` when debug message recording is enabled and the message
+	// was recorded.
+	//
+	// # Errors
+	//
+	// `ReturnCode::LoggingDisabled`
+	seal_debug_message(ctx, str_ptr: u32, str_len: u32) -> ReturnCode => {
+		ctx.charge_gas(RuntimeToken::DebugMessage)?;
+		if ctx.ext.append_debug_buffer("") {
+			let data = ctx.read_sandbox_memory(str_ptr, str_len)?;
+			let msg = core::str::from_utf8(&data)
+				.map_err(|_| ctx.store_err("Debug message is invalid UTF-8"))?;
+			ctx.ext.append_debug_buffer(msg);
+			return Ok(ReturnCode::Success);
+		}
+		Ok(ReturnCode::LoggingDisabled)
+	},
+
 	// Stores the current block number of the current contract into the supplied buffer.
 	//
 	// The value is stored to linear memory at the address pointed to by `out_ptr`.
@@ -1400,4 +1774,153 @@ define_env!(Env, <E: Ext>,
 		ctx.charge_gas(RuntimeToken::HashBlake128(input_len))?;
 		ctx.compute_hash_on_intermediate_buffer(blake2_128, input_ptr, input_len, output_ptr)
 	},
+
+	// Recovers the ECDSA public key from the given message hash and signature.
+	//
+	// Writes the public key into the given output buffer.
+	// Assumes the secp256k1 curve.
+	//
+	// # Parameters
+	//
+	// - `signature_ptr`: the pointer into the linear memory where the signature
+	//                    is placed. Should be 65 bytes long in RSV format.
+	// - `message_hash_ptr`: the pointer into the linear memory where the message
+	//                       hash is placed. Should be 32 bytes long.
+	// - `output_ptr`: the pointer into the linear memory where the output
+	//                 data is placed. The buffer should be 33 bytes. The function
+	//                 will write the compressed public key into this buffer.
+	//
+	// # Errors
+	//
+	// `ReturnCode::EcdsaRecoverFailed`
+	seal_ecdsa_recover(
+		ctx,
+		signature_ptr: u32,
+		message_hash_ptr: u32,
+		output_ptr: u32
+	) -> ReturnCode => {
+		ctx.charge_gas(RuntimeToken::EcdsaRecovery)?;
+
+		let mut signature: [u8; 65] = [0; 65];
+		ctx.read_sandbox_memory_into_buf(signature_ptr, &mut signature)?;
+		let mut message_hash: [u8; 32] = [0; 32];
+		ctx.read_sandbox_memory_into_buf(message_hash_ptr, &mut message_hash)?;
+
+		match ctx.ext.ecdsa_recover(&signature, &message_hash) {
+			Ok(pub_key) => {
+				ctx.write_sandbox_memory(output_ptr, pub_key.as_ref())?;
+				Ok(ReturnCode::Success)
+			},
+			Err(_) => Ok(ReturnCode::EcdsaRecoverFailed),
+		}
+	},
+
+	// Calculates the Ethereum address from the given compressed ECDSA public key.
+	//
+	// Writes the address into the given output buffer.
+	//
+	// # Parameters
+	//
+	// - `key_ptr`: the pointer into the linear memory where the compressed public key
+	//              is placed. Should be 33 bytes long.
+	// - `output_ptr`: the pointer into the linear memory where the output
+	//                 data is placed. The buffer should be 20 bytes. The function
+	//                 will write the address into this buffer.
+	//
+	// # Errors
+	//
+	// `ReturnCode::EcdsaToEthAddressFailed`
+	seal_ecdsa_to_eth_address(ctx, key_ptr: u32, output_ptr: u32) -> ReturnCode => {
+		ctx.charge_gas(RuntimeToken::EcdsaToEthAddress)?;
+
+		let mut compressed_key: [u8; 33] = [0; 33];
+		ctx.read_sandbox_memory_into_buf(key_ptr, &mut compressed_key)?;
+
+		match ctx.ext.ecdsa_to_eth_address(&compressed_key) {
+			Ok(address) => {
+				ctx.write_sandbox_memory(output_ptr, address.as_ref())?;
+				Ok(ReturnCode::Success)
+			},
+			Err(_) => Ok(ReturnCode::EcdsaToEthAddressFailed),
+		}
+	},
+
+	// Verifies a sr25519 signature.
+	//
+	// # Parameters
+	//
+	// - `signature_ptr`: the pointer into the linear memory where the signature
+	//                    is placed. Should be 64 bytes long.
+	// - `pub_key_ptr`: the pointer into the linear memory where the public key
+	//                  is placed. Should be 32 bytes long.
+	// - `message_len`: the length of the message in bytes.
+	// - `message_ptr`: the pointer into the linear memory where the message
+	//                  is placed.
+	//
+	// # Errors
+	//
+	// `ReturnCode::Sr25519VerifyFailed`
+	seal_sr25519_verify(
+		ctx,
+		signature_ptr: u32,
+		pub_key_ptr: u32,
+		message_len: u32,
+		message_ptr: u32
+	) -> ReturnCode => {
+		ctx.charge_gas(RuntimeToken::Sr25519Verify(message_len))?;
+
+		let mut signature: [u8; 64] = [0; 64];
+		ctx.read_sandbox_memory_into_buf(signature_ptr, &mut signature)?;
+		let mut pub_key: [u8; 32] = [0; 32];
+		ctx.read_sandbox_memory_into_buf(pub_key_ptr, &mut pub_key)?;
+		let message = ctx.read_sandbox_memory(message_ptr, message_len)?;
+
+		if ctx.ext.sr25519_verify(&signature, &message, &pub_key) {
+			Ok(ReturnCode::Success)
+		} else {
+			Ok(ReturnCode::Sr25519VerifyFailed)
+		}
+	},
+
+	// Call into the chain extension provided by the chain if any.
+	//
+	// Handling of the input values is up to the specific chain extension and so is the
+	// return value. The extension can decide to use the inputs as primitive inputs or as
+	// in/out arguments by interpreting them as pointers. Any caller of this function
+	// must therefore coordinate with the chain that it targets.
+	//
+	// The upper 16 bits of `func_id` determine which chain extension is called when the chain
+	// has multiple extensions registered. The lower 16 bits are passed on unchanged and are
+	// interpreted by the selected extension.
+	//
+	// # Note
+	//
+	// If no chain extension exists the contract will trap with the `NoChainExtension`
+	// module error.
+	seal_call_chain_extension(
+		ctx,
+		func_id: u32,
+		input_ptr: u32,
+		input_len: u32,
+		output_ptr: u32,
+		output_len_ptr: u32
+	) -> u32 => {
+		use crate::chain_extension::{ChainExtension, Environment, RetVal};
+		ctx.charge_gas(RuntimeToken::CallChainExtension)?;
+		if !<E::T as Config>::ChainExtension::enabled() {
+			Err(ctx.store_err(Error::<E::T>::NoChainExtension))?;
+		}
+		let env = Environment::new(ctx, input_ptr, input_len, output_ptr, output_len_ptr);
+		match <E::T as Config>::ChainExtension::call(func_id, env) {
+			Ok(RetVal::Converging(val)) => Ok(val),
+			Ok(RetVal::Diverging{flags, data}) => {
+				ctx.trap_reason = Some(TrapReason::Return(ReturnData {
+					flags: flags.bits(),
+					data,
+				}));
+				Err(sp_sandbox::HostError)
+			},
+			Err(err) => Err(ctx.store_err(err)),
+		}
+	},
 );
@@ -43,13 +43,20 @@ use sp_std::marker::PhantomData;
 
 /// Weight functions needed for pallet_contracts.
 pub trait WeightInfo {
+	fn on_initialize() -> Weight;
+	fn on_initialize_per_trie_key(k: u32, ) -> Weight;
+	fn on_initialize_per_queue_item(q: u32, ) -> Weight;
 	fn update_schedule() -> Weight;
-	fn put_code(n: u32, ) -> Weight;
+	fn upload_code(n: u32, ) -> Weight;
+	fn remove_code() -> Weight;
+	fn instantiate_with_code(c: u32, n: u32, s: u32, ) -> Weight;
 	fn instantiate(n: u32, s: u32, ) -> Weight;
 	fn call() -> Weight;
-	fn claim_surcharge() -> Weight;
 	fn seal_caller(r: u32, ) -> Weight;
 	fn seal_address(r: u32, ) -> Weight;
+	fn seal_caller_is_origin(r: u32, ) -> Weight;
+	fn seal_is_contract(r: u32, ) -> Weight;
+	fn seal_code_hash(r: u32, ) -> Weight;
 	fn seal_gas_left(r: u32, ) -> Weight;
 	fn seal_balance(r: u32, ) -> Weight;
 	fn seal_value_transferred(r: u32, ) -> Weight;
@@ -65,11 +72,10 @@ pub trait WeightInfo {
 	fn seal_return(r: u32, ) -> Weight;
 	fn seal_return_per_kb(n: u32, ) -> Weight;
 	fn seal_terminate(r: u32, ) -> Weight;
-	fn seal_restore_to(r: u32, ) -> Weight;
-	fn seal_restore_to_per_delta(d: u32, ) -> Weight;
 	fn seal_random(r: u32, ) -> Weight;
 	fn seal_deposit_event(r: u32, ) -> Weight;
 	fn seal_deposit_event_per_topic_and_kb(t: u32, n: u32, ) -> Weight;
+	fn seal_debug_message(r: u32, ) -> Weight;
 	fn seal_set_rent_allowance(r: u32, ) -> Weight;
 	fn seal_set_storage(r: u32, ) -> Weight;
 	fn seal_set_storage_per_kb(n: u32, ) -> Weight;
@@ -79,6 +85,10 @@ pub trait WeightInfo {
 	fn seal_transfer(r: u32, ) -> Weight;
 	fn seal_call(r: u32, ) -> Weight;
 	fn seal_call_per_transfer_input_output_kb(t: u32, i: u32, o: u32, ) -> Weight;
+	fn seal_delegate_call(r: u32, ) -> Weight;
+	fn seal_set_code_hash(r: u32, ) -> Weight;
+	fn seal_add_delegate_dependency(r: u32, ) -> Weight;
+	fn seal_remove_delegate_dependency(r: u32, ) -> Weight;
 	fn seal_instantiate(r: u32, ) -> Weight;
 	fn seal_instantiate_per_input_output_salt_kb(i: u32, o: u32, s: u32, ) -> Weight;
 	fn seal_hash_sha2_256(r: u32, ) -> Weight;
@@ -89,6 +99,10 @@ pub trait WeightInfo {
 	fn seal_hash_blake2_256_per_kb(n: u32, ) -> Weight;
 	fn seal_hash_blake2_128(r: u32, ) -> Weight;
 	fn seal_hash_blake2_128_per_kb(n: u32, ) -> Weight;
+	fn seal_ecdsa_recover(r: u32, ) -> Weight;
+	fn seal_ecdsa_to_eth_address(r: u32, ) -> Weight;
+	fn seal_sr25519_verify(r: u32, ) -> Weight;
+	fn seal_sr25519_verify_per_kb(n: u32, ) -> Weight;
 	fn instr_i64const(r: u32, ) -> Weight;
 	fn instr_i64load(r: u32, ) -> Weight;
 	fn instr_i64store(r: u32, ) -> Weight;
@@ -145,34 +159,60 @@ pub trait WeightInfo {
 /// Weights for pallet_contracts using the Substrate node and recommended hardware.
 pub struct SubstrateWeight<T>(PhantomData<T>);
 impl<T: frame_system::Config> WeightInfo for SubstrateWeight<T> {
+	fn on_initialize() -> Weight {
+		(3_733_000 as Weight)
+			.saturating_add(T::DbWeight::get().reads(1 as Weight))
+	}
+	fn on_initialize_per_trie_key(k: u32, ) -> Weight {
+		(49_569_000 as Weight)
+			.saturating_add((2_784_000 as Weight).saturating_mul(k as Weight))
+			.saturating_add(T::DbWeight::get().reads(1 as Weight))
+			.saturating_add(T::DbWeight::get().reads((1 as Weight).saturating_mul(k as Weight)))
+			.saturating_add(T::DbWeight::get().writes(1 as Weight))
+			.saturating_add(T::DbWeight::get().writes((1 as Weight).saturating_mul(k as Weight)))
+	}
+	fn on_initialize_per_queue_item(q: u32, ) -> Weight {
+		(0 as Weight)
+			.saturating_add((35_267_000 as Weight).saturating_mul(q as Weight))
+			.saturating_add(T::DbWeight::get().reads(1 as Weight))
+			.saturating_add(T::DbWeight::get().writes(1 as Weight))
+	}
 	fn update_schedule() -> Weight {
 		(35_214_000 as Weight)
 			.saturating_add(T::DbWeight::get().reads(1 as Weight))
 			.saturating_add(T::DbWeight::get().writes(1 as Weight))
 	}
-	fn put_code(n: u32, ) -> Weight {
+	fn upload_code(n: u32, ) -> Weight {
 		(0 as Weight)
 			.saturating_add((109_242_000 as Weight).saturating_mul(n as Weight))
-			.saturating_add(T::DbWeight::get().reads(1 as Weight))
-			.saturating_add(T::DbWeight::get().writes(2 as Weight))
+			.saturating_add(T::DbWeight::get().reads(2 as Weight))
+			.saturating_add(T::DbWeight::get().writes(4 as Weight))
+	}
+	fn remove_code() -> Weight {
+		(48_306_000 as Weight)
+			.saturating_add(T::DbWeight::get().reads(2 as Weight))
+			.saturating_add(T::DbWeight::get().writes(4 as Weight))
+	}
+	fn instantiate_with_code(c: u32, n: u32, s: u32, ) -> Weight {
+		(246_812_000 as Weight)
+			.saturating_add((109_242_000 as Weight).saturating_mul(c as Weight))
+			.saturating_add((35_000 as Weight).saturating_mul(n as Weight))
+			.saturating_add((2_244_000 as Weight).saturating_mul(s as Weight))
+			.saturating_add(T::DbWeight::get().reads(7 as Weight))
+			.saturating_add(T::DbWeight::get().writes(7 as Weight))
 	}
 	fn instantiate(n: u32, s: u32, ) -> Weight {
 		(195_276_000 as Weight)
 			.saturating_add((35_000 as Weight).saturating_mul(n as Weight))
 			.saturating_add((2_244_000 as Weight).saturating_mul(s as Weight))
-			.saturating_add(T::DbWeight::get().reads(6 as Weight))
-			.saturating_add(T::DbWeight::get().writes(3 as Weight))
+			.saturating_add(T::DbWeight::get().reads(7 as Weight))
+			.saturating_add(T::DbWeight::get().writes(4 as Weight))
 	}
 	fn call() -> Weight {
 		(207_142_000 as Weight)
 			.saturating_add(T::DbWeight::get().reads(5 as Weight))
 			.saturating_add(T::DbWeight::get().writes(2 as Weight))
 	}
-	fn claim_surcharge() -> Weight {
-		(489_633_000 as Weight)
-			.saturating_add(T::DbWeight::get().reads(3 as Weight))
-			.saturating_add(T::DbWeight::get().writes(2 as Weight))
-	}
 	fn seal_caller(r: u32, ) -> Weight {
 		(136_550_000 as Weight)
 			.saturating_add((373_182_000 as Weight).saturating_mul(r as Weight))
@@ -183,6 +223,23 @@ impl<T: frame_system::Config> WeightInfo for SubstrateWeight<T> {
 			.saturating_add((373_392_000 as Weight).saturating_mul(r as Weight))
 			.saturating_add(T::DbWeight::get().reads(4 as Weight))
 	}
+	fn seal_caller_is_origin(r: u32, ) -> Weight {
+		(112_064_000 as Weight)
+			.saturating_add((171_348_000 as Weight).saturating_mul(r as Weight))
+			.saturating_add(T::DbWeight::get().reads(4 as Weight))
+	}
+	fn seal_is_contract(r: u32, ) -> Weight {
+		(82_458_000 as Weight)
+			.saturating_add((760_926_000 as Weight).saturating_mul(r as Weight))
+			.saturating_add(T::DbWeight::get().reads(4 as Weight))
+			.saturating_add(T::DbWeight::get().reads((100 as Weight).saturating_mul(r as Weight)))
+	}
+	fn seal_code_hash(r: u32, ) -> Weight {
+		(86_130_000 as Weight)
+			.saturating_add((821_455_000 as Weight).saturating_mul(r as Weight))
+			.saturating_add(T::DbWeight::get().reads(4 as Weight))
+			.saturating_add(T::DbWeight::get().reads((100 as Weight).saturating_mul(r as Weight)))
+	}
 	fn seal_gas_left(r: u32, ) -> Weight {
 		(111_577_000 as Weight)
 			.saturating_add((373_536_000 as Weight).saturating_mul(r as Weight))
@@ -260,21 +317,6 @@ impl<T: frame_system::Config> WeightInfo for SubstrateWeight<T> {
 			.saturating_add(T::DbWeight::get().reads((2 as Weight).saturating_mul(r as Weight)))
 			.saturating_add(T::DbWeight::get().writes((3 as Weight).saturating_mul(r as Weight)))
 	}
-	fn seal_restore_to(r: u32, ) -> Weight {
-		(233_645_000 as Weight)
-			.saturating_add((135_355_000 as Weight).saturating_mul(r as Weight))
-			.saturating_add(T::DbWeight::get().reads(5 as Weight))
-			.saturating_add(T::DbWeight::get().reads((3 as Weight).saturating_mul(r as Weight)))
-			.saturating_add(T::DbWeight::get().writes((4 as Weight).saturating_mul(r as Weight)))
-	}
-	fn seal_restore_to_per_delta(d: u32, ) -> Weight {
-		(74_573_000 as Weight)
-			.saturating_add((3_768_682_000 as Weight).saturating_mul(d as Weight))
-			.saturating_add(T::DbWeight::get().reads(7 as Weight))
-			.saturating_add(T::DbWeight::get().reads((100 as Weight).saturating_mul(d as Weight)))
-			.saturating_add(T::DbWeight::get().writes(5 as Weight))
-			.saturating_add(T::DbWeight::get().writes((100 as Weight).saturating_mul(d as Weight)))
-	}
 	fn seal_random(r: u32, ) -> Weight {
 		(140_286_000 as Weight)
 			.saturating_add((950_890_000 as Weight).saturating_mul(r as Weight))
@@ -293,6 +335,11 @@ impl<T: frame_system::Config> WeightInfo for SubstrateWeight<T> {
 			.saturating_add(T::DbWeight::get().reads((100 as Weight).saturating_mul(t as Weight)))
 			.saturating_add(T::DbWeight::get().writes((100 as Weight).saturating_mul(t as Weight)))
 	}
+	fn seal_debug_message(r: u32, ) -> Weight {
+		(135_892_000 as Weight)
+			.saturating_add((332_104_000 as Weight).saturating_mul(r as Weight))
+			.saturating_add(T::DbWeight::get().reads(4 as Weight))
+	}
 	fn seal_set_rent_allowance(r: u32, ) -> Weight {
 		(156_911_000 as Weight)
 			.saturating_add((1_006_139_000 as Weight).saturating_mul(r as Weight))
@@ -355,6 +402,33 @@ impl<T: frame_system::Config> WeightInfo for SubstrateWeight<T> {
 			.saturating_add(T::DbWeight::get().reads((101 as Weight).saturating_mul(t as Weight)))
 			.saturating_add(T::DbWeight::get().writes((101 as Weight).saturating_mul(t as Weight)))
 	}
+	fn seal_delegate_call(r: u32, ) -> Weight {
+		(0 as Weight)
+			.saturating_add((9_926_438_000 as Weight).saturating_mul(r as Weight))
+			.saturating_add(T::DbWeight::get().reads(4 as Weight))
+			.saturating_add(T::DbWeight::get().reads((100 as Weight).saturating_mul(r as Weight)))
+	}
+	fn seal_set_code_hash(r: u32, ) -> Weight {
+		(135_418_000 as Weight)
+			.saturating_add((2_617_234_000 as Weight).saturating_mul(r as Weight))
+			.saturating_add(T::DbWeight::get().reads(4 as Weight))
+			.saturating_add(T::DbWeight::get().reads((300 as Weight).saturating_mul(r as Weight)))
+			.saturating_add(T::DbWeight::get().writes((300 as Weight).saturating_mul(r as Weight)))
+	}
+	fn seal_add_delegate_dependency(r: u32, ) -> Weight {
+		(131_287_000 as Weight)
+			.saturating_add((118_452_000 as Weight).saturating_mul(r as Weight))
+			.saturating_add(T::DbWeight::get().reads(4 as Weight))
+			.saturating_add(T::DbWeight::get().reads((3 as Weight).saturating_mul(r as Weight)))
+			.saturating_add(T::DbWeight::get().writes((2 as Weight).saturating_mul(r as Weight)))
+	}
+	fn seal_remove_delegate_dependency(r: u32, ) -> Weight {
+		(132_015_000 as Weight)
+			.saturating_add((97_306_000 as Weight).saturating_mul(r as Weight))
+			.saturating_add(T::DbWeight::get().reads(4 as Weight))
+			.saturating_add(T::DbWeight::get().reads((2 as Weight).saturating_mul(r as Weight)))
+			.saturating_add(T::DbWeight::get().writes((2 as Weight).saturating_mul(r as Weight)))
+	}
 	fn seal_instantiate(r: u32, ) -> Weight {
 		(0 as Weight)
 			.saturating_add((22_365_908_000 as Weight).saturating_mul(r as Weight))
@@ -411,6 +485,26 @@ impl<T: frame_system::Config> WeightInfo for SubstrateWeight<T> {
 			.saturating_add((153_146_000 as Weight).saturating_mul(n as Weight))
 			.saturating_add(T::DbWeight::get().reads(4 as Weight))
 	}
+	fn seal_ecdsa_recover(r: u32, ) -> Weight {
+		(194_280_000 as Weight)
+			.saturating_add((4_610_527_000 as Weight).saturating_mul(r as Weight))
+			.saturating_add(T::DbWeight::get().reads(4 as Weight))
+	}
+	fn seal_ecdsa_to_eth_address(r: u32, ) -> Weight {
+		(162_340_000 as Weight)
+			.saturating_add((1_289_830_000 as Weight).saturating_mul(r as Weight))
+			.saturating_add(T::DbWeight::get().reads(4 as Weight))
+	}
+	fn seal_sr25519_verify(r: u32, ) -> Weight {
+		(174_090_000 as Weight)
+			.saturating_add((5_212_446_000 as Weight).saturating_mul(r as Weight))
+			.saturating_add(T::DbWeight::get().reads(4 as Weight))
+	}
+	fn seal_sr25519_verify_per_kb(n: u32, ) -> Weight {
+		(5_389_718_000 as Weight)
+			.saturating_add((489_032_000 as Weight).saturating_mul(n as Weight))
+			.saturating_add(T::DbWeight::get().reads(4 as Weight))
+	}
 	fn instr_i64const(r: u32, ) -> Weight {
 		(26_679_000 as Weight)
 			.saturating_add((3_155_000 as Weight).saturating_mul(r as Weight))
@@ -619,34 +713,60 @@ impl<T: frame_system::Config> WeightInfo for SubstrateWeight<T> {
 
 // For backwards compatibility and tests
 impl WeightInfo for () {
+	fn on_initialize() -> Weight {
+		(3_733_000 as Weight)
+			.saturating_add(RocksDbWeight::get().reads(1 as Weight))
+	}
+	fn on_initialize_per_trie_key(k: u32, ) -> Weight {
+		(49_569_000 as Weight)
+			.saturating_add((2_784_000 as Weight).saturating_mul(k as Weight))
+			.saturating_add(RocksDbWeight::get().reads(1 as Weight))
+			.saturating_add(RocksDbWeight::get().reads((1 as Weight).saturating_mul(k as Weight)))
+			.saturating_add(RocksDbWeight::get().writes(1 as Weight))
+			.saturating_add(RocksDbWeight::get().writes((1 as Weight).saturating_mul(k as Weight)))
+	}
+	fn on_initialize_per_queue_item(q: u32, ) -> Weight {
+		(0 as Weight)
+			.saturating_add((35_267_000 as Weight).saturating_mul(q as Weight))
+			.saturating_add(RocksDbWeight::get().reads(1 as Weight))
+			.saturating_add(RocksDbWeight::get().writes(1 as Weight))
+	}
 	fn update_schedule() -> Weight {
 		(35_214_000 as Weight)
 			.saturating_add(RocksDbWeight::get().reads(1 as Weight))
 			.saturating_add(RocksDbWeight::get().writes(1 as Weight))
 	}
-	fn put_code(n: u32, ) -> Weight {
+	fn upload_code(n: u32, ) -> Weight {
 		(0 as Weight)
 			.saturating_add((109_242_000 as Weight).saturating_mul(n as Weight))
-			.saturating_add(RocksDbWeight::get().reads(1 as Weight))
-			.saturating_add(RocksDbWeight::get().writes(2 as Weight))
+			.saturating_add(RocksDbWeight::get().reads(2 as Weight))
+			.saturating_add(RocksDbWeight::get().writes(4 as Weight))
+	}
+	fn remove_code() -> Weight {
+		(48_306_000 as Weight)
+			.saturating_add(RocksDbWeight::get().reads(2 as Weight))
+			.saturating_add(RocksDbWeight::get().writes(4 as Weight))
+	}
+	fn instantiate_with_code(c: u32, n: u32, s: u32, ) -> Weight {
+		(246_812_000 as Weight)
+			.saturating_add((109_242_000 as Weight).saturating_mul(c as Weight))
+			.saturating_add((35_000 as Weight).saturating_mul(n as Weight))
+			.saturating_add((2_244_000 as Weight).saturating_mul(s as Weight))
+			.saturating_add(RocksDbWeight::get().reads(7 as Weight))
+			.saturating_add(RocksDbWeight::get().writes(7 as Weight))
 	}
 	fn instantiate(n: u32, s: u32, ) -> Weight {
 		(195_276_000 as Weight)
 			.saturating_add((35_000 as Weight).saturating_mul(n as Weight))
 			.saturating_add((2_244_000 as Weight).saturating_mul(s as Weight))
-			.saturating_add(RocksDbWeight::get().reads(6 as Weight))
-			.saturating_add(RocksDbWeight::get().writes(3 as Weight))
+			.saturating_add(RocksDbWeight::get().reads(7 as Weight))
+			.saturating_add(RocksDbWeight::get().writes(4 as Weight))
 	}
 	fn call() -> Weight {
 		(207_142_000 as Weight)
 			.saturating_add(RocksDbWeight::get().reads(5 as Weight))
 			.saturating_add(RocksDbWeight::get().writes(2 as Weight))
 	}
-	fn claim_surcharge() -> Weight {
-		(489_633_000 as Weight)
-			.saturating_add(RocksDbWeight::get().reads(3 as Weight))
-			.saturating_add(RocksDbWeight::get().writes(2 as Weight))
-	}
 	fn seal_caller(r: u32, ) -> Weight {
 		(136_550_000 as Weight)
 			.saturating_add((373_182_000 as Weight).saturating_mul(r as Weight))
@@ -657,6 +777,23 @@ impl WeightInfo for () {
 			.saturating_add((373_392_000 as Weight).saturating_mul(r as Weight))
 			.saturating_add(RocksDbWeight::get().reads(4 as Weight))
 	}
+	fn seal_caller_is_origin(r: u32, ) -> Weight {
+		(112_064_000 as Weight)
+			.saturating_add((171_348_000 as Weight).saturating_mul(r as Weight))
+			.saturating_add(RocksDbWeight::get().reads(4 as Weight))
+	}
+	fn seal_is_contract(r: u32, ) -> Weight {
+		(82_458_000 as Weight)
+			.saturating_add((760_926_000 as Weight).saturating_mul(r as Weight))
+			.saturating_add(RocksDbWeight::get().reads(4 as Weight))
+			.saturating_add(RocksDbWeight::get().reads((100 as Weight).saturating_mul(r as Weight)))
+	}
+	fn seal_code_hash(r: u32, ) -> Weight {
+		(86_130_000 as Weight)
+			.saturating_add((821_455_000 as Weight).saturating_mul(r as Weight))
+			.saturating_add(RocksDbWeight::get().reads(4 as Weight))
+			.saturating_add(RocksDbWeight::get().reads((100 as Weight).saturating_mul(r as Weight)))
+	}
 	fn seal_gas_left(r: u32, ) -> Weight {
 		(111_577_000 as Weight)
 			.saturating_add((373_536_000 as Weight).saturating_mul(r as Weight))
@@ -734,21 +871,6 @@ impl WeightInfo for () {
 			.saturating_add(RocksDbWeight::get().reads((2 as Weight).saturating_mul(r as Weight)))
 			.saturating_add(RocksDbWeight::get().writes((3 as Weight).saturating_mul(r as Weight)))
 	}
-	fn seal_restore_to(r: u32, ) -> Weight {
-		(233_645_000 as Weight)
-			.saturating_add((135_355_000 as Weight).saturating_mul(r as Weight))
-			.saturating_add(RocksDbWeight::get().reads(5 as Weight))
-			.saturating_add(RocksDbWeight::get().reads((3 as Weight).saturating_mul(r as Weight)))
-			.saturating_add(RocksDbWeight::get().writes((4 as Weight).saturating_mul(r as Weight)))
-	}
-	fn seal_restore_to_per_delta(d: u32, ) -> Weight {
-		(74_573_000 as Weight)
-			.saturating_add((3_768_682_000 as Weight).saturating_mul(d as Weight))
-			.saturating_add(RocksDbWeight::get().reads(7 as Weight))
-			.saturating_add(RocksDbWeight::get().reads((100 as Weight).saturating_mul(d as Weight)))
-			.saturating_add(RocksDbWeight::get().writes(5 as Weight))
-			.saturating_add(RocksDbWeight::get().writes((100 as Weight).saturating_mul(d as Weight)))
-	}
 	fn seal_random(r: u32, ) -> Weight {
 		(140_286_000 as Weight)
 			.saturating_add((950_890_000 as Weight).saturating_mul(r as Weight))
@@ -767,6 +889,11 @@ impl WeightInfo for () {
 			.saturating_add(RocksDbWeight::get().reads((100 as Weight).saturating_mul(t as Weight)))
 			.saturating_add(RocksDbWeight::get().writes((100 as Weight).saturating_mul(t as Weight)))
 	}
+	fn seal_debug_message(r: u32, ) -> Weight {
+		(135_892_000 as Weight)
+			.saturating_add((332_104_000 as Weight).saturating_mul(r as Weight))
+			.saturating_add(RocksDbWeight::get().reads(4 as Weight))
+	}
 	fn seal_set_rent_allowance(r: u32, ) -> Weight {
 		(156_911_000 as Weight)
 			.saturating_add((1_006_139_000 as Weight).saturating_mul(r as Weight))
@@ -829,6 +956,33 @@ impl WeightInfo for () {
 			.saturating_add(RocksDbWeight::get().reads((101 as Weight).saturating_mul(t as Weight)))
 			.saturating_add(RocksDbWeight::get().writes((101 as Weight).saturating_mul(t as Weight)))
 	}
+	fn seal_delegate_call(r: u32, ) -> Weight {
+		(0 as Weight)
+			.saturating_add((9_926_438_000 as Weight).saturating_mul(r as Weight))
+			.saturating_add(RocksDbWeight::get().reads(4 as Weight))
+			.saturating_add(RocksDbWeight::get().reads((100 as Weight).saturating_mul(r as Weight)))
+	}
+	fn seal_set_code_hash(r: u32, ) -> Weight {
+		(135_418_000 as Weight)
+			.saturating_add((2_617_234_000 as Weight).saturating_mul(r as Weight))
+			.saturating_add(RocksDbWeight::get().reads(4 as Weight))
+			.saturating_add(RocksDbWeight::get().reads((300 as Weight).saturating_mul(r as Weight)))
+			.saturating_add(RocksDbWeight::get().writes((300 as Weight).saturating_mul(r as Weight)))
+	}
+	fn seal_add_delegate_dependency(r: u32, ) -> Weight {
+		(131_287_000 as Weight)
+			.saturating_add((118_452_000 as Weight).saturating_mul(r as Weight))
+			.saturating_add(RocksDbWeight::get().reads(4 as Weight))
+			.saturating_add(RocksDbWeight::get().reads((3 as Weight).saturating_mul(r as Weight)))
+			.saturating_add(RocksDbWeight::get().writes((2 as Weight).saturating_mul(r as Weight)))
+	}
+	fn seal_remove_delegate_dependency(r: u32, ) -> Weight {
+		(132_015_000 as Weight)
+			.saturating_add((97_306_000 as Weight).saturating_mul(r as Weight))
+			.saturating_add(RocksDbWeight::get().reads(4 as Weight))
+			.saturating_add(RocksDbWeight::get().reads((2 as Weight).saturating_mul(r as Weight)))
+			.saturating_add(RocksDbWeight::get().writes((2 as Weight).saturating_mul(r as Weight)))
+	}
 	fn seal_instantiate(r: u32, ) -> Weight {
 		(0 as Weight)
 			.saturating_add((22_365_908_000 as Weight).saturating_mul(r as Weight))
@@ -885,6 +1039,26 @@ impl WeightInfo for () {
 			.saturating_add((153_146_000 as Weight).saturating_mul(n as Weight))
 			.saturating_add(RocksDbWeight::get().reads(4 as Weight))
 	}
+	fn seal_ecdsa_recover(r: u32, ) -> Weight {
+		(194_280_000 as Weight)
+			.saturating_add((4_610_527_000 as Weight).saturating_mul(r as Weight))
+			.saturating_add(RocksDbWeight::get().reads(4 as Weight))
+	}
+	fn seal_ecdsa_to_eth_address(r: u32, ) -> Weight {
+		(162_340_000 as Weight)
+			.saturating_add((1_289_830_000 as Weight).saturating_mul(r as Weight))
+			.saturating_add(RocksDbWeight::get().reads(4 as Weight))
+	}
+	fn seal_sr25519_verify(r: u32, ) -> Weight {
+		(174_090_000 as Weight)
+			.saturating_add((5_212_446_000 as Weight).saturating_mul(r as Weight))
+			.saturating_add(RocksDbWeight::get().reads(4 as Weight))
+	}
+	fn seal_sr25519_verify_per_kb(n: u32, ) -> Weight {
+		(5_389_718_000 as Weight)
+			.saturating_add((489_032_000 as Weight).saturating_mul(n as Weight))
+			.saturating_add(RocksDbWeight::get().reads(4 as Weight))
+	}
 	fn instr_i64const(r: u32, ) -> Weight {
 		(26_679_000 as Weight)
 			.saturating_add((3_155_000 as Weight).saturating_mul(r as Weight))
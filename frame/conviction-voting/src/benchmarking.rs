@@ -0,0 +1,227 @@
+// This file is part of Substrate.
+
+// Copyright (C) 2020 Parity Technologies (UK) Ltd.
+// SPDX-License-Identifier: Apache-2.0
+
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+// 	http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! ConvictionVoting pallet benchmarking.
+
+use super::*;
+
+use frame_benchmarking::{benchmarks, account, whitelist_account};
+use frame_support::traits::{Currency, Get};
+use frame_system::RawOrigin;
+use sp_runtime::traits::{Bounded, StaticLookup};
+
+use crate::Module as ConvictionVoting;
+
+const SEED: u32 = 0;
+
+fn funded_account<T: Config>(name: &'static str, index: u32) -> T::AccountId {
+	let caller: T::AccountId = account(name, index, SEED);
+	T::Currency::make_free_balance_be(&caller, BalanceOf::<T>::max_value());
+	caller
+}
+
+fn account_vote<T: Config>(b: BalanceOf<T>) -> AccountVote<BalanceOf<T>> {
+	let v = Vote {
+		aye: true,
+		conviction: Conviction::Locked1x,
+	};
+
+	AccountVote::Standard {
+		vote: v,
+		balance: b,
+	}
+}
+
+/// Create `n` ongoing polls in the first class, returning the class and the poll indices.
+fn create_polls<T: Config>(n: u32) -> (ClassOf<T>, Vec<PollIndexOf<T>>) {
+	let class = T::Polls::classes().into_iter().next().expect("polling system has no classes");
+	let polls = (0..n)
+		.map(|_| T::Polls::create_ongoing(class).expect("poll creation failed"))
+		.collect();
+	(class, polls)
+}
+
+fn votes_of<T: Config>(who: &T::AccountId, class: ClassOf<T>) -> usize {
+	match VotingFor::<T>::get(who, class) {
+		Voting::Casting(Casting { votes, .. }) => votes.len(),
+		Voting::Delegating(_) => 0,
+	}
+}
+
+benchmarks! {
+	_ { }
+
+	vote_new {
+		let caller = funded_account::<T>("caller", 0);
+		whitelist_account!(caller);
+		let account_vote = account_vote::<T>(100u32.into());
+
+		let (class, polls) = create_polls::<T>(T::MaxVotes::get());
+		let r = polls.len() - 1;
+		// We need to create existing votes
+		for i in polls.iter().skip(1) {
+			ConvictionVoting::<T>::vote(RawOrigin::Signed(caller.clone()).into(), *i, account_vote)?;
+		}
+		assert_eq!(votes_of::<T>(&caller, class), r, "Votes were not recorded.");
+
+		let index = polls[0];
+	}: vote(RawOrigin::Signed(caller.clone()), index, account_vote)
+	verify {
+		assert_eq!(votes_of::<T>(&caller, class), r + 1, "Vote was not recorded.");
+	}
+
+	vote_existing {
+		let caller = funded_account::<T>("caller", 0);
+		whitelist_account!(caller);
+		let old_account_vote = account_vote::<T>(100u32.into());
+
+		let (class, polls) = create_polls::<T>(T::MaxVotes::get());
+		let r = polls.len();
+		// We need to create existing votes
+		for i in polls.iter() {
+			ConvictionVoting::<T>::vote(RawOrigin::Signed(caller.clone()).into(), *i, old_account_vote)?;
+		}
+		assert_eq!(votes_of::<T>(&caller, class), r, "Votes were not recorded.");
+
+		let nay = Vote { aye: false, conviction: Conviction::Locked1x };
+		let new_account_vote = AccountVote::Standard { vote: nay, balance: 1000u32.into() };
+		let index = polls[0];
+	}: vote(RawOrigin::Signed(caller.clone()), index, new_account_vote)
+	verify {
+		assert_eq!(votes_of::<T>(&caller, class), r, "Vote was incorrectly added");
+	}
+
+	remove_vote {
+		let caller = funded_account::<T>("caller", 0);
+		whitelist_account!(caller);
+		let old_account_vote = account_vote::<T>(100u32.into());
+
+		let (class, polls) = create_polls::<T>(T::MaxVotes::get());
+		let r = polls.len();
+		// We need to create existing votes
+		for i in polls.iter() {
+			ConvictionVoting::<T>::vote(RawOrigin::Signed(caller.clone()).into(), *i, old_account_vote)?;
+		}
+		assert_eq!(votes_of::<T>(&caller, class), r, "Votes were not recorded.");
+
+		let index = polls[0];
+	}: _(RawOrigin::Signed(caller.clone()), Some(class), index)
+	verify {
+		assert_eq!(votes_of::<T>(&caller, class), r - 1, "Vote was not removed");
+	}
+
+	remove_other_vote {
+		let caller = funded_account::<T>("caller", 0);
+		let voter = funded_account::<T>("voter", 0);
+		whitelist_account!(caller);
+		let old_account_vote = account_vote::<T>(100u32.into());
+
+		let (class, polls) = create_polls::<T>(T::MaxVotes::get());
+		let r = polls.len();
+		// We need to create existing votes
+		for i in polls.iter() {
+			ConvictionVoting::<T>::vote(RawOrigin::Signed(voter.clone()).into(), *i, old_account_vote)?;
+		}
+		assert_eq!(votes_of::<T>(&voter, class), r, "Votes were not recorded.");
+
+		let index = polls[0];
+		// The aye vote loses, so its lock expires immediately.
+		assert!(T::Polls::end_ongoing(index, false).is_ok());
+	}: _(RawOrigin::Signed(caller.clone()), T::Lookup::unlookup(voter.clone()), class, index)
+	verify {
+		assert_eq!(votes_of::<T>(&voter, class), r - 1, "Vote was not removed");
+	}
+
+	delegate {
+		let r in 0 .. T::MaxVotes::get();
+
+		let voter = funded_account::<T>("voter", 0);
+		let caller = funded_account::<T>("caller", 0);
+		whitelist_account!(caller);
+		let account_vote = account_vote::<T>(100u32.into());
+
+		// The account being delegated to has voted in `r` ongoing polls.
+		let (class, polls) = create_polls::<T>(r);
+		for i in polls.iter() {
+			ConvictionVoting::<T>::vote(RawOrigin::Signed(voter.clone()).into(), *i, account_vote)?;
+		}
+		assert_eq!(votes_of::<T>(&voter, class), r as usize, "Votes were not recorded.");
+		let voter_lookup = T::Lookup::unlookup(voter);
+	}: _(RawOrigin::Signed(caller.clone()), class, voter_lookup, Conviction::Locked1x, 100u32.into())
+	verify {
+		assert!(matches!(VotingFor::<T>::get(&caller, class), Voting::Delegating(_)));
+	}
+
+	undelegate {
+		let r in 0 .. T::MaxVotes::get();
+
+		let voter = funded_account::<T>("voter", 0);
+		let caller = funded_account::<T>("caller", 0);
+		whitelist_account!(caller);
+		let account_vote = account_vote::<T>(100u32.into());
+
+		let (class, polls) = create_polls::<T>(r);
+		ConvictionVoting::<T>::delegate(
+			RawOrigin::Signed(caller.clone()).into(),
+			class,
+			T::Lookup::unlookup(voter.clone()),
+			Conviction::Locked1x,
+			100u32.into(),
+		)?;
+		// The account being delegated to has voted in `r` ongoing polls.
+		for i in polls.iter() {
+			ConvictionVoting::<T>::vote(RawOrigin::Signed(voter.clone()).into(), *i, account_vote)?;
+		}
+		assert_eq!(votes_of::<T>(&voter, class), r as usize, "Votes were not recorded.");
+	}: _(RawOrigin::Signed(caller.clone()), class)
+	verify {
+		assert!(matches!(VotingFor::<T>::get(&caller, class), Voting::Casting(_)));
+	}
+
+	unlock {
+		let caller = funded_account::<T>("caller", 0);
+		whitelist_account!(caller);
+		let small_vote = account_vote::<T>(100u32.into());
+		let big_vote = account_vote::<T>(200u32.into());
+
+		let (class, polls) = create_polls::<T>(2);
+		ConvictionVoting::<T>::vote(RawOrigin::Signed(caller.clone()).into(), polls[0], small_vote)?;
+		ConvictionVoting::<T>::vote(RawOrigin::Signed(caller.clone()).into(), polls[1], big_vote)?;
+		ConvictionVoting::<T>::remove_vote(RawOrigin::Signed(caller.clone()).into(), Some(class), polls[1])?;
+	}: _(RawOrigin::Signed(caller.clone()), class, T::Lookup::unlookup(caller.clone()))
+	verify {
+		assert_eq!(ClassLocksFor::<T>::get(&caller), vec![(class, 100u32.into())]);
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use crate::tests::{new_test_ext, Test};
+	use frame_support::assert_ok;
+
+	#[test]
+	fn test_benchmarks() {
+		new_test_ext().execute_with(|| assert_ok!(test_benchmark_vote_new::<Test>()));
+		new_test_ext().execute_with(|| assert_ok!(test_benchmark_vote_existing::<Test>()));
+		new_test_ext().execute_with(|| assert_ok!(test_benchmark_remove_vote::<Test>()));
+		new_test_ext().execute_with(|| assert_ok!(test_benchmark_remove_other_vote::<Test>()));
+		new_test_ext().execute_with(|| assert_ok!(test_benchmark_delegate::<Test>()));
+		new_test_ext().execute_with(|| assert_ok!(test_benchmark_undelegate::<Test>()));
+		new_test_ext().execute_with(|| assert_ok!(test_benchmark_unlock::<Test>()));
+	}
+}
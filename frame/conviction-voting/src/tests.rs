@@ -0,0 +1,431 @@
+// This file is part of Substrate.
+
+// Copyright (C) 2020 Parity Technologies (UK) Ltd.
+// SPDX-License-Identifier: Apache-2.0
+
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+// 	http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! The crate's tests.
+
+use std::collections::BTreeMap;
+use super::*;
+use crate as pallet_conviction_voting;
+use frame_support::{
+	impl_outer_origin, impl_outer_event, assert_noop, assert_ok, parameter_types,
+	weights::Weight,
+};
+use sp_core::H256;
+use sp_runtime::{
+	Perbill,
+	traits::{BlakeTwo256, IdentityLookup},
+	testing::Header,
+};
+
+impl_outer_origin! {
+	pub enum Origin for Test where system = frame_system {}
+}
+
+impl_outer_event! {
+	pub enum Event for Test {
+		frame_system<T>,
+		pallet_balances<T>,
+		pallet_conviction_voting<T>,
+	}
+}
+
+// Workaround for https://github.com/rust-lang/rust/issues/26925 . Remove when sorted.
+#[derive(Clone, Eq, PartialEq, Debug)]
+pub struct Test;
+parameter_types! {
+	pub const BlockHashCount: u64 = 250;
+	pub const MaximumBlockWeight: Weight = 1_000_000;
+	pub const MaximumBlockLength: u32 = 2 * 1024;
+	pub const AvailableBlockRatio: Perbill = Perbill::one();
+}
+impl frame_system::Config for Test {
+	type BaseCallFilter = ();
+	type Origin = Origin;
+	type Index = u64;
+	type BlockNumber = u64;
+	type Call = ();
+	type Hash = H256;
+	type Hashing = BlakeTwo256;
+	type AccountId = u64;
+	type Lookup = IdentityLookup<Self::AccountId>;
+	type Header = Header;
+	type Event = Event;
+	type BlockHashCount = BlockHashCount;
+	type MaximumBlockWeight = MaximumBlockWeight;
+	type DbWeight = ();
+	type BlockExecutionWeight = ();
+	type ExtrinsicBaseWeight = ();
+	type MaximumExtrinsicWeight = MaximumBlockWeight;
+	type MaximumBlockLength = MaximumBlockLength;
+	type AvailableBlockRatio = AvailableBlockRatio;
+	type Version = ();
+	type PalletInfo = ();
+	type AccountData = pallet_balances::AccountData<u64>;
+	type OnNewAccount = ();
+	type OnKilledAccount = ();
+	type SystemWeightInfo = ();
+}
+parameter_types! {
+	pub const ExistentialDeposit: u64 = 1;
+}
+impl pallet_balances::Config for Test {
+	type MaxLocks = ();
+	type Balance = u64;
+	type Event = Event;
+	type DustRemoval = ();
+	type ExistentialDeposit = ExistentialDeposit;
+	type AccountStore = System;
+	type WeightInfo = ();
+}
+
+#[derive(Clone, PartialEq, Eq, Debug)]
+pub enum TestPollState {
+	Ongoing(TallyOf<Test>, u8),
+	Completed(u64, bool),
+}
+use TestPollState::*;
+
+parameter_types! {
+	pub static Polls: BTreeMap<u8, TestPollState> = vec![
+		(1, Completed(1, true)),
+		(2, Completed(2, false)),
+		(3, Ongoing(Tally::from_parts(0, 0, 0), 0)),
+		(4, Ongoing(Tally::from_parts(0, 0, 0), 1)),
+		(5, Ongoing(Tally::from_parts(0, 0, 0), 0)),
+		(6, Ongoing(Tally::from_parts(0, 0, 0), 0)),
+		(7, Ongoing(Tally::from_parts(0, 0, 0), 0)),
+	].into_iter().collect();
+}
+
+/// A polling system whose polls are held in a thread-local map, altered directly by the tests.
+pub struct TestPolls;
+impl Polling<TallyOf<Test>> for TestPolls {
+	type Index = u8;
+	type Votes = u64;
+	type Moment = u64;
+	type Class = u8;
+
+	fn classes() -> Vec<u8> {
+		vec![0, 1, 2]
+	}
+
+	fn as_ongoing(index: u8) -> Option<(TallyOf<Test>, Self::Class)> {
+		Polls::get().remove(&index).and_then(|x| {
+			if let TestPollState::Ongoing(t, c) = x {
+				Some((t, c))
+			} else {
+				None
+			}
+		})
+	}
+
+	fn access_poll<R>(
+		index: Self::Index,
+		f: impl FnOnce(PollStatus<&mut TallyOf<Test>, u64, u8>) -> R,
+	) -> R {
+		let mut polls = Polls::get();
+		let entry = polls.get_mut(&index);
+		let r = match entry {
+			Some(Ongoing(ref mut tally_mut_ref, class)) =>
+				f(PollStatus::Ongoing(tally_mut_ref, *class)),
+			Some(Completed(when, succeeded)) => f(PollStatus::Completed(*when, *succeeded)),
+			None => f(PollStatus::None),
+		};
+		Polls::set(polls);
+		r
+	}
+
+	fn try_access_poll<R>(
+		index: Self::Index,
+		f: impl FnOnce(PollStatus<&mut TallyOf<Test>, u64, u8>) -> Result<R, DispatchError>,
+	) -> Result<R, DispatchError> {
+		let mut polls = Polls::get();
+		let entry = polls.get_mut(&index);
+		let r = match entry {
+			Some(Ongoing(ref mut tally_mut_ref, class)) =>
+				f(PollStatus::Ongoing(tally_mut_ref, *class)),
+			Some(Completed(when, succeeded)) => f(PollStatus::Completed(*when, *succeeded)),
+			None => f(PollStatus::None),
+		}?;
+		Polls::set(polls);
+		Ok(r)
+	}
+
+	#[cfg(feature = "runtime-benchmarks")]
+	fn create_ongoing(class: Self::Class) -> Result<Self::Index, ()> {
+		let mut polls = Polls::get();
+		let i = polls.keys().rev().next().map_or(0, |x| x + 1);
+		polls.insert(i, Ongoing(Tally::default(), class));
+		Polls::set(polls);
+		Ok(i)
+	}
+
+	#[cfg(feature = "runtime-benchmarks")]
+	fn end_ongoing(index: Self::Index, approved: bool) -> Result<(), ()> {
+		let mut polls = Polls::get();
+		match polls.get(&index) {
+			Some(Ongoing(..)) => {},
+			_ => return Err(()),
+		}
+		let now = frame_system::Module::<Test>::block_number();
+		polls.insert(index, Completed(now, approved));
+		Polls::set(polls);
+		Ok(())
+	}
+}
+
+parameter_types! {
+	pub const MaxVotes: u32 = 3;
+	pub const VoteLockingPeriod: u64 = 3;
+}
+impl Config for Test {
+	type Event = Event;
+	type Currency = pallet_balances::Module<Self>;
+	type Polls = TestPolls;
+	type MaxTurnout = TotalIssuanceOf<Balances, Self::AccountId>;
+	type MaxVotes = MaxVotes;
+	type VoteLockingPeriod = VoteLockingPeriod;
+	type WeightInfo = ();
+}
+
+pub fn new_test_ext() -> sp_io::TestExternalities {
+	let mut t = frame_system::GenesisConfig::default().build_storage::<Test>().unwrap();
+	pallet_balances::GenesisConfig::<Test>{
+		balances: vec![(1, 10), (2, 20), (3, 30), (4, 40), (5, 50), (6, 60)],
+	}.assimilate_storage(&mut t).unwrap();
+	let mut ext = sp_io::TestExternalities::new(t);
+	ext.execute_with(|| System::set_block_number(1));
+	ext
+}
+
+type System = frame_system::Module<Test>;
+type Balances = pallet_balances::Module<Test>;
+type ConvictionVoting = Module<Test>;
+
+fn aye(amount: u64, conviction: Conviction) -> AccountVote<u64> {
+	let vote = Vote { aye: true, conviction };
+	AccountVote::Standard { vote, balance: amount }
+}
+
+fn nay(amount: u64, conviction: Conviction) -> AccountVote<u64> {
+	let vote = Vote { aye: false, conviction };
+	AccountVote::Standard { vote, balance: amount }
+}
+
+fn split(aye: u64, nay: u64) -> AccountVote<u64> {
+	AccountVote::Split { aye, nay }
+}
+
+fn tally(index: u8) -> TallyOf<Test> {
+	<TestPolls as Polling<TallyOf<Test>>>::as_ongoing(index).expect("No poll").0
+}
+
+fn end_poll(index: u8, approved: bool) {
+	let mut polls = Polls::get();
+	polls.insert(index, Completed(System::block_number(), approved));
+	Polls::set(polls);
+}
+
+fn locked(who: u64) -> u64 {
+	Balances::locks(who).iter().map(|l| l.amount).max().unwrap_or(0)
+}
+
+#[test]
+fn params_should_work() {
+	new_test_ext().execute_with(|| {
+		assert_eq!(Balances::free_balance(42), 0);
+		assert_eq!(Balances::total_issuance(), 210);
+	});
+}
+
+#[test]
+fn basic_voting_works() {
+	new_test_ext().execute_with(|| {
+		assert_ok!(ConvictionVoting::vote(Origin::signed(1), 3, aye(2, Conviction::Locked2x)));
+		assert_eq!(tally(3), Tally::from_parts(4, 0, 2));
+		assert_ok!(ConvictionVoting::vote(Origin::signed(1), 3, nay(5, Conviction::Locked2x)));
+		assert_eq!(tally(3), Tally::from_parts(0, 10, 0));
+		assert_eq!(locked(1), 5);
+		assert_ok!(ConvictionVoting::vote(Origin::signed(1), 3, aye(10, Conviction::None)));
+		assert_eq!(tally(3), Tally::from_parts(1, 0, 10));
+		assert_eq!(locked(1), 10);
+
+		assert_ok!(ConvictionVoting::remove_vote(Origin::signed(1), None, 3));
+		assert_eq!(tally(3), Tally::from_parts(0, 0, 0));
+		// The lock remains until explicitly removed.
+		assert_eq!(locked(1), 10);
+		assert_ok!(ConvictionVoting::unlock(Origin::signed(1), 0, 1));
+		assert_eq!(locked(1), 0);
+	});
+}
+
+#[test]
+fn split_voting_works() {
+	new_test_ext().execute_with(|| {
+		assert_ok!(ConvictionVoting::vote(Origin::signed(1), 3, split(10, 0)));
+		assert_eq!(tally(3), Tally::from_parts(1, 0, 10));
+		assert_ok!(ConvictionVoting::vote(Origin::signed(1), 3, split(5, 5)));
+		assert_eq!(tally(3), Tally::from_parts(0, 0, 5));
+		assert_eq!(locked(1), 10);
+
+		assert_ok!(ConvictionVoting::remove_vote(Origin::signed(1), None, 3));
+		assert_eq!(tally(3), Tally::from_parts(0, 0, 0));
+	});
+}
+
+#[test]
+fn winning_conviction_votes_stay_locked() {
+	new_test_ext().execute_with(|| {
+		assert_ok!(ConvictionVoting::vote(Origin::signed(1), 3, aye(5, Conviction::Locked1x)));
+		assert_ok!(ConvictionVoting::vote(Origin::signed(2), 3, nay(10, Conviction::Locked1x)));
+		end_poll(3, true);
+
+		assert_ok!(ConvictionVoting::remove_vote(Origin::signed(1), Some(0), 3));
+		assert_ok!(ConvictionVoting::remove_vote(Origin::signed(2), Some(0), 3));
+		assert_ok!(ConvictionVoting::unlock(Origin::signed(1), 0, 1));
+		assert_ok!(ConvictionVoting::unlock(Origin::signed(2), 0, 2));
+		// The winning voter remains locked for one voting lock period.
+		assert_eq!(locked(1), 5);
+		assert_eq!(locked(2), 0);
+
+		System::set_block_number(3);
+		assert_ok!(ConvictionVoting::unlock(Origin::signed(1), 0, 1));
+		assert_eq!(locked(1), 5);
+		System::set_block_number(4);
+		assert_ok!(ConvictionVoting::unlock(Origin::signed(1), 0, 1));
+		assert_eq!(locked(1), 0);
+	});
+}
+
+#[test]
+fn remove_other_vote_works() {
+	new_test_ext().execute_with(|| {
+		assert_ok!(ConvictionVoting::vote(Origin::signed(2), 3, aye(10, Conviction::Locked1x)));
+		assert_noop!(
+			ConvictionVoting::remove_other_vote(Origin::signed(1), 2, 0, 3),
+			Error::<Test>::NoPermission,
+		);
+		end_poll(3, true);
+		assert_noop!(
+			ConvictionVoting::remove_other_vote(Origin::signed(1), 2, 0, 3),
+			Error::<Test>::NoPermissionYet,
+		);
+		System::set_block_number(4);
+		assert_ok!(ConvictionVoting::remove_other_vote(Origin::signed(1), 2, 0, 3));
+		assert_ok!(ConvictionVoting::unlock(Origin::signed(1), 0, 2));
+		assert_eq!(locked(2), 0);
+	});
+}
+
+#[test]
+fn delegation_works() {
+	new_test_ext().execute_with(|| {
+		assert_ok!(ConvictionVoting::delegate(Origin::signed(2), 0, 1, Conviction::Locked1x, 20));
+		assert_eq!(locked(2), 20);
+		assert_ok!(ConvictionVoting::vote(Origin::signed(1), 3, aye(10, Conviction::None)));
+		assert_eq!(tally(3), Tally::from_parts(21, 0, 30));
+		// Delegations only count towards polls of the delegated class.
+		assert_ok!(ConvictionVoting::vote(Origin::signed(1), 4, aye(10, Conviction::None)));
+		assert_eq!(tally(4), Tally::from_parts(1, 0, 10));
+		// A delegating account may not vote in that class.
+		assert_noop!(
+			ConvictionVoting::vote(Origin::signed(2), 5, aye(10, Conviction::None)),
+			Error::<Test>::AlreadyDelegating,
+		);
+
+		assert_ok!(ConvictionVoting::undelegate(Origin::signed(2), 0));
+		assert_eq!(tally(3), Tally::from_parts(1, 0, 10));
+		assert_noop!(ConvictionVoting::undelegate(Origin::signed(2), 0), Error::<Test>::NotDelegating);
+		// The delegated balance remains locked for the conviction's lock period.
+		assert_ok!(ConvictionVoting::unlock(Origin::signed(2), 0, 2));
+		assert_eq!(locked(2), 20);
+		System::set_block_number(4);
+		assert_ok!(ConvictionVoting::unlock(Origin::signed(2), 0, 2));
+		assert_eq!(locked(2), 0);
+	});
+}
+
+#[test]
+fn bad_delegations_are_rejected() {
+	new_test_ext().execute_with(|| {
+		assert_noop!(
+			ConvictionVoting::delegate(Origin::signed(1), 0, 1, Conviction::None, 10),
+			Error::<Test>::Nonsense,
+		);
+		assert_noop!(
+			ConvictionVoting::delegate(Origin::signed(1), 9, 2, Conviction::None, 10),
+			Error::<Test>::BadClass,
+		);
+		assert_noop!(
+			ConvictionVoting::delegate(Origin::signed(1), 0, 2, Conviction::None, 11),
+			Error::<Test>::InsufficientFunds,
+		);
+		assert_ok!(ConvictionVoting::vote(Origin::signed(3), 3, aye(10, Conviction::None)));
+		assert_noop!(
+			ConvictionVoting::delegate(Origin::signed(3), 0, 1, Conviction::None, 10),
+			Error::<Test>::AlreadyVoting,
+		);
+		// Voting in one class doesn't prevent delegation in another.
+		assert_ok!(ConvictionVoting::delegate(Origin::signed(3), 1, 1, Conviction::None, 10));
+	});
+}
+
+#[test]
+fn classes_are_locked_separately() {
+	new_test_ext().execute_with(|| {
+		assert_ok!(ConvictionVoting::vote(Origin::signed(1), 3, aye(5, Conviction::Locked1x)));
+		assert_ok!(ConvictionVoting::vote(Origin::signed(1), 4, aye(10, Conviction::Locked1x)));
+		assert_eq!(locked(1), 10);
+		assert_eq!(ClassLocksFor::<Test>::get(1), vec![(0, 5), (1, 10)]);
+
+		assert_ok!(ConvictionVoting::remove_vote(Origin::signed(1), None, 4));
+		assert_ok!(ConvictionVoting::unlock(Origin::signed(1), 1, 1));
+		assert_eq!(locked(1), 5);
+		assert_eq!(ClassLocksFor::<Test>::get(1), vec![(0, 5)]);
+
+		assert_ok!(ConvictionVoting::remove_vote(Origin::signed(1), None, 3));
+		assert_ok!(ConvictionVoting::unlock(Origin::signed(1), 0, 1));
+		assert_eq!(locked(1), 0);
+		assert!(ClassLocksFor::<Test>::get(1).is_empty());
+	});
+}
+
+#[test]
+fn bad_votes_are_rejected() {
+	new_test_ext().execute_with(|| {
+		assert_noop!(
+			ConvictionVoting::vote(Origin::signed(1), 1, aye(10, Conviction::None)),
+			Error::<Test>::NotOngoing,
+		);
+		assert_noop!(
+			ConvictionVoting::vote(Origin::signed(1), 3, aye(11, Conviction::None)),
+			Error::<Test>::InsufficientFunds,
+		);
+		assert_ok!(ConvictionVoting::vote(Origin::signed(1), 3, aye(10, Conviction::None)));
+		assert_ok!(ConvictionVoting::vote(Origin::signed(1), 5, aye(10, Conviction::None)));
+		assert_ok!(ConvictionVoting::vote(Origin::signed(1), 6, aye(10, Conviction::None)));
+		assert_noop!(
+			ConvictionVoting::vote(Origin::signed(1), 7, aye(10, Conviction::None)),
+			Error::<Test>::MaxVotesReached,
+		);
+		// The limit applies per class.
+		assert_ok!(ConvictionVoting::vote(Origin::signed(1), 4, aye(10, Conviction::None)));
+
+		assert_noop!(ConvictionVoting::remove_vote(Origin::signed(1), None, 1), Error::<Test>::ClassNeeded);
+		assert_noop!(ConvictionVoting::remove_vote(Origin::signed(2), None, 3), Error::<Test>::NotVoter);
+	});
+}
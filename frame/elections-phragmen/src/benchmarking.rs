@@ -68,20 +68,6 @@ fn candidate_count<T: Config>() -> u32 {
 	<Candidates<T>>::decode_len().unwrap_or(0usize) as u32
 }
 
-/// Get the number of votes of a voter.
-fn vote_count_of<T: Config>(who: &T::AccountId) -> u32 {
-	<Voting<T>>::get(who).1.len() as u32
-}
-
-/// A `DefunctVoter` struct with correct value
-fn defunct_for<T: Config>(who: T::AccountId) -> DefunctVoter<Lookup<T>> {
-	DefunctVoter {
-		who: as_lookup::<T>(who.clone()),
-		candidate_count: candidate_count::<T>(),
-		vote_count: vote_count_of::<T>(&who),
-	}
-}
-
 /// Add `c` new candidates.
 fn submit_candidates<T: Config>(c: u32, prefix: &'static str)
 	-> Result<Vec<T::AccountId>, &'static str>
@@ -151,8 +137,8 @@ fn fill_seats_up_to<T: Config>(m: u32) -> Result<Vec<T::AccountId>, &'static str
 	Ok(
 		<Elections<T>>::members()
 			.into_iter()
-			.map(|(x, _)| x)
-			.chain(<Elections<T>>::runners_up().into_iter().map(|(x, _)| x))
+			.map(|m| m.who)
+			.chain(<Elections<T>>::runners_up().into_iter().map(|r| r.who))
 			.collect()
 	)
 }
@@ -170,7 +156,7 @@ benchmarks! {
 
 	// -- Signed ones
 	vote {
-		let v in 1 .. (MAXIMUM_VOTE as u32);
+		let v in 1 .. T::MaxVotesPerVoter::get();
 		clean::<T>();
 
 		// create a bunch of candidates.
@@ -186,7 +172,7 @@ benchmarks! {
 	}: _(RawOrigin::Signed(caller), votes, stake)
 
 	vote_update {
-		let v in 1 .. (MAXIMUM_VOTE as u32);
+		let v in 1 .. T::MaxVotesPerVoter::get();
 		clean::<T>();
 
 		// create a bunch of candidates.
@@ -207,7 +193,7 @@ benchmarks! {
 
 	remove_voter {
 		// we fix the number of voted candidates to max
-		let v = MAXIMUM_VOTE as u32;
+		let v = T::MaxVotesPerVoter::get();
 		clean::<T>();
 
 		// create a bunch of candidates.
@@ -221,126 +207,9 @@ benchmarks! {
 		whitelist!(caller);
 	}: _(RawOrigin::Signed(caller))
 
-	report_defunct_voter_correct {
-		// number of already existing candidates that may or may not be voted by the reported
-		// account.
-		let c in 1 .. MAX_CANDIDATES;
-		// number of candidates that the reported voter voted for. The worse case of search here is
-		// basically `c * v`.
-		let v in 1 .. (MAXIMUM_VOTE as u32);
-		// we fix the number of members to the number of desired members and runners-up. We'll be in
-		// this state almost always.
-		let m = T::DesiredMembers::get() + T::DesiredRunnersUp::get();
-
-		clean::<T>();
-		let stake = default_stake::<T>(BALANCE_FACTOR);
-
-		// create m members and runners combined.
-		let _ = fill_seats_up_to::<T>(m)?;
-
-		// create a bunch of candidates as well.
-		let bailing_candidates = submit_candidates::<T>(v, "bailing_candidates")?;
-		let all_candidates = submit_candidates::<T>(c, "all_candidates")?;
-
-		// account 1 is the reporter and must be whitelisted, and a voter.
-		let account_1 = endowed_account::<T>("caller", 0);
-		submit_voter::<T>(
-			account_1.clone(),
-			all_candidates.iter().take(1).cloned().collect(),
-			stake,
-		)?;
-
-		// account 2 votes for all of the mentioned candidates.
-		let account_2 = endowed_account::<T>("caller_2", 1);
-		submit_voter::<T>(
-			account_2.clone(),
-			bailing_candidates.clone(),
-			stake,
-		)?;
-
-		// all the bailers go away. NOTE: we can simplify this. There's no need to create all these
-		// candidates and remove them. The defunct voter can just vote for random accounts as long
-		// as there are enough members (potential candidates).
-		bailing_candidates.into_iter().for_each(|b| {
-			let count = candidate_count::<T>();
-			assert!(<Elections<T>>::renounce_candidacy(
-				RawOrigin::Signed(b).into(),
-				Renouncing::Candidate(count),
-			).is_ok());
-		});
-
-		let defunct_info = defunct_for::<T>(account_2.clone());
-		whitelist!(account_1);
-
-		assert!(<Elections<T>>::is_voter(&account_2));
-	}: report_defunct_voter(RawOrigin::Signed(account_1.clone()), defunct_info)
-	verify {
-		assert!(!<Elections<T>>::is_voter(&account_2));
-		#[cfg(test)]
-		{
-			// reset members in between benchmark tests.
-			use crate::tests::MEMBERS;
-			MEMBERS.with(|m| *m.borrow_mut() = vec![]);
-		}
-	}
-
-	report_defunct_voter_incorrect {
-		// number of already existing candidates that may or may not be voted by the reported
-		// account.
-		let c in 1 .. MAX_CANDIDATES;
-		// number of candidates that the reported voter voted for. The worse case of search here is
-		// basically `c * v`.
-		let v in 1 .. (MAXIMUM_VOTE as u32);
-		// we fix the number of members to the number of desired members and runners-up. We'll be in
-		// this state almost always.
-		let m = T::DesiredMembers::get() + T::DesiredRunnersUp::get();
-
-		clean::<T>();
-		let stake = default_stake::<T>(BALANCE_FACTOR);
-
-		// create m members and runners combined.
-		let _ = fill_seats_up_to::<T>(m)?;
-
-		// create a bunch of candidates as well.
-		let all_candidates = submit_candidates::<T>(c, "candidates")?;
-
-		// account 1 is the reporter and need to be whitelisted, and a voter.
-		let account_1 = endowed_account::<T>("caller", 0);
-		submit_voter::<T>(
-			account_1.clone(),
-			all_candidates.iter().take(1).cloned().collect(),
-			stake,
-		)?;
-
-		// account 2 votes for a bunch of crap, and finally a correct candidate.
-		let account_2 = endowed_account::<T>("caller_2", 1);
-		let mut invalid: Vec<T::AccountId> = (0..(v-1))
-			.map(|seed| account::<T::AccountId>("invalid", 0, seed).clone())
-			.collect();
-		invalid.push(all_candidates.last().unwrap().clone());
-		submit_voter::<T>(
-			account_2.clone(),
-			invalid,
-			stake,
-		)?;
-
-		let defunct_info = defunct_for::<T>(account_2.clone());
-		whitelist!(account_1);
-	}: report_defunct_voter(RawOrigin::Signed(account_1.clone()), defunct_info)
-	verify {
-		// account 2 is still a voter.
-		assert!(<Elections<T>>::is_voter(&account_2));
-		#[cfg(test)]
-		{
-			// reset members in between benchmark tests.
-			use crate::tests::MEMBERS;
-			MEMBERS.with(|m| *m.borrow_mut() = vec![]);
-		}
-	}
-
 	submit_candidacy {
 		// number of already existing candidates.
-		let c in 1 .. MAX_CANDIDATES;
+		let c in 1 .. MAX_CANDIDATES.min(T::MaxCandidates::get() - 1);
 		// we fix the number of members to the number of desired members and runners-up. We'll be in
 		// this state almost always.
 		let m = T::DesiredMembers::get() + T::DesiredRunnersUp::get();
@@ -460,7 +329,7 @@ benchmarks! {
 		let replacements = submit_candidates_with_self_vote::<T>(c, "new_candidate")?;
 
 		// create some voters for these replacements.
-		distribute_voters::<T>(replacements, MAX_VOTERS, MAXIMUM_VOTE)?;
+		distribute_voters::<T>(replacements, MAX_VOTERS, T::MaxVotesPerVoter::get() as usize)?;
 
 		let to_remove = as_lookup::<T>(all_members[0].clone());
 	}: remove_member(RawOrigin::Root, to_remove, false)
@@ -531,7 +400,7 @@ benchmarks! {
 		// create c candidates.
 		let all_candidates = submit_candidates_with_self_vote::<T>(c, "candidates")?;
 		// create 500 voters, each voting the maximum 16
-		distribute_voters::<T>(all_candidates, MAX_VOTERS, MAXIMUM_VOTE)?;
+		distribute_voters::<T>(all_candidates, MAX_VOTERS, T::MaxVotesPerVoter::get() as usize)?;
 	}: {
 		// elect
 		<Elections<T>>::on_initialize(T::TermDuration::get());
@@ -551,19 +420,54 @@ benchmarks! {
 		}
 	}
 
-	#[extra]
-	phragmen {
-		// This is just to focus on phragmen in the context of this module. We always select 20
-		// members, this is hard-coded in the runtime and cannot be trivially changed at this stage.
-		// Yet, change the number of voters, candidates and edge per voter to see the impact. Note
-		// that we give all candidates a self vote to make sure they are all considered.
+	clean_defunct_voters {
+		// total number of voters.
+		let v in (MAX_VOTERS / 2) .. MAX_VOTERS;
+		// those that are defunct and need removal.
+		let d in 1 .. (MAX_VOTERS / 2);
+		clean::<T>();
+
+		// create a bunch of candidates, and voters who vote for all of them.
+		let votes_per_voter = T::MaxVotesPerVoter::get();
+		let all_candidates = submit_candidates::<T>(votes_per_voter, "candidates")?;
+		distribute_voters::<T>(all_candidates, v, votes_per_voter as usize)?;
+
+		// all candidates leave, so that all voters become defunct.
+		<Candidates<T>>::kill();
+		assert!(
+			<Voting<T>>::iter().all(|(_, voter)| <Elections<T>>::is_defunct_voter(&voter.votes))
+		);
+		assert_eq!(<Voting<T>>::iter().count() as u32, v);
+	}: _(RawOrigin::Root, v, d)
+	verify {
+		assert_eq!(<Voting<T>>::iter().count() as u32, v - d);
+	}
+
+	election_phragmen {
+		// This is just to focus on phragmen in the context of this module. We always select the
+		// desired members and runners-up. Change the number of candidates, voters and edges (i.e.
+		// votes) to see the impact. Note that we give all candidates a self vote to make sure they
+		// are all considered.
 		let c in 1 .. MAX_CANDIDATES;
 		let v in 1 .. MAX_VOTERS;
-		let e in 1 .. (MAXIMUM_VOTE as u32);
+		let e in MAX_VOTERS .. MAX_VOTERS * T::MaxVotesPerVoter::get();
 		clean::<T>();
 
+		// so we have a situation with v and e. we want e to basically always be in the range of
+		// `e -> e * MaxVotesPerVoter`, but we cannot express that now with the benchmarks. So
+		// what we do is: when c is being iterated, v, and e are max and fine. when v is being
+		// iterated, e is being set to max and this is a problem. In these cases, we cap e to a
+		// lower value, namely v * MaxVotesPerVoter. when e is being iterated, v is at max, and
+		// again fine. all in all, votes_per_voter can never be more than MaxVotesPerVoter. Note
+		// that this might cause `v` to be an overestimate.
+		let votes_per_voter = (e / v).min(T::MaxVotesPerVoter::get());
+
 		let all_candidates = submit_candidates_with_self_vote::<T>(c, "candidates")?;
-		let _ = distribute_voters::<T>(all_candidates, v, e as usize)?;
+		let _ = distribute_voters::<T>(
+			all_candidates,
+			v.saturating_sub(c),
+			votes_per_voter as usize,
+		)?;
 	}: {
 		<Elections<T>>::on_initialize(T::TermDuration::get());
 	}
@@ -599,14 +503,6 @@ mod tests {
 			assert_ok!(test_benchmark_remove_voter::<Test>());
 		});
 
-		ExtBuilder::default().desired_members(13).desired_runners_up(7).build_and_execute(|| {
-			assert_ok!(test_benchmark_report_defunct_voter_correct::<Test>());
-		});
-
-		ExtBuilder::default().desired_members(13).desired_runners_up(7).build_and_execute(|| {
-			assert_ok!(test_benchmark_report_defunct_voter_incorrect::<Test>());
-		});
-
 		ExtBuilder::default().desired_members(13).desired_runners_up(7).build_and_execute(|| {
 			assert_ok!(test_benchmark_submit_candidacy::<Test>());
 		});
@@ -636,7 +532,11 @@ mod tests {
 		});
 
 		ExtBuilder::default().desired_members(13).desired_runners_up(7).build_and_execute(|| {
-			assert_ok!(test_benchmark_phragmen::<Test>());
+			assert_ok!(test_benchmark_clean_defunct_voters::<Test>());
+		});
+
+		ExtBuilder::default().desired_members(13).desired_runners_up(7).build_and_execute(|| {
+			assert_ok!(test_benchmark_election_phragmen::<Test>());
 		});
 	}
 }
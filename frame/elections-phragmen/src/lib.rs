@@ -31,6 +31,13 @@
 //! Based on the current block number and `TermDuration`, the condition `BlockNumber % TermDuration
 //! == 0` being satisfied will always trigger a new election round.
 //!
+//! ### Bounds
+//!
+//! The election happens on-chain, so its size is bounded by the runtime: at most `MaxCandidates`
+//! candidates may submit their candidacy in a round, a voter may vote for at most
+//! `MaxVotesPerVoter` candidates, and at most `MaxVoters` voters are taken into account in each
+//! election. The weight of the election is accounted for in the block in which it happens.
+//!
 //! ### Voting
 //!
 //! Voters can vote for any set of the candidates by providing a list of account ids. Invalid votes
@@ -42,12 +49,9 @@
 //! further rounds. A voter is responsible for calling `remove_voter` once they are done to have
 //! their bond back and remove the lock.
 //!
-//! Voters also report other voters as being defunct to earn their bond. A voter is defunct once all
-//! of the candidates that they have voted for are neither a valid candidate anymore nor a member.
-//! Upon reporting, if the target voter is actually defunct, the reporter will be rewarded by the
-//! voting bond of the target. The target will lose their bond and get removed. If the target is not
-//! defunct, the reporter is slashed and removed. To prevent being reported, voters should manually
-//! submit a `remove_voter()` as soon as they are in the defunct state.
+//! A voter is defunct once all of the candidates that they have voted for are neither a valid
+//! candidate anymore nor a member. Defunct voters can be removed in bulk by root through
+//! `clean_defunct_voters`, in which case their bond is returned.
 //!
 //! ### Candidacy and Members
 //!
@@ -64,6 +68,10 @@
 //!     _outgoing member or runner_, meaning that they are an active member who failed to keep their
 //!     spot. An outgoing will always lose their bond.
 //!
+//! The bond of each voter, candidate, member and runner-up is recorded alongside them at the time
+//! it is reserved, so that a change of `VotingBond` or `CandidacyBond` never causes more or less
+//! than the reserved amount to be returned or slashed.
+//!
 //! ##### Renouncing candidacy.
 //!
 //! All candidates, elected or not, can renounce their candidacy. A call to [`Module::renounce_candidacy`]
@@ -90,7 +98,7 @@ use frame_support::{
 	ensure,
 	storage::{IterableStorageMap, StorageMap},
 	traits::{
-		BalanceStatus, ChangeMembers, Contains, ContainsLengthBound, Currency, CurrencyToVote, Get,
+		ChangeMembers, Contains, ContainsLengthBound, Currency, CurrencyToVote, Get,
 		InitializeMembers, LockIdentifier, LockableCurrency, OnUnbalanced, ReservableCurrency,
 		WithdrawReasons,
 	},
@@ -105,12 +113,10 @@ use sp_runtime::{
 use sp_std::prelude::*;
 
 mod benchmarking;
+pub mod migrations;
 pub mod weights;
 pub use weights::WeightInfo;
 
-/// The maximum votes allowed per voter.
-pub const MAXIMUM_VOTE: usize = 16;
-
 type BalanceOf<T> =
 	<<T as Config>::Currency as Currency<<T as frame_system::Config>::AccountId>>::Balance;
 type NegativeImbalanceOf<T> =
@@ -127,17 +133,46 @@ pub enum Renouncing {
 	Candidate(#[codec(compact)] u32),
 }
 
-/// Information needed to prove the defunct-ness of a voter.
-#[derive(Encode, Decode, Clone, PartialEq, RuntimeDebug)]
-pub struct DefunctVoter<AccountId> {
-	/// the voter's who's being challenged for being defunct
+/// An active voter.
+#[derive(Encode, Decode, Clone, Default, PartialEq, RuntimeDebug)]
+pub struct Voter<AccountId, Balance> {
+	/// The members being backed.
+	pub votes: Vec<AccountId>,
+	/// The amount of stake placed on this vote.
+	pub stake: Balance,
+	/// The amount of deposit reserved for this vote.
+	///
+	/// To be unreserved upon removal.
+	pub deposit: Balance,
+}
+
+/// A holder of a seat as either a member or a runner-up.
+#[derive(Encode, Decode, Clone, Default, PartialEq, RuntimeDebug)]
+pub struct SeatHolder<AccountId, Balance> {
+	/// The holder.
 	pub who: AccountId,
-	/// The number of votes that `who` has placed.
-	#[codec(compact)]
-	pub vote_count: u32,
-	/// The number of current active candidates.
-	#[codec(compact)]
-	pub candidate_count: u32
+	/// The total backing stake.
+	pub stake: Balance,
+	/// The amount of deposit held on-chain.
+	///
+	/// To be unreserved upon renouncing, or slashed upon being a loser.
+	pub deposit: Balance,
+}
+
+// A value placed in storage that represents the current version of the elections-phragmen
+// storage. This value is used by the `on_runtime_upgrade` logic to determine whether we run
+// storage migration logic.
+#[derive(Encode, Decode, Clone, Copy, PartialEq, Eq, RuntimeDebug)]
+enum Releases {
+	V2_0_0,
+	V3_0_0, // recorded deposits of voters, candidates and seat holders.
+}
+
+impl Default for Releases {
+	fn default() -> Self {
+		// chains which predate the introduction of this value have the `V2_0_0` layout.
+		Releases::V2_0_0
+	}
 }
 
 pub trait Config: frame_system::Config {
@@ -171,9 +206,6 @@ pub trait Config: frame_system::Config {
 	/// Handler for the unbalanced reduction when a candidate has lost (and is not a runner-up)
 	type LoserCandidate: OnUnbalanced<NegativeImbalanceOf<Self>>;
 
-	/// Handler for the unbalanced reduction when a reporter has submitted a bad defunct report.
-	type BadReport: OnUnbalanced<NegativeImbalanceOf<Self>>;
-
 	/// Handler for the unbalanced reduction when a member has been kicked.
 	type KickedMember: OnUnbalanced<NegativeImbalanceOf<Self>>;
 
@@ -188,6 +220,21 @@ pub trait Config: frame_system::Config {
 	/// be in passive mode.
 	type TermDuration: Get<Self::BlockNumber>;
 
+	/// The maximum number of candidates in a phragmen election.
+	///
+	/// The election happens on-chain, so this value bounds its size. Once this many candidates
+	/// exist, no more candidacies are accepted until the next round.
+	type MaxCandidates: Get<u32>;
+
+	/// The maximum number of voters to take into account in a phragmen election.
+	///
+	/// The election happens on-chain, so this value bounds its size. Any voters beyond this
+	/// number are ignored in the election.
+	type MaxVoters: Get<u32>;
+
+	/// The maximum number of candidates that a single voter may vote for.
+	type MaxVotesPerVoter: Get<u32>;
+
 	/// Weight information for extrinsics in this pallet.
 	type WeightInfo: WeightInfo;
 }
@@ -196,20 +243,26 @@ decl_storage! {
 	trait Store for Module<T: Config> as PhragmenElection {
 		// ---- State
 		/// The current elected membership. Sorted based on account id.
-		pub Members get(fn members): Vec<(T::AccountId, BalanceOf<T>)>;
+		pub Members get(fn members): Vec<SeatHolder<T::AccountId, BalanceOf<T>>>;
 		/// The current runners_up. Sorted based on low to high merit (worse to best).
-		pub RunnersUp get(fn runners_up): Vec<(T::AccountId, BalanceOf<T>)>;
+		pub RunnersUp get(fn runners_up): Vec<SeatHolder<T::AccountId, BalanceOf<T>>>;
 		/// The total number of vote rounds that have happened, excluding the upcoming one.
 		pub ElectionRounds get(fn election_rounds): u32 = Zero::zero();
 
-		/// Votes and locked stake of a particular voter.
+		/// Votes, locked stake and reserved deposit of a particular voter.
 		///
 		/// TWOX-NOTE: SAFE as `AccountId` is a crypto hash
-		pub Voting get(fn voting): map hasher(twox_64_concat) T::AccountId => (BalanceOf<T>, Vec<T::AccountId>);
+		pub Voting get(fn voting): map hasher(twox_64_concat) T::AccountId => Voter<T::AccountId, BalanceOf<T>>;
 
-		/// The present candidate list. Sorted based on account-id. A current member or runner-up
-		/// can never enter this vector and is always implicitly assumed to be a candidate.
-		pub Candidates get(fn candidates): Vec<T::AccountId>;
+		/// The present candidate list, along with the deposit of each. Sorted based on account-id.
+		/// A current member or runner-up can never enter this vector and is always implicitly
+		/// assumed to be a candidate.
+		pub Candidates get(fn candidates): Vec<(T::AccountId, BalanceOf<T>)>;
+
+		/// Storage version of the pallet.
+		///
+		/// This is set to v3.0.0 for new networks.
+		StorageVersion build(|_: &GenesisConfig<T>| Releases::V3_0_0): Releases;
 	} add_extra_genesis {
 		config(members): Vec<(T::AccountId, BalanceOf<T>)>;
 		build(|config: &GenesisConfig<T>| {
@@ -221,16 +274,20 @@ decl_storage! {
 				);
 
 				// reserve candidacy bond and set as members.
-				T::Currency::reserve(&member, T::CandidacyBond::get())
+				let deposit = T::CandidacyBond::get();
+				T::Currency::reserve(&member, deposit)
 					.expect("Genesis member does not have enough balance to be a candidate");
 
 				// Note: all members will only vote for themselves, hence they must be given exactly
 				// their own stake as total backing. Any sane election should behave as such.
 				// Nonetheless, stakes will be updated for term 1 onwards according to the election.
 				Members::<T>::mutate(|members| {
-					match members.binary_search_by(|(a, _b)| a.cmp(member)) {
+					match members.binary_search_by(|m| m.who.cmp(member)) {
 						Ok(_) => panic!("Duplicate member in elections phragmen genesis: {}", member),
-						Err(pos) => members.insert(pos, (member.clone(), *stake)),
+						Err(pos) => members.insert(
+							pos,
+							SeatHolder { who: member.clone(), stake: *stake, deposit },
+						),
 					}
 				});
 
@@ -266,10 +323,10 @@ decl_error! {
 		UnableToPayBond,
 		/// Must be a voter.
 		MustBeVoter,
-		/// Cannot report self.
-		ReportSelf,
 		/// Duplicated candidate submission.
 		DuplicatedCandidate,
+		/// Too many candidates have been created.
+		TooManyCandidates,
 		/// Member cannot re-submit candidacy.
 		MemberSubmit,
 		/// Runner cannot re-submit candidacy.
@@ -280,8 +337,6 @@ decl_error! {
 		NotMember,
 		/// The provided count of number of candidates is incorrect.
 		InvalidCandidateCount,
-		/// The provided count of number of votes is incorrect.
-		InvalidVoteCount,
 		/// The renouncing origin presented a wrong `Renouncing` parameter.
 		InvalidRenouncing,
 		/// Prediction regarding replacement after member removal is wrong.
@@ -301,17 +356,30 @@ decl_module! {
 		const DesiredRunnersUp: u32 = T::DesiredRunnersUp::get();
 		const TermDuration: T::BlockNumber = T::TermDuration::get();
 		const ModuleId: LockIdentifier  = T::ModuleId::get();
+		const MaxCandidates: u32 = T::MaxCandidates::get();
+		const MaxVoters: u32 = T::MaxVoters::get();
+		const MaxVotesPerVoter: u32 = T::MaxVotesPerVoter::get();
+
+		fn on_runtime_upgrade() -> Weight {
+			if StorageVersion::get() == Releases::V2_0_0 {
+				migrations::v3::migrate::<T>()
+			} else {
+				0
+			}
+		}
 
 		/// Vote for a set of candidates for the upcoming round of election. This can be called to
 		/// set the initial votes, or update already existing votes.
 		///
 		/// Upon initial voting, `value` units of `who`'s balance is locked and a bond amount is
-		/// reserved.
+		/// reserved. The reserved amount is recorded and returned in full upon removal of the
+		/// voter.
 		///
 		/// The `votes` should:
 		///   - not be empty.
 		///   - be less than the number of possible candidates. Note that all current members and
 		///     runners-up are also automatically candidates for the next round.
+		///   - be no more than `MaxVotesPerVoter`.
 		///
 		/// It is the responsibility of the caller to not place all of their balance into the lock
 		/// and keep some for further transactions.
@@ -336,7 +404,10 @@ decl_module! {
 		) {
 			let who = ensure_signed(origin)?;
 
-			ensure!(votes.len() <= MAXIMUM_VOTE, Error::<T>::MaximumVotesExceeded);
+			ensure!(
+				votes.len() <= T::MaxVotesPerVoter::get() as usize,
+				Error::<T>::MaximumVotesExceeded,
+			);
 			ensure!(!votes.is_empty(), Error::<T>::NoVotes);
 
 			let candidates_count = <Candidates<T>>::decode_len().unwrap_or(0);
@@ -351,11 +422,14 @@ decl_module! {
 
 			ensure!(value > T::Currency::minimum_balance(), Error::<T>::LowBalance);
 
-			// first time voter. Reserve bond.
-			if !Self::is_voter(&who) {
-				T::Currency::reserve(&who, T::VotingBond::get())
-					.map_err(|_| Error::<T>::UnableToPayBond)?;
-			}
+			// first time voter. Reserve bond. Otherwise keep the deposit already reserved.
+			let deposit = if Self::is_voter(&who) {
+				Voting::<T>::get(&who).deposit
+			} else {
+				let deposit = T::VotingBond::get();
+				T::Currency::reserve(&who, deposit).map_err(|_| Error::<T>::UnableToPayBond)?;
+				deposit
+			};
 
 			// Amount to be locked up.
 			let locked_balance = value.min(T::Currency::total_balance(&who));
@@ -368,7 +442,7 @@ decl_module! {
 				WithdrawReasons::except(WithdrawReasons::TRANSACTION_PAYMENT),
 			);
 
-			Voting::<T>::insert(&who, (locked_balance, votes));
+			Voting::<T>::insert(&who, Voter { votes, stake: locked_balance, deposit });
 		}
 
 		/// Remove `origin` as a voter. This removes the lock and returns the bond.
@@ -389,89 +463,7 @@ decl_module! {
 			let who = ensure_signed(origin)?;
 			ensure!(Self::is_voter(&who), Error::<T>::MustBeVoter);
 
-			Self::do_remove_voter(&who, true);
-		}
-
-		/// Report `target` for being an defunct voter. In case of a valid report, the reporter is
-		/// rewarded by the bond amount of `target`. Otherwise, the reporter itself is removed and
-		/// their bond is slashed.
-		///
-		/// A defunct voter is defined to be:
-		///   - a voter whose current submitted votes are all invalid. i.e. all of them are no
-		///     longer a candidate nor an active member or a runner-up.
-		///
-		///
-		/// The origin must provide the number of current candidates and votes of the reported target
-		/// for the purpose of accurate weight calculation.
-		///
-		/// # <weight>
-		/// No Base weight based on min square analysis.
-		/// Complexity of candidate_count: 1.755 µs
-		/// Complexity of vote_count: 18.51 µs
-		/// State reads:
-		///  	- Voting(reporter)
-		///  	- Candidate.len()
-		///  	- Voting(Target)
-		///  	- Candidates, Members, RunnersUp (is_defunct_voter)
-		/// State writes:
-		/// 	- Lock(reporter || target)
-		/// 	- [AccountBalance(reporter)] + AccountBalance(target)
-		/// 	- Voting(reporter || target)
-		/// Note: the db access is worse with respect to db, which is when the report is correct.
-		/// # </weight>
-		#[weight = T::WeightInfo::report_defunct_voter_correct(
-			defunct.candidate_count,
-			defunct.vote_count,
-		)]
-		fn report_defunct_voter(
-			origin,
-			defunct: DefunctVoter<<T::Lookup as StaticLookup>::Source>,
-		) -> DispatchResultWithPostInfo {
-			let reporter = ensure_signed(origin)?;
-			let target = T::Lookup::lookup(defunct.who)?;
-
-			ensure!(reporter != target, Error::<T>::ReportSelf);
-			ensure!(Self::is_voter(&reporter), Error::<T>::MustBeVoter);
-
-			let DefunctVoter { candidate_count, vote_count, .. }  = defunct;
-
-			ensure!(
-				<Candidates<T>>::decode_len().unwrap_or(0) as u32 <= candidate_count,
-				Error::<T>::InvalidCandidateCount,
-			);
-
-			let (_, votes) = <Voting<T>>::get(&target);
-			// indirect way to ensure target is a voter. We could call into `::contains()`, but it
-			// would have the same effect with one extra db access. Note that votes cannot be
-			// submitted with length 0. Hence, a non-zero length means that the target is a voter.
-			ensure!(votes.len() > 0, Error::<T>::MustBeVoter);
-
-			// ensure that the size of votes that need to be searched is correct.
-			ensure!(
-				votes.len() as u32 <= vote_count,
-				Error::<T>::InvalidVoteCount,
-			);
-
-			let valid = Self::is_defunct_voter(&votes);
-			let maybe_refund = if valid {
-				// reporter will get the voting bond of the target
-				T::Currency::repatriate_reserved(&target, &reporter, T::VotingBond::get(), BalanceStatus::Free)?;
-				// remove the target. They are defunct.
-				Self::do_remove_voter(&target, false);
-				None
-			} else {
-				// slash the bond of the reporter.
-				let imbalance = T::Currency::slash_reserved(&reporter, T::VotingBond::get()).0;
-				T::BadReport::on_unbalanced(imbalance);
-				// remove the reporter.
-				Self::do_remove_voter(&reporter, false);
-				Some(T::WeightInfo::report_defunct_voter_incorrect(
-					defunct.candidate_count,
-					defunct.vote_count,
-				))
-			};
-			Self::deposit_event(RawEvent::VoterReported(target, reporter, valid));
-			Ok(maybe_refund.into())
+			Self::do_remove_voter(&who);
 		}
 
 		/// Submit oneself for candidacy.
@@ -482,6 +474,8 @@ decl_module! {
 		///   - Become a runner-up. Runners-ups are reserved members in case one gets forcefully
 		///     removed.
 		///
+		/// No more than `MaxCandidates` candidates may exist at once.
+		///
 		/// # <weight>
 		/// Base weight = 33.33 µs
 		/// Complexity of candidate_count: 0.375 µs
@@ -498,11 +492,12 @@ decl_module! {
 		fn submit_candidacy(origin, #[compact] candidate_count: u32) {
 			let who = ensure_signed(origin)?;
 
-			let actual_count = <Candidates<T>>::decode_len().unwrap_or(0);
+			let actual_count = <Candidates<T>>::decode_len().unwrap_or(0) as u32;
 			ensure!(
-				actual_count as u32 <= candidate_count,
+				actual_count <= candidate_count,
 				Error::<T>::InvalidCandidateCount,
 			);
+			ensure!(actual_count < T::MaxCandidates::get(), Error::<T>::TooManyCandidates);
 
 			let is_candidate = Self::is_candidate(&who);
 			ensure!(is_candidate.is_err(), Error::<T>::DuplicatedCandidate);
@@ -513,10 +508,11 @@ decl_module! {
 			ensure!(!Self::is_member(&who), Error::<T>::MemberSubmit);
 			ensure!(!Self::is_runner_up(&who), Error::<T>::RunnerSubmit);
 
-			T::Currency::reserve(&who, T::CandidacyBond::get())
+			let deposit = T::CandidacyBond::get();
+			T::Currency::reserve(&who, deposit)
 				.map_err(|_| Error::<T>::InsufficientCandidateFunds)?;
 
-			<Candidates<T>>::mutate(|c| c.insert(index, who));
+			<Candidates<T>>::mutate(|c| c.insert(index, (who, deposit)));
 		}
 
 		/// Renounce one's intention to be a candidate for the next election round. 3 potential
@@ -564,20 +560,19 @@ decl_module! {
 			let who = ensure_signed(origin)?;
 			match renouncing {
 				Renouncing::Member => {
-					// returns NoMember error in case of error.
-					let _ = Self::remove_and_replace_member(&who)?;
-					T::Currency::unreserve(&who, T::CandidacyBond::get());
+					// returns NoMember error in case of error. The deposit is returned.
+					let _ = Self::remove_and_replace_member(&who, false)?;
 					Self::deposit_event(RawEvent::MemberRenounced(who));
 				},
 				Renouncing::RunnerUp => {
 					let mut runners_up_with_stake = Self::runners_up();
 					if let Some(index) = runners_up_with_stake
 						.iter()
-						.position(|(ref r, ref _s)| r == &who)
+						.position(|r| r.who == who)
 					{
-						runners_up_with_stake.remove(index);
+						let SeatHolder { deposit, .. } = runners_up_with_stake.remove(index);
 						// unreserve the bond
-						T::Currency::unreserve(&who, T::CandidacyBond::get());
+						T::Currency::unreserve(&who, deposit);
 						// update storage.
 						<RunnersUp<T>>::put(runners_up_with_stake);
 					} else {
@@ -587,10 +582,10 @@ decl_module! {
 				Renouncing::Candidate(count) => {
 					let mut candidates = Self::candidates();
 					ensure!(count >= candidates.len() as u32, Error::<T>::InvalidRenouncing);
-					if let Some(index) = candidates.iter().position(|x| *x == who) {
-						candidates.remove(index);
+					if let Some(index) = candidates.iter().position(|(x, _)| *x == who) {
+						let (_, deposit) = candidates.remove(index);
 						// unreserve the bond
-						T::Currency::unreserve(&who, T::CandidacyBond::get());
+						T::Currency::unreserve(&who, deposit);
 						// update storage.
 						<Candidates<T>>::put(candidates);
 					} else {
@@ -616,7 +611,8 @@ decl_module! {
 		/// 		- Members, RunnersUp (remove_and_replace_member)
 		/// 	- State writes:
 		/// 		- Members, RunnersUp (remove_and_replace_member)
-		/// Else, since this is a root call and will go into phragmen, we assume full block for now.
+		/// Else, since this is a root call and will go into phragmen, we assume full block and
+		/// refund the weight of the election that actually happened.
 		/// # </weight>
 		#[weight = if *has_replacement {
 			T::WeightInfo::remove_member_with_replacement()
@@ -641,21 +637,44 @@ decl_module! {
 				));
 			} // else, prediction was correct.
 
-			Self::remove_and_replace_member(&who).map(|had_replacement| {
-				let (imbalance, _) = T::Currency::slash_reserved(&who, T::CandidacyBond::get());
-				T::KickedMember::on_unbalanced(imbalance);
+			Self::remove_and_replace_member(&who, true).map(|had_replacement| {
 				Self::deposit_event(RawEvent::MemberKicked(who.clone()));
 
 				if !had_replacement {
-					// if we end up here, we will charge a full block weight.
-					Self::do_phragmen();
+					// if we end up here, we charge the weight of the election on top.
+					let election_weight = Self::do_phragmen();
+					Some(
+						T::WeightInfo::remove_member_with_replacement()
+							.saturating_add(election_weight)
+					).into()
+				} else {
+					// no refund needed.
+					None.into()
 				}
-
-				// no refund needed.
-				None.into()
 			}).map_err(|e| e.into())
 		}
 
+		/// Clean all voters who are defunct (i.e. they do not serve any purpose at all). The
+		/// deposit of the removed voters are returned.
+		///
+		/// At most `num_voters` voters are inspected, and at most `num_defunct` of them are
+		/// removed.
+		///
+		/// The dispatch origin of this call must be root.
+		///
+		/// # <weight>
+		/// The total number of voters and those that are defunct must be provided as witness data.
+		/// # </weight>
+		#[weight = T::WeightInfo::clean_defunct_voters(*num_voters, *num_defunct)]
+		fn clean_defunct_voters(origin, num_voters: u32, num_defunct: u32) {
+			ensure_root(origin)?;
+			<Voting<T>>::iter()
+				.take(num_voters as usize)
+				.filter(|(_, voter)| Self::is_defunct_voter(&voter.votes))
+				.take(num_defunct as usize)
+				.for_each(|(who, _)| Self::do_remove_voter(&who));
+		}
+
 		/// What to do at the end of each block. Checks if an election needs to happen or not.
 		fn on_initialize(n: T::BlockNumber) -> Weight {
 			// returns the correct weight.
@@ -688,9 +707,6 @@ decl_event!(
 		SeatHolderSlashed(AccountId, Balance),
 		/// A \[member\] has renounced their candidacy.
 		MemberRenounced(AccountId),
-		/// A voter was reported with the the report being successful or not.
-		/// \[voter, reporter, success\]
-		VoterReported(AccountId, AccountId, bool),
 	}
 );
 
@@ -700,6 +716,8 @@ impl<T: Config> Module<T> {
 	///
 	/// Otherwise, `Ok(false)` is returned to signal the caller.
 	///
+	/// The deposit of the removed member is slashed if `slash` is true, and returned otherwise.
+	///
 	/// If a replacement exists, `Members` and `RunnersUp` storage is updated, where the first
 	/// element of `RunnersUp` is used as the replacement and `Ok(true)` is returned. Else,
 	/// `Ok(false)` is returned with no storage updated.
@@ -708,23 +726,30 @@ impl<T: Config> Module<T> {
 	/// (`Ok(true)`).
 	///
 	/// If replacement exists, this will read and write from/into both `Members` and `RunnersUp`.
-	fn remove_and_replace_member(who: &T::AccountId) -> Result<bool, DispatchError> {
+	fn remove_and_replace_member(who: &T::AccountId, slash: bool) -> Result<bool, DispatchError> {
 		let mut members_with_stake = Self::members();
-		if let Ok(index) = members_with_stake.binary_search_by(|(ref m, ref _s)| m.cmp(who)) {
-			members_with_stake.remove(index);
+		if let Ok(index) = members_with_stake.binary_search_by(|m| m.who.cmp(who)) {
+			let removed = members_with_stake.remove(index);
+			if slash {
+				let (imbalance, _) = T::Currency::slash_reserved(who, removed.deposit);
+				T::KickedMember::on_unbalanced(imbalance);
+			} else {
+				T::Currency::unreserve(who, removed.deposit);
+			}
 
 			let next_up = <RunnersUp<T>>::mutate(|runners_up| runners_up.pop());
-			let maybe_replacement = next_up.and_then(|(replacement, stake)|
-				members_with_stake.binary_search_by(|(ref m, ref _s)| m.cmp(&replacement))
+			let maybe_replacement = next_up.and_then(|replacement|
+				members_with_stake.binary_search_by(|m| m.who.cmp(&replacement.who))
 					.err()
 					.map(|index| {
-						members_with_stake.insert(index, (replacement.clone(), stake));
-						replacement
+						let replacement_id = replacement.who.clone();
+						members_with_stake.insert(index, replacement);
+						replacement_id
 					})
 			);
 
 			<Members<T>>::put(&members_with_stake);
-			let members = members_with_stake.into_iter().map(|m| m.0).collect::<Vec<_>>();
+			let members = members_with_stake.into_iter().map(|m| m.who).collect::<Vec<_>>();
 			let result = Ok(maybe_replacement.is_some());
 			let old = [who.clone()];
 			match maybe_replacement {
@@ -742,7 +767,7 @@ impl<T: Config> Module<T> {
 	///
 	/// O(LogN) given N candidates.
 	fn is_candidate(who: &T::AccountId) -> Result<(), usize> {
-		Self::candidates().binary_search(who).map(|_| ())
+		Self::candidates().binary_search_by(|(c, _)| c.cmp(who)).map(|_| ())
 	}
 
 	/// Check if `who` is a voter. It may or may not be a _current_ one.
@@ -756,14 +781,14 @@ impl<T: Config> Module<T> {
 	///
 	/// O(LogN) given N members. Since members are limited, O(1).
 	fn is_member(who: &T::AccountId) -> bool {
-		Self::members().binary_search_by(|(a, _b)| a.cmp(who)).is_ok()
+		Self::members().binary_search_by(|m| m.who.cmp(who)).is_ok()
 	}
 
 	/// Check if `who` is currently an active runner-up.
 	///
 	/// O(LogN) given N runners-up. Since runners-up are limited, O(1).
 	fn is_runner_up(who: &T::AccountId) -> bool {
-		Self::runners_up().iter().position(|(a, _b)| a == who).is_some()
+		Self::runners_up().iter().position(|r| &r.who == who).is_some()
 	}
 
 	/// Returns number of desired members.
@@ -783,12 +808,22 @@ impl<T: Config> Module<T> {
 
 	/// Get the members' account ids.
 	fn members_ids() -> Vec<T::AccountId> {
-		Self::members().into_iter().map(|(m, _)| m).collect::<Vec<T::AccountId>>()
+		Self::members().into_iter().map(|m| m.who).collect::<Vec<T::AccountId>>()
 	}
 
 	/// The the runners' up account ids.
 	fn runners_up_ids() -> Vec<T::AccountId> {
-		Self::runners_up().into_iter().map(|(r, _)| r).collect::<Vec<T::AccountId>>()
+		Self::runners_up().into_iter().map(|r| r.who).collect::<Vec<T::AccountId>>()
+	}
+
+	/// Get the members and runners-up, along with their deposits, as implicit candidates of the
+	/// next election.
+	fn implicit_candidates_with_deposit() -> Vec<(T::AccountId, BalanceOf<T>)> {
+		Self::members()
+			.into_iter()
+			.chain(Self::runners_up().into_iter())
+			.map(|s| (s.who, s.deposit))
+			.collect::<Vec<_>>()
 	}
 
 	/// Check if `votes` will correspond to a defunct voter. As no origin is part of the inputs,
@@ -806,19 +841,15 @@ impl<T: Config> Module<T> {
 
 	/// Remove a certain someone as a voter.
 	///
-	/// This will clean always clean the storage associated with the voter, and remove the balance
-	/// lock. Optionally, it would also return the reserved voting bond if indicated by `unreserve`.
-	/// If unreserve is true, has 3 storage reads and 1 reads.
+	/// This will clean always clean the storage associated with the voter, remove the balance
+	/// lock and return the deposit that was reserved when they first voted.
 	///
-	/// DB access: Voting and Lock are always written to, if unreserve, then 1 read and write added.
-	fn do_remove_voter(who: &T::AccountId, unreserve: bool) {
-		// remove storage and lock.
-		Voting::<T>::remove(who);
+	/// DB access: Voting, Lock and the account data of `who` are read from and written to.
+	fn do_remove_voter(who: &T::AccountId) {
+		// remove storage, lock and unreserve.
+		let Voter { deposit, .. } = Voting::<T>::take(who);
 		T::Currency::remove_lock(T::ModuleId::get(), who);
-
-		if unreserve {
-			T::Currency::unreserve(who, T::VotingBond::get());
-		}
+		T::Currency::unreserve(who, deposit);
 	}
 
 	/// Check there's nothing to do this block.
@@ -828,8 +859,7 @@ impl<T: Config> Module<T> {
 	fn end_block(block_number: T::BlockNumber) -> Weight {
 		if !Self::term_duration().is_zero() {
 			if (block_number % Self::term_duration()).is_zero() {
-				Self::do_phragmen();
-				return T::MaximumBlockWeight::get()
+				return Self::do_phragmen();
 			}
 		}
 		0
@@ -842,54 +872,92 @@ impl<T: Config> Module<T> {
 	///
 	/// Reads: O(C + V*E) where C = candidates, V voters and E votes per voter exits.
 	/// Writes: O(M + R) with M desired members and R runners_up.
-	fn do_phragmen() {
+	///
+	/// Returns the weight of the election, as measured by the `election_phragmen` benchmark.
+	fn do_phragmen() -> Weight {
 		let desired_seats = Self::desired_members() as usize;
 		let desired_runners_up = Self::desired_runners_up() as usize;
 		let num_to_elect = desired_runners_up + desired_seats;
 
-		let mut candidates = Self::candidates();
+		let mut candidates_and_deposit = Self::candidates();
 		// candidates who explicitly called `submit_candidacy`. Only these folks are at risk of
 		// losing their bond.
-		let exposed_candidates = candidates.clone();
-		// current members are always a candidate for the next round as well.
-		// this is guaranteed to not create any duplicates.
-		candidates.append(&mut Self::members_ids());
-		// previous runners_up are also always candidates for the next round.
-		candidates.append(&mut Self::runners_up_ids());
-
-		if candidates.len().is_zero() {
+		let exposed_candidates = candidates_and_deposit.clone();
+		// current members and previous runners_up are always a candidate for the next round as
+		// well. this is guaranteed to not create any duplicates.
+		candidates_and_deposit.append(&mut Self::implicit_candidates_with_deposit());
+
+		if candidates_and_deposit.len().is_zero() {
 			Self::deposit_event(RawEvent::EmptyTerm);
-			return;
+			return T::DbWeight::get().reads(5);
 		}
 
+		// sort by id, so that the deposit of any winner can be looked up.
+		candidates_and_deposit.sort_by(|a, b| a.0.cmp(&b.0));
+		let candidate_ids = candidates_and_deposit
+			.iter()
+			.map(|(c, _)| c.clone())
+			.collect::<Vec<T::AccountId>>();
+
 		// helper closures to deal with balance/stake.
 		let total_issuance = T::Currency::total_issuance();
 		let to_votes = |b: BalanceOf<T>| T::CurrencyToVote::to_vote(b, total_issuance);
 		let to_balance = |e: ExtendedBalance| T::CurrencyToVote::to_currency(e, total_issuance);
-
-		// used for prime election.
-		let voters_and_stakes = Voting::<T>::iter()
-			.map(|(voter, (stake, votes))| (voter, stake, votes))
+		let deposit_of_candidate = |who: &T::AccountId| -> BalanceOf<T> {
+			// defensive-only. This is only used for phragmen winners, who must be candidates.
+			candidates_and_deposit
+				.binary_search_by(|(c, _)| c.cmp(who))
+				.map(|i| candidates_and_deposit[i].1)
+				.unwrap_or_default()
+		};
+
+		let max_voters = T::MaxVoters::get() as usize;
+		let max_votes = T::MaxVotesPerVoter::get() as usize;
+		let mut num_edges: u32 = 0;
+
+		// used for prime election. Only the first `MaxVoters` voters are taken into account, and
+		// only up to `MaxVotesPerVoter` votes each.
+		let mut all_voters = Voting::<T>::iter();
+		let voters_and_stakes = (&mut all_voters)
+			.take(max_voters)
+			.map(|(voter, Voter { stake, mut votes, .. })| {
+				votes.truncate(max_votes);
+				num_edges = num_edges.saturating_add(votes.len() as u32);
+				(voter, stake, votes)
+			})
 			.collect::<Vec<_>>();
+		if all_voters.next().is_some() {
+			frame_support::debug::warn!(
+				"elections-phragmen: more than {} voters exist, the rest are ignored.",
+				max_voters,
+			);
+		}
+
 		// used for phragmen.
 		let voters_and_votes = voters_and_stakes.iter()
 			.cloned()
 			.map(|(voter, stake, votes)| { (voter, to_votes(stake), votes)} )
 			.collect::<Vec<_>>();
 
+		let weight = T::WeightInfo::election_phragmen(
+			candidate_ids.len() as u32,
+			voters_and_votes.len() as u32,
+			num_edges,
+		);
+
 		let _ = sp_npos_elections::seq_phragmen::<T::AccountId, Perbill>(
 			num_to_elect,
-			candidates,
-			voters_and_votes.clone(),
+			candidate_ids,
+			voters_and_votes,
 			None,
 		).map(|ElectionResult { winners, assignments: _ }| {
 			// this is already sorted by id.
 			let old_members_ids_sorted = <Members<T>>::take().into_iter()
-				.map(|(m, _)| m)
+				.map(|m| m.who)
 				.collect::<Vec<T::AccountId>>();
 			// this one needs a sort by id.
 			let mut old_runners_up_ids_sorted = <RunnersUp<T>>::take().into_iter()
-				.map(|(r, _)| r)
+				.map(|r| r.who)
 				.collect::<Vec<T::AccountId>>();
 			old_runners_up_ids_sorted.sort();
 
@@ -906,21 +974,31 @@ impl<T: Config> Module<T> {
 
 			// split new set into winners and runners up.
 			let split_point = desired_seats.min(new_set_with_stake.len());
-			let mut new_members_sorted_by_id = (&new_set_with_stake[..split_point]).to_vec();
+			let mut new_members_sorted_by_id = (&new_set_with_stake[..split_point])
+				.iter()
+				.map(|(who, stake)| SeatHolder {
+					deposit: deposit_of_candidate(who),
+					who: who.clone(),
+					stake: *stake,
+				})
+				.collect::<Vec<_>>();
 
 			// save the runners up as-is. They are sorted based on desirability.
 			// save the members, sorted based on account id.
-			new_members_sorted_by_id.sort_by(|i, j| i.0.cmp(&j.0));
+			new_members_sorted_by_id.sort_by(|i, j| i.who.cmp(&j.who));
 
 			// Now we select a prime member using a [Borda count](https://en.wikipedia.org/wiki/Borda_count).
 			// We weigh everyone's vote for that new member by a multiplier based on the order
 			// of the votes. i.e. the first person a voter votes for gets a 16x multiplier,
-			// the next person gets a 15x multiplier, an so on... (assuming `MAXIMUM_VOTE` = 16)
-			let mut prime_votes: Vec<_> = new_members_sorted_by_id.iter().map(|c| (&c.0, BalanceOf::<T>::zero())).collect();
+			// the next person gets a 15x multiplier, an so on... (assuming `MaxVotesPerVoter` = 16)
+			let mut prime_votes: Vec<_> = new_members_sorted_by_id
+				.iter()
+				.map(|c| (&c.who, BalanceOf::<T>::zero()))
+				.collect();
 			for (_, stake, votes) in voters_and_stakes.into_iter() {
 				for (vote_multiplier, who) in votes.iter()
 					.enumerate()
-					.map(|(vote_position, who)| ((MAXIMUM_VOTE - vote_position) as u32, who))
+					.map(|(vote_position, who)| ((max_votes - vote_position) as u32, who))
 				{
 					if let Ok(i) = prime_votes.binary_search_by_key(&who, |k| k.0) {
 						prime_votes[i].1 = prime_votes[i].1.saturating_add(
@@ -937,18 +1015,22 @@ impl<T: Config> Module<T> {
 			// new_members_sorted_by_id is sorted by account id.
 			let new_members_ids_sorted = new_members_sorted_by_id
 				.iter()
-				.map(|(m, _)| m.clone())
+				.map(|m| m.who.clone())
 				.collect::<Vec<T::AccountId>>();
 
-			let new_runners_up_sorted_by_rank = &new_set_with_stake[split_point..]
+			let new_runners_up_sorted_by_rank = new_set_with_stake[split_point..]
 				.into_iter()
-				.cloned()
 				.rev()
-				.collect::<Vec<(T::AccountId, BalanceOf<T>)>>();
+				.map(|(who, stake)| SeatHolder {
+					deposit: deposit_of_candidate(who),
+					who: who.clone(),
+					stake: *stake,
+				})
+				.collect::<Vec<_>>();
 			// new_runners_up remains sorted by desirability.
 			let mut new_runners_up_ids_sorted = new_runners_up_sorted_by_rank
 				.iter()
-				.map(|(r, _)| r.clone())
+				.map(|r| r.who.clone())
 				.collect::<Vec<T::AccountId>>();
 			new_runners_up_ids_sorted.sort();
 
@@ -992,29 +1074,34 @@ impl<T: Config> Module<T> {
 			// Burn loser bond. members list is sorted. O(NLogM) (N candidates, M members)
 			// runner up list is also sorted. O(NLogK) given K runner ups. Overall: O(NLogM + N*K)
 			// both the member and runner counts are bounded.
-			exposed_candidates.into_iter().for_each(|c| {
+			exposed_candidates.into_iter().for_each(|(c, deposit)| {
 				// any candidate who is not a member and not a runner up.
 				if
 					new_members_ids_sorted.binary_search(&c).is_err() &&
 					new_runners_up_ids_sorted.binary_search(&c).is_err()
 				{
-					let (imbalance, _) = T::Currency::slash_reserved(&c, T::CandidacyBond::get());
-					Self::deposit_event(RawEvent::CandidateSlashed(c, T::CandidacyBond::get()));
+					let (imbalance, _) = T::Currency::slash_reserved(&c, deposit);
+					Self::deposit_event(RawEvent::CandidateSlashed(c, deposit));
 					T::LoserCandidate::on_unbalanced(imbalance);
 				}
 			});
 
 			// Burn outgoing bonds
 			to_burn_bond.into_iter().for_each(|x| {
-				let (imbalance, _) = T::Currency::slash_reserved(&x, T::CandidacyBond::get());
-				Self::deposit_event(RawEvent::SeatHolderSlashed(x, T::CandidacyBond::get()));
+				let deposit = deposit_of_candidate(&x);
+				let (imbalance, _) = T::Currency::slash_reserved(&x, deposit);
+				Self::deposit_event(RawEvent::SeatHolderSlashed(x, deposit));
 				T::LoserCandidate::on_unbalanced(imbalance);
 			});
 
-			<Members<T>>::put(&new_members_sorted_by_id);
+			let new_members_with_stake = new_members_sorted_by_id
+				.iter()
+				.map(|m| (m.who.clone(), m.stake))
+				.collect::<Vec<_>>();
+			<Members<T>>::put(new_members_sorted_by_id);
 			<RunnersUp<T>>::put(new_runners_up_sorted_by_rank);
 
-			Self::deposit_event(RawEvent::NewTerm(new_members_sorted_by_id.clone().to_vec()));
+			Self::deposit_event(RawEvent::NewTerm(new_members_with_stake));
 
 			// clean candidates.
 			<Candidates<T>>::kill();
@@ -1024,6 +1111,8 @@ impl<T: Config> Module<T> {
 			frame_support::debug::error!("elections-phragmen: failed to run election [{:?}].", e);
 			Self::deposit_event(RawEvent::ElectionError);
 		});
+
+		weight
 	}
 }
 
@@ -1038,9 +1127,9 @@ impl<T: Config> Contains<T::AccountId> for Module<T> {
 	#[cfg(feature = "runtime-benchmarks")]
 	fn add(who: &T::AccountId) {
 		Members::<T>::mutate(|members| {
-			match members.binary_search_by(|(a, _b)| a.cmp(who)) {
+			match members.binary_search_by(|m| m.who.cmp(who)) {
 				Ok(_) => (),
-				Err(pos) => members.insert(pos, (who.clone(), BalanceOf::<T>::default())),
+				Err(pos) => members.insert(pos, SeatHolder { who: who.clone(), ..Default::default() }),
 			}
 		})
 	}
@@ -1127,6 +1216,9 @@ mod tests {
 		pub static DesiredMembers: u32 = 2;
 		pub static DesiredRunnersUp: u32 = 2;
 		pub static TermDuration: u64 = 5;
+		pub static MaxCandidates: u32 = 1000;
+		pub static MaxVoters: u32 = 1000;
+		pub static MaxVotesPerVoter: u32 = 16;
 		pub static Members: Vec<u64> = vec![];
 		pub static Prime: Option<u64> = None;
 	}
@@ -1189,7 +1281,9 @@ mod tests {
 		type DesiredRunnersUp = DesiredRunnersUp;
 		type LoserCandidate = ();
 		type KickedMember = ();
-		type BadReport = ();
+		type MaxCandidates = MaxCandidates;
+		type MaxVoters = MaxVoters;
+		type MaxVotesPerVoter = MaxVotesPerVoter;
 		type WeightInfo = ();
 	}
 
@@ -1304,19 +1398,19 @@ mod tests {
 	}
 
 	fn ensure_members_sorted() {
-		let mut members = Elections::members().clone();
+		let mut members = members_and_stake().clone();
 		members.sort();
-		assert_eq!(Elections::members(), members);
+		assert_eq!(members_and_stake(), members);
 	}
 
 	fn ensure_candidates_sorted() {
-		let mut candidates = Elections::candidates().clone();
+		let mut candidates = candidate_ids().clone();
 		candidates.sort();
-		assert_eq!(Elections::candidates(), candidates);
+		assert_eq!(candidate_ids(), candidates);
 	}
 
 	fn locked_stake_of(who: &u64) -> u64 {
-		Voting::<Test>::get(who).0
+		Voting::<Test>::get(who).stake
 	}
 
 	fn ensure_members_has_approval_stake() {
@@ -1325,15 +1419,15 @@ mod tests {
 		assert!(
 			Elections::members().iter().chain(
 				Elections::runners_up().iter()
-			).all(|(_, s)| *s != u64::zero())
+			).all(|s| s.stake != u64::zero())
 		);
 	}
 
 	fn ensure_member_candidates_runners_up_disjoint() {
 		// members, candidates and runners-up must always be disjoint sets.
-		assert!(!intersects(&Elections::members_ids(), &Elections::candidates()));
+		assert!(!intersects(&Elections::members_ids(), &candidate_ids()));
 		assert!(!intersects(&Elections::members_ids(), &Elections::runners_up_ids()));
-		assert!(!intersects(&Elections::candidates(), &Elections::runners_up_ids()));
+		assert!(!intersects(&candidate_ids(), &Elections::runners_up_ids()));
 	}
 
 	fn pre_conditions() {
@@ -1350,7 +1444,7 @@ mod tests {
 	}
 
 	fn submit_candidacy(origin: Origin) -> DispatchResult {
-		Elections::submit_candidacy(origin, Elections::candidates().len() as u32)
+		Elections::submit_candidacy(origin, candidate_ids().len() as u32)
 	}
 
 	fn vote(origin: Origin, votes: Vec<u64>, stake: u64) -> DispatchResult {
@@ -1362,15 +1456,19 @@ mod tests {
 	}
 
 	fn votes_of(who: &u64) -> Vec<u64> {
-		Voting::<Test>::get(who).1
+		Voting::<Test>::get(who).votes
 	}
 
-	fn defunct_for(who: u64) -> DefunctVoter<u64> {
-		DefunctVoter {
-			who,
-			candidate_count: Elections::candidates().len() as u32,
-			vote_count: votes_of(&who).len() as u32
-		}
+	fn candidate_ids() -> Vec<u64> {
+		Elections::candidates().into_iter().map(|(c, _)| c).collect::<Vec<_>>()
+	}
+
+	fn members_and_stake() -> Vec<(u64, u64)> {
+		Elections::members().into_iter().map(|m| (m.who, m.stake)).collect::<Vec<_>>()
+	}
+
+	fn runners_up_and_stake() -> Vec<(u64, u64)> {
+		Elections::runners_up().into_iter().map(|r| (r.who, r.stake)).collect::<Vec<_>>()
 	}
 
 	#[test]
@@ -1380,10 +1478,10 @@ mod tests {
 			assert_eq!(Elections::term_duration(), 5);
 			assert_eq!(Elections::election_rounds(), 0);
 
-			assert!(Elections::members().is_empty());
-			assert!(Elections::runners_up().is_empty());
+			assert!(members_and_stake().is_empty());
+			assert!(runners_up_and_stake().is_empty());
 
-			assert!(Elections::candidates().is_empty());
+			assert!(candidate_ids().is_empty());
 			assert_eq!(<Candidates<Test>>::decode_len(), None);
 			assert!(Elections::is_candidate(&1).is_err());
 
@@ -1396,10 +1494,10 @@ mod tests {
 	fn genesis_members_should_work() {
 		ExtBuilder::default().genesis_members(vec![(1, 10), (2, 20)]).build_and_execute(|| {
 			System::set_block_number(1);
-			assert_eq!(Elections::members(), vec![(1, 10), (2, 20)]);
+			assert_eq!(members_and_stake(), vec![(1, 10), (2, 20)]);
 
-			assert_eq!(Elections::voting(1), (10, vec![1]));
-			assert_eq!(Elections::voting(2), (20, vec![2]));
+			assert_eq!(Elections::voting(1), Voter { stake: 10, votes: vec![1], deposit: 2 });
+			assert_eq!(Elections::voting(2), Voter { stake: 20, votes: vec![2], deposit: 2 });
 
 			// they will persist since they have self vote.
 			System::set_block_number(5);
@@ -1413,10 +1511,10 @@ mod tests {
 	fn genesis_members_unsorted_should_work() {
 		ExtBuilder::default().genesis_members(vec![(2, 20), (1, 10)]).build_and_execute(|| {
 			System::set_block_number(1);
-			assert_eq!(Elections::members(), vec![(1, 10), (2, 20)]);
+			assert_eq!(members_and_stake(), vec![(1, 10), (2, 20)]);
 
-			assert_eq!(Elections::voting(1), (10, vec![1]));
-			assert_eq!(Elections::voting(2), (20, vec![2]));
+			assert_eq!(Elections::voting(1), Voter { stake: 10, votes: vec![1], deposit: 2 });
+			assert_eq!(Elections::voting(2), Voter { stake: 20, votes: vec![2], deposit: 2 });
 
 			// they will persist since they have self vote.
 			System::set_block_number(5);
@@ -1464,22 +1562,22 @@ mod tests {
 			assert_eq!(Elections::election_rounds(), 0);
 
 			assert!(Elections::members_ids().is_empty());
-			assert!(Elections::runners_up().is_empty());
-			assert!(Elections::candidates().is_empty());
+			assert!(runners_up_and_stake().is_empty());
+			assert!(candidate_ids().is_empty());
 
 			System::set_block_number(5);
 			Elections::end_block(System::block_number());
 
 			assert!(Elections::members_ids().is_empty());
-			assert!(Elections::runners_up().is_empty());
-			assert!(Elections::candidates().is_empty());
+			assert!(runners_up_and_stake().is_empty());
+			assert!(candidate_ids().is_empty());
 		});
 	}
 
 	#[test]
 	fn simple_candidate_submission_should_work() {
 		ExtBuilder::default().build_and_execute(|| {
-			assert_eq!(Elections::candidates(), Vec::<u64>::new());
+			assert_eq!(candidate_ids(), Vec::<u64>::new());
 			assert!(Elections::is_candidate(&1).is_err());
 			assert!(Elections::is_candidate(&2).is_err());
 
@@ -1487,7 +1585,7 @@ mod tests {
 			assert_ok!(submit_candidacy(Origin::signed(1)));
 			assert_eq!(balances(&1), (7, 3));
 
-			assert_eq!(Elections::candidates(), vec![1]);
+			assert_eq!(candidate_ids(), vec![1]);
 
 			assert!(Elections::is_candidate(&1).is_ok());
 			assert!(Elections::is_candidate(&2).is_err());
@@ -1496,7 +1594,7 @@ mod tests {
 			assert_ok!(submit_candidacy(Origin::signed(2)));
 			assert_eq!(balances(&2), (17, 3));
 
-			assert_eq!(Elections::candidates(), vec![1, 2]);
+			assert_eq!(candidate_ids(), vec![1, 2]);
 
 			assert!(Elections::is_candidate(&1).is_ok());
 			assert!(Elections::is_candidate(&2).is_ok());
@@ -1506,36 +1604,36 @@ mod tests {
 	#[test]
 	fn simple_candidate_submission_with_no_votes_should_work() {
 		ExtBuilder::default().build_and_execute(|| {
-			assert_eq!(Elections::candidates(), Vec::<u64>::new());
+			assert_eq!(candidate_ids(), Vec::<u64>::new());
 
 			assert_ok!(submit_candidacy(Origin::signed(1)));
 			assert_ok!(submit_candidacy(Origin::signed(2)));
 
 			assert!(Elections::is_candidate(&1).is_ok());
 			assert!(Elections::is_candidate(&2).is_ok());
-			assert_eq!(Elections::candidates(), vec![1, 2]);
+			assert_eq!(candidate_ids(), vec![1, 2]);
 
 			assert!(Elections::members_ids().is_empty());
-			assert!(Elections::runners_up().is_empty());
+			assert!(runners_up_and_stake().is_empty());
 
 			System::set_block_number(5);
 			Elections::end_block(System::block_number());
 
 			assert!(Elections::is_candidate(&1).is_err());
 			assert!(Elections::is_candidate(&2).is_err());
-			assert!(Elections::candidates().is_empty());
+			assert!(candidate_ids().is_empty());
 
 			assert!(Elections::members_ids().is_empty());
-			assert!(Elections::runners_up().is_empty());
+			assert!(runners_up_and_stake().is_empty());
 		});
 	}
 
 	#[test]
 	fn dupe_candidate_submission_should_not_work() {
 		ExtBuilder::default().build_and_execute(|| {
-			assert_eq!(Elections::candidates(), Vec::<u64>::new());
+			assert_eq!(candidate_ids(), Vec::<u64>::new());
 			assert_ok!(submit_candidacy(Origin::signed(1)));
-			assert_eq!(Elections::candidates(), vec![1]);
+			assert_eq!(candidate_ids(), vec![1]);
 			assert_noop!(
 				submit_candidacy(Origin::signed(1)),
 				Error::<Test>::DuplicatedCandidate,
@@ -1543,6 +1641,19 @@ mod tests {
 		});
 	}
 
+	#[test]
+	fn candidates_are_bounded() {
+		ExtBuilder::default().build_and_execute(|| {
+			MAX_CANDIDATES.with(|m| *m.borrow_mut() = 2);
+			assert_ok!(submit_candidacy(Origin::signed(5)));
+			assert_ok!(submit_candidacy(Origin::signed(4)));
+			assert_noop!(submit_candidacy(Origin::signed(3)), Error::<Test>::TooManyCandidates);
+
+			// the deposit of each candidate is recorded.
+			assert_eq!(Elections::candidates(), vec![(4, 3), (5, 3)]);
+		});
+	}
+
 	#[test]
 	fn member_candidacy_submission_should_not_work() {
 		// critically important to make sure that outgoing candidates and losers are not mixed up.
@@ -1554,8 +1665,8 @@ mod tests {
 			Elections::end_block(System::block_number());
 
 			assert_eq!(Elections::members_ids(), vec![5]);
-			assert!(Elections::runners_up().is_empty());
-			assert!(Elections::candidates().is_empty());
+			assert!(runners_up_and_stake().is_empty());
+			assert!(candidate_ids().is_empty());
 
 			assert_noop!(
 				submit_candidacy(Origin::signed(5)),
@@ -1590,7 +1701,7 @@ mod tests {
 	#[test]
 	fn poor_candidate_submission_should_not_work() {
 		ExtBuilder::default().build_and_execute(|| {
-			assert_eq!(Elections::candidates(), Vec::<u64>::new());
+			assert_eq!(candidate_ids(), Vec::<u64>::new());
 			assert_noop!(
 				submit_candidacy(Origin::signed(7)),
 				Error::<Test>::InsufficientCandidateFunds,
@@ -1601,7 +1712,7 @@ mod tests {
 	#[test]
 	fn simple_voting_should_work() {
 		ExtBuilder::default().build_and_execute(|| {
-			assert_eq!(Elections::candidates(), Vec::<u64>::new());
+			assert_eq!(candidate_ids(), Vec::<u64>::new());
 			assert_eq!(balances(&2), (20, 0));
 
 			assert_ok!(submit_candidacy(Origin::signed(5)));
@@ -1615,7 +1726,7 @@ mod tests {
 	#[test]
 	fn can_vote_with_custom_stake() {
 		ExtBuilder::default().build_and_execute(|| {
-			assert_eq!(Elections::candidates(), Vec::<u64>::new());
+			assert_eq!(candidate_ids(), Vec::<u64>::new());
 			assert_eq!(balances(&2), (20, 0));
 
 			assert_ok!(submit_candidacy(Origin::signed(5)));
@@ -1669,7 +1780,7 @@ mod tests {
 			Elections::end_block(System::block_number());
 
 			assert_eq!(Elections::members_ids(), vec![4, 5]);
-			assert!(Elections::candidates().is_empty());
+			assert!(candidate_ids().is_empty());
 
 			assert_ok!(vote(Origin::signed(3), vec![4, 5], 10));
 		});
@@ -1692,7 +1803,7 @@ mod tests {
 			Elections::end_block(System::block_number());
 
 			assert_eq!(Elections::members_ids(), vec![4, 5]);
-			assert!(Elections::candidates().is_empty());
+			assert!(candidate_ids().is_empty());
 
 			assert_ok!(vote(Origin::signed(3), vec![4, 5], 10));
 			assert_eq!(PRIME.with(|p| *p.borrow()), Some(4));
@@ -1718,7 +1829,7 @@ mod tests {
 			Elections::end_block(System::block_number());
 
 			assert_eq!(Elections::members_ids(), vec![3, 5]);
-			assert!(Elections::candidates().is_empty());
+			assert!(candidate_ids().is_empty());
 
 			assert_eq!(PRIME.with(|p| *p.borrow()), Some(5));
 		});
@@ -1773,6 +1884,22 @@ mod tests {
 		})
 	}
 
+	#[test]
+	fn cannot_vote_for_more_than_max_votes_per_voter() {
+		ExtBuilder::default().build_and_execute(|| {
+			MAX_VOTES_PER_VOTER.with(|m| *m.borrow_mut() = 2);
+			assert_ok!(submit_candidacy(Origin::signed(5)));
+			assert_ok!(submit_candidacy(Origin::signed(4)));
+			assert_ok!(submit_candidacy(Origin::signed(3)));
+
+			assert_noop!(
+				vote(Origin::signed(2), vec![3, 4, 5], 20),
+				Error::<Test>::MaximumVotesExceeded,
+			);
+			assert_ok!(vote(Origin::signed(2), vec![4, 5], 20));
+		});
+	}
+
 	#[test]
 	fn can_vote_for_more_than_total_balance_but_moot() {
 		ExtBuilder::default().build_and_execute(|| {
@@ -1852,67 +1979,35 @@ mod tests {
 	}
 
 	#[test]
-	fn reporter_must_be_voter() {
-		ExtBuilder::default().build_and_execute(|| {
-			assert_noop!(
-				Elections::report_defunct_voter(Origin::signed(1), defunct_for(2)),
-				Error::<Test>::MustBeVoter,
-			);
-		});
-	}
-
-	#[test]
-	fn reporter_must_provide_lengths() {
+	fn clean_defunct_voters_should_work() {
 		ExtBuilder::default().build_and_execute(|| {
 			assert_ok!(submit_candidacy(Origin::signed(5)));
 			assert_ok!(submit_candidacy(Origin::signed(4)));
-			assert_ok!(submit_candidacy(Origin::signed(3)));
 
-			// both are defunct.
-			assert_ok!(vote(Origin::signed(5), vec![99, 999, 9999], 50));
-			assert_ok!(vote(Origin::signed(4), vec![999], 40));
+			assert_ok!(vote(Origin::signed(5), vec![5], 50));
+			assert_ok!(vote(Origin::signed(4), vec![4], 40));
+			assert_ok!(vote(Origin::signed(2), vec![4, 5], 20));
+			// will be soon a defunct voter.
+			assert_ok!(vote(Origin::signed(3), vec![3], 30));
 
-			// 3 candidates! incorrect candidate length.
-			assert_noop!(
-				Elections::report_defunct_voter(Origin::signed(4), DefunctVoter {
-					who: 5,
-					candidate_count: 2,
-					vote_count: 3,
-				}),
-				Error::<Test>::InvalidCandidateCount,
-			);
+			System::set_block_number(5);
+			Elections::end_block(System::block_number());
+
+			assert_eq!(Elections::members_ids(), vec![4, 5]);
+			assert!(candidate_ids().is_empty());
+			assert_eq!(balances(&3), (28, 2));
 
-			// 3 votes! incorrect vote length
 			assert_noop!(
-				Elections::report_defunct_voter(Origin::signed(4), DefunctVoter {
-					who: 5,
-					candidate_count: 3,
-					vote_count: 2,
-				}),
-				Error::<Test>::InvalidVoteCount,
+				Elections::clean_defunct_voters(Origin::signed(5), 4, 1),
+				DispatchError::BadOrigin,
 			);
 
-			// correct.
-			assert_ok!(Elections::report_defunct_voter(Origin::signed(4), DefunctVoter {
-				who: 5,
-				candidate_count: 3,
-				vote_count: 3,
-			}));
-		});
-	}
+			assert_ok!(Elections::clean_defunct_voters(Origin::root(), 4, 1));
+			assert_eq_uvec!(all_voters(), vec![2, 4, 5]);
 
-	#[test]
-	fn reporter_can_overestimate_length() {
-		ExtBuilder::default().build_and_execute(|| {
-			assert_ok!(submit_candidacy(Origin::signed(5)));
-			assert_ok!(submit_candidacy(Origin::signed(4)));
-
-			// both are defunct.
-			assert_ok!(vote(Origin::signed(5), vec![99], 50));
-			assert_ok!(vote(Origin::signed(4), vec![999], 40));
-
-			// 2 candidates! overestimation is okay.
-			assert_ok!(Elections::report_defunct_voter(Origin::signed(4), defunct_for(5)));
+			// the defunct voter gets their bond back and is unlocked.
+			assert_eq!(balances(&3), (30, 0));
+			assert!(Balances::locks(&3).is_empty());
 		});
 	}
 
@@ -1935,7 +2030,7 @@ mod tests {
 
 			assert_eq!(Elections::members_ids(), vec![4, 5]);
 			assert_eq!(Elections::runners_up_ids(), vec![6]);
-			assert!(Elections::candidates().is_empty());
+			assert!(candidate_ids().is_empty());
 
 			// all of them have a member or runner-up that they voted for.
 			assert_eq!(Elections::is_defunct_voter(&votes_of(&5)), false);
@@ -1956,61 +2051,77 @@ mod tests {
 	}
 
 	#[test]
-	fn report_voter_should_work_and_earn_reward() {
+	fn clean_defunct_voters_respects_limits() {
 		ExtBuilder::default().build_and_execute(|| {
 			assert_ok!(submit_candidacy(Origin::signed(5)));
 			assert_ok!(submit_candidacy(Origin::signed(4)));
+			assert_ok!(submit_candidacy(Origin::signed(3)));
 
 			assert_ok!(vote(Origin::signed(5), vec![5], 50));
 			assert_ok!(vote(Origin::signed(4), vec![4], 40));
-			assert_ok!(vote(Origin::signed(2), vec![4, 5], 20));
-			// will be soon a defunct voter.
 			assert_ok!(vote(Origin::signed(3), vec![3], 30));
+			assert_ok!(vote(Origin::signed(2), vec![3], 20));
+			assert_ok!(vote(Origin::signed(1), vec![3], 10));
 
-			System::set_block_number(5);
-			Elections::end_block(System::block_number());
+			// 1, 2 and 3 become defunct.
+			assert_ok!(Elections::renounce_candidacy(Origin::signed(3), Renouncing::Candidate(3)));
 
-			assert_eq!(Elections::members_ids(), vec![4, 5]);
-			assert!(Elections::candidates().is_empty());
+			// at most one of them is removed.
+			assert_ok!(Elections::clean_defunct_voters(Origin::root(), 5, 1));
+			assert_eq!(all_voters().len(), 4);
 
-			assert_eq!(balances(&3), (28, 2));
-			assert_eq!(balances(&5), (45, 5));
-
-			assert_ok!(Elections::report_defunct_voter(Origin::signed(5), defunct_for(3)));
-			assert!(System::events().iter().any(|event| {
-				event.event == Event::elections_phragmen(RawEvent::VoterReported(3, 5, true))
-			}));
+			// no voter is inspected.
+			assert_ok!(Elections::clean_defunct_voters(Origin::root(), 0, 5));
+			assert_eq!(all_voters().len(), 4);
 
-			assert_eq!(balances(&3), (28, 0));
-			assert_eq!(balances(&5), (47, 5));
+			assert_ok!(Elections::clean_defunct_voters(Origin::root(), 5, 5));
+			assert_eq_uvec!(all_voters(), vec![4, 5]);
+			assert_eq!(balances(&1), (10, 0));
+			assert_eq!(balances(&2), (20, 0));
+			assert_eq!(balances(&3), (30, 0));
 		});
 	}
 
 	#[test]
-	fn report_voter_should_slash_when_bad_report() {
+	fn recorded_voting_deposit_is_returned() {
 		ExtBuilder::default().build_and_execute(|| {
 			assert_ok!(submit_candidacy(Origin::signed(5)));
-			assert_ok!(submit_candidacy(Origin::signed(4)));
+			assert_ok!(vote(Origin::signed(2), vec![5], 20));
+			assert_eq!(balances(&2), (18, 2));
 
-			assert_ok!(vote(Origin::signed(5), vec![5], 50));
-			assert_ok!(vote(Origin::signed(4), vec![4], 40));
+			// the bond changes, but the voter keeps the deposit that they have already reserved.
+			VOTING_BOND.with(|v| *v.borrow_mut() = 5);
+			assert_ok!(vote(Origin::signed(2), vec![5], 15));
+			assert_eq!(balances(&2), (18, 2));
+			assert_eq!(Elections::voting(2).deposit, 2);
 
-			System::set_block_number(5);
-			Elections::end_block(System::block_number());
+			assert_ok!(Elections::remove_voter(Origin::signed(2)));
+			assert_eq!(balances(&2), (20, 0));
+		});
+	}
 
-			assert_eq!(Elections::members_ids(), vec![4, 5]);
-			assert!(Elections::candidates().is_empty());
+	#[test]
+	fn migration_to_v3_works() {
+		ExtBuilder::default().build_and_execute(|| {
+			use frame_support::{storage::unhashed, traits::OnRuntimeUpgrade};
 
-			assert_eq!(balances(&4), (35, 5));
-			assert_eq!(balances(&5), (45, 5));
+			// the layout prior to `V3_0_0`.
+			unhashed::put(&<Voting<Test>>::hashed_key_for(&2), &(20u64, vec![5u64]));
+			unhashed::put(&<Candidates<Test>>::hashed_key(), &vec![5u64]);
+			unhashed::put(&<Members<Test>>::hashed_key(), &vec![(4u64, 40u64)]);
+			unhashed::put(&<RunnersUp<Test>>::hashed_key(), &Vec::<(u64, u64)>::new());
+			StorageVersion::put(Releases::V2_0_0);
 
-			assert_ok!(Elections::report_defunct_voter(Origin::signed(5), defunct_for(4)));
-			assert!(System::events().iter().any(|event| {
-				event.event == Event::elections_phragmen(RawEvent::VoterReported(4, 5, false))
-			}));
+			Elections::on_runtime_upgrade();
 
-			assert_eq!(balances(&4), (35, 5));
-			assert_eq!(balances(&5), (45, 3));
+			assert_eq!(StorageVersion::get(), Releases::V3_0_0);
+			assert_eq!(Elections::voting(2), Voter { votes: vec![5], stake: 20, deposit: 2 });
+			assert_eq!(Elections::candidates(), vec![(5, 3)]);
+			assert_eq!(Elections::members(), vec![SeatHolder { who: 4, stake: 40, deposit: 3 }]);
+			assert!(Elections::runners_up().is_empty());
+
+			// nothing happens the second time around.
+			assert_eq!(Elections::on_runtime_upgrade(), 0);
 		});
 	}
 
@@ -2031,7 +2142,7 @@ mod tests {
 			assert_eq!(votes_of(&3), vec![3]);
 			assert_eq!(votes_of(&4), vec![4]);
 
-			assert_eq!(Elections::candidates(), vec![3, 4, 5]);
+			assert_eq!(candidate_ids(), vec![3, 4, 5]);
 			assert_eq!(<Candidates<Test>>::decode_len().unwrap(), 3);
 
 			assert_eq!(Elections::election_rounds(), 0);
@@ -2039,10 +2150,10 @@ mod tests {
 			System::set_block_number(5);
 			Elections::end_block(System::block_number());
 
-			assert_eq!(Elections::members(), vec![(3, 30), (5, 20)]);
-			assert!(Elections::runners_up().is_empty());
+			assert_eq!(members_and_stake(), vec![(3, 30), (5, 20)]);
+			assert!(runners_up_and_stake().is_empty());
 			assert_eq_uvec!(all_voters(), vec![2, 3, 4]);
-			assert!(Elections::candidates().is_empty());
+			assert!(candidate_ids().is_empty());
 			assert_eq!(<Candidates<Test>>::decode_len(), None);
 
 			assert_eq!(Elections::election_rounds(), 1);
@@ -2080,8 +2191,8 @@ mod tests {
 				Event::elections_phragmen(RawEvent::NewTerm(vec![(4, 40), (5, 50)])),
 			);
 
-			assert_eq!(Elections::members(), vec![(4, 40), (5, 50)]);
-			assert_eq!(Elections::runners_up(), vec![]);
+			assert_eq!(members_and_stake(), vec![(4, 40), (5, 50)]);
+			assert_eq!(runners_up_and_stake(), vec![]);
 
 			assert_ok!(Elections::remove_voter(Origin::signed(5)));
 			assert_ok!(Elections::remove_voter(Origin::signed(4)));
@@ -2112,7 +2223,7 @@ mod tests {
 			System::set_block_number(5);
 			Elections::end_block(System::block_number());
 
-			assert_eq!(Elections::members(), vec![(5, 50)]);
+			assert_eq!(members_and_stake(), vec![(5, 50)]);
 			assert_eq!(Elections::election_rounds(), 1);
 
 			// but now it has a valid target.
@@ -2122,12 +2233,31 @@ mod tests {
 			Elections::end_block(System::block_number());
 
 			// candidate 4 is affected by an old vote.
-			assert_eq!(Elections::members(), vec![(4, 30), (5, 50)]);
+			assert_eq!(members_and_stake(), vec![(4, 30), (5, 50)]);
 			assert_eq!(Elections::election_rounds(), 2);
 			assert_eq_uvec!(all_voters(), vec![3, 5]);
 		});
 	}
 
+	#[test]
+	fn only_max_voters_are_counted_in_election() {
+		ExtBuilder::default().build_and_execute(|| {
+			MAX_VOTERS.with(|m| *m.borrow_mut() = 1);
+			assert_ok!(submit_candidacy(Origin::signed(5)));
+			assert_ok!(submit_candidacy(Origin::signed(4)));
+
+			assert_ok!(vote(Origin::signed(5), vec![5], 50));
+			assert_ok!(vote(Origin::signed(4), vec![4], 40));
+
+			System::set_block_number(5);
+			Elections::end_block(System::block_number());
+
+			// only one of the voters is taken into account, hence only one member is elected.
+			assert_eq!(Elections::members_ids().len(), 1);
+			assert_eq!(Elections::election_rounds(), 1);
+		});
+	}
+
 	#[test]
 	fn only_desired_seats_are_chosen() {
 		ExtBuilder::default().build_and_execute(|| {
@@ -2158,7 +2288,7 @@ mod tests {
 			System::set_block_number(5);
 			Elections::end_block(System::block_number());
 
-			assert!(Elections::candidates().is_empty());
+			assert!(candidate_ids().is_empty());
 			assert_eq!(Elections::election_rounds(), 1);
 			assert!(Elections::members_ids().is_empty());
 
@@ -2211,15 +2341,15 @@ mod tests {
 
 			System::set_block_number(5);
 			Elections::end_block(System::block_number());
-			assert_eq!(Elections::members(), vec![(4, 40), (5, 50)]);
-			assert_eq!(Elections::runners_up(), vec![(2, 20), (3, 30)]);
+			assert_eq!(members_and_stake(), vec![(4, 40), (5, 50)]);
+			assert_eq!(runners_up_and_stake(), vec![(2, 20), (3, 30)]);
 
 			assert_ok!(vote(Origin::signed(5), vec![5], 15));
 
 			System::set_block_number(10);
 			Elections::end_block(System::block_number());
-			assert_eq!(Elections::members(), vec![(3, 30), (4, 40)]);
-			assert_eq!(Elections::runners_up(), vec![(5, 15), (2, 20)]);
+			assert_eq!(members_and_stake(), vec![(3, 30), (4, 40)]);
+			assert_eq!(runners_up_and_stake(), vec![(5, 15), (2, 20)]);
 		});
 	}
 
@@ -2324,7 +2454,7 @@ mod tests {
 			assert_ok!(Elections::remove_voter(Origin::signed(4)));
 
 			// 5 will persist as candidates despite not being in the list.
-			assert_eq!(Elections::candidates(), vec![2, 3]);
+			assert_eq!(candidate_ids(), vec![2, 3]);
 
 			System::set_block_number(10);
 			Elections::end_block(System::block_number());
@@ -2353,10 +2483,10 @@ mod tests {
 				System::set_block_number(b.into());
 				Elections::end_block(System::block_number());
 				// we keep re-electing the same folks.
-				assert_eq!(Elections::members(), vec![(4, 40), (5, 50)]);
-				assert_eq!(Elections::runners_up(), vec![(2, 20), (3, 30)]);
+				assert_eq!(members_and_stake(), vec![(4, 40), (5, 50)]);
+				assert_eq!(runners_up_and_stake(), vec![(2, 20), (3, 30)]);
 				// no new candidates but old members and runners-up are always added.
-				assert!(Elections::candidates().is_empty());
+				assert!(candidate_ids().is_empty());
 				assert_eq!(Elections::election_rounds(), b / 5);
 				assert_eq_uvec!(all_voters(), vec![2, 3, 4, 5]);
 			};
@@ -2504,7 +2634,7 @@ mod tests {
 			Elections::end_block(System::block_number());
 
 			// 3, 4 are new members, must still be bonded, nothing slashed.
-			assert_eq!(Elections::members(), vec![(3, 30), (4, 48)]);
+			assert_eq!(members_and_stake(), vec![(3, 30), (4, 48)]);
 			assert_eq!(balances(&3), (25, 5));
 			assert_eq!(balances(&4), (35, 5));
 
@@ -2554,9 +2684,9 @@ mod tests {
 			System::set_block_number(5);
 			Elections::end_block(System::block_number());
 			// id: low -> high.
-			assert_eq!(Elections::members(), vec![(4, 50), (5, 40)]);
+			assert_eq!(members_and_stake(), vec![(4, 50), (5, 40)]);
 			// merit: low -> high.
-			assert_eq!(Elections::runners_up(), vec![(3, 20), (2, 30)]);
+			assert_eq!(runners_up_and_stake(), vec![(3, 20), (2, 30)]);
 		});
 	}
 
@@ -2566,13 +2696,13 @@ mod tests {
 			assert_ok!(submit_candidacy(Origin::signed(5)));
 			assert_ok!(submit_candidacy(Origin::signed(3)));
 
-			assert_eq!(Elections::candidates(), vec![3, 5]);
+			assert_eq!(candidate_ids(), vec![3, 5]);
 
 			assert_ok!(submit_candidacy(Origin::signed(2)));
 			assert_ok!(submit_candidacy(Origin::signed(4)));
 			assert_ok!(Elections::renounce_candidacy(Origin::signed(3), Renouncing::Candidate(4)));
 
-			assert_eq!(Elections::candidates(), vec![2, 4, 5]);
+			assert_eq!(candidate_ids(), vec![2, 4, 5]);
 		})
 	}
 
@@ -2703,11 +2833,11 @@ mod tests {
 		ExtBuilder::default().build_and_execute(|| {
 			assert_ok!(submit_candidacy(Origin::signed(5)));
 			assert_eq!(balances(&5), (47, 3));
-			assert_eq!(Elections::candidates(), vec![5]);
+			assert_eq!(candidate_ids(), vec![5]);
 
 			assert_ok!(Elections::renounce_candidacy(Origin::signed(5), Renouncing::Candidate(1)));
 			assert_eq!(balances(&5), (50, 0));
-			assert!(Elections::candidates().is_empty());
+			assert!(candidate_ids().is_empty());
 		})
 	}
 
@@ -2819,7 +2949,7 @@ mod tests {
 
 			assert_eq!(Elections::members_ids(), vec![1, 4]);
 			assert_eq!(Elections::runners_up_ids(), vec![2, 3]);
-			assert!(Elections::candidates().is_empty());
+			assert!(candidate_ids().is_empty());
 		})
 	}
 
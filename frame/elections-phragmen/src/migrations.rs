@@ -0,0 +1,62 @@
+// This file is part of Substrate.
+
+// Copyright (C) 2020 Parity Technologies (UK) Ltd.
+// SPDX-License-Identifier: Apache-2.0
+
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+// 	http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Storage migrations for the elections-phragmen module.
+
+/// Migration to `Releases::V3_0_0`, which records the deposit of every voter, candidate, member
+/// and runner-up alongside them.
+pub mod v3 {
+	use crate::*;
+	use frame_support::storage::StorageValue;
+
+	/// Translate all existing `Voting`, `Candidates`, `Members` and `RunnersUp` entries to the new
+	/// layout. Since deposits were not recorded before, the current `VotingBond` and
+	/// `CandidacyBond` are assumed to be what was reserved.
+	pub fn migrate<T: Config>() -> Weight {
+		let voting_bond = T::VotingBond::get();
+		let candidacy_bond = T::CandidacyBond::get();
+		let count = sp_std::cell::Cell::new(0 as Weight);
+
+		<Voting<T>>::translate::<(BalanceOf<T>, Vec<T::AccountId>), _>(|_, (stake, votes)| {
+			count.set(count.get() + 1);
+			Some(Voter { votes, stake, deposit: voting_bond })
+		});
+
+		let _ = <Candidates<T>>::translate::<Vec<T::AccountId>, _>(|maybe_old| {
+			maybe_old.map(|old| old.into_iter().map(|c| (c, candidacy_bond)).collect::<Vec<_>>())
+		});
+
+		let to_seat_holders = |maybe_old: Option<Vec<(T::AccountId, BalanceOf<T>)>>| {
+			maybe_old.map(|old| {
+				old.into_iter()
+					.map(|(who, stake)| SeatHolder { who, stake, deposit: candidacy_bond })
+					.collect::<Vec<_>>()
+			})
+		};
+		let _ = <Members<T>>::translate(to_seat_holders);
+		let _ = <RunnersUp<T>>::translate(to_seat_holders);
+
+		StorageVersion::put(Releases::V3_0_0);
+		let count = count.get();
+		frame_support::debug::info!(
+			"elections-phragmen: migrated {} voters to the deposit-recording layout.",
+			count,
+		);
+
+		T::DbWeight::get().reads_writes(count.saturating_add(4), count.saturating_add(4))
+	}
+}
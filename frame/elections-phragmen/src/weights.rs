@@ -46,14 +46,14 @@ pub trait WeightInfo {
 	fn vote(_v: u32, ) -> Weight;
 	fn vote_update(_v: u32, ) -> Weight;
 	fn remove_voter() -> Weight;
-	fn report_defunct_voter_correct(_c: u32, _v: u32, ) -> Weight;
-	fn report_defunct_voter_incorrect(_c: u32, _v: u32, ) -> Weight;
 	fn submit_candidacy(_c: u32, ) -> Weight;
 	fn renounce_candidacy_candidate(_c: u32, ) -> Weight;
 	fn renounce_candidacy_members() -> Weight;
 	fn renounce_candidacy_runners_up() -> Weight;
 	fn remove_member_with_replacement() -> Weight;
 	fn remove_member_wrong_refund() -> Weight;
+	fn clean_defunct_voters(_v: u32, _d: u32, ) -> Weight;
+	fn election_phragmen(_c: u32, _v: u32, _e: u32, ) -> Weight;
 	
 }
 
@@ -79,22 +79,6 @@ impl<T: frame_system::Config> WeightInfo for SubstrateWeight<T> {
 			.saturating_add(T::DbWeight::get().reads(2 as Weight))
 			.saturating_add(T::DbWeight::get().writes(2 as Weight))
 			
-	}
-	fn report_defunct_voter_correct(c: u32, v: u32, ) -> Weight {
-		(0 as Weight)
-			.saturating_add((1_746_000 as Weight).saturating_mul(c as Weight))
-			.saturating_add((31_383_000 as Weight).saturating_mul(v as Weight))
-			.saturating_add(T::DbWeight::get().reads(7 as Weight))
-			.saturating_add(T::DbWeight::get().writes(3 as Weight))
-			
-	}
-	fn report_defunct_voter_incorrect(c: u32, v: u32, ) -> Weight {
-		(0 as Weight)
-			.saturating_add((1_725_000 as Weight).saturating_mul(c as Weight))
-			.saturating_add((31_293_000 as Weight).saturating_mul(v as Weight))
-			.saturating_add(T::DbWeight::get().reads(6 as Weight))
-			.saturating_add(T::DbWeight::get().writes(2 as Weight))
-			
 	}
 	fn submit_candidacy(c: u32, ) -> Weight {
 		(73_403_000 as Weight)
@@ -133,6 +117,25 @@ impl<T: frame_system::Config> WeightInfo for SubstrateWeight<T> {
 			.saturating_add(T::DbWeight::get().reads(1 as Weight))
 			
 	}
+	fn clean_defunct_voters(v: u32, d: u32, ) -> Weight {
+		(0 as Weight)
+			.saturating_add((114_000 as Weight).saturating_mul(v as Weight))
+			.saturating_add((155_382_000 as Weight).saturating_mul(d as Weight))
+			.saturating_add(T::DbWeight::get().reads(4 as Weight))
+			.saturating_add(T::DbWeight::get().reads((3 as Weight).saturating_mul(v as Weight)))
+			.saturating_add(T::DbWeight::get().writes((3 as Weight).saturating_mul(d as Weight)))
+			
+	}
+	fn election_phragmen(c: u32, v: u32, e: u32, ) -> Weight {
+		(0 as Weight)
+			.saturating_add((1_846_000 as Weight).saturating_mul(c as Weight))
+			.saturating_add((359_000 as Weight).saturating_mul(v as Weight))
+			.saturating_add((27_153_000 as Weight).saturating_mul(e as Weight))
+			.saturating_add(T::DbWeight::get().reads((2 as Weight).saturating_mul(c as Weight)))
+			.saturating_add(T::DbWeight::get().reads((1 as Weight).saturating_mul(v as Weight)))
+			.saturating_add(T::DbWeight::get().writes((2 as Weight).saturating_mul(c as Weight)))
+			
+	}
 	
 }
 
@@ -157,22 +160,6 @@ impl WeightInfo for () {
 			.saturating_add(RocksDbWeight::get().reads(2 as Weight))
 			.saturating_add(RocksDbWeight::get().writes(2 as Weight))
 			
-	}
-	fn report_defunct_voter_correct(c: u32, v: u32, ) -> Weight {
-		(0 as Weight)
-			.saturating_add((1_746_000 as Weight).saturating_mul(c as Weight))
-			.saturating_add((31_383_000 as Weight).saturating_mul(v as Weight))
-			.saturating_add(RocksDbWeight::get().reads(7 as Weight))
-			.saturating_add(RocksDbWeight::get().writes(3 as Weight))
-			
-	}
-	fn report_defunct_voter_incorrect(c: u32, v: u32, ) -> Weight {
-		(0 as Weight)
-			.saturating_add((1_725_000 as Weight).saturating_mul(c as Weight))
-			.saturating_add((31_293_000 as Weight).saturating_mul(v as Weight))
-			.saturating_add(RocksDbWeight::get().reads(6 as Weight))
-			.saturating_add(RocksDbWeight::get().writes(2 as Weight))
-			
 	}
 	fn submit_candidacy(c: u32, ) -> Weight {
 		(73_403_000 as Weight)
@@ -211,5 +198,24 @@ impl WeightInfo for () {
 			.saturating_add(RocksDbWeight::get().reads(1 as Weight))
 			
 	}
+	fn clean_defunct_voters(v: u32, d: u32, ) -> Weight {
+		(0 as Weight)
+			.saturating_add((114_000 as Weight).saturating_mul(v as Weight))
+			.saturating_add((155_382_000 as Weight).saturating_mul(d as Weight))
+			.saturating_add(RocksDbWeight::get().reads(4 as Weight))
+			.saturating_add(RocksDbWeight::get().reads((3 as Weight).saturating_mul(v as Weight)))
+			.saturating_add(RocksDbWeight::get().writes((3 as Weight).saturating_mul(d as Weight)))
+			
+	}
+	fn election_phragmen(c: u32, v: u32, e: u32, ) -> Weight {
+		(0 as Weight)
+			.saturating_add((1_846_000 as Weight).saturating_mul(c as Weight))
+			.saturating_add((359_000 as Weight).saturating_mul(v as Weight))
+			.saturating_add((27_153_000 as Weight).saturating_mul(e as Weight))
+			.saturating_add(RocksDbWeight::get().reads((2 as Weight).saturating_mul(c as Weight)))
+			.saturating_add(RocksDbWeight::get().reads((1 as Weight).saturating_mul(v as Weight)))
+			.saturating_add(RocksDbWeight::get().writes((2 as Weight).saturating_mul(c as Weight)))
+			
+	}
 	
 }
@@ -0,0 +1,155 @@
+// This file is part of Substrate.
+
+// Copyright (C) 2020 Parity Technologies (UK) Ltd.
+// SPDX-License-Identifier: Apache-2.0
+
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+// 	http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Ranked collective pallet benchmarking.
+
+use super::*;
+
+use frame_benchmarking::{benchmarks, account, whitelisted_caller};
+use frame_support::traits::UnfilteredDispatchable;
+use frame_system::RawOrigin as SystemOrigin;
+
+use crate::Module as Collective;
+
+const SEED: u32 = 0;
+
+fn make_member<T: Config>(rank: Rank) -> T::AccountId {
+	let who = account::<T::AccountId>("member", MemberCount::get(0), SEED);
+	assert!(Collective::<T>::do_add_member(who.clone()).is_ok());
+	for _ in 0..rank {
+		assert!(Collective::<T>::do_promote_member(who.clone()).is_ok());
+	}
+	who
+}
+
+benchmarks! {
+	_ { }
+
+	add_member {
+		let who = account::<T::AccountId>("member", 0, SEED);
+		let origin = T::PromoteOrigin::successful_origin();
+		let call = Call::<T>::add_member(who.clone());
+	}: { call.dispatch_bypass_filter(origin)? }
+	verify {
+		assert_eq!(MemberCount::get(0), 1);
+	}
+
+	remove_member {
+		let r in 0 .. 10;
+		let rank = r as u16;
+		let first = make_member::<T>(rank);
+		let who = make_member::<T>(rank);
+		let last = make_member::<T>(rank);
+		let last_index = (0..=rank)
+			.map(|r| IdToIndex::<T>::get(r, &last).unwrap())
+			.collect::<Vec<_>>();
+		let origin = T::DemoteOrigin::successful_origin();
+		let call = Call::<T>::remove_member(who.clone(), rank);
+	}: { call.dispatch_bypass_filter(origin)? }
+	verify {
+		for r in 0..=rank {
+			assert_eq!(MemberCount::get(r), 2);
+			assert_ne!(last_index[r as usize], IdToIndex::<T>::get(r, &last).unwrap());
+		}
+		assert!(IdToIndex::<T>::get(0, &first).is_some());
+	}
+
+	promote_member {
+		let r in 0 .. 10;
+		let rank = r as u16;
+		let who = make_member::<T>(rank);
+		let origin = T::PromoteOrigin::successful_origin();
+		let call = Call::<T>::promote_member(who.clone());
+	}: { call.dispatch_bypass_filter(origin)? }
+	verify {
+		assert_eq!(Members::<T>::get(&who).unwrap().rank, rank + 1);
+	}
+
+	demote_member {
+		let r in 0 .. 10;
+		let rank = r as u16;
+		let first = make_member::<T>(rank);
+		let who = make_member::<T>(rank);
+		let last = make_member::<T>(rank);
+		let last_index = IdToIndex::<T>::get(rank, &last).unwrap();
+		let origin = T::DemoteOrigin::successful_origin();
+		let call = Call::<T>::demote_member(who.clone());
+	}: { call.dispatch_bypass_filter(origin)? }
+	verify {
+		assert_eq!(Members::<T>::get(&who).map(|x| x.rank), rank.checked_sub(1));
+		assert_eq!(MemberCount::get(rank), 2);
+		assert_ne!(last_index, IdToIndex::<T>::get(rank, &last).unwrap());
+		assert!(IdToIndex::<T>::get(rank, &first).is_some());
+	}
+
+	vote {
+		let class = T::Polls::classes().into_iter().next().unwrap();
+		let rank = T::MinRankOfClass::convert(class);
+
+		let caller = make_member::<T>(rank);
+		let poll = T::Polls::create_ongoing(class).expect("Must always be able to create a poll");
+
+		// Vote once.
+		assert!(Collective::<T>::vote(SystemOrigin::Signed(caller.clone()).into(), poll, true).is_ok());
+	}: _(SystemOrigin::Signed(caller.clone()), poll, false)
+	verify {
+		let votes = T::VoteWeight::convert(0);
+		assert_eq!(Voting::<T>::get(&poll, &caller), Some(VoteRecord::Nay(votes)));
+		assert_eq!(T::Polls::as_ongoing(poll).unwrap().0, Tally::from_parts(0, 0, votes));
+	}
+
+	cleanup_poll {
+		let n in 1 .. 100;
+
+		// Create a poll
+		let class = T::Polls::classes().into_iter().next().unwrap();
+		let rank = T::MinRankOfClass::convert(class);
+		let poll = T::Polls::create_ongoing(class).expect("Must always be able to create a poll");
+
+		// Vote in the poll by each of `n` members
+		for _ in 0..n {
+			let voter = make_member::<T>(rank);
+			Collective::<T>::vote(SystemOrigin::Signed(voter).into(), poll, true)?;
+		}
+
+		// End the poll.
+		T::Polls::end_ongoing(poll, false).expect("Must always be able to end a poll");
+
+		assert_eq!(Voting::<T>::iter_prefix(&poll).count(), n as usize);
+		let caller: T::AccountId = whitelisted_caller();
+	}: _(SystemOrigin::Signed(caller), poll, n + 1)
+	verify {
+		assert_eq!(Voting::<T>::iter_prefix(&poll).count(), 0);
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use crate::tests::{new_test_ext, Test};
+	use frame_support::assert_ok;
+
+	#[test]
+	fn test_benchmarks() {
+		new_test_ext().execute_with(|| assert_ok!(test_benchmark_add_member::<Test>()));
+		new_test_ext().execute_with(|| assert_ok!(test_benchmark_remove_member::<Test>()));
+		new_test_ext().execute_with(|| assert_ok!(test_benchmark_promote_member::<Test>()));
+		new_test_ext().execute_with(|| assert_ok!(test_benchmark_demote_member::<Test>()));
+		new_test_ext().execute_with(|| assert_ok!(test_benchmark_vote::<Test>()));
+		new_test_ext().execute_with(|| assert_ok!(test_benchmark_cleanup_poll::<Test>()));
+	}
+}
@@ -0,0 +1,546 @@
+// This file is part of Substrate.
+
+// Copyright (C) 2020 Parity Technologies (UK) Ltd.
+// SPDX-License-Identifier: Apache-2.0
+
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+// 	http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! # Ranked collective system.
+//!
+//! This is a membership pallet providing a `Tally` implementation ready for use with polling
+//! systems such as the Referenda pallet. Members each have a rank, with zero being the lowest.
+//! There is no complexity limitation on either the number of members at a rank or the number of
+//! ranks in the system thus allowing potentially public membership. A member of at least a given
+//! rank can be selected at random in O(1) time, allowing for various games to be constructed using
+//! this as a primitive. Members may only be promoted and demoted by one rank at a time, however
+//! all operations (save one) are O(1) in complexity. The only operation which is not O(1) is the
+//! `remove_member` since they must be removed from all ranks from the present down to zero.
+//!
+//! Different ranks have different voting power, and are able to vote in different polls. In
+//! general rank privileges are cumulative. Higher ranks are able to vote in any polls open to
+//! lower ranks. Similarly, higher ranks always have at least as much voting power in any given
+//! poll as lower ranks.
+//!
+//! Two `Config` trait items control these "rank privileges": `MinRankOfClass` and `VoteWeight`.
+//! The first controls which ranks are allowed to vote on a particular class of poll. The second
+//! controls the weight of a vote given the voter's rank compared to the minimum rank of the poll.
+//!
+//! An origin control, `EnsureRanked`, ensures that the origin is a member of the collective of
+//! at least a particular rank.
+//!
+//! Where `pallet-collective` decides motions through its own proposal, threshold and close
+//! machinery, here the decision is left to the polling system (e.g. the approval and support
+//! curves of a referendum track) and this pallet only provides the tally.
+
+#![cfg_attr(not(feature = "std"), no_std)]
+#![recursion_limit="128"]
+
+use sp_std::{prelude::*, marker::PhantomData};
+use sp_core::u32_trait::Value as U32;
+use sp_runtime::{
+	Perbill, RuntimeDebug,
+	traits::Convert,
+};
+use frame_support::{
+	codec::{Decode, Encode},
+	decl_error, decl_event, decl_module, decl_storage, ensure,
+	CloneNoBound, EqNoBound, PartialEqNoBound, RuntimeDebugNoBound,
+	dispatch::{DispatchError, DispatchResultWithPostInfo},
+	storage::IterableStorageDoubleMap,
+	traits::{EnsureOrigin, voting::{Polling, PollStatus, VoteTally}},
+};
+use frame_system::{ensure_signed, EnsureSigned};
+
+pub mod weights;
+pub use weights::WeightInfo;
+
+#[cfg(test)]
+mod tests;
+
+#[cfg(feature = "runtime-benchmarks")]
+mod benchmarking;
+
+/// A number of members.
+pub type MemberIndex = u32;
+
+/// Member rank.
+pub type Rank = u16;
+
+/// Votes.
+pub type Votes = u32;
+
+/// The tally type used by this pallet.
+pub type TallyOf<T> = Tally<Module<T>>;
+type PollIndexOf<T> = <<T as Config>::Polls as Polling<TallyOf<T>>>::Index;
+type ClassOf<T> = <<T as Config>::Polls as Polling<TallyOf<T>>>::Class;
+
+/// Something which can provide the total number of voters in a poll.
+pub trait GetMaxVoters {
+	/// Return the maximum number of voters.
+	fn get_max_voters() -> MemberIndex;
+}
+
+/// Aggregated votes for an ongoing poll by members of the ranked collective.
+#[derive(Encode, Decode, CloneNoBound, PartialEqNoBound, EqNoBound, RuntimeDebugNoBound)]
+pub struct Tally<M: GetMaxVoters> {
+	/// The number of members voting aye, regardless of their rank.
+	pub bare_ayes: MemberIndex,
+	/// The rank-weighted aye votes.
+	pub ayes: Votes,
+	/// The rank-weighted nay votes.
+	pub nays: Votes,
+	/// Dummy.
+	dummy: PhantomData<M>,
+}
+
+impl<M: GetMaxVoters> Default for Tally<M> {
+	fn default() -> Self {
+		Self::from_parts(0, 0, 0)
+	}
+}
+
+impl<M: GetMaxVoters> Tally<M> {
+	/// Create a tally from its constituent parts.
+	pub fn from_parts(bare_ayes: MemberIndex, ayes: Votes, nays: Votes) -> Self {
+		Tally { bare_ayes, ayes, nays, dummy: PhantomData }
+	}
+}
+
+impl<M: GetMaxVoters> VoteTally<Votes> for Tally<M> {
+	fn ayes(&self) -> Votes {
+		self.bare_ayes
+	}
+
+	fn support(&self) -> Perbill {
+		Perbill::from_rational_approximation(self.bare_ayes, M::get_max_voters())
+	}
+
+	fn approval(&self) -> Perbill {
+		Perbill::from_rational_approximation(self.ayes, 1.max(self.ayes + self.nays))
+	}
+
+	#[cfg(feature = "runtime-benchmarks")]
+	fn unanimity() -> Self {
+		let max = M::get_max_voters();
+		Self::from_parts(max, max, 0)
+	}
+
+	#[cfg(feature = "runtime-benchmarks")]
+	fn rejection() -> Self {
+		Self::from_parts(0, 0, M::get_max_voters())
+	}
+}
+
+/// Record needed for every member.
+#[derive(Encode, Decode, Copy, Clone, Default, PartialEq, Eq, RuntimeDebug)]
+pub struct MemberRecord {
+	/// The rank of the member.
+	pub rank: Rank,
+}
+
+/// Record needed for every vote.
+#[derive(Encode, Decode, Copy, Clone, PartialEq, Eq, RuntimeDebug)]
+pub enum VoteRecord {
+	/// Vote was an aye with given vote weight.
+	Aye(Votes),
+	/// Vote was a nay with given vote weight.
+	Nay(Votes),
+}
+
+impl From<(bool, Votes)> for VoteRecord {
+	fn from((aye, votes): (bool, Votes)) -> Self {
+		match aye {
+			true => VoteRecord::Aye(votes),
+			false => VoteRecord::Nay(votes),
+		}
+	}
+}
+
+/// Vote-weight scheme where all voters get one vote regardless of rank.
+pub struct Unit;
+impl Convert<Rank, Votes> for Unit {
+	fn convert(_: Rank) -> Votes {
+		1
+	}
+}
+
+/// Vote-weight scheme where all voters get one vote plus an additional vote for every excess rank
+/// they have. I.e.:
+///
+/// - Each member with an excess rank of 0 gets 1 vote;
+/// - ...with an excess rank of 1 gets 2 votes;
+/// - ...with an excess rank of 2 gets 3 votes;
+/// - ...with an excess rank of 3 gets 4 votes;
+/// - ...with an excess rank of 4 gets 5 votes.
+pub struct Linear;
+impl Convert<Rank, Votes> for Linear {
+	fn convert(r: Rank) -> Votes {
+		(r as Votes).saturating_add(1)
+	}
+}
+
+/// Vote-weight scheme where all voters get one vote plus additional votes for every excess rank
+/// they have incrementing by one vote for each excess rank. I.e.:
+///
+/// - Each member with an excess rank of 0 gets 1 vote;
+/// - ...with an excess rank of 1 gets 3 votes;
+/// - ...with an excess rank of 2 gets 6 votes;
+/// - ...with an excess rank of 3 gets 10 votes;
+/// - ...with an excess rank of 4 gets 15 votes.
+pub struct Geometric;
+impl Convert<Rank, Votes> for Geometric {
+	fn convert(r: Rank) -> Votes {
+		let v = (r as Votes).saturating_add(1);
+		v.saturating_mul(v.saturating_add(1)) / 2
+	}
+}
+
+pub trait Config: frame_system::Config {
+	/// The outer event type.
+	type Event: From<Event<Self>> + Into<<Self as frame_system::Config>::Event>;
+
+	/// Weight information for extrinsics in this pallet.
+	type WeightInfo: WeightInfo;
+
+	/// The origin required to add or promote a member.
+	type PromoteOrigin: EnsureOrigin<Self::Origin>;
+
+	/// The origin required to demote or remove a member.
+	type DemoteOrigin: EnsureOrigin<Self::Origin>;
+
+	/// The polling system used for our voting.
+	type Polls: Polling<TallyOf<Self>, Votes=Votes, Moment=Self::BlockNumber>;
+
+	/// Convert the tally class into the minimum rank required to vote on the poll.
+	type MinRankOfClass: Convert<ClassOf<Self>, Rank>;
+
+	/// Convert a rank_delta into a number of votes the rank gets.
+	///
+	/// Rank_delta is defined as the number of ranks above the minimum required to take part
+	/// in the poll.
+	type VoteWeight: Convert<Rank, Votes>;
+}
+
+decl_storage! {
+	trait Store for Module<T: Config> as RankedCollective {
+		/// The number of members in the collective who have at least the rank according to the
+		/// index of the vec.
+		pub MemberCount get(fn member_count): map hasher(twox_64_concat) Rank => MemberIndex;
+
+		/// The current members of the collective.
+		pub Members get(fn members): map hasher(twox_64_concat) T::AccountId => Option<MemberRecord>;
+
+		/// The index of each ranks's member into the group of members who have at least that rank.
+		pub IdToIndex get(fn id_to_index):
+			double_map hasher(twox_64_concat) Rank, hasher(twox_64_concat) T::AccountId
+			=> Option<MemberIndex>;
+
+		/// The members in the collective by index. All indices in the range `0..MemberCount` will
+		/// return `Some`, however a member's index is not guaranteed to remain unchanged over time.
+		pub IndexToId get(fn index_to_id):
+			double_map hasher(twox_64_concat) Rank, hasher(twox_64_concat) MemberIndex
+			=> Option<T::AccountId>;
+
+		/// Votes on a given proposal, if it is ongoing.
+		pub Voting get(fn voting):
+			double_map hasher(twox_64_concat) PollIndexOf<T>, hasher(twox_64_concat) T::AccountId
+			=> Option<VoteRecord>;
+	}
+}
+
+decl_event! {
+	pub enum Event<T> where
+		<T as frame_system::Config>::AccountId,
+		PollIndex = PollIndexOf<T>,
+		Tally = TallyOf<T>,
+	{
+		/// A member has been added. \[who\]
+		MemberAdded(AccountId),
+		/// The member's rank has been changed to the given rank. \[who, rank\]
+		RankChanged(AccountId, Rank),
+		/// The member has been removed from the given rank (and all ranks below it).
+		/// \[who, rank\]
+		MemberRemoved(AccountId, Rank),
+		/// The member has voted on the poll with the given vote, leading to an updated tally.
+		/// \[who, poll, vote, tally\]
+		Voted(AccountId, PollIndex, VoteRecord, Tally),
+	}
+}
+
+decl_error! {
+	pub enum Error for Module<T: Config> {
+		/// Account is already a member.
+		AlreadyMember,
+		/// Account is not a member.
+		NotMember,
+		/// The given poll index is unknown or has closed.
+		NotPolling,
+		/// The given poll is still ongoing.
+		Ongoing,
+		/// There are no further records to be removed.
+		NoneRemaining,
+		/// Unexpected error in state.
+		Corruption,
+		/// The member's rank is too low to vote.
+		RankTooLow,
+		/// The member's rank is too high to be promoted further.
+		RankTooHigh,
+		/// The information provided is incorrect.
+		InvalidWitness,
+	}
+}
+
+decl_module! {
+	pub struct Module<T: Config> for enum Call where origin: T::Origin {
+		type Error = Error<T>;
+
+		fn deposit_event() = default;
+
+		/// Introduce a new member.
+		///
+		/// - `origin`: Must be the `PromoteOrigin`.
+		/// - `who`: Account of non-member which will become a member.
+		///
+		/// Weight: `O(1)`
+		#[weight = T::WeightInfo::add_member()]
+		fn add_member(origin, who: T::AccountId) {
+			T::PromoteOrigin::ensure_origin(origin)?;
+			Self::do_add_member(who)?;
+		}
+
+		/// Increment the rank of an existing member by one.
+		///
+		/// - `origin`: Must be the `PromoteOrigin`.
+		/// - `who`: Account of existing member.
+		///
+		/// Weight: `O(1)`
+		#[weight = T::WeightInfo::promote_member()]
+		fn promote_member(origin, who: T::AccountId) {
+			T::PromoteOrigin::ensure_origin(origin)?;
+			Self::do_promote_member(who)?;
+		}
+
+		/// Decrement the rank of an existing member by one. If the member is already at rank zero,
+		/// then they are removed entirely.
+		///
+		/// - `origin`: Must be the `DemoteOrigin`.
+		/// - `who`: Account of existing member of rank greater than zero.
+		///
+		/// Weight: `O(1)`, less if the member's index is highest in its rank.
+		#[weight = T::WeightInfo::demote_member()]
+		fn demote_member(origin, who: T::AccountId) {
+			T::DemoteOrigin::ensure_origin(origin)?;
+			let mut record = Self::ensure_member(&who)?;
+			let rank = record.rank;
+
+			Self::remove_from_rank(&who, rank)?;
+			match rank.checked_sub(1) {
+				None => {
+					Members::<T>::remove(&who);
+					Self::deposit_event(RawEvent::MemberRemoved(who, 0));
+				},
+				Some(rank) => {
+					record.rank = rank;
+					Members::<T>::insert(&who, &record);
+					Self::deposit_event(RawEvent::RankChanged(who, rank));
+				},
+			}
+		}
+
+		/// Remove the member entirely.
+		///
+		/// - `origin`: Must be the `DemoteOrigin`.
+		/// - `who`: Account of existing member of rank greater than zero.
+		/// - `min_rank`: The rank of the member or greater.
+		///
+		/// Weight: `O(min_rank)`.
+		#[weight = T::WeightInfo::remove_member(*min_rank as u32)]
+		fn remove_member(origin, who: T::AccountId, min_rank: Rank) -> DispatchResultWithPostInfo {
+			T::DemoteOrigin::ensure_origin(origin)?;
+			let MemberRecord { rank, .. } = Self::ensure_member(&who)?;
+			ensure!(min_rank >= rank, Error::<T>::InvalidWitness);
+
+			for r in 0..=rank {
+				Self::remove_from_rank(&who, r)?;
+			}
+			Members::<T>::remove(&who);
+			Self::deposit_event(RawEvent::MemberRemoved(who, rank));
+			Ok(Some(T::WeightInfo::remove_member(rank as u32)).into())
+		}
+
+		/// Add an aye or nay vote for the sender to the given proposal.
+		///
+		/// - `origin`: Must be `Signed` by a member account.
+		/// - `poll`: Index of a poll which is ongoing.
+		/// - `aye`: `true` if the vote is to approve the proposal, `false` otherwise.
+		///
+		/// Weight: `O(1)`, less if there was no previous vote on the poll by the member.
+		#[weight = T::WeightInfo::vote()]
+		fn vote(origin, poll: PollIndexOf<T>, aye: bool) {
+			let who = ensure_signed(origin)?;
+			let record = Self::ensure_member(&who)?;
+			let (vote, tally) = T::Polls::try_access_poll(poll, |status| {
+				match status {
+					PollStatus::None | PollStatus::Completed(..) =>
+						Err(Error::<T>::NotPolling.into()),
+					PollStatus::Ongoing(tally, class) => {
+						match Voting::<T>::get(&poll, &who) {
+							Some(VoteRecord::Aye(votes)) => {
+								tally.bare_ayes = tally.bare_ayes.saturating_sub(1);
+								tally.ayes = tally.ayes.saturating_sub(votes);
+							},
+							Some(VoteRecord::Nay(votes)) =>
+								tally.nays = tally.nays.saturating_sub(votes),
+							None => {},
+						}
+						let min_rank = T::MinRankOfClass::convert(class);
+						let votes = Self::rank_to_votes(record.rank, min_rank)?;
+						match aye {
+							true => {
+								tally.bare_ayes = tally.bare_ayes.saturating_add(1);
+								tally.ayes = tally.ayes.saturating_add(votes);
+							},
+							false => tally.nays = tally.nays.saturating_add(votes),
+						}
+						let vote = VoteRecord::from((aye, votes));
+						Voting::<T>::insert(&poll, &who, &vote);
+						Ok((vote, tally.clone()))
+					},
+				}
+			})?;
+			Self::deposit_event(RawEvent::Voted(who, poll, vote, tally));
+		}
+
+		/// Remove votes from the given poll. It must have ended.
+		///
+		/// - `origin`: Must be `Signed` by any account.
+		/// - `poll_index`: Index of a poll which is completed and for which votes continue to
+		///   exist.
+		/// - `max`: Maximum number of vote items to remove in this call.
+		///
+		/// Weight `O(max)` (less if there are fewer items to remove than `max`).
+		#[weight = T::WeightInfo::cleanup_poll(*max)]
+		fn cleanup_poll(origin, poll_index: PollIndexOf<T>, max: u32) -> DispatchResultWithPostInfo {
+			ensure_signed(origin)?;
+			ensure!(T::Polls::as_ongoing(poll_index).is_none(), Error::<T>::Ongoing);
+
+			let voters = Voting::<T>::iter_prefix(&poll_index)
+				.map(|(who, _)| who)
+				.take(max as usize)
+				.collect::<Vec<_>>();
+			ensure!(!voters.is_empty(), Error::<T>::NoneRemaining);
+			for who in voters.iter() {
+				Voting::<T>::remove(&poll_index, who);
+			}
+			Ok(Some(T::WeightInfo::cleanup_poll(voters.len() as u32)).into())
+		}
+	}
+}
+
+impl<T: Config> Module<T> {
+	fn ensure_member(who: &T::AccountId) -> Result<MemberRecord, DispatchError> {
+		Members::<T>::get(who).ok_or(Error::<T>::NotMember.into())
+	}
+
+	fn rank_to_votes(rank: Rank, min: Rank) -> Result<Votes, DispatchError> {
+		let excess = rank.checked_sub(min).ok_or(Error::<T>::RankTooLow)?;
+		Ok(T::VoteWeight::convert(excess))
+	}
+
+	/// Remove `who` from the group of members of at least `rank`, swapping the last member of
+	/// the group into its place.
+	fn remove_from_rank(who: &T::AccountId, rank: Rank) -> Result<(), DispatchError> {
+		let last_index = MemberCount::get(rank).saturating_sub(1);
+		let index = IdToIndex::<T>::get(rank, who).ok_or(Error::<T>::Corruption)?;
+		if index != last_index {
+			let last = IndexToId::<T>::get(rank, last_index).ok_or(Error::<T>::Corruption)?;
+			IdToIndex::<T>::insert(rank, &last, index);
+			IndexToId::<T>::insert(rank, index, &last);
+		}
+		MemberCount::insert(rank, last_index);
+		IdToIndex::<T>::remove(rank, who);
+		IndexToId::<T>::remove(rank, last_index);
+		Ok(())
+	}
+
+	/// Add `who` to the group of members of at least `rank`.
+	fn add_to_rank(who: &T::AccountId, rank: Rank) {
+		let index = MemberCount::get(rank);
+		IdToIndex::<T>::insert(rank, who, index);
+		IndexToId::<T>::insert(rank, index, who);
+		MemberCount::insert(rank, index.saturating_add(1));
+	}
+
+	/// Add a member to the rank collective.
+	pub fn do_add_member(who: T::AccountId) -> Result<(), DispatchError> {
+		ensure!(!Members::<T>::contains_key(&who), Error::<T>::AlreadyMember);
+		Self::add_to_rank(&who, 0);
+		Members::<T>::insert(&who, MemberRecord { rank: 0 });
+		Self::deposit_event(RawEvent::MemberAdded(who));
+		Ok(())
+	}
+
+	/// Promote a member of the ranked collective by one rank.
+	pub fn do_promote_member(who: T::AccountId) -> Result<(), DispatchError> {
+		let mut record = Self::ensure_member(&who)?;
+		let rank = record.rank.checked_add(1).ok_or(Error::<T>::RankTooHigh)?;
+		Self::add_to_rank(&who, rank);
+		record.rank = rank;
+		Members::<T>::insert(&who, &record);
+		Self::deposit_event(RawEvent::RankChanged(who, rank));
+		Ok(())
+	}
+
+	/// Select a member of at least `rank` using an `index` into the group of such members, which
+	/// may be derived from some source of randomness.
+	pub fn member_of_rank(rank: Rank, index: MemberIndex) -> Option<T::AccountId> {
+		let count = MemberCount::get(rank);
+		if count == 0 {
+			return None
+		}
+		IndexToId::<T>::get(rank, index % count)
+	}
+}
+
+impl<T: Config> GetMaxVoters for Module<T> {
+	fn get_max_voters() -> MemberIndex {
+		MemberCount::get(0)
+	}
+}
+
+/// Guard to ensure that the given origin is a member of the collective. The account ID of the
+/// member is the `Success` value.
+pub struct EnsureRanked<T, N: U32>(PhantomData<(T, N)>);
+impl<T: Config, N: U32> EnsureOrigin<T::Origin> for EnsureRanked<T, N> {
+	type Success = T::AccountId;
+
+	fn try_origin(o: T::Origin) -> Result<Self::Success, T::Origin> {
+		let who = EnsureSigned::<T::AccountId>::try_origin(o)?;
+		match Members::<T>::get(&who) {
+			Some(MemberRecord { rank, .. }) if rank as u32 >= N::VALUE => Ok(who),
+			_ => Err(frame_system::RawOrigin::Signed(who).into()),
+		}
+	}
+
+	#[cfg(feature = "runtime-benchmarks")]
+	fn successful_origin() -> T::Origin {
+		let who = T::AccountId::default();
+		if !Members::<T>::contains_key(&who) {
+			let _ = Module::<T>::do_add_member(who.clone());
+		}
+		while Members::<T>::get(&who).map_or(false, |r| (r.rank as u32) < N::VALUE) {
+			let _ = Module::<T>::do_promote_member(who.clone());
+		}
+		frame_system::RawOrigin::Signed(who).into()
+	}
+}
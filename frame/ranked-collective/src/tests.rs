@@ -0,0 +1,375 @@
+// This file is part of Substrate.
+
+// Copyright (C) 2020 Parity Technologies (UK) Ltd.
+// SPDX-License-Identifier: Apache-2.0
+
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+// 	http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! The crate's tests.
+
+use std::collections::BTreeMap;
+use super::*;
+use crate as pallet_ranked_collective;
+use frame_support::{
+	impl_outer_origin, impl_outer_event, assert_noop, assert_ok, parameter_types,
+	weights::Weight,
+};
+use sp_core::{H256, u32_trait::{_1, _3}};
+use sp_runtime::{
+	traits::{BlakeTwo256, IdentityLookup, Identity, BadOrigin},
+	testing::Header,
+};
+use frame_system::EnsureRoot;
+
+impl_outer_origin! {
+	pub enum Origin for Test where system = frame_system {}
+}
+
+impl_outer_event! {
+	pub enum Event for Test {
+		frame_system<T>,
+		pallet_ranked_collective<T>,
+	}
+}
+
+// Workaround for https://github.com/rust-lang/rust/issues/26925 . Remove when sorted.
+#[derive(Clone, Eq, PartialEq, Debug)]
+pub struct Test;
+parameter_types! {
+	pub const BlockHashCount: u64 = 250;
+	pub const MaximumBlockWeight: Weight = 1_000_000;
+	pub const MaximumBlockLength: u32 = 2 * 1024;
+	pub const AvailableBlockRatio: Perbill = Perbill::one();
+}
+impl frame_system::Config for Test {
+	type BaseCallFilter = ();
+	type Origin = Origin;
+	type Index = u64;
+	type BlockNumber = u64;
+	type Call = ();
+	type Hash = H256;
+	type Hashing = BlakeTwo256;
+	type AccountId = u64;
+	type Lookup = IdentityLookup<Self::AccountId>;
+	type Header = Header;
+	type Event = Event;
+	type BlockHashCount = BlockHashCount;
+	type MaximumBlockWeight = MaximumBlockWeight;
+	type DbWeight = ();
+	type BlockExecutionWeight = ();
+	type ExtrinsicBaseWeight = ();
+	type MaximumExtrinsicWeight = MaximumBlockWeight;
+	type MaximumBlockLength = MaximumBlockLength;
+	type AvailableBlockRatio = AvailableBlockRatio;
+	type Version = ();
+	type PalletInfo = ();
+	type AccountData = ();
+	type OnNewAccount = ();
+	type OnKilledAccount = ();
+	type SystemWeightInfo = ();
+}
+
+#[derive(Clone, PartialEq, Eq, Debug)]
+pub enum TestPollState {
+	Ongoing(TallyOf<Test>, Rank),
+	Completed(u64, bool),
+}
+use TestPollState::*;
+
+parameter_types! {
+	pub static Polls: BTreeMap<u8, TestPollState> = vec![
+		(1, Completed(1, true)),
+		(2, Completed(2, false)),
+		(3, Ongoing(Tally::from_parts(0, 0, 0), 1)),
+	].into_iter().collect();
+}
+
+/// A polling system whose polls are held in a thread-local map, altered directly by the tests.
+pub struct TestPolls;
+impl Polling<TallyOf<Test>> for TestPolls {
+	type Index = u8;
+	type Votes = Votes;
+	type Moment = u64;
+	type Class = Rank;
+
+	fn classes() -> Vec<Self::Class> {
+		vec![0, 1, 2]
+	}
+
+	fn as_ongoing(index: u8) -> Option<(TallyOf<Test>, Self::Class)> {
+		Polls::get().remove(&index).and_then(|x| {
+			if let TestPollState::Ongoing(t, c) = x {
+				Some((t, c))
+			} else {
+				None
+			}
+		})
+	}
+
+	fn access_poll<R>(
+		index: Self::Index,
+		f: impl FnOnce(PollStatus<&mut TallyOf<Test>, u64, Rank>) -> R,
+	) -> R {
+		let mut polls = Polls::get();
+		let entry = polls.get_mut(&index);
+		let r = match entry {
+			Some(Ongoing(ref mut tally_mut_ref, class)) =>
+				f(PollStatus::Ongoing(tally_mut_ref, *class)),
+			Some(Completed(when, succeeded)) => f(PollStatus::Completed(*when, *succeeded)),
+			None => f(PollStatus::None),
+		};
+		Polls::set(polls);
+		r
+	}
+
+	fn try_access_poll<R>(
+		index: Self::Index,
+		f: impl FnOnce(PollStatus<&mut TallyOf<Test>, u64, Rank>) -> Result<R, DispatchError>,
+	) -> Result<R, DispatchError> {
+		let mut polls = Polls::get();
+		let entry = polls.get_mut(&index);
+		let r = match entry {
+			Some(Ongoing(ref mut tally_mut_ref, class)) =>
+				f(PollStatus::Ongoing(tally_mut_ref, *class)),
+			Some(Completed(when, succeeded)) => f(PollStatus::Completed(*when, *succeeded)),
+			None => f(PollStatus::None),
+		}?;
+		Polls::set(polls);
+		Ok(r)
+	}
+
+	#[cfg(feature = "runtime-benchmarks")]
+	fn create_ongoing(class: Self::Class) -> Result<Self::Index, ()> {
+		let mut polls = Polls::get();
+		let i = polls.keys().rev().next().map_or(0, |x| x + 1);
+		polls.insert(i, Ongoing(Tally::default(), class));
+		Polls::set(polls);
+		Ok(i)
+	}
+
+	#[cfg(feature = "runtime-benchmarks")]
+	fn end_ongoing(index: Self::Index, approved: bool) -> Result<(), ()> {
+		let mut polls = Polls::get();
+		match polls.get(&index) {
+			Some(Ongoing(..)) => {},
+			_ => return Err(()),
+		}
+		let now = frame_system::Module::<Test>::block_number();
+		polls.insert(index, Completed(now, approved));
+		Polls::set(polls);
+		Ok(())
+	}
+}
+
+impl Config for Test {
+	type WeightInfo = ();
+	type Event = Event;
+	type PromoteOrigin = EnsureRoot<Self::AccountId>;
+	type DemoteOrigin = EnsureRoot<Self::AccountId>;
+	type Polls = TestPolls;
+	type MinRankOfClass = Identity;
+	type VoteWeight = Geometric;
+}
+
+pub fn new_test_ext() -> sp_io::TestExternalities {
+	let t = frame_system::GenesisConfig::default().build_storage::<Test>().unwrap();
+	let mut ext = sp_io::TestExternalities::new(t);
+	ext.execute_with(|| frame_system::Module::<Test>::set_block_number(1));
+	ext
+}
+
+type Club = Module<Test>;
+
+fn next_block() {
+	let n = frame_system::Module::<Test>::block_number() + 1;
+	frame_system::Module::<Test>::set_block_number(n);
+}
+
+fn member_count(r: Rank) -> MemberIndex {
+	MemberCount::get(r)
+}
+
+fn tally(index: u8) -> TallyOf<Test> {
+	<TestPolls as Polling<TallyOf<Test>>>::as_ongoing(index).expect("No poll").0
+}
+
+fn end_poll(index: u8, approved: bool) {
+	let mut polls = Polls::get();
+	polls.insert(index, Completed(frame_system::Module::<Test>::block_number(), approved));
+	Polls::set(polls);
+}
+
+/// Add `who` as a member of rank `rank`.
+fn add_member_of_rank(who: u64, rank: Rank) {
+	assert_ok!(Club::add_member(Origin::root(), who));
+	for _ in 0..rank {
+		assert_ok!(Club::promote_member(Origin::root(), who));
+	}
+}
+
+#[test]
+fn basic_stuff() {
+	new_test_ext().execute_with(|| {
+		assert_eq!(tally(3), Tally::from_parts(0, 0, 0));
+		assert_eq!(member_count(0), 0);
+		assert!(Club::members(1).is_none());
+	});
+}
+
+#[test]
+fn membership_works() {
+	new_test_ext().execute_with(|| {
+		assert_noop!(Club::add_member(Origin::signed(1), 1), BadOrigin);
+		assert_ok!(Club::add_member(Origin::root(), 1));
+		assert_noop!(Club::add_member(Origin::root(), 1), Error::<Test>::AlreadyMember);
+		assert_eq!(Club::members(1), Some(MemberRecord { rank: 0 }));
+		assert_eq!(member_count(0), 1);
+		assert_eq!(member_count(1), 0);
+
+		assert_noop!(Club::promote_member(Origin::signed(1), 1), BadOrigin);
+		assert_noop!(Club::promote_member(Origin::root(), 2), Error::<Test>::NotMember);
+		assert_ok!(Club::promote_member(Origin::root(), 1));
+		assert_eq!(Club::members(1), Some(MemberRecord { rank: 1 }));
+		assert_eq!(member_count(0), 1);
+		assert_eq!(member_count(1), 1);
+
+		assert_noop!(Club::demote_member(Origin::signed(1), 1), BadOrigin);
+		assert_ok!(Club::demote_member(Origin::root(), 1));
+		assert_eq!(Club::members(1), Some(MemberRecord { rank: 0 }));
+		assert_eq!(member_count(0), 1);
+		assert_eq!(member_count(1), 0);
+
+		// Demoting from the lowest rank removes the member altogether.
+		assert_ok!(Club::demote_member(Origin::root(), 1));
+		assert!(Club::members(1).is_none());
+		assert_eq!(member_count(0), 0);
+		assert_noop!(Club::demote_member(Origin::root(), 1), Error::<Test>::NotMember);
+	});
+}
+
+#[test]
+fn remove_member_works() {
+	new_test_ext().execute_with(|| {
+		add_member_of_rank(1, 0);
+		add_member_of_rank(2, 2);
+		add_member_of_rank(3, 1);
+		assert_eq!(member_count(0), 3);
+		assert_eq!(member_count(1), 2);
+		assert_eq!(member_count(2), 1);
+
+		assert_noop!(Club::remove_member(Origin::signed(1), 2, 2), BadOrigin);
+		assert_noop!(Club::remove_member(Origin::root(), 2, 1), Error::<Test>::InvalidWitness);
+		assert_ok!(Club::remove_member(Origin::root(), 2, 2));
+		assert!(Club::members(2).is_none());
+		assert_eq!(member_count(0), 2);
+		assert_eq!(member_count(1), 1);
+		assert_eq!(member_count(2), 0);
+
+		// The last member of each rank is moved into the place of the one removed.
+		assert_eq!(Club::index_to_id(0, 0), Some(1));
+		assert_eq!(Club::index_to_id(0, 1), Some(3));
+		assert_eq!(Club::id_to_index(0, 3), Some(1));
+		assert_eq!(Club::index_to_id(1, 0), Some(3));
+		assert_eq!(Club::id_to_index(1, 3), Some(0));
+		assert!(Club::index_to_id(1, 1).is_none());
+		assert!(Club::id_to_index(1, 2).is_none());
+	});
+}
+
+#[test]
+fn member_of_rank_works() {
+	new_test_ext().execute_with(|| {
+		assert_eq!(Club::member_of_rank(0, 0), None);
+		add_member_of_rank(1, 0);
+		add_member_of_rank(2, 1);
+		add_member_of_rank(3, 1);
+		assert_eq!(Club::member_of_rank(0, 0), Some(1));
+		assert_eq!(Club::member_of_rank(0, 4), Some(2));
+		assert_eq!(Club::member_of_rank(1, 0), Some(2));
+		assert_eq!(Club::member_of_rank(1, 3), Some(3));
+		assert_eq!(Club::member_of_rank(2, 0), None);
+	});
+}
+
+#[test]
+fn voting_works() {
+	new_test_ext().execute_with(|| {
+		add_member_of_rank(0, 0);
+		add_member_of_rank(1, 1);
+		add_member_of_rank(2, 2);
+		add_member_of_rank(3, 3);
+
+		// Poll 3 is of class 1, needing a rank of at least one.
+		assert_noop!(Club::vote(Origin::signed(0), 3, true), Error::<Test>::RankTooLow);
+		assert_noop!(Club::vote(Origin::signed(4), 3, true), Error::<Test>::NotMember);
+
+		assert_ok!(Club::vote(Origin::signed(1), 3, true));
+		assert_eq!(tally(3), Tally::from_parts(1, 1, 0));
+		assert_ok!(Club::vote(Origin::signed(2), 3, false));
+		assert_eq!(tally(3), Tally::from_parts(1, 1, 3));
+		assert_ok!(Club::vote(Origin::signed(3), 3, true));
+		assert_eq!(tally(3), Tally::from_parts(2, 7, 3));
+		assert_eq!(Club::voting(3, 3), Some(VoteRecord::Aye(6)));
+
+		// Votes may be changed.
+		assert_ok!(Club::vote(Origin::signed(3), 3, false));
+		assert_eq!(tally(3), Tally::from_parts(1, 1, 9));
+		assert_eq!(Club::voting(3, 3), Some(VoteRecord::Nay(6)));
+
+		// Support is measured against all members, approval against the votes cast.
+		assert_eq!(tally(3).support(), Perbill::from_percent(25));
+		assert_eq!(tally(3).approval(), Perbill::from_percent(10));
+
+		assert_noop!(Club::vote(Origin::signed(1), 1, true), Error::<Test>::NotPolling);
+		assert_noop!(Club::vote(Origin::signed(1), 9, true), Error::<Test>::NotPolling);
+	});
+}
+
+#[test]
+fn cleanup_works() {
+	new_test_ext().execute_with(|| {
+		add_member_of_rank(1, 1);
+		add_member_of_rank(2, 2);
+		add_member_of_rank(3, 3);
+		assert_ok!(Club::vote(Origin::signed(1), 3, true));
+		assert_ok!(Club::vote(Origin::signed(2), 3, false));
+		assert_ok!(Club::vote(Origin::signed(3), 3, true));
+
+		assert_noop!(Club::cleanup_poll(Origin::signed(4), 3, 10), Error::<Test>::Ongoing);
+		next_block();
+		end_poll(3, true);
+		assert_ok!(Club::cleanup_poll(Origin::signed(4), 3, 2));
+		assert_eq!(Voting::<Test>::iter_prefix(3).count(), 1);
+		assert_ok!(Club::cleanup_poll(Origin::signed(4), 3, 10));
+		assert_eq!(Voting::<Test>::iter_prefix(3).count(), 0);
+		assert_noop!(Club::cleanup_poll(Origin::signed(4), 3, 10), Error::<Test>::NoneRemaining);
+	});
+}
+
+#[test]
+fn ensure_ranked_works() {
+	new_test_ext().execute_with(|| {
+		add_member_of_rank(1, 1);
+		add_member_of_rank(2, 2);
+		add_member_of_rank(3, 3);
+
+		type Rank1 = EnsureRanked<Test, _1>;
+		type Rank3 = EnsureRanked<Test, _3>;
+		assert_eq!(Rank1::try_origin(Origin::signed(1)).unwrap(), 1);
+		assert_eq!(Rank1::try_origin(Origin::signed(3)).unwrap(), 3);
+		assert!(Rank3::try_origin(Origin::signed(2)).is_err());
+		assert_eq!(Rank3::try_origin(Origin::signed(3)).unwrap(), 3);
+		assert!(Rank1::try_origin(Origin::signed(4)).is_err());
+		assert!(Rank1::try_origin(Origin::root()).is_err());
+	});
+}
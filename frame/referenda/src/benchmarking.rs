@@ -0,0 +1,150 @@
+// This file is part of Substrate.
+
+// Copyright (C) 2020 Parity Technologies (UK) Ltd.
+// SPDX-License-Identifier: Apache-2.0
+
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+// 	http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Referenda pallet benchmarking.
+
+use super::*;
+
+use frame_benchmarking::{benchmarks, account, whitelist_account};
+use frame_support::traits::{Currency, EnsureOrigin, UnfilteredDispatchable};
+use frame_system::RawOrigin;
+use sp_runtime::traits::Bounded;
+
+use crate::Module as Referenda;
+
+const SEED: u32 = 0;
+
+fn funded_account<T: Config>(name: &'static str, index: u32) -> T::AccountId {
+	let caller: T::AccountId = account(name, index, SEED);
+	T::Currency::make_free_balance_be(&caller, BalanceOf::<T>::max_value());
+	caller
+}
+
+/// Submit a referendum on the track of the `Root` origin, which must exist for these benchmarks.
+fn create_referendum<T: Config>() -> (T::AccountId, ReferendumIndex) {
+	let caller = funded_account::<T>("caller", 0);
+	whitelist_account!(caller);
+	let proposal: T::Proposal = Call::<T>::nudge_referendum(0).into();
+	assert!(Referenda::<T>::submit(
+		RawOrigin::Signed(caller.clone()).into(),
+		RawOrigin::Root.into(),
+		Box::new(proposal),
+		DispatchTime::After(0u32.into()),
+	).is_ok());
+	let index = ReferendumCount::get() - 1;
+	(caller, index)
+}
+
+fn place_deposit<T: Config>(index: ReferendumIndex) {
+	let caller = funded_account::<T>("caller", 0);
+	whitelist_account!(caller);
+	assert!(Referenda::<T>::place_decision_deposit(RawOrigin::Signed(caller).into(), index).is_ok());
+}
+
+fn info<T: Config>(index: ReferendumIndex) -> &'static TrackInfoOf<T> {
+	let status = Referenda::<T>::ensure_ongoing(index).unwrap();
+	T::Tracks::info(status.track).expect("Id value returned from T::Tracks")
+}
+
+benchmarks! {
+	_ { }
+
+	submit {
+		let caller = funded_account::<T>("caller", 0);
+		whitelist_account!(caller);
+		let proposal: T::Proposal = Call::<T>::nudge_referendum(0).into();
+	}: _(
+		RawOrigin::Signed(caller),
+		RawOrigin::Root.into(),
+		Box::new(proposal),
+		DispatchTime::After(0u32.into())
+	) verify {
+		let index = ReferendumCount::get().checked_sub(1).unwrap();
+		assert!(matches!(ReferendumInfoFor::<T>::get(index), Some(ReferendumInfo::Ongoing(_))));
+	}
+
+	place_decision_deposit {
+		let (caller, index) = create_referendum::<T>();
+	}: _(RawOrigin::Signed(caller), index)
+	verify {
+		let status = Referenda::<T>::ensure_ongoing(index).unwrap();
+		assert!(status.decision_deposit.is_some());
+	}
+
+	refund_decision_deposit {
+		let (caller, index) = create_referendum::<T>();
+		place_deposit::<T>(index);
+		let origin = T::CancelOrigin::successful_origin();
+		Referenda::<T>::cancel(origin, index)?;
+	}: _(RawOrigin::Signed(caller), index)
+	verify {
+		assert!(matches!(
+			ReferendumInfoFor::<T>::get(index),
+			Some(ReferendumInfo::Cancelled(_, _, None))
+		));
+	}
+
+	cancel {
+		let (_caller, index) = create_referendum::<T>();
+		place_deposit::<T>(index);
+		let origin = T::CancelOrigin::successful_origin();
+		let call = Call::<T>::cancel(index);
+	}: { call.dispatch_bypass_filter(origin)? }
+	verify {
+		assert!(matches!(ReferendumInfoFor::<T>::get(index), Some(ReferendumInfo::Cancelled(..))));
+	}
+
+	kill {
+		let (_caller, index) = create_referendum::<T>();
+		place_deposit::<T>(index);
+		let origin = T::KillOrigin::successful_origin();
+		let call = Call::<T>::kill(index);
+	}: { call.dispatch_bypass_filter(origin)? }
+	verify {
+		assert!(matches!(ReferendumInfoFor::<T>::get(index), Some(ReferendumInfo::Killed(..))));
+	}
+
+	nudge_referendum {
+		let (_caller, index) = create_referendum::<T>();
+		place_deposit::<T>(index);
+		// Move past the preparation period so that the nudge begins the decision.
+		let prepare_end = frame_system::Module::<T>::block_number()
+			.saturating_add(info::<T>(index).prepare_period);
+		frame_system::Module::<T>::set_block_number(prepare_end);
+	}: _(RawOrigin::Root, index)
+	verify {
+		let status = Referenda::<T>::ensure_ongoing(index).unwrap();
+		assert!(status.deciding.is_some());
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use crate::mock::{new_test_ext, Test};
+	use frame_support::assert_ok;
+
+	#[test]
+	fn test_benchmarks() {
+		new_test_ext().execute_with(|| assert_ok!(test_benchmark_submit::<Test>()));
+		new_test_ext().execute_with(|| assert_ok!(test_benchmark_place_decision_deposit::<Test>()));
+		new_test_ext().execute_with(|| assert_ok!(test_benchmark_refund_decision_deposit::<Test>()));
+		new_test_ext().execute_with(|| assert_ok!(test_benchmark_cancel::<Test>()));
+		new_test_ext().execute_with(|| assert_ok!(test_benchmark_kill::<Test>()));
+		new_test_ext().execute_with(|| assert_ok!(test_benchmark_nudge_referendum::<Test>()));
+	}
+}
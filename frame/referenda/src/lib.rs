@@ -0,0 +1,894 @@
+// This file is part of Substrate.
+
+// Copyright (C) 2020 Parity Technologies (UK) Ltd.
+// SPDX-License-Identifier: Apache-2.0
+
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+// 	http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! # Referenda Pallet
+//!
+//! - [`Config`]
+//! - [`Call`]
+//!
+//! ## Overview
+//!
+//! A pallet for executing referenda. No voting logic is present here, and the `Polling` and
+//! `PollStatus` traits are used to allow the voting logic (likely in a pallet) to be utilized.
+//!
+//! A referendum is a vote on whether a proposal should be dispatched from a particular origin. The
+//! origin is used to determine which one of several _tracks_ that a referendum happens under.
+//! Tracks each have their own configuration which governs the voting process and parameters.
+//!
+//! A referendum's lifecycle has three main stages: Preparation, deciding and conclusion.
+//! Referenda are considered "ongoing" immediately after submission until their eventual
+//! conclusion, and votes may be cast throughout.
+//!
+//! In order to progress from preparation to being decided, three things must be in place:
+//! - There must have been a *Decision Deposit* placed, an amount determined by the track. Anyone
+//!   may place this deposit.
+//! - A period must have elapsed since submission of the referendum. This period is known as the
+//!   *Preparation Period* and is determined by the track.
+//! - The track must not already be at capacity with referendum being decided. The maximum number
+//!   of referenda which may be being decided simultaneously is determined by the track.
+//!
+//! In order to become concluded, one of three things must happen:
+//! - The referendum should remain in an unbroken _Passing_ state for a period of time. This
+//!   is known as the _Confirmation Period_ and is determined by the track. A referendum is
+//!   considered _Passing_ when there is a sufficiently high support and approval, given the
+//!   amount of time it has been being decided. Generally the threshold for what counts as being
+//!   "sufficiently high" will reduce over time. The curves setting these thresholds are determined
+//!   by the track. In this case, the referendum is considered _Approved_ and the proposal is
+//!   scheduled for dispatch.
+//! - The referendum reaches the end of its deciding phase without being _Passing_. It ends in
+//!   _Rejection_ and the proposal is not dispatched.
+//! - The referendum is cancelled.
+//!
+//! A general time-out is also in place and referenda which exist in preparation for too long may
+//! conclude without ever entering into a deciding stage.
+//!
+//! Once a referendum is concluded, the decision deposit may be refunded.
+//!
+//! ## Interface
+//!
+//! ### Dispatchable Functions
+//!
+//! - `submit` - Submit a new referendum.
+//! - `place_decision_deposit` - Place the Decision Deposit for a referendum.
+//! - `refund_decision_deposit` - Refund the Decision Deposit for a concluded referendum.
+//! - `cancel` - Cancel a referendum, refunding its deposits.
+//! - `kill` - Cancel a referendum and slash its deposits.
+//! - `nudge_referendum` - Advance a referendum onto its next logical state. Only used internally.
+
+#![recursion_limit="128"]
+#![cfg_attr(not(feature = "std"), no_std)]
+
+use sp_std::prelude::*;
+use codec::Encode;
+use sp_runtime::{
+	DispatchError, Perbill, PerThing,
+	traits::{Zero, One, Saturating, Bounded, Dispatchable, Hash, AtLeast32BitUnsigned},
+};
+#[cfg(feature = "runtime-benchmarks")]
+use sp_runtime::traits::TrailingZeroInput;
+use frame_support::{
+	decl_module, decl_storage, decl_event, decl_error, ensure, Parameter,
+	traits::{
+		Currency, ReservableCurrency, Get, EnsureOrigin, OnUnbalanced, LockIdentifier,
+		schedule::{Named as ScheduleNamed, DispatchTime},
+		voting::{Polling, PollStatus, VoteTally},
+	},
+};
+use frame_system::{ensure_signed, ensure_root};
+
+mod types;
+pub mod weights;
+pub use weights::WeightInfo;
+pub use types::{
+	TrackInfo, TracksInfo, Curve, Deposit, DecidingStatus, ReferendumInfo, ReferendumStatus,
+	ReferendumIndex, TrackIdOf, TrackInfoOf, ReferendumInfoOf, ReferendumStatusOf, BalanceOf,
+	NegativeImbalanceOf, DecidingStatusOf,
+};
+
+#[cfg(test)]
+mod mock;
+#[cfg(test)]
+mod tests;
+
+#[cfg(feature = "runtime-benchmarks")]
+pub mod benchmarking;
+
+const REFERENDA_ID: LockIdentifier = *b"referend";
+
+pub trait Config: frame_system::Config + Sized {
+	/// The proposal type which is dispatched by an approved referendum, also used for scheduling
+	/// the wake-up alarms of this pallet.
+	type Proposal: Parameter + Dispatchable<Origin=Self::Origin> + From<Call<Self>>;
+
+	type Event: From<Event<Self>> + Into<<Self as frame_system::Config>::Event>;
+
+	/// The Scheduler.
+	type Scheduler: ScheduleNamed<Self::BlockNumber, Self::Proposal, Self::PalletsOrigin>;
+
+	/// Currency type for this pallet.
+	type Currency: ReservableCurrency<Self::AccountId>;
+
+	/// Overarching type of all pallets origins. The origin with which an approved proposal is
+	/// dispatched, and by which its track is determined.
+	type PalletsOrigin: Parameter + From<frame_system::RawOrigin<Self::AccountId>>;
+
+	/// Origin from which any vote may be cancelled.
+	type CancelOrigin: EnsureOrigin<Self::Origin>;
+
+	/// Origin from which any vote may be killed.
+	type KillOrigin: EnsureOrigin<Self::Origin>;
+
+	/// Handler for the unbalanced reduction when slashing a preimage deposit.
+	type Slash: OnUnbalanced<NegativeImbalanceOf<Self>>;
+
+	/// The counting type for votes. Usually just balance.
+	type Votes: AtLeast32BitUnsigned + Parameter + Copy;
+
+	/// The tallying type.
+	type Tally: VoteTally<Self::Votes> + Default + Parameter;
+
+	/// The minimum amount to be used as a deposit for a public referendum proposal.
+	type SubmissionDeposit: Get<BalanceOf<Self>>;
+
+	/// Maximum size of the referendum queue for a single track.
+	type MaxQueued: Get<u32>;
+
+	/// The number of blocks after submission that a referendum must begin being decided by.
+	/// Once this passes, then anyone may cancel the referendum.
+	type UndecidingTimeout: Get<Self::BlockNumber>;
+
+	/// Quantization level for the referendum wakeup scheduler. A higher number will result in
+	/// fewer storage reads/writes needed for smaller voters, but also result in delays to the
+	/// automatic referendum status changes. Explicit servicing instructions are unaffected.
+	type AlarmInterval: Get<Self::BlockNumber>;
+
+	/// Information concerning the different referendum tracks.
+	type Tracks: TracksInfo<BalanceOf<Self>, Self::BlockNumber, Origin=Self::PalletsOrigin>;
+
+	/// Weight information for extrinsics in this pallet.
+	type WeightInfo: WeightInfo;
+}
+
+decl_storage! {
+	trait Store for Module<T: Config> as Referenda {
+		/// The next free referendum index, aka the number of referenda started so far.
+		pub ReferendumCount get(fn referendum_count): ReferendumIndex;
+
+		/// Information concerning any given referendum.
+		pub ReferendumInfoFor get(fn referendum_info):
+			map hasher(blake2_128_concat) ReferendumIndex => Option<ReferendumInfoOf<T>>;
+
+		/// The proposal which will be dispatched should a referendum be approved.
+		pub ProposalOf get(fn proposal_of):
+			map hasher(twox_64_concat) ReferendumIndex => Option<T::Proposal>;
+
+		/// The sorted list of referenda ready to be decided but not yet being decided, ordered by
+		/// conviction-weighted approvals.
+		///
+		/// This should be empty if `DecidingCount` is less than `TrackInfo::max_deciding`.
+		pub TrackQueue get(fn track_queue):
+			map hasher(twox_64_concat) TrackIdOf<T> => Vec<(ReferendumIndex, T::Votes)>;
+
+		/// The number of referenda being decided currently.
+		pub DecidingCount get(fn deciding_count): map hasher(twox_64_concat) TrackIdOf<T> => u32;
+	}
+}
+
+decl_event! {
+	pub enum Event<T> where
+		<T as frame_system::Config>::AccountId,
+		<T as frame_system::Config>::Hash,
+		Balance = BalanceOf<T>,
+		TrackId = TrackIdOf<T>,
+		Tally = <T as Config>::Tally,
+	{
+		/// A referendum has being submitted. \[index, track, proposal_hash\]
+		Submitted(ReferendumIndex, TrackId, Hash),
+		/// The decision deposit has been placed. \[index, who, amount\]
+		DecisionDepositPlaced(ReferendumIndex, AccountId, Balance),
+		/// The decision deposit has been refunded. \[index, who, amount\]
+		DecisionDepositRefunded(ReferendumIndex, AccountId, Balance),
+		/// A deposit has been slashed. \[who, amount\]
+		DepositSlashed(AccountId, Balance),
+		/// A referendum has moved into the deciding phase. \[index, track, proposal_hash, tally\]
+		DecisionStarted(ReferendumIndex, TrackId, Hash, Tally),
+		/// A referendum has entered its confirmation period. \[index\]
+		ConfirmStarted(ReferendumIndex),
+		/// A referendum has left its confirmation period without being approved. \[index\]
+		ConfirmAborted(ReferendumIndex),
+		/// A referendum has ended its confirmation phase and is ready for approval.
+		/// \[index, tally\]
+		Confirmed(ReferendumIndex, Tally),
+		/// A referendum has been approved and its proposal has been scheduled. \[index\]
+		Approved(ReferendumIndex),
+		/// A proposal has been rejected by referendum. \[index, tally\]
+		Rejected(ReferendumIndex, Tally),
+		/// A referendum has been timed out without being decided. \[index, tally\]
+		TimedOut(ReferendumIndex, Tally),
+		/// A referendum has been cancelled. \[index, tally\]
+		Cancelled(ReferendumIndex, Tally),
+		/// A referendum has been killed. \[index, tally\]
+		Killed(ReferendumIndex, Tally),
+	}
+}
+
+decl_error! {
+	pub enum Error for Module<T: Config> {
+		/// Referendum is not ongoing.
+		NotOngoing,
+		/// Referendum's decision deposit is already paid.
+		HasDeposit,
+		/// The referendum index provided is invalid in this context.
+		BadReferendum,
+		/// No track exists for the proposal origin.
+		NoTrack,
+		/// Any deposit cannot be refunded until after the decision is over.
+		Unfinished,
+		/// The deposit cannot be refunded since none was made.
+		NoDeposit,
+	}
+}
+
+decl_module! {
+	pub struct Module<T: Config> for enum Call where origin: T::Origin {
+		type Error = Error<T>;
+
+		/// The minimum amount to be used as a deposit for a public referendum proposal.
+		const SubmissionDeposit: BalanceOf<T> = T::SubmissionDeposit::get();
+
+		/// Maximum size of the referendum queue for a single track.
+		const MaxQueued: u32 = T::MaxQueued::get();
+
+		/// The number of blocks after submission that a referendum must begin being decided by.
+		/// Once this passes, then anyone may cancel the referendum.
+		const UndecidingTimeout: T::BlockNumber = T::UndecidingTimeout::get();
+
+		/// Quantization level for the referendum wakeup scheduler.
+		const AlarmInterval: T::BlockNumber = T::AlarmInterval::get();
+
+		fn deposit_event() = default;
+
+		/// Propose a referendum on a privileged action.
+		///
+		/// - `origin`: must be `Signed` and the account must have `SubmissionDeposit` funds
+		///   available.
+		/// - `proposal_origin`: The origin from which the proposal should be executed.
+		/// - `proposal`: The proposal.
+		/// - `enactment_moment`: The moment that the proposal should be enacted.
+		///
+		/// Emits `Submitted`.
+		#[weight = T::WeightInfo::submit()]
+		fn submit(origin,
+			proposal_origin: T::PalletsOrigin,
+			proposal: Box<T::Proposal>,
+			enactment_moment: DispatchTime<T::BlockNumber>,
+		) {
+			let who = ensure_signed(origin)?;
+
+			let track = T::Tracks::track_for(&proposal_origin).map_err(|_| Error::<T>::NoTrack)?;
+			let submission_deposit = Self::take_deposit(who, T::SubmissionDeposit::get())?;
+			let index = ReferendumCount::mutate(|x| {
+				let r = *x;
+				*x += 1;
+				r
+			});
+			let now = frame_system::Module::<T>::block_number();
+			let nudge_at = now.saturating_add(One::one());
+			let proposal_hash = T::Hashing::hash_of(&proposal);
+			let mut status = ReferendumStatus {
+				track,
+				origin: proposal_origin,
+				proposal_hash,
+				enactment: enactment_moment,
+				submitted: now,
+				submission_deposit,
+				decision_deposit: None,
+				deciding: None,
+				tally: Default::default(),
+				in_queue: false,
+				alarm: None,
+			};
+			Self::ensure_alarm_at(&mut status, index, nudge_at);
+			ReferendumInfoFor::<T>::insert(index, ReferendumInfo::Ongoing(status));
+			ProposalOf::<T>::insert(index, *proposal);
+
+			Self::deposit_event(RawEvent::Submitted(index, track, proposal_hash));
+		}
+
+		/// Post the Decision Deposit for a referendum.
+		///
+		/// - `origin`: must be `Signed` and the account must have funds available for the
+		///   referendum's track's Decision Deposit.
+		/// - `index`: The index of the submitted referendum whose Decision Deposit is yet to be
+		///   posted.
+		///
+		/// Emits `DecisionDepositPlaced`.
+		#[weight = T::WeightInfo::place_decision_deposit()]
+		fn place_decision_deposit(origin, index: ReferendumIndex) {
+			let who = ensure_signed(origin)?;
+			let mut status = Self::ensure_ongoing(index)?;
+			ensure!(status.decision_deposit.is_none(), Error::<T>::HasDeposit);
+			let track = Self::track(status.track).ok_or(Error::<T>::NoTrack)?;
+			status.decision_deposit = Some(Self::take_deposit(who.clone(), track.decision_deposit)?);
+			let now = frame_system::Module::<T>::block_number();
+			let (info, _) = Self::service_referendum(now, index, status);
+			ReferendumInfoFor::<T>::insert(index, info);
+
+			Self::deposit_event(RawEvent::DecisionDepositPlaced(index, who, track.decision_deposit));
+		}
+
+		/// Refund the Decision Deposit for a closed referendum back to the depositor.
+		///
+		/// - `origin`: must be `Signed` or `Root`.
+		/// - `index`: The index of a closed referendum whose Decision Deposit has not yet been
+		///   refunded.
+		///
+		/// Emits `DecisionDepositRefunded`.
+		#[weight = T::WeightInfo::refund_decision_deposit()]
+		fn refund_decision_deposit(origin, index: ReferendumIndex) {
+			ensure_signed(origin)?;
+			let mut info = ReferendumInfoFor::<T>::get(index).ok_or(Error::<T>::BadReferendum)?;
+			let deposit = info
+				.take_decision_deposit()
+				.map_err(|_| Error::<T>::Unfinished)?
+				.ok_or(Error::<T>::NoDeposit)?;
+			Self::refund_deposit(Some(deposit.clone()));
+			ReferendumInfoFor::<T>::insert(index, info);
+
+			Self::deposit_event(RawEvent::DecisionDepositRefunded(index, deposit.who, deposit.amount));
+		}
+
+		/// Cancel an ongoing referendum.
+		///
+		/// - `origin`: must be the `CancelOrigin`.
+		/// - `index`: The index of the referendum to be cancelled.
+		///
+		/// Emits `Cancelled`.
+		#[weight = T::WeightInfo::cancel()]
+		fn cancel(origin, index: ReferendumIndex) {
+			T::CancelOrigin::ensure_origin(origin)?;
+			let status = Self::ensure_ongoing(index)?;
+			Self::conclude_early(index, &status);
+			Self::deposit_event(RawEvent::Cancelled(index, status.tally));
+			let info = ReferendumInfo::Cancelled(
+				frame_system::Module::<T>::block_number(),
+				status.submission_deposit,
+				status.decision_deposit,
+			);
+			ReferendumInfoFor::<T>::insert(index, info);
+		}
+
+		/// Cancel an ongoing referendum and slash the deposits.
+		///
+		/// - `origin`: must be the `KillOrigin`.
+		/// - `index`: The index of the referendum to be cancelled.
+		///
+		/// Emits `Killed` and `DepositSlashed`.
+		#[weight = T::WeightInfo::kill()]
+		fn kill(origin, index: ReferendumIndex) {
+			T::KillOrigin::ensure_origin(origin)?;
+			let status = Self::ensure_ongoing(index)?;
+			Self::conclude_early(index, &status);
+			Self::deposit_event(RawEvent::Killed(index, status.tally));
+			Self::slash_deposit(Some(status.submission_deposit));
+			Self::slash_deposit(status.decision_deposit);
+			let info = ReferendumInfo::Killed(frame_system::Module::<T>::block_number());
+			ReferendumInfoFor::<T>::insert(index, info);
+		}
+
+		/// Advance a referendum onto its next logical state. Only used internally.
+		///
+		/// - `origin`: must be `Root`.
+		/// - `index`: the referendum to be advanced.
+		#[weight = T::WeightInfo::nudge_referendum()]
+		fn nudge_referendum(origin, index: ReferendumIndex) {
+			ensure_root(origin)?;
+			let now = frame_system::Module::<T>::block_number();
+			let mut status = Self::ensure_ongoing(index)?;
+			// This is our wake-up, so we can disregard the alarm.
+			status.alarm = None;
+			let (info, _) = Self::service_referendum(now, index, status);
+			ReferendumInfoFor::<T>::insert(index, info);
+		}
+	}
+}
+
+impl<T: Config> Polling<T::Tally> for Module<T> {
+	type Index = ReferendumIndex;
+	type Votes = T::Votes;
+	type Class = TrackIdOf<T>;
+	type Moment = T::BlockNumber;
+
+	fn classes() -> Vec<Self::Class> {
+		T::Tracks::tracks().iter().map(|x| x.0).collect()
+	}
+
+	fn as_ongoing(index: Self::Index) -> Option<(T::Tally, Self::Class)> {
+		Self::ensure_ongoing(index).ok().map(|x| (x.tally, x.track))
+	}
+
+	fn access_poll<R>(
+		index: Self::Index,
+		f: impl FnOnce(PollStatus<&mut T::Tally, T::BlockNumber, TrackIdOf<T>>) -> R,
+	) -> R {
+		match ReferendumInfoFor::<T>::get(index) {
+			Some(ReferendumInfo::Ongoing(mut status)) => {
+				let result = f(PollStatus::Ongoing(&mut status.tally, status.track));
+				let now = frame_system::Module::<T>::block_number();
+				Self::ensure_alarm_at(&mut status, index, now.saturating_add(One::one()));
+				ReferendumInfoFor::<T>::insert(index, ReferendumInfo::Ongoing(status));
+				result
+			},
+			Some(ReferendumInfo::Approved(end, ..)) => f(PollStatus::Completed(end, true)),
+			Some(ReferendumInfo::Rejected(end, ..)) => f(PollStatus::Completed(end, false)),
+			_ => f(PollStatus::None),
+		}
+	}
+
+	fn try_access_poll<R>(
+		index: Self::Index,
+		f: impl FnOnce(
+			PollStatus<&mut T::Tally, T::BlockNumber, TrackIdOf<T>>,
+		) -> Result<R, DispatchError>,
+	) -> Result<R, DispatchError> {
+		match ReferendumInfoFor::<T>::get(index) {
+			Some(ReferendumInfo::Ongoing(mut status)) => {
+				let result = f(PollStatus::Ongoing(&mut status.tally, status.track))?;
+				let now = frame_system::Module::<T>::block_number();
+				Self::ensure_alarm_at(&mut status, index, now.saturating_add(One::one()));
+				ReferendumInfoFor::<T>::insert(index, ReferendumInfo::Ongoing(status));
+				Ok(result)
+			},
+			Some(ReferendumInfo::Approved(end, ..)) => f(PollStatus::Completed(end, true)),
+			Some(ReferendumInfo::Rejected(end, ..)) => f(PollStatus::Completed(end, false)),
+			_ => f(PollStatus::None),
+		}
+	}
+
+	#[cfg(feature = "runtime-benchmarks")]
+	fn create_ongoing(class: Self::Class) -> Result<Self::Index, ()> {
+		let track = T::Tracks::info(class).ok_or(())?;
+		let index = ReferendumCount::mutate(|x| {
+			let r = *x;
+			*x += 1;
+			r
+		});
+		let now = frame_system::Module::<T>::block_number();
+		let dummy_account_id = codec::Decode::decode(&mut TrailingZeroInput::new(&b"dummy"[..]))
+			.expect("infinite length input; no invalid inputs for type; qed");
+		let mut status = ReferendumStatus {
+			track: class,
+			origin: frame_system::RawOrigin::Root.into(),
+			proposal_hash: Default::default(),
+			enactment: DispatchTime::After(Zero::zero()),
+			submitted: now,
+			submission_deposit: Deposit { who: dummy_account_id, amount: Zero::zero() },
+			decision_deposit: None,
+			deciding: None,
+			tally: Default::default(),
+			in_queue: false,
+			alarm: None,
+		};
+		DecidingCount::<T>::mutate(class, |x| *x = x.saturating_add(1));
+		Self::begin_deciding(&mut status, index, now, track);
+		ReferendumInfoFor::<T>::insert(index, ReferendumInfo::Ongoing(status));
+		Ok(index)
+	}
+
+	#[cfg(feature = "runtime-benchmarks")]
+	fn end_ongoing(index: Self::Index, approved: bool) -> Result<(), ()> {
+		let mut status = Self::ensure_ongoing(index).map_err(|_| ())?;
+		Self::ensure_no_alarm(&mut status, index);
+		if status.deciding.is_some() {
+			Self::note_one_fewer_deciding(status.track);
+		}
+		let now = frame_system::Module::<T>::block_number();
+		let info = if approved {
+			ReferendumInfo::Approved(now, status.submission_deposit, status.decision_deposit)
+		} else {
+			ReferendumInfo::Rejected(now, status.submission_deposit, status.decision_deposit)
+		};
+		ReferendumInfoFor::<T>::insert(index, info);
+		Ok(())
+	}
+}
+
+impl<T: Config> Module<T> {
+	/// Check that referendum `index` is in the `Ongoing` state and return the `ReferendumStatus`
+	/// value, or `Err` otherwise.
+	pub fn ensure_ongoing(index: ReferendumIndex) -> Result<ReferendumStatusOf<T>, DispatchError> {
+		match ReferendumInfoFor::<T>::get(index) {
+			Some(ReferendumInfo::Ongoing(status)) => Ok(status),
+			_ => Err(Error::<T>::NotOngoing.into()),
+		}
+	}
+
+	/// Get the track info value for the track `id`.
+	fn track(id: TrackIdOf<T>) -> Option<&'static TrackInfoOf<T>> {
+		T::Tracks::info(id)
+	}
+
+	/// The name under which the wake-up alarm for referendum `index` due at `when` is scheduled.
+	fn alarm_name(index: ReferendumIndex, when: T::BlockNumber) -> Vec<u8> {
+		(REFERENDA_ID, "alarm", index, when).encode()
+	}
+
+	/// Schedule the approved proposal of referendum `index` for dispatch.
+	fn schedule_enactment(
+		index: ReferendumIndex,
+		track: &TrackInfoOf<T>,
+		desired: DispatchTime<T::BlockNumber>,
+		origin: T::PalletsOrigin,
+	) {
+		let now = frame_system::Module::<T>::block_number();
+		let earliest_allowed = now.saturating_add(track.min_enactment_period.max(One::one()));
+		let desired = match desired {
+			DispatchTime::At(x) => x,
+			DispatchTime::After(x) => now.saturating_add(x),
+		};
+		let when = desired.max(earliest_allowed);
+		if let Some(proposal) = ProposalOf::<T>::take(index) {
+			// Note: the name used here is distinct from the alarm names, which include the time.
+			let ok = T::Scheduler::schedule_named(
+				(REFERENDA_ID, index).encode(),
+				DispatchTime::At(when),
+				None,
+				63,
+				origin,
+				proposal,
+			).is_ok();
+			debug_assert!(ok, "LOGIC ERROR: schedule_enactment/schedule_named failed");
+		}
+	}
+
+	/// Reserve a deposit and return the `Deposit` instance.
+	fn take_deposit(
+		who: T::AccountId,
+		amount: BalanceOf<T>,
+	) -> Result<Deposit<T::AccountId, BalanceOf<T>>, DispatchError> {
+		T::Currency::reserve(&who, amount)?;
+		Ok(Deposit { who, amount })
+	}
+
+	/// Return a deposit, if `Some`.
+	fn refund_deposit(deposit: Option<Deposit<T::AccountId, BalanceOf<T>>>) {
+		if let Some(Deposit { who, amount }) = deposit {
+			T::Currency::unreserve(&who, amount);
+		}
+	}
+
+	/// Slash a deposit, if `Some`.
+	fn slash_deposit(deposit: Option<Deposit<T::AccountId, BalanceOf<T>>>) {
+		if let Some(Deposit { who, amount }) = deposit {
+			T::Slash::on_unbalanced(T::Currency::slash_reserved(&who, amount).0);
+			Self::deposit_event(RawEvent::DepositSlashed(who, amount));
+		}
+	}
+
+	/// Tidy up after a referendum is concluded by something other than its own servicing: remove
+	/// its alarm, proposal and any place it holds on its track.
+	fn conclude_early(index: ReferendumIndex, status: &ReferendumStatusOf<T>) {
+		if let Some(when) = status.alarm {
+			let _ = T::Scheduler::cancel_named(Self::alarm_name(index, when));
+		}
+		ProposalOf::<T>::remove(index);
+		if status.deciding.is_some() {
+			Self::note_one_fewer_deciding(status.track);
+		} else if status.in_queue {
+			TrackQueue::<T>::mutate(status.track, |q| q.retain(|x| x.0 != index));
+		}
+	}
+
+	/// Set an alarm to dispatch `nudge_referendum` at or shortly after block `alarm`, quantized
+	/// to `AlarmInterval`. Any previous alarm is cancelled.
+	///
+	/// Returns `true` if the alarm changed.
+	fn ensure_alarm_at(
+		status: &mut ReferendumStatusOf<T>,
+		index: ReferendumIndex,
+		alarm: T::BlockNumber,
+	) -> bool {
+		let now = frame_system::Module::<T>::block_number();
+		let interval = T::AlarmInterval::get().max(One::one());
+		let alarm = alarm.max(now.saturating_add(One::one()));
+		let alarm = alarm
+			.saturating_add(interval.saturating_sub(One::one()))
+			/ interval * interval;
+		if status.alarm == Some(alarm) {
+			return false
+		}
+		Self::ensure_no_alarm(status, index);
+		let ok = T::Scheduler::schedule_named(
+			Self::alarm_name(index, alarm),
+			DispatchTime::At(alarm),
+			None,
+			128u8,
+			frame_system::RawOrigin::Root.into(),
+			Call::nudge_referendum(index).into(),
+		).is_ok();
+		debug_assert!(ok, "LOGIC ERROR: ensure_alarm_at/schedule_named failed");
+		status.alarm = if ok { Some(alarm) } else { None };
+		true
+	}
+
+	/// Cancel the alarm in `status`, if one exists.
+	fn ensure_no_alarm(status: &mut ReferendumStatusOf<T>, index: ReferendumIndex) {
+		if let Some(when) = status.alarm.take() {
+			let _ = T::Scheduler::cancel_named(Self::alarm_name(index, when));
+		}
+	}
+
+	/// Begin deciding a referendum. The caller must have already accounted for the increase in
+	/// `DecidingCount`.
+	///
+	/// Returns the block at which the referendum should next be serviced.
+	fn begin_deciding(
+		status: &mut ReferendumStatusOf<T>,
+		index: ReferendumIndex,
+		now: T::BlockNumber,
+		track: &TrackInfoOf<T>,
+	) -> T::BlockNumber {
+		let is_passing = Self::is_passing(
+			&status.tally,
+			Zero::zero(),
+			track.decision_period,
+			&track.min_support,
+			&track.min_approval,
+		);
+		status.in_queue = false;
+		Self::deposit_event(RawEvent::DecisionStarted(
+			index,
+			status.track,
+			status.proposal_hash,
+			status.tally.clone(),
+		));
+		let confirming = if is_passing {
+			Self::deposit_event(RawEvent::ConfirmStarted(index));
+			Some(now.saturating_add(track.confirm_period))
+		} else {
+			None
+		};
+		let deciding_status = DecidingStatus { since: now, confirming };
+		let alarm = Self::decision_time(&deciding_status, &status.tally, track);
+		status.deciding = Some(deciding_status);
+		alarm
+	}
+
+	/// Action item for when there is now one fewer referendum in the deciding phase and the
+	/// `DecidingCount` is not yet updated. This means that we should either:
+	/// - begin deciding another referendum (and leave `DecidingCount` alone); or
+	/// - decrement `DecidingCount`.
+	fn note_one_fewer_deciding(track: TrackIdOf<T>) {
+		let next = TrackQueue::<T>::mutate(track, |q| q.pop());
+		let started = next.and_then(|(index, _)| {
+			let mut status = Self::ensure_ongoing(index).ok()?;
+			let info = Self::track(track)?;
+			let now = frame_system::Module::<T>::block_number();
+			let alarm = Self::begin_deciding(&mut status, index, now, info);
+			Self::ensure_alarm_at(&mut status, index, alarm);
+			ReferendumInfoFor::<T>::insert(index, ReferendumInfo::Ongoing(status));
+			Some(())
+		});
+		if started.is_none() {
+			DecidingCount::<T>::mutate(track, |x| *x = x.saturating_sub(1));
+		}
+	}
+
+	/// Place a referendum which is prepared and has its decision deposit into the deciding
+	/// phase if there is space on its track, otherwise into the track's queue.
+	///
+	/// Returns the block at which the referendum should next be serviced, if it began deciding.
+	fn ready_for_deciding(
+		now: T::BlockNumber,
+		track: &TrackInfoOf<T>,
+		index: ReferendumIndex,
+		status: &mut ReferendumStatusOf<T>,
+	) -> Option<T::BlockNumber> {
+		let deciding_count = DecidingCount::<T>::get(status.track);
+		if deciding_count < track.max_deciding {
+			// Begin deciding.
+			DecidingCount::<T>::insert(status.track, deciding_count.saturating_add(1));
+			Some(Self::begin_deciding(status, index, now, track))
+		} else {
+			// Add to queue, if there is space or we beat the weakest queued referendum.
+			let item = (index, status.tally.ayes());
+			let mut queue = TrackQueue::<T>::get(status.track);
+			if queue.len() as u32 >= T::MaxQueued::get() {
+				match queue.first() {
+					Some(&(_, lowest)) if lowest < item.1 => {
+						let (evicted, _) = queue.remove(0);
+						if let Ok(mut evicted_status) = Self::ensure_ongoing(evicted) {
+							evicted_status.in_queue = false;
+							Self::ensure_alarm_at(&mut evicted_status, evicted, now);
+							ReferendumInfoFor::<T>::insert(
+								evicted,
+								ReferendumInfo::Ongoing(evicted_status),
+							);
+						}
+					},
+					_ => return None,
+				}
+			}
+			// Referenda with equal votes are served in order of arrival.
+			let pos = queue.iter().position(|x| x.1 >= item.1).unwrap_or(queue.len());
+			queue.insert(pos, item);
+			TrackQueue::<T>::insert(status.track, queue);
+			status.in_queue = true;
+			None
+		}
+	}
+
+	/// Advance the state of a referendum, which comes down to:
+	/// - If it's ready to be decided, start deciding;
+	/// - If it's not ready to be decided and non-deciding timeout has passed, fail;
+	/// - If it's ongoing and passing, ensure confirming; if at end of confirmation period, pass.
+	/// - If it's ongoing and not passing, stop confirming; if it has reached end time, fail.
+	///
+	/// Weight will be a bit different depending on what it does, but it's designed so as not to
+	/// differ dramatically, especially if `MaxQueue` is kept small. In particular _there are no
+	/// balance operations in here_.
+	///
+	/// In terms of storage, at most a single referendum and the track queue are touched, and
+	/// potentially the scheduler agenda.
+	///
+	/// Returns the new referendum info and whether it differs from that which was passed in.
+	fn service_referendum(
+		now: T::BlockNumber,
+		index: ReferendumIndex,
+		mut status: ReferendumStatusOf<T>,
+	) -> (ReferendumInfoOf<T>, bool) {
+		let mut dirty = false;
+		// Should it begin being decided?
+		let track = match Self::track(status.track) {
+			Some(x) => x,
+			None => return (ReferendumInfo::Ongoing(status), false),
+		};
+		let timeout = status.submitted.saturating_add(T::UndecidingTimeout::get());
+		// Default the alarm to the submission timeout.
+		let mut alarm = timeout;
+		match status.deciding {
+			None => {
+				if status.in_queue {
+					// Does our position in the queue need updating?
+					let ayes = status.tally.ayes();
+					TrackQueue::<T>::mutate(status.track, |queue| {
+						if let Some(i) = queue.iter().position(|x| x.0 == index) {
+							queue.remove(i);
+							let pos = queue.iter().position(|x| x.1 >= ayes).unwrap_or(queue.len());
+							queue.insert(pos, (index, ayes));
+						}
+					});
+					alarm = T::BlockNumber::max_value();
+				} else if status.decision_deposit.is_some() {
+					// Are we ready for deciding?
+					let prepare_end = status.submitted.saturating_add(track.prepare_period);
+					if now >= prepare_end {
+						match Self::ready_for_deciding(now, track, index, &mut status) {
+							Some(decision_alarm) => alarm = decision_alarm,
+							// Queued or queue full: check again later, up to the timeout.
+							None if status.in_queue => alarm = T::BlockNumber::max_value(),
+							None => alarm = timeout.min(now.saturating_add(T::AlarmInterval::get())),
+						}
+						dirty = true;
+					} else {
+						alarm = prepare_end;
+					}
+				}
+				if status.deciding.is_none() && !status.in_queue && now >= timeout {
+					// Too long without being decided - end it.
+					Self::ensure_no_alarm(&mut status, index);
+					ProposalOf::<T>::remove(index);
+					Self::deposit_event(RawEvent::TimedOut(index, status.tally));
+					return (
+						ReferendumInfo::TimedOut(now, status.submission_deposit, status.decision_deposit),
+						true,
+					)
+				}
+			},
+			Some(mut deciding) => {
+				let is_passing = Self::is_passing(
+					&status.tally,
+					now.saturating_sub(deciding.since),
+					track.decision_period,
+					&track.min_support,
+					&track.min_approval,
+				);
+				if is_passing {
+					if deciding.confirming.map_or(false, |c| now >= c) {
+						// Passed!
+						Self::ensure_no_alarm(&mut status, index);
+						Self::note_one_fewer_deciding(status.track);
+						Self::schedule_enactment(index, track, status.enactment, status.origin);
+						Self::deposit_event(RawEvent::Confirmed(index, status.tally));
+						Self::deposit_event(RawEvent::Approved(index));
+						return (
+							ReferendumInfo::Approved(now, status.submission_deposit, status.decision_deposit),
+							true,
+						)
+					}
+					if deciding.confirming.is_none() {
+						// Start confirming.
+						dirty = true;
+						deciding.confirming = Some(now.saturating_add(track.confirm_period));
+						Self::deposit_event(RawEvent::ConfirmStarted(index));
+					}
+				} else {
+					if now >= deciding.since.saturating_add(track.decision_period) {
+						// Failed!
+						Self::ensure_no_alarm(&mut status, index);
+						Self::note_one_fewer_deciding(status.track);
+						ProposalOf::<T>::remove(index);
+						Self::deposit_event(RawEvent::Rejected(index, status.tally));
+						return (
+							ReferendumInfo::Rejected(now, status.submission_deposit, status.decision_deposit),
+							true,
+						)
+					}
+					if deciding.confirming.is_some() {
+						// Stop confirming.
+						dirty = true;
+						deciding.confirming = None;
+						Self::deposit_event(RawEvent::ConfirmAborted(index));
+					}
+				}
+				alarm = Self::decision_time(&deciding, &status.tally, track);
+				status.deciding = Some(deciding);
+			},
+		}
+
+		let dirty_alarm = if alarm < T::BlockNumber::max_value() {
+			Self::ensure_alarm_at(&mut status, index, alarm)
+		} else {
+			let had_alarm = status.alarm.is_some();
+			Self::ensure_no_alarm(&mut status, index);
+			had_alarm
+		};
+		(ReferendumInfo::Ongoing(status), dirty_alarm || dirty)
+	}
+
+	/// Determine the point at which a referendum will be accepted, move into confirmation with the
+	/// given `tally` or end with rejection (whichever happens sooner).
+	fn decision_time(
+		deciding: &DecidingStatusOf<T>,
+		tally: &T::Tally,
+		track: &TrackInfoOf<T>,
+	) -> T::BlockNumber {
+		deciding.confirming.unwrap_or_else(|| {
+			// Set alarm to the point where the current voting would make it pass.
+			let approval = tally.approval();
+			let support = tally.support();
+			let until_approval = track.min_approval.delay(approval);
+			let until_support = track.min_support.delay(support);
+			let offset = until_support.max(until_approval);
+			deciding.since.saturating_add(offset.mul_ceil(track.decision_period))
+		})
+	}
+
+	/// Determine whether the given `tally` would result in a referendum passing at `elapsed`
+	/// blocks into a total decision `period`, given the two curves for `support_needed` and
+	/// `approval_needed`.
+	fn is_passing(
+		tally: &T::Tally,
+		elapsed: T::BlockNumber,
+		period: T::BlockNumber,
+		support_needed: &Curve,
+		approval_needed: &Curve,
+	) -> bool {
+		let x = Perbill::from_rational_approximation(elapsed.min(period), period);
+		support_needed.passing(x, tally.support()) && approval_needed.passing(x, tally.approval())
+	}
+}
@@ -0,0 +1,279 @@
+// This file is part of Substrate.
+
+// Copyright (C) 2020 Parity Technologies (UK) Ltd.
+// SPDX-License-Identifier: Apache-2.0
+
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+// 	http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! The crate's tests.
+
+use super::*;
+use crate::mock::*;
+use frame_support::{assert_noop, assert_ok};
+use frame_system::RawOrigin;
+use sp_runtime::traits::BadOrigin;
+
+#[test]
+fn params_should_work() {
+	new_test_ext().execute_with(|| {
+		assert_eq!(ReferendumCount::get(), 0);
+		assert_eq!(Balances::free_balance(42), 0);
+		assert_eq!(Balances::total_issuance(), 600);
+	});
+}
+
+#[test]
+fn basic_happy_path_works() {
+	new_test_ext().execute_with(|| {
+		// #1: submit
+		let i = submit_proposal(RawOrigin::Root, 1);
+		assert_eq!(Balances::reserved_balance(&1), 2);
+		assert_eq!(ReferendumCount::get(), 1);
+		assert_ok!(Referenda::place_decision_deposit(Origin::signed(2), i));
+		assert_eq!(Balances::reserved_balance(&2), 10);
+		run_to(4);
+		assert_eq!(deciding_and_failing_since(i), None);
+		run_to(5);
+		// #5: 4 blocks after submit - vote should now be deciding.
+		assert_eq!(deciding_and_failing_since(i), Some(5));
+		assert_eq!(DecidingCount::<Test>::get(0), 1);
+		set_tally(i, 100, 0);
+		run_to(6);
+		// #6: Lots of ayes. Should now be confirming.
+		assert!(is_confirming(i));
+		run_to(7);
+		assert!(is_confirming(i));
+		run_to(8);
+		// #8: Should be confirmed & ended.
+		assert_eq!(
+			ReferendumInfoFor::<Test>::get(i),
+			Some(ReferendumInfo::Approved(
+				8,
+				Deposit { who: 1, amount: 2 },
+				Some(Deposit { who: 2, amount: 10 }),
+			)),
+		);
+		assert_eq!(DecidingCount::<Test>::get(0), 0);
+		assert!(ProposalOf::<Test>::get(i).is_none());
+		run_to(11);
+		// #11: Should not yet be enacted.
+		assert_eq!(Balances::free_balance(&42), 0);
+		run_to(12);
+		// #12: Should now be enacted.
+		assert_eq!(Balances::free_balance(&42), 1);
+	});
+}
+
+#[test]
+fn failing_referendum_is_rejected() {
+	new_test_ext().execute_with(|| {
+		let i = submit_proposal(RawOrigin::Root, 1);
+		assert_ok!(Referenda::place_decision_deposit(Origin::signed(2), i));
+		run_to(5);
+		assert_eq!(deciding_and_failing_since(i), Some(5));
+		set_tally(i, 0, 100);
+		run_to(8);
+		assert_eq!(deciding_and_failing_since(i), Some(5));
+		run_to(9);
+		assert_eq!(
+			ReferendumInfoFor::<Test>::get(i),
+			Some(ReferendumInfo::Rejected(
+				9,
+				Deposit { who: 1, amount: 2 },
+				Some(Deposit { who: 2, amount: 10 }),
+			)),
+		);
+		assert_eq!(DecidingCount::<Test>::get(0), 0);
+		// The decision deposit may now be refunded by anyone.
+		assert_ok!(Referenda::refund_decision_deposit(Origin::signed(3), i));
+		assert_eq!(Balances::reserved_balance(&2), 0);
+		assert_noop!(
+			Referenda::refund_decision_deposit(Origin::signed(3), i),
+			Error::<Test>::NoDeposit,
+		);
+		// The submission deposit is retained.
+		assert_eq!(Balances::reserved_balance(&1), 2);
+	});
+}
+
+#[test]
+fn confirming_can_be_aborted() {
+	new_test_ext().execute_with(|| {
+		let i = submit_proposal(RawOrigin::Root, 1);
+		assert_ok!(Referenda::place_decision_deposit(Origin::signed(2), i));
+		run_to(5);
+		set_tally(i, 100, 0);
+		run_to(6);
+		assert!(is_confirming(i));
+		set_tally(i, 100, 100);
+		run_to(7);
+		assert_eq!(deciding_and_failing_since(i), Some(5));
+		set_tally(i, 100, 0);
+		run_to(8);
+		// Confirmation restarts, with the whole confirmation period ahead of it again.
+		assert!(is_confirming(i));
+		run_to(9);
+		assert!(is_confirming(i));
+		run_to(10);
+		assert!(matches!(ReferendumInfoFor::<Test>::get(i), Some(ReferendumInfo::Approved(10, ..))));
+	});
+}
+
+#[test]
+fn undecided_referendum_times_out() {
+	new_test_ext().execute_with(|| {
+		let i = submit_proposal(RawOrigin::Root, 1);
+		run_to(20);
+		assert!(Referenda::ensure_ongoing(i).is_ok());
+		run_to(21);
+		assert_eq!(
+			ReferendumInfoFor::<Test>::get(i),
+			Some(ReferendumInfo::TimedOut(21, Deposit { who: 1, amount: 2 }, None)),
+		);
+		assert!(ProposalOf::<Test>::get(i).is_none());
+		assert_noop!(
+			Referenda::place_decision_deposit(Origin::signed(2), i),
+			Error::<Test>::NotOngoing,
+		);
+	});
+}
+
+#[test]
+fn queueing_works() {
+	new_test_ext().execute_with(|| {
+		// The root track may only decide one referendum at a time.
+		let a = submit_proposal(RawOrigin::Root, 1);
+		let b = submit_proposal(RawOrigin::Root, 2);
+		let c = submit_proposal(RawOrigin::Root, 3);
+		for &i in &[a, b, c] {
+			assert_ok!(Referenda::place_decision_deposit(Origin::signed(2), i));
+		}
+		run_to(5);
+		assert_eq!(deciding_and_failing_since(a), Some(5));
+		assert_eq!(DecidingCount::<Test>::get(0), 1);
+		assert_eq!(TrackQueue::<Test>::get(0), vec![(c, 0), (b, 0)]);
+
+		// Votes on queued referenda reorder the queue.
+		set_tally(b, 20, 0);
+		set_tally(c, 10, 0);
+		run_to(6);
+		assert_eq!(TrackQueue::<Test>::get(0), vec![(c, 10), (b, 20)]);
+
+		// Once the deciding referendum ends, the strongest queued referendum begins deciding.
+		assert_ok!(Referenda::cancel(Origin::signed(1), a));
+		assert_eq!(deciding_and_failing_since(b), Some(6));
+		assert_eq!(TrackQueue::<Test>::get(0), vec![(c, 10)]);
+		assert_eq!(DecidingCount::<Test>::get(0), 1);
+
+		assert_ok!(Referenda::kill(Origin::root(), b));
+		assert_eq!(deciding_and_failing_since(c), Some(6));
+		assert!(TrackQueue::<Test>::get(0).is_empty());
+
+		assert_ok!(Referenda::cancel(Origin::signed(1), c));
+		assert_eq!(DecidingCount::<Test>::get(0), 0);
+	});
+}
+
+#[test]
+fn tracks_are_independent() {
+	new_test_ext().execute_with(|| {
+		let a = submit_proposal(RawOrigin::Root, 1);
+		let b = submit_proposal(RawOrigin::None, 2);
+		assert_eq!(Referenda::ensure_ongoing(a).unwrap().track, 0);
+		assert_eq!(Referenda::ensure_ongoing(b).unwrap().track, 1);
+		assert_ok!(Referenda::place_decision_deposit(Origin::signed(2), a));
+		assert_ok!(Referenda::place_decision_deposit(Origin::signed(2), b));
+		// Track 1 has a shorter preparation period and deposit.
+		assert_eq!(Balances::reserved_balance(&2), 11);
+		run_to(3);
+		assert_eq!(deciding_and_failing_since(b), Some(3));
+		assert_eq!(deciding_and_failing_since(a), None);
+		run_to(5);
+		assert_eq!(deciding_and_failing_since(a), Some(5));
+		assert_eq!(DecidingCount::<Test>::get(0), 1);
+		assert_eq!(DecidingCount::<Test>::get(1), 1);
+	});
+}
+
+#[test]
+fn cancel_works_and_refunds() {
+	new_test_ext().execute_with(|| {
+		let i = submit_proposal(RawOrigin::Root, 1);
+		assert_ok!(Referenda::place_decision_deposit(Origin::signed(2), i));
+		assert_noop!(Referenda::cancel(Origin::signed(2), i), BadOrigin);
+		run_to(6);
+		assert_ok!(Referenda::cancel(Origin::signed(1), i));
+		assert_eq!(
+			ReferendumInfoFor::<Test>::get(i),
+			Some(ReferendumInfo::Cancelled(
+				6,
+				Deposit { who: 1, amount: 2 },
+				Some(Deposit { who: 2, amount: 10 }),
+			)),
+		);
+		assert_eq!(DecidingCount::<Test>::get(0), 0);
+		assert_ok!(Referenda::refund_decision_deposit(Origin::signed(2), i));
+		assert_eq!(Balances::reserved_balance(&2), 0);
+		// Nothing happens when the cancelled referendum's old alarms would have fired.
+		run_to(20);
+		assert!(matches!(ReferendumInfoFor::<Test>::get(i), Some(ReferendumInfo::Cancelled(6, ..))));
+	});
+}
+
+#[test]
+fn kill_works_and_slashes() {
+	new_test_ext().execute_with(|| {
+		let i = submit_proposal(RawOrigin::Root, 1);
+		assert_ok!(Referenda::place_decision_deposit(Origin::signed(2), i));
+		assert_noop!(Referenda::kill(Origin::signed(1), i), BadOrigin);
+		run_to(6);
+		assert_ok!(Referenda::kill(Origin::root(), i));
+		assert_eq!(ReferendumInfoFor::<Test>::get(i), Some(ReferendumInfo::Killed(6)));
+		assert_eq!(Balances::reserved_balance(&1), 0);
+		assert_eq!(Balances::free_balance(&1), 98);
+		assert_eq!(Balances::reserved_balance(&2), 0);
+		assert_eq!(Balances::free_balance(&2), 90);
+		assert_eq!(DecidingCount::<Test>::get(0), 0);
+	});
+}
+
+#[test]
+fn bad_calls_are_rejected() {
+	new_test_ext().execute_with(|| {
+		assert_noop!(
+			Referenda::submit(
+				Origin::signed(1),
+				RawOrigin::Signed(1).into(),
+				Box::new(set_balance_proposal(1)),
+				DispatchTime::After(0),
+			),
+			Error::<Test>::NoTrack,
+		);
+		let i = submit_proposal(RawOrigin::Root, 1);
+		assert_ok!(Referenda::place_decision_deposit(Origin::signed(2), i));
+		assert_noop!(
+			Referenda::place_decision_deposit(Origin::signed(3), i),
+			Error::<Test>::HasDeposit,
+		);
+		assert_noop!(
+			Referenda::refund_decision_deposit(Origin::signed(2), i),
+			Error::<Test>::Unfinished,
+		);
+		assert_noop!(
+			Referenda::refund_decision_deposit(Origin::signed(2), i + 1),
+			Error::<Test>::BadReferendum,
+		);
+		assert_noop!(Referenda::nudge_referendum(Origin::signed(1), i), BadOrigin);
+		assert_noop!(Referenda::cancel(Origin::signed(1), i + 1), Error::<Test>::NotOngoing);
+	});
+}
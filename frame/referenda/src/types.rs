@@ -0,0 +1,297 @@
+// This file is part of Substrate.
+
+// Copyright (C) 2020 Parity Technologies (UK) Ltd.
+// SPDX-License-Identifier: Apache-2.0
+
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+// 	http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Miscellaneous additional datatypes.
+
+use super::*;
+use codec::{Encode, Decode};
+use frame_support::{Parameter, traits::schedule::DispatchTime};
+use sp_runtime::{RuntimeDebug, PerThing, Perbill};
+
+pub type BalanceOf<T> =
+	<<T as Config>::Currency as Currency<<T as frame_system::Config>::AccountId>>::Balance;
+pub type NegativeImbalanceOf<T> =
+	<<T as Config>::Currency as Currency<<T as frame_system::Config>::AccountId>>::NegativeImbalance;
+pub type TrackIdOf<T> = <<T as Config>::Tracks as TracksInfo<
+	BalanceOf<T>,
+	<T as frame_system::Config>::BlockNumber,
+>>::Id;
+pub type TrackInfoOf<T> = TrackInfo<BalanceOf<T>, <T as frame_system::Config>::BlockNumber>;
+pub type ReferendumInfoOf<T> = ReferendumInfo<
+	TrackIdOf<T>,
+	<T as Config>::PalletsOrigin,
+	<T as frame_system::Config>::BlockNumber,
+	<T as frame_system::Config>::Hash,
+	BalanceOf<T>,
+	<T as Config>::Tally,
+	<T as frame_system::Config>::AccountId,
+>;
+pub type ReferendumStatusOf<T> = ReferendumStatus<
+	TrackIdOf<T>,
+	<T as Config>::PalletsOrigin,
+	<T as frame_system::Config>::BlockNumber,
+	<T as frame_system::Config>::Hash,
+	BalanceOf<T>,
+	<T as Config>::Tally,
+	<T as frame_system::Config>::AccountId,
+>;
+pub type DecidingStatusOf<T> = DecidingStatus<<T as frame_system::Config>::BlockNumber>;
+
+/// A referendum index.
+pub type ReferendumIndex = u32;
+
+/// Details of the funds placed as a deposit by some account.
+#[derive(Encode, Decode, Clone, PartialEq, Eq, RuntimeDebug)]
+pub struct Deposit<AccountId, Balance> {
+	/// The account which placed the deposit.
+	pub who: AccountId,
+	/// The amount placed.
+	pub amount: Balance,
+}
+
+/// The state of a referendum which is being decided.
+#[derive(Encode, Decode, Copy, Clone, PartialEq, Eq, RuntimeDebug)]
+pub struct DecidingStatus<BlockNumber> {
+	/// When this referendum began being "decided". If confirming, then the end will actually be
+	/// delayed until the end of the confirmation period.
+	pub since: BlockNumber,
+	/// If `Some`, then the referendum has entered confirmation stage and will end at the block
+	/// number as long as it doesn't lose its approval in the meantime.
+	pub confirming: Option<BlockNumber>,
+}
+
+/// The parameters of a single track of referenda.
+#[derive(Clone, PartialEq, Eq, RuntimeDebug)]
+pub struct TrackInfo<Balance, Moment> {
+	/// Name of this track.
+	pub name: &'static str,
+	/// A limit for the number of referenda on this track that can be being decided at once.
+	/// For Root origin this should generally be just one.
+	pub max_deciding: u32,
+	/// Amount that must be placed on deposit before a decision can be made.
+	pub decision_deposit: Balance,
+	/// Amount of time this must be submitted for before a decision can be made.
+	pub prepare_period: Moment,
+	/// Amount of time that a decision may take to be approved prior to cancellation.
+	pub decision_period: Moment,
+	/// Amount of time that the approval criteria must hold before it can be approved.
+	pub confirm_period: Moment,
+	/// Minimum amount of time that an approved proposal must be in the dispatch queue.
+	pub min_enactment_period: Moment,
+	/// Minimum aye votes as percentage of overall conviction-weighted votes needed for
+	/// approval as a function of time into decision period.
+	pub min_approval: Curve,
+	/// Minimum turnout as percentage of overall population that is needed for
+	/// approval as a function of time into decision period.
+	pub min_support: Curve,
+}
+
+/// Information on the voting tracks.
+pub trait TracksInfo<Balance, Moment> {
+	/// The identifier for a track.
+	type Id: Parameter + Ord + Copy;
+
+	/// The origin type from which a track is implied.
+	type Origin;
+
+	/// Return the array of known tracks and their information.
+	fn tracks() -> &'static [(Self::Id, TrackInfo<Balance, Moment>)];
+
+	/// Determine the voting track for the given `origin`.
+	fn track_for(origin: &Self::Origin) -> Result<Self::Id, ()>;
+
+	/// Return the track info for track `id`, by default this just looks it up in `Self::tracks()`.
+	fn info(id: Self::Id) -> Option<&'static TrackInfo<Balance, Moment>> {
+		Self::tracks().iter().find(|x| x.0 == id).map(|x| &x.1)
+	}
+}
+
+/// Info regarding an ongoing referendum.
+#[derive(Encode, Decode, Clone, PartialEq, Eq, RuntimeDebug)]
+pub struct ReferendumStatus<TrackId, Origin, Moment, Hash, Balance, Tally, AccountId> {
+	/// The track of this referendum.
+	pub track: TrackId,
+	/// The origin for this referendum.
+	pub origin: Origin,
+	/// The hash of the proposal up for referendum.
+	pub proposal_hash: Hash,
+	/// The time the proposal should be scheduled for enactment.
+	pub enactment: DispatchTime<Moment>,
+	/// The time of submission. Once `UndecidingTimeout` passes, it may be closed by anyone if it
+	/// `deciding` is `None`.
+	pub submitted: Moment,
+	/// The deposit reserved for the submission of this referendum.
+	pub submission_deposit: Deposit<AccountId, Balance>,
+	/// The deposit reserved for this referendum to be decided.
+	pub decision_deposit: Option<Deposit<AccountId, Balance>>,
+	/// The status of a decision being made. If `None`, it has not entered the deciding period.
+	pub deciding: Option<DecidingStatus<Moment>>,
+	/// The current tally of votes in this referendum.
+	pub tally: Tally,
+	/// Whether we have been placed in the queue for being decided or not.
+	pub in_queue: bool,
+	/// The next scheduled wake-up, if any.
+	pub alarm: Option<Moment>,
+}
+
+/// Info regarding a referendum, present or past.
+#[derive(Encode, Decode, Clone, PartialEq, Eq, RuntimeDebug)]
+pub enum ReferendumInfo<TrackId, Origin, Moment, Hash, Balance, Tally, AccountId> {
+	/// Referendum has been submitted and is being voted on.
+	Ongoing(ReferendumStatus<TrackId, Origin, Moment, Hash, Balance, Tally, AccountId>),
+	/// Referendum finished with approval. Submission deposit is held.
+	Approved(Moment, Deposit<AccountId, Balance>, Option<Deposit<AccountId, Balance>>),
+	/// Referendum finished with rejection. Submission deposit is held.
+	Rejected(Moment, Deposit<AccountId, Balance>, Option<Deposit<AccountId, Balance>>),
+	/// Referendum finished with cancellation. Submission deposit is held.
+	Cancelled(Moment, Deposit<AccountId, Balance>, Option<Deposit<AccountId, Balance>>),
+	/// Referendum finished and was never decided. Submission deposit is held.
+	TimedOut(Moment, Deposit<AccountId, Balance>, Option<Deposit<AccountId, Balance>>),
+	/// Referendum finished with a kill.
+	Killed(Moment),
+}
+
+impl<TrackId, Origin, Moment, Hash, Balance, Tally, AccountId>
+	ReferendumInfo<TrackId, Origin, Moment, Hash, Balance, Tally, AccountId>
+{
+	/// Take the Decision Deposit from `self`, if there is one. Returns an `Err` if `self` is not
+	/// in a valid state for the Decision Deposit to be refunded.
+	pub fn take_decision_deposit(&mut self) -> Result<Option<Deposit<AccountId, Balance>>, ()> {
+		use ReferendumInfo::*;
+		match self {
+			Ongoing(x) if x.decision_deposit.is_none() => Ok(None),
+			// Cannot refund deposit if Ongoing as this breaks assumptions.
+			Ongoing(_) => Err(()),
+			Approved(_, _, d) | Rejected(_, _, d) | TimedOut(_, _, d) | Cancelled(_, _, d) =>
+				Ok(d.take()),
+			Killed(_) => Ok(None),
+		}
+	}
+}
+
+/// A curve describing a threshold (of approval or support) which decreases over the course of a
+/// decision period.
+///
+/// The `x` axis is the proportion of the decision period which has elapsed and the `y` axis is the
+/// threshold at that point.
+#[derive(Encode, Decode, Clone, PartialEq, Eq, RuntimeDebug)]
+pub enum Curve {
+	/// Linear curve starting at `(0, begin)`, ending at `(period, begin - delta)`.
+	LinearDecreasing { begin: Perbill, delta: Perbill },
+	/// Stepped curve, beginning at `(0, begin)` and falling by `step` every `period`, never going
+	/// below `end`.
+	SteppedDecreasing { begin: Perbill, end: Perbill, step: Perbill, period: Perbill },
+}
+
+impl Curve {
+	/// Determine the `y` value for the given `x` value.
+	pub fn threshold(&self, x: Perbill) -> Perbill {
+		match self {
+			Curve::LinearDecreasing { begin, delta } => begin.saturating_sub(delta.saturating_mul(x)),
+			Curve::SteppedDecreasing { begin, end, step, period } => {
+				let steps = x.deconstruct().checked_div(period.deconstruct()).unwrap_or(0);
+				let fall = (step.deconstruct() as u64).saturating_mul(steps as u64)
+					.min(Perbill::ACCURACY as u64);
+				begin.saturating_sub(Perbill::from_parts(fall as u32)).max(*end)
+			},
+		}
+	}
+
+	/// Determine the smallest `x` value such that `passing` returns `true` when passed along with
+	/// the given `y` value.
+	///
+	/// If `passing` never returns `true` for any value of `x` within the period, then
+	/// `Perbill::one()` is returned.
+	pub fn delay(&self, y: Perbill) -> Perbill {
+		match self {
+			Curve::LinearDecreasing { begin, delta } => {
+				if y >= *begin {
+					Perbill::zero()
+				} else if y < begin.saturating_sub(*delta) {
+					Perbill::one()
+				} else {
+					Perbill::from_rational_approximation(
+						begin.deconstruct() - y.deconstruct(),
+						delta.deconstruct(),
+					)
+				}
+			},
+			Curve::SteppedDecreasing { begin, end, step, period } => {
+				if y >= *begin {
+					Perbill::zero()
+				} else if y < *end || step.is_zero() {
+					Perbill::one()
+				} else {
+					let needed = (begin.deconstruct() - y.deconstruct()) as u64;
+					let step = step.deconstruct() as u64;
+					let steps = (needed + step - 1) / step;
+					let x = (period.deconstruct() as u64).saturating_mul(steps)
+						.min(Perbill::ACCURACY as u64);
+					Perbill::from_parts(x as u32)
+				}
+			},
+		}
+	}
+
+	/// Return `true` iff the `y` value is greater than or equal to the threshold at `x`.
+	pub fn passing(&self, x: Perbill, y: Perbill) -> bool {
+		y >= self.threshold(x)
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	fn percent(x: u32) -> Perbill {
+		Perbill::from_percent(x)
+	}
+
+	#[test]
+	fn linear_curve_works() {
+		let c = Curve::LinearDecreasing { begin: percent(100), delta: percent(50) };
+		assert_eq!(c.threshold(percent(0)), percent(100));
+		assert_eq!(c.threshold(percent(50)), percent(75));
+		assert_eq!(c.threshold(percent(100)), percent(50));
+		assert_eq!(c.delay(percent(100)), percent(0));
+		assert_eq!(c.delay(percent(75)), percent(50));
+		assert_eq!(c.delay(percent(50)), percent(100));
+		assert_eq!(c.delay(percent(49)), percent(100));
+		assert!(c.passing(percent(50), percent(75)));
+		assert!(!c.passing(percent(50), percent(74)));
+	}
+
+	#[test]
+	fn stepped_curve_works() {
+		let c = Curve::SteppedDecreasing {
+			begin: percent(80),
+			end: percent(30),
+			step: percent(10),
+			period: percent(15),
+		};
+		assert_eq!(c.threshold(percent(0)), percent(80));
+		assert_eq!(c.threshold(percent(14)), percent(80));
+		assert_eq!(c.threshold(percent(15)), percent(70));
+		assert_eq!(c.threshold(percent(60)), percent(40));
+		assert_eq!(c.threshold(percent(100)), percent(30));
+		assert_eq!(c.delay(percent(80)), percent(0));
+		assert_eq!(c.delay(percent(75)), percent(15));
+		assert_eq!(c.delay(percent(70)), percent(15));
+		assert_eq!(c.delay(percent(30)), percent(75));
+		assert_eq!(c.delay(percent(29)), percent(100));
+	}
+}
@@ -280,6 +280,7 @@ pub mod slashing;
 pub mod offchain_election;
 pub mod inflation;
 pub mod weights;
+pub mod migrations;
 
 use sp_std::{
 	result,
@@ -453,12 +454,17 @@ pub struct ValidatorPrefs {
 	/// nominators.
 	#[codec(compact)]
 	pub commission: Perbill,
+	/// Whether or not this validator is accepting more nominations. If `true`, then no nominator
+	/// who is not already nominating this validator may nominate them. By default, validators
+	/// are accepting nominations.
+	pub blocked: bool,
 }
 
 impl Default for ValidatorPrefs {
 	fn default() -> Self {
 		ValidatorPrefs {
 			commission: Default::default(),
+			blocked: false,
 		}
 	}
 }
@@ -895,11 +901,12 @@ enum Releases {
 	V2_0_0,
 	V3_0_0,
 	V4_0_0,
+	V5_0_0, // blockable validators.
 }
 
 impl Default for Releases {
 	fn default() -> Self {
-		Releases::V4_0_0
+		Releases::V5_0_0
 	}
 }
 
@@ -1014,6 +1021,11 @@ decl_storage! {
 		/// Mode of era forcing.
 		pub ForceEra get(fn force_era) config(): Forcing;
 
+		/// The minimum amount of commission that validators can set.
+		///
+		/// If set to `0`, no limit exists.
+		pub MinCommission get(fn min_commission) config(): Perbill;
+
 		/// The percentage of the slash that is distributed to reporters.
 		///
 		/// The rest of the slashed value is handled by the `Slash`.
@@ -1083,8 +1095,8 @@ decl_storage! {
 		/// True if network has been upgraded to this version.
 		/// Storage version of the pallet.
 		///
-		/// This is set to v3.0.0 for new networks.
-		StorageVersion build(|_: &GenesisConfig<T>| Releases::V4_0_0): Releases;
+		/// This is set to v5.0.0 for new networks.
+		StorageVersion build(|_: &GenesisConfig<T>| Releases::V5_0_0): Releases;
 	}
 	add_extra_genesis {
 		config(stakers):
@@ -1148,6 +1160,8 @@ decl_event!(
 		/// An account has called `withdraw_unbonded` and removed unbonding chunks worth `Balance`
 		/// from the unlocking queue. \[stash, amount\]
 		Withdrawn(AccountId, Balance),
+		/// A nominator has been kicked from a validator. \[nominator, stash\]
+		Kicked(AccountId, AccountId),
 	}
 );
 
@@ -1219,6 +1233,10 @@ decl_error! {
 		IncorrectHistoryDepth,
 		/// Incorrect number of slashing spans provided.
 		IncorrectSlashingSpans,
+		/// Commission is too low. Must be at least `MinCommission`.
+		CommissionTooLow,
+		/// A nomination target was supplied that was blocked or otherwise not a validator.
+		BadTarget,
 	}
 }
 
@@ -1332,6 +1350,14 @@ decl_module! {
 			}
 		}
 
+		fn on_runtime_upgrade() -> Weight {
+			if StorageVersion::get() == Releases::V4_0_0 {
+				migrations::v5::migrate::<T>()
+			} else {
+				0
+			}
+		}
+
 		fn on_finalize() {
 			// Set the start of the first era.
 			if let Some(mut active_era) = Self::active_era() {
@@ -1636,6 +1662,7 @@ decl_module! {
 			ensure!(Self::era_election_status().is_closed(), Error::<T>::CallNotAllowed);
 			let controller = ensure_signed(origin)?;
 			let ledger = Self::ledger(&controller).ok_or(Error::<T>::NotController)?;
+			ensure!(prefs.commission >= Self::min_commission(), Error::<T>::CommissionTooLow);
 			let stash = &ledger.stash;
 			<Nominators<T>>::remove(stash);
 			<Validators<T>>::insert(stash, prefs);
@@ -1646,6 +1673,9 @@ decl_module! {
 		/// Effects will be felt at the beginning of the next era. This can only be called when
 		/// [`EraElectionStatus`] is `Closed`.
 		///
+		/// Validators that are `blocked` can only be nominated if they were already among the
+		/// targets of the origin's previous nominations.
+		///
 		/// The dispatch origin for this call must be _Signed_ by the controller, not the stash.
 		/// And, it can be only called when [`EraElectionStatus`] is `Closed`.
 		///
@@ -1667,9 +1697,16 @@ decl_module! {
 			let ledger = Self::ledger(&controller).ok_or(Error::<T>::NotController)?;
 			let stash = &ledger.stash;
 			ensure!(!targets.is_empty(), Error::<T>::EmptyTargets);
+			let old = Self::nominators(stash).map_or_else(Vec::new, |x| x.targets);
+
 			let targets = targets.into_iter()
 				.take(MAX_NOMINATIONS)
-				.map(|t| T::Lookup::lookup(t))
+				.map(|t| T::Lookup::lookup(t).map_err(DispatchError::from))
+				.map(|n| n.and_then(|n| if old.contains(&n) || !Validators::<T>::get(&n).blocked {
+					Ok(n)
+				} else {
+					Err(Error::<T>::BadTarget.into())
+				}))
 				.collect::<result::Result<Vec<T::AccountId>, _>>()?;
 
 			let nominations = Nominations {
@@ -1778,6 +1815,23 @@ decl_module! {
 			ValidatorCount::put(new);
 		}
 
+		/// Set the minimum commission that validators must charge.
+		///
+		/// This does not affect validators that are already validating with a lower commission
+		/// until they re-declare their preferences.
+		///
+		/// The dispatch origin must be Root.
+		///
+		/// # <weight>
+		/// Weight: O(1)
+		/// Write: MinCommission
+		/// # </weight>
+		#[weight = T::WeightInfo::set_min_commission()]
+		fn set_min_commission(origin, new: Perbill) {
+			ensure_root(origin)?;
+			MinCommission::put(new);
+		}
+
 		/// Increments the ideal number of validators.
 		///
 		/// The dispatch origin must be Root.
@@ -2042,6 +2096,48 @@ decl_module! {
 			T::Currency::remove_lock(STAKING_ID, &stash);
 		}
 
+		/// Remove the given nominations from the calling validator.
+		///
+		/// Effects will be felt at the beginning of the next era.
+		///
+		/// The dispatch origin for this call must be _Signed_ by the controller, not the stash.
+		/// And, it can be only called when [`EraElectionStatus`] is `Closed`.
+		///
+		/// - `who`: A list of nominator stash accounts who are nominating this validator which
+		///   should no longer be nominating this validator.
+		///
+		/// Note: Making this call only makes sense if you first set the validator preferences to
+		/// block any further nominations.
+		///
+		/// # <weight>
+		/// Complexity: O(K) where K is the number of accounts in `who`.
+		/// DB Weight:
+		/// - Reads: Era Election Status, Ledger, Nominators * K
+		/// - Writes: Nominators * K
+		/// # </weight>
+		#[weight = T::WeightInfo::kick(who.len() as u32)]
+		pub fn kick(origin, who: Vec<<T::Lookup as StaticLookup>::Source>) -> DispatchResult {
+			ensure!(Self::era_election_status().is_closed(), Error::<T>::CallNotAllowed);
+			let controller = ensure_signed(origin)?;
+			let ledger = Self::ledger(&controller).ok_or(Error::<T>::NotController)?;
+			let stash = &ledger.stash;
+
+			for nom_stash in who.into_iter()
+				.map(T::Lookup::lookup)
+				.collect::<result::Result<Vec<T::AccountId>, _>>()?
+				.into_iter()
+			{
+				Nominators::<T>::mutate(&nom_stash, |maybe_nom| if let Some(ref mut nom) = maybe_nom {
+					if let Some(pos) = nom.targets.iter().position(|v| v == stash) {
+						nom.targets.swap_remove(pos);
+						Self::deposit_event(RawEvent::Kicked(nom_stash.clone(), stash.clone()));
+					}
+				});
+			}
+
+			Ok(())
+		}
+
 		/// Submit an election result to the chain. If the solution:
 		///
 		/// 1. is valid.
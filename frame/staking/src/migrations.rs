@@ -0,0 +1,61 @@
+// This file is part of Substrate.
+
+// Copyright (C) 2020 Parity Technologies (UK) Ltd.
+// SPDX-License-Identifier: Apache-2.0
+
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+// 	http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Storage migrations for the staking module.
+
+/// Migration to `Releases::V5_0_0`, which adds the `blocked` flag to [`ValidatorPrefs`].
+pub mod v5 {
+	use crate::*;
+	use frame_support::{storage::{StorageMap, StorageDoubleMap, StorageValue}, traits::Get};
+
+	/// The layout of [`ValidatorPrefs`] prior to `V5_0_0`.
+	#[derive(Encode, Decode)]
+	struct OldValidatorPrefs {
+		#[codec(compact)]
+		pub commission: Perbill,
+	}
+
+	impl OldValidatorPrefs {
+		fn upgraded(self) -> ValidatorPrefs {
+			ValidatorPrefs {
+				commission: self.commission,
+				.. Default::default()
+			}
+		}
+	}
+
+	/// Translate all `Validators` and `ErasValidatorPrefs` entries to the new layout. All existing
+	/// validators are left unblocked.
+	pub fn migrate<T: Config>() -> Weight {
+		let count = sp_std::cell::Cell::new(0 as Weight);
+
+		<Validators<T>>::translate::<OldValidatorPrefs, _>(|_, p| {
+			count.set(count.get() + 1);
+			Some(p.upgraded())
+		});
+		<ErasValidatorPrefs<T>>::translate::<OldValidatorPrefs, _>(|_, _, p| {
+			count.set(count.get() + 1);
+			Some(p.upgraded())
+		});
+
+		StorageVersion::put(Releases::V5_0_0);
+		let count = count.get();
+		crate::log!(info, "💸 Migrated {} validator preferences to `blocked` layout.", count);
+
+		T::DbWeight::get().reads_writes(count.saturating_add(1), count.saturating_add(1))
+	}
+}
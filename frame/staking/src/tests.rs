@@ -385,7 +385,7 @@ fn no_candidate_emergency_condition() {
 		.execute_with(|| {
 			// initial validators
 			assert_eq_uvec!(validator_controllers(), vec![10, 20, 30, 40]);
-			let prefs = ValidatorPrefs { commission: Perbill::one() };
+			let prefs = ValidatorPrefs { commission: Perbill::one(), .. Default::default() };
 			<Staking as crate::Store>::Validators::insert(11, prefs.clone());
 
 			// set the minimum validator count.
@@ -902,6 +902,7 @@ fn validator_payment_prefs_work() {
 		let commission = Perbill::from_percent(40);
 		<Validators<Test>>::insert(&11, ValidatorPrefs {
 			commission: commission.clone(),
+			.. Default::default()
 		});
 
 		// Reward controller so staked ratio doesn't change.
@@ -4696,3 +4697,77 @@ fn payout_to_any_account_works() {
 		assert!(Balances::free_balance(42) > 0);
 	})
 }
+
+#[test]
+fn validate_respects_min_commission() {
+	ExtBuilder::default().build_and_execute(|| {
+		assert_noop!(
+			Staking::set_min_commission(Origin::signed(10), Perbill::from_percent(5)),
+			BadOrigin,
+		);
+		assert_ok!(Staking::set_min_commission(Origin::root(), Perbill::from_percent(5)));
+		assert_eq!(Staking::min_commission(), Perbill::from_percent(5));
+
+		// commission below the minimum is rejected.
+		assert_noop!(
+			Staking::validate(Origin::signed(10), ValidatorPrefs {
+				commission: Perbill::from_percent(4),
+				.. Default::default()
+			}),
+			Error::<Test>::CommissionTooLow,
+		);
+
+		// commission at or above the minimum is accepted.
+		let prefs = ValidatorPrefs { commission: Perbill::from_percent(5), .. Default::default() };
+		assert_ok!(Staking::validate(Origin::signed(10), prefs.clone()));
+		assert_eq!(Staking::validators(11), prefs);
+	})
+}
+
+#[test]
+fn blocked_validator_refuses_new_nominations() {
+	ExtBuilder::default().build_and_execute(|| {
+		// 101 nominates 11 and 21 at genesis.
+		assert_eq!(Staking::nominators(101).unwrap().targets, vec![11, 21]);
+
+		// 11 blocks further nominations.
+		assert_ok!(Staking::validate(
+			Origin::signed(10),
+			ValidatorPrefs { blocked: true, .. Default::default() },
+		));
+
+		// a new nominator cannot nominate 11.
+		bond_nominator(61, 60, 500, vec![21]);
+		assert_noop!(
+			Staking::nominate(Origin::signed(60), vec![11, 21]),
+			Error::<Test>::BadTarget,
+		);
+
+		// an existing nominator can keep 11 among its targets.
+		assert_ok!(Staking::nominate(Origin::signed(100), vec![11, 31]));
+		assert_eq!(Staking::nominators(101).unwrap().targets, vec![11, 31]);
+	})
+}
+
+#[test]
+fn kick_removes_nominations() {
+	ExtBuilder::default().build_and_execute(|| {
+		bond_nominator(61, 60, 500, vec![11, 21]);
+		assert_eq!(Staking::nominators(101).unwrap().targets, vec![11, 21]);
+
+		// only a controller can kick.
+		assert_noop!(Staking::kick(Origin::signed(11), vec![101]), Error::<Test>::NotController);
+
+		assert_ok!(Staking::kick(Origin::signed(10), vec![101, 61]));
+		assert_eq!(Staking::nominators(101).unwrap().targets, vec![21]);
+		assert_eq!(Staking::nominators(61).unwrap().targets, vec![21]);
+		assert_eq!(
+			staking_events().into_iter().rev().take(2).collect::<Vec<_>>(),
+			vec![RawEvent::Kicked(61, 11), RawEvent::Kicked(101, 11)],
+		);
+
+		// kicking someone who does not nominate us is a no-op.
+		assert_ok!(Staking::kick(Origin::signed(20), vec![41]));
+		assert_eq!(Staking::nominators(101).unwrap().targets, vec![21]);
+	})
+}
@@ -67,6 +67,8 @@ pub trait WeightInfo {
 	fn reap_stash(_s: u32, ) -> Weight;
 	fn new_era(_v: u32, _n: u32, ) -> Weight;
 	fn submit_solution_better(_v: u32, _n: u32, _a: u32, _w: u32, ) -> Weight;
+	fn kick(_k: u32, ) -> Weight;
+	fn set_min_commission() -> Weight;
 
 }
 
@@ -235,6 +237,18 @@ impl<T: frame_system::Config> WeightInfo for SubstrateWeight<T> {
 			.saturating_add(T::DbWeight::get().writes(2 as Weight))
 
 	}
+	fn kick(k: u32, ) -> Weight {
+		(28_130_000 as Weight)
+			.saturating_add((5_347_000 as Weight).saturating_mul(k as Weight))
+			.saturating_add(T::DbWeight::get().reads(2 as Weight))
+			.saturating_add(T::DbWeight::get().reads((1 as Weight).saturating_mul(k as Weight)))
+			.saturating_add(T::DbWeight::get().writes((1 as Weight).saturating_mul(k as Weight)))
+	}
+	fn set_min_commission() -> Weight {
+		(3_420_000 as Weight)
+			.saturating_add(T::DbWeight::get().writes(1 as Weight))
+
+	}
 
 }
 
@@ -402,5 +416,17 @@ impl WeightInfo for () {
 			.saturating_add(RocksDbWeight::get().writes(2 as Weight))
 
 	}
+	fn kick(k: u32, ) -> Weight {
+		(28_130_000 as Weight)
+			.saturating_add((5_347_000 as Weight).saturating_mul(k as Weight))
+			.saturating_add(RocksDbWeight::get().reads(2 as Weight))
+			.saturating_add(RocksDbWeight::get().reads((1 as Weight).saturating_mul(k as Weight)))
+			.saturating_add(RocksDbWeight::get().writes((1 as Weight).saturating_mul(k as Weight)))
+	}
+	fn set_min_commission() -> Weight {
+		(3_420_000 as Weight)
+			.saturating_add(RocksDbWeight::get().writes(1 as Weight))
+
+	}
 
 }
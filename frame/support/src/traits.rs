@@ -1630,6 +1630,100 @@ pub mod schedule {
 	}
 }
 
+pub mod voting {
+	use super::*;
+	use sp_runtime::Perbill;
+
+	/// A tally of votes on a poll, able to express itself in terms of the two quantities which a
+	/// polling system uses to decide whether the poll passes.
+	pub trait VoteTally<Votes> {
+		/// The number of aye votes.
+		fn ayes(&self) -> Votes;
+		/// The proportion of the total possible turnout which voted aye.
+		fn support(&self) -> Perbill;
+		/// The proportion of votes cast which were aye.
+		fn approval(&self) -> Perbill;
+		/// A tally which would pass any curve. Only useful for benchmarking.
+		#[cfg(feature = "runtime-benchmarks")]
+		fn unanimity() -> Self;
+		/// A tally which would fail any curve. Only useful for benchmarking.
+		#[cfg(feature = "runtime-benchmarks")]
+		fn rejection() -> Self;
+	}
+
+	/// The status of a poll, as seen by the voting system which tallies its votes.
+	pub enum PollStatus<Tally, Moment, Class> {
+		/// The poll does not exist.
+		None,
+		/// The poll is ongoing; votes may be altered through the tally. The second item is the
+		/// class of the poll.
+		Ongoing(Tally, Class),
+		/// The poll finished at the given moment, and was either approved (`true`) or not.
+		Completed(Moment, bool),
+	}
+
+	impl<Tally, Moment, Class> PollStatus<Tally, Moment, Class> {
+		/// Return the tally and class if the poll is ongoing.
+		pub fn ensure_ongoing(self) -> Option<(Tally, Class)> {
+			match self {
+				PollStatus::Ongoing(t, c) => Some((t, c)),
+				_ => None,
+			}
+		}
+	}
+
+	/// Something which holds polls that may be voted on by a separate voting system.
+	///
+	/// The voting system alters the tally of an ongoing poll through `access_poll`; the polling
+	/// system is responsible for deciding the outcome of the poll based on that tally.
+	pub trait Polling<Tally> {
+		/// The type by which polls are identified.
+		type Index: Parameter + Ord + Copy;
+		/// The type by which votes are measured.
+		type Votes: Parameter + Ord + Copy;
+		/// The class of a poll. Votes and delegations in one class are independent of those in
+		/// any other class.
+		type Class: Parameter + Ord + Copy;
+		/// The type by which time is measured.
+		type Moment;
+
+		/// All classes that a poll may have.
+		fn classes() -> Vec<Self::Class>;
+
+		/// `Some` if the referendum `index` can be voted on, along with the tally and class of
+		/// referendum.
+		///
+		/// Don't use this if you might mutate - use `try_access_poll` instead.
+		fn as_ongoing(index: Self::Index) -> Option<(Tally, Self::Class)>;
+
+		/// Access the status of a poll, possibly altering its tally if it is ongoing.
+		fn access_poll<R>(
+			index: Self::Index,
+			f: impl FnOnce(PollStatus<&mut Tally, Self::Moment, Self::Class>) -> R,
+		) -> R;
+
+		/// Fallible version of `access_poll`; changes to the tally are only kept if `f` returns
+		/// `Ok`.
+		fn try_access_poll<R>(
+			index: Self::Index,
+			f: impl FnOnce(PollStatus<&mut Tally, Self::Moment, Self::Class>) -> Result<R, DispatchError>,
+		) -> Result<R, DispatchError>;
+
+		/// Create an ongoing majority-carries poll of given class lasting given period for the
+		/// purpose of benchmarking.
+		///
+		/// May return `Err` if it is impossible.
+		#[cfg(feature = "runtime-benchmarks")]
+		fn create_ongoing(class: Self::Class) -> Result<Self::Index, ()>;
+
+		/// End the given ongoing poll and return the result.
+		///
+		/// Returns `Err` if `index` is not an ongoing poll.
+		#[cfg(feature = "runtime-benchmarks")]
+		fn end_ongoing(index: Self::Index, approved: bool) -> Result<(), ()>;
+	}
+}
+
 /// Some sort of check on the origin is performed by this object.
 pub trait EnsureOrigin<OuterOrigin> {
 	/// A return type.
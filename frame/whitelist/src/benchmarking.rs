@@ -0,0 +1,116 @@
+// This file is part of Substrate.
+
+// Copyright (C) 2020 Parity Technologies (UK) Ltd.
+// SPDX-License-Identifier: Apache-2.0
+
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+// 	http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Whitelist pallet benchmarking.
+
+use super::*;
+
+use frame_benchmarking::{benchmarks, whitelisted_caller};
+use frame_system::RawOrigin as SystemOrigin;
+
+use crate::Module as Whitelist;
+
+/// The maximum size of the remark used as a whitelisted call.
+const MAX_REMARK_LEN: u32 = 10_000;
+
+fn whitelisted_remark<T: Config>(n: u32) -> (<T as Config>::Call, T::Hash) {
+	let call: <T as Config>::Call = frame_system::Call::<T>::remark(vec![1u8; n as usize]).into();
+	let call_hash = T::Hashing::hash_of(&call);
+	let origin = T::WhitelistOrigin::successful_origin();
+	assert!(Call::<T>::whitelist_call(call_hash).dispatch_bypass_filter(origin).is_ok());
+	(call, call_hash)
+}
+
+benchmarks! {
+	_ { }
+
+	whitelist_call {
+		let origin = T::WhitelistOrigin::successful_origin();
+		let call_hash = Default::default();
+		let call = Call::<T>::whitelist_call(call_hash);
+	}: { call.dispatch_bypass_filter(origin)? }
+	verify {
+		assert!(WhitelistedCall::<T>::contains_key(call_hash));
+	}
+
+	remove_whitelisted_call {
+		let (call, call_hash) = whitelisted_remark::<T>(MAX_REMARK_LEN);
+		let caller: T::AccountId = whitelisted_caller();
+		Whitelist::<T>::note_whitelisted_call_preimage(SystemOrigin::Signed(caller).into(), call.encode())?;
+		let origin = T::WhitelistOrigin::successful_origin();
+		let call = Call::<T>::remove_whitelisted_call(call_hash);
+	}: { call.dispatch_bypass_filter(origin)? }
+	verify {
+		assert!(!WhitelistedCall::<T>::contains_key(call_hash));
+		assert!(!CallPreimage::<T>::contains_key(call_hash));
+	}
+
+	note_whitelisted_call_preimage {
+		let n in 1 .. MAX_REMARK_LEN;
+		let (call, call_hash) = whitelisted_remark::<T>(n);
+		let caller: T::AccountId = whitelisted_caller();
+	}: _(SystemOrigin::Signed(caller), call.encode())
+	verify {
+		assert!(CallPreimage::<T>::contains_key(call_hash));
+	}
+
+	dispatch_whitelisted_call {
+		let n in 1 .. MAX_REMARK_LEN;
+		let (call, call_hash) = whitelisted_remark::<T>(n);
+		let call_weight = call.get_dispatch_info().weight;
+		let encoded_call = call.encode();
+		let call_len = encoded_call.len() as u32;
+		let caller: T::AccountId = whitelisted_caller();
+		Whitelist::<T>::note_whitelisted_call_preimage(SystemOrigin::Signed(caller).into(), encoded_call)?;
+		let origin = T::DispatchWhitelistedOrigin::successful_origin();
+		let call = Call::<T>::dispatch_whitelisted_call(call_hash, call_len, call_weight);
+	}: { call.dispatch_bypass_filter(origin)? }
+	verify {
+		assert!(!WhitelistedCall::<T>::contains_key(call_hash));
+		assert!(!CallPreimage::<T>::contains_key(call_hash));
+	}
+
+	dispatch_whitelisted_call_with_preimage {
+		let n in 1 .. MAX_REMARK_LEN;
+		let (call, call_hash) = whitelisted_remark::<T>(n);
+		let origin = T::DispatchWhitelistedOrigin::successful_origin();
+		let call = Call::<T>::dispatch_whitelisted_call_with_preimage(Box::new(call));
+	}: { call.dispatch_bypass_filter(origin)? }
+	verify {
+		assert!(!WhitelistedCall::<T>::contains_key(call_hash));
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use crate::mock::{new_test_ext, Test};
+	use frame_support::assert_ok;
+
+	#[test]
+	fn test_benchmarks() {
+		new_test_ext().execute_with(|| assert_ok!(test_benchmark_whitelist_call::<Test>()));
+		new_test_ext().execute_with(|| assert_ok!(test_benchmark_remove_whitelisted_call::<Test>()));
+		new_test_ext().execute_with(|| {
+			assert_ok!(test_benchmark_note_whitelisted_call_preimage::<Test>())
+		});
+		new_test_ext().execute_with(|| assert_ok!(test_benchmark_dispatch_whitelisted_call::<Test>()));
+		new_test_ext().execute_with(|| {
+			assert_ok!(test_benchmark_dispatch_whitelisted_call_with_preimage::<Test>())
+		});
+	}
+}
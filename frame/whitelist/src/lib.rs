@@ -0,0 +1,290 @@
+// This file is part of Substrate.
+
+// Copyright (C) 2020 Parity Technologies (UK) Ltd.
+// SPDX-License-Identifier: Apache-2.0
+
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+// 	http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! # Whitelist Pallet
+//!
+//! - [`Config`]
+//! - [`Call`]
+//!
+//! ## Overview
+//!
+//! Allow some configurable origin: [`Config::WhitelistOrigin`] to whitelist some hash of a call,
+//! and allow another configurable origin: [`Config::DispatchWhitelistedOrigin`] to dispatch them
+//! with the root origin.
+//!
+//! In the meantime the call corresponding to the hash must have its preimage noted with
+//! `note_whitelisted_call_preimage`. Only preimages of whitelisted hashes are accepted, so no
+//! deposit is taken for them; the preimage is removed along with the whitelisting.
+//!
+//! ## Interface
+//!
+//! ### Dispatchable Functions
+//!
+//! - `whitelist_call` - Whitelist the hash of a call.
+//! - `remove_whitelisted_call` - Remove a hash from the whitelist.
+//! - `note_whitelisted_call_preimage` - Note the preimage of a whitelisted hash.
+//! - `dispatch_whitelisted_call` - Dispatch a whitelisted call, given its hash and witnesses of
+//!   its length and weight.
+//! - `dispatch_whitelisted_call_with_preimage` - Dispatch a whitelisted call, given the call
+//!   itself.
+
+#![cfg_attr(not(feature = "std"), no_std)]
+
+use sp_std::prelude::*;
+use codec::{Encode, Decode};
+use sp_runtime::{DispatchResult, traits::Hash};
+use frame_support::{
+	Parameter, decl_module, decl_event, decl_storage, decl_error, ensure,
+	dispatch::DispatchResultWithPostInfo,
+	weights::{Weight, GetDispatchInfo},
+	traits::{UnfilteredDispatchable, EnsureOrigin},
+};
+use frame_system::ensure_signed;
+
+pub mod weights;
+pub use weights::WeightInfo;
+
+#[cfg(test)]
+mod mock;
+#[cfg(test)]
+mod tests;
+
+#[cfg(feature = "runtime-benchmarks")]
+mod benchmarking;
+
+pub trait Config: frame_system::Config {
+	/// The overarching event type.
+	type Event: From<Event<Self>> + Into<<Self as frame_system::Config>::Event>;
+
+	/// The overarching call type.
+	type Call: Parameter + UnfilteredDispatchable<Origin=Self::Origin> + GetDispatchInfo
+		+ From<frame_system::Call<Self>>;
+
+	/// Required origin for whitelisting a call, or removing a call from the whitelist.
+	type WhitelistOrigin: EnsureOrigin<Self::Origin>;
+
+	/// Required origin for dispatching a whitelisted call with root origin.
+	type DispatchWhitelistedOrigin: EnsureOrigin<Self::Origin>;
+
+	/// Weight information for extrinsics in this pallet.
+	type WeightInfo: WeightInfo;
+}
+
+decl_storage! {
+	trait Store for Module<T: Config> as Whitelist {
+		/// The hashes of the calls which are whitelisted.
+		pub WhitelistedCall get(fn whitelisted_call): map hasher(identity) T::Hash => Option<()>;
+
+		/// The encoded calls of whitelisted hashes, once noted.
+		pub CallPreimage get(fn call_preimage): map hasher(identity) T::Hash => Option<Vec<u8>>;
+	}
+}
+
+decl_event! {
+	pub enum Event<T> where
+		<T as frame_system::Config>::Hash,
+	{
+		/// A call has been whitelisted. \[call_hash\]
+		CallWhitelisted(Hash),
+		/// A call has been removed from the whitelist. \[call_hash\]
+		WhitelistedCallRemoved(Hash),
+		/// The preimage of a whitelisted call has been noted. \[call_hash\]
+		CallPreimageNoted(Hash),
+		/// A whitelisted call has been dispatched with the given result. \[call_hash, result\]
+		WhitelistedCallDispatched(Hash, DispatchResult),
+	}
+}
+
+decl_error! {
+	pub enum Error for Module<T: Config> {
+		/// The preimage of the call hash could not be loaded.
+		UnavailablePreImage,
+		/// The call could not be decoded.
+		UndecodableCall,
+		/// The length of the call's encoding is greater than the witness.
+		InvalidCallLengthWitness,
+		/// The weight of the decoded call was higher than the witness.
+		InvalidCallWeightWitness,
+		/// The call was not whitelisted.
+		CallIsNotWhitelisted,
+		/// The call was already whitelisted; No-Op.
+		CallAlreadyWhitelisted,
+		/// The preimage of the call was already noted; No-Op.
+		PreimageAlreadyNoted,
+	}
+}
+
+decl_module! {
+	pub struct Module<T: Config> for enum Call where origin: T::Origin {
+		type Error = Error<T>;
+
+		fn deposit_event() = default;
+
+		/// Whitelist the hash of a call, so that it may be dispatched with root origin by
+		/// `DispatchWhitelistedOrigin`.
+		///
+		/// The dispatch origin for this call must be `WhitelistOrigin`.
+		///
+		/// Emits `CallWhitelisted`.
+		#[weight = T::WeightInfo::whitelist_call()]
+		fn whitelist_call(origin, call_hash: T::Hash) {
+			T::WhitelistOrigin::ensure_origin(origin)?;
+			ensure!(
+				!WhitelistedCall::<T>::contains_key(call_hash),
+				Error::<T>::CallAlreadyWhitelisted,
+			);
+
+			WhitelistedCall::<T>::insert(call_hash, ());
+			Self::deposit_event(RawEvent::CallWhitelisted(call_hash));
+		}
+
+		/// Remove the hash of a call from the whitelist, along with any noted preimage.
+		///
+		/// The dispatch origin for this call must be `WhitelistOrigin`.
+		///
+		/// Emits `WhitelistedCallRemoved`.
+		#[weight = T::WeightInfo::remove_whitelisted_call()]
+		fn remove_whitelisted_call(origin, call_hash: T::Hash) {
+			T::WhitelistOrigin::ensure_origin(origin)?;
+			WhitelistedCall::<T>::take(call_hash).ok_or(Error::<T>::CallIsNotWhitelisted)?;
+			CallPreimage::<T>::remove(call_hash);
+
+			Self::deposit_event(RawEvent::WhitelistedCallRemoved(call_hash));
+		}
+
+		/// Note the preimage of a whitelisted call.
+		///
+		/// The dispatch origin for this call must be _Signed_. No deposit is taken, since only
+		/// the preimage of a hash which is whitelisted may be noted.
+		///
+		/// Emits `CallPreimageNoted`.
+		#[weight = T::WeightInfo::note_whitelisted_call_preimage(encoded_call.len() as u32)]
+		fn note_whitelisted_call_preimage(origin, encoded_call: Vec<u8>) {
+			ensure_signed(origin)?;
+			let call_hash = T::Hashing::hash(&encoded_call[..]);
+			ensure!(
+				WhitelistedCall::<T>::contains_key(call_hash),
+				Error::<T>::CallIsNotWhitelisted,
+			);
+			ensure!(
+				!CallPreimage::<T>::contains_key(call_hash),
+				Error::<T>::PreimageAlreadyNoted,
+			);
+
+			CallPreimage::<T>::insert(call_hash, encoded_call);
+			Self::deposit_event(RawEvent::CallPreimageNoted(call_hash));
+		}
+
+		/// Dispatch a whitelisted call with root origin, given the hash of the call. Its
+		/// preimage must have been noted.
+		///
+		/// The dispatch origin for this call must be `DispatchWhitelistedOrigin`.
+		///
+		/// - `call_hash`: The hash of the whitelisted call.
+		/// - `call_encoded_len`: A witness of the length of the encoded call; must be at least
+		///   the length of the noted preimage.
+		/// - `call_weight_witness`: A witness of the weight of the call; must be at least the
+		///   dispatch weight of the call.
+		///
+		/// Emits `WhitelistedCallDispatched`.
+		#[weight = T::WeightInfo::dispatch_whitelisted_call(*call_encoded_len)
+			.saturating_add(*call_weight_witness)]
+		fn dispatch_whitelisted_call(origin,
+			call_hash: T::Hash,
+			call_encoded_len: u32,
+			call_weight_witness: Weight,
+		) -> DispatchResultWithPostInfo {
+			T::DispatchWhitelistedOrigin::ensure_origin(origin)?;
+			ensure!(
+				WhitelistedCall::<T>::contains_key(call_hash),
+				Error::<T>::CallIsNotWhitelisted,
+			);
+
+			let encoded_call = CallPreimage::<T>::get(call_hash)
+				.ok_or(Error::<T>::UnavailablePreImage)?;
+			ensure!(
+				encoded_call.len() as u32 <= call_encoded_len,
+				Error::<T>::InvalidCallLengthWitness,
+			);
+			let call = <T as Config>::Call::decode(&mut &encoded_call[..])
+				.map_err(|_| Error::<T>::UndecodableCall)?;
+			ensure!(
+				call.get_dispatch_info().weight <= call_weight_witness,
+				Error::<T>::InvalidCallWeightWitness,
+			);
+
+			let actual_weight = Self::clean_and_dispatch(call_hash, call).map(|w| {
+				w.saturating_add(T::WeightInfo::dispatch_whitelisted_call(encoded_call.len() as u32))
+			});
+			Ok(actual_weight.into())
+		}
+
+		/// Dispatch a whitelisted call with root origin, given the call itself.
+		///
+		/// The dispatch origin for this call must be `DispatchWhitelistedOrigin`.
+		///
+		/// Emits `WhitelistedCallDispatched`.
+		#[weight = {
+			let call_weight = call.get_dispatch_info().weight;
+			let call_len = call.encoded_size() as u32;
+
+			T::WeightInfo::dispatch_whitelisted_call_with_preimage(call_len)
+				.saturating_add(call_weight)
+		}]
+		fn dispatch_whitelisted_call_with_preimage(origin,
+			call: Box<<T as Config>::Call>,
+		) -> DispatchResultWithPostInfo {
+			T::DispatchWhitelistedOrigin::ensure_origin(origin)?;
+
+			let call_hash = T::Hashing::hash_of(&call);
+			ensure!(
+				WhitelistedCall::<T>::contains_key(call_hash),
+				Error::<T>::CallIsNotWhitelisted,
+			);
+
+			let call_len = call.encoded_size() as u32;
+			let actual_weight = Self::clean_and_dispatch(call_hash, *call).map(|w| {
+				w.saturating_add(T::WeightInfo::dispatch_whitelisted_call_with_preimage(call_len))
+			});
+			Ok(actual_weight.into())
+		}
+	}
+}
+
+impl<T: Config> Module<T> {
+	/// Clean whitelisting/preimage and dispatch call.
+	///
+	/// Return the call actual weight of the dispatched call if there is some.
+	fn clean_and_dispatch(call_hash: T::Hash, call: <T as Config>::Call) -> Option<Weight> {
+		WhitelistedCall::<T>::remove(call_hash);
+		CallPreimage::<T>::remove(call_hash);
+
+		let result = call.dispatch_bypass_filter(frame_system::RawOrigin::Root.into());
+
+		let call_actual_weight = match &result {
+			Ok(call_post_info) => call_post_info.actual_weight,
+			Err(call_err) => call_err.post_info.actual_weight,
+		};
+
+		Self::deposit_event(RawEvent::WhitelistedCallDispatched(
+			call_hash,
+			result.map(|_| ()).map_err(|e| e.error),
+		));
+
+		call_actual_weight
+	}
+}
@@ -0,0 +1,188 @@
+// This file is part of Substrate.
+
+// Copyright (C) 2020 Parity Technologies (UK) Ltd.
+// SPDX-License-Identifier: Apache-2.0
+
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+// 	http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! The crate's tests.
+
+use super::*;
+use crate::mock::*;
+use codec::Encode;
+use frame_support::{assert_noop, assert_ok, dispatch::GetDispatchInfo};
+use sp_core::H256;
+use sp_runtime::{traits::Hash, DispatchError};
+
+fn set_storage_call() -> Call {
+	Call::System(frame_system::Call::set_storage(vec![(b"key".to_vec(), b"value".to_vec())]))
+}
+
+fn call_hash(call: &Call) -> H256 {
+	<Test as frame_system::Config>::Hashing::hash_of(call)
+}
+
+#[test]
+fn test_whitelist_call_and_remove() {
+	new_test_ext().execute_with(|| {
+		let call = set_storage_call();
+		let hash = call_hash(&call);
+
+		assert_noop!(
+			Whitelist::remove_whitelisted_call(Origin::root(), hash),
+			Error::<Test>::CallIsNotWhitelisted,
+		);
+		assert_noop!(Whitelist::whitelist_call(Origin::signed(1), hash), DispatchError::BadOrigin);
+
+		assert_ok!(Whitelist::whitelist_call(Origin::root(), hash));
+		assert!(WhitelistedCall::<Test>::contains_key(hash));
+		assert_noop!(
+			Whitelist::whitelist_call(Origin::root(), hash),
+			Error::<Test>::CallAlreadyWhitelisted,
+		);
+
+		assert_ok!(Whitelist::note_whitelisted_call_preimage(Origin::signed(1), call.encode()));
+		assert_noop!(
+			Whitelist::remove_whitelisted_call(Origin::signed(1), hash),
+			DispatchError::BadOrigin,
+		);
+		assert_ok!(Whitelist::remove_whitelisted_call(Origin::root(), hash));
+		assert!(!WhitelistedCall::<Test>::contains_key(hash));
+		assert!(!CallPreimage::<Test>::contains_key(hash));
+	});
+}
+
+#[test]
+fn test_note_whitelisted_call_preimage() {
+	new_test_ext().execute_with(|| {
+		let call = set_storage_call();
+		let hash = call_hash(&call);
+
+		assert_noop!(
+			Whitelist::note_whitelisted_call_preimage(Origin::signed(1), call.encode()),
+			Error::<Test>::CallIsNotWhitelisted,
+		);
+
+		assert_ok!(Whitelist::whitelist_call(Origin::root(), hash));
+		assert_ok!(Whitelist::note_whitelisted_call_preimage(Origin::signed(1), call.encode()));
+		assert_eq!(CallPreimage::<Test>::get(hash), Some(call.encode()));
+		assert_noop!(
+			Whitelist::note_whitelisted_call_preimage(Origin::signed(2), call.encode()),
+			Error::<Test>::PreimageAlreadyNoted,
+		);
+	});
+}
+
+#[test]
+fn test_whitelist_call_and_execute() {
+	new_test_ext().execute_with(|| {
+		let call = set_storage_call();
+		let call_weight = call.get_dispatch_info().weight;
+		let encoded_call = call.encode();
+		let len = encoded_call.len() as u32;
+		let hash = call_hash(&call);
+
+		assert_noop!(
+			Whitelist::dispatch_whitelisted_call(Origin::root(), hash, len, call_weight),
+			Error::<Test>::CallIsNotWhitelisted,
+		);
+
+		assert_ok!(Whitelist::whitelist_call(Origin::root(), hash));
+
+		assert_noop!(
+			Whitelist::dispatch_whitelisted_call(Origin::signed(1), hash, len, call_weight),
+			DispatchError::BadOrigin,
+		);
+		assert_noop!(
+			Whitelist::dispatch_whitelisted_call(Origin::root(), hash, len, call_weight),
+			Error::<Test>::UnavailablePreImage,
+		);
+
+		assert_ok!(Whitelist::note_whitelisted_call_preimage(Origin::signed(1), encoded_call.clone()));
+
+		assert_noop!(
+			Whitelist::dispatch_whitelisted_call(Origin::root(), hash, len - 1, call_weight),
+			Error::<Test>::InvalidCallLengthWitness,
+		);
+		assert_noop!(
+			Whitelist::dispatch_whitelisted_call(Origin::root(), hash, len, call_weight - 1),
+			Error::<Test>::InvalidCallWeightWitness,
+		);
+
+		assert_ok!(Whitelist::dispatch_whitelisted_call(
+			Origin::root(),
+			hash,
+			len,
+			call_weight,
+		));
+		assert_eq!(sp_io::storage::get(b"key"), Some(b"value".to_vec()));
+		assert!(!WhitelistedCall::<Test>::contains_key(hash));
+		assert!(!CallPreimage::<Test>::contains_key(hash));
+		assert_eq!(
+			System::events().last().unwrap().event,
+			Event::pallet_whitelist(RawEvent::WhitelistedCallDispatched(hash, Ok(()))),
+		);
+	});
+}
+
+#[test]
+fn test_whitelist_call_and_execute_with_preimage() {
+	new_test_ext().execute_with(|| {
+		let call = set_storage_call();
+		let hash = call_hash(&call);
+
+		assert_noop!(
+			Whitelist::dispatch_whitelisted_call_with_preimage(Origin::root(), Box::new(call.clone())),
+			Error::<Test>::CallIsNotWhitelisted,
+		);
+
+		assert_ok!(Whitelist::whitelist_call(Origin::root(), hash));
+
+		assert_noop!(
+			Whitelist::dispatch_whitelisted_call_with_preimage(Origin::signed(1), Box::new(call.clone())),
+			DispatchError::BadOrigin,
+		);
+
+		assert_ok!(Whitelist::dispatch_whitelisted_call_with_preimage(Origin::root(), Box::new(call)));
+		assert_eq!(sp_io::storage::get(b"key"), Some(b"value".to_vec()));
+		assert!(!WhitelistedCall::<Test>::contains_key(hash));
+	});
+}
+
+#[test]
+fn test_whitelist_call_and_execute_failing_call() {
+	new_test_ext().execute_with(|| {
+		let call = Call::Whitelist(crate::Call::remove_whitelisted_call(H256::zero()));
+		let call_weight = call.get_dispatch_info().weight;
+		let encoded_call = call.encode();
+		let hash = call_hash(&call);
+
+		assert_ok!(Whitelist::whitelist_call(Origin::root(), hash));
+		assert_ok!(Whitelist::note_whitelisted_call_preimage(Origin::signed(1), encoded_call.clone()));
+		// The whitelisted call fails, but the whitelisting is still consumed.
+		assert_ok!(Whitelist::dispatch_whitelisted_call(
+			Origin::root(),
+			hash,
+			encoded_call.len() as u32,
+			call_weight,
+		));
+		assert!(!WhitelistedCall::<Test>::contains_key(hash));
+		assert_eq!(
+			System::events().last().unwrap().event,
+			Event::pallet_whitelist(RawEvent::WhitelistedCallDispatched(
+				hash,
+				Err(Error::<Test>::CallIsNotWhitelisted.into()),
+			)),
+		);
+	});
+}
@@ -21,7 +21,7 @@ use std::sync::Arc;
 
 use sp_runtime::traits::{Block as BlockT, Header as HeaderT, NumberFor};
 use sp_runtime::generic::BlockId;
-use sp_runtime::Justification;
+use sp_runtime::Justifications;
 use log::warn;
 use parking_lot::RwLock;
 
@@ -84,8 +84,8 @@ pub trait HeaderBackend<Block: BlockT>: Send + Sync {
 pub trait Backend<Block: BlockT>: HeaderBackend<Block> + HeaderMetadata<Block, Error=Error> {
 	/// Get block body. Returns `None` if block is not found.
 	fn body(&self, id: BlockId<Block>) -> Result<Option<Vec<<Block as BlockT>::Extrinsic>>>;
-	/// Get block justification. Returns `None` if justification does not exist.
-	fn justification(&self, id: BlockId<Block>) -> Result<Option<Justification>>;
+	/// Get block justifications. Returns `None` if no justification exists.
+	fn justifications(&self, id: BlockId<Block>) -> Result<Option<Justifications>>;
 	/// Get last finalized block hash.
 	fn last_finalized(&self) -> Result<Block::Hash>;
 	/// Returns data cache reference, if it is enabled on this backend.
@@ -256,7 +256,10 @@ pub struct Info<Block: BlockT> {
 	/// Last finalized block number.
 	pub finalized_number: <<Block as BlockT>::Header as HeaderT>::Number,
 	/// Number of concurrent leave forks.
-	pub number_leaves: usize
+	pub number_leaves: usize,
+	/// Range of missing block numbers, lowest and highest included, below a block that was
+	/// imported with its state without its ancestors.
+	pub block_gap: Option<(NumberFor<Block>, NumberFor<Block>)>,
 }
 
 /// Block status.
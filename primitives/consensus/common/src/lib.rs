@@ -49,7 +49,7 @@ mod metrics;
 pub use self::error::Error;
 pub use block_import::{
 	BlockImport, BlockOrigin, ForkChoiceStrategy, ImportedAux, BlockImportParams, BlockCheckParams,
-	ImportResult, JustificationImport,
+	ImportResult, JustificationImport, ImportedState,
 };
 pub use select_chain::SelectChain;
 pub use sp_state_machine::Backend as StateBackend;
@@ -95,7 +95,65 @@ pub use either::Either;
 /// the block itself would allow swapping justifications to change the block's hash
 /// (and thus fork the chain). Sending a `Justification` alongside a block instead
 /// bypasses this problem.
-pub type Justification = Vec<u8>;
+///
+/// Each justification is provided as an encoded blob, and is tagged with the ID of
+/// the consensus engine that generated the proof (we might have multiple justifications
+/// from different engines for the same block).
+pub type Justification = (ConsensusEngineId, EncodedJustification);
+
+/// The encoded justification specific to a consensus engine.
+pub type EncodedJustification = Vec<u8>;
+
+/// Collection of justifications for a given block, multiple justifications may
+/// be provided by different consensus engines for the same block.
+#[cfg_attr(feature = "std", derive(Serialize, Deserialize))]
+#[derive(Clone, PartialEq, Eq, Encode, Decode, RuntimeDebug)]
+pub struct Justifications(Vec<Justification>);
+
+impl Justifications {
+	/// Return an iterator over the justifications.
+	pub fn iter(&self) -> impl Iterator<Item = &Justification> {
+		self.0.iter()
+	}
+
+	/// Append a justification. Returns false if a justification with the same
+	/// `ConsensusEngineId` already exists, in which case the justification is
+	/// not inserted.
+	pub fn append(&mut self, justification: Justification) -> bool {
+		if self.get(justification.0).is_some() {
+			return false
+		}
+		self.0.push(justification);
+		true
+	}
+
+	/// Return the encoded justification for the given consensus engine, if it
+	/// exists.
+	pub fn get(&self, engine_id: ConsensusEngineId) -> Option<&EncodedJustification> {
+		self.iter().find(|j| j.0 == engine_id).map(|j| &j.1)
+	}
+
+	/// Consume the justifications and return the encoded justification for the
+	/// given consensus engine, if it exists.
+	pub fn into_justification(self, engine_id: ConsensusEngineId) -> Option<EncodedJustification> {
+		self.into_iter().find(|j| j.0 == engine_id).map(|j| j.1)
+	}
+}
+
+impl IntoIterator for Justifications {
+	type Item = Justification;
+	type IntoIter = sp_std::vec::IntoIter<Self::Item>;
+
+	fn into_iter(self) -> Self::IntoIter {
+		self.0.into_iter()
+	}
+}
+
+impl From<Justification> for Justifications {
+	fn from(justification: Justification) -> Self {
+		Self(vec![justification])
+	}
+}
 
 use traits::{Verify, Lazy};
 
@@ -730,6 +788,18 @@ mod tests {
 		assert_eq!(serde_json::to_string(&ex).unwrap(), "\"0x1001020304\"".to_owned());
 	}
 
+	#[test]
+	fn justifications_are_unique_per_engine() {
+		let mut justifications = Justifications::from((*b"ENG1", vec![1]));
+		assert!(justifications.append((*b"ENG2", vec![2])));
+		assert!(!justifications.append((*b"ENG1", vec![3])));
+
+		assert_eq!(justifications.get(*b"ENG1"), Some(&vec![1]));
+		assert_eq!(justifications.get(*b"ENG3"), None);
+		assert_eq!(justifications.clone().into_justification(*b"ENG2"), Some(vec![2]));
+		assert_eq!(justifications.iter().count(), 2);
+	}
+
 	#[test]
 	fn dispatch_error_encoding() {
 		let error = DispatchError::Module {
@@ -791,6 +791,205 @@ mod execution {
 		Ok(proving_backend.extract_proof())
 	}
 
+	/// Storage entries proven by a state range proof.
+	#[derive(Debug, Clone, PartialEq, Eq)]
+	pub struct ProvenStateRange {
+		/// Proven entries in iteration order. Entries of default child tries carry the
+		/// information of their child trie.
+		pub entries: Vec<(Option<ChildInfo>, Vec<u8>, Vec<u8>)>,
+		/// Position to continue from with the next range, or `None` if the range reached the
+		/// end of the state.
+		pub next_start: Option<Vec<Vec<u8>>>,
+	}
+
+	/// Generate a proof of the storage entries that follow `start_at`, including the entries
+	/// of the default child tries.
+	///
+	/// `start_at` is empty to start at the beginning of the state. Otherwise it holds the last
+	/// top trie key that was already read or, when a child trie was only partially read, the
+	/// prefixed storage key of that child trie followed by the last key read from it.
+	///
+	/// Entries are added until the proof reaches `size_limit` bytes, at least one entry is
+	/// always included. Returns the proof and the number of entries it contains.
+	pub fn prove_range_read_with_child_with_size<B, H>(
+		mut backend: B,
+		size_limit: usize,
+		start_at: &[Vec<u8>],
+	) -> Result<(StorageProof, u32), Box<dyn Error>>
+	where
+		B: Backend<H>,
+		H: Hasher,
+		H::Out: Ord + Codec,
+	{
+		let trie_backend = backend.as_trie_backend()
+			.ok_or_else(|| Box::new(ExecutionError::UnableToGenerateProof) as Box<dyn Error>)?;
+		prove_range_read_with_child_with_size_on_trie_backend(trie_backend, size_limit, start_at)
+	}
+
+	/// Generate range proof on pre-created trie backend, see
+	/// `prove_range_read_with_child_with_size`.
+	pub fn prove_range_read_with_child_with_size_on_trie_backend<S, H>(
+		trie_backend: &TrieBackend<S, H>,
+		size_limit: usize,
+		start_at: &[Vec<u8>],
+	) -> Result<(StorageProof, u32), Box<dyn Error>>
+	where
+		S: trie_backend_essence::TrieBackendStorage<H>,
+		H: Hasher,
+		H::Out: Ord + Codec,
+	{
+		check_range_start(start_at)?;
+		let proving_backend = proving_backend::ProvingBackend::<_, H>::new(trie_backend);
+		let mut count = 0;
+		let mut position = Vec::new();
+		iterate_state_with_child(&proving_backend, start_at, &mut position, &mut |_, _, _| {
+			count += 1;
+			proving_backend.estimate_encoded_size() < size_limit
+		}).map_err(|e| Box::new(e) as Box<dyn Error>)?;
+		Ok((proving_backend.extract_proof(), count))
+	}
+
+	/// Check a range proof generated by `prove_range_read_with_child_with_size` and return
+	/// the entries it proves.
+	pub fn read_range_proof_check_with_child<H>(
+		root: H::Out,
+		proof: StorageProof,
+		start_at: &[Vec<u8>],
+	) -> Result<ProvenStateRange, Box<dyn Error>>
+	where
+		H: Hasher,
+		H::Out: Ord + Codec,
+	{
+		check_range_start(start_at)?;
+		let proving_backend = create_proof_check_backend::<H>(root, proof)?;
+		let mut entries = Vec::new();
+		let mut position = Vec::new();
+		// The range ends where the proof is missing the trie nodes to continue.
+		let complete = iterate_state_with_child(
+			&proving_backend,
+			start_at,
+			&mut position,
+			&mut |child_info, key, value| {
+				entries.push((child_info.cloned(), key, value));
+				true
+			},
+		).unwrap_or(false);
+		Ok(ProvenStateRange {
+			entries,
+			next_start: if complete { None } else { Some(position) },
+		})
+	}
+
+	fn check_range_start(start_at: &[Vec<u8>]) -> Result<(), Box<dyn Error>> {
+		match start_at {
+			[] | [_] => Ok(()),
+			[storage_key, _] if default_child_info(storage_key).is_some() => Ok(()),
+			_ => Err(Box::new("Invalid start of the state range") as Box<dyn Error>),
+		}
+	}
+
+	fn default_child_info(prefixed_storage_key: &[u8]) -> Option<ChildInfo> {
+		let prefix = sp_core::storage::well_known_keys::DEFAULT_CHILD_STORAGE_KEY_PREFIX;
+		if prefixed_storage_key.starts_with(prefix) {
+			Some(ChildInfo::new_default(&prefixed_storage_key[prefix.len()..]))
+		} else {
+			None
+		}
+	}
+
+	/// Iterate over the state entries following `start_at` and call `f` for each of them until
+	/// it returns `false`. The entries of a default child trie are visited in place of the top
+	/// trie entry holding its root.
+	///
+	/// `position` is kept pointing at the last visited entry, in the format of `start_at`.
+	/// Returns `true` if the end of the state was reached.
+	fn iterate_state_with_child<B, H>(
+		backend: &B,
+		start_at: &[Vec<u8>],
+		position: &mut Vec<Vec<u8>>,
+		f: &mut impl FnMut(Option<&ChildInfo>, Vec<u8>, Vec<u8>) -> bool,
+	) -> Result<bool, B::Error>
+	where
+		B: Backend<H>,
+		H: Hasher,
+	{
+		*position = start_at.to_vec();
+		if let [storage_key, child_key] = start_at {
+			if let Some(child_info) = default_child_info(storage_key) {
+				let completed = iterate_child_with_start(
+					backend,
+					storage_key,
+					&child_info,
+					Some(child_key),
+					position,
+					f,
+				)?;
+				if !completed {
+					return Ok(false);
+				}
+			}
+			position.truncate(1);
+		}
+
+		loop {
+			let next = match position.first() {
+				Some(key) => backend.next_storage_key(key)?,
+				None if backend.storage(&[])?.is_some() => Some(Vec::new()),
+				None => backend.next_storage_key(&[])?,
+			};
+			let key = match next {
+				Some(key) => key,
+				None => return Ok(true),
+			};
+
+			if let Some(child_info) = default_child_info(&key) {
+				if !iterate_child_with_start(backend, &key, &child_info, None, position, f)? {
+					return Ok(false);
+				}
+				*position = vec![key];
+			} else {
+				let value = backend.storage(&key)?.unwrap_or_default();
+				*position = vec![key.clone()];
+				if !f(None, key, value) {
+					return Ok(false);
+				}
+			}
+		}
+	}
+
+	fn iterate_child_with_start<B, H>(
+		backend: &B,
+		storage_key: &[u8],
+		child_info: &ChildInfo,
+		start_at: Option<&[u8]>,
+		position: &mut Vec<Vec<u8>>,
+		f: &mut impl FnMut(Option<&ChildInfo>, Vec<u8>, Vec<u8>) -> bool,
+	) -> Result<bool, B::Error>
+	where
+		B: Backend<H>,
+		H: Hasher,
+	{
+		let mut last = start_at.map(|key| key.to_vec());
+		loop {
+			let next = match &last {
+				Some(key) => backend.next_child_storage_key(child_info, key)?,
+				None if backend.child_storage(child_info, &[])?.is_some() => Some(Vec::new()),
+				None => backend.next_child_storage_key(child_info, &[])?,
+			};
+			let key = match next {
+				Some(key) => key,
+				None => return Ok(true),
+			};
+
+			let value = backend.child_storage(child_info, &key)?.unwrap_or_default();
+			*position = vec![storage_key.to_vec(), key.clone()];
+			last = Some(key.clone());
+			if !f(Some(child_info), key, value) {
+				return Ok(false);
+			}
+		}
+	}
+
 	/// Check storage read proof, generated by `prove_read` call.
 	pub fn read_proof_check<H, I>(
 		root: H::Out,
@@ -1406,6 +1605,69 @@ mod tests {
 		);
 	}
 
+	#[test]
+	fn prove_range_read_with_child_and_proof_check_works() {
+		let remote_backend = trie_backend::tests::test_trie();
+		let remote_root = remote_backend.storage_root(::std::iter::empty()).0;
+
+		// The child trie entries take the place of the top trie entry holding its root.
+		let child_info = ChildInfo::new_default(b"sub1");
+		let mut expected = vec![
+			(Some(child_info.clone()), b"value3".to_vec(), vec![142]),
+			(Some(child_info.clone()), b"value4".to_vec(), vec![124]),
+		];
+		expected.extend(remote_backend.pairs().into_iter()
+			.filter(|(k, _)| k != &child_info.prefixed_storage_key().into_inner())
+			.map(|(k, v)| (None, k, v)));
+		assert_eq!(expected.len(), 133);
+
+		// Download the state in small ranges, as done by state sync.
+		let mut entries = Vec::new();
+		let mut start_at = Vec::new();
+		let mut ranges = 0;
+		loop {
+			let (proof, count) = prove_range_read_with_child_with_size(
+				trie_backend::tests::test_trie(),
+				64,
+				&start_at,
+			).unwrap();
+			let range = read_range_proof_check_with_child::<BlakeTwo256>(
+				remote_root,
+				proof,
+				&start_at,
+			).unwrap();
+			assert!(range.entries.len() >= count as usize);
+			entries.extend(range.entries);
+			ranges += 1;
+			match range.next_start {
+				Some(next_start) => start_at = next_start,
+				None => break,
+			}
+		}
+		assert!(ranges > 1);
+		assert_eq!(entries, expected);
+
+		// A proof of the first entries does not prove the rest of the state.
+		let (proof, _) = prove_range_read_with_child_with_size(
+			trie_backend::tests::test_trie(),
+			0,
+			&[],
+		).unwrap();
+		let range = read_range_proof_check_with_child::<BlakeTwo256>(
+			remote_root,
+			proof,
+			&[],
+		).unwrap();
+		assert!(range.entries.len() < expected.len());
+		assert!(range.next_start.is_some());
+
+		assert!(read_range_proof_check_with_child::<BlakeTwo256>(
+			remote_root,
+			StorageProof::empty(),
+			&[],
+		).is_err());
+	}
+
 	#[test]
 	fn child_storage_uuid() {
 
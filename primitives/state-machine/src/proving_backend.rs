@@ -17,7 +17,7 @@
 
 //! Proving state machine backend.
 
-use std::{sync::Arc, collections::HashMap};
+use std::{sync::{Arc, atomic::{AtomicUsize, Ordering}}, collections::HashMap};
 use parking_lot::RwLock;
 use codec::{Decode, Codec};
 use log::debug;
@@ -123,6 +123,8 @@ pub struct ProvingBackend<'a, S: 'a + TrieBackendStorage<H>, H: 'a + Hasher> (
 pub struct ProofRecorderBackend<'a, S: 'a + TrieBackendStorage<H>, H: 'a + Hasher> {
 	backend: &'a S,
 	proof_recorder: ProofRecorder<H>,
+	/// Encoded size of the nodes recorded through this backend.
+	recorded_size: AtomicUsize,
 }
 
 impl<'a, S: 'a + TrieBackendStorage<H>, H: 'a + Hasher> ProvingBackend<'a, S, H>
@@ -144,6 +146,7 @@ impl<'a, S: 'a + TrieBackendStorage<H>, H: 'a + Hasher> ProvingBackend<'a, S, H>
 		let recorder = ProofRecorderBackend {
 			backend: essence.backend_storage(),
 			proof_recorder,
+			recorded_size: AtomicUsize::new(0),
 		};
 		ProvingBackend(TrieBackend::new(recorder, root))
 	}
@@ -157,6 +160,14 @@ impl<'a, S: 'a + TrieBackendStorage<H>, H: 'a + Hasher> ProvingBackend<'a, S, H>
 			.collect();
 		StorageProof::new(trie_nodes)
 	}
+
+	/// Returns the encoded size of the trie nodes recorded so far.
+	///
+	/// Nodes that were already present in the recorder the backend was created with are not
+	/// counted.
+	pub fn estimate_encoded_size(&self) -> usize {
+		self.0.essence().backend_storage().recorded_size.load(Ordering::Relaxed)
+	}
 }
 
 impl<'a, S: 'a + TrieBackendStorage<H>, H: 'a + Hasher> TrieBackendStorage<H>
@@ -169,6 +180,9 @@ impl<'a, S: 'a + TrieBackendStorage<H>, H: 'a + Hasher> TrieBackendStorage<H>
 			return Ok(v.clone());
 		}
 		let backend_value =  self.backend.get(key, prefix)?;
+		if let Some(value) = &backend_value {
+			self.recorded_size.fetch_add(value.len(), Ordering::Relaxed);
+		}
 		self.proof_recorder.write().insert(key.clone(), backend_value.clone());
 		Ok(backend_value)
 	}